@@ -1,19 +1,95 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use obsidian_fs::PrivateRoots;
+
+use crate::frontmatter_schema::FrontmatterSchema;
+use crate::graph::GraphIgnore;
+use crate::projects::ProjectDiscoveryConfig;
 
 /// Server configuration loaded from environment variables.
 #[derive(Debug, Clone)]
 pub struct Config {
     /// Path to the Obsidian vault root directory
     pub vault_path: PathBuf,
-    /// Name of the vault (derived from vault_path)
+    /// Name of the vault for `obsidian://` URIs (the vault directory's name
+    /// unless `OBSIDIAN_VAULT_NAME` overrides it)
     pub vault_name: String,
+    /// Prefix prepended to search queries before encoding (empty by default)
+    pub embedding_query_prefix: String,
+    /// Prefix prepended to note content before encoding (empty by default)
+    pub embedding_document_prefix: String,
+    /// Per-folder frontmatter schema enforced by WriteNote/UpdateFrontmatter (empty by default)
+    pub frontmatter_schema: FrontmatterSchema,
+    /// Rules for which notes are project notes (notes under `projects/` by default)
+    pub project_discovery: ProjectDiscoveryConfig,
+    /// Let note paths follow symlinks that point outside the vault (off by default)
+    pub allow_external_symlinks: bool,
+    /// Per-tool time limits, keyed by tool name (search has one by default)
+    pub tool_timeouts: HashMap<String, Duration>,
+    /// Register debugging tools such as DebugEmbed (off by default)
+    pub debug_tools: bool,
+    /// Folders whose notes tools may read but not modify (none by default)
+    pub protected_folders: Vec<String>,
+    /// Folders whose notes need consent to read or search (`private/` by default)
+    pub private_roots: PrivateRoots,
+    /// Paths left out of the graph index (`.obsidian/` and `.sync/` by default)
+    pub graph_ignore: GraphIgnore,
+    /// Notes encoded per model call when embedding in bulk
+    pub embedding_batch_size: usize,
+    /// Embedding batches allowed to run at the same time
+    pub embedding_concurrency: usize,
+    /// Never download the embedding model (off by default)
+    pub offline: bool,
+    /// Characters of context Remember may return before trimming older log days (unlimited by default)
+    pub remember_budget: Option<usize>,
+    /// Levels of nested embeds inlined when a note is rendered
+    pub embed_depth: usize,
+    /// Base URL of a vault served over HTTP that tools read and write notes
+    /// through instead of `vault_path` (unset by default)
+    pub storage_url: Option<String>,
+    /// Bearer token sent to `storage_url` (unset by default)
+    pub storage_token: Option<String>,
 }
 
+/// Notes per embedding batch unless `OBSIDIAN_EMBEDDING_BATCH_SIZE` overrides it
+pub const DEFAULT_EMBEDDING_BATCH_SIZE: usize = 25;
+
+/// Concurrent embedding batches unless `OBSIDIAN_EMBEDDING_CONCURRENCY` overrides it
+pub const DEFAULT_EMBEDDING_CONCURRENCY: usize = 1;
+
+/// Levels of embeds inlined unless `OBSIDIAN_EMBED_DEPTH` overrides it
+pub const DEFAULT_EMBED_DEPTH: usize = 1;
+
+/// Time limit for the search tool unless `OBSIDIAN_TOOL_TIMEOUTS` overrides it
+pub const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
+
 impl Config {
     /// Load configuration from environment variables.
     ///
     /// Required environment variables:
     /// - `OBSIDIAN_VAULT_PATH`: Path to the Obsidian vault root (supports ~ for home directory)
+    ///
+    /// Optional environment variables:
+    /// - `OBSIDIAN_VAULT_NAME`: Vault name used in `obsidian://` URIs (defaults to the vault directory's name)
+    /// - `OBSIDIAN_EMBEDDING_QUERY_PREFIX`: Instruction prefix for search queries (e.g. "query: ")
+    /// - `OBSIDIAN_EMBEDDING_DOCUMENT_PREFIX`: Instruction prefix for note content (e.g. "passage: ")
+    /// - `OBSIDIAN_FRONTMATTER_SCHEMA`: Path to a JSON file of per-folder frontmatter schemas
+    /// - `OBSIDIAN_PROJECT_DISCOVERY`: Path to a JSON file of rules selecting project notes
+    /// - `OBSIDIAN_ALLOW_EXTERNAL_SYMLINKS`: Set to `1` or `true` to allow symlinks out of the vault
+    /// - `OBSIDIAN_TOOL_TIMEOUTS`: Per-tool limits in seconds (e.g. "search=60,similar_to_note=30", 0 disables)
+    /// - `OBSIDIAN_DEBUG_TOOLS`: Set to `1` or `true` to expose debugging tools
+    /// - `OBSIDIAN_PROTECTED_FOLDERS`: Comma-separated read-only folders (e.g. "archive,published")
+    /// - `OBSIDIAN_PRIVATE_ROOTS`: Comma-separated private folders (default "private")
+    /// - `OBSIDIAN_GRAPH_IGNORE`: Comma-separated folders (ending in `/`) or globs to leave out of the graph, on top of `.obsidian/` and `.sync/`
+    /// - `OBSIDIAN_EMBEDDING_BATCH_SIZE`: Notes encoded per model call (default 25)
+    /// - `OBSIDIAN_EMBEDDING_CONCURRENCY`: Embedding batches run at once (default 1)
+    /// - `OBSIDIAN_OFFLINE`: Set to `1` or `true` to fail fast instead of downloading a missing model
+    /// - `OBSIDIAN_REMEMBER_BUDGET`: Characters of session context Remember returns before trimming Log.md
+    /// - `OBSIDIAN_EMBED_DEPTH`: Levels of nested `![[embeds]]` inlined when rendering (default 1)
+    /// - `OBSIDIAN_STORAGE_URL`: Vault served over HTTP to read and write notes through (needs the `remote-storage` feature)
+    /// - `OBSIDIAN_STORAGE_TOKEN`: Bearer token for `OBSIDIAN_STORAGE_URL`
     pub fn from_env() -> Result<Self, ConfigError> {
         let vault_path_str = std::env::var("OBSIDIAN_VAULT_PATH")
             .map_err(|_| ConfigError::MissingVaultPath)?;
@@ -21,18 +97,205 @@ impl Config {
         // Expand tilde to home directory
         let vault_path = expand_tilde(&vault_path_str);
 
-        // Derive vault name from path
-        let vault_name = vault_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("vault")
-            .to_string();
+        let vault_name = resolve_vault_name(
+            std::env::var("OBSIDIAN_VAULT_NAME").ok().as_deref(),
+            &vault_path,
+        )?;
+
+        let embedding_query_prefix =
+            std::env::var("OBSIDIAN_EMBEDDING_QUERY_PREFIX").unwrap_or_default();
+        let embedding_document_prefix =
+            std::env::var("OBSIDIAN_EMBEDDING_DOCUMENT_PREFIX").unwrap_or_default();
+
+        let frontmatter_schema = match std::env::var("OBSIDIAN_FRONTMATTER_SCHEMA") {
+            Ok(schema_path) => load_frontmatter_schema(expand_tilde(&schema_path))?,
+            Err(_) => FrontmatterSchema::default(),
+        };
+
+        let project_discovery = match std::env::var("OBSIDIAN_PROJECT_DISCOVERY") {
+            Ok(config_path) => load_project_discovery(expand_tilde(&config_path))?,
+            Err(_) => ProjectDiscoveryConfig::default(),
+        };
+
+        let allow_external_symlinks = std::env::var("OBSIDIAN_ALLOW_EXTERNAL_SYMLINKS")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        let tool_timeouts =
+            parse_tool_timeouts(&std::env::var("OBSIDIAN_TOOL_TIMEOUTS").unwrap_or_default())?;
+
+        let debug_tools = std::env::var("OBSIDIAN_DEBUG_TOOLS")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        let protected_folders = parse_protected_folders(
+            &std::env::var("OBSIDIAN_PROTECTED_FOLDERS").unwrap_or_default(),
+        );
+
+        let private_roots = match std::env::var("OBSIDIAN_PRIVATE_ROOTS") {
+            Ok(spec) => PrivateRoots::new(spec.split(',')),
+            Err(_) => PrivateRoots::default(),
+        };
+
+        let graph_ignore = match std::env::var("OBSIDIAN_GRAPH_IGNORE") {
+            Ok(spec) => GraphIgnore::new(spec.split(',')),
+            Err(_) => GraphIgnore::default(),
+        };
+
+        let embedding_batch_size =
+            parse_positive("OBSIDIAN_EMBEDDING_BATCH_SIZE", DEFAULT_EMBEDDING_BATCH_SIZE)?;
+        let embedding_concurrency =
+            parse_positive("OBSIDIAN_EMBEDDING_CONCURRENCY", DEFAULT_EMBEDDING_CONCURRENCY)?;
+
+        let embed_depth = parse_positive("OBSIDIAN_EMBED_DEPTH", DEFAULT_EMBED_DEPTH)?;
+
+        let offline = std::env::var("OBSIDIAN_OFFLINE")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        let remember_budget = match std::env::var("OBSIDIAN_REMEMBER_BUDGET") {
+            Ok(value) => Some(parse_positive_value("OBSIDIAN_REMEMBER_BUDGET", &value)?),
+            Err(_) => None,
+        };
+
+        let storage_url = std::env::var("OBSIDIAN_STORAGE_URL")
+            .ok()
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+        if storage_url.is_some() && !cfg!(feature = "remote-storage") {
+            return Err(ConfigError::RemoteStorageUnavailable);
+        }
+        let storage_token = std::env::var("OBSIDIAN_STORAGE_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty());
 
         Ok(Self {
             vault_path,
             vault_name,
+            embedding_query_prefix,
+            embedding_document_prefix,
+            frontmatter_schema,
+            project_discovery,
+            allow_external_symlinks,
+            tool_timeouts,
+            debug_tools,
+            protected_folders,
+            private_roots,
+            graph_ignore,
+            embedding_batch_size,
+            embedding_concurrency,
+            offline,
+            remember_budget,
+            embed_depth,
+            storage_url,
+            storage_token,
         })
     }
+
+    /// Time limit for a tool, if it has one.
+    pub fn tool_timeout(&self, tool: &str) -> Option<Duration> {
+        self.tool_timeouts.get(tool).copied()
+    }
+}
+
+/// Parse `tool=seconds` pairs on top of the default timeouts.
+///
+/// A limit of 0 removes the tool's timeout.
+fn parse_tool_timeouts(spec: &str) -> Result<HashMap<String, Duration>, ConfigError> {
+    let mut timeouts = HashMap::from([("search".to_string(), DEFAULT_SEARCH_TIMEOUT)]);
+
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let invalid = || ConfigError::InvalidToolTimeout {
+            entry: entry.to_string(),
+        };
+        let (tool, seconds) = entry.split_once('=').ok_or_else(invalid)?;
+        let seconds: f64 = seconds.trim().parse().map_err(|_| invalid())?;
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(invalid());
+        }
+
+        let tool = tool.trim().to_string();
+        if seconds == 0.0 {
+            timeouts.remove(&tool);
+        } else {
+            timeouts.insert(tool, Duration::from_secs_f64(seconds));
+        }
+    }
+
+    Ok(timeouts)
+}
+
+/// Vault name from `configured`, falling back to the vault directory's name.
+///
+/// A blank configured name counts as unset. Paths like `.` are resolved first
+/// so the real directory name is found.
+fn resolve_vault_name(configured: Option<&str>, vault_path: &Path) -> Result<String, ConfigError> {
+    if let Some(name) = configured.map(str::trim).filter(|name| !name.is_empty()) {
+        return Ok(name.to_string());
+    }
+
+    let directory_name = |path: &Path| {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .filter(|n| !n.is_empty())
+            .map(str::to_string)
+    };
+    directory_name(vault_path)
+        .or_else(|| std::fs::canonicalize(vault_path).ok().as_deref().and_then(directory_name))
+        .ok_or_else(|| ConfigError::UndetectableVaultName {
+            path: vault_path.to_path_buf(),
+        })
+}
+
+/// Read a positive integer from `var`, falling back to `default` when unset.
+fn parse_positive(var: &'static str, default: usize) -> Result<usize, ConfigError> {
+    match std::env::var(var) {
+        Ok(value) => parse_positive_value(var, &value),
+        Err(_) => Ok(default),
+    }
+}
+
+fn parse_positive_value(var: &'static str, value: &str) -> Result<usize, ConfigError> {
+    match value.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(ConfigError::InvalidPositiveInteger {
+            var,
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Parse a comma-separated folder list, dropping surrounding slashes.
+fn parse_protected_folders(spec: &str) -> Vec<String> {
+    spec.split(',')
+        .map(|folder| folder.trim().trim_matches('/'))
+        .filter(|folder| !folder.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Read and parse a frontmatter schema file.
+fn load_frontmatter_schema(path: PathBuf) -> Result<FrontmatterSchema, ConfigError> {
+    let json = std::fs::read_to_string(&path).map_err(|e| ConfigError::InvalidFrontmatterSchema {
+        path: path.clone(),
+        reason: e.to_string(),
+    })?;
+    FrontmatterSchema::from_json(&json).map_err(|e| ConfigError::InvalidFrontmatterSchema {
+        path,
+        reason: e.to_string(),
+    })
+}
+
+/// Read and parse a project discovery config file.
+fn load_project_discovery(path: PathBuf) -> Result<ProjectDiscoveryConfig, ConfigError> {
+    let json = std::fs::read_to_string(&path).map_err(|e| ConfigError::InvalidProjectDiscovery {
+        path: path.clone(),
+        reason: e.to_string(),
+    })?;
+    ProjectDiscoveryConfig::from_json(&json).map_err(|e| ConfigError::InvalidProjectDiscovery {
+        path,
+        reason: e.to_string(),
+    })
 }
 
 /// Expand ~ or ~/ prefix to the user's home directory.
@@ -52,4 +315,89 @@ fn expand_tilde(path: &str) -> PathBuf {
 pub enum ConfigError {
     #[error("OBSIDIAN_VAULT_PATH environment variable not set")]
     MissingVaultPath,
+    #[error("Invalid frontmatter schema {path:?}: {reason}")]
+    InvalidFrontmatterSchema { path: PathBuf, reason: String },
+    #[error("Invalid project discovery config {path:?}: {reason}")]
+    InvalidProjectDiscovery { path: PathBuf, reason: String },
+    #[error("Invalid OBSIDIAN_TOOL_TIMEOUTS entry {entry:?}: expected tool=seconds")]
+    InvalidToolTimeout { entry: String },
+    #[error("Cannot determine the vault name from {path:?}; set OBSIDIAN_VAULT_NAME")]
+    UndetectableVaultName { path: PathBuf },
+    #[error("Invalid {var} {value:?}: expected a positive integer")]
+    InvalidPositiveInteger { var: &'static str, value: String },
+    #[error("OBSIDIAN_STORAGE_URL is set, but this build doesn't include the remote-storage feature")]
+    RemoteStorageUnavailable,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool_timeouts_defaults_to_search() {
+        let timeouts = parse_tool_timeouts("").unwrap();
+        assert_eq!(timeouts.get("search"), Some(&DEFAULT_SEARCH_TIMEOUT));
+        assert_eq!(timeouts.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_tool_timeouts_overrides_and_disables() {
+        let timeouts = parse_tool_timeouts("search=0, similar_to_note=2.5").unwrap();
+        assert_eq!(timeouts.get("search"), None);
+        assert_eq!(timeouts.get("similar_to_note"), Some(&Duration::from_millis(2500)));
+    }
+
+    #[test]
+    fn test_parse_tool_timeouts_rejects_bad_entries() {
+        assert!(parse_tool_timeouts("search").is_err());
+        assert!(parse_tool_timeouts("search=soon").is_err());
+        assert!(parse_tool_timeouts("search=-1").is_err());
+    }
+
+    #[test]
+    fn test_vault_name_override() {
+        let name = resolve_vault_name(Some(" Work Notes "), Path::new("/home/me/notes")).unwrap();
+        assert_eq!(name, "Work Notes");
+    }
+
+    #[test]
+    fn test_empty_vault_name_falls_back_to_directory() {
+        let path = Path::new("/home/me/notes");
+        assert_eq!(resolve_vault_name(None, path).unwrap(), "notes");
+        assert_eq!(resolve_vault_name(Some(""), path).unwrap(), "notes");
+        assert_eq!(resolve_vault_name(Some("   "), path).unwrap(), "notes");
+        assert_eq!(resolve_vault_name(None, Path::new("/home/me/notes/")).unwrap(), "notes");
+    }
+
+    #[test]
+    fn test_vault_name_detected_through_relative_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vault = temp_dir.path().join("My Vault");
+        std::fs::create_dir_all(vault.join("sub")).unwrap();
+
+        let name = resolve_vault_name(None, &vault.join("sub/..")).unwrap();
+        assert_eq!(name, "My Vault");
+    }
+
+    #[test]
+    fn test_undetectable_vault_name_is_an_error() {
+        let err = resolve_vault_name(None, Path::new("/")).unwrap_err();
+        assert!(err.to_string().contains("OBSIDIAN_VAULT_NAME"));
+    }
+
+    #[test]
+    fn test_parse_positive_value() {
+        assert_eq!(parse_positive_value("OBSIDIAN_EMBEDDING_BATCH_SIZE", " 8 ").unwrap(), 8);
+        assert!(parse_positive_value("OBSIDIAN_EMBEDDING_BATCH_SIZE", "0").is_err());
+        assert!(parse_positive_value("OBSIDIAN_EMBEDDING_CONCURRENCY", "many").is_err());
+    }
+
+    #[test]
+    fn test_parse_protected_folders() {
+        assert_eq!(
+            parse_protected_folders(" archive/, /published ,, journal/2024"),
+            vec!["archive", "published", "journal/2024"]
+        );
+        assert!(parse_protected_folders("").is_empty());
+    }
 }
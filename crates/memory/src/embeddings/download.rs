@@ -1,10 +1,22 @@
 //! Model downloading from Hugging Face.
+//!
+//! Files download to a `.part` file next to the destination and are only
+//! renamed into place once complete. An interrupted download resumes from the
+//! `.part` file with an HTTP range request. Files Hugging Face stores in LFS
+//! (the model weights) are checked against the SHA-256 it reports, and
+//! re-downloaded from scratch on a mismatch.
 
 use anyhow::{Context, Result};
 use futures_util::StreamExt;
+use reqwest::header::{CONTENT_RANGE, ETAG, RANGE};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
-use tokio::fs::{self, File};
-use tokio::io::AsyncWriteExt;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Hugging Face host
+const HF_BASE_URL: &str = "https://huggingface.co";
 
 /// Hugging Face model repository
 const REPO: &str = "sentence-transformers/all-MiniLM-L6-v2";
@@ -18,10 +30,18 @@ const MODEL_FILES: &[&str] = &[
     "model.safetensors",
 ];
 
+/// Download attempts per file before giving up on errors or checksum mismatches
+const MAX_ATTEMPTS: usize = 3;
+
 /// Download the all-MiniLM-L6-v2 model to the specified directory.
 ///
 /// Returns the path to the model directory.
 pub async fn download_model(model_dir: &Path) -> Result<PathBuf> {
+    download_model_from(HF_BASE_URL, model_dir).await
+}
+
+/// Download the model from a Hugging Face compatible host.
+async fn download_model_from(base_url: &str, model_dir: &Path) -> Result<PathBuf> {
     // Create model directory if needed
     if !model_dir.exists() {
         fs::create_dir_all(model_dir).await?;
@@ -37,6 +57,10 @@ pub async fn download_model(model_dir: &Path) -> Result<PathBuf> {
 
     tracing::info!("Downloading all-MiniLM-L6-v2 model from Hugging Face...");
 
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::limited(10))
+        .build()?;
+
     // Download each file
     for (idx, file) in MODEL_FILES.iter().enumerate() {
         let dest_path = model_dir.join(file);
@@ -52,7 +76,7 @@ pub async fn download_model(model_dir: &Path) -> Result<PathBuf> {
             fs::remove_file(&dest_path).await?;
         }
 
-        let url = format!("https://huggingface.co/{}/resolve/main/{}", REPO, file);
+        let url = format!("{}/{}/resolve/main/{}", base_url, REPO, file);
         let progress_pct = ((idx + 1) * 100) / MODEL_FILES.len();
         tracing::info!(
             file = file,
@@ -60,7 +84,8 @@ pub async fn download_model(model_dir: &Path) -> Result<PathBuf> {
             "Downloading model file"
         );
 
-        download_file(&url, &dest_path).await
+        download_verified(&client, &url, &dest_path)
+            .await
             .with_context(|| format!("Failed to download {}", file))?;
     }
 
@@ -71,6 +96,21 @@ pub async fn download_model(model_dir: &Path) -> Result<PathBuf> {
     Ok(model_dir.to_path_buf())
 }
 
+/// Use the model already in `model_dir` without touching the network.
+///
+/// Fails immediately if any model file is missing, for offline mode.
+pub async fn require_local_model(model_dir: &Path) -> Result<PathBuf> {
+    if check_model_files(model_dir).await {
+        return Ok(model_dir.to_path_buf());
+    }
+    anyhow::bail!(
+        "Embedding model not available offline: {} is missing model files. \
+         Build with the embedded-model feature, or copy the all-MiniLM-L6-v2 files ({}) into that directory.",
+        model_dir.display(),
+        MODEL_FILES.join(", ")
+    )
+}
+
 /// Check if all model files exist and are valid.
 async fn check_model_files(model_dir: &Path) -> bool {
     for file in MODEL_FILES {
@@ -83,24 +123,128 @@ async fn check_model_files(model_dir: &Path) -> bool {
     true
 }
 
-/// Download a single file with redirect handling.
-async fn download_file(url: &str, dest: &Path) -> Result<()> {
+/// Path of the in-progress download for `dest`.
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Download `url` to `dest`, resuming a partial download and verifying the
+/// SHA-256 when the server reports one.
+async fn download_verified(client: &reqwest::Client, url: &str, dest: &Path) -> Result<()> {
+    let expected = expected_sha256(url).await;
+    if expected.is_none() {
+        tracing::debug!("No checksum available for {}, skipping verification", url);
+    }
+    let partial = partial_path(dest);
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        // A dropped connection leaves the partial file, so the next attempt resumes it
+        if let Err(e) = download_file(client, url, &partial).await {
+            if attempt == MAX_ATTEMPTS {
+                return Err(e);
+            }
+            tracing::warn!(attempt, "Download of {} interrupted: {}. Resuming", url, e);
+            continue;
+        }
+
+        if let Some(expected) = &expected {
+            let actual = sha256_file(&partial).await?;
+            if &actual != expected {
+                fs::remove_file(&partial).await?;
+                tracing::warn!(
+                    attempt,
+                    expected = expected.as_str(),
+                    actual = actual.as_str(),
+                    "Checksum mismatch for {}, downloading again",
+                    url
+                );
+                continue;
+            }
+        }
+
+        fs::rename(&partial, dest).await?;
+        return Ok(());
+    }
+
+    anyhow::bail!("Checksum mismatch after {} attempts", MAX_ATTEMPTS)
+}
+
+/// SHA-256 of a file as Hugging Face reports it, if it reports one.
+///
+/// LFS files carry their SHA-256 in `X-Linked-Etag` on the (unfollowed)
+/// redirect; other files only have a git object ID in `ETag`, which isn't a
+/// content hash, so they aren't verified.
+async fn expected_sha256(url: &str) -> Option<String> {
     let client = reqwest::Client::builder()
-        .redirect(reqwest::redirect::Policy::limited(10))
-        .build()?;
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .ok()?;
+    let response = client.head(url).send().await.ok()?;
+
+    let headers = response.headers();
+    let etag = headers
+        .get("x-linked-etag")
+        .or_else(|| headers.get(ETAG))?
+        .to_str()
+        .ok()?;
+    parse_sha256_etag(etag)
+}
+
+/// Extract a SHA-256 hex digest from an ETag value, if it is one.
+fn parse_sha256_etag(etag: &str) -> Option<String> {
+    let etag = etag.trim().trim_start_matches("W/").trim_matches('"');
+    (etag.len() == 64 && etag.bytes().all(|b| b.is_ascii_hexdigit())).then(|| etag.to_ascii_lowercase())
+}
+
+/// SHA-256 of a file's contents, as lowercase hex.
+async fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Download a single file, appending to `dest` if it already holds a prefix.
+async fn download_file(client: &reqwest::Client, url: &str, dest: &Path) -> Result<()> {
+    let existing = fs::metadata(dest).await.map(|m| m.len()).unwrap_or(0);
 
-    let response = client
-        .get(url)
-        .send()
-        .await?
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(RANGE, format!("bytes={}-", existing));
+    }
+    let response = request.send().await?;
+
+    // The partial file already holds everything
+    if existing > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        return Ok(());
+    }
+    let response = response
         .error_for_status()
         .with_context(|| format!("HTTP error downloading {}", url))?;
 
-    let total_size = response.content_length();
-    let mut stream = response.bytes_stream();
+    // Servers that ignore the range send the whole file again
+    let resuming = existing > 0
+        && response.status() == StatusCode::PARTIAL_CONTENT
+        && response.headers().contains_key(CONTENT_RANGE);
+    let mut file = if resuming {
+        tracing::info!("Resuming download of {} at {} bytes", url, existing);
+        OpenOptions::new().append(true).open(dest).await?
+    } else {
+        File::create(dest).await?
+    };
 
-    let mut file = File::create(dest).await?;
-    let mut downloaded: u64 = 0;
+    let mut downloaded: u64 = if resuming { existing } else { 0 };
+    let total_size = response.content_length().map(|len| len + downloaded);
+    let mut stream = response.bytes_stream();
 
     while let Some(chunk) = stream.next().await {
         let chunk = chunk?;
@@ -156,8 +300,153 @@ async fn optimize_tokenizer(model_dir: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use crate::test_server::{self, Response};
     use tempfile::TempDir;
 
+    const MODEL_BYTES: &[u8] = b"pretend these are model weights, long enough to matter";
+
+    fn sha256_hex(bytes: &[u8]) -> String {
+        hex::encode(Sha256::digest(bytes))
+    }
+
+    /// Requests seen by the mock server.
+    #[derive(Default)]
+    struct Requests {
+        gets: AtomicUsize,
+        ranged_gets: AtomicUsize,
+    }
+
+    /// Serve `body` over HTTP, reporting `sha256` as `X-Linked-Etag` like
+    /// Hugging Face does for LFS files. GETs numbered in `corrupt_gets` get
+    /// mangled content instead. Range requests are honored.
+    async fn mock_server(
+        body: &'static [u8],
+        sha256: String,
+        corrupt_gets: &'static [usize],
+    ) -> (String, Arc<Requests>) {
+        let requests = Arc::new(Requests::default());
+        let seen = requests.clone();
+
+        let url = test_server::serve(move |request| {
+            let etag = format!("\"{}\"", sha256);
+            if request.method == "HEAD" {
+                return Response::new(200).header("x-linked-etag", etag).body(body);
+            }
+
+            let n = seen.gets.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut content = body.to_vec();
+            if corrupt_gets.contains(&n) {
+                content.reverse();
+            }
+            let start = request
+                .header("range")
+                .and_then(|range| range.strip_prefix("bytes="))
+                .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+            match start {
+                Some(start) => {
+                    seen.ranged_gets.fetch_add(1, Ordering::SeqCst);
+                    let range = format!("bytes {}-{}/{}", start, content.len() - 1, content.len());
+                    Response::new(206)
+                        .header("x-linked-etag", etag)
+                        .header("content-range", range)
+                        .body(content.split_off(start))
+                }
+                None => Response::new(200).header("x-linked-etag", etag).body(content),
+            }
+        })
+        .await;
+
+        (format!("{}/model.safetensors", url), requests)
+    }
+
+    #[tokio::test]
+    async fn test_verified_download() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("model.safetensors");
+        let (url, requests) = mock_server(MODEL_BYTES, sha256_hex(MODEL_BYTES), &[]).await;
+
+        download_verified(&reqwest::Client::new(), &url, &dest).await.unwrap();
+
+        assert_eq!(fs::read(&dest).await.unwrap(), MODEL_BYTES);
+        assert!(!partial_path(&dest).exists());
+        assert_eq!(requests.gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_downloads_again() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("model.safetensors");
+        let (url, requests) = mock_server(MODEL_BYTES, sha256_hex(MODEL_BYTES), &[1]).await;
+
+        download_verified(&reqwest::Client::new(), &url, &dest).await.unwrap();
+
+        assert_eq!(fs::read(&dest).await.unwrap(), MODEL_BYTES);
+        assert_eq!(requests.gets.load(Ordering::SeqCst), 2);
+        // The retry starts over rather than resuming the corrupt file
+        assert_eq!(requests.ranged_gets.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_checksum_mismatch_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("model.safetensors");
+        let (url, requests) = mock_server(MODEL_BYTES, sha256_hex(b"something else"), &[]).await;
+
+        let err = download_verified(&reqwest::Client::new(), &url, &dest).await.unwrap_err();
+
+        assert!(err.to_string().contains("Checksum mismatch"));
+        assert_eq!(requests.gets.load(Ordering::SeqCst), MAX_ATTEMPTS);
+        assert!(!dest.exists());
+        assert!(!partial_path(&dest).exists());
+    }
+
+    #[tokio::test]
+    async fn test_partial_download_resumes() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("model.safetensors");
+        fs::write(partial_path(&dest), &MODEL_BYTES[..20]).await.unwrap();
+        let (url, requests) = mock_server(MODEL_BYTES, sha256_hex(MODEL_BYTES), &[]).await;
+
+        download_verified(&reqwest::Client::new(), &url, &dest).await.unwrap();
+
+        assert_eq!(fs::read(&dest).await.unwrap(), MODEL_BYTES);
+        assert_eq!(requests.ranged_gets.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_parse_sha256_etag() {
+        let sha = sha256_hex(b"x");
+        assert_eq!(parse_sha256_etag(&format!("\"{}\"", sha)), Some(sha.clone()));
+        assert_eq!(parse_sha256_etag(&format!("W/\"{}\"", sha.to_uppercase())), Some(sha));
+        // Git object IDs (SHA-1) aren't content hashes
+        assert_eq!(parse_sha256_etag("\"2a5a1a1b2b1d3f0c6e8f0d3c5e5f1a2b3c4d5e6f\""), None);
+    }
+
+    #[tokio::test]
+    async fn test_offline_without_local_model_fails_fast() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let started = std::time::Instant::now();
+        let err = require_local_model(temp_dir.path()).await.unwrap_err();
+
+        assert!(err.to_string().contains("not available offline"));
+        assert!(err.to_string().contains("embedded-model"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_offline_with_local_model() {
+        let temp_dir = TempDir::new().unwrap();
+        for file in MODEL_FILES {
+            fs::write(temp_dir.path().join(file), vec![b'x'; 200]).await.unwrap();
+        }
+
+        let path = require_local_model(temp_dir.path()).await.unwrap();
+        assert_eq!(path, temp_dir.path());
+    }
+
     #[tokio::test]
     async fn test_check_model_files_empty_dir() {
         let temp_dir = TempDir::new().unwrap();
@@ -5,12 +5,23 @@ use semantic_embeddings::SemanticEmbeddings;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::fs;
 use tokio::sync::RwLock;
 
+use super::sections::{combine_sections, split_sections};
+use crate::config::Config;
+
 #[cfg(feature = "download-model")]
-use super::download::download_model;
+use super::download::{download_model, require_local_model};
+
+/// Version of the embedding scheme, stored with each cache entry.
+///
+/// Bumped when the way notes are turned into embeddings changes, so entries
+/// computed the old way are treated as stale and every note in the vault is
+/// scored the same way. Version 1 embeds long notes by section.
+const CACHE_VERSION: u32 = 1;
 
 /// Cache entry storing an embedding and its content hash.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -19,6 +30,130 @@ struct CacheEntry {
     content_hash: String,
     /// The embedding vector
     embedding: Vec<f32>,
+    /// Prefix prepended to the content before encoding (empty for entries
+    /// written before prefixes existed)
+    #[serde(default)]
+    prefix: String,
+    /// Embeddings of each section, in order, for long notes embedded by
+    /// section (empty for notes embedded whole)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sections: Vec<SectionEntry>,
+    /// `CACHE_VERSION` the entry was computed with (0 for entries written
+    /// before versions existed)
+    #[serde(default)]
+    version: u32,
+}
+
+/// Cached embedding of one section of a long note.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct SectionEntry {
+    /// SHA-256 hash of the section text
+    hash: String,
+    /// The section's embedding vector
+    embedding: Vec<f32>,
+}
+
+impl CacheEntry {
+    /// Whether this entry was computed from the given content with the given
+    /// prefix, by the current embedding scheme.
+    fn is_fresh(&self, content_hash: &str, prefix: &str) -> bool {
+        self.version == CACHE_VERSION
+            && self.content_hash == content_hash
+            && self.prefix == prefix
+    }
+}
+
+/// A note whose embedding needs computing.
+struct PendingNote {
+    path: String,
+    content_hash: String,
+    /// Prefixed texts to encode: the whole note, or the sections that changed
+    texts: Vec<String>,
+    /// For notes embedded by section, each section's hash and its cached
+    /// embedding if the section is unchanged (None for notes embedded whole)
+    sections: Option<Vec<(String, Option<Vec<f32>>)>>,
+}
+
+impl PendingNote {
+    /// Build the cache entry from the embeddings of `texts`, in order.
+    fn to_entry(&self, encoded: Vec<Vec<f32>>, prefix: &str) -> Result<CacheEntry> {
+        let mut encoded = encoded.into_iter();
+        let Some(sections) = &self.sections else {
+            return Ok(CacheEntry {
+                content_hash: self.content_hash.clone(),
+                embedding: encoded.next().context("Missing note embedding")?,
+                prefix: prefix.to_string(),
+                sections: Vec::new(),
+                version: CACHE_VERSION,
+            });
+        };
+
+        let mut entries = Vec::with_capacity(sections.len());
+        for (hash, cached) in sections {
+            let embedding = match cached {
+                Some(embedding) => embedding.clone(),
+                None => encoded.next().context("Missing section embedding")?,
+            };
+            entries.push(SectionEntry {
+                hash: hash.clone(),
+                embedding,
+            });
+        }
+        Ok(CacheEntry {
+            content_hash: self.content_hash.clone(),
+            embedding: combine_sections(entries.iter().map(|section| &section.embedding)),
+            prefix: prefix.to_string(),
+            sections: entries,
+            version: CACHE_VERSION,
+        })
+    }
+}
+
+/// Instruction prefixes prepended to text before encoding.
+///
+/// Some sentence-transformer models are trained for asymmetric search and expect
+/// a task prefix such as `"query: "` / `"passage: "`. MiniLM uses none, which is
+/// the default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbeddingPrefixes {
+    /// Prepended to search queries
+    pub query: String,
+    /// Prepended to note content
+    pub document: String,
+}
+
+/// Limits on how many notes are encoded at once when embedding in bulk.
+///
+/// Smaller batches and less concurrency keep CPU and memory use smooth on
+/// large vaults, at the cost of a slower startup preload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchLimits {
+    /// Notes encoded per model call
+    pub batch_size: usize,
+    /// Model calls allowed to run at the same time
+    pub concurrency: usize,
+}
+
+impl Default for BatchLimits {
+    fn default() -> Self {
+        Self {
+            batch_size: 25,
+            concurrency: 1,
+        }
+    }
+}
+
+/// Snapshot of the embedding cache, reported by the ReindexEmbeddings tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct CacheStats {
+    /// Number of cached embeddings
+    pub entries: usize,
+    /// Whether a full reindex is currently running
+    pub reindexing: bool,
+    /// Number of notes in the current (or last) reindex
+    pub reindex_total: usize,
+    /// Number of notes the running reindex has embedded so far
+    pub reindexed: usize,
 }
 
 /// Manages semantic embeddings for notes.
@@ -29,12 +164,24 @@ pub struct EmbeddingManager {
     embeddings: Arc<SemanticEmbeddings>,
     /// Cache of note embeddings: note_path -> (content_hash, embedding)
     cache: RwLock<HashMap<String, CacheEntry>>,
+    /// Entries built by a running reindex, swapped into `cache` when it finishes
+    staged: RwLock<HashMap<String, CacheEntry>>,
     /// Path to the cache file
     cache_path: PathBuf,
     /// Whether the model is loaded
     model_loaded: RwLock<bool>,
     /// Path to the model directory
     model_dir: PathBuf,
+    /// Prefixes applied to queries and documents before encoding
+    prefixes: EmbeddingPrefixes,
+    /// Batch size and concurrency for bulk encoding
+    batch_limits: BatchLimits,
+    /// Never download the model; fail if it isn't already on disk
+    offline: bool,
+    /// Set while a full reindex is running so concurrent requests don't stack
+    reindexing: AtomicBool,
+    /// Number of notes in the current (or last) reindex
+    reindex_total: AtomicUsize,
 }
 
 impl EmbeddingManager {
@@ -48,16 +195,64 @@ impl EmbeddingManager {
         Self {
             embeddings: Arc::new(SemanticEmbeddings::new()),
             cache: RwLock::new(HashMap::new()),
+            staged: RwLock::new(HashMap::new()),
             cache_path,
             model_loaded: RwLock::new(false),
             model_dir,
+            prefixes: EmbeddingPrefixes::default(),
+            batch_limits: BatchLimits::default(),
+            offline: false,
+            reindexing: AtomicBool::new(false),
+            reindex_total: AtomicUsize::new(0),
         }
     }
 
+    /// Create a manager with the prefixes, batch limits, and offline setting from `config`.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(&config.vault_path)
+            .with_prefixes(EmbeddingPrefixes {
+                query: config.embedding_query_prefix.clone(),
+                document: config.embedding_document_prefix.clone(),
+            })
+            .with_batch_limits(BatchLimits {
+                batch_size: config.embedding_batch_size,
+                concurrency: config.embedding_concurrency,
+            })
+            .offline(config.offline)
+    }
+
+    /// Set the query and document prefixes applied before encoding.
+    ///
+    /// Cached embeddings computed with different prefixes are treated as stale.
+    pub fn with_prefixes(mut self, prefixes: EmbeddingPrefixes) -> Self {
+        self.prefixes = prefixes;
+        self
+    }
+
+    /// Set the batch size and concurrency used when embedding many notes.
+    ///
+    /// Zero values are raised to 1.
+    pub fn with_batch_limits(mut self, limits: BatchLimits) -> Self {
+        self.batch_limits = BatchLimits {
+            batch_size: limits.batch_size.max(1),
+            concurrency: limits.concurrency.max(1),
+        };
+        self
+    }
+
+    /// Never download the model; initialization fails right away if it's missing.
+    ///
+    /// Has no effect with the `embedded-model` feature, which needs no download.
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
     /// Initialize the embedding manager by loading the model.
     ///
     /// With `embedded-model` feature: loads model from binary (no network).
-    /// With `download-model` feature: downloads from HuggingFace if not present.
+    /// With `download-model` feature: downloads from HuggingFace if not present
+    /// (or, offline, fails unless it is).
     ///
     /// Uses write lock for the entire operation to prevent race conditions.
     pub async fn initialize(&self) -> Result<()> {
@@ -79,7 +274,11 @@ impl EmbeddingManager {
         #[cfg(all(feature = "download-model", not(feature = "embedded-model")))]
         {
             // Download model if needed
-            download_model(&self.model_dir).await?;
+            if self.offline {
+                require_local_model(&self.model_dir).await?;
+            } else {
+                download_model(&self.model_dir).await?;
+            }
 
             // Load model from disk
             self.embeddings
@@ -112,10 +311,17 @@ impl EmbeddingManager {
         Ok(())
     }
 
-    /// Get or compute embedding for a note.
+    /// Get or compute embedding for search query text.
     ///
-    /// Uses cached embedding if content hasn't changed.
-    pub async fn get_embedding(&self, note_path: &str, content: &str) -> Result<Vec<f32>> {
+    /// The query prefix is applied before encoding.
+    pub async fn get_query_embedding(&self, text: &str) -> Result<Vec<f32>> {
+        self.get_embedding("__query__", text, &self.prefixes.query).await
+    }
+
+    /// Get or compute embedding for a cache key, encoding `prefix` + `content`.
+    ///
+    /// Uses cached embedding if neither content nor prefix has changed.
+    async fn get_embedding(&self, note_path: &str, content: &str, prefix: &str) -> Result<Vec<f32>> {
         self.ensure_loaded().await?;
 
         let content_hash = compute_hash(content);
@@ -124,14 +330,14 @@ impl EmbeddingManager {
         {
             let cache = self.cache.read().await;
             if let Some(entry) = cache.get(note_path) {
-                if entry.content_hash == content_hash {
+                if entry.is_fresh(&content_hash, prefix) {
                     return Ok(entry.embedding.clone());
                 }
             }
         }
 
         // Compute new embedding
-        let embedding = self.embeddings.encode(content)?;
+        let embedding = self.embeddings.encode(&apply_prefix(prefix, content))?;
 
         // Update cache
         {
@@ -141,6 +347,9 @@ impl EmbeddingManager {
                 CacheEntry {
                     content_hash,
                     embedding: embedding.clone(),
+                    prefix: prefix.to_string(),
+                    sections: Vec::new(),
+                    version: CACHE_VERSION,
                 },
             );
         }
@@ -148,69 +357,187 @@ impl EmbeddingManager {
         Ok(embedding)
     }
 
+    /// Encode `text` with the query or document prefix, bypassing the cache.
+    ///
+    /// Returns the embedding and the number of tokens the model saw, for
+    /// debugging why texts score the way they do.
+    pub async fn encode_uncached(&self, text: &str, as_query: bool) -> Result<(Vec<f32>, usize)> {
+        self.ensure_loaded().await?;
+
+        let prefix = if as_query {
+            &self.prefixes.query
+        } else {
+            &self.prefixes.document
+        };
+        let input = apply_prefix(prefix, text);
+
+        let embedding = self.embeddings.encode(&input)?;
+        let token_count = self.embeddings.count_tokens(&input)?;
+        Ok((embedding, token_count))
+    }
+
     /// Get embeddings for multiple notes in batch.
+    ///
+    /// The document prefix is applied before encoding. Long notes are encoded
+    /// by section, reusing cached embeddings of sections that haven't changed.
     pub async fn get_embeddings_batch(
         &self,
         notes: &[(String, String)], // (path, content)
     ) -> Result<Vec<(String, Vec<f32>)>> {
         self.ensure_loaded().await?;
 
+        let prefix = &self.prefixes.document;
         let mut results = Vec::with_capacity(notes.len());
-        let mut to_compute: Vec<(String, String, String)> = Vec::new(); // (path, content, content_hash)
+        let mut to_compute = Vec::new();
 
         // Check cache for each note
         {
             let cache = self.cache.read().await;
             for (path, content) in notes.iter() {
                 let content_hash = compute_hash(content);
+                let cached = cache.get(path);
 
-                if let Some(entry) = cache.get(path) {
-                    if entry.content_hash == content_hash {
-                        results.push((path.clone(), entry.embedding.clone()));
-                        continue;
-                    }
+                if let Some(entry) = cached
+                    && entry.is_fresh(&content_hash, prefix)
+                {
+                    results.push((path.clone(), entry.embedding.clone()));
+                    continue;
                 }
 
                 // Need to compute this one - store hash to avoid recomputing later
-                to_compute.push((path.clone(), content.clone(), content_hash));
+                to_compute.push(self.plan(path, content, content_hash, cached));
             }
         }
 
-        // Batch compute embeddings for cache misses in chunks to limit memory usage
+        // Batch compute embeddings for cache misses, within the configured limits
         if !to_compute.is_empty() {
-            let total = to_compute.len();
             tracing::info!(
-                "Computing {} embeddings in chunks ({} cached)",
-                total,
+                "Computing {} embeddings in batches of {} ({} cached)",
+                to_compute.len(),
+                self.batch_limits.batch_size,
                 results.len()
             );
 
-            const CHUNK_SIZE: usize = 25;
+            let model = self.embeddings.clone();
+            let computed = self
+                .compute_and_cache(&self.cache, to_compute, move |texts| model.encode_batch(&texts))
+                .await?;
+            results.extend(computed);
 
-            let mut computed = 0;
-            for chunk in to_compute.chunks(CHUNK_SIZE) {
-                let texts: Vec<String> = chunk.iter().map(|(_, content, _)| content.clone()).collect();
-                let embeddings = self.embeddings.encode_batch(&texts)?;
+            tracing::debug!(cache_size = results.len(), "Embedding computation complete");
+        } else {
+            tracing::debug!(cache_hits = results.len(), "All embeddings from cache");
+        }
 
-                let mut cache = self.cache.write().await;
-                for ((path, _, content_hash), embedding) in chunk.iter().zip(embeddings) {
-                    cache.insert(
-                        path.clone(),
-                        CacheEntry {
-                            content_hash: content_hash.clone(),
-                            embedding: embedding.clone(),
-                        },
-                    );
-                    results.push((path.clone(), embedding));
-                    computed += 1;
+        Ok(results)
+    }
+
+    /// Work out what to encode for a note, reusing section embeddings from
+    /// `cached` whose text hasn't changed.
+    fn plan(
+        &self,
+        path: &str,
+        content: &str,
+        content_hash: String,
+        cached: Option<&CacheEntry>,
+    ) -> PendingNote {
+        let prefix = &self.prefixes.document;
+        let sections = split_sections(content);
+        if sections.len() < 2 {
+            return PendingNote {
+                path: path.to_string(),
+                content_hash,
+                texts: vec![apply_prefix(prefix, content)],
+                sections: None,
+            };
+        }
+
+        let reusable: HashMap<&str, &Vec<f32>> = cached
+            .filter(|entry| entry.prefix == *prefix)
+            .map(|entry| {
+                entry
+                    .sections
+                    .iter()
+                    .map(|section| (section.hash.as_str(), &section.embedding))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut texts = Vec::new();
+        let sections = sections
+            .into_iter()
+            .map(|section| {
+                let hash = compute_hash(section);
+                let embedding = reusable.get(hash.as_str()).map(|&embedding| embedding.clone());
+                if embedding.is_none() {
+                    texts.push(apply_prefix(prefix, section));
                 }
+                (hash, embedding)
+            })
+            .collect();
+
+        PendingNote {
+            path: path.to_string(),
+            content_hash,
+            texts,
+            sections: Some(sections),
+        }
+    }
 
-                tracing::debug!("Computed {}/{} embeddings", computed, total);
+    /// Encode pending notes with `encode`, caching each result in `cache`.
+    ///
+    /// Notes are split into batches of `batch_size`, and up to `concurrency`
+    /// batches run at once on the blocking thread pool. Each finished batch is
+    /// cached right away, so a failure part way keeps the work already done.
+    async fn compute_and_cache<E>(
+        &self,
+        cache: &RwLock<HashMap<String, CacheEntry>>,
+        to_compute: Vec<PendingNote>,
+        encode: E,
+    ) -> Result<Vec<(String, Vec<f32>)>>
+    where
+        E: Fn(Vec<String>) -> Result<Vec<Vec<f32>>> + Clone + Send + 'static,
+    {
+        let BatchLimits {
+            batch_size,
+            concurrency,
+        } = self.batch_limits;
+        let prefix = &self.prefixes.document;
+        let total = to_compute.len();
+        let batches: Vec<_> = to_compute.chunks(batch_size).collect();
+
+        let mut results = Vec::with_capacity(total);
+        for wave in batches.chunks(concurrency) {
+            let tasks: Vec<_> = wave
+                .iter()
+                .map(|batch| {
+                    let texts: Vec<String> =
+                        batch.iter().flat_map(|note| note.texts.clone()).collect();
+                    let encode = encode.clone();
+                    tokio::task::spawn_blocking(move || {
+                        // Notes whose sections are all cached have nothing to encode
+                        if texts.is_empty() {
+                            Ok(Vec::new())
+                        } else {
+                            encode(texts)
+                        }
+                    })
+                })
+                .collect();
+
+            for (batch, task) in wave.iter().zip(tasks) {
+                let mut embeddings = task.await.context("Embedding task panicked")??.into_iter();
+
+                let mut cache = cache.write().await;
+                for note in batch.iter() {
+                    let encoded = embeddings.by_ref().take(note.texts.len()).collect();
+                    let entry = note.to_entry(encoded, prefix)?;
+                    results.push((note.path.clone(), entry.embedding.clone()));
+                    cache.insert(note.path.clone(), entry);
+                }
             }
 
-            tracing::debug!(cache_size = results.len(), "Embedding computation complete");
-        } else {
-            tracing::debug!(cache_hits = results.len(), "All embeddings from cache");
+            tracing::info!("Computed {}/{} embeddings", results.len(), total);
         }
 
         Ok(results)
@@ -247,16 +574,136 @@ impl EmbeddingManager {
         Ok(())
     }
 
+    /// Re-embed a changed note that's already cached, encoding only the
+    /// sections whose text changed.
+    ///
+    /// Does nothing if the note was never embedded or the model isn't loaded,
+    /// so edits to notes no search has touched don't load the model. Returns
+    /// the number of texts encoded.
+    pub async fn refresh(&self, note_path: &str, content: &str) -> Result<usize> {
+        let model = self.embeddings.clone();
+        self.refresh_with(note_path, content, move |texts| model.encode_batch(&texts))
+            .await
+    }
+
+    /// `refresh`, encoding with `encode`.
+    async fn refresh_with<E>(&self, note_path: &str, content: &str, encode: E) -> Result<usize>
+    where
+        E: Fn(Vec<String>) -> Result<Vec<Vec<f32>>> + Clone + Send + 'static,
+    {
+        if !*self.model_loaded.read().await {
+            return Ok(0);
+        }
+
+        let pending = {
+            let cache = self.cache.read().await;
+            let Some(entry) = cache.get(note_path) else {
+                return Ok(0);
+            };
+            let content_hash = compute_hash(content);
+            if entry.is_fresh(&content_hash, &self.prefixes.document) {
+                return Ok(0);
+            }
+            self.plan(note_path, content, content_hash, Some(entry))
+        };
+
+        let encoded = pending.texts.len();
+        self.compute_and_cache(&self.cache, vec![pending], encode).await?;
+        Ok(encoded)
+    }
+
     /// Invalidate cache entry for a note.
     pub async fn invalidate(&self, note_path: &str) {
         let mut cache = self.cache.write().await;
         cache.remove(note_path);
     }
 
+    /// Get a snapshot of the cache and reindex state.
+    pub async fn cache_stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.cache.read().await.len(),
+            reindexing: self.reindexing.load(Ordering::SeqCst),
+            reindex_total: self.reindex_total.load(Ordering::SeqCst),
+            reindexed: self.staged.read().await.len(),
+        }
+    }
+
+    /// Claim the reindex slot for `total` notes.
+    ///
+    /// Returns false if a reindex is already running. On success the caller must
+    /// follow up with `reindex`, which releases the slot when it finishes.
+    pub fn try_begin_reindex(&self, total: usize) -> bool {
+        let claimed = self
+            .reindexing
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok();
+        if claimed {
+            self.reindex_total.store(total, Ordering::SeqCst);
+        }
+        claimed
+    }
+
+    /// Recompute embeddings for all given notes and replace the cache with them.
+    ///
+    /// The new entries are built off to the side and swapped in at the end, so
+    /// searches running at the same time keep using the old cache. Returns the
+    /// number of embeddings computed.
+    pub async fn reindex(&self, notes: &[(String, String)]) -> Result<usize> {
+        // With no notes there's nothing to encode, so don't load the model
+        if !notes.is_empty()
+            && let Err(e) = self.ensure_loaded().await
+        {
+            self.reindexing.store(false, Ordering::SeqCst);
+            return Err(e);
+        }
+
+        let model = self.embeddings.clone();
+        self.reindex_with(notes, move |texts| model.encode_batch(&texts))
+            .await
+    }
+
+    /// `reindex`, encoding with `encode`. Releases the reindex slot when done.
+    pub(crate) async fn reindex_with<E>(&self, notes: &[(String, String)], encode: E) -> Result<usize>
+    where
+        E: Fn(Vec<String>) -> Result<Vec<Vec<f32>>> + Clone + Send + 'static,
+    {
+        // Nothing is reused from the old cache, so every note is encoded in full
+        let to_compute = notes
+            .iter()
+            .map(|(path, content)| self.plan(path, content, compute_hash(content), None))
+            .collect();
+
+        let result = self.compute_and_cache(&self.staged, to_compute, encode).await;
+        let staged = std::mem::take(&mut *self.staged.write().await);
+        let mut cache = self.cache.write().await;
+        self.reindexing.store(false, Ordering::SeqCst);
+        match result {
+            Ok(computed) => {
+                *cache = staged;
+                Ok(computed.len())
+            }
+            Err(e) => {
+                // Keep what was finished; the rest of the old cache stays usable
+                cache.extend(staged);
+                Err(e)
+            }
+        }
+    }
+
     /// Compute cosine similarity between two embeddings.
     pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Result<f32> {
         SemanticEmbeddings::cosine_similarity(a, b)
     }
+
+    /// Find indices of the `top_k` candidates most similar to `query`, best first.
+    pub fn find_most_similar(query: &[f32], candidates: &[Vec<f32>], top_k: usize) -> Result<Vec<u32>> {
+        SemanticEmbeddings::find_most_similar(query, candidates, top_k)
+    }
+}
+
+/// Prepend an instruction prefix to text before encoding.
+fn apply_prefix(prefix: &str, text: &str) -> String {
+    format!("{}{}", prefix, text)
 }
 
 /// Compute SHA-256 hash of content.
@@ -280,4 +727,253 @@ mod tests {
         assert_ne!(hash1, hash3);
         assert_eq!(hash1.len(), 64); // SHA-256 hex = 64 chars
     }
+
+    #[test]
+    fn test_apply_prefix() {
+        assert_eq!(apply_prefix("", "hello"), "hello");
+        assert_eq!(apply_prefix("query: ", "hello"), "query: hello");
+        assert_ne!(apply_prefix("query: ", "hello"), apply_prefix("passage: ", "hello"));
+    }
+
+    #[test]
+    fn test_cache_entry_requires_matching_prefix() {
+        let hash = compute_hash("content");
+        let entry = CacheEntry {
+            content_hash: hash.clone(),
+            embedding: vec![0.0; 3],
+            prefix: "passage: ".to_string(),
+            sections: Vec::new(),
+            version: CACHE_VERSION,
+        };
+
+        assert!(entry.is_fresh(&hash, "passage: "));
+        assert!(!entry.is_fresh(&hash, ""));
+        assert!(!entry.is_fresh(&compute_hash("other"), "passage: "));
+    }
+
+    #[test]
+    fn test_legacy_cache_entry_has_no_prefix() {
+        let json = r#"{"content_hash":"abc","embedding":[0.5],"version":1}"#;
+        let entry: CacheEntry = serde_json::from_str(json).unwrap();
+
+        assert_eq!(entry.prefix, "");
+        assert!(entry.is_fresh("abc", ""));
+        assert!(!entry.is_fresh("abc", "passage: "));
+    }
+
+    #[test]
+    fn test_unversioned_cache_entry_is_stale() {
+        // Written before long notes were embedded by section
+        let json = r#"{"content_hash":"abc","embedding":[0.5],"prefix":""}"#;
+        let entry: CacheEntry = serde_json::from_str(json).unwrap();
+
+        assert_eq!(entry.version, 0);
+        assert!(!entry.is_fresh("abc", ""));
+    }
+
+    #[test]
+    fn test_with_prefixes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let prefixes = EmbeddingPrefixes {
+            query: "query: ".to_string(),
+            document: "passage: ".to_string(),
+        };
+
+        let manager = EmbeddingManager::new(temp_dir.path());
+        assert_eq!(manager.prefixes, EmbeddingPrefixes::default());
+
+        let manager = manager.with_prefixes(prefixes.clone());
+        assert_eq!(manager.prefixes, prefixes);
+    }
+
+    #[cfg(all(feature = "download-model", not(feature = "embedded-model")))]
+    #[tokio::test]
+    async fn test_offline_initialize_fails_without_model() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = EmbeddingManager::new(temp_dir.path()).offline(true);
+
+        let err = manager.initialize().await.unwrap_err();
+        assert!(format!("{:#}", err).contains("not available offline"));
+        assert!(!*manager.model_loaded.read().await);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_slot_is_exclusive() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = EmbeddingManager::new(temp_dir.path());
+
+        assert!(manager.try_begin_reindex(3));
+        assert!(!manager.try_begin_reindex(5));
+
+        let stats = manager.cache_stats().await;
+        assert!(stats.reindexing);
+        assert_eq!(stats.reindex_total, 3);
+    }
+
+    /// Run `compute_and_cache` over `count` notes with a fake encoder,
+    /// returning the results and the size of every batch it was given.
+    async fn run_fake_batches(
+        limits: BatchLimits,
+        count: usize,
+    ) -> (Vec<(String, Vec<f32>)>, Vec<usize>) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = EmbeddingManager::new(temp_dir.path()).with_batch_limits(limits);
+        let to_compute = (0..count)
+            .map(|i| {
+                let content = format!("note {}", i);
+                let content_hash = compute_hash(&content);
+                manager.plan(&format!("{}.md", i), &content, content_hash, None)
+            })
+            .collect();
+
+        let batch_sizes = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = batch_sizes.clone();
+        let results = manager
+            .compute_and_cache(&manager.cache, to_compute, move |texts: Vec<String>| {
+                recorded.lock().unwrap().push(texts.len());
+                Ok(texts.iter().map(|t| vec![t.len() as f32]).collect())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(manager.cache_stats().await.entries, count);
+        let batch_sizes = batch_sizes.lock().unwrap().clone();
+        (results, batch_sizes)
+    }
+
+    #[tokio::test]
+    async fn test_batch_size_bounds_notes_per_call() {
+        let limits = BatchLimits {
+            batch_size: 4,
+            concurrency: 1,
+        };
+        let (results, batch_sizes) = run_fake_batches(limits, 10).await;
+
+        assert_eq!(batch_sizes, vec![4, 4, 2]);
+        assert_eq!(results.len(), 10);
+        // Results stay in input order
+        assert_eq!(results[0].0, "0.md");
+        assert_eq!(results[9].0, "9.md");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_batches_keep_size_and_order() {
+        let limits = BatchLimits {
+            batch_size: 3,
+            concurrency: 2,
+        };
+        let (results, mut batch_sizes) = run_fake_batches(limits, 8).await;
+
+        // Concurrent batches may finish in any order
+        batch_sizes.sort();
+        assert_eq!(batch_sizes, vec![2, 3, 3]);
+        let paths: Vec<&str> = results.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["0.md", "1.md", "2.md", "3.md", "4.md", "5.md", "6.md", "7.md"]);
+    }
+
+    /// A note long enough to embed by section, with `second` as the text of
+    /// its second section.
+    fn sectioned_note(second: &str) -> String {
+        let filler = "word ".repeat(crate::embeddings::sections::SECTION_MIN_CHARS / 5);
+        format!("# One\n{filler}\n# Two\n{second}\n# Three\n{filler}")
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reencodes_only_changed_sections() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = EmbeddingManager::new(temp_dir.path());
+        *manager.model_loaded.write().await = true;
+
+        let encoded = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = encoded.clone();
+        let encode = move |texts: Vec<String>| -> Result<Vec<Vec<f32>>> {
+            let embeddings = texts.iter().map(|t| vec![t.len() as f32, 1.0]).collect();
+            recorded.lock().unwrap().extend(texts);
+            Ok(embeddings)
+        };
+
+        // Never embedded, so there's nothing to refresh
+        let original = sectioned_note("Before");
+        let count = manager.refresh_with("Long.md", &original, encode.clone()).await.unwrap();
+        assert_eq!(count, 0);
+
+        let pending = manager.plan("Long.md", &original, compute_hash(&original), None);
+        manager
+            .compute_and_cache(&manager.cache, vec![pending], encode.clone())
+            .await
+            .unwrap();
+        assert_eq!(encoded.lock().unwrap().len(), 3);
+        let before = manager.cache.read().await["Long.md"].clone();
+
+        encoded.lock().unwrap().clear();
+        let edited = sectioned_note("After the edit");
+        let count = manager.refresh_with("Long.md", &edited, encode.clone()).await.unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(*encoded.lock().unwrap(), vec!["# Two\nAfter the edit\n".to_string()]);
+
+        let after = manager.cache.read().await["Long.md"].clone();
+        assert_eq!(after.content_hash, compute_hash(&edited));
+        assert_eq!(after.sections[0].embedding, before.sections[0].embedding);
+        assert_ne!(after.sections[1].hash, before.sections[1].hash);
+        assert_eq!(after.sections[2].embedding, before.sections[2].embedding);
+        assert_ne!(after.embedding, before.embedding);
+
+        // Refreshing unchanged content encodes nothing
+        let count = manager.refresh_with("Long.md", &edited, encode).await.unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_document_prefix_reaches_encoder_and_invalidates_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut manager = EmbeddingManager::new(temp_dir.path()).with_prefixes(EmbeddingPrefixes {
+            query: String::new(),
+            document: "passage: ".to_string(),
+        });
+        *manager.model_loaded.write().await = true;
+
+        let encoded = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = encoded.clone();
+        let encode = move |texts: Vec<String>| -> Result<Vec<Vec<f32>>> {
+            let embeddings = texts.iter().map(|t| vec![t.len() as f32, 1.0]).collect();
+            recorded.lock().unwrap().extend(texts);
+            Ok(embeddings)
+        };
+
+        let content = "A short note";
+        let pending = manager.plan("Note.md", content, compute_hash(content), None);
+        manager
+            .compute_and_cache(&manager.cache, vec![pending], encode.clone())
+            .await
+            .unwrap();
+        assert_eq!(*encoded.lock().unwrap(), vec!["passage: A short note".to_string()]);
+        assert_eq!(manager.cache.read().await["Note.md"].prefix, "passage: ");
+
+        // Same content, so only the prefix change makes the entry stale
+        encoded.lock().unwrap().clear();
+        manager.prefixes.document = "document: ".to_string();
+        let count = manager.refresh_with("Note.md", content, encode).await.unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(*encoded.lock().unwrap(), vec!["document: A short note".to_string()]);
+
+        let entry = manager.cache.read().await["Note.md"].clone();
+        assert_eq!(entry.prefix, "document: ");
+        assert!(entry.is_fresh(&compute_hash(content), "document: "));
+    }
+
+    #[test]
+    fn test_zero_batch_limits_are_raised() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = EmbeddingManager::new(temp_dir.path()).with_batch_limits(BatchLimits {
+            batch_size: 0,
+            concurrency: 0,
+        });
+        assert_eq!(
+            manager.batch_limits,
+            BatchLimits {
+                batch_size: 1,
+                concurrency: 1,
+            }
+        );
+    }
 }
@@ -3,11 +3,12 @@
 //! This module handles:
 //! - Model downloading from Hugging Face (with `download-model` feature)
 //! - Model loading from embedded binary (with `embedded-model` feature)
-//! - Embedding generation with caching
+//! - Embedding generation with caching, by section for long notes
 //! - Cache persistence to disk
 
 #[cfg(feature = "download-model")]
 mod download;
 mod manager;
+mod sections;
 
-pub use manager::EmbeddingManager;
+pub use manager::{CacheStats, EmbeddingManager};
@@ -0,0 +1,116 @@
+//! Heading-aware chunking of long notes for embedding.
+//!
+//! The model only sees the first few hundred tokens of its input, so a long
+//! note is embedded section by section, split at its headings, and the note's
+//! embedding is the average of its sections. Each section is cached under its
+//! own hash, so an edit to one section only re-encodes that section.
+
+use obsidian_fs::extract_headings;
+
+/// Notes shorter than this many characters are embedded whole.
+pub const SECTION_MIN_CHARS: usize = 2000;
+
+/// Split a note into sections at its headings.
+///
+/// The first section holds everything before the first heading, frontmatter
+/// included; each later one runs from a heading to the next heading. Blank
+/// sections are dropped. Notes shorter than `SECTION_MIN_CHARS` come back as a
+/// single section.
+pub fn split_sections(content: &str) -> Vec<&str> {
+    if content.chars().count() < SECTION_MIN_CHARS {
+        return vec![content];
+    }
+
+    // Byte offset of the start of each line
+    let mut line_starts = Vec::new();
+    let mut offset = 0;
+    for line in content.split('\n') {
+        line_starts.push(offset);
+        offset += line.len() + 1;
+    }
+
+    let mut bounds: Vec<usize> = extract_headings(content)
+        .iter()
+        .map(|heading| line_starts[heading.line])
+        .collect();
+    bounds.insert(0, 0);
+    bounds.push(content.len());
+    bounds.dedup();
+
+    let sections: Vec<&str> = bounds
+        .windows(2)
+        .map(|window| &content[window[0]..window[1]])
+        .filter(|section| !section.trim().is_empty())
+        .collect();
+    if sections.is_empty() {
+        vec![content]
+    } else {
+        sections
+    }
+}
+
+/// Combine section embeddings into one note embedding: their mean, scaled to
+/// unit length like the model's own output.
+pub fn combine_sections<'a, I>(embeddings: I) -> Vec<f32>
+where
+    I: IntoIterator<Item = &'a Vec<f32>>,
+{
+    let mut sum: Vec<f32> = Vec::new();
+    for embedding in embeddings {
+        if sum.is_empty() {
+            sum = vec![0.0; embedding.len()];
+        }
+        for (total, value) in sum.iter_mut().zip(embedding) {
+            *total += value;
+        }
+    }
+
+    let norm = sum.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in &mut sum {
+            *value /= norm;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A note long enough to be split, with an intro and two sections.
+    fn long_note() -> String {
+        let filler = "word ".repeat(SECTION_MIN_CHARS / 5);
+        format!(
+            "---\ntitle: Long\n---\nIntro {filler}\n# One\n{filler}\n```sh\n# not a heading\n```\n## Two\nShort"
+        )
+    }
+
+    #[test]
+    fn test_short_notes_are_one_section() {
+        let content = "# One\nText\n# Two\nMore";
+        assert_eq!(split_sections(content), vec![content]);
+    }
+
+    #[test]
+    fn test_long_notes_split_at_headings() {
+        let content = long_note();
+        let sections = split_sections(&content);
+
+        assert_eq!(sections.len(), 3);
+        assert!(sections[0].starts_with("---\ntitle: Long"));
+        assert!(sections[1].starts_with("# One\n"));
+        assert!(sections[1].contains("# not a heading"));
+        assert_eq!(sections[2], "## Two\nShort");
+        assert_eq!(sections.concat(), content);
+    }
+
+    #[test]
+    fn test_combine_sections_averages_to_unit_length() {
+        let combined = combine_sections(&[vec![1.0, 0.0], vec![0.0, 1.0]]);
+        let expected = 1.0 / 2f32.sqrt();
+        assert!((combined[0] - expected).abs() < 1e-6);
+        assert!((combined[1] - expected).abs() < 1e-6);
+        assert!(combine_sections(&Vec::<Vec<f32>>::new()).is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Per-folder frontmatter schemas enforced on write.
+//!
+//! A schema file maps vault folders to the frontmatter fields their notes must
+//! carry. Notes outside every listed folder are not validated. When folders
+//! nest, the most specific one wins:
+//!
+//! ```json
+//! {
+//!   "projects": {
+//!     "status": { "type": "string", "required": true },
+//!     "created": { "type": "date", "required": true },
+//!     "tags": { "type": "list" }
+//!   }
+//! }
+//! ```
+
+use chrono::NaiveDate;
+use obsidian_fs::Frontmatter;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Expected type of a frontmatter field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    /// Any value
+    #[default]
+    Any,
+    String,
+    Number,
+    Boolean,
+    List,
+    /// `YYYY-MM-DD` string
+    Date,
+}
+
+impl FieldType {
+    fn matches(self, value: &JsonValue) -> bool {
+        match self {
+            FieldType::Any => true,
+            FieldType::String => value.is_string(),
+            FieldType::Number => value.is_number(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::List => value.is_array(),
+            FieldType::Date => value
+                .as_str()
+                .is_some_and(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()),
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FieldType::Any => "any",
+            FieldType::String => "string",
+            FieldType::Number => "number",
+            FieldType::Boolean => "boolean",
+            FieldType::List => "list",
+            FieldType::Date => "date (YYYY-MM-DD)",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Rule for a single frontmatter field.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct FieldRule {
+    #[serde(default, rename = "type")]
+    pub field_type: FieldType,
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// Frontmatter schemas keyed by vault folder (e.g. `projects` or `knowledge/people`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct FrontmatterSchema {
+    folders: BTreeMap<String, BTreeMap<String, FieldRule>>,
+}
+
+/// Fields that made a note's frontmatter fail its folder's schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// Folder whose schema was applied
+    pub folder: String,
+    /// Required fields that are absent
+    pub missing: Vec<String>,
+    /// Fields present with the wrong type, with the expected type
+    pub invalid: Vec<(String, FieldType)>,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Frontmatter doesn't match the schema for '{}/'", self.folder)?;
+        if !self.missing.is_empty() {
+            write!(f, "; missing required fields: {}", self.missing.join(", "))?;
+        }
+        if !self.invalid.is_empty() {
+            let invalid: Vec<String> = self
+                .invalid
+                .iter()
+                .map(|(field, expected)| format!("{} (expected {})", field, expected))
+                .collect();
+            write!(f, "; invalid fields: {}", invalid.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl FrontmatterSchema {
+    /// Parse a schema from its JSON representation.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let schema: Self = serde_json::from_str(json)?;
+        // Normalize folder keys so "projects/" and "projects" behave the same
+        let folders = schema
+            .folders
+            .into_iter()
+            .map(|(folder, fields)| (folder.trim_matches('/').to_string(), fields))
+            .collect();
+        Ok(Self { folders })
+    }
+
+    /// Returns true if no folder has a schema.
+    pub fn is_empty(&self) -> bool {
+        self.folders.is_empty()
+    }
+
+    /// Most specific folder with a schema that contains `note_path`.
+    fn folder_for(&self, note_path: &str) -> Option<(&str, &BTreeMap<String, FieldRule>)> {
+        self.folders
+            .iter()
+            .filter(|(folder, _)| {
+                folder.is_empty()
+                    || note_path
+                        .strip_prefix(folder.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .max_by_key(|(folder, _)| folder.len())
+            .map(|(folder, fields)| (folder.as_str(), fields))
+    }
+
+    /// Validate a note's frontmatter against the schema for its folder.
+    ///
+    /// `note_path` is vault-relative, with or without the `.md` extension.
+    pub fn validate(
+        &self,
+        note_path: &str,
+        frontmatter: Option<&Frontmatter>,
+    ) -> Result<(), SchemaViolation> {
+        let Some((folder, fields)) = self.folder_for(note_path) else {
+            return Ok(());
+        };
+
+        let mut missing = Vec::new();
+        let mut invalid = Vec::new();
+        for (name, rule) in fields {
+            match frontmatter.and_then(|fm| fm.get(name)) {
+                None | Some(JsonValue::Null) if rule.required => missing.push(name.clone()),
+                None | Some(JsonValue::Null) => {}
+                Some(value) if !rule.field_type.matches(value) => {
+                    invalid.push((name.clone(), rule.field_type))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if missing.is_empty() && invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(SchemaViolation {
+                folder: folder.to_string(),
+                missing,
+                invalid,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> FrontmatterSchema {
+        FrontmatterSchema::from_json(
+            r#"{
+                "projects/": {
+                    "status": { "type": "string", "required": true },
+                    "created": { "type": "date", "required": true },
+                    "tags": { "type": "list" }
+                },
+                "projects/archive": {
+                    "archived": { "type": "boolean", "required": true }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn frontmatter(value: JsonValue) -> Frontmatter {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_notes_outside_schema_folders_are_not_validated() {
+        let schema = schema();
+        assert!(schema.validate("knowledge/Note", None).is_ok());
+        // Prefix match must be on a folder boundary
+        assert!(schema.validate("projects-old/Note", None).is_ok());
+    }
+
+    #[test]
+    fn test_missing_and_invalid_fields_reported() {
+        let fm = frontmatter(json!({ "created": "yesterday", "tags": "one" }));
+        let err = schema().validate("projects/App.md", Some(&fm)).unwrap_err();
+
+        assert_eq!(err.folder, "projects");
+        assert_eq!(err.missing, vec!["status"]);
+        assert_eq!(
+            err.invalid,
+            vec![
+                ("created".to_string(), FieldType::Date),
+                ("tags".to_string(), FieldType::List),
+            ]
+        );
+        let message = err.to_string();
+        assert!(message.contains("missing required fields: status"), "{}", message);
+        assert!(message.contains("tags (expected list)"), "{}", message);
+    }
+
+    #[test]
+    fn test_valid_frontmatter_passes() {
+        let fm = frontmatter(json!({
+            "status": "active",
+            "created": "2025-01-31",
+            "tags": ["rust"],
+            "extra": 1
+        }));
+        assert!(schema().validate("projects/App", Some(&fm)).is_ok());
+    }
+
+    #[test]
+    fn test_most_specific_folder_wins() {
+        let fm = frontmatter(json!({ "archived": true }));
+        assert!(schema().validate("projects/archive/Old", Some(&fm)).is_ok());
+
+        let err = schema().validate("projects/archive/Old", None).unwrap_err();
+        assert_eq!(err.folder, "projects/archive");
+        assert_eq!(err.missing, vec!["archived"]);
+    }
+
+    #[test]
+    fn test_invalid_schema_json_rejected() {
+        assert!(FrontmatterSchema::from_json(r#"{ "projects": { "status": { "type": "uuid" } } }"#).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+//! Paths left out of the graph index.
+//!
+//! Ignored notes get no graph entry, so they don't resolve as link targets,
+//! show up as backlinks, or appear in search.
+
+use crate::projects::glob_match;
+
+/// Patterns ignored even when none are configured.
+pub const DEFAULT_GRAPH_IGNORE: &[&str] = &[".obsidian/", ".sync/"];
+
+/// Vault paths the graph index skips.
+///
+/// A pattern ending in `/` ignores everything under that vault folder; any
+/// other pattern is matched against the whole vault-relative path, where `*`
+/// matches within one folder and `**` matches across folders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphIgnore {
+    patterns: Vec<String>,
+}
+
+impl GraphIgnore {
+    /// The default patterns plus `patterns`, ignoring surrounding whitespace and
+    /// empty patterns.
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut ignore = Self::default();
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim().trim_start_matches('/');
+            if !pattern.is_empty() && !ignore.patterns.iter().any(|p| p == pattern) {
+                ignore.patterns.push(pattern.to_string());
+            }
+        }
+        ignore
+    }
+
+    /// Whether `path` (relative to the vault) is ignored.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.patterns.iter().any(|pattern| {
+            if pattern.ends_with('/') {
+                path.starts_with(pattern.as_str())
+            } else {
+                glob_match(pattern.as_bytes(), path.as_bytes())
+            }
+        })
+    }
+}
+
+impl Default for GraphIgnore {
+    fn default() -> Self {
+        Self {
+            patterns: DEFAULT_GRAPH_IGNORE.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_ignores_app_folders() {
+        let ignore = GraphIgnore::default();
+        assert!(ignore.is_ignored(".obsidian/plugins/Readme.md"));
+        assert!(ignore.is_ignored(".sync/documents/Note.md"));
+        assert!(!ignore.is_ignored("knowledge/Note.md"));
+        assert!(!ignore.is_ignored("notes/.obsidian/Note.md"));
+    }
+
+    #[test]
+    fn test_configured_folders_and_globs() {
+        let ignore = GraphIgnore::new([" templates/", "", "**/*.excalidraw.md", "Scratch.md"]);
+        assert_eq!(ignore.patterns.len(), 5);
+        assert!(ignore.is_ignored("templates/Daily.md"));
+        assert!(!ignore.is_ignored("knowledge/templates/Daily.md"));
+        assert!(ignore.is_ignored("drawings/Plan.excalidraw.md"));
+        assert!(ignore.is_ignored("Plan.excalidraw.md"));
+        assert!(ignore.is_ignored("Scratch.md"));
+        assert!(!ignore.is_ignored("knowledge/Scratch.md"));
+    }
+}
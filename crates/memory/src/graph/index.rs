@@ -1,8 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use obsidian_fs::{parse_note, Frontmatter, PrivateRoots};
 use tokio::fs;
-use wiki_links::extract_linked_notes;
+
+use super::pagerank::personalized_pagerank;
+use super::snapshot::{FileStamp, GraphSnapshot, SnapshotNote};
+use super::GraphIgnore;
+
+/// Radius (in hops) of the neighborhoods cached for search boosting.
+pub const NEIGHBORHOOD_RADIUS: usize = 2;
+
+/// Notes within `NEIGHBORHOOD_RADIUS` of a source path, mapped to their hop distance.
+pub type Neighborhood = HashMap<String, usize>;
+
+/// Personalized PageRank scores from a seed note, keyed by note name.
+pub type SeedScores = HashMap<String, f64>;
 
 /// Tracks forward links and backlinks between notes in the vault.
 ///
@@ -13,6 +27,10 @@ use wiki_links::extract_linked_notes;
 /// Notes are keyed by their relative path (e.g., "knowledge/Index.md") to avoid
 /// collisions between same-named notes in different folders. Wiki-links reference
 /// note names, so we maintain a name → paths lookup for resolution.
+///
+/// Inline tags (`#project/active`) are tracked too, as a secondary connection
+/// signal: notes sharing a tag are related (see `tag_neighbors`), but tags don't
+/// count as links for neighborhoods, cycles, or PageRank.
 #[derive(Debug, Default)]
 pub struct GraphIndex {
     /// Map from relative path to its forward links (note names from wiki-links)
@@ -21,6 +39,26 @@ pub struct GraphIndex {
     backlinks: HashMap<String, HashSet<String>>,
     /// Map from note name to all paths with that name (for wiki-link resolution)
     name_to_paths: HashMap<String, HashSet<String>>,
+    /// Cached bounded-radius neighborhoods keyed by source path.
+    ///
+    /// Behind a mutex so lookups can fill the cache while callers only hold a
+    /// read lock on the index. Entries are invalidated on edge changes.
+    neighborhood_cache: Mutex<HashMap<String, Arc<Neighborhood>>>,
+    /// Cached seed scores keyed by seed note name, limited to the seed's
+    /// neighborhoods and invalidated along with them.
+    seed_score_cache: Mutex<HashMap<String, Arc<SeedScores>>>,
+    /// Parsed frontmatter keyed by relative path (only notes that have any)
+    frontmatter: HashMap<String, Frontmatter>,
+    /// Map from relative path to its inline tags, lowercased (only notes that have any)
+    tags: HashMap<String, HashSet<String>>,
+    /// Map from lowercased tag to paths using it
+    tag_to_paths: HashMap<String, HashSet<String>>,
+    /// Folders whose notes are private
+    private_roots: PrivateRoots,
+    /// Paths left out of the index
+    ignore: GraphIgnore,
+    /// Paths added, updated or removed since `track_changes`, while tracking
+    changed_paths: Option<HashSet<String>>,
 }
 
 impl GraphIndex {
@@ -31,25 +69,137 @@ impl GraphIndex {
     /// Initialize the graph index by scanning the vault.
     ///
     /// Recursively scans all markdown files in the vault, extracts wiki-links,
-    /// and builds the forward links and backlinks graph.
+    /// and builds the forward links and backlinks graph. Ignored paths are
+    /// skipped without being read. Notes unchanged since the last snapshot are
+    /// restored from it rather than re-parsed, and a fresh snapshot is saved
+    /// for the next start.
     pub async fn initialize(&mut self, vault_path: &Path) -> Result<(), std::io::Error> {
+        let previous = GraphSnapshot::load(vault_path).await.unwrap_or_default();
+        self.scan(vault_path, previous).await
+    }
+
+    /// Like `initialize`, but re-parses every note instead of trusting the snapshot.
+    pub async fn initialize_full(&mut self, vault_path: &Path) -> Result<(), std::io::Error> {
+        self.scan(vault_path, GraphSnapshot::default()).await
+    }
+
+    /// An empty index with the same private roots and ignore list.
+    pub fn empty_like(&self) -> Self {
+        Self {
+            private_roots: self.private_roots.clone(),
+            ignore: self.ignore.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Start recording the paths that `update_note` and `remove_note` touch.
+    pub fn track_changes(&mut self) {
+        self.changed_paths = Some(HashSet::new());
+    }
+
+    /// Stop recording and return the paths changed since `track_changes`.
+    pub fn take_changes(&mut self) -> HashSet<String> {
+        self.changed_paths.take().unwrap_or_default()
+    }
+
+    /// Re-read one note from disk, or remove it if the file is gone.
+    pub async fn refresh_note(&mut self, vault_path: &Path, path_key: &str) {
+        let relative_path = PathBuf::from(path_key);
+        let note_name = relative_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        match Self::parse_file(&vault_path.join(&relative_path)).await {
+            Ok((links, tags, frontmatter)) => {
+                self.set_frontmatter(path_key, frontmatter);
+                self.set_tags(path_key, tags);
+                self.update_note(&note_name, relative_path, links);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.remove_note(&note_name, &relative_path);
+            }
+            Err(e) => tracing::warn!("Failed to index {}: {}", path_key, e),
+        }
+    }
+
+    /// Index the vault's notes, restoring unchanged ones from `previous`.
+    async fn scan(
+        &mut self,
+        vault_path: &Path,
+        mut previous: GraphSnapshot,
+    ) -> Result<(), std::io::Error> {
         tracing::info!("Scanning vault for notes...");
 
         let files = Self::get_all_markdown_files(vault_path).await?;
         tracing::info!("Found {} markdown files", files.len());
 
+        let mut snapshot = GraphSnapshot::default();
+        let mut reparsed = 0;
+
         for file_path in files {
-            if let Err(e) = self.index_file(vault_path, &file_path).await {
-                tracing::warn!("Failed to index {}: {}", file_path.display(), e);
+            let relative_path = file_path.strip_prefix(vault_path).unwrap_or(&file_path);
+            let path_key = relative_path.to_string_lossy().to_string();
+            if self.is_ignored(&path_key) {
+                continue;
+            }
+
+            // Stamp before reading, so an edit during the read counts as a change next time
+            let stamp = fs::metadata(&file_path)
+                .await
+                .ok()
+                .and_then(|metadata| FileStamp::from_metadata(&metadata));
+            let (links, tags, frontmatter) = match previous.notes.remove(&path_key) {
+                Some(note) if Some(note.stamp) == stamp => {
+                    (note.links, note.tags, note.frontmatter)
+                }
+                _ => match Self::parse_file(&file_path).await {
+                    Ok(parsed) => {
+                        reparsed += 1;
+                        parsed
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to index {}: {}", file_path.display(), e);
+                        continue;
+                    }
+                },
+            };
+
+            // Get note name (filename without .md extension)
+            let note_name = file_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_string();
+
+            self.set_frontmatter(&path_key, frontmatter.clone());
+            self.set_tags(&path_key, tags.clone());
+            self.update_note(&note_name, relative_path.to_path_buf(), links.clone());
+            if let Some(stamp) = stamp {
+                snapshot.notes.insert(
+                    path_key,
+                    SnapshotNote {
+                        stamp,
+                        links,
+                        tags,
+                        frontmatter,
+                    },
+                );
             }
         }
 
         tracing::info!(
-            "Indexed {} notes with {} total links",
+            "Indexed {} notes with {} total links ({} parsed, the rest from snapshot)",
             self.len(),
-            self.get_total_links()
+            self.get_total_links(),
+            reparsed
         );
 
+        if let Err(e) = snapshot.save(vault_path).await {
+            tracing::warn!("Failed to save graph snapshot: {}", e);
+        }
+
         Ok(())
     }
 
@@ -81,31 +231,18 @@ impl GraphIndex {
         Ok(files)
     }
 
-    /// Index a single file (extract links and update graph).
-    async fn index_file(&mut self, vault_path: &Path, file_path: &Path) -> Result<(), std::io::Error> {
+    /// Read a note and extract its links, inline tags, and frontmatter.
+    async fn parse_file(
+        file_path: &Path,
+    ) -> Result<(HashSet<String>, HashSet<String>, Option<Frontmatter>), std::io::Error> {
         let content = fs::read_to_string(file_path).await?;
 
-        // Get note name (filename without .md extension)
-        let note_name = file_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or_default()
-            .to_string();
-
-        // Get relative path from vault root
-        let relative_path = file_path
-            .strip_prefix(vault_path)
-            .unwrap_or(file_path)
-            .to_path_buf();
-
-        // Extract linked notes using wiki-links crate
-        let linked_notes = extract_linked_notes(&content);
-        let links: HashSet<String> = linked_notes.into_iter().collect();
+        // Frontmatter, linked notes (wiki links and Markdown links), and tags in one pass
+        let note = parse_note(&content);
+        let links = note.linked_notes.into_iter().collect();
+        let tags = note.tags.into_iter().collect();
 
-        // Update the graph
-        self.update_note(&note_name, relative_path, links);
-
-        Ok(())
+        Ok((links, tags, note.frontmatter))
     }
 
     /// Get total number of links in the graph.
@@ -117,9 +254,18 @@ impl GraphIndex {
     ///
     /// This removes any existing links for the note and replaces them with the new set.
     /// The path should be relative to the vault root (e.g., "knowledge/Note.md").
+    /// Ignored paths are removed from the index instead.
     pub fn update_note(&mut self, note_name: &str, path: PathBuf, links: HashSet<String>) {
         let path_key = path.to_string_lossy().to_string();
-        
+        if let Some(changed) = &mut self.changed_paths {
+            changed.insert(path_key.clone());
+        }
+        if self.is_ignored(&path_key) {
+            self.remove_note(note_name, &path);
+            return;
+        }
+        let old_neighbors = self.get_neighborhood(&path_key);
+
         // Remove old backlinks for this path
         if let Some(old_links) = self.forward_links.get(&path_key) {
             for target in old_links.iter() {
@@ -144,7 +290,12 @@ impl GraphIndex {
         self.name_to_paths
             .entry(note_name.to_string())
             .or_default()
-            .insert(path_key);
+            .insert(path_key.clone());
+
+        let mut touched = old_neighbors;
+        touched.extend(self.get_neighborhood(&path_key));
+        touched.insert(path_key);
+        self.invalidate_neighborhoods(&touched);
     }
 
     /// Remove a note from the index entirely.
@@ -152,7 +303,12 @@ impl GraphIndex {
     /// The path should be the relative path used when the note was added.
     pub fn remove_note(&mut self, note_name: &str, path: &Path) {
         let path_key = path.to_string_lossy().to_string();
-        
+        if let Some(changed) = &mut self.changed_paths {
+            changed.insert(path_key.clone());
+        }
+        let mut touched = self.get_neighborhood(&path_key);
+        touched.insert(path_key.clone());
+
         // Remove forward links and their backlink entries
         if let Some(links) = self.forward_links.remove(&path_key) {
             for target in links {
@@ -174,6 +330,132 @@ impl GraphIndex {
                 self.name_to_paths.remove(note_name);
             }
         }
+
+        self.frontmatter.remove(&path_key);
+        self.set_tags(&path_key, HashSet::new());
+
+        self.invalidate_neighborhoods(&touched);
+    }
+
+    /// Cache a note's parsed frontmatter, or clear it if the note has none.
+    pub fn set_frontmatter(&mut self, path: &str, frontmatter: Option<Frontmatter>) {
+        match frontmatter {
+            Some(frontmatter) if !frontmatter.is_empty() && !self.is_ignored(path) => {
+                self.frontmatter.insert(path.to_string(), frontmatter);
+            }
+            _ => {
+                self.frontmatter.remove(path);
+            }
+        }
+    }
+
+    /// Get the cached frontmatter for a note by path.
+    pub fn get_frontmatter(&self, path: &str) -> Option<&Frontmatter> {
+        self.frontmatter.get(path)
+    }
+
+    /// Replace a note's inline tags (names without `#`, like "project/active").
+    ///
+    /// Tags are matched case-insensitively, as in Obsidian, so they're stored
+    /// lowercased. Ignored paths get no tags.
+    pub fn set_tags(&mut self, path: &str, tags: HashSet<String>) {
+        if let Some(old_tags) = self.tags.remove(path) {
+            for tag in old_tags {
+                if let Some(paths) = self.tag_to_paths.get_mut(&tag) {
+                    paths.remove(path);
+                    if paths.is_empty() {
+                        self.tag_to_paths.remove(&tag);
+                    }
+                }
+            }
+        }
+        if tags.is_empty() || self.is_ignored(path) {
+            return;
+        }
+
+        let tags: HashSet<String> = tags.iter().map(|tag| tag.to_lowercase()).collect();
+        for tag in &tags {
+            self.tag_to_paths
+                .entry(tag.clone())
+                .or_default()
+                .insert(path.to_string());
+        }
+        self.tags.insert(path.to_string(), tags);
+    }
+
+    /// Get a note's inline tags by path, lowercased.
+    pub fn get_tags(&self, path: &str) -> Option<&HashSet<String>> {
+        self.tags.get(path)
+    }
+
+    /// Get the paths using a tag, matched case-insensitively.
+    ///
+    /// Only exact tags match: `project` doesn't include notes tagged `project/active`.
+    pub fn get_paths_for_tag(&self, tag: &str) -> Option<&HashSet<String>> {
+        self.tag_to_paths.get(&tag.to_lowercase())
+    }
+
+    /// Get the other notes sharing at least one inline tag with a note.
+    ///
+    /// A weaker signal than links, so it's kept out of `get_neighborhood`.
+    pub fn tag_neighbors(&self, path: &str) -> HashSet<String> {
+        self.get_tags(path)
+            .into_iter()
+            .flatten()
+            .filter_map(|tag| self.get_paths_for_tag(tag))
+            .flatten()
+            .filter(|other| *other != path)
+            .cloned()
+            .collect()
+    }
+
+    /// Whether a note has opted out of embedding and search.
+    ///
+    /// A note is excluded when its frontmatter sets `search: false` or `noindex: true`.
+    pub fn is_excluded_from_search(&self, path: &str) -> bool {
+        let Some(frontmatter) = self.get_frontmatter(path) else {
+            return false;
+        };
+
+        let search = frontmatter.get("search").and_then(|v| v.as_bool());
+        let noindex = frontmatter.get("noindex").and_then(|v| v.as_bool());
+
+        search == Some(false) || noindex == Some(true)
+    }
+
+    /// Set the folders whose notes are private (`private/` by default).
+    pub fn set_private_roots(&mut self, private_roots: PrivateRoots) {
+        self.private_roots = private_roots;
+    }
+
+    /// Folders whose notes are private.
+    pub fn private_roots(&self) -> &PrivateRoots {
+        &self.private_roots
+    }
+
+    /// Set the paths left out of the index (`.obsidian/` and `.sync/` by default).
+    ///
+    /// Takes effect for notes indexed afterwards, so call it before `initialize`.
+    pub fn set_ignore(&mut self, ignore: GraphIgnore) {
+        self.ignore = ignore;
+    }
+
+    /// Whether `path` (relative to the vault) is left out of the index.
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.ignore.is_ignored(path)
+    }
+
+    /// Whether a note needs consent to read or search.
+    ///
+    /// A note is private when it's under a private root or its frontmatter sets
+    /// `visibility: private`, wherever it lives.
+    pub fn is_private(&self, path: &str) -> bool {
+        self.private_roots.contains(path)
+            || self
+                .get_frontmatter(path)
+                .and_then(|fm| fm.get("visibility"))
+                .and_then(|v| v.as_str())
+                .is_some_and(|visibility| visibility.eq_ignore_ascii_case("private"))
     }
 
     /// Get forward links for a note by path (notes this note links TO).
@@ -252,6 +534,259 @@ impl GraphIndex {
             })
             .collect()
     }
+
+    /// Check whether a note links to itself (e.g. `[[This Note]]` inside `This Note.md`).
+    pub fn has_self_link(&self, path: &str) -> bool {
+        let Some(note_name) = Path::new(path).file_stem().and_then(|s| s.to_str()) else {
+            return false;
+        };
+        self.get_forward_links(path)
+            .is_some_and(|links| links.contains(note_name))
+    }
+
+    /// Resolve a note's forward links to the paths they point at.
+    fn forward_paths(&self, path: &str) -> HashSet<String> {
+        self.get_forward_links(path)
+            .into_iter()
+            .flatten()
+            .filter_map(|name| self.get_paths_for_name(name))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+
+    /// Find link cycles in the graph (diagnostic).
+    ///
+    /// Returns each strongly connected group of notes that link around back to
+    /// themselves, following forward links only. A self-linking note is reported
+    /// as a cycle of one. Paths within a cycle and the cycles themselves are sorted.
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        let mut tarjan = Tarjan::default();
+        let mut paths: Vec<&String> = self.all_paths().collect();
+        paths.sort();
+
+        for path in paths {
+            if !tarjan.index.contains_key(path.as_str()) {
+                tarjan.visit(self, path);
+            }
+        }
+
+        let mut cycles: Vec<Vec<String>> = tarjan
+            .components
+            .into_iter()
+            .filter(|component| component.len() > 1 || self.has_self_link(&component[0]))
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// Breadth-first search for all paths within `radius` hops of `path`.
+    ///
+    /// Edges are undirected (forward links and backlinks both count). The
+    /// source path is included at distance 0.
+    pub fn neighborhood_within(&self, path: &str, radius: usize) -> Neighborhood {
+        let mut distances = Neighborhood::new();
+        distances.insert(path.to_string(), 0);
+
+        let mut queue = VecDeque::from([path.to_string()]);
+        while let Some(current) = queue.pop_front() {
+            let distance = distances[&current];
+            if distance >= radius {
+                continue;
+            }
+            for neighbor in self.get_neighborhood(&current) {
+                if !distances.contains_key(&neighbor) {
+                    distances.insert(neighbor.clone(), distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Get the cached `NEIGHBORHOOD_RADIUS` neighborhood for a path.
+    ///
+    /// Computes and caches the neighborhood on a miss, so repeated lookups on a
+    /// stable graph don't redo the BFS.
+    pub fn cached_neighborhood(&self, path: &str) -> Arc<Neighborhood> {
+        if let Some(cached) = lock(&self.neighborhood_cache).get(path) {
+            return cached.clone();
+        }
+
+        let neighborhood = Arc::new(self.neighborhood_within(path, NEIGHBORHOOD_RADIUS));
+        lock(&self.neighborhood_cache).insert(path.to_string(), neighborhood.clone());
+        neighborhood
+    }
+
+    /// Get cached Personalized PageRank scores from a seed note name.
+    ///
+    /// Only notes in the cached neighborhood of a path with the seed's name get
+    /// a score; anything further out is left out. Entries are invalidated with
+    /// those neighborhoods, so an edge change far from the seed (which only
+    /// shifts its scores slightly) doesn't force a recompute.
+    pub fn cached_seed_scores(&self, seed: &str) -> Arc<SeedScores> {
+        if let Some(cached) = lock(&self.seed_score_cache).get(seed) {
+            return cached.clone();
+        }
+
+        let reachable: HashSet<String> = self
+            .get_paths_for_name(seed)
+            .into_iter()
+            .flatten()
+            .flat_map(|path| {
+                self.cached_neighborhood(path)
+                    .keys()
+                    .filter_map(|p| note_name(p))
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut scores = personalized_pagerank(self, seed);
+        scores.retain(|name, _| reachable.contains(name));
+        let scores = Arc::new(scores);
+        lock(&self.seed_score_cache).insert(seed.to_string(), scores.clone());
+        scores
+    }
+
+    /// Precompute cached neighborhoods for every note in the index.
+    ///
+    /// Cached seed scores are dropped and recomputed on the next lookup.
+    pub fn precompute_neighborhoods(&self) {
+        let neighborhoods: HashMap<String, Arc<Neighborhood>> = self
+            .all_paths()
+            .map(|path| {
+                let neighborhood = self.neighborhood_within(path, NEIGHBORHOOD_RADIUS);
+                (path.clone(), Arc::new(neighborhood))
+            })
+            .collect();
+
+        tracing::debug!(
+            notes = neighborhoods.len(),
+            radius = NEIGHBORHOOD_RADIUS,
+            "Precomputed graph neighborhoods"
+        );
+
+        *lock(&self.neighborhood_cache) = neighborhoods;
+        lock(&self.seed_score_cache).clear();
+    }
+
+    /// Drop cached neighborhoods that could be affected by an edge change.
+    ///
+    /// `touched` holds the endpoints of every added or removed edge. A cached
+    /// neighborhood can only gain or lose members through an edge it reaches,
+    /// so any entry that doesn't contain a touched path is still valid.
+    ///
+    /// Seed scores cover the same neighborhoods, so they're dropped whenever a
+    /// touched note has the seed's name or a score.
+    fn invalidate_neighborhoods(&self, touched: &HashSet<String>) {
+        lock(&self.neighborhood_cache).retain(|source, neighborhood| {
+            !touched.contains(source) && !touched.iter().any(|p| neighborhood.contains_key(p))
+        });
+
+        let names: HashSet<&str> = touched.iter().filter_map(|p| note_name(p)).collect();
+        lock(&self.seed_score_cache).retain(|seed, scores| {
+            !names.contains(seed.as_str()) && !names.iter().any(|name| scores.contains_key(*name))
+        });
+    }
+}
+
+/// Note name (file stem) of a relative path.
+fn note_name(path: &str) -> Option<&str> {
+    Path::new(path).file_stem().and_then(|s| s.to_str())
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // The caches hold no invariants across a panic, so recover from poisoning
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Tarjan's strongly connected components over forward links.
+#[derive(Default)]
+struct Tarjan {
+    next_index: usize,
+    index: HashMap<String, usize>,
+    low_link: HashMap<String, usize>,
+    stack: Vec<String>,
+    on_stack: HashSet<String>,
+    components: Vec<Vec<String>>,
+}
+
+impl Tarjan {
+    /// Visit every note reachable from `root`, collecting finished components.
+    ///
+    /// Uses an explicit call stack rather than recursion, so long link chains
+    /// can't overflow the thread's stack.
+    fn visit(&mut self, graph: &GraphIndex, root: &str) {
+        let mut frames = vec![self.enter(graph, root)];
+
+        while let Some(frame) = frames.last_mut() {
+            if let Some(target) = frame.targets.pop() {
+                let path = frame.path.clone();
+                if !self.index.contains_key(&target) {
+                    frames.push(self.enter(graph, &target));
+                } else if self.on_stack.contains(&target) {
+                    let low = self.low_link[&path].min(self.index[&target]);
+                    self.low_link.insert(path, low);
+                }
+                continue;
+            }
+
+            let Some(frame) = frames.pop() else { break };
+            self.finish(&frame.path);
+            if let Some(parent) = frames.last() {
+                let low = self.low_link[&parent.path].min(self.low_link[&frame.path]);
+                self.low_link.insert(parent.path.clone(), low);
+            }
+        }
+    }
+
+    /// Assign `path` its index and push it onto the component stack.
+    fn enter(&mut self, graph: &GraphIndex, path: &str) -> TarjanFrame {
+        self.index.insert(path.to_string(), self.next_index);
+        self.low_link.insert(path.to_string(), self.next_index);
+        self.next_index += 1;
+        self.stack.push(path.to_string());
+        self.on_stack.insert(path.to_string());
+
+        // Reverse sorted so popping walks targets in order
+        let mut targets: Vec<String> = graph.forward_paths(path).into_iter().collect();
+        targets.sort_by(|a, b| b.cmp(a));
+
+        TarjanFrame {
+            path: path.to_string(),
+            targets,
+        }
+    }
+
+    /// Pop the component rooted at `path`, if it is one.
+    fn finish(&mut self, path: &str) {
+        if self.low_link[path] != self.index[path] {
+            return;
+        }
+
+        let mut component = Vec::new();
+        while let Some(member) = self.stack.pop() {
+            self.on_stack.remove(&member);
+            let done = member == path;
+            component.push(member);
+            if done {
+                break;
+            }
+        }
+        self.components.push(component);
+    }
+}
+
+/// A note being visited, with the link targets still left to walk.
+struct TarjanFrame {
+    path: String,
+    targets: Vec<String>,
 }
 
 #[cfg(test)]
@@ -414,4 +949,341 @@ mod tests {
         let path = index.get_path("Index").unwrap();
         assert!(path.to_string_lossy().ends_with("Index.md"));
     }
+
+    fn links(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// A -> B -> C -> D chain
+    fn chain_index() -> GraphIndex {
+        let mut index = GraphIndex::new();
+        index.update_note("A", PathBuf::from("A.md"), links(&["B"]));
+        index.update_note("B", PathBuf::from("B.md"), links(&["C"]));
+        index.update_note("C", PathBuf::from("C.md"), links(&["D"]));
+        index.update_note("D", PathBuf::from("D.md"), HashSet::new());
+        index
+    }
+
+    #[test]
+    fn test_neighborhood_within_radius() {
+        let index = chain_index();
+
+        let neighborhood = index.neighborhood_within("A.md", 2);
+        assert_eq!(neighborhood.get("A.md"), Some(&0));
+        assert_eq!(neighborhood.get("B.md"), Some(&1));
+        assert_eq!(neighborhood.get("C.md"), Some(&2));
+        assert!(!neighborhood.contains_key("D.md"));
+    }
+
+    #[test]
+    fn test_cached_neighborhood_matches_fresh_bfs() {
+        let index = chain_index();
+        index.precompute_neighborhoods();
+
+        for path in ["A.md", "B.md", "C.md", "D.md"] {
+            let cached = index.cached_neighborhood(path);
+            assert_eq!(*cached, index.neighborhood_within(path, NEIGHBORHOOD_RADIUS));
+        }
+    }
+
+    #[test]
+    fn test_cached_neighborhood_updates_after_edge_added() {
+        let mut index = chain_index();
+        index.update_note("E", PathBuf::from("E.md"), HashSet::new());
+        index.precompute_neighborhoods();
+        assert!(!index.cached_neighborhood("A.md").contains_key("E.md"));
+
+        // B now also links to E, which puts E two hops from A
+        index.update_note("B", PathBuf::from("B.md"), links(&["C", "E"]));
+
+        let cached = index.cached_neighborhood("A.md");
+        assert_eq!(cached.get("E.md"), Some(&2));
+        assert_eq!(*cached, index.neighborhood_within("A.md", NEIGHBORHOOD_RADIUS));
+    }
+
+    #[test]
+    fn test_cached_neighborhood_updates_after_edge_removed() {
+        let mut index = chain_index();
+        index.precompute_neighborhoods();
+        assert!(index.cached_neighborhood("A.md").contains_key("C.md"));
+
+        // B stops linking to C, cutting A off from C and D
+        index.update_note("B", PathBuf::from("B.md"), HashSet::new());
+
+        let cached = index.cached_neighborhood("A.md");
+        assert!(!cached.contains_key("C.md"));
+        assert_eq!(*cached, index.neighborhood_within("A.md", NEIGHBORHOOD_RADIUS));
+
+        let cached_d = index.cached_neighborhood("D.md");
+        assert_eq!(*cached_d, index.neighborhood_within("D.md", NEIGHBORHOOD_RADIUS));
+    }
+
+    #[test]
+    fn test_cached_neighborhood_updates_after_note_removed() {
+        let mut index = chain_index();
+        index.precompute_neighborhoods();
+
+        index.remove_note("B", Path::new("B.md"));
+
+        let cached = index.cached_neighborhood("A.md");
+        assert!(!cached.contains_key("B.md"));
+        assert_eq!(*cached, index.neighborhood_within("A.md", NEIGHBORHOOD_RADIUS));
+    }
+
+    #[test]
+    fn test_cached_seed_scores_stop_at_neighborhood() {
+        let index = chain_index();
+
+        let scores = index.cached_seed_scores("A");
+        let mut names: Vec<&str> = scores.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["A", "B", "C"]);
+
+        let full = personalized_pagerank(&index, "A");
+        assert!(full["D"] > 0.0);
+        for (name, score) in scores.iter() {
+            assert_eq!(*score, full[name]);
+        }
+    }
+
+    #[test]
+    fn test_cached_seed_scores_invalidated_with_neighborhood() {
+        let mut index = chain_index();
+        index.precompute_neighborhoods();
+        let before = index.cached_seed_scores("A");
+
+        // An edge outside A's neighborhood keeps both caches
+        index.update_note("X", PathBuf::from("X.md"), links(&["Y"]));
+        assert!(Arc::ptr_eq(&before, &index.cached_seed_scores("A")));
+
+        // An edge inside it drops both
+        index.update_note("C", PathBuf::from("C.md"), links(&["D", "E"]));
+        index.update_note("E", PathBuf::from("E.md"), HashSet::new());
+        let after = index.cached_seed_scores("A");
+        assert!(!Arc::ptr_eq(&before, &after));
+        assert_ne!(after["C"], before["C"]);
+        assert_eq!(*after, {
+            let mut full = personalized_pagerank(&index, "A");
+            full.retain(|name, _| ["A", "B", "C"].contains(&name.as_str()));
+            full
+        });
+    }
+
+    #[test]
+    fn test_cached_seed_scores_pick_up_new_seed_note() {
+        let mut index = chain_index();
+        assert!(index.cached_seed_scores("New").is_empty());
+
+        index.update_note("New", PathBuf::from("New.md"), links(&["A"]));
+        let scores = index.cached_seed_scores("New");
+        assert!(scores.contains_key("New"));
+        assert!(scores.contains_key("B"));
+    }
+
+    #[test]
+    fn test_has_self_link() {
+        let mut index = GraphIndex::new();
+        index.update_note("Loop", PathBuf::from("Loop.md"), links(&["Loop", "Other"]));
+        index.update_note("Other", PathBuf::from("Other.md"), links(&["Loop"]));
+
+        assert!(index.has_self_link("Loop.md"));
+        assert!(!index.has_self_link("Other.md"));
+        assert!(!index.has_self_link("Missing.md"));
+    }
+
+    #[test]
+    fn test_find_cycles_reports_self_link() {
+        let mut index = GraphIndex::new();
+        index.update_note("Loop", PathBuf::from("Loop.md"), links(&["Loop"]));
+        index.update_note("Plain", PathBuf::from("Plain.md"), HashSet::new());
+
+        assert_eq!(index.find_cycles(), vec![vec!["Loop.md".to_string()]]);
+    }
+
+    #[test]
+    fn test_find_cycles_reports_three_note_cycle() {
+        let mut index = GraphIndex::new();
+        index.update_note("A", PathBuf::from("A.md"), links(&["B"]));
+        index.update_note("B", PathBuf::from("B.md"), links(&["C"]));
+        index.update_note("C", PathBuf::from("C.md"), links(&["A"]));
+        index.update_note("D", PathBuf::from("D.md"), links(&["A"]));
+
+        assert_eq!(
+            index.find_cycles(),
+            vec![vec!["A.md".to_string(), "B.md".to_string(), "C.md".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_find_cycles_handles_long_chains() {
+        // Deep enough to overflow the stack if the walk recursed per note
+        let mut index = GraphIndex::new();
+        let count = 100_000;
+        for i in 0..count {
+            let next = format!("N{}", (i + 1) % count);
+            index.update_note(
+                &format!("N{}", i),
+                PathBuf::from(format!("N{}.md", i)),
+                links(&[next.as_str()]),
+            );
+        }
+
+        let cycles = index.find_cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), count);
+    }
+
+    #[test]
+    fn test_acyclic_graph_has_no_cycles() {
+        let index = chain_index();
+        assert!(index.find_cycles().is_empty());
+    }
+
+    #[test]
+    fn test_traversals_terminate_on_cycles() {
+        let mut index = GraphIndex::new();
+        index.update_note("A", PathBuf::from("A.md"), links(&["A", "B"]));
+        index.update_note("B", PathBuf::from("B.md"), links(&["C"]));
+        index.update_note("C", PathBuf::from("C.md"), links(&["A"]));
+
+        let neighborhood = index.neighborhood_within("A.md", 10);
+        assert_eq!(neighborhood.len(), 3);
+        assert_eq!(neighborhood.get("A.md"), Some(&0));
+
+        index.precompute_neighborhoods();
+        assert_eq!(index.cached_neighborhood("B.md").len(), 3);
+
+        let scores = crate::graph::pagerank::personalized_pagerank(&index, "A");
+        assert_eq!(scores.len(), 3);
+    }
+
+    fn frontmatter(yaml: &str) -> Option<Frontmatter> {
+        obsidian_fs::parse_frontmatter(&format!("---\n{}\n---\nBody", yaml)).frontmatter
+    }
+
+    #[test]
+    fn test_search_exclusion_flags() {
+        let mut index = GraphIndex::new();
+        index.set_frontmatter("hidden.md", frontmatter("search: false"));
+        index.set_frontmatter("noindex.md", frontmatter("noindex: true"));
+        index.set_frontmatter("visible.md", frontmatter("search: true\nnoindex: false"));
+        index.set_frontmatter("plain.md", None);
+
+        assert!(index.is_excluded_from_search("hidden.md"));
+        assert!(index.is_excluded_from_search("noindex.md"));
+        assert!(!index.is_excluded_from_search("visible.md"));
+        assert!(!index.is_excluded_from_search("plain.md"));
+        assert!(!index.is_excluded_from_search("missing.md"));
+    }
+
+    #[test]
+    fn test_remove_note_clears_frontmatter() {
+        let mut index = GraphIndex::new();
+        index.update_note("hidden", PathBuf::from("hidden.md"), HashSet::new());
+        index.set_frontmatter("hidden.md", frontmatter("search: false"));
+
+        index.remove_note("hidden", Path::new("hidden.md"));
+
+        assert!(index.get_frontmatter("hidden.md").is_none());
+        assert!(!index.is_excluded_from_search("hidden.md"));
+    }
+
+    #[test]
+    fn test_shared_tags_connect_notes() {
+        let mut index = GraphIndex::new();
+        index.update_note("A", PathBuf::from("A.md"), HashSet::new());
+        index.update_note("B", PathBuf::from("B.md"), HashSet::new());
+        index.update_note("C", PathBuf::from("C.md"), HashSet::new());
+        index.set_tags("A.md", links(&["project/active", "rust"]));
+        index.set_tags("B.md", links(&["Project/Active"]));
+        index.set_tags("C.md", links(&["project"]));
+
+        assert_eq!(index.tag_neighbors("A.md"), HashSet::from(["B.md".to_string()]));
+        assert_eq!(index.get_paths_for_tag("PROJECT/active").unwrap().len(), 2);
+        // Tags are a separate signal from links
+        assert!(index.get_neighborhood("A.md").is_empty());
+
+        // Retagging and removing notes drop stale entries
+        index.set_tags("B.md", links(&["rust"]));
+        assert_eq!(index.get_paths_for_tag("project/active").unwrap().len(), 1);
+        index.remove_note("A", Path::new("A.md"));
+        assert!(index.get_tags("A.md").is_none());
+        assert!(index.get_paths_for_tag("project/active").is_none());
+        assert!(index.tag_neighbors("B.md").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_indexes_inline_tags() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("A.md"), "# Plan\n#project/active word#not").unwrap();
+        std::fs::write(temp.path().join("B.md"), "Also #project/active `#code`").unwrap();
+
+        let mut index = GraphIndex::new();
+        index.initialize(temp.path()).await.unwrap();
+        assert_eq!(
+            index.get_tags("A.md"),
+            Some(&HashSet::from(["project/active".to_string()]))
+        );
+        assert_eq!(index.tag_neighbors("A.md"), HashSet::from(["B.md".to_string()]));
+
+        // Restored from the snapshot on the next start
+        let mut restored = GraphIndex::new();
+        restored.initialize(temp.path()).await.unwrap();
+        assert_eq!(restored.get_tags("B.md"), index.get_tags("B.md"));
+    }
+
+    #[tokio::test]
+    async fn test_ignored_notes_are_left_out() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("templates")).unwrap();
+        std::fs::write(temp.path().join("templates/Daily.md"), "See [[Index]]").unwrap();
+        std::fs::write(temp.path().join("Index.md"), "See [[Daily]]").unwrap();
+
+        let mut index = GraphIndex::new();
+        index.set_ignore(GraphIgnore::new(["templates/"]));
+        index.initialize(temp.path()).await.unwrap();
+
+        assert_eq!(index.all_paths().collect::<Vec<_>>(), vec!["Index.md"]);
+        assert!(index.get_paths_for_name("Daily").is_none());
+        assert!(index.get_backlinks("Index").is_none_or(|paths| paths.is_empty()));
+
+        // Updates for an ignored path drop it rather than indexing it
+        index.update_note("Daily", PathBuf::from("templates/Daily.md"), HashSet::new());
+        index.set_frontmatter("templates/Daily.md", frontmatter("search: false"));
+        index.update_note("Plugin", PathBuf::from(".obsidian/Plugin.md"), HashSet::new());
+        assert_eq!(index.len(), 1);
+        assert!(index.get_frontmatter("templates/Daily.md").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_reparses_only_changed_notes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let unchanged = temp.path().join("A.md");
+        let changed = temp.path().join("B.md");
+        std::fs::write(&unchanged, "[[One]]").unwrap();
+        std::fs::write(&changed, "[[Two]]").unwrap();
+        GraphIndex::new().initialize(temp.path()).await.unwrap();
+        assert!(crate::graph::snapshot::snapshot_path(temp.path()).exists());
+
+        // Same size and mtime, so the snapshot is trusted and the edit goes unseen
+        let mtime = std::fs::metadata(&unchanged).unwrap().modified().unwrap();
+        std::fs::write(&unchanged, "[[Uno]]").unwrap();
+        let file = std::fs::File::options().write(true).open(&unchanged).unwrap();
+        file.set_modified(mtime).unwrap();
+
+        std::fs::write(&changed, "[[Three]]").unwrap();
+        let file = std::fs::File::options().write(true).open(&changed).unwrap();
+        file.set_modified(mtime + std::time::Duration::from_secs(60)).unwrap();
+
+        let mut index = GraphIndex::new();
+        index.initialize(temp.path()).await.unwrap();
+        assert_eq!(
+            index.get_forward_links("A.md").unwrap(),
+            &HashSet::from(["One".to_string()])
+        );
+        assert_eq!(
+            index.get_forward_links("B.md").unwrap(),
+            &HashSet::from(["Three".to_string()])
+        );
+    }
 }
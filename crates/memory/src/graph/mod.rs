@@ -1,4 +1,7 @@
+mod ignore;
 mod index;
 pub mod pagerank;
+mod snapshot;
 
-pub use index::GraphIndex;
+pub use ignore::GraphIgnore;
+pub use index::{GraphIndex, SeedScores};
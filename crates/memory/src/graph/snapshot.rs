@@ -0,0 +1,174 @@
+//! On-disk snapshot of the graph index, so restarts only re-parse changed notes.
+//!
+//! The snapshot records each note's links, tags, and frontmatter with the file's
+//! modification time and size when it was parsed. On startup, a note whose
+//! file still has that stamp is restored from the snapshot instead of being
+//! read. Snapshots written by another format or server version are discarded,
+//! since link parsing may have changed between them.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use obsidian_fs::Frontmatter;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+/// Version of the snapshot layout; bump when what's stored per note, or how
+/// notes are parsed into it, changes.
+pub const SNAPSHOT_FORMAT: u32 = 3;
+
+/// Where the snapshot lives, next to the embedding cache.
+pub fn snapshot_path(vault_path: &Path) -> PathBuf {
+    vault_path.join(".obsidian/graph-index.json")
+}
+
+/// Modification time and size of a note's file when it was parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    mtime_ns: u64,
+    size: u64,
+}
+
+impl FileStamp {
+    /// Stamp for a file, or None if the platform doesn't report mtimes.
+    pub fn from_metadata(metadata: &Metadata) -> Option<Self> {
+        let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            mtime_ns: mtime.as_nanos() as u64,
+            size: metadata.len(),
+        })
+    }
+}
+
+/// A note as it was last indexed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotNote {
+    pub stamp: FileStamp,
+    pub links: HashSet<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub tags: HashSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frontmatter: Option<Frontmatter>,
+}
+
+/// Indexed notes keyed by relative path.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphSnapshot {
+    format: u32,
+    server_version: String,
+    pub notes: HashMap<String, SnapshotNote>,
+}
+
+impl Default for GraphSnapshot {
+    fn default() -> Self {
+        Self {
+            format: SNAPSHOT_FORMAT,
+            server_version: env!("CARGO_PKG_VERSION").to_string(),
+            notes: HashMap::new(),
+        }
+    }
+}
+
+impl GraphSnapshot {
+    /// Load the vault's snapshot, or None if it's missing, unreadable, or stale.
+    pub async fn load(vault_path: &Path) -> Option<Self> {
+        let json = fs::read_to_string(snapshot_path(vault_path)).await.ok()?;
+        let snapshot: Self = match serde_json::from_str(&json) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable graph snapshot: {}", e);
+                return None;
+            }
+        };
+
+        let current = Self::default();
+        if snapshot.format != current.format || snapshot.server_version != current.server_version {
+            tracing::info!(
+                "Graph snapshot is from version {} (format {}), rescanning vault",
+                snapshot.server_version,
+                snapshot.format
+            );
+            return None;
+        }
+        Some(snapshot)
+    }
+
+    /// Write the snapshot, replacing any existing one.
+    ///
+    /// Written to a temp file and renamed into place so a crash can't leave a
+    /// half-written snapshot behind.
+    pub async fn save(&self, vault_path: &Path) -> Result<(), std::io::Error> {
+        let path = snapshot_path(vault_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let json = serde_json::to_string(self)?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, json).await?;
+        fs::rename(&temp_path, &path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_snapshot_round_trips() {
+        let temp = TempDir::new().unwrap();
+        let mut snapshot = GraphSnapshot::default();
+        snapshot.notes.insert(
+            "Note.md".to_string(),
+            SnapshotNote {
+                stamp: FileStamp {
+                    mtime_ns: 1,
+                    size: 2,
+                },
+                links: HashSet::from(["Other".to_string()]),
+                tags: HashSet::from(["project/active".to_string()]),
+                frontmatter: None,
+            },
+        );
+        snapshot.save(temp.path()).await.unwrap();
+
+        let loaded = GraphSnapshot::load(temp.path()).await.unwrap();
+        assert_eq!(
+            loaded.notes["Note.md"].links,
+            snapshot.notes["Note.md"].links
+        );
+        assert_eq!(loaded.notes["Note.md"].tags, snapshot.notes["Note.md"].tags);
+        assert_eq!(
+            loaded.notes["Note.md"].stamp,
+            snapshot.notes["Note.md"].stamp
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stale_or_corrupt_snapshot_is_discarded() {
+        let temp = TempDir::new().unwrap();
+        assert!(GraphSnapshot::load(temp.path()).await.is_none());
+
+        let snapshot = GraphSnapshot {
+            format: SNAPSHOT_FORMAT + 1,
+            ..GraphSnapshot::default()
+        };
+        snapshot.save(temp.path()).await.unwrap();
+        assert!(GraphSnapshot::load(temp.path()).await.is_none());
+
+        let snapshot = GraphSnapshot {
+            server_version: "0.0.0-old".to_string(),
+            ..GraphSnapshot::default()
+        };
+        snapshot.save(temp.path()).await.unwrap();
+        assert!(GraphSnapshot::load(temp.path()).await.is_none());
+
+        fs::write(snapshot_path(temp.path()), "not json")
+            .await
+            .unwrap();
+        assert!(GraphSnapshot::load(temp.path()).await.is_none());
+    }
+}
@@ -1,9 +1,14 @@
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    handler::server::{
+        router::tool::ToolRouter,
+        tool::ToolCallContext,
+        wrapper::Parameters,
+    },
     model::*,
-    tool, tool_handler, tool_router,
+    service::RequestContext,
+    tool, tool_router,
     transport::stdio,
-    ServiceExt,
+    RoleServer, ServiceExt,
 };
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -18,16 +23,23 @@ use rmcp::transport::streamable_http_server::{
 
 mod config;
 mod embeddings;
+mod frontmatter_schema;
 mod graph;
+mod metrics;
 mod projects;
+mod self_test;
 mod storage;
+#[cfg(all(test, any(feature = "download-model", feature = "remote-storage")))]
+mod test_server;
+mod timeout;
 mod tools;
 mod watcher;
 
 use config::Config;
 use embeddings::EmbeddingManager;
 use graph::GraphIndex;
-use storage::FileStorage;
+use metrics::ToolMetrics;
+use storage::{Backend, JournaledStorage};
 use watcher::VaultWatcher;
 
 /// Parameters for the Log tool
@@ -45,6 +57,36 @@ pub struct GetNoteInfoParams {
     pub note: String,
 }
 
+/// Parameters for the GetNoteHistory tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetNoteHistoryParams {
+    /// Note reference - supports: "memory:Note Name", "memory:knowledge/Note Name", "knowledge/Note Name", "[[Note Name]]"
+    pub note: String,
+    /// Version from the note's history; returns the note's content as of that change
+    pub version: Option<String>,
+}
+
+/// Parameters for the GetGraph tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetGraphParams {
+    /// Only export notes in this folder (e.g. "knowledge"), including subfolders
+    pub folder: Option<String>,
+    /// Whether to include private notes. Requires explicit user consent.
+    #[serde(default)]
+    pub include_private: bool,
+    /// Maximum number of notes per page (default: 500)
+    pub max_nodes: Option<usize>,
+    /// Cursor from a previous page's next_cursor, to fetch the following page
+    pub cursor: Option<String>,
+}
+
+/// Parameters for the Remember tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RememberParams {
+    /// Cursor from a previous call's next_cursor, to load the remaining context files
+    pub cursor: Option<String>,
+}
+
 /// Parameters for the UpdateFrontmatter tool
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct UpdateFrontmatterParams {
@@ -69,6 +111,43 @@ pub struct SearchParams {
     pub debug: bool,
 }
 
+/// Parameters for the SimilarToNote tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SimilarToNoteParams {
+    /// Note reference - supports wiki-links ([[Note]]), memory URIs (memory:knowledge/Note), or plain names
+    pub note: String,
+    /// Whether to include private notes in results. Requires explicit user consent.
+    #[serde(default)]
+    pub include_private: bool,
+}
+
+/// Parameters for the FindDuplicates tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FindDuplicatesParams {
+    /// Minimum similarity (0-1) for two notes to count as duplicates (default: 0.95)
+    pub threshold: Option<f32>,
+    /// Only report notes with identical content, skipping the embedding comparison
+    #[serde(default)]
+    pub identical_only: bool,
+    /// Whether to include private notes. Requires explicit user consent.
+    #[serde(default)]
+    pub include_private: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Parameters for the DebugEmbed tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DebugEmbedParams {
+    /// Text to encode
+    pub text: String,
+    /// Apply the query prefix, as Search does for queries (default: true). Set false to encode as note content.
+    #[serde(default = "default_true")]
+    pub as_query: bool,
+}
+
 /// Parameters for the WriteLogs tool
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct WriteLogsParams {
@@ -79,12 +158,23 @@ pub struct WriteLogsParams {
     pub entries: std::collections::HashMap<String, String>,
 }
 
+/// Parameters for the CompactLog tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CompactLogParams {
+    /// Archive days older than this many days (default: 14)
+    #[serde(default, rename = "olderThanDays")]
+    pub older_than_days: Option<u32>,
+}
+
 /// Parameters for the Reflect tool
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct ReflectParams {
     /// Include private notes in reflection (default: false)
     #[serde(default, rename = "includePrivate")]
     pub include_private: bool,
+    /// Days after which log days and labile notes are listed as consolidation candidates (default: 14)
+    #[serde(default, rename = "staleAfterDays")]
+    pub stale_after_days: Option<u32>,
 }
 
 /// Parameters for the LoadPrivateMemory tool
@@ -99,6 +189,30 @@ pub struct LoadPrivateMemoryParams {
 pub struct ReadNoteParams {
     /// Note reference - supports wiki-links ([[Note]]), memory URIs (memory:knowledge/Note), or plain names
     pub note: String,
+    /// Content format: raw "markdown" (default) or rendered "html" with wiki-links resolved to memory: URIs
+    #[serde(default)]
+    pub format: tools::read_note::NoteFormat,
+}
+
+/// Parameters for the RenderNote tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RenderNoteParams {
+    /// Note reference - supports wiki-links ([[Note]]), memory URIs (memory:knowledge/Note), or plain names
+    pub note: String,
+}
+
+/// Parameters for the NoteExists tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NoteExistsParams {
+    /// Note reference - supports wiki-links ([[Note]]), memory URIs (memory:knowledge/Note), or plain names
+    pub note: String,
+}
+
+/// Parameters for the ResolveLink tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ResolveLinkParams {
+    /// Wiki-link or note reference to resolve - supports [[Note]], [[folder/Note#Header|alias]], memory URIs, or plain names
+    pub link: String,
 }
 
 /// Parameters for the WriteNote tool
@@ -137,11 +251,42 @@ pub struct EditNoteParams {
     pub dry_run: bool,
 }
 
+/// Parameters for the GenerateToc tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GenerateTocParams {
+    /// Note reference - supports wiki-links ([[Note]]), memory URIs (memory:knowledge/Note), or plain names
+    pub note: String,
+    /// Write the TOC into the note between <!-- toc --> and <!-- /toc --> markers (default: false). The note must contain <!-- toc -->.
+    #[serde(default)]
+    pub insert: bool,
+    /// Content hash from ReadNote - required when insert is true
+    pub content_hash: Option<String>,
+}
+
+/// Parameters for the InsertUnderHeading tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct InsertUnderHeadingParams {
+    /// Note reference - supports wiki-links ([[Note]]), memory URIs (memory:knowledge/Note), or plain names
+    pub note: String,
+    /// Heading to insert under, e.g. "Tasks" (any level) or "## Tasks" (that level only). Created at the end of the note if missing.
+    pub heading: String,
+    /// Content to insert (e.g. a bullet point)
+    pub content: String,
+    /// Insert at the top or bottom of the section (default: bottom)
+    #[serde(default)]
+    pub position: tools::insert_under_heading::InsertPosition,
+    /// Content hash from ReadNote - required to verify note hasn't changed
+    pub content_hash: String,
+}
+
 /// Parameters for the DeleteNote tool
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct DeleteNoteParams {
     /// Note reference - supports wiki-links ([[Note]]), memory URIs (memory:knowledge/Note), or plain names
     pub note: String,
+    /// Delete even if other notes link to it, leaving those links dangling (default: false)
+    #[serde(default)]
+    pub force: bool,
 }
 
 /// Parameters for the MoveNote tool
@@ -151,6 +296,20 @@ pub struct MoveNoteParams {
     pub from: String,
     /// Destination note reference
     pub to: String,
+    /// Preview the move and the link rewrites without applying them (default: false)
+    #[serde(default, rename = "dryRun")]
+    pub dry_run: bool,
+    /// Leave a stub note at the old path linking to the new one, for references from outside the vault (default: false)
+    #[serde(default, rename = "leaveRedirect")]
+    pub leave_redirect: bool,
+}
+
+/// Parameters for the RebuildIndex tool
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct RebuildIndexParams {
+    /// Also rebuild all note embeddings once the graph is rebuilt (default: false)
+    #[serde(default, rename = "reindexEmbeddings")]
+    pub reindex_embeddings: bool,
 }
 
 /// Shared state that can be reused across multiple HTTP sessions.
@@ -160,7 +319,11 @@ pub struct SharedState {
     config: Arc<Config>,
     graph: Arc<RwLock<GraphIndex>>,
     embeddings: Arc<EmbeddingManager>,
-    storage: Arc<FileStorage>,
+    storage: Arc<Backend>,
+    /// Per-tool call counts and latencies, shared across sessions
+    metrics: Arc<ToolMetrics>,
+    /// Progress of RebuildIndex runs, shared so sessions don't start duplicates
+    graph_rebuild: Arc<tools::rebuild_index::RebuildTracker>,
     /// File watcher handle - kept alive for the lifetime of the shared state.
     #[allow(dead_code)]
     watcher: Option<Arc<VaultWatcher>>,
@@ -171,12 +334,15 @@ impl SharedState {
     pub async fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
         // Initialize graph index by scanning the vault
         let mut graph = GraphIndex::new();
+        graph.set_private_roots(config.private_roots.clone());
+        graph.set_ignore(config.graph_ignore.clone());
         graph.initialize(&config.vault_path).await?;
+        graph.precompute_neighborhoods();
 
         let graph = Arc::new(RwLock::new(graph));
 
         // Create embedding manager and preload model + embeddings at startup
-        let embeddings = Arc::new(EmbeddingManager::new(&config.vault_path));
+        let embeddings = Arc::new(EmbeddingManager::from_config(&config));
 
         // Spawn background task to preload embeddings
         // Server starts immediately - search will wait for model but not for preload
@@ -186,20 +352,8 @@ impl SharedState {
             let vault_path = config.vault_path.clone();
 
             tokio::spawn(async move {
-                // Collect paths first, then drop lock before doing I/O
-                let paths: Vec<String> = {
-                    let graph_read = graph_clone.read().await;
-                    graph_read.all_paths().cloned().collect()
-                };
-
-                // Read files asynchronously without holding lock
-                let mut notes = Vec::with_capacity(paths.len());
-                for path in paths {
-                    let full_path = vault_path.join(&path);
-                    if let Ok(content) = tokio::fs::read_to_string(&full_path).await {
-                        notes.push((path, content));
-                    }
-                }
+                let notes =
+                    tools::reindex_embeddings::collect_embeddable_notes(&vault_path, &graph_clone).await;
 
                 if !notes.is_empty() {
                     tracing::info!("Preloading embeddings for {} notes in background...", notes.len());
@@ -213,7 +367,7 @@ impl SharedState {
         }
 
         // Create storage backend
-        let storage = Arc::new(FileStorage::new(config.vault_path.clone()));
+        let storage = Arc::new(Backend::from_config(&config).await?);
 
         // Start file watcher to keep graph index and embeddings up to date
         let watcher = match VaultWatcher::start(
@@ -236,6 +390,8 @@ impl SharedState {
             graph,
             embeddings,
             storage,
+            metrics: Arc::new(ToolMetrics::new()),
+            graph_rebuild: Arc::new(Default::default()),
             watcher,
         })
     }
@@ -246,6 +402,8 @@ impl SharedState {
 pub struct MemoryServer {
     /// Shared state (graph, embeddings, storage, config) - same across all sessions
     shared: SharedState,
+    /// This session's view of storage, recording changes for UndoLastChange
+    storage: Arc<JournaledStorage<Backend>>,
     tool_router: ToolRouter<Self>,
 }
 
@@ -259,9 +417,20 @@ impl MemoryServer {
 
     /// Create a server from pre-initialized shared state (sync, for HTTP factory).
     pub fn from_shared(shared: SharedState) -> Self {
+        let mut tool_router = Self::tool_router();
+        if !shared.config.debug_tools {
+            tool_router.remove_route("debug_embed");
+        }
+        if !tools::get_note_history::sync_enabled(&shared.config.vault_path) {
+            tool_router.remove_route("get_note_history");
+        }
+
+        let storage = Arc::new(JournaledStorage::new(shared.storage.clone()));
+
         Self {
             shared,
-            tool_router: Self::tool_router(),
+            storage,
+            tool_router,
         }
     }
 
@@ -278,8 +447,8 @@ impl MemoryServer {
         &self.shared.embeddings
     }
 
-    fn storage(&self) -> &FileStorage {
-        &self.shared.storage
+    fn storage(&self) -> &JournaledStorage<Backend> {
+        &self.storage
     }
 
     #[tool(description = "Get the current date and time in ISO format for use in Working Memory timeline entries. Returns ISO 8601 formatted datetime (YYYY-MM-DDTHH:MM) and additional context.")]
@@ -287,9 +456,14 @@ impl MemoryServer {
         tools::get_current_datetime::execute()
     }
 
+    #[tool(description = "Get per-tool call counts, error counts, and latency percentiles (p50/p90/p99/max in ms) since the server started. Useful for diagnosing slow tools.")]
+    async fn get_server_metrics(&self) -> Result<CallToolResult, ErrorData> {
+        tools::get_server_metrics::execute(&self.shared.metrics)
+    }
+
     #[tool(description = "Append a timestamped entry to Log.md for active work state and debugging context tracking. Records chronological session activity - what happened when. The tool automatically adds timestamps and organizes entries by day. Use this for tracking work in progress, debugging steps, state changes, and decisions made during active work.")]
     async fn log(&self, params: Parameters<LogParams>) -> Result<CallToolResult, ErrorData> {
-        tools::log::execute(&self.config().vault_path, &params.0.content).await
+        tools::log::execute(self.storage(), &params.0.content).await
     }
 
     #[tool(description = "Get metadata and graph connections for the current week's journal note. Returns path, URIs, frontmatter, and links/backlinks. Works whether or not the note exists yet. Use ReadNote tool to get content.")]
@@ -315,12 +489,35 @@ impl MemoryServer {
         .await
     }
 
+    #[tool(description = "List a note's edit history from its sync document (only in vaults with sync enabled). Returns JSON with each change's version, peer, and timestamp, oldest first. Pass a version to also get the note's content as of that change.")]
+    async fn get_note_history(&self, params: Parameters<GetNoteHistoryParams>) -> Result<CallToolResult, ErrorData> {
+        tools::get_note_history::execute(
+            &self.config().vault_path,
+            &params.0.note,
+            params.0.version.as_deref(),
+        )
+        .await
+    }
+
+    #[tool(description = "Export the vault's link graph as JSON for visualization. Returns nodes (path, title) and edges (source, target, kind: resolved or dangling), sorted by path, in pages of max_nodes notes. When next_cursor is returned, pass it as cursor to fetch the next page.")]
+    async fn get_graph(&self, params: Parameters<GetGraphParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
+        tools::get_graph::execute(
+            &graph,
+            params.0.folder.as_deref(),
+            params.0.include_private,
+            params.0.max_nodes,
+            params.0.cursor.as_deref(),
+        )
+    }
+
     #[tool(description = "Update frontmatter metadata in a note. Requires content_hash from ReadNote. Returns JSON with new content_hash.")]
     async fn update_frontmatter(&self, params: Parameters<UpdateFrontmatterParams>) -> Result<CallToolResult, ErrorData> {
         let graph = self.graph().read().await;
         tools::update_frontmatter::execute(
             &*self.storage(),
             &graph,
+            &self.config().frontmatter_schema,
             &params.0.note,
             params.0.updates,
             &params.0.content_hash,
@@ -328,11 +525,19 @@ impl MemoryServer {
         .await
     }
 
-    #[tool(description = "Load all session context files in a single call. Returns Log.md, Working Memory.md, current weekly note, and discovered project notes. Automatically discovers projects based on git remotes and directory names. Use this at the start of every session to get complete context about recent work, current focus, this week's activity, and project context.")]
-    async fn remember(&self) -> Result<CallToolResult, ErrorData> {
+    #[tool(description = "Load all session context files in a single call. Returns Log.md, Working Memory.md, current weekly note, and discovered project notes. Automatically discovers projects based on git remotes and directory names. Use this at the start of every session to get complete context about recent work, current focus, this week's activity, and project context. Large context is split into pages: when next_cursor is returned, call again with it as cursor.")]
+    async fn remember(&self, params: Parameters<RememberParams>) -> Result<CallToolResult, ErrorData> {
         let graph = self.graph().read().await;
         let cwd = std::env::current_dir().unwrap_or_default();
-        tools::remember::execute(&self.config().vault_path, &graph, &cwd).await
+        tools::remember::execute(
+            &self.config().vault_path,
+            &graph,
+            &cwd,
+            &self.config().project_discovery,
+            self.config().remember_budget,
+            params.0.cursor.as_deref(),
+        )
+        .await
     }
 
     #[tool(description = "Search for relevant notes using semantic similarity. Encodes the query and compares it against all note embeddings. Returns similarity-ordered list of potentially relevant notes. Supports note references via wiki-links: [[Note Name]]")]
@@ -349,37 +554,135 @@ impl MemoryServer {
         .await
     }
 
+    #[tool(description = "Find notes semantically similar to an existing note. Uses the note's stored embedding as the query and excludes the note itself. Returns similarity-ordered list of related notes with scores. Cheaper and more accurate than pasting note content into Search.")]
+    async fn similar_to_note(&self, params: Parameters<SimilarToNoteParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
+        tools::similar_to_note::execute(
+            self.storage(),
+            &self.config().vault_path,
+            &graph,
+            self.embeddings(),
+            &params.0.note,
+            params.0.include_private,
+        )
+        .await
+    }
+
+    #[tool(description = "Find pairs of near-duplicate notes by semantic similarity, plus notes with identical content. Returns candidates for the user to review; nothing is merged or deleted.")]
+    async fn find_duplicates(&self, params: Parameters<FindDuplicatesParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
+        tools::find_duplicates::execute(
+            &self.config().vault_path,
+            &graph,
+            self.embeddings(),
+            params.0.threshold,
+            params.0.identical_only,
+            params.0.include_private,
+        )
+        .await
+    }
+
+    #[tool(description = "Debug tool: encode text with the embedding model and return the raw 384-dim vector, its token count, norm, and min/max/mean. Use to diagnose why texts get the similarity scores they do.")]
+    async fn debug_embed(&self, params: Parameters<DebugEmbedParams>) -> Result<CallToolResult, ErrorData> {
+        tools::debug_embed::execute(self.embeddings(), &params.0.text, params.0.as_query).await
+    }
+
+    #[tool(description = "Rebuild all note embeddings from scratch. Recomputes embeddings for every note in the background and replaces the embedding cache when done. Use after changing the embedding model or prefixes. Safe to call repeatedly - returns progress if a rebuild is already running. Returns JSON with status and cache stats.")]
+    async fn reindex_embeddings(&self) -> Result<CallToolResult, ErrorData> {
+        tools::reindex_embeddings::execute(
+            &self.config().vault_path,
+            self.graph(),
+            self.embeddings(),
+        )
+        .await
+    }
+
+    #[tool(description = "Rebuild the graph index (links, backlinks, frontmatter) from the vault files, for recovery when links or backlinks look out of date. Runs in the background; the old index stays in use until the new one is ready. Set reindexEmbeddings to also rebuild embeddings afterwards. Safe to call repeatedly - reports progress if a rebuild is already running. Returns JSON with status and completed rebuild count.")]
+    async fn rebuild_index(&self, params: Parameters<RebuildIndexParams>) -> Result<CallToolResult, ErrorData> {
+        tools::rebuild_index::execute(
+            &self.config().vault_path,
+            self.graph(),
+            self.embeddings(),
+            &self.shared.graph_rebuild,
+            params.0.reindex_embeddings,
+        )
+        .await
+    }
+
     #[tool(description = "Replace an entire day's log entries with consolidated/compacted entries. Use this ONLY during memory consolidation to rewrite or summarize a day's logs. For adding new entries during active work, use the Log tool instead (it's simpler and doesn't require reading the log first). This tool automatically formats entries with correct timestamps, en-dashes, and chronological sorting. Pass an empty object to delete the entire day section (header and all entries).")]
     async fn write_logs(&self, params: Parameters<WriteLogsParams>) -> Result<CallToolResult, ErrorData> {
         tools::write_logs::execute(
-            &self.config().vault_path,
+            self.storage(),
             &params.0.iso_week_date,
             params.0.entries,
         )
         .await
     }
 
-    #[tool(description = "Review active context (Log.md, Working Memory.md, current weekly journal, project notes) and consolidate content into permanent storage. Optimizes token usage by keeping active/relevant work accessible while compressing or archiving finished work. Applies information lifecycle: active work = keep lean, shipped/merged = compress and archive. Returns detailed consolidation instructions.")]
+    #[tool(description = "Move old days out of Log.md into weekly archive notes (log/YYYY-wWW.md). Days older than olderThanDays (default 14) are archived verbatim; recent days stay in Log.md. Safe to run repeatedly. Returns JSON with the archived days, archive note URIs, and how many days remain.")]
+    async fn compact_log(&self, params: Parameters<CompactLogParams>) -> Result<CallToolResult, ErrorData> {
+        tools::compact_log::execute(self.storage(), params.0.older_than_days).await
+    }
+
+    #[tool(description = "Review active context (Log.md, Working Memory.md, current weekly journal, project notes) and consolidate content into permanent storage. Optimizes token usage by keeping active/relevant work accessible while compressing or archiving finished work. Applies information lifecycle: active work = keep lean, shipped/merged = compress and archive. Returns detailed consolidation instructions, plus structured candidates (stale log days and notes, with age and suggested action).")]
     async fn reflect(&self, params: Parameters<ReflectParams>) -> Result<CallToolResult, ErrorData> {
-        tools::reflect::execute(params.0.include_private)
+        let graph = self.graph().read().await;
+        tools::reflect::execute(
+            &self.config().vault_path,
+            &graph,
+            &self.config().project_discovery,
+            params.0.include_private,
+            params.0.stale_after_days,
+        )
+        .await
     }
 
     #[tool(description = "Load private memory indexes (requires explicit user consent)")]
     async fn load_private_memory(&self, params: Parameters<LoadPrivateMemoryParams>) -> Result<CallToolResult, ErrorData> {
-        tools::load_private_memory::execute(&self.config().vault_path, &params.0.reason).await
+        tools::load_private_memory::execute(
+            &self.config().vault_path,
+            &self.config().private_roots,
+            &params.0.reason,
+        )
+        .await
     }
 
-    #[tool(description = "Read the complete contents of a note. Returns JSON with content and content_hash. Use content_hash when calling WriteNote or EditNote.")]
+    #[tool(description = "Read the complete contents of a note. Returns JSON with content and content_hash. Use content_hash when calling WriteNote or EditNote. Set format to html for rendered HTML.")]
     async fn read_note(&self, params: Parameters<ReadNoteParams>) -> Result<CallToolResult, ErrorData> {
         let graph = self.graph().read().await;
         tools::read_note::execute(
             self.storage(),
             &graph,
             &params.0.note,
+            params.0.format,
+        )
+        .await
+    }
+
+    #[tool(description = "Render a note to HTML for previewing, roughly as Obsidian displays it: wiki-links become anchors to memory: URIs, ![[Note]] and ![[Note#Heading]] embeds are inlined (one level deep unless the server sets a higher embed depth), and frontmatter is shown as a table. Returns JSON with the HTML and the embedded note URIs. Use ReadNote to get content for editing.")]
+    async fn render_note(&self, params: Parameters<RenderNoteParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
+        tools::render_note::execute(
+            self.storage(),
+            &graph,
+            &params.0.note,
+            self.config().embed_depth,
         )
         .await
     }
 
+    #[tool(description = "Check whether a note exists without reading it. Returns JSON with exists, plus uri and path when found (or candidates when the name matches notes in several folders).")]
+    async fn note_exists(&self, params: Parameters<NoteExistsParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
+        tools::note_exists::execute(self.storage(), &graph, &params.0.note).await
+    }
+
+    #[tool(description = "Resolve a wiki-link or note reference to the note it points to. Returns JSON with status 'resolved' (uri and path), 'ambiguous' (candidates, when same-named notes exist in different folders), or 'not_found'.")]
+    async fn resolve_link(&self, params: Parameters<ResolveLinkParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
+        tools::resolve_link::execute(self.storage(), &graph, &params.0.link).await
+    }
+
     #[tool(description = "Create a new note or overwrite an existing note. For existing notes, include content_hash from ReadNote. Returns JSON with new content_hash for chained writes.")]
     async fn write_note(&self, params: Parameters<WriteNoteParams>) -> Result<CallToolResult, ErrorData> {
         let graph = self.graph().read().await;
@@ -387,6 +690,7 @@ impl MemoryServer {
             &self.config().vault_path,
             self.storage(),
             &graph,
+            &self.config().frontmatter_schema,
             &params.0.note,
             &params.0.content,
             params.0.content_hash.as_deref(),
@@ -417,17 +721,53 @@ impl MemoryServer {
         .await
     }
 
-    #[tool(description = "Permanently delete a note from the vault. Returns an error if the note doesn't exist.")]
+    #[tool(description = "Generate a table of contents for a note: a nested list of links to its headings, indented by heading level. Optionally writes it into the note under a <!-- toc --> marker, replacing any previous TOC (requires content_hash from ReadNote). Returns JSON with the TOC.")]
+    async fn generate_toc(&self, params: Parameters<GenerateTocParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
+        tools::generate_toc::execute(
+            self.storage(),
+            &graph,
+            &params.0.note,
+            params.0.insert,
+            params.0.content_hash.as_deref(),
+        )
+        .await
+    }
+
+    #[tool(description = "Insert content into the section under a heading (e.g. add a bullet under ## Tasks), creating the heading at the end of the note if it doesn't exist. The section extends to the next heading of the same or higher level. More robust than EditNote for structured notes. Requires content_hash from ReadNote. Returns JSON with new content_hash.")]
+    async fn insert_under_heading(&self, params: Parameters<InsertUnderHeadingParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
+        tools::insert_under_heading::execute(
+            self.storage(),
+            &graph,
+            &params.0.note,
+            &params.0.heading,
+            &params.0.content,
+            params.0.position,
+            &params.0.content_hash,
+        )
+        .await
+    }
+
+    #[tool(description = "Permanently delete a note from the vault. Returns an error if the note doesn't exist. If other notes link to it, nothing is deleted and the linking notes are listed instead; pass force: true to delete anyway.")]
     async fn delete_note(&self, params: Parameters<DeleteNoteParams>) -> Result<CallToolResult, ErrorData> {
+        let graph = self.graph().read().await;
         tools::delete_note::execute(
             &self.config().vault_path,
             self.storage(),
+            &graph,
             &params.0.note,
+            params.0.force,
         )
         .await
     }
 
-    #[tool(description = "Move or rename a note. Automatically updates wiki-links in all notes that reference the moved note. Fails if destination already exists.")]
+    #[tool(description = "Undo the most recent note change made in this session (WriteNote, EditNote, DeleteNote, MoveNote, and other tools that modify notes). Call repeatedly to step further back. Refuses if an affected note has been modified since.")]
+    async fn undo_last_change(&self) -> Result<CallToolResult, ErrorData> {
+        tools::undo_last_change::execute(self.storage()).await
+    }
+
+    #[tool(description = "Move or rename a note. Automatically updates wiki-links in all notes that reference the moved note. Fails if destination already exists. Set dryRun to preview the destination and the link changes without modifying anything. Set leaveRedirect to leave a stub at the old path that links to the new one.")]
     async fn move_note(&self, params: Parameters<MoveNoteParams>) -> Result<CallToolResult, ErrorData> {
         tools::move_note::execute(
             &self.config().vault_path,
@@ -435,13 +775,46 @@ impl MemoryServer {
             self.graph(),
             &params.0.from,
             &params.0.to,
+            params.0.dry_run,
+            params.0.leave_redirect,
         )
         .await
     }
 }
 
-#[tool_handler]
 impl rmcp::ServerHandler for MemoryServer {
+    /// Dispatch to the tool router, applying time limits and recording metrics.
+    /// Each call starts a new undo group so its changes are undone together.
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let tool = request.name.to_string();
+        self.storage.begin_operation(&tool);
+        let ct = context.ct.clone();
+        let tcc = ToolCallContext::new(self, request, context);
+        let call = timeout::run_with_limits(
+            &tool,
+            self.config().tool_timeout(&tool),
+            ct.cancelled(),
+            self.tool_router.call(tcc),
+        );
+        self.shared.metrics.measure(&tool, call).await
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult {
+            tools: self.tool_router.list_all(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::LATEST,
@@ -476,6 +849,10 @@ struct Cli {
     /// Address to bind to in HTTP mode. Use 0.0.0.0 for all interfaces (unsafe without auth).
     #[arg(long, default_value = "127.0.0.1")]
     bind: String,
+
+    /// Check the vault, graph index, embedding model, and search, then exit
+    #[arg(long)]
+    self_test: bool,
 }
 
 #[cfg(feature = "http")]
@@ -489,18 +866,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(EnvFilter::from_default_env())
         .init();
 
+    #[cfg(feature = "http")]
+    let cli = {
+        use clap::Parser;
+        Cli::parse()
+    };
+    #[cfg(feature = "http")]
+    let self_test = cli.self_test;
+    #[cfg(not(feature = "http"))]
+    let self_test = std::env::args().skip(1).any(|arg| arg == "--self-test");
+
+    if self_test {
+        let checks = self_test::run(Config::from_env()).await;
+        print!("{}", self_test::report(&checks));
+        std::process::exit(self_test::exit_code(&checks));
+    }
+
     // Load configuration from environment
     let config = Config::from_env()?;
     tracing::info!("Vault path: {}", config.vault_path.display());
+    if !config.frontmatter_schema.is_empty() {
+        tracing::info!("Frontmatter schema validation enabled");
+    }
 
     #[cfg(feature = "http")]
-    {
-        use clap::Parser;
-        let cli = Cli::parse();
-
-        if cli.http {
-            return run_http_server(config, &cli.bind, cli.port).await;
-        }
+    if cli.http {
+        return run_http_server(config, &cli.bind, cli.port).await;
     }
 
     // Default: Run with STDIO transport
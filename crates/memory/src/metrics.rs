@@ -0,0 +1,189 @@
+//! Per-tool invocation metrics.
+//!
+//! Every tool call goes through [`ToolMetrics::measure`], which records the
+//! call, its latency, and whether it failed. Latency percentiles are computed
+//! over the most recent samples so memory stays bounded on long-running servers.
+
+use rmcp::model::{CallToolResult, ErrorData};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Latency samples kept per tool for percentiles
+pub const MAX_LATENCY_SAMPLES: usize = 1024;
+
+/// Counters and recent latencies for one tool.
+#[derive(Debug, Default)]
+struct ToolStats {
+    calls: u64,
+    errors: u64,
+    latencies: VecDeque<Duration>,
+}
+
+/// Summary of one tool's metrics.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolMetricsSnapshot {
+    pub tool: String,
+    pub calls: u64,
+    /// Calls that returned an error or an error result
+    pub errors: u64,
+    /// Latency percentiles over the recent samples, in milliseconds
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    /// Number of samples the percentiles are based on
+    pub samples: usize,
+}
+
+/// Invocation counts, errors, and latencies keyed by tool name.
+#[derive(Debug, Default)]
+pub struct ToolMetrics {
+    tools: Mutex<BTreeMap<String, ToolStats>>,
+}
+
+impl ToolMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run a tool call and record its outcome under `tool`.
+    pub async fn measure<F>(&self, tool: &str, call: F) -> Result<CallToolResult, ErrorData>
+    where
+        F: Future<Output = Result<CallToolResult, ErrorData>>,
+    {
+        let started = Instant::now();
+        let result = call.await;
+        let failed = match &result {
+            Ok(result) => result.is_error == Some(true),
+            Err(_) => true,
+        };
+        self.record(tool, started.elapsed(), failed);
+        result
+    }
+
+    /// Record one call of `tool`.
+    pub fn record(&self, tool: &str, latency: Duration, failed: bool) {
+        let mut tools = self.tools.lock().unwrap();
+        let stats = tools.entry(tool.to_string()).or_default();
+
+        stats.calls += 1;
+        if failed {
+            stats.errors += 1;
+        }
+        if stats.latencies.len() == MAX_LATENCY_SAMPLES {
+            stats.latencies.pop_front();
+        }
+        stats.latencies.push_back(latency);
+    }
+
+    /// Metrics for every tool called so far, sorted by tool name.
+    pub fn snapshot(&self) -> Vec<ToolMetricsSnapshot> {
+        let tools = self.tools.lock().unwrap();
+        tools
+            .iter()
+            .map(|(tool, stats)| {
+                let mut sorted: Vec<Duration> = stats.latencies.iter().copied().collect();
+                sorted.sort();
+
+                ToolMetricsSnapshot {
+                    tool: tool.clone(),
+                    calls: stats.calls,
+                    errors: stats.errors,
+                    p50_ms: percentile_ms(&sorted, 0.50),
+                    p90_ms: percentile_ms(&sorted, 0.90),
+                    p99_ms: percentile_ms(&sorted, 0.99),
+                    max_ms: sorted.last().map(as_ms).unwrap_or(0.0),
+                    samples: sorted.len(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn as_ms(duration: &Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Nearest-rank percentile of sorted latencies, in milliseconds.
+fn percentile_ms(sorted: &[Duration], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percentile * sorted.len() as f64).ceil() as usize;
+    as_ms(&sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+
+    #[tokio::test]
+    async fn test_measure_counts_call_and_records_latency() {
+        let metrics = ToolMetrics::new();
+
+        metrics
+            .measure("get_current_datetime", async {
+                crate::tools::get_current_datetime::execute()
+            })
+            .await
+            .unwrap();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].tool, "get_current_datetime");
+        assert_eq!(snapshot[0].calls, 1);
+        assert_eq!(snapshot[0].errors, 0);
+        assert_eq!(snapshot[0].samples, 1);
+    }
+
+    #[tokio::test]
+    async fn test_measure_counts_errors() {
+        let metrics = ToolMetrics::new();
+
+        let _ = metrics
+            .measure("read_note", async {
+                Err(ErrorData::invalid_params("Note not found", None))
+            })
+            .await;
+        metrics
+            .measure("read_note", async {
+                Ok(CallToolResult::error(vec![Content::text("failed")]))
+            })
+            .await
+            .unwrap();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot[0].calls, 2);
+        assert_eq!(snapshot[0].errors, 2);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let metrics = ToolMetrics::new();
+        for ms in 1..=100 {
+            metrics.record("search", Duration::from_millis(ms), false);
+        }
+
+        let snapshot = &metrics.snapshot()[0];
+        assert_eq!(snapshot.p50_ms, 50.0);
+        assert_eq!(snapshot.p90_ms, 90.0);
+        assert_eq!(snapshot.p99_ms, 99.0);
+        assert_eq!(snapshot.max_ms, 100.0);
+    }
+
+    #[test]
+    fn test_latency_samples_are_bounded() {
+        let metrics = ToolMetrics::new();
+        for _ in 0..MAX_LATENCY_SAMPLES + 10 {
+            metrics.record("search", Duration::from_millis(1), false);
+        }
+
+        let snapshot = &metrics.snapshot()[0];
+        assert_eq!(snapshot.calls, (MAX_LATENCY_SAMPLES + 10) as u64);
+        assert_eq!(snapshot.samples, MAX_LATENCY_SAMPLES);
+    }
+}
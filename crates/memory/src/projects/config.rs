@@ -0,0 +1,154 @@
+//! Rules for which notes count as project notes.
+//!
+//! By default every note under `projects/` is a project note. A discovery
+//! config file replaces that with its own rules; a note is a project note when
+//! any rule matches it:
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     { "type": "folder", "folder": "projects" },
+//!     { "type": "frontmatter", "field": "project", "value": true },
+//!     { "type": "path", "pattern": "work/*/README.md" }
+//!   ]
+//! }
+//! ```
+//!
+//! Matching project notes to directories (remotes and slugs) is unchanged.
+
+use obsidian_fs::Frontmatter;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+/// A rule selecting project notes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum ProjectRule {
+    /// Notes in a vault folder, including subfolders
+    Folder { folder: String },
+    /// Notes whose frontmatter `field` equals `value`
+    Frontmatter { field: String, value: JsonValue },
+    /// Notes whose vault path matches `pattern`, where `*` matches within one
+    /// folder and `**` matches across folders
+    Path { pattern: String },
+}
+
+impl ProjectRule {
+    /// Whether the note at `path` (relative to the vault) matches this rule.
+    fn matches(&self, path: &str, frontmatter: Option<&Frontmatter>) -> bool {
+        match self {
+            ProjectRule::Folder { folder } => {
+                let folder = folder.trim_matches('/');
+                path.strip_prefix(folder)
+                    .is_some_and(|rest| folder.is_empty() || rest.starts_with('/'))
+            }
+            ProjectRule::Frontmatter { field, value } => frontmatter
+                .and_then(|fm| fm.get(field))
+                .is_some_and(|actual| actual == value),
+            ProjectRule::Path { pattern } => glob_match(pattern.as_bytes(), path.as_bytes()),
+        }
+    }
+}
+
+/// Configuration for project discovery.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectDiscoveryConfig {
+    /// Rules selecting project notes; a note matching any of them is a project note
+    pub rules: Vec<ProjectRule>,
+}
+
+impl Default for ProjectDiscoveryConfig {
+    /// Every note under `projects/`.
+    fn default() -> Self {
+        Self {
+            rules: vec![ProjectRule::Folder {
+                folder: "projects".to_string(),
+            }],
+        }
+    }
+}
+
+impl ProjectDiscoveryConfig {
+    /// Parse a discovery config from JSON.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Whether the note at `path` (relative to the vault) is a project note.
+    pub fn is_project(&self, path: &str, frontmatter: Option<&Frontmatter>) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.matches(path, frontmatter))
+    }
+}
+
+/// Match `path` against a pattern where `*` matches any run of characters
+/// other than `/` and `**` matches any run including `/`.
+pub(crate) fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            // `**/` also matches no folders at all
+            let rest_after_slash = rest.strip_prefix(b"/").unwrap_or(rest);
+            (0..=path.len()).any(|i| glob_match(rest, &path[i..]))
+                || (rest.len() != rest_after_slash.len() && glob_match(rest_after_slash, path))
+        }
+        [b'*', rest @ ..] => {
+            let segment_end = path.iter().position(|&c| c == b'/').unwrap_or(path.len());
+            (0..=segment_end).any(|i| glob_match(rest, &path[i..]))
+        }
+        [c, rest @ ..] => path.first() == Some(c) && glob_match(rest, &path[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn frontmatter(value: JsonValue) -> Frontmatter {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_default_matches_projects_folder() {
+        let config = ProjectDiscoveryConfig::default();
+        assert!(config.is_project("projects/Memory.md", None));
+        assert!(config.is_project("projects/archive/Old.md", None));
+        assert!(!config.is_project("projects-old/Memory.md", None));
+        assert!(!config.is_project("knowledge/Memory.md", None));
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let config = ProjectDiscoveryConfig::from_json(
+            r#"{"rules": [
+                {"type": "frontmatter", "field": "project", "value": true},
+                {"type": "path", "pattern": "work/**/README.md"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let marked = frontmatter(json!({"project": true}));
+        let unmarked = frontmatter(json!({"project": "yes"}));
+        assert!(config.is_project("knowledge/Memory.md", Some(&marked)));
+        assert!(!config.is_project("knowledge/Memory.md", Some(&unmarked)));
+        assert!(!config.is_project("projects/Memory.md", None));
+        assert!(config.is_project("work/README.md", None));
+        assert!(config.is_project("work/client/api/README.md", None));
+
+        assert!(ProjectDiscoveryConfig::from_json(r#"{"rules": [{"type": "regex"}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_glob_single_star_stays_in_folder() {
+        assert!(glob_match(b"work/*/README.md", b"work/api/README.md"));
+        assert!(!glob_match(
+            b"work/*/README.md",
+            b"work/client/api/README.md"
+        ));
+        assert!(glob_match(b"*.md", b"Note.md"));
+        assert!(!glob_match(b"*.md", b"projects/Note.md"));
+    }
+}
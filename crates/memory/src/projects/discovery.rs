@@ -6,7 +6,8 @@
 //! 3. Search all project notes for strict matches (current remotes/slug)
 //! 4. If no strict match, search for loose matches (old remotes/slugs)
 //! 5. If no matches at all, find similar projects for suggestions
-//! 6. Return all matches ordered by depth (closest first)
+//! 6. Return all matches ordered by depth (closest first), each with the notes
+//!    it links to or is linked from
 
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
@@ -16,6 +17,7 @@ use serde_json::Value as JsonValue;
 
 use crate::graph::GraphIndex;
 
+use super::config::ProjectDiscoveryConfig;
 use super::types::*;
 
 /// Normalize git remote URL to a consistent format for comparison.
@@ -152,16 +154,22 @@ fn load_project_metadata(note_name: &str, file_path: &Path) -> Option<ProjectMet
     })
 }
 
-/// Get all project notes from the vault's projects/ folder.
+/// Get all project notes from the vault.
 ///
-/// Scans all notes in the graph index, filtering for those in the projects/ folder,
-/// and loads their frontmatter to extract project metadata.
-fn get_all_projects(graph_index: &GraphIndex, vault_path: &Path) -> Vec<ProjectMetadata> {
+/// Scans all notes in the graph index, keeping those the config's rules select
+/// (by default, notes in the projects/ folder), and loads their frontmatter to
+/// extract project metadata. Notes are returned in path order.
+fn get_all_projects(
+    graph_index: &GraphIndex,
+    vault_path: &Path,
+    config: &ProjectDiscoveryConfig,
+) -> Vec<ProjectMetadata> {
     let mut projects = Vec::new();
+    let mut rel_paths: Vec<&String> = graph_index.all_paths().collect();
+    rel_paths.sort();
 
-    for rel_path in graph_index.all_paths() {
-        // Only consider notes in projects/ folder
-        if !rel_path.starts_with("projects/") {
+    for rel_path in rel_paths {
+        if !config.is_project(rel_path, graph_index.get_frontmatter(rel_path)) {
             continue;
         }
 
@@ -310,22 +318,50 @@ fn find_similar_projects(dir_name: &str, all_projects: &[ProjectMetadata]) -> Ve
     similar
 }
 
+/// Names of the notes directly linked with a project note, in either direction.
+///
+/// Comes from the project note's graph neighborhood, excluding the project
+/// itself and private notes, sorted by name.
+fn related_notes(graph_index: &GraphIndex, vault_path: &Path, project: &ProjectMetadata) -> Vec<String> {
+    let Some(rel_path) = project
+        .file_path
+        .strip_prefix(vault_path)
+        .ok()
+        .and_then(|p| p.to_str())
+    else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = graph_index
+        .get_neighborhood(rel_path)
+        .into_iter()
+        .filter(|path| !graph_index.is_private(path))
+        .filter_map(|path| Path::new(&path).file_stem()?.to_str().map(String::from))
+        .filter(|name| *name != project.name)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
 /// Discover projects for a working directory.
 ///
 /// Algorithm:
 /// 1. Crawl from CWD up to home directory
 /// 2. For each directory, extract git remotes and directory name
-/// 3. Search all project notes for strict matches (current remotes/slug)
+/// 3. Search all project notes (as selected by `config`) for strict matches (current remotes/slug)
 /// 4. If no strict match, search for loose matches (old remotes/slugs)
 /// 5. If no matches at all, find similar projects for suggestions
-/// 6. Return all matches ordered by depth (closest first)
+/// 6. Return all matches ordered by depth (closest first), each with the notes
+///    it links to or is linked from
 pub fn discover_projects(
     cwd: &Path,
     graph_index: &GraphIndex,
     vault_path: &Path,
+    config: &ProjectDiscoveryConfig,
 ) -> DiscoveryResult {
     let directories = crawl_directories(cwd);
-    let all_projects = get_all_projects(graph_index, vault_path);
+    let all_projects = get_all_projects(graph_index, vault_path, config);
 
     let mut strict_matches = Vec::new();
     let mut loose_matches = Vec::new();
@@ -350,6 +386,7 @@ pub fn discover_projects(
                     matched_on: strict.on,
                     matched_value: strict.value,
                     depth: directory.depth,
+                    related_notes: related_notes(graph_index, vault_path, project),
                 });
                 continue;
             }
@@ -363,6 +400,7 @@ pub fn discover_projects(
                     matched_on: loose.on,
                     matched_value: loose.value,
                     depth: directory.depth,
+                    related_notes: related_notes(graph_index, vault_path, project),
                 });
             }
         }
@@ -525,6 +563,104 @@ mod tests {
         assert_eq!(result.on, Some(MatchedOn::OldSlug));
     }
 
+    #[test]
+    fn test_frontmatter_rule_discovers_marked_notes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vault_path = temp_dir.path().join("vault");
+        let cwd = temp_dir.path().join("code").join("memory");
+        std::fs::create_dir_all(vault_path.join("knowledge")).unwrap();
+        std::fs::create_dir_all(vault_path.join("projects")).unwrap();
+        std::fs::create_dir_all(&cwd).unwrap();
+
+        // Both notes match the directory's slug; only one is marked as a project
+        let notes = [
+            ("Memory", "knowledge/Memory.md", "---\nproject: true\nslug: memory\n---\n"),
+            ("Legacy", "projects/Legacy.md", "---\nslug: memory\n---\n"),
+        ];
+        let mut graph = GraphIndex::new();
+        for (name, path, content) in notes {
+            std::fs::write(vault_path.join(path), content).unwrap();
+            graph.update_note(name, PathBuf::from(path), HashSet::new());
+            graph.set_frontmatter(path, obsidian_fs::parse_frontmatter(content).frontmatter);
+        }
+
+        let config = ProjectDiscoveryConfig::from_json(
+            r#"{"rules": [{"type": "frontmatter", "field": "project", "value": true}]}"#,
+        )
+        .unwrap();
+        let result = discover_projects(&cwd, &graph, &vault_path, &config);
+        let names: Vec<&str> = result
+            .strict_matches
+            .iter()
+            .map(|m| m.metadata.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Memory"]);
+        assert_eq!(result.strict_matches[0].matched_on, Some(MatchedOn::Slug));
+
+        // The default rules only look in projects/
+        let result = discover_projects(&cwd, &graph, &vault_path, &ProjectDiscoveryConfig::default());
+        let names: Vec<&str> = result
+            .strict_matches
+            .iter()
+            .map(|m| m.metadata.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Legacy"]);
+    }
+
+    #[test]
+    fn test_discovered_project_includes_linked_notes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vault_path = temp_dir.path().join("vault");
+        let cwd = temp_dir.path().join("code").join("memory");
+        std::fs::create_dir_all(vault_path.join("projects")).unwrap();
+        std::fs::create_dir_all(&cwd).unwrap();
+        std::fs::write(
+            vault_path.join("projects/Memory.md"),
+            "---\nslug: memory\n---\nUses [[Rust]] and [[Loro]].",
+        )
+        .unwrap();
+
+        let mut graph = GraphIndex::new();
+        let links = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        graph.update_note("Memory", PathBuf::from("projects/Memory.md"), links(&["Rust", "Loro"]));
+        graph.update_note("Rust", PathBuf::from("knowledge/Rust.md"), HashSet::new());
+        graph.update_note("Loro", PathBuf::from("knowledge/Loro.md"), HashSet::new());
+        graph.update_note("Unrelated", PathBuf::from("knowledge/Unrelated.md"), HashSet::new());
+
+        let result = discover_projects(&cwd, &graph, &vault_path, &ProjectDiscoveryConfig::default());
+
+        assert_eq!(result.strict_matches.len(), 1);
+        assert_eq!(result.strict_matches[0].related_notes, vec!["Loro", "Rust"]);
+    }
+
+    #[test]
+    fn test_related_notes_exclude_private_notes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vault_path = temp_dir.path().join("vault");
+        let cwd = temp_dir.path().join("code").join("memory");
+        std::fs::create_dir_all(vault_path.join("projects")).unwrap();
+        std::fs::create_dir_all(&cwd).unwrap();
+        std::fs::write(
+            vault_path.join("projects/Memory.md"),
+            "---\nslug: memory\n---\nUses [[Rust]].",
+        )
+        .unwrap();
+
+        let mut graph = GraphIndex::new();
+        let links = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        graph.update_note("Memory", PathBuf::from("projects/Memory.md"), links(&["Rust"]));
+        graph.update_note("Rust", PathBuf::from("knowledge/Rust.md"), HashSet::new());
+        graph.update_note("Diary", PathBuf::from("knowledge/Diary.md"), links(&["Memory"]));
+        graph.set_frontmatter(
+            "knowledge/Diary.md",
+            obsidian_fs::parse_frontmatter("---\nvisibility: private\n---\n").frontmatter,
+        );
+
+        let result = discover_projects(&cwd, &graph, &vault_path, &ProjectDiscoveryConfig::default());
+
+        assert_eq!(result.strict_matches[0].related_notes, vec!["Rust"]);
+    }
+
     #[test]
     fn test_find_similar_projects() {
         let projects = vec![
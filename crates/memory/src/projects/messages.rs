@@ -14,7 +14,15 @@ pub fn generate_discovery_status_message(discovery_result: &DiscoveryResult, cwd
             .iter()
             .map(|m| format!("[[{}]]", m.metadata.name))
             .collect();
-        return format!("Projects auto-loaded: {}", project_names.join(", "));
+        let mut message = format!("Projects auto-loaded: {}", project_names.join(", "));
+        for m in &discovery_result.strict_matches {
+            if m.related_notes.is_empty() {
+                continue;
+            }
+            let related: Vec<String> = m.related_notes.iter().map(|n| format!("[[{}]]", n)).collect();
+            message.push_str(&format!("\nRelated to [[{}]]: {}", m.metadata.name, related.join(", ")));
+        }
+        return message;
     }
 
     // Disconnect detected (loose match)
@@ -130,6 +138,7 @@ mod tests {
                 matched_on: Some(MatchedOn::Remote),
                 matched_value: Some("github.com/user/test".to_string()),
                 depth: 0,
+                related_notes: vec![],
             }],
             loose_matches: vec![],
             suggestions: vec![],
@@ -162,6 +171,7 @@ mod tests {
                     matched_on: Some(MatchedOn::Remote),
                     matched_value: Some("github.com/company/project".to_string()),
                     depth: 0,
+                    related_notes: vec![],
                 },
                 DiscoveredProject {
                     metadata: ProjectMetadata {
@@ -176,6 +186,7 @@ mod tests {
                     matched_on: Some(MatchedOn::Slug),
                     matched_value: Some("company".to_string()),
                     depth: 1,
+                    related_notes: vec![],
                 },
             ],
             loose_matches: vec![],
@@ -190,6 +201,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_strict_match_lists_related_notes() {
+        let result = DiscoveryResult {
+            cwd: PathBuf::from("/code/test"),
+            git_remotes: vec![],
+            searched_paths: vec![PathBuf::from("/code/test")],
+            strict_matches: vec![DiscoveredProject {
+                metadata: ProjectMetadata {
+                    name: "Test Project".to_string(),
+                    file_path: PathBuf::from("/vault/projects/Test Project.md"),
+                    remotes: None,
+                    old_remotes: None,
+                    slug: Some("test".to_string()),
+                    old_slugs: None,
+                },
+                match_type: MatchType::Strict,
+                matched_on: Some(MatchedOn::Slug),
+                matched_value: Some("test".to_string()),
+                depth: 0,
+                related_notes: vec!["Loro".to_string(), "Rust".to_string()],
+            }],
+            loose_matches: vec![],
+            suggestions: vec![],
+        };
+
+        let message = generate_discovery_status_message(&result, &PathBuf::from("/code/test"));
+        assert_eq!(
+            message,
+            "Projects auto-loaded: [[Test Project]]\nRelated to [[Test Project]]: [[Loro]], [[Rust]]"
+        );
+    }
+
     #[test]
     fn test_no_match_no_suggestions() {
         let result = DiscoveryResult {
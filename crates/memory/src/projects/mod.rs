@@ -1,7 +1,10 @@
+mod config;
 mod discovery;
 mod messages;
 mod types;
 
+pub(crate) use config::glob_match;
+pub use config::ProjectDiscoveryConfig;
 pub use discovery::discover_projects;
 pub use messages::generate_discovery_status_message;
 pub use types::*;
@@ -61,6 +61,8 @@ pub struct DiscoveredProject {
     pub matched_value: Option<String>,
     /// Directory depth (0 = CWD, 1 = parent, etc.)
     pub depth: usize,
+    /// Names of notes the project note links to or is linked from, sorted
+    pub related_notes: Vec<String>,
 }
 
 /// Result of project discovery for a working directory
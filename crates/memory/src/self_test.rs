@@ -0,0 +1,328 @@
+//! `--self-test` mode for diagnosing setup problems without an MCP client.
+//!
+//! Runs the stages the server depends on in order (configuration, vault
+//! access, graph indexing, model loading, and a sample search) and reports
+//! pass or fail for each. A check is skipped when an earlier one it needs failed.
+//! The graph check also counts link cycles, as a diagnostic.
+
+use std::fmt;
+use std::time::Instant;
+
+use semantic_embeddings::EMBEDDING_DIM;
+
+use crate::config::{Config, ConfigError};
+use crate::embeddings::EmbeddingManager;
+use crate::graph::GraphIndex;
+use crate::tools;
+
+/// Text encoded by the model check
+const SAMPLE_TEXT: &str = "The quick brown fox jumps over the lazy dog";
+
+/// Query run by the search check
+const SAMPLE_QUERY: &str = "notes";
+
+/// Outcome of one check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass(String),
+    Fail(String),
+    /// Not run because a check it depends on failed
+    Skipped,
+}
+
+/// A named check and its outcome.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.status {
+            Status::Pass(detail) => write!(f, "[PASS] {}: {}", self.name, detail),
+            Status::Fail(reason) => write!(f, "[FAIL] {}: {}", self.name, reason),
+            Status::Skipped => write!(f, "[SKIP] {}", self.name),
+        }
+    }
+}
+
+/// Collects check results, skipping checks once one fails.
+struct Checks(Vec<Check>);
+
+impl Checks {
+    /// Record `result` under `name`, returning the value if it passed.
+    fn record<T>(&mut self, name: &'static str, result: Result<(T, String), String>) -> Option<T> {
+        match result {
+            Ok((value, detail)) => {
+                self.0.push(Check {
+                    name,
+                    status: Status::Pass(detail),
+                });
+                Some(value)
+            }
+            Err(reason) => {
+                self.0.push(Check {
+                    name,
+                    status: Status::Fail(reason),
+                });
+                None
+            }
+        }
+    }
+
+    /// Mark the remaining checks as skipped.
+    fn skip(mut self, names: &[&'static str]) -> Vec<Check> {
+        self.0.extend(names.iter().map(|&name| Check {
+            name,
+            status: Status::Skipped,
+        }));
+        self.0
+    }
+}
+
+const CONFIG: &str = "configuration";
+const VAULT: &str = "vault readable";
+const GRAPH: &str = "graph index";
+const MODEL: &str = "embedding model";
+const SEARCH: &str = "sample search";
+
+/// Run every check against the loaded configuration.
+pub async fn run(config: Result<Config, ConfigError>) -> Vec<Check> {
+    let mut checks = Checks(Vec::new());
+
+    let Some(config) = checks.record(
+        CONFIG,
+        config
+            .map(|config| {
+                let detail = format!("vault at {}", config.vault_path.display());
+                (config, detail)
+            })
+            .map_err(|e| e.to_string()),
+    ) else {
+        return checks.skip(&[VAULT, GRAPH, MODEL, SEARCH]);
+    };
+
+    if checks.record(VAULT, check_vault(&config)).is_none() {
+        return checks.skip(&[GRAPH, MODEL, SEARCH]);
+    }
+
+    let Some(graph) = checks.record(GRAPH, check_graph(&config).await) else {
+        return checks.skip(&[MODEL, SEARCH]);
+    };
+
+    let Some(embeddings) = checks.record(MODEL, check_model(&config).await) else {
+        return checks.skip(&[SEARCH]);
+    };
+
+    checks.record(SEARCH, check_search(&config, &graph, &embeddings).await);
+    checks.0
+}
+
+/// Whether every check passed.
+pub fn passed(checks: &[Check]) -> bool {
+    checks.iter().all(|check| matches!(check.status, Status::Pass(_)))
+}
+
+/// Process exit status for the checks: 0 if all passed, 1 otherwise.
+pub fn exit_code(checks: &[Check]) -> i32 {
+    if passed(checks) { 0 } else { 1 }
+}
+
+/// One line per check, followed by a summary.
+pub fn report(checks: &[Check]) -> String {
+    let mut output = String::new();
+    for check in checks {
+        output.push_str(&format!("{}\n", check));
+    }
+    if passed(checks) {
+        output.push_str("All checks passed.\n");
+    } else {
+        output.push_str("Self-test failed.\n");
+    }
+    output
+}
+
+fn check_vault(config: &Config) -> Result<((), String), String> {
+    let entries = std::fs::read_dir(&config.vault_path)
+        .map_err(|e| format!("cannot read {}: {}", config.vault_path.display(), e))?;
+    Ok(((), format!("{} top-level entries", entries.count())))
+}
+
+async fn check_graph(config: &Config) -> Result<(GraphIndex, String), String> {
+    let mut graph = GraphIndex::new();
+    graph.set_private_roots(config.private_roots.clone());
+    graph.set_ignore(config.graph_ignore.clone());
+    graph
+        .initialize(&config.vault_path)
+        .await
+        .map_err(|e| format!("failed to scan vault: {}", e))?;
+    let notes = graph.all_paths().count();
+    let cycles = match graph.find_cycles().len() {
+        0 => "no link cycles".to_string(),
+        1 => "1 link cycle".to_string(),
+        n => format!("{} link cycles", n),
+    };
+    Ok((graph, format!("{} notes indexed, {}", notes, cycles)))
+}
+
+async fn check_model(config: &Config) -> Result<(EmbeddingManager, String), String> {
+    let embeddings = EmbeddingManager::from_config(config);
+    let started = Instant::now();
+    let (vector, tokens) = embeddings
+        .encode_uncached(SAMPLE_TEXT, false)
+        .await
+        .map_err(|e| format!("{:#}", e))?;
+
+    if vector.len() != EMBEDDING_DIM {
+        return Err(format!(
+            "unexpected embedding dimension {} (expected {})",
+            vector.len(),
+            EMBEDDING_DIM
+        ));
+    }
+    let detail = format!(
+        "encoded {} tokens into {} dimensions in {} ms",
+        tokens,
+        vector.len(),
+        started.elapsed().as_millis()
+    );
+    Ok((embeddings, detail))
+}
+
+async fn check_search(
+    config: &Config,
+    graph: &GraphIndex,
+    embeddings: &EmbeddingManager,
+) -> Result<((), String), String> {
+    let started = Instant::now();
+    let result = tools::search::execute(&config.vault_path, graph, embeddings, SAMPLE_QUERY, false, false)
+        .await
+        .map_err(|e| e.message.to_string())?;
+
+    if result.is_error == Some(true) {
+        return Err("search returned an error result".to_string());
+    }
+    Ok(((), format!("completed in {} ms", started.elapsed().as_millis())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter_schema::FrontmatterSchema;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    fn test_config(vault_path: &Path) -> Config {
+        Config {
+            vault_path: vault_path.to_path_buf(),
+            vault_name: "vault".to_string(),
+            embedding_query_prefix: String::new(),
+            embedding_document_prefix: String::new(),
+            frontmatter_schema: FrontmatterSchema::default(),
+            project_discovery: crate::projects::ProjectDiscoveryConfig::default(),
+            allow_external_symlinks: false,
+            tool_timeouts: HashMap::new(),
+            debug_tools: false,
+            protected_folders: Vec::new(),
+            private_roots: obsidian_fs::PrivateRoots::default(),
+            graph_ignore: crate::graph::GraphIgnore::default(),
+            embedding_batch_size: crate::config::DEFAULT_EMBEDDING_BATCH_SIZE,
+            embedding_concurrency: crate::config::DEFAULT_EMBEDDING_CONCURRENCY,
+            offline: true,
+            remember_budget: None,
+            embed_depth: crate::config::DEFAULT_EMBED_DEPTH,
+            storage_url: None,
+            storage_token: None,
+        }
+    }
+
+    /// A small vault with the test model copied in, so no download is needed.
+    ///
+    /// Requires the model in `crates/semantic-embeddings/models` (see
+    /// `scripts/download-model.sh`), so tests using it are ignored by default.
+    fn vault_with_model() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("Rust.md"), "Rust is a systems language. See [[Cargo]].").unwrap();
+        std::fs::write(temp_dir.path().join("Cargo.md"), "Cargo builds Rust projects.").unwrap();
+
+        let source = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../semantic-embeddings/models/all-MiniLM-L6-v2");
+        let dest = temp_dir.path().join(".obsidian/models/all-MiniLM-L6-v2");
+        std::fs::create_dir_all(&dest).unwrap();
+        for entry in std::fs::read_dir(&source).expect("test model not downloaded") {
+            let entry = entry.unwrap();
+            std::fs::copy(entry.path(), dest.join(entry.file_name())).unwrap();
+        }
+        temp_dir
+    }
+
+    #[tokio::test]
+    #[ignore] // Needs the test model. Run with: cargo test -p memory -- --ignored
+    async fn test_all_checks_pass_on_temp_vault() {
+        let vault = vault_with_model();
+
+        let checks = run(Ok(test_config(vault.path()))).await;
+
+        let names: Vec<&str> = checks.iter().map(|c| c.name).collect();
+        assert_eq!(names, vec![CONFIG, VAULT, GRAPH, MODEL, SEARCH]);
+        assert!(passed(&checks), "{}", report(&checks));
+        assert_eq!(exit_code(&checks), 0);
+        assert_eq!(checks[2].status, Status::Pass("2 notes indexed, no link cycles".to_string()));
+        assert!(report(&checks).ends_with("All checks passed.\n"));
+    }
+
+    #[tokio::test]
+    async fn test_graph_check_counts_link_cycles() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("A.md"), "[[B]]").unwrap();
+        std::fs::write(temp_dir.path().join("B.md"), "[[A]]").unwrap();
+        std::fs::write(temp_dir.path().join("Loop.md"), "[[Loop]]").unwrap();
+
+        let (_, detail) = check_graph(&test_config(temp_dir.path())).await.unwrap();
+        assert_eq!(detail, "3 notes indexed, 2 link cycles");
+    }
+
+    #[tokio::test]
+    async fn test_missing_vault_fails_and_skips_the_rest() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = test_config(&temp_dir.path().join("missing"));
+
+        let checks = run(Ok(config)).await;
+
+        assert!(!passed(&checks));
+        assert!(matches!(checks[1].status, Status::Fail(_)));
+        assert!(checks[2..].iter().all(|c| c.status == Status::Skipped));
+        assert!(report(&checks).contains("[FAIL] vault readable: cannot read"));
+    }
+
+    #[tokio::test]
+    async fn test_config_error_is_reported() {
+        let checks = run(Err(ConfigError::MissingVaultPath)).await;
+
+        assert_eq!(
+            checks[0].status,
+            Status::Fail("OBSIDIAN_VAULT_PATH environment variable not set".to_string())
+        );
+        assert_eq!(checks.len(), 5);
+    }
+
+    #[cfg(all(feature = "download-model", not(feature = "embedded-model")))]
+    #[tokio::test]
+    async fn test_missing_model_offline_fails_model_check() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("Note.md"), "content").unwrap();
+
+        let checks = run(Ok(test_config(temp_dir.path()))).await;
+
+        assert!(checks[..3].iter().all(|c| matches!(c.status, Status::Pass(_))));
+        assert_eq!(checks[2].status, Status::Pass("1 notes indexed, no link cycles".to_string()));
+        match &checks[3].status {
+            Status::Fail(reason) => assert!(reason.contains("not available offline")),
+            other => panic!("expected model check to fail, got {:?}", other),
+        }
+        assert_eq!(checks[4].status, Status::Skipped);
+        assert_ne!(exit_code(&checks), 0);
+        assert!(report(&checks).ends_with("Self-test failed.\n"));
+    }
+}
@@ -0,0 +1,165 @@
+//! The storage backend the server runs on, chosen by configuration.
+
+use super::FileStorage;
+#[cfg(feature = "remote-storage")]
+use super::HttpStorage;
+#[cfg(feature = "synced-storage")]
+use super::SyncedStorage;
+use super::traits::{NoteMetadata, Storage, StorageError, WriteResult};
+use crate::config::Config;
+#[cfg(feature = "synced-storage")]
+use sync_daemon::NativeFs;
+
+/// Storage backend selected by the server's configuration.
+///
+/// Notes are in the local vault unless `OBSIDIAN_STORAGE_URL` points at a
+/// vault served over HTTP. A local vault with sync enabled (a `.sync` folder)
+/// is written through its sync `Vault` when built with `synced-storage`.
+pub enum Backend {
+    /// The vault at `OBSIDIAN_VAULT_PATH`
+    File(FileStorage),
+    /// The vault served at `OBSIDIAN_STORAGE_URL`
+    #[cfg(feature = "remote-storage")]
+    Http(HttpStorage),
+    /// The vault at `OBSIDIAN_VAULT_PATH`, through its sync state
+    #[cfg(feature = "synced-storage")]
+    Synced(SyncedStorage<NativeFs>),
+}
+
+impl Backend {
+    /// Build the backend `config` selects.
+    ///
+    /// Fails only if the vault's sync state can't be loaded.
+    pub async fn from_config(config: &Config) -> Result<Self, Box<dyn std::error::Error>> {
+        #[cfg(feature = "remote-storage")]
+        if let Some(url) = &config.storage_url {
+            let storage = HttpStorage::new(url).protect_folders(config.protected_folders.clone());
+            return Ok(Self::Http(match &config.storage_token {
+                Some(token) => storage.with_token(token.clone()),
+                None => storage,
+            }));
+        }
+
+        #[cfg(feature = "synced-storage")]
+        if crate::tools::get_note_history::sync_enabled(&config.vault_path) {
+            let storage = Self::synced(&config.vault_path, config.protected_folders.clone()).await?;
+            return Ok(Self::Synced(storage));
+        }
+
+        Ok(Self::File(
+            FileStorage::new(config.vault_path.clone())
+                .allow_external_symlinks(config.allow_external_symlinks)
+                .protect_folders(config.protected_folders.clone()),
+        ))
+    }
+
+    /// Load the vault's sync state to write notes through.
+    ///
+    /// The server edits under its own peer ID, so its changes never share op
+    /// IDs with the sync daemon's. There's no transport to peers here: the
+    /// daemon picks the changes up from the vault and sends them on, so the
+    /// broadcast channel is left without a receiver.
+    #[cfg(feature = "synced-storage")]
+    async fn synced(
+        vault_path: &std::path::Path,
+        protected_folders: Vec<String>,
+    ) -> Result<SyncedStorage<NativeFs>, sync_core::VaultError> {
+        use std::sync::Arc;
+        use sync_core::{PeerId, Vault};
+
+        let fs = NativeFs::new(vault_path.to_path_buf());
+        let vault = Vault::load(fs, PeerId::generate()).await?;
+        let (broadcasts, _) = tokio::sync::mpsc::unbounded_channel();
+        let storage = SyncedStorage::new(Arc::new(vault), broadcasts).protect_folders(protected_folders);
+
+        tracing::info!("Writing notes through the sync vault as peer {}", storage.vault().peer_id());
+        Ok(storage)
+    }
+
+    /// The selected storage, which every `Storage` call goes to.
+    fn inner(&self) -> &dyn Storage {
+        match self {
+            Self::File(storage) => storage,
+            #[cfg(feature = "remote-storage")]
+            Self::Http(storage) => storage,
+            #[cfg(feature = "synced-storage")]
+            Self::Synced(storage) => storage,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for Backend {
+    async fn exists(&self, uri: &str) -> Result<bool, StorageError> {
+        self.inner().exists(uri).await
+    }
+
+    async fn read(&self, uri: &str) -> Result<(String, NoteMetadata), StorageError> {
+        self.inner().read(uri).await
+    }
+
+    async fn write(
+        &self,
+        uri: &str,
+        content: &str,
+        expected_hash: Option<&str>,
+    ) -> Result<WriteResult, StorageError> {
+        self.inner().write(uri, content, expected_hash).await
+    }
+
+    async fn delete(&self, uri: &str) -> Result<(), StorageError> {
+        self.inner().delete(uri).await
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        self.inner().list(prefix).await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.inner().rename(from, to).await
+    }
+
+    async fn create_folder(&self, path: &str) -> Result<(), StorageError> {
+        self.inner().create_folder(path).await
+    }
+
+    async fn append(&self, uri: &str, text: &str) -> Result<(), StorageError> {
+        self.inner().append(uri, text).await
+    }
+
+    fn check_writable(&self, uri: &str) -> Result<(), StorageError> {
+        self.inner().check_writable(uri)
+    }
+}
+
+#[cfg(all(test, feature = "synced-storage"))]
+mod tests {
+    use super::*;
+    use sync_core::{PeerId, Vault, document_sync_path};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_synced_backend_updates_sync_state() {
+        let temp = TempDir::new().unwrap();
+        Vault::init(NativeFs::new(temp.path().to_path_buf()), PeerId::generate())
+            .await
+            .unwrap();
+
+        let storage = Backend::synced(temp.path(), vec!["archive".to_string()])
+            .await
+            .unwrap();
+        let backend = Backend::Synced(storage);
+        backend.write("Note", "Hello", None).await.unwrap();
+
+        assert!(temp.path().join(document_sync_path("Note.md")).exists());
+        assert_eq!(backend.read("Note").await.unwrap().0, "Hello");
+        assert!(matches!(
+            backend.read("Missing").await,
+            Err(StorageError::NotFound { .. })
+        ));
+        assert!(matches!(
+            backend.write("archive/Note", "Hello", None).await,
+            Err(StorageError::ReadOnly { .. })
+        ));
+    }
+}
@@ -1,11 +1,12 @@
 //! Filesystem storage implementation.
 
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 use obsidian_fs::validate_relative_path;
 use rand::Rng;
 use sha2::{Digest, Sha256};
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 
 use super::traits::{NoteMetadata, Storage, StorageError, WriteResult};
 
@@ -15,17 +16,86 @@ use super::traits::{NoteMetadata, Storage, StorageError, WriteResult};
 /// Uses SHA-256 hashing for optimistic locking.
 pub struct FileStorage {
     vault_path: PathBuf,
+    /// Vault root with symlinks resolved, used for the jail check
+    canonical_root: PathBuf,
+    /// Allow symlinks inside the vault to point elsewhere on disk
+    allow_external_symlinks: bool,
+    /// Folders (relative to the vault, no trailing slash) that can't be modified
+    protected_folders: Vec<String>,
 }
 
 impl FileStorage {
     /// Create a new FileStorage for the given vault path.
     pub fn new(vault_path: PathBuf) -> Self {
-        Self { vault_path }
+        let canonical_root = std::fs::canonicalize(&vault_path).unwrap_or_else(|_| vault_path.clone());
+        Self {
+            vault_path,
+            canonical_root,
+            allow_external_symlinks: false,
+            protected_folders: Vec::new(),
+        }
+    }
+
+    /// Allow note paths that resolve outside the vault through symlinks.
+    ///
+    /// Off by default. Paths must still be relative and free of `..`.
+    pub fn allow_external_symlinks(mut self, allow: bool) -> Self {
+        self.allow_external_symlinks = allow;
+        self
+    }
+
+    /// Make notes under these folders read-only.
+    ///
+    /// Writes, deletes, and renames into or out of a protected folder fail
+    /// with `StorageError::ReadOnly`; reads are unaffected.
+    pub fn protect_folders(mut self, folders: Vec<String>) -> Self {
+        self.protected_folders = normalize_folders(folders);
+        self
+    }
+
+    /// Verify a vault path can't escape the vault root.
+    ///
+    /// The path must be lexically under the root, and once symlinks are
+    /// resolved it must still be under the canonical root. Paths that don't
+    /// exist yet (new notes) are checked through their nearest existing ancestor.
+    fn ensure_inside_vault(&self, uri: &str, path: &Path) -> Result<(), StorageError> {
+        let outside = || StorageError::OutsideVault {
+            uri: uri.to_string(),
+        };
+
+        let relative = path.strip_prefix(&self.vault_path).map_err(|_| outside())?;
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(outside());
+        }
+
+        if self.allow_external_symlinks {
+            return Ok(());
+        }
+
+        let mut existing = path;
+        let resolved = loop {
+            match std::fs::canonicalize(existing) {
+                Ok(resolved) => break resolved,
+                // A dangling symlink can't be resolved, so we can't tell where writes would land
+                Err(_) if std::fs::symlink_metadata(existing).is_ok() => return Err(outside()),
+                Err(_) => existing = existing.parent().ok_or_else(outside)?,
+            }
+        };
+
+        if resolved.starts_with(&self.canonical_root) {
+            Ok(())
+        } else {
+            Err(outside())
+        }
     }
 
     /// Convert a memory URI to a filesystem path.
     ///
-    /// Validates the path to prevent directory traversal attacks.
+    /// Validates the path to prevent directory traversal attacks, including
+    /// through symlinks (see `ensure_inside_vault`).
     fn uri_to_path(&self, uri: &str) -> Result<PathBuf, StorageError> {
         // Validate the URI as a relative path
         let clean = validate_relative_path(uri).map_err(|e| StorageError::InvalidPath {
@@ -40,7 +110,9 @@ impl FileStorage {
             format!("{}.md", clean)
         };
 
-        Ok(self.vault_path.join(with_ext))
+        let path = self.vault_path.join(with_ext);
+        self.ensure_inside_vault(uri, &path)?;
+        Ok(path)
     }
 
     /// Convert a filesystem path back to a memory URI.
@@ -124,6 +196,7 @@ impl Storage for FileStorage {
         content: &str,
         expected_hash: Option<&str>,
     ) -> Result<WriteResult, StorageError> {
+        self.check_writable(uri)?;
         let path = self.uri_to_path(uri)?;
 
         // Optimistic locking: check hash if provided
@@ -172,6 +245,7 @@ impl Storage for FileStorage {
     }
 
     async fn delete(&self, uri: &str) -> Result<(), StorageError> {
+        self.check_writable(uri)?;
         let path = self.uri_to_path(uri)?;
 
         fs::remove_file(&path).await.map_err(|e| {
@@ -194,7 +268,9 @@ impl Storage for FileStorage {
                 uri: prefix.to_string(),
                 reason: e.to_string(),
             })?;
-            self.vault_path.join(clean)
+            let dir = self.vault_path.join(clean);
+            self.ensure_inside_vault(prefix, &dir)?;
+            dir
         };
 
         if !search_dir.exists() {
@@ -207,6 +283,8 @@ impl Storage for FileStorage {
     }
 
     async fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.check_writable(from)?;
+        self.check_writable(to)?;
         let from_path = self.uri_to_path(from)?;
         let to_path = self.uri_to_path(to)?;
 
@@ -237,6 +315,77 @@ impl Storage for FileStorage {
         fs::rename(&from_path, &to_path).await?;
         Ok(())
     }
+
+    async fn create_folder(&self, path: &str) -> Result<(), StorageError> {
+        self.check_writable(path)?;
+        let clean = validate_relative_path(path).map_err(|e| StorageError::InvalidPath {
+            uri: path.to_string(),
+            reason: e.to_string(),
+        })?;
+        let dir = self.vault_path.join(clean);
+        self.ensure_inside_vault(path, &dir)?;
+
+        fs::create_dir_all(&dir).await?;
+        Ok(())
+    }
+
+    /// Appends in place, without rewriting the note.
+    async fn append(&self, uri: &str, text: &str) -> Result<(), StorageError> {
+        self.check_writable(uri)?;
+        let path = self.uri_to_path(uri)?;
+
+        if let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            return Err(StorageError::ParentNotFound {
+                uri: uri.to_string(),
+                parent: parent.to_path_buf(),
+            });
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        file.write_all(text.as_bytes()).await?;
+        file.flush().await?;
+        Ok(())
+    }
+
+    fn check_writable(&self, uri: &str) -> Result<(), StorageError> {
+        check_protected(&self.protected_folders, uri)
+    }
+}
+
+/// Trim slashes from protected folder names, dropping empty ones.
+pub(super) fn normalize_folders(folders: Vec<String>) -> Vec<String> {
+    folders
+        .into_iter()
+        .map(|f| f.trim_matches('/').to_string())
+        .filter(|f| !f.is_empty())
+        .collect()
+}
+
+/// Fail with `StorageError::ReadOnly` if `uri` is under one of `folders`.
+pub(super) fn check_protected(folders: &[String], uri: &str) -> Result<(), StorageError> {
+    let clean = validate_relative_path(uri).map_err(|e| StorageError::InvalidPath {
+        uri: uri.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    let protected = folders.iter().find(|folder| {
+        clean
+            .strip_prefix(folder.as_str())
+            .is_some_and(|rest| rest.starts_with('/'))
+    });
+    match protected {
+        Some(folder) => Err(StorageError::ReadOnly {
+            uri: uri.to_string(),
+            folder: folder.clone(),
+        }),
+        None => Ok(()),
+    }
 }
 
 impl FileStorage {
@@ -307,6 +456,17 @@ mod tests {
         assert_eq!(metadata.hash, result.hash);
     }
 
+    #[tokio::test]
+    async fn test_append_extends_and_creates() {
+        let (temp, storage) = create_test_storage().await;
+
+        storage.append("log", "first\n").await.unwrap();
+        storage.append("log", "second\n").await.unwrap();
+
+        let content = fs::read_to_string(temp.path().join("log.md")).await.unwrap();
+        assert_eq!(content, "first\nsecond\n");
+    }
+
     #[tokio::test]
     async fn test_read_nonexistent_returns_not_found() {
         let (_temp, storage) = create_test_storage().await;
@@ -451,6 +611,88 @@ mod tests {
         assert!(matches!(result, Err(StorageError::InvalidPath { .. })));
     }
 
+    #[tokio::test]
+    async fn test_rejects_absolute_paths() {
+        let (temp, storage) = create_test_storage().await;
+
+        let result = storage.read("//etc/passwd").await;
+        assert!(matches!(
+            result,
+            Err(StorageError::InvalidPath { .. } | StorageError::OutsideVault { .. })
+        ));
+
+        // Joining an absolute path replaces the vault root entirely
+        let escaped = temp.path().join(Path::new("/etc/passwd"));
+        let result = storage.ensure_inside_vault("/etc/passwd", &escaped);
+        assert!(matches!(result, Err(StorageError::OutsideVault { .. })));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_rejects_symlink_outside_vault() {
+        let (temp, storage) = create_test_storage().await;
+        let outside = TempDir::new().unwrap();
+        let secret = outside.path().join("secret.md");
+        fs::write(&secret, "secret").await.unwrap();
+        std::os::unix::fs::symlink(&secret, temp.path().join("link.md")).unwrap();
+
+        let result = storage.read("link").await;
+        assert!(matches!(result, Err(StorageError::OutsideVault { .. })));
+
+        let result = storage.write("link", "overwritten", None).await;
+        assert!(matches!(result, Err(StorageError::OutsideVault { .. })));
+
+        let result = storage.delete("link").await;
+        assert!(matches!(result, Err(StorageError::OutsideVault { .. })));
+
+        assert_eq!(fs::read_to_string(&secret).await.unwrap(), "secret");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_rejects_new_note_in_symlinked_dir_outside_vault() {
+        let (temp, storage) = create_test_storage().await;
+        let outside = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), temp.path().join("external")).unwrap();
+
+        let result = storage.write("external/new", "content", None).await;
+        assert!(matches!(result, Err(StorageError::OutsideVault { .. })));
+        assert!(!outside.path().join("new.md").exists());
+
+        let result = storage.list("external").await;
+        assert!(matches!(result, Err(StorageError::OutsideVault { .. })));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_within_vault_allowed() {
+        let (temp, storage) = create_test_storage().await;
+        storage.write("target", "content", None).await.unwrap();
+        std::os::unix::fs::symlink(temp.path().join("target.md"), temp.path().join("alias.md"))
+            .unwrap();
+
+        let (content, _) = storage.read("alias").await.unwrap();
+        assert_eq!(content, "content");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_external_symlinks_allowed_when_configured() {
+        let temp = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp.path().to_path_buf()).allow_external_symlinks(true);
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("shared.md"), "shared").await.unwrap();
+        std::os::unix::fs::symlink(outside.path().join("shared.md"), temp.path().join("shared.md"))
+            .unwrap();
+
+        let (content, _) = storage.read("shared").await.unwrap();
+        assert_eq!(content, "shared");
+
+        // Traversal is still rejected
+        let result = storage.read("../etc/passwd").await;
+        assert!(matches!(result, Err(StorageError::InvalidPath { .. })));
+    }
+
     #[tokio::test]
     async fn test_handles_md_extension_in_uri() {
         let (_temp, storage) = create_test_storage().await;
@@ -462,4 +704,51 @@ mod tests {
         let (content, _) = storage.read("test.md").await.unwrap();
         assert_eq!(content, "content");
     }
+
+    #[tokio::test]
+    async fn test_protected_folder_rejects_writes() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join("archive")).await.unwrap();
+        fs::write(temp.path().join("archive/old.md"), "old").await.unwrap();
+        let storage = FileStorage::new(temp.path().to_path_buf())
+            .protect_folders(vec!["archive/".to_string()]);
+
+        let result = storage.write("archive/new", "content", None).await;
+        assert!(matches!(
+            result,
+            Err(StorageError::ReadOnly { ref folder, .. }) if folder == "archive"
+        ));
+        assert!(!temp.path().join("archive/new.md").exists());
+
+        assert!(matches!(
+            storage.delete("archive/old").await,
+            Err(StorageError::ReadOnly { .. })
+        ));
+        assert!(matches!(
+            storage.rename("archive/old", "old").await,
+            Err(StorageError::ReadOnly { .. })
+        ));
+        assert!(matches!(
+            storage.rename("elsewhere", "archive/elsewhere").await,
+            Err(StorageError::ReadOnly { .. })
+        ));
+
+        // Reads still work
+        let (content, _) = storage.read("archive/old").await.unwrap();
+        assert_eq!(content, "old");
+    }
+
+    #[tokio::test]
+    async fn test_unprotected_folders_still_writable() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join("archived")).await.unwrap();
+        let storage = FileStorage::new(temp.path().to_path_buf())
+            .protect_folders(vec!["archive".to_string()]);
+
+        // A shared name prefix isn't the protected folder
+        storage.write("archived/note", "content", None).await.unwrap();
+        storage.write("note", "content", None).await.unwrap();
+        assert!(temp.path().join("archived/note.md").exists());
+        assert!(temp.path().join("note.md").exists());
+    }
 }
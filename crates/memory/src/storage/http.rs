@@ -0,0 +1,448 @@
+//! HTTP storage implementation for remote vaults.
+//!
+//! Talks to a vault served over HTTP (e.g. a home server) with a small REST API,
+//! so the MCP tools can run on a machine that doesn't hold the vault:
+//!
+//! | Operation | Request | Success | Errors |
+//! |-----------|---------|---------|--------|
+//! | exists | `HEAD /notes/{uri}` | 200 | 404 |
+//! | read | `GET /notes/{uri}` | 200, body is the note | 404 |
+//! | write | `PUT /notes/{uri}`, optional `If-Match: {hash}` | 200/201/204 | 404 (missing parent), 412 with `ETag: {actual hash}` |
+//! | delete | `DELETE /notes/{uri}` | 200/204 | 404 |
+//! | list | `GET /notes?prefix={prefix}` | 200, JSON array of URIs | |
+//! | rename | `POST /rename`, JSON `{"from", "to"}` | 200/204 | 404, 409 |
+//! | create folder | `PUT /folders/{path}`, creating missing parents | 200/201/204 | |
+//!
+//! URIs are sent without the `.md` extension and paths one percent-encoded
+//! segment per component. Any endpoint may answer 400 with a reason for invalid paths.
+
+use obsidian_fs::validate_relative_path;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+
+use super::content_hash::ContentHash;
+use super::file::{check_protected, normalize_folders};
+use super::traits::{NoteMetadata, Storage, StorageError, WriteResult};
+
+/// Remote storage backend speaking the REST API above.
+pub struct HttpStorage {
+    client: Client,
+    /// Base URL without trailing slash (e.g. "https://home.example/vault")
+    base_url: String,
+    /// Optional bearer token sent with every request
+    token: Option<String>,
+    /// Folders (relative to the vault, no trailing slash) that can't be modified
+    protected_folders: Vec<String>,
+}
+
+impl HttpStorage {
+    /// Create a new HttpStorage for the vault served at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: None,
+            protected_folders: Vec::new(),
+        }
+    }
+
+    /// Authenticate requests with a bearer token.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Make notes under these folders read-only, as `FileStorage::protect_folders` does.
+    ///
+    /// Checked before any request is sent.
+    pub fn protect_folders(mut self, folders: Vec<String>) -> Self {
+        self.protected_folders = normalize_folders(folders);
+        self
+    }
+
+    /// URL for a note, validating the URI the same way `FileStorage` does.
+    fn note_url(&self, uri: &str) -> Result<String, StorageError> {
+        let clean = Self::clean_path(uri)?;
+        let clean = clean.strip_suffix(".md").unwrap_or(&clean);
+        Ok(self.path_url("notes", clean))
+    }
+
+    /// URL for a folder, validating the path the same way `FileStorage` does.
+    fn folder_url(&self, path: &str) -> Result<String, StorageError> {
+        let clean = Self::clean_path(path)?;
+        Ok(self.path_url("folders", &clean))
+    }
+
+    /// Validate a vault-relative path, as `validate_relative_path` does for `FileStorage`.
+    fn clean_path(path: &str) -> Result<String, StorageError> {
+        validate_relative_path(path).map_err(|e| StorageError::InvalidPath {
+            uri: path.to_string(),
+            reason: e.to_string(),
+        })
+    }
+
+    /// URL under `endpoint` for a validated path, one encoded segment per component.
+    fn path_url(&self, endpoint: &str, clean: &str) -> String {
+        let encoded: Vec<_> = clean.split('/').map(urlencoding::encode).collect();
+        format!("{}/{}/{}", self.base_url, endpoint, encoded.join("/"))
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send(request: RequestBuilder) -> Result<Response, StorageError> {
+        request.send().await.map_err(|e| StorageError::IoError {
+            message: e.to_string(),
+        })
+    }
+
+    /// Map an error response to the matching `StorageError`.
+    async fn error_for(uri: &str, response: Response) -> StorageError {
+        let status = response.status();
+        match status {
+            StatusCode::NOT_FOUND => StorageError::NotFound {
+                uri: uri.to_string(),
+            },
+            StatusCode::CONFLICT => StorageError::AlreadyExists {
+                uri: uri.to_string(),
+            },
+            StatusCode::BAD_REQUEST => StorageError::InvalidPath {
+                uri: uri.to_string(),
+                reason: response.text().await.unwrap_or_default(),
+            },
+            _ => StorageError::IoError {
+                message: format!(
+                    "Remote storage returned {} for '{}': {}",
+                    status,
+                    uri,
+                    response.text().await.unwrap_or_default()
+                ),
+            },
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for HttpStorage {
+    async fn exists(&self, uri: &str) -> Result<bool, StorageError> {
+        let url = self.note_url(uri)?;
+        let response = Self::send(self.request(Method::HEAD, &url)).await?;
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            StatusCode::NOT_FOUND => Ok(false),
+            _ => Err(Self::error_for(uri, response).await),
+        }
+    }
+
+    async fn read(&self, uri: &str) -> Result<(String, NoteMetadata), StorageError> {
+        let url = self.note_url(uri)?;
+        let response = Self::send(self.request(Method::GET, &url)).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_for(uri, response).await);
+        }
+
+        let content = response.text().await.map_err(|e| StorageError::IoError {
+            message: e.to_string(),
+        })?;
+        let hash = ContentHash::from_content(&content).as_str().to_string();
+        Ok((
+            content,
+            NoteMetadata {
+                hash,
+                uri: uri.to_string(),
+            },
+        ))
+    }
+
+    async fn write(
+        &self,
+        uri: &str,
+        content: &str,
+        expected_hash: Option<&str>,
+    ) -> Result<WriteResult, StorageError> {
+        self.check_writable(uri)?;
+        let url = self.note_url(uri)?;
+        let mut request = self
+            .request(Method::PUT, &url)
+            .header("content-type", "text/markdown; charset=utf-8")
+            .body(content.to_string());
+        if let Some(expected) = expected_hash {
+            request = request.header("if-match", expected);
+        }
+
+        let response = Self::send(request).await?;
+        match response.status() {
+            status if status.is_success() => Ok(WriteResult {
+                hash: ContentHash::from_content(content).as_str().to_string(),
+                uri: uri.to_string(),
+            }),
+            StatusCode::PRECONDITION_FAILED => {
+                let actual = response
+                    .headers()
+                    .get("etag")
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.trim_matches('"').to_string())
+                    .unwrap_or_else(|| "(file does not exist)".to_string());
+                Err(StorageError::HashMismatch {
+                    uri: uri.to_string(),
+                    expected: expected_hash.unwrap_or_default().to_string(),
+                    actual,
+                })
+            }
+            StatusCode::NOT_FOUND => {
+                let parent = uri.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("");
+                Err(StorageError::ParentNotFound {
+                    uri: uri.to_string(),
+                    parent: parent.into(),
+                })
+            }
+            _ => Err(Self::error_for(uri, response).await),
+        }
+    }
+
+    async fn delete(&self, uri: &str) -> Result<(), StorageError> {
+        self.check_writable(uri)?;
+        let url = self.note_url(uri)?;
+        let response = Self::send(self.request(Method::DELETE, &url)).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_for(uri, response).await);
+        }
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        if !prefix.is_empty() {
+            validate_relative_path(prefix).map_err(|e| StorageError::InvalidPath {
+                uri: prefix.to_string(),
+                reason: e.to_string(),
+            })?;
+        }
+
+        let url = format!("{}/notes?prefix={}", self.base_url, urlencoding::encode(prefix));
+        let response = Self::send(self.request(Method::GET, &url)).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_for(prefix, response).await);
+        }
+
+        let body = response.text().await.map_err(|e| StorageError::IoError {
+            message: e.to_string(),
+        })?;
+        serde_json::from_str(&body).map_err(|e| StorageError::IoError {
+            message: format!("Invalid note list from remote storage: {}", e),
+        })
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.check_writable(from)?;
+        self.check_writable(to)?;
+        // Validate both URIs before sending
+        self.note_url(from)?;
+        self.note_url(to)?;
+
+        let body = serde_json::json!({ "from": from, "to": to }).to_string();
+        let url = format!("{}/rename", self.base_url);
+        let request = self
+            .request(Method::POST, &url)
+            .header("content-type", "application/json")
+            .body(body);
+
+        let response = Self::send(request).await?;
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            StatusCode::NOT_FOUND => Err(StorageError::NotFound {
+                uri: from.to_string(),
+            }),
+            StatusCode::CONFLICT => Err(StorageError::AlreadyExists {
+                uri: to.to_string(),
+            }),
+            _ => Err(Self::error_for(from, response).await),
+        }
+    }
+
+    async fn create_folder(&self, path: &str) -> Result<(), StorageError> {
+        self.check_writable(path)?;
+        let url = self.folder_url(path)?;
+        let response = Self::send(self.request(Method::PUT, &url)).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_for(path, response).await);
+        }
+        Ok(())
+    }
+
+    fn check_writable(&self, uri: &str) -> Result<(), StorageError> {
+        check_protected(&self.protected_folders, uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, Request, Response};
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    type Notes = Arc<Mutex<BTreeMap<String, String>>>;
+
+    /// Serve the REST API from an in-memory map of URI -> content.
+    fn handle(notes: &Notes, request: &Request) -> Response {
+        let mut notes = notes.lock().unwrap();
+
+        if request.method == "POST" && request.path == "/rename" {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let (from, to) = (body["from"].as_str().unwrap(), body["to"].as_str().unwrap());
+            if notes.contains_key(to) {
+                return Response::new(409);
+            }
+            return match notes.remove(from) {
+                Some(content) => {
+                    notes.insert(to.to_string(), content);
+                    Response::new(204)
+                }
+                None => Response::new(404),
+            };
+        }
+
+        if request.method == "PUT" && request.path.starts_with("/folders/") {
+            return Response::new(201);
+        }
+
+        if let Some(query) = request.path.strip_prefix("/notes?prefix=") {
+            let prefix = urlencoding::decode(query).unwrap().to_string();
+            let uris: Vec<&String> = notes.keys().filter(|uri| uri.starts_with(&prefix)).collect();
+            return Response::new(200).body(serde_json::to_string(&uris).unwrap());
+        }
+
+        let uri = urlencoding::decode(request.path.strip_prefix("/notes/").unwrap())
+            .unwrap()
+            .to_string();
+        match request.method.as_str() {
+            "HEAD" | "GET" => match notes.get(&uri) {
+                Some(content) => Response::new(200).body(content.clone()),
+                None => Response::new(404),
+            },
+            "PUT" => {
+                if let Some(expected) = request.header("if-match") {
+                    let actual = notes.get(&uri).map(|c| ContentHash::from_content(c));
+                    if actual.as_ref().map(|h| h.as_str()) != Some(expected) {
+                        let etag = actual.map(|h| format!("\"{}\"", h.as_str())).unwrap_or_default();
+                        return Response::new(412).header("etag", etag);
+                    }
+                }
+                notes.insert(uri, request.text());
+                Response::new(204)
+            }
+            "DELETE" => match notes.remove(&uri) {
+                Some(_) => Response::new(204),
+                None => Response::new(404),
+            },
+            _ => Response::new(405),
+        }
+    }
+
+    async fn create_test_env() -> (Notes, HttpStorage) {
+        let notes: Notes = Arc::new(Mutex::new(BTreeMap::new()));
+        let served = Arc::clone(&notes);
+        let url = test_server::serve(move |request| handle(&served, request)).await;
+        (notes, HttpStorage::new(&url))
+    }
+
+    #[tokio::test]
+    async fn test_write_and_read() {
+        let (notes, storage) = create_test_env().await;
+
+        let result = storage.write("knowledge/My Note", "# Hello", None).await.unwrap();
+        assert_eq!(result.hash, ContentHash::from_content("# Hello").as_str());
+        assert_eq!(notes.lock().unwrap()["knowledge/My Note"], "# Hello");
+
+        let (content, metadata) = storage.read("knowledge/My Note.md").await.unwrap();
+        assert_eq!(content, "# Hello");
+        assert_eq!(metadata.hash, result.hash);
+        assert!(storage.exists("knowledge/My Note").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_read_missing_note() {
+        let (_notes, storage) = create_test_env().await;
+
+        assert!(!storage.exists("missing").await.unwrap());
+        let err = storage.read("missing").await.unwrap_err();
+        assert!(matches!(err, StorageError::NotFound { .. }), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_write_with_stale_hash() {
+        let (notes, storage) = create_test_env().await;
+        notes.lock().unwrap().insert("note".into(), "current".into());
+
+        let err = storage.write("note", "new", Some("stale")).await.unwrap_err();
+        match err {
+            StorageError::HashMismatch { expected, actual, .. } => {
+                assert_eq!(expected, "stale");
+                assert_eq!(actual, ContentHash::from_content("current").as_str());
+            }
+            other => panic!("expected HashMismatch, got {:?}", other),
+        }
+        assert_eq!(notes.lock().unwrap()["note"], "current");
+
+        let hash = ContentHash::from_content("current");
+        storage.write("note", "new", Some(hash.as_str())).await.unwrap();
+        assert_eq!(notes.lock().unwrap()["note"], "new");
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let (notes, storage) = create_test_env().await;
+        notes.lock().unwrap().insert("note".into(), "content".into());
+
+        storage.delete("note").await.unwrap();
+        assert!(notes.lock().unwrap().is_empty());
+
+        let err = storage.delete("note").await.unwrap_err();
+        assert!(matches!(err, StorageError::NotFound { .. }), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_list_and_rename() {
+        let (notes, storage) = create_test_env().await;
+        {
+            let mut notes = notes.lock().unwrap();
+            notes.insert("knowledge/A".into(), "a".into());
+            notes.insert("knowledge/B".into(), "b".into());
+            notes.insert("Log".into(), "log".into());
+        }
+
+        assert_eq!(storage.list("knowledge/").await.unwrap(), vec!["knowledge/A", "knowledge/B"]);
+
+        storage.rename("knowledge/A", "archive/A").await.unwrap();
+        assert_eq!(notes.lock().unwrap()["archive/A"], "a");
+
+        let err = storage.rename("knowledge/B", "Log").await.unwrap_err();
+        assert!(matches!(err, StorageError::AlreadyExists { .. }), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_create_folder() {
+        let (_notes, storage) = create_test_env().await;
+        storage.create_folder("log/2026").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_paths_without_request() {
+        let storage = HttpStorage::new("http://127.0.0.1:9");
+
+        let err = storage.read("../outside").await.unwrap_err();
+        assert!(matches!(err, StorageError::InvalidPath { .. }), "{:?}", err);
+        let err = storage.write("a/../../x", "x", None).await.unwrap_err();
+        assert!(matches!(err, StorageError::InvalidPath { .. }), "{:?}", err);
+        let err = storage.create_folder("../outside").await.unwrap_err();
+        assert!(matches!(err, StorageError::InvalidPath { .. }), "{:?}", err);
+
+        let storage = storage.protect_folders(vec!["archive/".into()]);
+        let err = storage.write("archive/old", "x", None).await.unwrap_err();
+        assert!(matches!(err, StorageError::ReadOnly { .. }), "{:?}", err);
+    }
+}
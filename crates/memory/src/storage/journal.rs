@@ -0,0 +1,477 @@
+//! Undo journal for storage mutations.
+//!
+//! `JournaledStorage` wraps another backend and remembers what each write,
+//! delete, and rename replaced, grouped by the tool call that made them (a
+//! MoveNote that also rewrites backlinks is one operation). The most recent
+//! operation can be reverted with `undo_last`. History is in memory and kept
+//! per server session, capped at `UNDO_HISTORY` operations.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use super::traits::{NoteMetadata, Storage, StorageError, WriteResult};
+
+/// Operations kept for undo, oldest dropped first
+pub const UNDO_HISTORY: usize = 20;
+
+/// A single mutation and what it replaced.
+#[derive(Debug, Clone)]
+enum Change {
+    /// `uri` was written; `previous` is None when the note was created
+    Written {
+        uri: String,
+        previous: Option<String>,
+        /// Hash of the written content, to detect later edits
+        hash: String,
+    },
+    Deleted { uri: String, content: String },
+    Renamed { from: String, to: String },
+}
+
+/// Mutations made by one tool call.
+#[derive(Debug)]
+struct Operation {
+    label: String,
+    changes: Vec<Change>,
+}
+
+/// Summary of a reverted operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoneOperation {
+    /// Label passed to `begin_operation` (the tool name)
+    pub label: String,
+    /// Notes restored, in the order they were reverted
+    pub restored: Vec<String>,
+}
+
+/// Storage wrapper that records prior content so changes can be undone.
+pub struct JournaledStorage<S> {
+    inner: Arc<S>,
+    history: Mutex<VecDeque<Operation>>,
+}
+
+impl<S: Storage> JournaledStorage<S> {
+    pub fn new(inner: Arc<S>) -> Self {
+        Self {
+            inner,
+            history: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Start a new operation; later changes are undone together.
+    ///
+    /// Called once per tool call. Overlapping calls in the same session share
+    /// whichever operation was started last.
+    pub fn begin_operation(&self, label: &str) {
+        let mut history = self.history.lock().unwrap();
+        if history.back().is_some_and(|op| op.changes.is_empty()) {
+            history.pop_back();
+        }
+        if history.len() == UNDO_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(Operation {
+            label: label.to_string(),
+            changes: Vec::new(),
+        });
+    }
+
+    fn record(&self, change: Change) {
+        let mut history = self.history.lock().unwrap();
+        match history.back_mut() {
+            Some(op) => op.changes.push(change),
+            None => history.push_back(Operation {
+                label: "unknown".to_string(),
+                changes: vec![change],
+            }),
+        }
+    }
+
+    /// Revert the most recent operation that changed anything.
+    ///
+    /// Returns `None` when there is nothing to undo. Fails without touching
+    /// anything if a note the operation wrote has been edited since, so undo
+    /// never clobbers newer changes; the operation stays undoable.
+    pub async fn undo_last(&self) -> Result<Option<UndoneOperation>, StorageError> {
+        let operation = {
+            let mut history = self.history.lock().unwrap();
+            loop {
+                match history.pop_back() {
+                    Some(op) if op.changes.is_empty() => continue,
+                    other => break other,
+                }
+            }
+        };
+        let Some(operation) = operation else {
+            return Ok(None);
+        };
+
+        if let Err(e) = self.check_revertible(&operation.changes).await {
+            self.history.lock().unwrap().push_back(operation);
+            return Err(e);
+        }
+
+        let mut restored = Vec::new();
+        for change in operation.changes.iter().rev() {
+            restored.push(self.revert(change).await?);
+        }
+
+        Ok(Some(UndoneOperation {
+            label: operation.label,
+            restored,
+        }))
+    }
+
+    /// Verify every change can be reverted, newest first.
+    ///
+    /// Each change is checked against the vault as it will be once the
+    /// changes after it are reverted, so a MoveNote that left a redirect
+    /// stub at the old path (written after the rename) still checks out.
+    /// Notes an earlier revert restores are taken as restored; only notes
+    /// the operation left in place are compared with the vault.
+    async fn check_revertible(&self, changes: &[Change]) -> Result<(), StorageError> {
+        // URI -> whether it exists once the later changes are reverted
+        let mut reverted: HashMap<&str, bool> = HashMap::new();
+
+        for change in changes.iter().rev() {
+            match change {
+                Change::Written { uri, hash, previous } => {
+                    match reverted.get(uri.as_str()) {
+                        Some(true) => {}
+                        Some(false) => return Err(StorageError::NotFound { uri: uri.clone() }),
+                        None => {
+                            let (_, metadata) = self.inner.read(uri).await?;
+                            if &metadata.hash != hash {
+                                return Err(StorageError::HashMismatch {
+                                    uri: uri.clone(),
+                                    expected: hash.clone(),
+                                    actual: metadata.hash,
+                                });
+                            }
+                        }
+                    }
+                    reverted.insert(uri, previous.is_some());
+                }
+                Change::Deleted { uri, .. } => {
+                    if self.exists_after(&reverted, uri).await? {
+                        return Err(StorageError::AlreadyExists { uri: uri.clone() });
+                    }
+                    reverted.insert(uri, true);
+                }
+                Change::Renamed { from, to } => {
+                    if !self.exists_after(&reverted, to).await? {
+                        return Err(StorageError::NotFound { uri: to.clone() });
+                    }
+                    if self.exists_after(&reverted, from).await? {
+                        return Err(StorageError::AlreadyExists { uri: from.clone() });
+                    }
+                    reverted.insert(to, false);
+                    reverted.insert(from, true);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `uri` exists once the changes recorded in `reverted` are undone.
+    async fn exists_after(
+        &self,
+        reverted: &HashMap<&str, bool>,
+        uri: &str,
+    ) -> Result<bool, StorageError> {
+        match reverted.get(uri) {
+            Some(exists) => Ok(*exists),
+            None => self.inner.exists(uri).await,
+        }
+    }
+
+    /// Undo one change, returning the URI it restored.
+    async fn revert(&self, change: &Change) -> Result<String, StorageError> {
+        match change {
+            Change::Written {
+                uri,
+                previous: Some(content),
+                ..
+            }
+            | Change::Deleted { uri, content } => {
+                self.inner.write(uri, content, None).await?;
+                Ok(uri.clone())
+            }
+            Change::Written {
+                uri, previous: None, ..
+            } => {
+                self.inner.delete(uri).await?;
+                Ok(uri.clone())
+            }
+            Change::Renamed { from, to } => {
+                self.inner.rename(to, from).await?;
+                Ok(from.clone())
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: Storage> Storage for JournaledStorage<S> {
+    async fn exists(&self, uri: &str) -> Result<bool, StorageError> {
+        self.inner.exists(uri).await
+    }
+
+    async fn read(&self, uri: &str) -> Result<(String, NoteMetadata), StorageError> {
+        self.inner.read(uri).await
+    }
+
+    async fn write(
+        &self,
+        uri: &str,
+        content: &str,
+        expected_hash: Option<&str>,
+    ) -> Result<WriteResult, StorageError> {
+        let previous = match self.inner.read(uri).await {
+            Ok((content, _)) => Some(content),
+            Err(StorageError::NotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+
+        let result = self.inner.write(uri, content, expected_hash).await?;
+        self.record(Change::Written {
+            uri: uri.to_string(),
+            previous,
+            hash: result.hash.clone(),
+        });
+        Ok(result)
+    }
+
+    async fn delete(&self, uri: &str) -> Result<(), StorageError> {
+        let (content, _) = self.inner.read(uri).await?;
+        self.inner.delete(uri).await?;
+        self.record(Change::Deleted {
+            uri: uri.to_string(),
+            content,
+        });
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        self.inner.list(prefix).await
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.inner.rename(from, to).await?;
+        self.record(Change::Renamed {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+        Ok(())
+    }
+
+    async fn append(&self, uri: &str, text: &str) -> Result<(), StorageError> {
+        let previous = match self.inner.read(uri).await {
+            Ok((content, _)) => Some(content),
+            Err(StorageError::NotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+
+        self.inner.append(uri, text).await?;
+        let (_, meta) = self.inner.read(uri).await?;
+        self.record(Change::Written {
+            uri: uri.to_string(),
+            previous,
+            hash: meta.hash,
+        });
+        Ok(())
+    }
+
+    /// Not journaled: undoing the notes written into a folder leaves it empty, not gone.
+    async fn create_folder(&self, path: &str) -> Result<(), StorageError> {
+        self.inner.create_folder(path).await
+    }
+
+    fn check_writable(&self, uri: &str) -> Result<(), StorageError> {
+        self.inner.check_writable(uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    fn create_storage() -> (TempDir, JournaledStorage<FileStorage>) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = JournaledStorage::new(Arc::new(FileStorage::new(temp_dir.path().to_path_buf())));
+        (temp_dir, storage)
+    }
+
+    #[tokio::test]
+    async fn test_undo_write_restores_original_content() {
+        let (temp_dir, storage) = create_storage();
+        fs::write(temp_dir.path().join("note.md"), "original").await.unwrap();
+
+        storage.begin_operation("write_note");
+        storage.write("note", "rewritten", None).await.unwrap();
+
+        let undone = storage.undo_last().await.unwrap().unwrap();
+        assert_eq!(undone.label, "write_note");
+        assert_eq!(undone.restored, vec!["note"]);
+
+        let content = fs::read_to_string(temp_dir.path().join("note.md")).await.unwrap();
+        assert_eq!(content, "original");
+    }
+
+    #[tokio::test]
+    async fn test_undo_create_removes_note() {
+        let (temp_dir, storage) = create_storage();
+
+        storage.begin_operation("write_note");
+        storage.write("new", "content", None).await.unwrap();
+        storage.undo_last().await.unwrap().unwrap();
+
+        assert!(!temp_dir.path().join("new.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_undo_append_restores_original_content() {
+        let (temp_dir, storage) = create_storage();
+        fs::write(temp_dir.path().join("note.md"), "original").await.unwrap();
+
+        storage.begin_operation("log");
+        storage.append("note", "\nadded").await.unwrap();
+        storage.undo_last().await.unwrap().unwrap();
+
+        let content = fs::read_to_string(temp_dir.path().join("note.md")).await.unwrap();
+        assert_eq!(content, "original");
+    }
+
+    #[tokio::test]
+    async fn test_undo_delete_restores_file() {
+        let (temp_dir, storage) = create_storage();
+        fs::write(temp_dir.path().join("note.md"), "keep me").await.unwrap();
+
+        storage.begin_operation("delete_note");
+        storage.delete("note").await.unwrap();
+        assert!(!temp_dir.path().join("note.md").exists());
+
+        storage.undo_last().await.unwrap().unwrap();
+        let content = fs::read_to_string(temp_dir.path().join("note.md")).await.unwrap();
+        assert_eq!(content, "keep me");
+    }
+
+    #[tokio::test]
+    async fn test_undo_reverts_whole_operation() {
+        let (temp_dir, storage) = create_storage();
+        fs::write(temp_dir.path().join("Old.md"), "note").await.unwrap();
+        fs::write(temp_dir.path().join("Index.md"), "[[Old]]").await.unwrap();
+
+        // A move that also rewrites a backlink
+        storage.begin_operation("move_note");
+        storage.write("Index", "[[New]]", None).await.unwrap();
+        storage.rename("Old", "New").await.unwrap();
+
+        let undone = storage.undo_last().await.unwrap().unwrap();
+        assert_eq!(undone.restored, vec!["Old", "Index"]);
+        assert!(temp_dir.path().join("Old.md").exists());
+        assert!(!temp_dir.path().join("New.md").exists());
+        let index = fs::read_to_string(temp_dir.path().join("Index.md")).await.unwrap();
+        assert_eq!(index, "[[Old]]");
+    }
+
+    #[tokio::test]
+    async fn test_undo_rename_with_stub_at_old_path() {
+        let (temp_dir, storage) = create_storage();
+        fs::write(temp_dir.path().join("Old.md"), "note").await.unwrap();
+
+        // A move that leaves a redirect stub where the note was
+        storage.begin_operation("move_note");
+        storage.rename("Old", "New").await.unwrap();
+        storage.write("Old", "Moved to [[New]]", None).await.unwrap();
+
+        let undone = storage.undo_last().await.unwrap().unwrap();
+        assert_eq!(undone.restored, vec!["Old", "Old"]);
+        assert!(!temp_dir.path().join("New.md").exists());
+        let content = fs::read_to_string(temp_dir.path().join("Old.md")).await.unwrap();
+        assert_eq!(content, "note");
+    }
+
+    #[tokio::test]
+    async fn test_undo_write_then_rename_of_same_note() {
+        let (temp_dir, storage) = create_storage();
+        fs::write(temp_dir.path().join("B.md"), "See [[B]]").await.unwrap();
+
+        // A note that links to itself is rewritten, then moved
+        storage.begin_operation("move_note");
+        storage.write("B", "See [[C]]", None).await.unwrap();
+        storage.rename("B", "C").await.unwrap();
+
+        storage.undo_last().await.unwrap().unwrap();
+        assert!(!temp_dir.path().join("C.md").exists());
+        let content = fs::read_to_string(temp_dir.path().join("B.md")).await.unwrap();
+        assert_eq!(content, "See [[B]]");
+    }
+
+    #[tokio::test]
+    async fn test_undo_refuses_when_stub_changed_since() {
+        let (temp_dir, storage) = create_storage();
+        fs::write(temp_dir.path().join("Old.md"), "note").await.unwrap();
+
+        storage.begin_operation("move_note");
+        storage.rename("Old", "New").await.unwrap();
+        storage.write("Old", "Moved to [[New]]", None).await.unwrap();
+        fs::write(temp_dir.path().join("Old.md"), "user edit").await.unwrap();
+
+        let err = storage.undo_last().await.unwrap_err();
+        assert!(matches!(err, StorageError::HashMismatch { .. }));
+        assert!(temp_dir.path().join("New.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_undo_steps_back_through_operations() {
+        let (temp_dir, storage) = create_storage();
+
+        storage.begin_operation("write_note");
+        storage.write("note", "first", None).await.unwrap();
+        storage.begin_operation("edit_note");
+        storage.write("note", "second", None).await.unwrap();
+        // Read-only calls don't count as operations
+        storage.begin_operation("read_note");
+
+        assert_eq!(storage.undo_last().await.unwrap().unwrap().label, "edit_note");
+        let content = fs::read_to_string(temp_dir.path().join("note.md")).await.unwrap();
+        assert_eq!(content, "first");
+
+        assert_eq!(storage.undo_last().await.unwrap().unwrap().label, "write_note");
+        assert!(storage.undo_last().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_undo_refuses_when_note_changed_since() {
+        let (temp_dir, storage) = create_storage();
+
+        storage.begin_operation("write_note");
+        storage.write("note", "agent text", None).await.unwrap();
+        fs::write(temp_dir.path().join("note.md"), "user edit").await.unwrap();
+
+        let err = storage.undo_last().await.unwrap_err();
+        assert!(matches!(err, StorageError::HashMismatch { .. }));
+        let content = fs::read_to_string(temp_dir.path().join("note.md")).await.unwrap();
+        assert_eq!(content, "user edit");
+    }
+
+    #[tokio::test]
+    async fn test_history_is_bounded() {
+        let (_temp_dir, storage) = create_storage();
+
+        for i in 0..UNDO_HISTORY + 5 {
+            storage.begin_operation("write_note");
+            storage.write("note", &format!("v{}", i), None).await.unwrap();
+        }
+
+        let mut undone = 0;
+        while storage.undo_last().await.unwrap().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, UNDO_HISTORY);
+    }
+}
@@ -1,16 +1,30 @@
 //! Storage abstraction for note access.
 //!
-//! Provides a trait for filesystem operations that can be backed by local filesystem
-//! or remote HTTP in the future. This enables the same MCP tools to work both locally
-//! (for Claude Code) and remotely (for Claude iOS via home server).
+//! Provides a trait for filesystem operations, which the server backs with the
+//! local filesystem (`FileStorage`), a vault served over HTTP (`HttpStorage`,
+//! behind the `remote-storage` feature, when `OBSIDIAN_STORAGE_URL` is set), or
+//! a sync `Vault` (`SyncedStorage`, behind the `synced-storage` feature, when
+//! the vault has sync enabled). `Backend` picks between them.
 //!
 //! The Storage layer operates on memory URIs (e.g., "knowledge/My Note") and returns
 //! raw content. Higher-level concerns like wiki-link resolution stay in the MCP tools.
 
+mod backend;
 mod content_hash;
 mod file;
+#[cfg(feature = "remote-storage")]
+mod http;
+mod journal;
+#[cfg(feature = "synced-storage")]
+mod synced;
 mod traits;
 
+pub use backend::Backend;
 pub use content_hash::ContentHash;
 pub use file::FileStorage;
+pub use journal::JournaledStorage;
+#[cfg(feature = "remote-storage")]
+pub use http::HttpStorage;
+#[cfg(feature = "synced-storage")]
+pub use synced::SyncedStorage;
 pub use traits::{Storage, StorageError};
@@ -0,0 +1,374 @@
+//! Storage backed by a sync `Vault`.
+//!
+//! `FileStorage` writes markdown and leaves the sync daemon's file watcher to
+//! notice the change, which lags and has to tell our writes apart from sync
+//! echoes. `SyncedStorage` writes through the vault instead: each change
+//! updates the note's CRDT right away and the sync message announcing it is
+//! sent on a channel for the transport to broadcast to peers.
+
+use std::fmt::Display;
+use std::sync::Arc;
+
+use obsidian_fs::validate_relative_path;
+use sync_core::fs::FsError;
+use sync_core::{FileSystem, Vault};
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::content_hash::ContentHash;
+use super::file::{check_protected, normalize_folders};
+use super::traits::{NoteMetadata, Storage, StorageError, WriteResult};
+
+/// Storage backend that routes changes through a sync `Vault`.
+///
+/// Writes, deletes, and renames update the vault's CRDT state and send the
+/// prepared sync message (`DocumentUpdate`, `FileDeleted`, or `FileRenamed`)
+/// to `broadcasts`.
+pub struct SyncedStorage<F: FileSystem> {
+    vault: Arc<Vault<F>>,
+    broadcasts: UnboundedSender<Vec<u8>>,
+    /// Folders (relative to the vault, no trailing slash) that can't be modified
+    protected_folders: Vec<String>,
+}
+
+/// Wrap a filesystem or sync error, which have no matching `StorageError`.
+fn io_error(e: impl Display) -> StorageError {
+    StorageError::IoError {
+        message: e.to_string(),
+    }
+}
+
+impl<F: FileSystem> SyncedStorage<F> {
+    /// Create storage over `vault`, sending sync messages for changes to `broadcasts`.
+    pub fn new(vault: Arc<Vault<F>>, broadcasts: UnboundedSender<Vec<u8>>) -> Self {
+        Self {
+            vault,
+            broadcasts,
+            protected_folders: Vec::new(),
+        }
+    }
+
+    /// Make notes under these folders read-only, as `FileStorage::protect_folders` does.
+    pub fn protect_folders(mut self, folders: Vec<String>) -> Self {
+        self.protected_folders = normalize_folders(folders);
+        self
+    }
+
+    /// The vault changes are routed through.
+    pub fn vault(&self) -> &Arc<Vault<F>> {
+        &self.vault
+    }
+
+    /// Convert a memory URI to a vault-relative markdown path.
+    fn uri_to_path(uri: &str) -> Result<String, StorageError> {
+        let clean = validate_relative_path(uri).map_err(|e| StorageError::InvalidPath {
+            uri: uri.to_string(),
+            reason: e.to_string(),
+        })?;
+
+        if clean.ends_with(".md") {
+            Ok(clean)
+        } else {
+            Ok(format!("{}.md", clean))
+        }
+    }
+
+    /// Read a note's content, or None if it doesn't exist.
+    async fn read_path(&self, path: &str) -> Result<Option<String>, StorageError> {
+        match self.vault.fs().read(path).await {
+            Ok(bytes) => Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+            Err(FsError::NotFound(_)) => Ok(None),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    /// Queue a sync message for peers.
+    ///
+    /// Nobody listening just means there are no peers to tell, so a closed
+    /// channel isn't an error.
+    fn broadcast(&self, message: Vec<u8>) {
+        let _ = self.broadcasts.send(message);
+    }
+
+    /// Update the CRDT for a note just written and broadcast the change.
+    async fn sync_written(&self, path: &str) -> Result<(), StorageError> {
+        self.vault.on_file_changed(path).await.map_err(io_error)?;
+        // The vault is already up to date, so the watcher's event for this write is an echo
+        self.vault.mark_synced(path);
+
+        let update = self
+            .vault
+            .prepare_document_update(path)
+            .await
+            .map_err(io_error)?;
+        if let Some(update) = update {
+            self.broadcast(update);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<F: FileSystem + 'static> Storage for SyncedStorage<F> {
+    async fn exists(&self, uri: &str) -> Result<bool, StorageError> {
+        let path = Self::uri_to_path(uri)?;
+        self.vault.fs().exists(&path).await.map_err(io_error)
+    }
+
+    async fn read(&self, uri: &str) -> Result<(String, NoteMetadata), StorageError> {
+        let path = Self::uri_to_path(uri)?;
+        let content = self
+            .read_path(&path)
+            .await?
+            .ok_or_else(|| StorageError::NotFound {
+                uri: uri.to_string(),
+            })?;
+
+        let metadata = NoteMetadata {
+            hash: ContentHash::from_content(&content).as_str().to_string(),
+            uri: uri.to_string(),
+        };
+        Ok((content, metadata))
+    }
+
+    async fn write(
+        &self,
+        uri: &str,
+        content: &str,
+        expected_hash: Option<&str>,
+    ) -> Result<WriteResult, StorageError> {
+        self.check_writable(uri)?;
+        let path = Self::uri_to_path(uri)?;
+
+        // Optimistic locking: check hash if provided
+        if let Some(expected) = expected_hash {
+            let actual = match self.read_path(&path).await? {
+                Some(current) => ContentHash::from_content(&current).as_str().to_string(),
+                None => "(file does not exist)".to_string(),
+            };
+            if actual != expected {
+                return Err(StorageError::HashMismatch {
+                    uri: uri.to_string(),
+                    expected: expected.to_string(),
+                    actual,
+                });
+            }
+        }
+
+        self.vault
+            .fs()
+            .write(&path, content.as_bytes())
+            .await
+            .map_err(io_error)?;
+        self.sync_written(&path).await?;
+
+        Ok(WriteResult {
+            hash: ContentHash::from_content(content).as_str().to_string(),
+            uri: uri.to_string(),
+        })
+    }
+
+    async fn delete(&self, uri: &str) -> Result<(), StorageError> {
+        self.check_writable(uri)?;
+        let path = Self::uri_to_path(uri)?;
+        if !self.vault.fs().exists(&path).await.map_err(io_error)? {
+            return Err(StorageError::NotFound {
+                uri: uri.to_string(),
+            });
+        }
+
+        self.vault.fs().delete(&path).await.map_err(io_error)?;
+        self.vault.delete_file(&path).await.map_err(io_error)?;
+        self.vault.mark_synced(&path);
+
+        let message = self.vault.prepare_file_deleted(&path).map_err(io_error)?;
+        self.broadcast(message);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let dir = if prefix.is_empty() {
+            String::new()
+        } else {
+            validate_relative_path(prefix).map_err(|e| StorageError::InvalidPath {
+                uri: prefix.to_string(),
+                reason: e.to_string(),
+            })?
+        };
+
+        let entries = self
+            .vault
+            .fs()
+            .list_recursive(&dir)
+            .await
+            .map_err(io_error)?;
+        let notes = entries
+            .into_iter()
+            .filter(|entry| !entry.name.split('/').any(|part| part.starts_with('.')))
+            .filter_map(|entry| entry.name.strip_suffix(".md").map(str::to_string))
+            .collect();
+        Ok(notes)
+    }
+
+    async fn rename(&self, from: &str, to: &str) -> Result<(), StorageError> {
+        self.check_writable(from)?;
+        self.check_writable(to)?;
+        let from_path = Self::uri_to_path(from)?;
+        let to_path = Self::uri_to_path(to)?;
+
+        let content = self
+            .read_path(&from_path)
+            .await?
+            .ok_or_else(|| StorageError::NotFound {
+                uri: from.to_string(),
+            })?;
+        if self.vault.fs().exists(&to_path).await.map_err(io_error)? {
+            return Err(StorageError::AlreadyExists {
+                uri: to.to_string(),
+            });
+        }
+
+        let fs = self.vault.fs();
+        fs.write(&to_path, content.as_bytes())
+            .await
+            .map_err(io_error)?;
+        fs.delete(&from_path).await.map_err(io_error)?;
+        self.vault
+            .rename_file(&from_path, &to_path)
+            .await
+            .map_err(io_error)?;
+        self.vault.mark_synced(&from_path);
+        self.vault.mark_synced(&to_path);
+
+        let message = self
+            .vault
+            .prepare_file_renamed(&from_path, &to_path)
+            .map_err(io_error)?;
+        self.broadcast(message);
+        Ok(())
+    }
+
+    /// Folders aren't synced on their own; peers create them for the notes inside.
+    async fn create_folder(&self, path: &str) -> Result<(), StorageError> {
+        self.check_writable(path)?;
+        let clean = validate_relative_path(path).map_err(|e| StorageError::InvalidPath {
+            uri: path.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.vault.fs().mkdir(&clean).await.map_err(io_error)
+    }
+
+    fn check_writable(&self, uri: &str) -> Result<(), StorageError> {
+        check_protected(&self.protected_folders, uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sync_core::{InMemoryFs, PeerId};
+    use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+    async fn synced_storage(
+        peer: u64,
+    ) -> (SyncedStorage<Arc<InMemoryFs>>, UnboundedReceiver<Vec<u8>>) {
+        let vault = Vault::init(Arc::new(InMemoryFs::new()), PeerId::from(peer))
+            .await
+            .unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        (SyncedStorage::new(Arc::new(vault), tx), rx)
+    }
+
+    #[tokio::test]
+    async fn test_write_updates_crdt_and_prepares_broadcast() {
+        let (storage, mut broadcasts) = synced_storage(1).await;
+
+        let result = storage
+            .write("knowledge/Note", "Hello", None)
+            .await
+            .unwrap();
+        let doc = storage
+            .vault()
+            .get_document("knowledge/Note.md")
+            .await
+            .unwrap();
+        assert_eq!(doc.body().to_string(), "Hello");
+        let (content, metadata) = storage.read("knowledge/Note").await.unwrap();
+        assert_eq!(content, "Hello");
+        assert_eq!(metadata.hash, result.hash);
+
+        // A peer applying the broadcast gets the note
+        let update = broadcasts.try_recv().unwrap();
+        let (peer, _) = synced_storage(2).await;
+        let (_, modified) = peer.vault().process_sync_message(&update).await.unwrap();
+        assert_eq!(modified, vec!["knowledge/Note.md"]);
+        assert_eq!(peer.read("knowledge/Note").await.unwrap().0, "Hello");
+
+        // Edits update the existing document instead of replacing it
+        storage
+            .write("knowledge/Note", "Hello again", Some(&result.hash))
+            .await
+            .unwrap();
+        let doc = storage
+            .vault()
+            .get_document("knowledge/Note.md")
+            .await
+            .unwrap();
+        assert_eq!(doc.body().to_string(), "Hello again");
+        assert!(broadcasts.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_write_checks_expected_hash() {
+        let (storage, mut broadcasts) = synced_storage(1).await;
+        storage.write("Note", "Hello", None).await.unwrap();
+        broadcasts.try_recv().unwrap();
+
+        let err = storage.write("Note", "Changed", Some("stale")).await;
+        assert!(matches!(err, Err(StorageError::HashMismatch { .. })));
+        assert_eq!(storage.read("Note").await.unwrap().0, "Hello");
+        assert!(broadcasts.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_and_rename_broadcast() {
+        let (storage, mut broadcasts) = synced_storage(1).await;
+        storage.write("a/Old", "Content", None).await.unwrap();
+        storage.write("b/Gone", "Bye", None).await.unwrap();
+        while broadcasts.try_recv().is_ok() {}
+
+        storage.rename("a/Old", "a/New").await.unwrap();
+        assert!(!storage.exists("a/Old").await.unwrap());
+        assert_eq!(storage.read("a/New").await.unwrap().0, "Content");
+        assert!(broadcasts.try_recv().is_ok());
+
+        storage.delete("b/Gone").await.unwrap();
+        assert!(
+            !storage
+                .vault()
+                .registered_files()
+                .contains(&"b/Gone.md".to_string())
+        );
+        assert!(broadcasts.try_recv().is_ok());
+
+        assert_eq!(storage.list("").await.unwrap(), vec!["a/New"]);
+        assert!(matches!(
+            storage.delete("b/Gone").await,
+            Err(StorageError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_protected_folders_reject_changes_without_broadcast() {
+        let (storage, mut broadcasts) = synced_storage(1).await;
+        storage.write("archive/Old", "Kept", None).await.unwrap();
+        broadcasts.try_recv().unwrap();
+        let storage = storage.protect_folders(vec!["archive/".to_string()]);
+
+        let write = storage.write("archive/Old", "Changed", None).await;
+        assert!(matches!(write, Err(StorageError::ReadOnly { .. })));
+        let rename = storage.rename("archive/Old", "Old").await;
+        assert!(matches!(rename, Err(StorageError::ReadOnly { .. })));
+        assert_eq!(storage.read("archive/Old").await.unwrap().0, "Kept");
+        assert!(broadcasts.try_recv().is_err());
+    }
+}
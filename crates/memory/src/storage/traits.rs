@@ -21,6 +21,10 @@ pub enum StorageError {
     IoError { message: String },
     /// Parent directory doesn't exist
     ParentNotFound { uri: String, parent: PathBuf },
+    /// Resolved path (after following symlinks) is outside the vault root
+    OutsideVault { uri: String },
+    /// The note is under a protected (read-only) folder
+    ReadOnly { uri: String, folder: String },
 }
 
 impl std::fmt::Display for StorageError {
@@ -49,6 +53,12 @@ impl std::fmt::Display for StorageError {
                     parent.display()
                 )
             }
+            StorageError::OutsideVault { uri } => {
+                write!(f, "Path '{}' resolves outside the vault", uri)
+            }
+            StorageError::ReadOnly { uri, folder } => {
+                write!(f, "'{}' is in the read-only folder '{}/'", uri, folder)
+            }
         }
     }
 }
@@ -86,8 +96,9 @@ pub struct WriteResult {
 /// Implementations provide filesystem primitives for reading and writing notes.
 /// The trait uses memory URIs (without .md extension) for note identification.
 ///
-/// Current implementation: `LocalStorage` (filesystem)
-/// Future implementation: `RemoteStorage` (HTTP to another MCP server)
+/// Implementations: `FileStorage` (filesystem), `HttpStorage` (remote vault
+/// over HTTP, behind the `remote-storage` feature), and `SyncedStorage`
+/// (through a sync `Vault`, behind the `synced-storage` feature)
 #[async_trait::async_trait]
 pub trait Storage: Send + Sync {
     /// Check if a note exists at the given memory URI.
@@ -140,4 +151,41 @@ pub trait Storage: Send + Sync {
     /// * `from` - Source memory URI
     /// * `to` - Destination memory URI
     async fn rename(&self, from: &str, to: &str) -> Result<(), StorageError>;
+
+    /// Create a folder, and any missing parents, to write notes into.
+    ///
+    /// Succeeds if the folder already exists.
+    ///
+    /// # Arguments
+    /// * `path` - Folder path relative to the vault (e.g., "log")
+    async fn create_folder(&self, path: &str) -> Result<(), StorageError>;
+
+    /// Append text to a note, creating it if needed.
+    ///
+    /// The default reads the note and writes it back with `text` added;
+    /// backends that can append in place should override it.
+    ///
+    /// # Arguments
+    /// * `uri` - Memory URI without extension
+    /// * `text` - Text to add after the current content
+    async fn append(&self, uri: &str, text: &str) -> Result<(), StorageError> {
+        let (content, hash) = match self.read(uri).await {
+            Ok((content, meta)) => (content, Some(meta.hash)),
+            Err(StorageError::NotFound { .. }) => (String::new(), None),
+            Err(e) => return Err(e),
+        };
+        self.write(uri, &(content + text), hash.as_deref()).await?;
+        Ok(())
+    }
+
+    /// Check that a note may be modified, without modifying it.
+    ///
+    /// Lets multi-step tools (e.g. MoveNote) fail before making any changes.
+    /// Backends without write protection accept everything.
+    ///
+    /// # Arguments
+    /// * `uri` - Memory URI without extension
+    fn check_writable(&self, _uri: &str) -> Result<(), StorageError> {
+        Ok(())
+    }
 }
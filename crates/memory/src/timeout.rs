@@ -0,0 +1,115 @@
+//! Time limits and cancellation for tool calls.
+//!
+//! A cold search over a large vault can spend a long time computing
+//! embeddings. Tools with a configured limit return a timed-out error result
+//! instead of blocking the client, and any call stops early when the client
+//! cancels the request.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use std::future::Future;
+use std::time::Duration;
+
+/// Run a tool call until it finishes, `timeout` elapses, or `cancelled` resolves.
+///
+/// Timing out returns an error result (`is_error: true`) explaining what
+/// happened, so the client gets an answer rather than a hung request.
+pub async fn run_with_limits<F, C>(
+    tool: &str,
+    timeout: Option<Duration>,
+    cancelled: C,
+    call: F,
+) -> Result<CallToolResult, ErrorData>
+where
+    F: Future<Output = Result<CallToolResult, ErrorData>>,
+    C: Future<Output = ()>,
+{
+    let limited = async {
+        match timeout {
+            Some(limit) => tokio::time::timeout(limit, call)
+                .await
+                .unwrap_or_else(|_| Ok(timed_out(tool, limit))),
+            None => call.await,
+        }
+    };
+
+    tokio::select! {
+        result = limited => result,
+        _ = cancelled => {
+            tracing::info!(tool, "Tool call cancelled by client");
+            Err(ErrorData::internal_error(format!("{} was cancelled", tool), None))
+        }
+    }
+}
+
+/// Error result for a call that hit its time limit.
+fn timed_out(tool: &str, limit: Duration) -> CallToolResult {
+    tracing::warn!(tool, ?limit, "Tool call timed out");
+    CallToolResult::error(vec![Content::text(format!(
+        "Timed out: {} did not finish within {:.1}s. Embeddings may still be loading for a large vault; \
+         try again shortly, or raise the limit with OBSIDIAN_TOOL_TIMEOUTS (e.g. \"{}=120\").",
+        tool,
+        limit.as_secs_f64(),
+        tool
+    ))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::pending;
+
+    /// Stands in for a tool stuck computing embeddings.
+    async fn slow_embedding_step() -> Result<CallToolResult, ErrorData> {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        Ok(CallToolResult::success(vec![Content::text("results")]))
+    }
+
+    fn text(result: &CallToolResult) -> &str {
+        &result.content[0].raw.as_text().expect("Expected text").text
+    }
+
+    #[tokio::test]
+    async fn test_timeout_returns_timed_out_result() {
+        let result = run_with_limits(
+            "search",
+            Some(Duration::from_millis(50)),
+            pending(),
+            slow_embedding_step(),
+        )
+        .await
+        .expect("timeout is a result, not a protocol error");
+
+        assert_eq!(result.is_error, Some(true));
+        assert!(text(&result).starts_with("Timed out: search"), "{}", text(&result));
+    }
+
+    #[tokio::test]
+    async fn test_fast_call_is_unaffected() {
+        let result = run_with_limits("search", Some(Duration::from_secs(5)), pending(), async {
+            Ok(CallToolResult::success(vec![Content::text("results")]))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(text(&result), "results");
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_stops_call() {
+        let (cancel, cancelled) = tokio::sync::oneshot::channel::<()>();
+        cancel.send(()).unwrap();
+
+        let err = run_with_limits(
+            "search",
+            None,
+            async {
+                let _ = cancelled.await;
+            },
+            slow_embedding_step(),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.message.contains("cancelled"));
+    }
+}
@@ -1,6 +1,7 @@
 //! Shared utilities for note tools.
 
 use obsidian_fs::{normalize_note_reference, parse_frontmatter};
+use rmcp::model::ErrorData;
 use tokio::fs;
 
 use crate::graph::GraphIndex;
@@ -141,6 +142,94 @@ pub async fn resolve_note_uri<S: Storage>(
     Ok((normalized.path, false))
 }
 
+/// Error for a change to a note under a protected folder.
+pub fn read_only_error(uri: &str, folder: &str) -> ErrorData {
+    ErrorData::invalid_params(
+        format!(
+            "Cannot modify memory:{}: the '{}/' folder is read-only. \
+             Ask the user to make this change themselves.",
+            uri, folder
+        ),
+        None,
+    )
+}
+
+/// Build the `obsidian://open` URI for a note path.
+///
+/// Fails if the vault name is blank, since Obsidian can't open such a URI.
+pub fn obsidian_uri(vault_name: &str, path: &str) -> Result<String, ErrorData> {
+    if vault_name.trim().is_empty() {
+        return Err(ErrorData::internal_error(
+            format!(
+                "Cannot build an Obsidian URI for {}: the vault name is empty. \
+                 Set OBSIDIAN_VAULT_NAME to the vault's name as shown in Obsidian.",
+                path
+            ),
+            None,
+        ));
+    }
+    Ok(format!(
+        "obsidian://open?vault={}&file={}",
+        urlencoding::encode(vault_name),
+        urlencoding::encode(path)
+    ))
+}
+
+/// Outcome of resolving a wiki-link or note reference to a concrete note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkResolution {
+    /// Exactly one note matches; its path without `.md`
+    Unique(String),
+    /// Several same-named notes match; their paths without `.md`, sorted
+    Ambiguous(Vec<String>),
+    NotFound,
+}
+
+/// Resolve a wiki-link or note reference without guessing between duplicates.
+///
+/// Unlike [`resolve_note_uri`], which picks one path for a name, this reports
+/// every candidate when same-named notes exist in different folders. Aliases,
+/// headers, and block references in wiki-links are ignored.
+pub async fn resolve_link_target<S: Storage>(
+    storage: &S,
+    graph: &GraphIndex,
+    link: &str,
+) -> Result<LinkResolution, StorageError> {
+    // Drop `#Header` and `|alias` so only the target path is resolved
+    let target = match wiki_links::parse_wiki_links(link.trim()).first() {
+        Some(parsed) => parsed.path(),
+        None => link.to_string(),
+    };
+    let normalized = normalize_note_reference(&target);
+
+    // A folder in the reference pins the note to that exact path
+    if normalized.path.contains('/') {
+        return Ok(if storage.exists(&normalized.path).await? {
+            LinkResolution::Unique(normalized.path)
+        } else {
+            LinkResolution::NotFound
+        });
+    }
+
+    let mut candidates: Vec<String> = graph
+        .get_paths_for_name(&normalized.name)
+        .map(|paths| {
+            paths
+                .iter()
+                .map(|path| path.strip_suffix(".md").unwrap_or(path).to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    candidates.sort();
+
+    match candidates.len() {
+        1 => Ok(LinkResolution::Unique(candidates.remove(0))),
+        0 if storage.exists(&normalized.path).await? => Ok(LinkResolution::Unique(normalized.path)),
+        0 => Ok(LinkResolution::NotFound),
+        _ => Ok(LinkResolution::Ambiguous(candidates)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -269,4 +358,16 @@ mod tests {
         assert_eq!(uri, "b/note");
         assert!(exists);
     }
+
+    #[test]
+    fn test_obsidian_uri_encodes_vault_and_path() {
+        let uri = obsidian_uri("My Vault", "journal/2024-w01").unwrap();
+        assert_eq!(uri, "obsidian://open?vault=My%20Vault&file=journal%2F2024-w01");
+    }
+
+    #[test]
+    fn test_obsidian_uri_rejects_empty_vault_name() {
+        let err = obsidian_uri("  ", "Note").unwrap_err();
+        assert!(err.message.contains("vault name is empty"));
+    }
 }
@@ -0,0 +1,399 @@
+//! CompactLog tool for archiving old days out of Log.md.
+//!
+//! Day sections older than a threshold move to one archive note per ISO week
+//! (`log/YYYY-wWW.md`, named like weekly journal notes), keeping their
+//! WriteLogs formatting. Recent days stay in Log.md verbatim. Running it again
+//! is a no-op, and a day already in its archive note is replaced rather than
+//! duplicated.
+
+use chrono::{Local, NaiveDate, Weekday};
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::common::read_only_error;
+use crate::storage::{Storage, StorageError};
+
+/// Default age in days after which log days are archived.
+pub const DEFAULT_OLDER_THAN_DAYS: u32 = 14;
+
+/// Result of compacting Log.md.
+#[derive(Debug, Serialize)]
+pub struct CompactLogResponse {
+    /// ISO week dates of the archived days, oldest first
+    pub archived_days: Vec<String>,
+    /// Archive notes written, as memory URIs
+    pub archive_notes: Vec<String>,
+    /// Day sections left in Log.md
+    pub remaining_days: usize,
+}
+
+/// A `## ` day section of the log, from its header up to the next one.
+pub(super) struct DaySection<'a> {
+    /// ISO week date from the header, e.g. `2025-W48-1`
+    pub(super) iso_week_date: Option<&'a str>,
+    pub(super) text: &'a str,
+}
+
+/// Split a log into its preamble and day sections.
+pub(super) fn split_days(log: &str) -> (&str, Vec<DaySection<'_>>) {
+    let mut starts = Vec::new();
+    let mut offset = 0;
+    for line in log.split_inclusive('\n') {
+        if line.starts_with("## ") {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+
+    let preamble = &log[..starts.first().copied().unwrap_or(log.len())];
+    let days = starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(log.len());
+            let text = &log[start..end];
+            let iso_week_date = text[3..].split_whitespace().next();
+            DaySection {
+                iso_week_date,
+                text,
+            }
+        })
+        .collect();
+    (preamble, days)
+}
+
+/// Parse a `YYYY-Www-D` ISO week date, e.g. `2025-W50-1`.
+///
+/// Returns `None` unless the year has four digits, the week two, and the date
+/// exists (week 53 only in years that have one).
+pub(super) fn parse_iso_week_date(s: &str) -> Option<NaiveDate> {
+    let mut parts = s.split('-');
+    let year = parts.next().filter(|year| year.len() == 4)?;
+    let week = parts.next()?.strip_prefix('W').filter(|week| week.len() == 2)?;
+    let day: u8 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !(1..=7).contains(&day) {
+        return None;
+    }
+    let weekday = Weekday::try_from(day - 1).ok()?;
+    NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, weekday)
+}
+
+/// Archive note path (without extension) for a day's ISO week date.
+fn archive_note_path(iso_week_date: &str) -> String {
+    let (year_week, _day) = iso_week_date
+        .rsplit_once('-')
+        .unwrap_or((iso_week_date, ""));
+    format!("log/{}", year_week.replacen("-W", "-w", 1))
+}
+
+/// Section text ending in exactly one blank line, so sections stay separated.
+fn normalize_section(text: &str) -> String {
+    format!("{}\n\n", text.trim_end())
+}
+
+/// Merge day sections into an archive note, replacing any with the same header
+/// and keeping days in date order.
+fn merge_archive(existing: &str, title: &str, sections: &[&DaySection<'_>]) -> String {
+    let (preamble, days) = split_days(existing);
+    let mut by_date: BTreeMap<String, String> = days
+        .iter()
+        .map(|day| {
+            let key = day.iso_week_date.unwrap_or_default().to_string();
+            (key, normalize_section(day.text))
+        })
+        .collect();
+    for section in sections {
+        let key = section.iso_week_date.unwrap_or_default().to_string();
+        by_date.insert(key, normalize_section(section.text));
+    }
+
+    let preamble = if preamble.trim().is_empty() {
+        format!("# {}\n\n", title)
+    } else {
+        normalize_section(preamble)
+    };
+    let body: String = by_date.into_values().collect();
+    format!("{}{}", preamble, body.trim_end()) + "\n"
+}
+
+/// Map a storage error from writing `what` to a tool error.
+fn write_error(what: &str, e: StorageError) -> ErrorData {
+    match e {
+        StorageError::ReadOnly { uri, folder } => read_only_error(&uri, &folder),
+        e => ErrorData::internal_error(format!("Failed to write {}: {}", what, e), None),
+    }
+}
+
+/// Read a note and its hash, or nothing if it doesn't exist yet.
+async fn read_optional<S: Storage>(
+    storage: &S,
+    uri: &str,
+) -> Result<Option<(String, String)>, ErrorData> {
+    match storage.read(uri).await {
+        Ok((content, metadata)) => Ok(Some((content, metadata.hash))),
+        Err(StorageError::NotFound { .. }) => Ok(None),
+        Err(e) => Err(ErrorData::internal_error(
+            format!("Failed to read {}.md: {}", uri, e),
+            None,
+        )),
+    }
+}
+
+/// Move day sections older than `older_than_days` before `today` out of Log.md.
+async fn compact<S: Storage>(
+    storage: &S,
+    today: NaiveDate,
+    older_than_days: u32,
+) -> Result<CompactLogResponse, ErrorData> {
+    let Some((log, log_hash)) = read_optional(storage, "Log").await? else {
+        return Ok(CompactLogResponse {
+            archived_days: Vec::new(),
+            archive_notes: Vec::new(),
+            remaining_days: 0,
+        });
+    };
+
+    let cutoff = today - chrono::Duration::days(older_than_days.into());
+    let (preamble, days) = split_days(&log);
+    let (old, recent): (Vec<_>, Vec<_>) = days.iter().partition(|day| {
+        day.iso_week_date
+            .and_then(parse_iso_week_date)
+            .is_some_and(|date| date < cutoff)
+    });
+
+    let mut response = CompactLogResponse {
+        archived_days: Vec::new(),
+        archive_notes: Vec::new(),
+        remaining_days: recent.len(),
+    };
+    if old.is_empty() {
+        return Ok(response);
+    }
+
+    let mut by_week: BTreeMap<String, Vec<&DaySection<'_>>> = BTreeMap::new();
+    for &day in &old {
+        let iso_week_date = day.iso_week_date.unwrap_or_default();
+        by_week
+            .entry(archive_note_path(iso_week_date))
+            .or_default()
+            .push(day);
+        response.archived_days.push(iso_week_date.to_string());
+    }
+    response.archived_days.sort();
+
+    // Refuse up front rather than archiving days Log.md then can't drop
+    storage
+        .check_writable("Log")
+        .map_err(|e| write_error("Log.md", e))?;
+    for note_path in by_week.keys() {
+        storage
+            .check_writable(note_path)
+            .map_err(|e| write_error(&format!("{}.md", note_path), e))?;
+    }
+
+    // Write archives before trimming Log.md so an interruption can't lose days
+    storage
+        .create_folder("log")
+        .await
+        .map_err(|e| write_error("log/", e))?;
+    for (note_path, sections) in &by_week {
+        let existing = read_optional(storage, note_path).await?;
+        let title = format!("Log {}", note_path.trim_start_matches("log/"));
+        let (existing, hash) = match &existing {
+            Some((content, hash)) => (content.as_str(), Some(hash.as_str())),
+            None => ("", None),
+        };
+        let merged = merge_archive(existing, &title, sections);
+        storage
+            .write(note_path, &merged, hash)
+            .await
+            .map_err(|e| write_error(&format!("{}.md", note_path), e))?;
+        response.archive_notes.push(format!("memory:{}", note_path));
+    }
+
+    let mut compacted = preamble.to_string();
+    for day in &recent {
+        compacted.push_str(day.text);
+    }
+    storage
+        .write("Log", &compacted, Some(&log_hash))
+        .await
+        .map_err(|e| write_error("Log.md", e))?;
+
+    Ok(response)
+}
+
+/// Execute the CompactLog tool.
+pub async fn execute<S: Storage>(
+    storage: &S,
+    older_than_days: Option<u32>,
+) -> Result<CallToolResult, ErrorData> {
+    let today = Local::now().date_naive();
+    let response = compact(
+        storage,
+        today,
+        older_than_days.unwrap_or(DEFAULT_OLDER_THAN_DAYS),
+    )
+    .await?;
+
+    let json = serde_json::to_string_pretty(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize: {}", e), None))?;
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{FileStorage, JournaledStorage};
+    use std::path::Path;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    const LOG: &str = "# Log\n\n\
+        ## 2025-W48-1 (Mon)\n\n- 9:00 AM – Old work\n\n\
+        ## 2025-W49-3 (Wed)\n\n- 10:00 AM – Older work\n- 2:00 PM – More\n\n\
+        ## 2025-W50-1 (Mon)\n\n- 9:30 AM – Recent work\n";
+
+    // 2025-W50-4 (Thu): a 7 day threshold archives only weeks 48 and 49
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 12, 11).unwrap()
+    }
+
+    async fn compact_vault(vault: &Path) -> CompactLogResponse {
+        let storage = FileStorage::new(vault.to_path_buf());
+        compact(&storage, today(), 7).await.unwrap()
+    }
+
+    #[test]
+    fn test_parse_iso_week_date() {
+        assert_eq!(
+            parse_iso_week_date("2025-W50-1"),
+            NaiveDate::from_ymd_opt(2025, 12, 8)
+        );
+        assert_eq!(parse_iso_week_date("2025-W50-8"), None);
+        assert_eq!(parse_iso_week_date("2025-W5-1"), None);
+        assert_eq!(parse_iso_week_date("2025-W53-1"), None);
+        assert_eq!(parse_iso_week_date("Notes"), None);
+        assert_eq!(archive_note_path("2025-W50-1"), "log/2025-w50");
+    }
+
+    #[tokio::test]
+    async fn test_compact_archives_old_days_and_keeps_recent() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::write(vault.join("Log.md"), LOG).await.unwrap();
+
+        let response = compact_vault(vault).await;
+        assert_eq!(response.archived_days, vec!["2025-W48-1", "2025-W49-3"]);
+        assert_eq!(
+            response.archive_notes,
+            vec!["memory:log/2025-w48", "memory:log/2025-w49"]
+        );
+        assert_eq!(response.remaining_days, 1);
+
+        let log = fs::read_to_string(vault.join("Log.md")).await.unwrap();
+        assert_eq!(
+            log,
+            "# Log\n\n## 2025-W50-1 (Mon)\n\n- 9:30 AM – Recent work\n"
+        );
+
+        let week49 = fs::read_to_string(vault.join("log/2025-w49.md"))
+            .await
+            .unwrap();
+        assert_eq!(
+            week49,
+            "# Log 2025-w49\n\n## 2025-W49-3 (Wed)\n\n- 10:00 AM – Older work\n- 2:00 PM – More\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compact_is_idempotent() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::write(vault.join("Log.md"), LOG).await.unwrap();
+
+        compact_vault(vault).await;
+        let log = fs::read_to_string(vault.join("Log.md")).await.unwrap();
+        let archive = fs::read_to_string(vault.join("log/2025-w48.md"))
+            .await
+            .unwrap();
+
+        let again = compact_vault(vault).await;
+        assert!(again.archived_days.is_empty());
+        assert_eq!(fs::read_to_string(vault.join("Log.md")).await.unwrap(), log);
+        assert_eq!(
+            fs::read_to_string(vault.join("log/2025-w48.md"))
+                .await
+                .unwrap(),
+            archive
+        );
+
+        // A day restored to Log.md replaces its archived copy instead of duplicating it
+        fs::write(vault.join("Log.md"), LOG).await.unwrap();
+        compact_vault(vault).await;
+        let archive = fs::read_to_string(vault.join("log/2025-w48.md"))
+            .await
+            .unwrap();
+        assert_eq!(archive.matches("## 2025-W48-1").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_compact_merges_into_existing_archive_in_date_order() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("log")).await.unwrap();
+        fs::write(
+            vault.join("log/2025-w48.md"),
+            "# Log 2025-w48\n\n## 2025-W48-4 (Thu)\n\n- 1:00 PM – Archived earlier\n",
+        )
+        .await
+        .unwrap();
+        fs::write(vault.join("Log.md"), LOG).await.unwrap();
+
+        compact_vault(vault).await;
+        let archive = fs::read_to_string(vault.join("log/2025-w48.md"))
+            .await
+            .unwrap();
+        let monday = archive.find("## 2025-W48-1").unwrap();
+        let thursday = archive.find("## 2025-W48-4").unwrap();
+        assert!(monday < thursday);
+        assert!(archive.contains("Archived earlier"));
+    }
+
+    #[tokio::test]
+    async fn test_compact_without_log_is_noop() {
+        let temp = TempDir::new().unwrap();
+        let response = compact_vault(temp.path()).await;
+        assert!(response.archived_days.is_empty());
+        assert!(!temp.path().join("Log.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_compact_is_undoable() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::write(vault.join("Log.md"), LOG).await.unwrap();
+        let storage = JournaledStorage::new(Arc::new(FileStorage::new(vault.to_path_buf())));
+        storage.begin_operation("CompactLog");
+
+        compact(&storage, today(), 7).await.unwrap();
+        storage.undo_last().await.unwrap();
+
+        assert_eq!(fs::read_to_string(vault.join("Log.md")).await.unwrap(), LOG);
+        assert!(!vault.join("log/2025-w48.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_compact_respects_protected_folders() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::write(vault.join("Log.md"), LOG).await.unwrap();
+        let storage =
+            FileStorage::new(vault.to_path_buf()).protect_folders(vec!["log".into()]);
+
+        assert!(compact(&storage, today(), 7).await.is_err());
+        assert_eq!(fs::read_to_string(vault.join("Log.md")).await.unwrap(), LOG);
+    }
+}
@@ -0,0 +1,115 @@
+//! DebugEmbed tool - show the raw embedding for a piece of text.
+//!
+//! Only registered when `OBSIDIAN_DEBUG_TOOLS` is set. Returns the vector the
+//! model produces along with its token count and a few summary stats, which
+//! helps explain why two texts get the similarity score they do.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use semantic_embeddings::EMBEDDING_DIM;
+use serde::Serialize;
+
+use crate::embeddings::EmbeddingManager;
+
+/// Response from DebugEmbed tool.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DebugEmbedResponse {
+    /// Tokens the model saw, including [CLS] and [SEP]
+    pub token_count: usize,
+    pub dimension: usize,
+    /// L2 norm (close to 1.0, since embeddings are normalized)
+    pub norm: f32,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// The raw embedding
+    pub vector: Vec<f32>,
+}
+
+/// Summarize an embedding, checking it has the model's dimension.
+fn build_response(vector: Vec<f32>, token_count: usize) -> Result<DebugEmbedResponse, ErrorData> {
+    if vector.len() != EMBEDDING_DIM {
+        return Err(ErrorData::internal_error(
+            format!(
+                "Unexpected embedding dimension {} (expected {})",
+                vector.len(),
+                EMBEDDING_DIM
+            ),
+            None,
+        ));
+    }
+
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let min = vector.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = vector.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let mean = vector.iter().sum::<f32>() / vector.len() as f32;
+
+    Ok(DebugEmbedResponse {
+        token_count,
+        dimension: vector.len(),
+        norm,
+        min,
+        max,
+        mean,
+        vector,
+    })
+}
+
+/// Execute the DebugEmbed tool.
+///
+/// `as_query` applies the query prefix, as Search does for query text;
+/// otherwise the document prefix used for notes is applied.
+pub async fn execute(
+    embeddings: &EmbeddingManager,
+    text: &str,
+    as_query: bool,
+) -> Result<CallToolResult, ErrorData> {
+    let (vector, token_count) = embeddings
+        .encode_uncached(text, as_query)
+        .await
+        .map_err(|e| ErrorData::internal_error(format!("Failed to encode text: {}", e), None))?;
+
+    let response = build_response(vector, token_count)?;
+
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_vector() -> Vec<f32> {
+        let mut vector = vec![0.0; EMBEDDING_DIM];
+        vector[0] = 0.6;
+        vector[1] = -0.8;
+        vector
+    }
+
+    #[test]
+    fn test_build_response_reports_dimension_and_stats() {
+        let response = build_response(unit_vector(), 5).unwrap();
+
+        assert_eq!(response.dimension, EMBEDDING_DIM);
+        assert_eq!(response.vector.len(), EMBEDDING_DIM);
+        assert_eq!(response.token_count, 5);
+        assert!((response.norm - 1.0).abs() < 1e-6);
+        assert_eq!(response.min, -0.8);
+        assert_eq!(response.max, 0.6);
+    }
+
+    #[test]
+    fn test_identical_vectors_give_identical_responses() {
+        assert_eq!(
+            build_response(unit_vector(), 5).unwrap(),
+            build_response(unit_vector(), 5).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_response_rejects_wrong_dimension() {
+        let err = build_response(vec![1.0; 10], 3).unwrap_err();
+        assert!(err.message.contains("Unexpected embedding dimension 10"));
+    }
+}
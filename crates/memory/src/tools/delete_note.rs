@@ -4,16 +4,36 @@ use obsidian_fs::{ensure_markdown_extension, normalize_note_reference};
 use rmcp::model::{CallToolResult, Content, ErrorData};
 use std::path::Path;
 
+use super::common::read_only_error;
+use crate::graph::GraphIndex;
 use crate::storage::{Storage, StorageError};
 
+/// URIs of notes that link to the note at `uri`, sorted, excluding the note itself.
+fn linking_notes(graph: &GraphIndex, uri: &str, name: &str) -> Vec<String> {
+    let own_path = ensure_markdown_extension(uri);
+    let mut uris: Vec<String> = graph
+        .get_backlinks(name)
+        .into_iter()
+        .flatten()
+        .filter(|path| **path != own_path)
+        .map(|path| path.strip_suffix(".md").unwrap_or(path).to_string())
+        .collect();
+    uris.sort();
+    uris
+}
+
 /// Execute the DeleteNote tool.
 ///
 /// Permanently deletes a note from the vault.
-/// Returns an error if the note doesn't exist.
+/// Returns an error if the note doesn't exist. If other notes link to it,
+/// nothing is deleted unless `force` is set; the linking notes are listed
+/// instead so the dangling links aren't created by accident.
 pub async fn execute<S: Storage>(
     vault_path: &Path,
     storage: &S,
+    graph: &GraphIndex,
     note: &str,
+    force: bool,
 ) -> Result<CallToolResult, ErrorData> {
     let normalized = normalize_note_reference(note);
     let uri = &normalized.path;
@@ -22,27 +42,65 @@ pub async fn execute<S: Storage>(
         .join(ensure_markdown_extension(uri))
         .to_string_lossy()
         .to_string();
-
-    // Delete the note
-    storage.delete(uri).await.map_err(|e| match e {
-        StorageError::NotFound { uri } => ErrorData::invalid_params(
+    let not_found = |uri: &str| {
+        ErrorData::invalid_params(
             format!(
                 "Note not found: {}. Cannot delete a note that doesn't exist.\n\
                  Expected file: {}",
                 uri, file_path
             ),
             None,
-        ),
+        )
+    };
+
+    if !storage
+        .exists(uri)
+        .await
+        .map_err(|e| ErrorData::internal_error(format!("Failed to check note: {}", e), None))?
+    {
+        return Err(not_found(uri));
+    }
+
+    let backlinks = linking_notes(graph, uri, &normalized.name);
+    let backlink_list = backlinks
+        .iter()
+        .map(|uri| format!("- memory:{}", uri))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if !backlinks.is_empty() && !force {
+        let text = format!(
+            "Not deleted: {} note(s) link to {}:\n{}\n\n\
+             Deleting it would leave those links dangling. Update or remove the links first, \
+             or call DeleteNote again with force: true to delete anyway.",
+            backlinks.len(),
+            normalized.name,
+            backlink_list
+        );
+        return Ok(CallToolResult::success(vec![Content::text(text)]));
+    }
+
+    // Delete the note
+    storage.delete(uri).await.map_err(|e| match e {
+        StorageError::NotFound { uri } => not_found(&uri),
+        StorageError::ReadOnly { uri, folder } => read_only_error(&uri, &folder),
         _ => ErrorData::internal_error(format!("Failed to delete note: {}", e), None),
     })?;
 
-    let text = format!(
+    let mut text = format!(
         "Deleted note: {}\n\n\
          **URI:** memory:{}\n\
          **File:** {}\n\n\
          The note has been permanently deleted.",
         normalized.name, uri, file_path
     );
+    if !backlinks.is_empty() {
+        text.push_str(&format!(
+            "\n\n## Dangling Links\n\n{} note(s) still link to it:\n{}",
+            backlinks.len(),
+            backlink_list
+        ));
+    }
 
     Ok(CallToolResult::success(vec![Content::text(text)]))
 }
@@ -51,6 +109,8 @@ pub async fn execute<S: Storage>(
 mod tests {
     use super::*;
     use crate::storage::FileStorage;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
     use tempfile::TempDir;
     use tokio::fs;
 
@@ -68,7 +128,7 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), &storage, "test")
+        let result = execute(temp_dir.path(), &storage, &GraphIndex::new(), "test", false)
             .await
             .expect("should succeed");
 
@@ -97,9 +157,15 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), &storage, "knowledge/test")
-            .await
-            .expect("should succeed");
+        let result = execute(
+            temp_dir.path(),
+            &storage,
+            &GraphIndex::new(),
+            "knowledge/test",
+            false,
+        )
+        .await
+        .expect("should succeed");
 
         let text = result.content[0]
             .raw
@@ -116,7 +182,14 @@ mod tests {
     async fn test_delete_nonexistent_note() {
         let (temp_dir, storage) = create_test_storage().await;
 
-        let result = execute(temp_dir.path(), &storage, "nonexistent").await;
+        let result = execute(
+            temp_dir.path(),
+            &storage,
+            &GraphIndex::new(),
+            "nonexistent",
+            false,
+        )
+        .await;
 
         assert!(result.is_err());
         let err = result.unwrap_err();
@@ -131,9 +204,15 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), &storage, "[[test]]")
-            .await
-            .expect("should succeed");
+        let result = execute(
+            temp_dir.path(),
+            &storage,
+            &GraphIndex::new(),
+            "[[test]]",
+            false,
+        )
+        .await
+        .expect("should succeed");
 
         let text = result.content[0]
             .raw
@@ -145,4 +224,69 @@ mod tests {
         assert!(text.contains("Deleted note"));
         assert!(!temp_dir.path().join("test.md").exists());
     }
+
+    fn linked_graph() -> GraphIndex {
+        let mut graph = GraphIndex::new();
+        graph.update_note(
+            "A",
+            PathBuf::from("knowledge/A.md"),
+            ["test".to_string()].into_iter().collect(),
+        );
+        graph.update_note("test", PathBuf::from("test.md"), HashSet::new());
+        graph
+    }
+
+    #[tokio::test]
+    async fn test_delete_linked_note_warns_without_force() {
+        let (temp_dir, storage) = create_test_storage().await;
+        fs::write(temp_dir.path().join("test.md"), "Content")
+            .await
+            .unwrap();
+
+        let result = execute(temp_dir.path(), &storage, &linked_graph(), "test", false)
+            .await
+            .expect("should warn, not fail");
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+
+        assert!(text.contains("Not deleted: 1 note(s) link to test"));
+        assert!(text.contains("- memory:knowledge/A"));
+        assert!(temp_dir.path().join("test.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_delete_linked_note_with_force() {
+        let (temp_dir, storage) = create_test_storage().await;
+        fs::write(temp_dir.path().join("test.md"), "Content")
+            .await
+            .unwrap();
+
+        let result = execute(temp_dir.path(), &storage, &linked_graph(), "test", true)
+            .await
+            .expect("should succeed");
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+
+        assert!(text.contains("Deleted note"));
+        assert!(text.contains("Dangling Links"));
+        assert!(text.contains("- memory:knowledge/A"));
+        assert!(!temp_dir.path().join("test.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_delete_ignores_self_links() {
+        let (temp_dir, storage) = create_test_storage().await;
+        fs::write(temp_dir.path().join("test.md"), "See [[test]]")
+            .await
+            .unwrap();
+        let mut graph = GraphIndex::new();
+        graph.update_note(
+            "test",
+            PathBuf::from("test.md"),
+            ["test".to_string()].into_iter().collect(),
+        );
+
+        execute(temp_dir.path(), &storage, &graph, "test", false)
+            .await
+            .expect("should succeed");
+        assert!(!temp_dir.path().join("test.md").exists());
+    }
 }
@@ -8,7 +8,7 @@ use rmcp::model::{CallToolResult, Content, ErrorData};
 use serde::Serialize;
 use std::path::Path;
 
-use super::common::resolve_note_uri;
+use super::common::{read_only_error, resolve_note_uri};
 use crate::graph::GraphIndex;
 use crate::storage::{ContentHash, Storage, StorageError};
 
@@ -153,6 +153,12 @@ pub async fn execute<S: Storage>(
     let file_path = ensure_markdown_extension(&uri);
     let new_hash = ContentHash::from_content(&modified);
 
+    // Fail a preview the same way the real edit would
+    storage.check_writable(&uri).map_err(|e| match e {
+        StorageError::ReadOnly { uri, folder } => read_only_error(&uri, &folder),
+        _ => ErrorData::invalid_params(e.to_string(), None),
+    })?;
+
     if dry_run {
         let response = EditNoteDryRunResponse {
             uri: format!("memory:{}", uri),
@@ -176,6 +182,7 @@ pub async fn execute<S: Storage>(
             ),
             None,
         ),
+        StorageError::ReadOnly { uri, folder } => read_only_error(&uri, &folder),
         _ => ErrorData::internal_error(format!("Failed to write note: {}", e), None),
     })?;
 
@@ -580,6 +587,7 @@ mod tests {
             &storage,
             &graph,
             "My Note",
+            super::super::read_note::NoteFormat::Markdown,
         )
         .await
         .expect("ReadNote should succeed");
@@ -0,0 +1,254 @@
+//! FindDuplicates tool - find near-duplicate notes for review.
+//!
+//! Compares every note's embedding against its closest neighbors and reports
+//! pairs above a high similarity threshold. Notes with identical content are
+//! always reported, whatever their score. Nothing is merged automatically;
+//! the candidates are for the user to review.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use super::search::get_all_notes;
+use crate::embeddings::EmbeddingManager;
+use crate::graph::GraphIndex;
+use crate::storage::ContentHash;
+
+/// Similarity at or above which two notes count as duplicates
+pub const DEFAULT_THRESHOLD: f32 = 0.95;
+
+/// Nearest neighbors checked per note
+const NEIGHBORS_PER_NOTE: usize = 5;
+
+/// A pair of notes that look like duplicates
+#[derive(Debug, PartialEq)]
+struct DuplicatePair {
+    /// Path that sorts first
+    first: String,
+    second: String,
+    score: f32,
+    /// Whether the notes have identical content
+    identical: bool,
+}
+
+/// Execute the FindDuplicates tool.
+///
+/// With `identical_only`, only notes with identical content hashes are
+/// reported and no embeddings are computed.
+pub async fn execute(
+    vault_path: &Path,
+    graph: &GraphIndex,
+    embeddings: &EmbeddingManager,
+    threshold: Option<f32>,
+    identical_only: bool,
+    include_private: bool,
+) -> Result<CallToolResult, ErrorData> {
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD);
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        return Err(ErrorData::invalid_params(
+            format!("threshold must be between 0 and 1, got {}", threshold),
+            None,
+        ));
+    }
+
+    let notes = get_all_notes(vault_path, graph, include_private).await;
+
+    let hashes: HashMap<String, String> = notes
+        .iter()
+        .map(|(path, content)| (path.clone(), ContentHash::from_content(content).as_str().to_string()))
+        .collect();
+
+    let note_embeddings = if identical_only {
+        Vec::new()
+    } else {
+        embeddings
+            .get_embeddings_batch(&notes)
+            .await
+            .map_err(|e| ErrorData::internal_error(format!("Failed to compute embeddings: {}", e), None))?
+    };
+
+    let mut pairs = find_identical(&hashes);
+    for pair in find_similar(&note_embeddings, threshold) {
+        if !pairs.iter().any(|p| p.first == pair.first && p.second == pair.second) {
+            pairs.push(pair);
+        }
+    }
+    pairs.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.first.cmp(&b.first))
+            .then_with(|| a.second.cmp(&b.second))
+    });
+
+    tracing::info!(
+        notes = notes.len(),
+        duplicates = pairs.len(),
+        "Duplicate search complete"
+    );
+
+    Ok(CallToolResult::success(vec![Content::text(format_results(
+        &pairs, threshold,
+    ))]))
+}
+
+/// Pairs of notes with identical content hashes.
+fn find_identical(hashes: &HashMap<String, String>) -> Vec<DuplicatePair> {
+    let mut by_hash: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (path, hash) in hashes {
+        by_hash.entry(hash.as_str()).or_default().push(path.as_str());
+    }
+
+    let mut pairs = Vec::new();
+    for mut paths in by_hash.into_values() {
+        paths.sort();
+        for (i, first) in paths.iter().enumerate() {
+            for second in &paths[i + 1..] {
+                pairs.push(DuplicatePair {
+                    first: first.to_string(),
+                    second: second.to_string(),
+                    score: 1.0,
+                    identical: true,
+                });
+            }
+        }
+    }
+    pairs
+}
+
+/// Pairs of notes whose embeddings are at least `threshold` similar.
+///
+/// Each note is only compared against its nearest neighbors, which keeps the
+/// search well below all-pairs cost on large vaults.
+fn find_similar(notes: &[(String, Vec<f32>)], threshold: f32) -> Vec<DuplicatePair> {
+    let vectors: Vec<Vec<f32>> = notes.iter().map(|(_, embedding)| embedding.clone()).collect();
+    let mut seen = HashSet::new();
+    let mut pairs = Vec::new();
+
+    for (i, (path, embedding)) in notes.iter().enumerate() {
+        // +1 since the note is its own nearest neighbor
+        let Ok(neighbors) = EmbeddingManager::find_most_similar(embedding, &vectors, NEIGHBORS_PER_NOTE + 1) else {
+            continue;
+        };
+
+        for j in neighbors.into_iter().map(|j| j as usize).filter(|&j| j != i) {
+            let Ok(score) = EmbeddingManager::cosine_similarity(embedding, &vectors[j]) else {
+                continue;
+            };
+            // Neighbors are sorted, so the rest are below the threshold too
+            if score < threshold {
+                break;
+            }
+
+            let other = &notes[j].0;
+            let (first, second) = if path < other { (path, other) } else { (other, path) };
+            if seen.insert((first.clone(), second.clone())) {
+                pairs.push(DuplicatePair {
+                    first: first.clone(),
+                    second: second.clone(),
+                    score,
+                    identical: false,
+                });
+            }
+        }
+    }
+    pairs
+}
+
+/// Format duplicate candidates for output.
+fn format_results(pairs: &[DuplicatePair], threshold: f32) -> String {
+    let mut output = String::from("# Possible Duplicate Notes\n\n");
+
+    if pairs.is_empty() {
+        output.push_str(&format!(
+            "No duplicates found at {}% similarity.\n",
+            (threshold * 100.0) as i32
+        ));
+        return output;
+    }
+
+    output.push_str(&format!("Found {} candidate pairs:\n\n", pairs.len()));
+
+    for (i, pair) in pairs.iter().enumerate() {
+        let similarity = if pair.identical {
+            "identical content".to_string()
+        } else {
+            format!("{}% similar", (pair.score * 100.0) as i32)
+        };
+        output.push_str(&format!(
+            "{}. `{}` and `{}` ({})\n",
+            i + 1,
+            pair.first,
+            pair.second,
+            similarity
+        ));
+    }
+
+    output.push_str("\n*Review with ReadNote before merging or deleting either note*\n");
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corpus() -> Vec<(String, Vec<f32>)> {
+        vec![
+            ("knowledge/Rust.md".to_string(), vec![1.0, 0.1, 0.0]),
+            ("knowledge/Rust Language.md".to_string(), vec![0.99, 0.12, 0.0]),
+            ("knowledge/Cargo.md".to_string(), vec![0.7, 0.5, 0.2]),
+            ("knowledge/Gardening.md".to_string(), vec![0.0, 0.0, 1.0]),
+        ]
+    }
+
+    #[test]
+    fn test_find_similar_flags_obvious_duplicate() {
+        let pairs = find_similar(&corpus(), DEFAULT_THRESHOLD);
+
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].first, "knowledge/Rust Language.md");
+        assert_eq!(pairs[0].second, "knowledge/Rust.md");
+        assert!(pairs[0].score >= DEFAULT_THRESHOLD);
+        assert!(!pairs[0].identical);
+    }
+
+    #[test]
+    fn test_find_similar_ignores_dissimilar_notes() {
+        let pairs = find_similar(&corpus(), DEFAULT_THRESHOLD);
+
+        // Cargo is related but not a duplicate, Gardening is unrelated
+        assert!(pairs
+            .iter()
+            .all(|p| !p.first.contains("Cargo") && !p.second.contains("Cargo")));
+        assert!(pairs
+            .iter()
+            .all(|p| !p.first.contains("Gardening") && !p.second.contains("Gardening")));
+    }
+
+    #[test]
+    fn test_find_identical_pairs_by_hash() {
+        let hashes = HashMap::from([
+            ("b.md".to_string(), "hash-1".to_string()),
+            ("a.md".to_string(), "hash-1".to_string()),
+            ("c.md".to_string(), "hash-2".to_string()),
+        ]);
+
+        let pairs = find_identical(&hashes);
+        assert_eq!(
+            pairs,
+            vec![DuplicatePair {
+                first: "a.md".into(),
+                second: "b.md".into(),
+                score: 1.0,
+                identical: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_format_results_empty() {
+        let output = format_results(&[], DEFAULT_THRESHOLD);
+        assert!(output.contains("No duplicates found at 95% similarity."));
+    }
+}
@@ -0,0 +1,270 @@
+//! GenerateToc tool - build a table of contents from a note's headings.
+//!
+//! The TOC is a nested list of markdown links to each heading, indented by
+//! heading level. When requested, it is written into the note between
+//! `<!-- toc -->` and `<!-- /toc -->` markers, replacing any previous TOC.
+
+use obsidian_fs::{ensure_markdown_extension, extract_headings, heading_link_target, Heading};
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+
+use super::common::resolve_note_uri;
+use crate::graph::GraphIndex;
+use crate::storage::{ContentHash, Storage, StorageError};
+
+/// Marker opening the generated TOC block
+pub const TOC_START: &str = "<!-- toc -->";
+/// Marker closing the generated TOC block
+pub const TOC_END: &str = "<!-- /toc -->";
+
+/// Response from GenerateToc tool.
+#[derive(Serialize)]
+pub struct GenerateTocResponse {
+    /// The memory URI of the note
+    pub uri: String,
+    /// The file path relative to vault
+    pub path: String,
+    /// The generated table of contents (markdown list)
+    pub toc: String,
+    /// Whether the TOC was written into the note
+    pub inserted: bool,
+    /// New content hash when the note was modified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+}
+
+/// Build a nested markdown list linking to each heading.
+///
+/// Nesting follows the heading hierarchy rather than raw levels, so a `###`
+/// directly under a `#` is indented once, not twice.
+fn build_toc(headings: &[Heading]) -> String {
+    let mut parents: Vec<usize> = Vec::new();
+    let mut lines = Vec::new();
+
+    for heading in headings.iter().filter(|h| !h.text.is_empty()) {
+        while parents.last().is_some_and(|&level| level >= heading.level) {
+            parents.pop();
+        }
+        lines.push(format!(
+            "{}- [{}]({})",
+            "  ".repeat(parents.len()),
+            heading.text,
+            heading_link_target(&heading.text)
+        ));
+        parents.push(heading.level);
+    }
+
+    lines.join("\n")
+}
+
+/// Replace the content between the TOC markers with `toc`.
+fn insert_toc(content: &str, toc: &str) -> Result<String, String> {
+    let start = content.find(TOC_START).ok_or_else(|| {
+        format!(
+            "No TOC marker found. Add a '{}' line where the table of contents should go.",
+            TOC_START
+        )
+    })?;
+    let after_start = start + TOC_START.len();
+
+    let block = format!("{}\n{}\n{}", TOC_START, toc, TOC_END);
+    let end = content[after_start..]
+        .find(TOC_END)
+        .map(|offset| after_start + offset + TOC_END.len())
+        .unwrap_or(after_start);
+
+    Ok(format!("{}{}{}", &content[..start], block, &content[end..]))
+}
+
+/// Execute the GenerateToc tool.
+///
+/// Returns the TOC for the note. With `insert`, also writes it under the
+/// `<!-- toc -->` marker, which requires content_hash from a previous ReadNote call.
+pub async fn execute<S: Storage>(
+    storage: &S,
+    graph: &GraphIndex,
+    note: &str,
+    insert: bool,
+    content_hash: Option<&str>,
+) -> Result<CallToolResult, ErrorData> {
+    let (uri, exists) = resolve_note_uri(storage, graph, note).await.map_err(|e| {
+        ErrorData::internal_error(format!("Failed to resolve note: {}", e), None)
+    })?;
+
+    if !exists {
+        return Err(ErrorData::invalid_params(
+            format!("Note not found: {}", note),
+            None,
+        ));
+    }
+
+    let (content, _metadata) = storage.read(&uri).await.map_err(|e| {
+        ErrorData::internal_error(format!("Failed to read note: {}", e), None)
+    })?;
+
+    let toc = build_toc(&extract_headings(&content));
+    let path = ensure_markdown_extension(&uri);
+
+    let mut new_hash = None;
+    if insert {
+        let Some(content_hash) = content_hash else {
+            return Err(ErrorData::invalid_params(
+                "content_hash is required to insert the TOC. Read the note first.".to_string(),
+                None,
+            ));
+        };
+
+        let current_hash = ContentHash::from_content(&content);
+        if current_hash.as_str() != content_hash {
+            return Err(ErrorData::invalid_params(
+                format!(
+                    "Note modified since last read (expected hash: {}, actual: {}). \
+                     Read note again to get current content and hash.",
+                    content_hash,
+                    current_hash.as_str()
+                ),
+                None,
+            ));
+        }
+
+        let modified = insert_toc(&content, &toc)
+            .map_err(|e| ErrorData::invalid_params(format!("Insert failed: {}", e), None))?;
+
+        storage.write(&uri, &modified, Some(content_hash)).await.map_err(|e| match e {
+            StorageError::HashMismatch { expected, actual, .. } => ErrorData::invalid_params(
+                format!(
+                    "Note modified since last read (expected hash: {}, actual: {}). \
+                     Read note again to get current content and hash.",
+                    expected, actual
+                ),
+                None,
+            ),
+            _ => ErrorData::internal_error(format!("Failed to write note: {}", e), None),
+        })?;
+
+        new_hash = Some(ContentHash::from_content(&modified).as_str().to_string());
+    }
+
+    let response = GenerateTocResponse {
+        uri: format!("memory:{}", uri),
+        path,
+        toc,
+        inserted: insert,
+        content_hash: new_hash,
+    };
+
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    const NOTE: &str = "# Guide\n\n<!-- toc -->\n\n## Getting Started\n\n### Setup: Linux\n\n### Setup (macOS)\n\n## Usage\n\n#### Deep\n\n# Appendix";
+
+    const EXPECTED_TOC: &str = "\
+- [Guide](#Guide)
+  - [Getting Started](#Getting%20Started)
+    - [Setup: Linux](#Setup%20Linux)
+    - [Setup (macOS)](#Setup%20%28macOS%29)
+  - [Usage](#Usage)
+    - [Deep](#Deep)
+- [Appendix](#Appendix)";
+
+    async fn create_test_env() -> (TempDir, FileStorage, GraphIndex) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
+        let graph = GraphIndex::new();
+        (temp_dir, storage, graph)
+    }
+
+    fn parse_json(result: &CallToolResult) -> serde_json::Value {
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+        serde_json::from_str(text).expect("Expected valid JSON")
+    }
+
+    #[test]
+    fn test_build_toc_nests_by_heading_level() {
+        assert_eq!(build_toc(&extract_headings(NOTE)), EXPECTED_TOC);
+    }
+
+    #[test]
+    fn test_build_toc_empty_note() {
+        assert_eq!(build_toc(&extract_headings("No headings here")), "");
+    }
+
+    #[test]
+    fn test_insert_toc_replaces_previous_block() {
+        let content = "# A\n<!-- toc -->\n- [Old](#Old)\n<!-- /toc -->\nBody";
+        let result = insert_toc(content, "- [A](#A)").unwrap();
+        assert_eq!(result, "# A\n<!-- toc -->\n- [A](#A)\n<!-- /toc -->\nBody");
+
+        // Regenerating is stable
+        assert_eq!(insert_toc(&result, "- [A](#A)").unwrap(), result);
+    }
+
+    #[test]
+    fn test_insert_toc_requires_marker() {
+        let err = insert_toc("# A\nBody", "- [A](#A)").unwrap_err();
+        assert!(err.contains("<!-- toc -->"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_execute_returns_toc_without_modifying_note() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        fs::write(temp_dir.path().join("Guide.md"), NOTE).await.unwrap();
+        graph.update_note("Guide", PathBuf::from("Guide.md"), HashSet::new());
+
+        let result = execute(&storage, &graph, "Guide", false, None)
+            .await
+            .expect("should succeed");
+
+        let json = parse_json(&result);
+        assert_eq!(json["toc"], EXPECTED_TOC);
+        assert_eq!(json["inserted"], false);
+        assert!(json.get("content_hash").is_none());
+
+        let content = fs::read_to_string(temp_dir.path().join("Guide.md")).await.unwrap();
+        assert_eq!(content, NOTE);
+    }
+
+    #[tokio::test]
+    async fn test_execute_inserts_under_marker() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        fs::write(temp_dir.path().join("Guide.md"), NOTE).await.unwrap();
+        graph.update_note("Guide", PathBuf::from("Guide.md"), HashSet::new());
+        let hash = ContentHash::from_content(NOTE);
+
+        let result = execute(&storage, &graph, "Guide", true, Some(hash.as_str()))
+            .await
+            .expect("should succeed");
+
+        let content = fs::read_to_string(temp_dir.path().join("Guide.md")).await.unwrap();
+        let expected_block = format!("<!-- toc -->\n{}\n<!-- /toc -->", EXPECTED_TOC);
+        assert!(content.contains(&expected_block), "{}", content);
+        assert!(content.starts_with("# Guide\n\n<!-- toc -->"));
+        assert!(content.ends_with("# Appendix"));
+
+        let json = parse_json(&result);
+        assert_eq!(json["inserted"], true);
+        assert_eq!(json["content_hash"], ContentHash::from_content(&content).as_str());
+    }
+
+    #[tokio::test]
+    async fn test_execute_insert_requires_hash() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        fs::write(temp_dir.path().join("Guide.md"), NOTE).await.unwrap();
+        graph.update_note("Guide", PathBuf::from("Guide.md"), HashSet::new());
+
+        let err = execute(&storage, &graph, "Guide", true, None).await.unwrap_err();
+        assert!(err.message.contains("content_hash is required"));
+    }
+}
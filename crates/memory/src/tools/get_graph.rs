@@ -0,0 +1,324 @@
+//! GetGraph tool - export the link graph as JSON for visualization.
+//!
+//! Nodes are notes (path and title) and edges are wiki-links between them.
+//! Links to notes that don't exist are kept as `dangling` edges whose target
+//! is the linked name, so external tools can show missing notes too.
+//!
+//! Large graphs are exported in pages of `max_nodes` notes (see
+//! [`super::pagination`]). Each edge is sent with the page that contains the
+//! later of its two notes, so paging through every page yields each node and
+//! edge exactly once, and an edge never arrives before both of its notes.
+
+use std::collections::HashMap;
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+
+use super::pagination::{next_cursor, parse_cursor};
+use crate::graph::GraphIndex;
+
+/// Nodes per page when the caller doesn't give a cap
+pub const DEFAULT_MAX_NODES: usize = 500;
+
+/// How an edge's target was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    /// Target is an indexed note; `target` is its path
+    Resolved,
+    /// No note has the linked name; `target` is the name
+    Dangling,
+}
+
+/// A note in the exported graph.
+#[derive(Debug, Serialize)]
+pub struct GraphNode {
+    /// The file path relative to vault
+    pub path: String,
+    /// Frontmatter `title` if set, otherwise the note name
+    pub title: String,
+}
+
+/// A wiki-link in the exported graph.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct GraphEdge {
+    /// Path of the linking note
+    pub source: String,
+    /// Path of the linked note, or the linked name when dangling
+    pub target: String,
+    pub kind: LinkKind,
+}
+
+/// Response from GetGraph tool.
+#[derive(Serialize)]
+pub struct GetGraphResponse {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Whether more nodes are left on later pages
+    pub truncated: bool,
+    /// Cursor for the next page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Note title from frontmatter, falling back to the file stem.
+fn node_title(graph: &GraphIndex, path: &str) -> String {
+    graph
+        .get_frontmatter(path)
+        .and_then(|fm| fm.get("title"))
+        .and_then(|title| title.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            let file_name = path.rsplit('/').next().unwrap_or(path);
+            file_name.strip_suffix(".md").unwrap_or(file_name).to_string()
+        })
+}
+
+/// Build one page of the graph export.
+///
+/// Nodes are sorted by path, and the page holds up to `max_nodes` of them
+/// starting at index `start`. Edges are kept when both ends pass the filters
+/// (or the target is dangling), and are exported with the page holding the
+/// later of their two nodes.
+fn build_graph(
+    graph: &GraphIndex,
+    folder: Option<&str>,
+    include_private: bool,
+    max_nodes: usize,
+    start: usize,
+) -> GetGraphResponse {
+    let prefix = folder.map(|f| format!("{}/", f.trim_matches('/')));
+
+    let mut paths: Vec<&String> = graph
+        .all_paths()
+        .filter(|path| include_private || !graph.is_private(path))
+        .filter(|path| prefix.as_ref().is_none_or(|prefix| path.starts_with(prefix)))
+        .collect();
+    paths.sort();
+    let total = paths.len();
+    let page = start.min(total)..start.saturating_add(max_nodes).min(total);
+    let index: HashMap<&str, usize> = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| (path.as_str(), i))
+        .collect();
+
+    let mut edges = Vec::new();
+    // Edges on this page have both ends before its end
+    for (source_index, source) in paths[..page.end].iter().enumerate() {
+        let Some(links) = graph.get_forward_links(source) else {
+            continue;
+        };
+        for name in links {
+            let edge = match graph.get_path(name) {
+                Some(target) => {
+                    let target = target.to_string_lossy().to_string();
+                    let Some(&target_index) = index.get(target.as_str()) else {
+                        continue;
+                    };
+                    if !page.contains(&source_index.max(target_index)) {
+                        continue;
+                    }
+                    GraphEdge {
+                        source: source.to_string(),
+                        target,
+                        kind: LinkKind::Resolved,
+                    }
+                }
+                None if page.contains(&source_index) => GraphEdge {
+                    source: source.to_string(),
+                    target: name.clone(),
+                    kind: LinkKind::Dangling,
+                },
+                None => continue,
+            };
+            edges.push(edge);
+        }
+    }
+    edges.sort();
+
+    let nodes = paths[page.clone()]
+        .iter()
+        .map(|path| GraphNode {
+            path: path.to_string(),
+            title: node_title(graph, path),
+        })
+        .collect();
+
+    let next_cursor = next_cursor(page.end, total);
+    GetGraphResponse {
+        nodes,
+        edges,
+        truncated: next_cursor.is_some(),
+        next_cursor,
+    }
+}
+
+/// Execute the GetGraph tool.
+///
+/// Exports the page at `cursor` (the first page if `None`), of up to
+/// `max_nodes` notes. Private notes are only exported with `include_private`,
+/// which requires explicit user consent.
+pub fn execute(
+    graph: &GraphIndex,
+    folder: Option<&str>,
+    include_private: bool,
+    max_nodes: Option<usize>,
+    cursor: Option<&str>,
+) -> Result<CallToolResult, ErrorData> {
+    let max_nodes = max_nodes.unwrap_or(DEFAULT_MAX_NODES);
+    if max_nodes == 0 {
+        return Err(ErrorData::invalid_params(
+            "max_nodes must be at least 1".to_string(),
+            None,
+        ));
+    }
+
+    let start = parse_cursor(cursor)?;
+    let response = build_graph(graph, folder, include_private, max_nodes, start);
+
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    fn links(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    /// A links to B and a missing note, B links to C, C links to A and a private note.
+    fn small_vault() -> GraphIndex {
+        let mut graph = GraphIndex::new();
+        graph.update_note("A", PathBuf::from("A.md"), links(&["B", "Missing"]));
+        graph.update_note("B", PathBuf::from("knowledge/B.md"), links(&["C"]));
+        graph.update_note("C", PathBuf::from("knowledge/C.md"), links(&["A", "Secret"]));
+        graph.update_note("Secret", PathBuf::from("private/Secret.md"), links(&["A"]));
+        graph
+    }
+
+    fn edge(source: &str, target: &str, kind: LinkKind) -> GraphEdge {
+        GraphEdge {
+            source: source.into(),
+            target: target.into(),
+            kind,
+        }
+    }
+
+    fn node_paths(response: &GetGraphResponse) -> Vec<&str> {
+        response.nodes.iter().map(|n| n.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_export_matches_indexed_edges() {
+        let graph = small_vault();
+        let response = build_graph(&graph, None, true, DEFAULT_MAX_NODES, 0);
+
+        assert_eq!(
+            node_paths(&response),
+            vec!["A.md", "knowledge/B.md", "knowledge/C.md", "private/Secret.md"]
+        );
+        assert_eq!(
+            response.edges,
+            vec![
+                edge("A.md", "Missing", LinkKind::Dangling),
+                edge("A.md", "knowledge/B.md", LinkKind::Resolved),
+                edge("knowledge/B.md", "knowledge/C.md", LinkKind::Resolved),
+                edge("knowledge/C.md", "A.md", LinkKind::Resolved),
+                edge("knowledge/C.md", "private/Secret.md", LinkKind::Resolved),
+                edge("private/Secret.md", "A.md", LinkKind::Resolved),
+            ]
+        );
+        assert!(!response.truncated);
+    }
+
+    #[test]
+    fn test_private_notes_excluded_without_consent() {
+        let graph = small_vault();
+        let response = build_graph(&graph, None, false, DEFAULT_MAX_NODES, 0);
+
+        assert!(!node_paths(&response).contains(&"private/Secret.md"));
+        assert!(response
+            .edges
+            .iter()
+            .all(|e| e.source != "private/Secret.md" && e.target != "private/Secret.md"));
+    }
+
+    #[test]
+    fn test_folder_filter_keeps_edges_within_folder() {
+        let graph = small_vault();
+        let response = build_graph(&graph, Some("knowledge"), false, DEFAULT_MAX_NODES, 0);
+
+        assert_eq!(node_paths(&response), vec!["knowledge/B.md", "knowledge/C.md"]);
+        assert_eq!(
+            response.edges,
+            vec![edge("knowledge/B.md", "knowledge/C.md", LinkKind::Resolved)]
+        );
+    }
+
+    #[test]
+    fn test_max_nodes_truncates() {
+        let graph = small_vault();
+        let response = build_graph(&graph, None, false, 2, 0);
+
+        assert_eq!(node_paths(&response), vec!["A.md", "knowledge/B.md"]);
+        assert!(response.truncated);
+        assert!(response.edges.iter().all(|e| e.target != "knowledge/C.md"));
+    }
+
+    #[test]
+    fn test_paging_exports_every_node_and_edge_once() {
+        let graph = small_vault();
+        let full = build_graph(&graph, None, true, DEFAULT_MAX_NODES, 0);
+        assert_eq!(full.next_cursor, None);
+
+        for max_nodes in 1..=4 {
+            let mut nodes = Vec::new();
+            let mut edges = Vec::new();
+            let mut start = 0;
+            loop {
+                let page = build_graph(&graph, None, true, max_nodes, start);
+                // Edges only arrive once both of their notes have
+                nodes.extend(page.nodes.iter().map(|n| n.path.clone()));
+                assert!(page
+                    .edges
+                    .iter()
+                    .all(|e| nodes.contains(&e.source)
+                        && (e.kind == LinkKind::Dangling || nodes.contains(&e.target))));
+                edges.extend(page.edges);
+                match page.next_cursor {
+                    Some(cursor) => start = parse_cursor(Some(&cursor)).unwrap(),
+                    None => break,
+                }
+            }
+            edges.sort();
+
+            assert_eq!(nodes, node_paths(&full), "max_nodes {}", max_nodes);
+            assert_eq!(edges, full.edges, "max_nodes {}", max_nodes);
+        }
+    }
+
+    #[test]
+    fn test_title_prefers_frontmatter() {
+        let mut graph = small_vault();
+        let mut frontmatter = obsidian_fs::Frontmatter::new();
+        frontmatter.insert("title".into(), serde_json::json!("Note A"));
+        graph.set_frontmatter("A.md", Some(frontmatter));
+
+        assert_eq!(node_title(&graph, "A.md"), "Note A");
+        assert_eq!(node_title(&graph, "knowledge/B.md"), "B");
+    }
+
+    #[test]
+    fn test_execute_rejects_zero_max_nodes() {
+        let graph = small_vault();
+        let err = execute(&graph, None, false, Some(0), None).unwrap_err();
+        assert!(err.message.contains("max_nodes"));
+    }
+}
@@ -0,0 +1,222 @@
+//! GetNoteHistory tool - list a note's edits from its sync document.
+//!
+//! Only available in synced vaults: the sync daemon keeps a Loro document for
+//! every note under `.sync/documents/`, recording each change with the peer
+//! that made it and when. History is read from that file, so it reflects what
+//! the daemon last saved.
+
+use chrono::{DateTime, Local};
+use obsidian_fs::{ensure_markdown_extension, normalize_note_reference};
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+use std::path::Path;
+use sync_core::{HistoryEntry, NoteDocument, PeerId};
+use tokio::fs;
+
+/// Whether the vault has sync state to read history from.
+pub fn sync_enabled(vault_path: &Path) -> bool {
+    vault_path.join(".sync").is_dir()
+}
+
+/// One change to a note.
+#[derive(Debug, Serialize)]
+pub struct HistoryItem {
+    /// Pass as `version` to read the note as of this change
+    pub version: String,
+    /// Peer (device) that made the change
+    pub peer: String,
+    /// When the change was made, if recorded
+    pub timestamp: Option<String>,
+}
+
+impl From<HistoryEntry> for HistoryItem {
+    fn from(entry: HistoryEntry) -> Self {
+        Self {
+            version: entry.version,
+            peer: entry.peer.to_string(),
+            timestamp: entry
+                .timestamp
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|time| time.with_timezone(&Local).to_rfc3339()),
+        }
+    }
+}
+
+/// A note's history, plus its content at one version if requested.
+#[derive(Debug, Serialize)]
+pub struct NoteHistoryResponse {
+    pub uri: String,
+    /// Changes, oldest first
+    pub history: Vec<HistoryItem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+/// Load the sync document for the note at `path` (vault-relative, with extension).
+async fn load_document(vault_path: &Path, path: &str) -> Result<NoteDocument, ErrorData> {
+    if !sync_enabled(vault_path) {
+        return Err(ErrorData::invalid_params(
+            "Note history needs sync enabled: this vault has no .sync folder.".to_string(),
+            None,
+        ));
+    }
+
+    let sync_path = vault_path.join(sync_core::document_sync_path(path));
+    let bytes = match fs::read(&sync_path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ErrorData::invalid_params(
+                format!(
+                    "No sync history for {}. The note may not be synced yet.",
+                    path
+                ),
+                None,
+            ));
+        }
+        Err(e) => {
+            return Err(ErrorData::internal_error(
+                format!("Failed to read sync document for {}: {}", path, e),
+                None,
+            ));
+        }
+    };
+
+    // Loaded read-only and never saved, so the peer ID is never recorded
+    NoteDocument::from_bytes(path, &bytes, PeerId::generate()).map_err(|e| {
+        ErrorData::internal_error(
+            format!("Failed to load sync document for {}: {}", path, e),
+            None,
+        )
+    })
+}
+
+/// Every recorded change to the note at `path`, oldest first.
+pub async fn get_note_history(
+    vault_path: &Path,
+    path: &str,
+) -> Result<Vec<HistoryEntry>, ErrorData> {
+    Ok(load_document(vault_path, path).await?.history())
+}
+
+/// The note at `path` as of a `version` from its history.
+pub async fn get_note_at_version(
+    vault_path: &Path,
+    path: &str,
+    version: &str,
+) -> Result<String, ErrorData> {
+    load_document(vault_path, path)
+        .await?
+        .markdown_at_version(version)
+        .map_err(|e| {
+            ErrorData::invalid_params(
+                format!("{}. Pass a version from the note's history.", e),
+                None,
+            )
+        })
+}
+
+/// Execute the GetNoteHistory tool.
+pub async fn execute(
+    vault_path: &Path,
+    note: &str,
+    version: Option<&str>,
+) -> Result<CallToolResult, ErrorData> {
+    let normalized = normalize_note_reference(note);
+    let path = ensure_markdown_extension(&normalized.path);
+
+    let history = get_note_history(vault_path, &path).await?;
+    let content = match version {
+        Some(version) => Some(get_note_at_version(vault_path, &path, version).await?),
+        None => None,
+    };
+
+    let response = NoteHistoryResponse {
+        uri: format!("memory:{}", normalized.path),
+        history: history.into_iter().map(HistoryItem::from).collect(),
+        version: version.map(str::to_string),
+        content,
+    };
+    let json = serde_json::to_string_pretty(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize: {}", e), None))?;
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Save a synced note with three edits, as the sync daemon would.
+    async fn synced_vault() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let doc =
+            NoteDocument::from_markdown("knowledge/Note.md", "First", PeerId::from(1u64)).unwrap();
+        doc.update_body("Second").unwrap();
+        doc.commit();
+        doc.update_body("Third").unwrap();
+        doc.commit();
+
+        let sync_path = temp
+            .path()
+            .join(sync_core::document_sync_path("knowledge/Note.md"));
+        fs::create_dir_all(sync_path.parent().unwrap())
+            .await
+            .unwrap();
+        fs::write(&sync_path, doc.export_snapshot()).await.unwrap();
+        temp
+    }
+
+    #[tokio::test]
+    async fn test_three_edits_report_three_entries() {
+        let temp = synced_vault().await;
+        let history = get_note_history(temp.path(), "knowledge/Note.md")
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 3);
+        assert!(history.iter().all(|entry| entry.peer == PeerId::from(1u64)));
+
+        let first = get_note_at_version(temp.path(), "knowledge/Note.md", &history[0].version)
+            .await
+            .unwrap();
+        assert_eq!(first, "First");
+    }
+
+    #[tokio::test]
+    async fn test_execute_returns_history_and_content() {
+        let temp = synced_vault().await;
+        let history = get_note_history(temp.path(), "knowledge/Note.md")
+            .await
+            .unwrap();
+
+        let result = execute(temp.path(), "[[knowledge/Note]]", Some(&history[1].version))
+            .await
+            .unwrap();
+        let text = result.content[0].raw.as_text().unwrap().text.clone();
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["uri"], "memory:knowledge/Note");
+        assert_eq!(json["history"].as_array().unwrap().len(), 3);
+        assert_eq!(json["content"], "Second");
+
+        let err = execute(temp.path(), "knowledge/Note", Some("bogus"))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Invalid version"));
+    }
+
+    #[tokio::test]
+    async fn test_requires_sync() {
+        let temp = TempDir::new().unwrap();
+        let err = execute(temp.path(), "knowledge/Note", None)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("sync enabled"));
+
+        let synced = synced_vault().await;
+        let err = execute(synced.path(), "knowledge/Other", None)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("No sync history"));
+    }
+}
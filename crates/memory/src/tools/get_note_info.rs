@@ -1,11 +1,14 @@
-use obsidian_fs::{ensure_markdown_extension, generate_search_paths, normalize_note_reference, NoteRef};
+use obsidian_fs::{
+    ensure_markdown_extension, generate_search_paths, normalize_note_reference, NoteRef,
+    ResolutionOptions,
+};
 use rmcp::model::{CallToolResult, Content, ErrorData};
 use std::path::Path;
 
 use crate::graph::GraphIndex;
 use crate::tools::common::{
-    format_frontmatter_summary, format_links_summary, read_frontmatter_keys, resolve_backlinks,
-    resolve_forward_links,
+    format_frontmatter_summary, format_links_summary, obsidian_uri, read_frontmatter_keys,
+    resolve_backlinks, resolve_forward_links,
 };
 
 /// Resolve a note reference to a file path, searching the vault if needed.
@@ -34,7 +37,7 @@ async fn resolve_note_to_path(
     }
 
     // Generate search paths and try each one
-    let search_paths = generate_search_paths(&note_ref.name, false);
+    let search_paths = generate_search_paths(&note_ref.name, &ResolutionOptions::default());
     for search_path in &search_paths {
         let file_path = vault_path.join(ensure_markdown_extension(search_path));
         if file_path.exists() {
@@ -66,11 +69,7 @@ pub async fn execute(
         .to_string_lossy()
         .to_string();
     let memory_uri = format!("memory:{}", resolved_path);
-    let obsidian_uri = format!(
-        "obsidian://open?vault={}&file={}",
-        urlencoding::encode(vault_name),
-        urlencoding::encode(&resolved_path)
-    );
+    let obsidian_uri = obsidian_uri(vault_name, &resolved_path)?;
 
     if !exists {
         // Note doesn't exist - return helpful message
@@ -0,0 +1,25 @@
+//! GetServerMetrics tool - report per-tool call counts and latencies.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+
+use crate::metrics::{ToolMetrics, ToolMetricsSnapshot};
+
+/// Response from GetServerMetrics tool.
+#[derive(Serialize)]
+pub struct GetServerMetricsResponse {
+    /// One entry per tool called since the server started, sorted by name
+    pub tools: Vec<ToolMetricsSnapshot>,
+}
+
+/// Execute the GetServerMetrics tool.
+pub fn execute(metrics: &ToolMetrics) -> Result<CallToolResult, ErrorData> {
+    let response = GetServerMetricsResponse {
+        tools: metrics.snapshot(),
+    };
+
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
@@ -7,8 +7,8 @@ use tokio::fs;
 
 use crate::graph::GraphIndex;
 use crate::tools::common::{
-    format_frontmatter_summary, format_links_summary, read_frontmatter_keys, resolve_backlinks,
-    resolve_forward_links,
+    format_frontmatter_summary, format_links_summary, obsidian_uri, read_frontmatter_keys,
+    resolve_backlinks, resolve_forward_links,
 };
 
 /// Get the current ISO week date string and day name.
@@ -57,11 +57,7 @@ pub async fn execute(
         .to_string_lossy()
         .to_string();
     let memory_uri = format!("memory:{}", note_path);
-    let obsidian_uri = format!(
-        "obsidian://open?vault={}&file={}",
-        urlencoding::encode(vault_name),
-        urlencoding::encode(&note_path)
-    );
+    let obsidian_uri = obsidian_uri(vault_name, &note_path)?;
 
     // Check if file exists
     let exists = fs::metadata(&file_path).await.is_ok();
@@ -0,0 +1,352 @@
+//! InsertUnderHeading tool - add content to a heading's section.
+//!
+//! Locates a heading and inserts content at the top or bottom of its section
+//! (everything up to the next heading of the same or higher level), appending
+//! a new section when the heading doesn't exist yet. More robust than EditNote's
+//! oldText matching for structured notes like task lists.
+
+use obsidian_fs::{ensure_markdown_extension, extract_headings, parse_heading};
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::common::resolve_note_uri;
+use crate::graph::GraphIndex;
+use crate::storage::{ContentHash, Storage, StorageError};
+
+/// Where in the section to insert.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum InsertPosition {
+    /// Directly below the heading
+    Top,
+    /// After the last line of the section
+    #[default]
+    Bottom,
+}
+
+/// Response from InsertUnderHeading tool.
+#[derive(Serialize)]
+pub struct InsertUnderHeadingResponse {
+    /// The memory URI of the note
+    pub uri: String,
+    /// The file path relative to vault
+    pub path: String,
+    /// New content hash after insert - use this for subsequent writes
+    pub content_hash: String,
+    /// Whether the heading was missing and a new section was appended
+    pub heading_created: bool,
+}
+
+/// Level used when creating a heading given without `#` markers.
+const DEFAULT_HEADING_LEVEL: usize = 2;
+
+/// Insert `insert` into the section under `heading`.
+///
+/// `heading` is either bare text ("Tasks"), matching a heading of any level,
+/// or includes its markers ("## Tasks"), matching only that level.
+/// Returns the new note content and whether the heading had to be created.
+fn insert_under_heading(
+    content: &str,
+    heading: &str,
+    insert: &str,
+    position: InsertPosition,
+) -> Result<(String, bool), String> {
+    let (wanted_level, wanted_text) = match parse_heading(heading.trim()) {
+        Some((level, text)) => (Some(level), text),
+        None => (None, heading.trim()),
+    };
+    if wanted_text.is_empty() {
+        return Err("Heading text is empty".to_string());
+    }
+
+    let insert = insert.trim_end_matches('\n');
+    let headings = extract_headings(content);
+    let matches: Vec<_> = headings
+        .iter()
+        .enumerate()
+        .filter(|(_, h)| h.text == wanted_text && wanted_level.is_none_or(|l| l == h.level))
+        .collect();
+
+    let (index, target) = match matches.as_slice() {
+        [] => {
+            let level = wanted_level.unwrap_or(DEFAULT_HEADING_LEVEL);
+            let mut result = content.trim_end_matches('\n').to_string();
+            if !result.is_empty() {
+                result.push_str("\n\n");
+            }
+            result.push_str(&format!("{} {}\n{}\n", "#".repeat(level), wanted_text, insert));
+            return Ok((result, true));
+        }
+        [single] => *single,
+        many => {
+            return Err(format!(
+                "Heading '{}' appears {} times in note - insert would be ambiguous. \
+                 Include the heading level (e.g. '## {}') or use EditNote.",
+                wanted_text,
+                many.len(),
+                wanted_text
+            ));
+        }
+    };
+
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    // Section runs until the next heading at the same or a higher level
+    let section_end = headings[index + 1..]
+        .iter()
+        .find(|h| h.level <= target.level)
+        .map(|h| h.line)
+        .unwrap_or(lines.len());
+
+    let at = match position {
+        InsertPosition::Top => {
+            // Keep any blank line between the heading and its content
+            let mut at = target.line + 1;
+            while at < section_end && lines[at].trim().is_empty() && at + 1 < section_end {
+                at += 1;
+            }
+            at
+        }
+        InsertPosition::Bottom => {
+            // After the last non-blank line, leaving spacing before the next heading intact
+            let mut at = section_end;
+            while at > target.line + 1 && lines[at - 1].trim().is_empty() {
+                at -= 1;
+            }
+            at
+        }
+    };
+
+    lines.splice(at..at, insert.split('\n'));
+    Ok((lines.join("\n"), false))
+}
+
+/// Execute the InsertUnderHeading tool.
+///
+/// Inserts content into the section under a heading, creating the heading at
+/// the end of the note if it doesn't exist. Requires content_hash from a
+/// previous ReadNote call.
+pub async fn execute<S: Storage>(
+    storage: &S,
+    graph: &GraphIndex,
+    note: &str,
+    heading: &str,
+    content: &str,
+    position: InsertPosition,
+    content_hash: &str,
+) -> Result<CallToolResult, ErrorData> {
+    let (uri, exists) = resolve_note_uri(storage, graph, note).await.map_err(|e| {
+        ErrorData::internal_error(format!("Failed to resolve note: {}", e), None)
+    })?;
+
+    if !exists {
+        return Err(ErrorData::invalid_params(
+            format!("Note not found: {}", note),
+            None,
+        ));
+    }
+
+    let (current, _metadata) = storage.read(&uri).await.map_err(|e| {
+        ErrorData::internal_error(format!("Failed to read note: {}", e), None)
+    })?;
+
+    // Validate content_hash matches current content
+    let current_hash = ContentHash::from_content(&current);
+    if current_hash.as_str() != content_hash {
+        return Err(ErrorData::invalid_params(
+            format!(
+                "Note modified since last read (expected hash: {}, actual: {}). \
+                 Read note again to get current content and hash.",
+                content_hash,
+                current_hash.as_str()
+            ),
+            None,
+        ));
+    }
+
+    let (modified, heading_created) = insert_under_heading(&current, heading, content, position)
+        .map_err(|e| ErrorData::invalid_params(format!("Insert failed: {}", e), None))?;
+
+    // Write with optimistic locking (TOCTOU protection)
+    storage.write(&uri, &modified, Some(content_hash)).await.map_err(|e| match e {
+        StorageError::HashMismatch { expected, actual, .. } => ErrorData::invalid_params(
+            format!(
+                "Note modified since last read (expected hash: {}, actual: {}). \
+                 Read note again to get current content and hash.",
+                expected, actual
+            ),
+            None,
+        ),
+        _ => ErrorData::internal_error(format!("Failed to write note: {}", e), None),
+    })?;
+
+    let response = InsertUnderHeadingResponse {
+        uri: format!("memory:{}", uri),
+        path: ensure_markdown_extension(&uri),
+        content_hash: ContentHash::from_content(&modified).as_str().to_string(),
+        heading_created,
+    };
+
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    const NOTE: &str = "# Project\n\n## Tasks\n\n- first\n- second\n\n## Notes\n\nSome notes";
+
+    async fn create_test_env() -> (TempDir, FileStorage, GraphIndex) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
+        let graph = GraphIndex::new();
+        (temp_dir, storage, graph)
+    }
+
+    #[test]
+    fn test_insert_at_bottom_of_section() {
+        let (result, created) =
+            insert_under_heading(NOTE, "## Tasks", "- third", InsertPosition::Bottom).unwrap();
+        assert!(!created);
+        assert_eq!(
+            result,
+            "# Project\n\n## Tasks\n\n- first\n- second\n- third\n\n## Notes\n\nSome notes"
+        );
+    }
+
+    #[test]
+    fn test_insert_at_top_of_section() {
+        let (result, _) =
+            insert_under_heading(NOTE, "Tasks", "- zeroth", InsertPosition::Top).unwrap();
+        assert_eq!(
+            result,
+            "# Project\n\n## Tasks\n\n- zeroth\n- first\n- second\n\n## Notes\n\nSome notes"
+        );
+    }
+
+    #[test]
+    fn test_section_includes_subheadings() {
+        let note = "## Tasks\n- one\n### Later\n- two\n## Done\n- old";
+        let (result, _) =
+            insert_under_heading(note, "Tasks", "- three", InsertPosition::Bottom).unwrap();
+        assert_eq!(result, "## Tasks\n- one\n### Later\n- two\n- three\n## Done\n- old");
+    }
+
+    #[test]
+    fn test_insert_into_last_section() {
+        let (result, _) =
+            insert_under_heading(NOTE, "Notes", "More notes", InsertPosition::Bottom).unwrap();
+        assert!(result.ends_with("Some notes\nMore notes"), "{}", result);
+    }
+
+    #[test]
+    fn test_missing_heading_creates_section() {
+        let (result, created) =
+            insert_under_heading(NOTE, "Questions", "- why?", InsertPosition::Top).unwrap();
+        assert!(created);
+        assert!(result.ends_with("Some notes\n\n## Questions\n- why?\n"), "{}", result);
+
+        let (result, _) =
+            insert_under_heading("", "### Ideas", "- one", InsertPosition::Bottom).unwrap();
+        assert_eq!(result, "### Ideas\n- one\n");
+    }
+
+    #[test]
+    fn test_heading_level_must_match_when_given() {
+        let (_, created) =
+            insert_under_heading(NOTE, "### Tasks", "- x", InsertPosition::Bottom).unwrap();
+        assert!(created);
+    }
+
+    #[test]
+    fn test_ambiguous_heading_rejected() {
+        let note = "## Tasks\n- a\n## Tasks\n- b";
+        let err = insert_under_heading(note, "Tasks", "- c", InsertPosition::Bottom).unwrap_err();
+        assert!(err.contains("appears 2 times"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_execute_inserts_under_existing_heading() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        fs::write(temp_dir.path().join("Project.md"), NOTE).await.unwrap();
+        graph.update_note("Project", PathBuf::from("Project.md"), HashSet::new());
+        let hash = ContentHash::from_content(NOTE);
+
+        let result = execute(
+            &storage,
+            &graph,
+            "Project",
+            "Tasks",
+            "- third",
+            InsertPosition::Bottom,
+            hash.as_str(),
+        )
+        .await
+        .expect("should succeed");
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        let json: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(json["heading_created"], false);
+
+        let content = fs::read_to_string(temp_dir.path().join("Project.md")).await.unwrap();
+        assert!(content.contains("- second\n- third\n\n## Notes"), "{}", content);
+        assert_eq!(json["content_hash"], ContentHash::from_content(&content).as_str());
+    }
+
+    #[tokio::test]
+    async fn test_execute_creates_new_section() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        fs::write(temp_dir.path().join("Project.md"), NOTE).await.unwrap();
+        graph.update_note("Project", PathBuf::from("Project.md"), HashSet::new());
+        let hash = ContentHash::from_content(NOTE);
+
+        let result = execute(
+            &storage,
+            &graph,
+            "Project",
+            "## Blockers",
+            "- waiting on review",
+            InsertPosition::Top,
+            hash.as_str(),
+        )
+        .await
+        .expect("should succeed");
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        let json: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(json["heading_created"], true);
+
+        let content = fs::read_to_string(temp_dir.path().join("Project.md")).await.unwrap();
+        assert!(content.ends_with("\n\n## Blockers\n- waiting on review\n"), "{}", content);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_wrong_hash() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        fs::write(temp_dir.path().join("Project.md"), NOTE).await.unwrap();
+        graph.update_note("Project", PathBuf::from("Project.md"), HashSet::new());
+
+        let result = execute(
+            &storage,
+            &graph,
+            "Project",
+            "Tasks",
+            "- third",
+            InsertPosition::Bottom,
+            "wrong_hash",
+        )
+        .await;
+
+        assert!(result.unwrap_err().message.contains("Note modified since last read"));
+        let content = fs::read_to_string(temp_dir.path().join("Project.md")).await.unwrap();
+        assert_eq!(content, NOTE);
+    }
+}
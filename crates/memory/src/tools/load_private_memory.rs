@@ -5,14 +5,19 @@
 //! why it needs access, creating a consent-based access model.
 
 use rmcp::model::{CallToolResult, Content, ErrorData};
+use obsidian_fs::PrivateRoots;
 use std::path::Path;
 use tokio::fs;
 
 /// Execute the LoadPrivateMemory tool.
 ///
-/// Loads the private Working Memory file and returns its content along with
-/// a list of available private knowledge notes.
-pub async fn execute(vault_path: &Path, reason: &str) -> Result<CallToolResult, ErrorData> {
+/// Loads the Working Memory file of each private root and returns its content
+/// along with a list of available private knowledge notes.
+pub async fn execute(
+    vault_path: &Path,
+    private_roots: &PrivateRoots,
+    reason: &str,
+) -> Result<CallToolResult, ErrorData> {
     if reason.trim().is_empty() {
         return Err(ErrorData::invalid_params(
             "A reason for loading private memory is required".to_string(),
@@ -20,64 +25,86 @@ pub async fn execute(vault_path: &Path, reason: &str) -> Result<CallToolResult,
         ));
     }
 
-    let private_dir = vault_path.join("private");
-    let private_wm_path = private_dir.join("Working Memory.md");
+    let roots: Vec<&String> = private_roots
+        .roots()
+        .iter()
+        .filter(|root| vault_path.join(root).is_dir())
+        .collect();
 
-    // Check if private directory exists
-    if !private_dir.exists() {
+    // Check if any private directory exists
+    if private_roots.roots().is_empty() {
         return Ok(CallToolResult::success(vec![Content::text(
-            "No private memory directory found. Create `private/` folder in your vault to use private memory."
+            "No private folders are configured. Set OBSIDIAN_PRIVATE_ROOTS to use private memory.",
         )]));
     }
+    if roots.is_empty() {
+        let folders = private_roots
+            .roots()
+            .iter()
+            .map(|root| format!("`{}/`", root))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        return Ok(CallToolResult::success(vec![Content::text(format!(
+            "No private memory directory found. Create {} folder in your vault to use private memory.",
+            folders
+        ))]));
+    }
 
     let mut result_parts = Vec::new();
+    let mut private_notes = Vec::new();
 
-    // Load private Working Memory if it exists
-    match fs::read_to_string(&private_wm_path).await {
-        Ok(content) => {
-            result_parts.push(format!(
-                "## private/Working Memory.md\n\n{}",
-                content
-            ));
-        }
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-            result_parts.push(
-                "## private/Working Memory.md\n\nFile does not exist. Create it to store private working memory."
-                    .to_string(),
-            );
-        }
-        Err(e) => {
-            return Err(ErrorData::internal_error(
-                format!("Failed to read private Working Memory: {}", e),
-                None,
-            ));
+    for root in roots {
+        let private_dir = vault_path.join(root);
+        let private_wm_path = private_dir.join("Working Memory.md");
+
+        // Load private Working Memory if it exists
+        match fs::read_to_string(&private_wm_path).await {
+            Ok(content) => {
+                result_parts.push(format!(
+                    "## {}/Working Memory.md\n\n{}",
+                    root, content
+                ));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                result_parts.push(format!(
+                    "## {}/Working Memory.md\n\nFile does not exist. Create it to store private working memory.",
+                    root
+                ));
+            }
+            Err(e) => {
+                return Err(ErrorData::internal_error(
+                    format!("Failed to read private Working Memory: {}", e),
+                    None,
+                ));
+            }
         }
-    }
 
-    // List available private knowledge notes
-    let mut private_notes = Vec::new();
-    if let Ok(mut entries) = fs::read_dir(&private_dir).await {
-        while let Ok(Some(entry)) = entries.next_entry().await {
-            let path = entry.path();
-            if path.extension().map(|e| e == "md").unwrap_or(false) {
-                if let Some(name) = path.file_stem() {
+        // List available private knowledge notes
+        let mut root_notes = Vec::new();
+        if let Ok(mut entries) = fs::read_dir(&private_dir).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if path.extension().map(|e| e == "md").unwrap_or(false)
+                    && let Some(name) = path.file_stem()
+                {
                     let name_str = name.to_string_lossy();
                     // Skip Working Memory since we already loaded it
                     if name_str != "Working Memory" {
-                        private_notes.push(name_str.to_string());
+                        root_notes.push(format!("{}/{}", root, name_str));
                     }
                 }
             }
         }
+        root_notes.sort();
+        private_notes.extend(root_notes);
     }
 
     if !private_notes.is_empty() {
-        private_notes.sort();
         result_parts.push(format!(
             "\n## Available Private Notes\n\n{}",
             private_notes
                 .iter()
-                .map(|n| format!("- [[private/{}]]", n))
+                .map(|n| format!("- [[{}]]", n))
                 .collect::<Vec<_>>()
                 .join("\n")
         ));
@@ -101,14 +128,14 @@ mod tests {
     #[tokio::test]
     async fn test_requires_reason() {
         let temp_dir = TempDir::new().unwrap();
-        let result = execute(temp_dir.path(), "").await;
+        let result = execute(temp_dir.path(), &PrivateRoots::default(), "").await;
         assert!(result.is_err());
     }
 
     #[tokio::test]
     async fn test_no_private_dir() {
         let temp_dir = TempDir::new().unwrap();
-        let result = execute(temp_dir.path(), "Testing").await;
+        let result = execute(temp_dir.path(), &PrivateRoots::default(), "Testing").await;
         assert!(result.is_ok());
 
         let call_result = result.unwrap();
@@ -130,7 +157,7 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), "Need to check work notes").await;
+        let result = execute(temp_dir.path(), &PrivateRoots::default(), "Need to check work notes").await;
         assert!(result.is_ok());
 
         let call_result = result.unwrap();
@@ -158,7 +185,7 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), "Checking notes").await;
+        let result = execute(temp_dir.path(), &PrivateRoots::default(), "Checking notes").await;
         assert!(result.is_ok());
 
         let call_result = result.unwrap();
@@ -171,4 +198,37 @@ mod tests {
         // Should not list Working Memory in the available notes (it's shown separately)
         assert!(!content.text.contains("[[private/Working Memory]]"));
     }
+
+    #[tokio::test]
+    async fn test_custom_private_roots() {
+        let temp_dir = TempDir::new().unwrap();
+        let personal = temp_dir.path().join("personal");
+        let journal_private = temp_dir.path().join("journal/private");
+        fs::create_dir_all(&personal).await.unwrap();
+        fs::create_dir_all(&journal_private).await.unwrap();
+        fs::create_dir(temp_dir.path().join("private")).await.unwrap();
+
+        fs::write(personal.join("Working Memory.md"), "Personal focus")
+            .await
+            .unwrap();
+        fs::write(journal_private.join("Diary.md"), "# Diary")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("private/Old.md"), "# Old")
+            .await
+            .unwrap();
+
+        let roots = PrivateRoots::new(["personal", "journal/private"]);
+        let result = execute(temp_dir.path(), &roots, "Checking notes")
+            .await
+            .unwrap();
+        let content = result.content[0]
+            .raw
+            .as_text()
+            .expect("Expected text content");
+        assert!(content.text.contains("## personal/Working Memory.md\n\nPersonal focus"));
+        assert!(content.text.contains("[[journal/private/Diary]]"));
+        // `private/` isn't a private root in this configuration
+        assert!(!content.text.contains("[[private/Old]]"));
+    }
 }
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Datelike, Local, Timelike, Weekday};
 use rmcp::model::{CallToolResult, Content, ErrorData};
-use std::path::Path;
-use tokio::fs;
+
+use crate::storage::{Storage, StorageError};
+
+/// Memory URI of the log note
+const LOG_URI: &str = "Log";
 
 /// Format ISO week date as YYYY-Www-D (e.g., 2025-W48-1)
 /// Uses chrono's IsoWeek trait
@@ -97,11 +100,11 @@ fn parse_entry_time(entry: &str) -> Option<(u32, u32)> {
 }
 
 /// Add a new entry to the log file, organizing by day and sorting chronologically
-pub async fn add_log(
-    log_path: &Path,
+pub async fn add_log<S: Storage>(
+    storage: &S,
     time: DateTime<Local>,
     entry: &str,
-) -> Result<(String, String), std::io::Error> {
+) -> Result<(String, String), StorageError> {
     let iso_week_date = format_iso_week_date(&time);
     let day_abbrev = get_day_abbreviation(&time);
     let time_str = format_12_hour_time(&time);
@@ -111,9 +114,9 @@ pub async fn add_log(
     let new_entry = format!("- {} – {}", time_str, bullet_content);
 
     // Read existing log content
-    let log_content = match fs::read_to_string(log_path).await {
-        Ok(content) => content,
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+    let (log_content, hash) = match storage.read(LOG_URI).await {
+        Ok((content, metadata)) => (content, Some(metadata.hash)),
+        Err(StorageError::NotFound { .. }) => (String::new(), None),
         Err(e) => return Err(e),
     };
 
@@ -124,15 +127,18 @@ pub async fn add_log(
     // Find the section for this day
     let section_index = lines.iter().position(|l| l == &day_header);
 
-    if section_index.is_none() {
+    // Lines to add after the existing content, when nothing before them changes
+    let appended = if section_index.is_none() {
         // Day section doesn't exist - add at the end
+        let mut section = Vec::new();
         if !lines.is_empty() {
-            lines.push(String::new()); // blank line before new section
+            section.push(String::new()); // blank line before new section
         }
-        lines.push(day_header);
-        lines.push(String::new());
-        lines.push(new_entry);
-        lines.push(String::new()); // trailing newline
+        section.push(day_header);
+        section.push(String::new());
+        section.push(new_entry);
+        section.push(String::new()); // trailing newline
+        Some(section)
     } else {
         let section_start = section_index.unwrap();
 
@@ -174,23 +180,50 @@ pub async fn add_log(
             insert_index = entry_idx + 1;
         }
 
-        // Insert the new entry
-        lines.insert(insert_index, new_entry);
-    }
+        if insert_index == lines.len() {
+            // Latest entry in the last section
+            Some(vec![new_entry])
+        } else {
+            // Insert the new entry
+            lines.insert(insert_index, new_entry);
+            None
+        }
+    };
 
-    // Write the file
-    let content = lines.join("\n");
-    fs::write(log_path, content).await?;
+    match appended {
+        Some(new_lines) => {
+            storage
+                .append(LOG_URI, &appended_text(&log_content, &new_lines))
+                .await?
+        }
+        None => {
+            // Write the file
+            let content = lines.join("\n");
+            storage.write(LOG_URI, &content, hash.as_deref()).await?;
+        }
+    }
 
     Ok((iso_week_date, time_str))
 }
 
+/// Text that appends `new_lines` to the log without rewriting it.
+///
+/// Appending it produces the same content as pushing them onto the log's
+/// lines and writing the joined result back.
+fn appended_text(existing: &str, new_lines: &[String]) -> String {
+    let mut text = String::new();
+    if !existing.is_empty() && !existing.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&new_lines.join("\n"));
+    text
+}
+
 /// Execute the Log tool
-pub async fn execute(vault_path: &Path, content: &str) -> Result<CallToolResult, ErrorData> {
-    let log_path = vault_path.join("Log.md");
+pub async fn execute<S: Storage>(storage: &S, content: &str) -> Result<CallToolResult, ErrorData> {
     let now = Local::now();
 
-    match add_log(&log_path, now, content).await {
+    match add_log(storage, now, content).await {
         Ok((iso_week_date, time_str)) => {
             let text = format!("Logged at {} {}", iso_week_date, time_str);
             Ok(CallToolResult::success(vec![Content::text(text)]))
@@ -205,8 +238,10 @@ pub async fn execute(vault_path: &Path, content: &str) -> Result<CallToolResult,
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::FileStorage;
     use chrono::TimeZone;
     use tempfile::TempDir;
+    use tokio::fs;
 
     fn make_time(hour: u32, minute: u32) -> DateTime<Local> {
         Local
@@ -246,10 +281,11 @@ mod tests {
     #[tokio::test]
     async fn test_add_log_creates_file_if_not_exists() {
         let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
         let log_path = temp_dir.path().join("Log.md");
 
         let time = make_time(10, 30);
-        let (iso_week_date, time_str) = add_log(&log_path, time, "Test entry").await.unwrap();
+        let (iso_week_date, time_str) = add_log(&storage, time, "Test entry").await.unwrap();
 
         assert!(!iso_week_date.is_empty());
         assert_eq!(time_str, "10:30 AM");
@@ -262,13 +298,14 @@ mod tests {
     #[tokio::test]
     async fn test_add_log_appends_to_existing_section() {
         let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
         let log_path = temp_dir.path().join("Log.md");
 
         let time1 = make_time(9, 0);
-        add_log(&log_path, time1, "First entry").await.unwrap();
+        add_log(&storage, time1, "First entry").await.unwrap();
 
         let time2 = make_time(11, 0);
-        add_log(&log_path, time2, "Second entry").await.unwrap();
+        add_log(&storage, time2, "Second entry").await.unwrap();
 
         let content = fs::read_to_string(&log_path).await.unwrap();
         assert!(content.contains("First entry"));
@@ -280,17 +317,39 @@ mod tests {
         assert!(first_pos < second_pos);
     }
 
+    #[tokio::test]
+    async fn test_add_log_appends_without_rewriting() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
+        let log_path = temp_dir.path().join("Log.md");
+
+        let older = "## 2025-W01-1 (Mon)\n\n- 9:00 AM – Old entry";
+        fs::write(&log_path, older).await.unwrap();
+
+        let time1 = make_time(9, 0);
+        add_log(&storage, time1, "First entry").await.unwrap();
+        let first = fs::read_to_string(&log_path).await.unwrap();
+        assert!(first.starts_with(&format!("{}\n\n## ", older)));
+        assert!(first.ends_with("- 9:00 AM – First entry\n"));
+
+        let time2 = make_time(11, 0);
+        add_log(&storage, time2, "Second entry").await.unwrap();
+        let second = fs::read_to_string(&log_path).await.unwrap();
+        assert_eq!(second, format!("{}- 11:00 AM – Second entry", first));
+    }
+
     #[tokio::test]
     async fn test_add_log_maintains_chronological_order() {
         let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
         let log_path = temp_dir.path().join("Log.md");
 
         // Add entries out of order
         let time2 = make_time(14, 0);
-        add_log(&log_path, time2, "Afternoon entry").await.unwrap();
+        add_log(&storage, time2, "Afternoon entry").await.unwrap();
 
         let time1 = make_time(9, 0);
-        add_log(&log_path, time1, "Morning entry").await.unwrap();
+        add_log(&storage, time1, "Morning entry").await.unwrap();
 
         let content = fs::read_to_string(&log_path).await.unwrap();
 
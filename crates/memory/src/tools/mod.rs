@@ -1,16 +1,32 @@
 mod common;
+mod pagination;
+pub mod compact_log;
+pub mod debug_embed;
 pub mod delete_note;
 pub mod edit_note;
+pub mod find_duplicates;
+pub mod generate_toc;
 pub mod get_current_datetime;
+pub mod get_graph;
+pub mod get_note_history;
 pub mod get_note_info;
+pub mod get_server_metrics;
 pub mod get_weekly_note_info;
+pub mod insert_under_heading;
 pub mod load_private_memory;
 pub mod log;
 pub mod move_note;
+pub mod note_exists;
 pub mod read_note;
+pub mod rebuild_index;
 pub mod reflect;
+pub mod reindex_embeddings;
 pub mod remember;
+pub mod render_note;
+pub mod resolve_link;
 pub mod search;
+pub mod similar_to_note;
+pub mod undo_last_change;
 pub mod update_frontmatter;
 pub mod write_logs;
 pub mod write_note;
@@ -4,20 +4,139 @@ use obsidian_fs::{ensure_markdown_extension, normalize_note_reference};
 use rmcp::model::{CallToolResult, Content, ErrorData};
 use std::path::Path;
 use tokio::sync::RwLock;
+use wiki_links::{parse_wiki_links, rename_links};
 
+use super::common::read_only_error;
 use crate::graph::GraphIndex;
 use crate::storage::{Storage, StorageError};
 
+/// A note linking to the moved note, and how its links change.
+#[derive(Debug)]
+struct BacklinkRewrite {
+    /// Memory URI of the linking note
+    uri: String,
+    /// Content with links rewritten
+    updated: String,
+    /// Each link replaced, as (old, new)
+    changes: Vec<(String, String)>,
+}
+
+/// Each link that differs between `before` and a rewrite of it, as (old, new).
+fn link_changes(before: &str, after: &str) -> Vec<(String, String)> {
+    parse_wiki_links(before)
+        .iter()
+        .zip(parse_wiki_links(after).iter())
+        .map(|(old, new)| (&before[old.start..old.end], &after[new.start..new.end]))
+        .filter(|(old, new)| old != new)
+        .map(|(old, new)| (old.to_string(), new.to_string()))
+        .collect()
+}
+
+/// Work out which backlinking notes need their links rewritten, sorted by URI.
+async fn plan_backlink_rewrites<S: Storage>(
+    storage: &S,
+    graph: &GraphIndex,
+    from_uri: &str,
+    from_name: &str,
+    to_uri: &str,
+) -> Vec<BacklinkRewrite> {
+    // Get notes that link to the source note by name
+    let Some(linking_paths) = graph.get_backlinks(from_name) else {
+        return Vec::new();
+    };
+
+    let mut rewrites = Vec::new();
+    for path in linking_paths.iter() {
+        // Skip the source note itself
+        if path == &ensure_markdown_extension(from_uri) {
+            continue;
+        }
+
+        // Convert path to URI (remove .md)
+        let uri = path.strip_suffix(".md").unwrap_or(path);
+
+        // Read the linking note and retarget its links, keeping aliases and fragments
+        let Ok((content, _)) = storage.read(uri).await else {
+            continue;
+        };
+        let Some(updated) = rename_links(&content, from_uri, to_uri) else {
+            continue;
+        };
+
+        rewrites.push(BacklinkRewrite {
+            uri: uri.to_string(),
+            changes: link_changes(&content, &updated),
+            updated,
+        });
+    }
+
+    rewrites.sort_by(|a, b| a.uri.cmp(&b.uri));
+    rewrites
+}
+
+/// Describe planned backlink rewrites for a dry run.
+fn format_planned_rewrites<S: Storage>(storage: &S, rewrites: &[BacklinkRewrite]) -> String {
+    if rewrites.is_empty() {
+        return "\n\nNo backlinks to update.".to_string();
+    }
+
+    let (writable, read_only): (Vec<_>, Vec<_>) = rewrites
+        .iter()
+        .partition(|rewrite| storage.check_writable(&rewrite.uri).is_ok());
+
+    let mut output = String::new();
+    if !writable.is_empty() {
+        output.push_str(&format!(
+            "\n\n## Backlinks To Update\n\n{} note(s) would have links rewritten:\n",
+            writable.len()
+        ));
+        for rewrite in &writable {
+            output.push_str(&format!("- memory:{}\n", rewrite.uri));
+            for (old, new) in &rewrite.changes {
+                output.push_str(&format!("  - `{}` -> `{}`\n", old, new));
+            }
+        }
+        output.truncate(output.trim_end().len());
+    }
+    if !read_only.is_empty() {
+        output.push_str(&format!(
+            "\n\n## Backlinks Not Updated\n\n{} note(s) in read-only folders would still link to the old name:\n{}",
+            read_only.len(),
+            read_only
+                .iter()
+                .map(|rewrite| format!("- memory:{}", rewrite.uri))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ));
+    }
+    output
+}
+
+/// Content of the stub left at a moved note's old path.
+///
+/// The stub is kept out of search so it doesn't compete with the note itself.
+fn redirect_stub(to_uri: &str) -> String {
+    format!(
+        "---\nredirect: \"[[{to}]]\"\nsearch: false\n---\n\nMoved to [[{to}]].\n",
+        to = to_uri
+    )
+}
+
 /// Execute the MoveNote tool.
 ///
 /// Moves or renames a note and updates all notes that link to it.
-/// Always updates backlinks automatically.
+/// Always updates backlinks automatically. With `leave_redirect`, a stub
+/// linking to the new location is written at the old path, for references
+/// from outside the vault. With `dry_run`, reports the destination and the
+/// link changes that would be made, without changing anything.
 pub async fn execute<S: Storage>(
     vault_path: &Path,
     storage: &S,
     graph: &RwLock<GraphIndex>,
     from: &str,
     to: &str,
+    dry_run: bool,
+    leave_redirect: bool,
 ) -> Result<CallToolResult, ErrorData> {
     let from_normalized = normalize_note_reference(from);
     let to_normalized = normalize_note_reference(to);
@@ -61,38 +180,52 @@ pub async fn execute<S: Storage>(
         ));
     }
 
-    // Find and update backlinks before the move
-    let mut backlinks_updated = Vec::new();
-    {
+    // Refuse before touching backlinks, so a protected note isn't half-moved
+    for uri in [from_uri, to_uri] {
+        storage.check_writable(uri).map_err(|e| match e {
+            StorageError::ReadOnly { uri, folder } => read_only_error(&uri, &folder),
+            _ => ErrorData::invalid_params(e.to_string(), None),
+        })?;
+    }
+
+    let rewrites = {
         let graph_read = graph.read().await;
+        plan_backlink_rewrites(storage, &graph_read, from_uri, &from_normalized.name, to_uri)
+            .await
+    };
 
-        // Get notes that link to the source note by name
-        if let Some(linking_paths) = graph_read.get_backlinks(&from_normalized.name) {
-            let old_link = format!("[[{}]]", from_normalized.name);
-            let new_link = format!("[[{}]]", to_normalized.name);
-
-            for path in linking_paths.iter() {
-                // Skip the source note itself
-                if path == &ensure_markdown_extension(from_uri) {
-                    continue;
-                }
-
-                // Convert path to URI (remove .md)
-                let uri = path.strip_suffix(".md").unwrap_or(path);
-
-                // Read the linking note
-                if let Ok((content, _)) = storage.read(uri).await {
-                    // Replace the wiki-link
-                    if content.contains(&old_link) {
-                        let updated = content.replace(&old_link, &new_link);
-
-                        // Write back
-                        if storage.write(uri, &updated, None).await.is_ok() {
-                            backlinks_updated.push(uri.to_string());
-                        }
-                    }
-                }
-            }
+    if dry_run {
+        let redirect = if leave_redirect {
+            format!("\n**Redirect stub:** memory:{}", from_uri)
+        } else {
+            String::new()
+        };
+        let text = format!(
+            "Dry run: would move note: {} -> {}\n\n\
+             **From:** memory:{}\n\
+             **To:** memory:{}\n\
+             **New file:** {}{}{}\n\n\
+             No changes were made.",
+            from_normalized.name,
+            to_normalized.name,
+            from_uri,
+            to_uri,
+            to_file,
+            redirect,
+            format_planned_rewrites(storage, &rewrites)
+        );
+        return Ok(CallToolResult::success(vec![Content::text(text)]));
+    }
+
+    // Update backlinks before the move
+    let mut backlinks_updated = Vec::new();
+    let mut backlinks_read_only = Vec::new();
+    for rewrite in &rewrites {
+        // Write back, leaving notes in protected folders alone
+        match storage.write(&rewrite.uri, &rewrite.updated, None).await {
+            Ok(_) => backlinks_updated.push(rewrite.uri.clone()),
+            Err(StorageError::ReadOnly { .. }) => backlinks_read_only.push(rewrite.uri.clone()),
+            Err(_) => {}
         }
     }
 
@@ -107,11 +240,27 @@ pub async fn execute<S: Storage>(
             ),
             None,
         ),
+        StorageError::ReadOnly { uri, folder } => read_only_error(&uri, &folder),
         _ => ErrorData::internal_error(format!("Failed to move note: {}", e), None),
     })?;
 
+    let redirect = if leave_redirect {
+        storage
+            .write(from_uri, &redirect_stub(to_uri), None)
+            .await
+            .map_err(|e| {
+                ErrorData::internal_error(
+                    format!("Moved note, but failed to write redirect stub: {}", e),
+                    None,
+                )
+            })?;
+        format!("\n**Redirect stub:** memory:{}", from_uri)
+    } else {
+        String::new()
+    };
+
     // Build response
-    let backlinks_summary = if !backlinks_updated.is_empty() {
+    let mut backlinks_summary = if !backlinks_updated.is_empty() {
         format!(
             "\n\n## Updated Backlinks\n\nUpdated {} note(s) that linked to this note:\n{}",
             backlinks_updated.len(),
@@ -124,13 +273,30 @@ pub async fn execute<S: Storage>(
     } else {
         "\n\nNo backlinks to update.".to_string()
     };
+    if !backlinks_read_only.is_empty() {
+        backlinks_summary.push_str(&format!(
+            "\n\n## Backlinks Not Updated\n\n{} note(s) in read-only folders still link to the old name:\n{}",
+            backlinks_read_only.len(),
+            backlinks_read_only
+                .iter()
+                .map(|p| format!("- memory:{}", p))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ));
+    }
 
     let text = format!(
         "Moved note: {} -> {}\n\n\
          **From:** memory:{}\n\
          **To:** memory:{}\n\
-         **New file:** {}{}",
-        from_normalized.name, to_normalized.name, from_uri, to_uri, to_file, backlinks_summary
+         **New file:** {}{}{}",
+        from_normalized.name,
+        to_normalized.name,
+        from_uri,
+        to_uri,
+        to_file,
+        redirect,
+        backlinks_summary
     );
 
     Ok(CallToolResult::success(vec![Content::text(text)]))
@@ -139,7 +305,7 @@ pub async fn execute<S: Storage>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::storage::FileStorage;
+    use crate::storage::{FileStorage, JournaledStorage};
     use std::collections::HashSet;
     use std::path::PathBuf;
     use std::sync::Arc;
@@ -161,7 +327,7 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), &storage, &graph, "old", "new")
+        let result = execute(temp_dir.path(), &storage, &graph, "old", "new", false, false)
             .await
             .expect("should succeed");
 
@@ -188,7 +354,7 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), &storage, &graph, "test", "knowledge/test")
+        let result = execute(temp_dir.path(), &storage, &graph, "test", "knowledge/test", false, false)
             .await
             .expect("should succeed");
 
@@ -227,7 +393,7 @@ mod tests {
             g.update_note("B", PathBuf::from("B.md"), HashSet::new());
         }
 
-        let result = execute(temp_dir.path(), &storage, &graph, "B", "C")
+        let result = execute(temp_dir.path(), &storage, &graph, "B", "C", false, false)
             .await
             .expect("should succeed");
 
@@ -249,11 +415,51 @@ mod tests {
         assert!(!a_content.contains("[[B]]"));
     }
 
+    #[tokio::test]
+    async fn test_move_keeps_aliases_fragments_and_embeds() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+        fs::create_dir(temp_dir.path().join("notes")).await.unwrap();
+        fs::create_dir(temp_dir.path().join("archive")).await.unwrap();
+        fs::write(
+            temp_dir.path().join("A.md"),
+            "See [[B#Intro|the intro]], ![[B#^quote]], [[notes/B]], and [[Bee]].",
+        )
+        .await
+        .unwrap();
+        fs::write(temp_dir.path().join("notes/B.md"), "Target note")
+            .await
+            .unwrap();
+        graph.write().await.update_note(
+            "A",
+            PathBuf::from("A.md"),
+            HashSet::from(["B".to_string(), "Bee".to_string()]),
+        );
+
+        let result = execute(temp_dir.path(), &storage, &graph, "notes/B", "archive/C", true, false)
+            .await
+            .expect("should succeed");
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+        assert!(text.contains("  - `[[B#Intro|the intro]]` -> `[[C#Intro|the intro]]`"));
+        assert!(text.contains("  - `![[B#^quote]]` -> `![[C#^quote]]`"));
+        assert!(text.contains("  - `[[notes/B]]` -> `[[archive/C]]`"));
+
+        execute(temp_dir.path(), &storage, &graph, "notes/B", "archive/C", false, false)
+            .await
+            .expect("should succeed");
+        let a_content = fs::read_to_string(temp_dir.path().join("A.md"))
+            .await
+            .unwrap();
+        assert_eq!(
+            a_content,
+            "See [[C#Intro|the intro]], ![[C#^quote]], [[archive/C]], and [[Bee]]."
+        );
+    }
+
     #[tokio::test]
     async fn test_move_source_not_found() {
         let (temp_dir, storage, graph) = create_test_env().await;
 
-        let result = execute(temp_dir.path(), &storage, &graph, "nonexistent", "new").await;
+        let result = execute(temp_dir.path(), &storage, &graph, "nonexistent", "new", false, false).await;
 
         assert!(result.is_err());
         let err = result.unwrap_err();
@@ -271,7 +477,7 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), &storage, &graph, "source", "dest").await;
+        let result = execute(temp_dir.path(), &storage, &graph, "source", "dest", false, false).await;
 
         assert!(result.is_err());
         let err = result.unwrap_err();
@@ -286,10 +492,180 @@ mod tests {
             .await
             .unwrap();
 
-        let result = execute(temp_dir.path(), &storage, &graph, "test", "missing/dir/test").await;
+        let result = execute(temp_dir.path(), &storage, &graph, "test", "missing/dir/test", false, false).await;
 
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.message.contains("Parent directory doesn't exist"));
     }
+
+    #[tokio::test]
+    async fn test_move_out_of_protected_folder_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("archive")).await.unwrap();
+        fs::write(temp_dir.path().join("archive/old.md"), "Content")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("linker.md"), "See [[old]]")
+            .await
+            .unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf())
+            .protect_folders(vec!["archive".to_string()]);
+        let graph = Arc::new(RwLock::new(GraphIndex::new()));
+        graph.write().await.update_note(
+            "linker",
+            PathBuf::from("linker.md"),
+            HashSet::from(["old".to_string()]),
+        );
+
+        let err = execute(temp_dir.path(), &storage, &graph, "archive/old", "new", false, false)
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("read-only"));
+
+        // Nothing was touched, including backlinks
+        assert!(temp_dir.path().join("archive/old.md").exists());
+        let linker = fs::read_to_string(temp_dir.path().join("linker.md"))
+            .await
+            .unwrap();
+        assert_eq!(linker, "See [[old]]");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_lists_link_changes_without_moving() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+
+        fs::write(temp_dir.path().join("A.md"), "Link to [[B]], again [[B]]")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("D.md"), "Also [[B]]")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("B.md"), "Target note")
+            .await
+            .unwrap();
+        {
+            let mut g = graph.write().await;
+            g.update_note("A", PathBuf::from("A.md"), HashSet::from(["B".to_string()]));
+            g.update_note("D", PathBuf::from("D.md"), HashSet::from(["B".to_string()]));
+            g.update_note("B", PathBuf::from("B.md"), HashSet::new());
+        }
+
+        let result = execute(temp_dir.path(), &storage, &graph, "B", "knowledge/C", true, false)
+            .await
+            .expect("should succeed");
+
+        let text = result.content[0]
+            .raw
+            .as_text()
+            .expect("Expected text")
+            .text
+            .clone();
+
+        assert!(text.contains("Dry run"));
+        assert!(text.contains("**To:** memory:knowledge/C"));
+        assert!(text.contains("2 note(s) would have links rewritten"));
+        assert!(text.contains("- memory:A\n  - `[[B]]` -> `[[C]]`\n  - `[[B]]` -> `[[C]]`"));
+        assert!(text.contains("- memory:D\n  - `[[B]]` -> `[[C]]`"));
+
+        // Nothing on disk changed
+        assert!(temp_dir.path().join("B.md").exists());
+        assert!(!temp_dir.path().join("knowledge").exists());
+        let a_content = fs::read_to_string(temp_dir.path().join("A.md"))
+            .await
+            .unwrap();
+        assert_eq!(a_content, "Link to [[B]], again [[B]]");
+        let d_content = fs::read_to_string(temp_dir.path().join("D.md"))
+            .await
+            .unwrap();
+        assert_eq!(d_content, "Also [[B]]");
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_still_checks_destination() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+
+        fs::write(temp_dir.path().join("source.md"), "Source")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("dest.md"), "Dest")
+            .await
+            .unwrap();
+
+        let result = execute(temp_dir.path(), &storage, &graph, "source", "dest", true, false).await;
+        assert!(result.unwrap_err().message.contains("Destination already exists"));
+    }
+
+    #[tokio::test]
+    async fn test_move_with_redirect_leaves_stub() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+        fs::create_dir(temp_dir.path().join("knowledge"))
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("old.md"), "Content")
+            .await
+            .unwrap();
+
+        let result = execute(temp_dir.path(), &storage, &graph, "old", "knowledge/new", false, true)
+            .await
+            .expect("should succeed");
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+        assert!(text.contains("**Redirect stub:** memory:old"));
+
+        let moved = fs::read_to_string(temp_dir.path().join("knowledge/new.md"))
+            .await
+            .unwrap();
+        assert_eq!(moved, "Content");
+        let stub = fs::read_to_string(temp_dir.path().join("old.md"))
+            .await
+            .unwrap();
+        assert!(stub.contains("Moved to [[knowledge/new]]."));
+        assert!(stub.contains("search: false"));
+    }
+
+    #[tokio::test]
+    async fn test_undo_move_with_redirect() {
+        let (temp_dir, _, graph) = create_test_env().await;
+        let storage = JournaledStorage::new(Arc::new(FileStorage::new(temp_dir.path().to_path_buf())));
+        fs::write(temp_dir.path().join("old.md"), "Content")
+            .await
+            .unwrap();
+
+        storage.begin_operation("move_note");
+        execute(temp_dir.path(), &storage, &graph, "old", "new", false, true)
+            .await
+            .expect("should succeed");
+        assert!(temp_dir.path().join("new.md").exists());
+
+        let undone = storage.undo_last().await.unwrap().unwrap();
+        assert_eq!(undone.label, "move_note");
+        assert!(!temp_dir.path().join("new.md").exists());
+        let content = fs::read_to_string(temp_dir.path().join("old.md"))
+            .await
+            .unwrap();
+        assert_eq!(content, "Content");
+    }
+
+    #[tokio::test]
+    async fn test_move_without_redirect_leaves_no_stub() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+        fs::write(temp_dir.path().join("old.md"), "Content")
+            .await
+            .unwrap();
+
+        let result = execute(temp_dir.path(), &storage, &graph, "old", "new", false, false)
+            .await
+            .expect("should succeed");
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+        assert!(!text.contains("Redirect stub"));
+        assert!(!temp_dir.path().join("old.md").exists());
+
+        // A dry run with the flag reports the stub without writing anything
+        let result = execute(temp_dir.path(), &storage, &graph, "new", "newer", true, true)
+            .await
+            .expect("should succeed");
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+        assert!(text.contains("**Redirect stub:** memory:new"));
+        assert!(!temp_dir.path().join("newer.md").exists());
+    }
 }
@@ -0,0 +1,134 @@
+//! NoteExists tool - check whether a note exists without reading it.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+
+use super::common::{resolve_link_target, LinkResolution};
+use crate::graph::GraphIndex;
+use crate::storage::Storage;
+
+/// Response from NoteExists tool.
+#[derive(Serialize)]
+pub struct NoteExistsResponse {
+    pub exists: bool,
+    /// The memory URI of the note, when exactly one matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// The file path relative to vault, when exactly one matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Memory URIs of every same-named note, when the reference is ambiguous
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+}
+
+/// Execute the NoteExists tool.
+///
+/// Only checks storage and the graph's name index; the note is never read.
+pub async fn execute<S: Storage>(
+    storage: &S,
+    graph: &GraphIndex,
+    note: &str,
+) -> Result<CallToolResult, ErrorData> {
+    let resolution = resolve_link_target(storage, graph, note).await.map_err(|e| {
+        ErrorData::internal_error(format!("Failed to resolve note: {}", e), None)
+    })?;
+
+    let response = match resolution {
+        LinkResolution::Unique(path) => NoteExistsResponse {
+            exists: true,
+            uri: Some(format!("memory:{}", path)),
+            path: Some(format!("{}.md", path)),
+            candidates: Vec::new(),
+        },
+        LinkResolution::Ambiguous(paths) => NoteExistsResponse {
+            exists: true,
+            uri: None,
+            path: None,
+            candidates: paths.iter().map(|p| format!("memory:{}", p)).collect(),
+        },
+        LinkResolution::NotFound => NoteExistsResponse {
+            exists: false,
+            uri: None,
+            path: None,
+            candidates: Vec::new(),
+        },
+    };
+
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    async fn create_test_env() -> (TempDir, FileStorage, GraphIndex) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
+        let graph = GraphIndex::new();
+        (temp_dir, storage, graph)
+    }
+
+    async fn check(storage: &FileStorage, graph: &GraphIndex, note: &str) -> serde_json::Value {
+        let result = execute(storage, graph, note).await.expect("should succeed");
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+        serde_json::from_str(text).expect("Expected valid JSON")
+    }
+
+    #[tokio::test]
+    async fn test_existing_note() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        fs::create_dir(temp_dir.path().join("knowledge")).await.unwrap();
+        fs::write(temp_dir.path().join("knowledge/Rust.md"), "content").await.unwrap();
+        graph.update_note("Rust", PathBuf::from("knowledge/Rust.md"), HashSet::new());
+
+        let json = check(&storage, &graph, "[[Rust]]").await;
+        assert_eq!(json["exists"], true);
+        assert_eq!(json["uri"], "memory:knowledge/Rust");
+        assert_eq!(json["path"], "knowledge/Rust.md");
+    }
+
+    #[tokio::test]
+    async fn test_existing_note_not_yet_indexed() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+        fs::write(temp_dir.path().join("Fresh.md"), "content").await.unwrap();
+
+        let json = check(&storage, &graph, "Fresh").await;
+        assert_eq!(json["exists"], true);
+        assert_eq!(json["uri"], "memory:Fresh");
+    }
+
+    #[tokio::test]
+    async fn test_missing_note() {
+        let (_temp_dir, storage, graph) = create_test_env().await;
+
+        let json = check(&storage, &graph, "memory:knowledge/Missing").await;
+        assert_eq!(json["exists"], false);
+        assert!(json.get("uri").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ambiguous_reference_lists_candidates() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        for folder in ["a", "b"] {
+            fs::create_dir(temp_dir.path().join(folder)).await.unwrap();
+            fs::write(temp_dir.path().join(format!("{}/Note.md", folder)), "content")
+                .await
+                .unwrap();
+            graph.update_note("Note", PathBuf::from(format!("{}/Note.md", folder)), HashSet::new());
+        }
+
+        let json = check(&storage, &graph, "Note").await;
+        assert_eq!(json["exists"], true);
+        assert!(json.get("uri").is_none());
+        assert_eq!(json["candidates"], serde_json::json!(["memory:a/Note", "memory:b/Note"]));
+    }
+}
@@ -0,0 +1,69 @@
+//! Cursor-based pagination for tools that enumerate many items.
+//!
+//! Tools whose results can exceed client limits take an optional `cursor`
+//! param and return one page of results plus a `next_cursor`. Passing
+//! `next_cursor` back as `cursor` fetches the following page; it's absent on
+//! the last page.
+//!
+//! Cursors are opaque to clients but are offsets into the tool's ordering, so
+//! that ordering must be stable (sorted, with ties broken) for pages to
+//! neither overlap nor skip items.
+
+use rmcp::model::ErrorData;
+
+/// Offset into the ordering a cursor points at; no cursor is the first page.
+pub fn parse_cursor(cursor: Option<&str>) -> Result<usize, ErrorData> {
+    match cursor {
+        None => Ok(0),
+        Some(cursor) => cursor.parse().map_err(|_| {
+            ErrorData::invalid_params(
+                format!(
+                    "Invalid cursor: {}. Pass next_cursor from a previous response.",
+                    cursor
+                ),
+                None,
+            )
+        }),
+    }
+}
+
+/// Cursor for the page after one ending at `end`, if any items are left.
+pub fn next_cursor(end: usize, total: usize) -> Option<String> {
+    (end < total).then(|| end.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paging_yields_every_item_once() {
+        let items: Vec<usize> = (0..23).collect();
+
+        for page_size in [1, 5, 10, 23, 50] {
+            let mut seen = Vec::new();
+            let mut cursor = None;
+            loop {
+                let start = parse_cursor(cursor.as_deref()).unwrap();
+                let end = (start + page_size).min(items.len());
+                seen.extend_from_slice(&items[start..end]);
+                cursor = next_cursor(end, items.len());
+                if cursor.is_none() {
+                    break;
+                }
+            }
+            assert_eq!(seen, items, "page size {}", page_size);
+        }
+    }
+
+    #[test]
+    fn test_cursor_edge_cases() {
+        assert_eq!(parse_cursor(None).unwrap(), 0);
+        assert_eq!(parse_cursor(Some("40")).unwrap(), 40);
+        assert_eq!(next_cursor(0, 0), None);
+        assert_eq!(next_cursor(10, 23).as_deref(), Some("10"));
+
+        let err = parse_cursor(Some("page-2")).unwrap_err();
+        assert!(err.message.contains("Invalid cursor"));
+    }
+}
@@ -1,28 +1,181 @@
 //! ReadNote tool - read note content with content hash for optimistic locking.
+//!
+//! Content is returned as raw markdown by default. The HTML format renders the
+//! markdown and turns wiki-links into anchors to the notes they resolve to in
+//! the graph, or `dangling` spans when no such note exists.
 
+use obsidian_fs::{ensure_markdown_extension, heading_link_target};
+use pulldown_cmark::{html, Event, LinkType, Options, Parser, Tag, TagEnd};
 use rmcp::model::{CallToolResult, Content, ErrorData};
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wiki_links::{parse_wiki_links, WikiLink};
 
 use super::common::resolve_note_uri;
 use crate::graph::GraphIndex;
 use crate::storage::{ContentHash, Storage, StorageError};
 
+/// Format of the returned note content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteFormat {
+    /// Raw markdown, as stored
+    #[default]
+    Markdown,
+    /// HTML rendered from the markdown, with wiki-links resolved
+    Html,
+}
+
 /// Response from ReadNote tool.
 #[derive(Serialize)]
 pub struct ReadNoteResponse {
-    /// The content of the note
+    /// The content of the note, in the requested format
     pub content: String,
-    /// Content hash for optimistic locking - pass this to write_note or edit_note
+    /// Content hash for optimistic locking - pass this to write_note or edit_note.
+    /// Always the hash of the stored markdown, even for HTML responses.
     pub content_hash: String,
 }
 
+/// Resolve a parsed wiki-link to the path (with `.md`) of the note it points to.
+///
+/// Links with a folder must match that exact path; bare names resolve to the
+/// first note with that name, like other graph lookups.
+pub(super) fn resolve_link_path(graph: &GraphIndex, link: &WikiLink) -> Option<String> {
+    match &link.parent {
+        Some(_) => {
+            let path = ensure_markdown_extension(&link.path());
+            graph
+                .get_paths_for_name(&link.name)
+                .filter(|paths| paths.contains(&path))
+                .map(|_| path)
+        }
+        None => Some(graph.get_path(&link.name)?.to_string_lossy().to_string()),
+    }
+}
+
+/// Resolve a wiki-link target ("folder/Note#Header") to a memory URI.
+fn resolve_link_href(graph: &GraphIndex, target: &str) -> Option<String> {
+    let link = parse_wiki_links(&format!("[[{}]]", target)).into_iter().next()?;
+    let path = resolve_link_path(graph, &link)?;
+
+    let mut href = format!("memory:{}", path.strip_suffix(".md").unwrap_or(&path));
+    // A heading chain links to its innermost heading
+    if let Some(heading) = link.header_path().last() {
+        href.push_str(&heading_link_target(heading));
+    }
+    Some(href)
+}
+
+/// Escape text for use in HTML attribute values.
+pub(super) fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Render note markdown to HTML, resolving wiki-links through the graph.
+///
+/// Resolved links become `<a class="wiki-link" href="memory:...">` and
+/// unresolved ones `<span class="wiki-link dangling">`, so clients can style
+/// missing notes. Wiki-links inside code are left as text.
+pub(super) fn render_html(content: &str, graph: &GraphIndex) -> String {
+    render_html_with_embeds(content, graph, &HashMap::new())
+}
+
+/// Render note markdown to HTML, replacing `![[target]]` embeds found in
+/// `embeds` with their pre-rendered HTML.
+///
+/// Embeds missing from `embeds` render like links, as in [`render_html`].
+pub(super) fn render_html_with_embeds(
+    content: &str,
+    graph: &GraphIndex,
+    embeds: &HashMap<String, String>,
+) -> String {
+    // Closing tag for each open link or image; None for regular markdown links
+    let mut open: Vec<Option<&'static str>> = Vec::new();
+    // Images still open inside a replaced embed, whose events are dropped
+    let mut skipping = 0usize;
+
+    let parser = Parser::new_ext(content, Options::all()).filter_map(|event| {
+        if skipping > 0 {
+            match event {
+                Event::Start(Tag::Image { .. }) => skipping += 1,
+                Event::End(TagEnd::Image) => skipping -= 1,
+                _ => {}
+            }
+            return None;
+        }
+        if let Event::Start(Tag::Image { link_type: LinkType::WikiLink { .. }, dest_url, .. }) =
+            &event
+            && let Some(embed) = embeds.get(dest_url.as_ref())
+        {
+            skipping = 1;
+            return Some(Event::Html(embed.clone().into()));
+        }
+        Some(match event {
+            Event::Start(Tag::Link { link_type: LinkType::WikiLink { .. }, dest_url, .. })
+            | Event::Start(Tag::Image { link_type: LinkType::WikiLink { .. }, dest_url, .. }) => {
+                let (tag, close) = match resolve_link_href(graph, &dest_url) {
+                    Some(href) => (
+                        format!(r#"<a class="wiki-link" href="{}">"#, escape_attribute(&href)),
+                        "</a>",
+                    ),
+                    None => (
+                        format!(
+                            r#"<span class="wiki-link dangling" data-target="{}">"#,
+                            escape_attribute(&dest_url)
+                        ),
+                        "</span>",
+                    ),
+                };
+                open.push(Some(close));
+                Event::Html(tag.into())
+            }
+            Event::Start(tag @ (Tag::Link { .. } | Tag::Image { .. })) => {
+                open.push(None);
+                Event::Start(tag)
+            }
+            Event::End(end @ (TagEnd::Link | TagEnd::Image)) => match open.pop().flatten() {
+                Some(close) => Event::Html(close.into()),
+                None => Event::End(end),
+            },
+            other => other,
+        })
+    });
+
+    // An embed on its own line shouldn't end up inside a <p>
+    let mut events: Vec<Event> = Vec::new();
+    for event in parser {
+        let is_embed = matches!(&event, Event::Html(raw) if embeds.values().any(|e| e == raw.as_ref()));
+        if is_embed && matches!(events.last(), Some(Event::Start(Tag::Paragraph))) {
+            events.pop();
+            events.push(event);
+            events.push(Event::Start(Tag::Paragraph));
+        } else if matches!(event, Event::End(TagEnd::Paragraph))
+            && matches!(events.last(), Some(Event::Start(Tag::Paragraph)))
+        {
+            events.pop();
+        } else {
+            events.push(event);
+        }
+    }
+
+    let mut output = String::new();
+    html::push_html(&mut output, events.into_iter());
+    output
+}
+
 /// Execute the ReadNote tool.
 ///
-/// Returns note content and content hash for subsequent writes.
+/// Returns note content in `format` and the content hash for subsequent writes.
 pub async fn execute<S: Storage>(
     storage: &S,
     graph: &GraphIndex,
     note: &str,
+    format: NoteFormat,
 ) -> Result<CallToolResult, ErrorData> {
     // Resolve the note reference
     let (uri, exists) = resolve_note_uri(storage, graph, note).await.map_err(|e| {
@@ -48,6 +201,11 @@ pub async fn execute<S: Storage>(
     // Compute content hash for client to use in subsequent writes
     let content_hash = ContentHash::from_content(&content);
 
+    let content = match format {
+        NoteFormat::Markdown => content,
+        NoteFormat::Html => render_html(&content, graph),
+    };
+
     // Return JSON with content and hash
     let response = ReadNoteResponse {
         content,
@@ -102,7 +260,7 @@ mod tests {
             .unwrap();
         graph.update_note("test", PathBuf::from("test.md"), HashSet::new());
 
-        let result = execute(&storage, &graph, "test")
+        let result = execute(&storage, &graph, "test", NoteFormat::Markdown)
             .await
             .expect("should succeed");
 
@@ -123,10 +281,10 @@ mod tests {
         graph.update_note("test", PathBuf::from("test.md"), HashSet::new());
 
         // Read twice - should get same hash
-        let result1 = execute(&storage, &graph, "test")
+        let result1 = execute(&storage, &graph, "test", NoteFormat::Markdown)
             .await
             .expect("should succeed");
-        let result2 = execute(&storage, &graph, "test")
+        let result2 = execute(&storage, &graph, "test", NoteFormat::Markdown)
             .await
             .expect("should succeed");
 
@@ -156,7 +314,7 @@ mod tests {
             HashSet::new(),
         );
 
-        let result = execute(&storage, &graph, "My Note")
+        let result = execute(&storage, &graph, "My Note", NoteFormat::Markdown)
             .await
             .expect("should succeed");
 
@@ -168,7 +326,7 @@ mod tests {
     async fn test_read_nonexistent_note_returns_error() {
         let (_temp_dir, storage, graph) = create_test_env().await;
 
-        let result = execute(&storage, &graph, "nonexistent").await;
+        let result = execute(&storage, &graph, "nonexistent", NoteFormat::Markdown).await;
 
         // Should return an error, not success
         assert!(result.is_err());
@@ -185,7 +343,7 @@ mod tests {
             .unwrap();
         graph.update_note("test", PathBuf::from("test.md"), HashSet::new());
 
-        let result = execute(&storage, &graph, "[[test]]")
+        let result = execute(&storage, &graph, "[[test]]", NoteFormat::Markdown)
             .await
             .expect("should succeed");
 
@@ -205,11 +363,91 @@ mod tests {
             .unwrap();
         graph.update_note("test", PathBuf::from("knowledge/test.md"), HashSet::new());
 
-        let result = execute(&storage, &graph, "memory:knowledge/test")
+        let result = execute(&storage, &graph, "memory:knowledge/test", NoteFormat::Markdown)
             .await
             .expect("should succeed");
 
         let response = parse_response(&result);
         assert_eq!(response.content, "Content");
     }
+
+    #[tokio::test]
+    async fn test_read_html_renders_resolved_link_as_anchor() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+
+        fs::create_dir(temp_dir.path().join("knowledge")).await.unwrap();
+        fs::write(temp_dir.path().join("knowledge/Rust.md"), "Rust notes")
+            .await
+            .unwrap();
+        fs::write(temp_dir.path().join("test.md"), "# Title\n\nSee [[Rust|the Rust note]].")
+            .await
+            .unwrap();
+        graph.update_note("Rust", PathBuf::from("knowledge/Rust.md"), HashSet::new());
+        graph.update_note(
+            "test",
+            PathBuf::from("test.md"),
+            HashSet::from(["Rust".to_string()]),
+        );
+
+        let result = execute(&storage, &graph, "test", NoteFormat::Html)
+            .await
+            .expect("should succeed");
+
+        let response = parse_response(&result);
+        assert!(response.content.contains("<h1>Title</h1>"), "{}", response.content);
+        assert!(
+            response.content.contains(
+                r#"<a class="wiki-link" href="memory:knowledge/Rust">the Rust note</a>"#
+            ),
+            "{}",
+            response.content
+        );
+        // Hash is still of the stored markdown
+        assert_eq!(
+            response.content_hash,
+            ContentHash::from_content("# Title\n\nSee [[Rust|the Rust note]].").as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_html_renders_unresolved_link_as_dangling_span() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+
+        fs::write(temp_dir.path().join("test.md"), "See [[Missing]] and `[[Code]]`.")
+            .await
+            .unwrap();
+        graph.update_note("test", PathBuf::from("test.md"), HashSet::new());
+
+        let result = execute(&storage, &graph, "test", NoteFormat::Html)
+            .await
+            .expect("should succeed");
+
+        let response = parse_response(&result);
+        assert!(
+            response.content.contains(
+                r#"<span class="wiki-link dangling" data-target="Missing">Missing</span>"#
+            ),
+            "{}",
+            response.content
+        );
+        assert!(!response.content.contains("<a "), "{}", response.content);
+        // Links inside code stay literal
+        assert!(response.content.contains("<code>[[Code]]</code>"), "{}", response.content);
+    }
+
+    #[test]
+    fn test_resolve_link_href_requires_matching_folder() {
+        let mut graph = GraphIndex::new();
+        graph.update_note("Note", PathBuf::from("knowledge/Note.md"), HashSet::new());
+
+        assert_eq!(
+            resolve_link_href(&graph, "knowledge/Note#Some Header").as_deref(),
+            Some("memory:knowledge/Note#Some%20Header")
+        );
+        assert_eq!(
+            resolve_link_href(&graph, "Note#Setup#Some Header").as_deref(),
+            Some("memory:knowledge/Note#Some%20Header")
+        );
+        assert_eq!(resolve_link_href(&graph, "projects/Note"), None);
+    }
 }
@@ -0,0 +1,352 @@
+//! RebuildIndex tool - rebuild the graph index from the vault in the background.
+//!
+//! A recovery tool for when the index has drifted from the files, e.g. after
+//! missed watcher events. Every note is re-parsed, ignoring the startup
+//! snapshot, into a fresh index that replaces the live one once it's built, so
+//! other tools keep using the old index in the meantime.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tokio::sync::RwLock;
+
+use super::reindex_embeddings::collect_embeddable_notes;
+use crate::embeddings::EmbeddingManager;
+use crate::graph::GraphIndex;
+
+/// Progress of graph rebuilds, shared across sessions so calls don't stack.
+#[derive(Debug, Default)]
+pub struct RebuildTracker {
+    /// Set while a rebuild is running
+    running: AtomicBool,
+    /// Rebuilds finished since the server started
+    completed: AtomicUsize,
+    /// Notes indexed by the last finished rebuild
+    last_notes: AtomicUsize,
+}
+
+impl RebuildTracker {
+    /// Claim the rebuild slot, or return false if a rebuild is already running.
+    fn try_begin(&self) -> bool {
+        self.running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+
+    /// Release the slot, recording the note count if the rebuild succeeded.
+    fn finish(&self, notes: Option<usize>) {
+        if let Some(notes) = notes {
+            self.last_notes.store(notes, Ordering::SeqCst);
+            self.completed.fetch_add(1, Ordering::SeqCst);
+        }
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Response from RebuildIndex tool.
+#[derive(Debug, Serialize)]
+pub struct RebuildIndexResponse {
+    /// "started", or "in_progress" if a rebuild was already running
+    pub status: &'static str,
+    /// Whether embeddings are reindexed once this rebuild finishes
+    pub reindex_embeddings: bool,
+    /// Rebuilds finished since the server started
+    pub completed_rebuilds: usize,
+    /// Notes indexed by the last finished rebuild, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_rebuild_notes: Option<usize>,
+}
+
+/// Re-parse the whole vault into a fresh index and swap it in for `graph`.
+///
+/// The fresh index keeps `graph`'s private roots and ignore list. Notes the
+/// watcher updates while the vault is scanned are re-read before the swap, so
+/// those updates aren't lost. Returns the number of notes indexed.
+pub async fn rebuild(
+    vault_path: &Path,
+    graph: &RwLock<GraphIndex>,
+) -> Result<usize, std::io::Error> {
+    graph.write().await.track_changes();
+
+    // Built without holding the lock, so readers and the watcher aren't blocked
+    let mut rebuilt = graph.read().await.empty_like();
+    if let Err(e) = rebuilt.initialize_full(vault_path).await {
+        graph.write().await.take_changes();
+        return Err(e);
+    }
+    rebuilt.precompute_neighborhoods();
+
+    Ok(swap_in(vault_path, graph, rebuilt).await)
+}
+
+/// Replace `graph` with `rebuilt`, first re-reading the notes changed in
+/// `graph` since it started tracking changes. Returns the number of notes.
+async fn swap_in(
+    vault_path: &Path,
+    graph: &RwLock<GraphIndex>,
+    mut rebuilt: GraphIndex,
+) -> usize {
+    let mut graph = graph.write().await;
+    let changed = graph.take_changes();
+    for path in &changed {
+        rebuilt.refresh_note(vault_path, path).await;
+    }
+    if !changed.is_empty() {
+        tracing::debug!("Re-read {} notes changed during the rebuild", changed.len());
+    }
+
+    let notes = rebuilt.len();
+    *graph = rebuilt;
+    notes
+}
+
+/// Releases the rebuild slot when dropped, so a rebuild that panics doesn't
+/// leave it claimed forever.
+struct RebuildGuard {
+    tracker: Arc<RebuildTracker>,
+    /// Notes indexed, once the rebuild has succeeded
+    notes: Option<usize>,
+}
+
+impl Drop for RebuildGuard {
+    fn drop(&mut self) {
+        self.tracker.finish(self.notes);
+    }
+}
+
+/// Rebuild the index, then reindex embeddings if asked.
+async fn rebuild_in_background(
+    vault_path: PathBuf,
+    graph: Arc<RwLock<GraphIndex>>,
+    embeddings: Arc<EmbeddingManager>,
+    tracker: Arc<RebuildTracker>,
+    reindex_embeddings: bool,
+) {
+    let mut guard = RebuildGuard {
+        tracker,
+        notes: None,
+    };
+    tracing::info!("Rebuilding graph index...");
+    let notes = match rebuild(&vault_path, &graph).await {
+        Ok(notes) => {
+            tracing::info!("Rebuilt graph index with {} notes", notes);
+            Some(notes)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to rebuild graph index: {}", e);
+            None
+        }
+    };
+    guard.notes = notes;
+    drop(guard);
+
+    if notes.is_none() || !reindex_embeddings {
+        return;
+    }
+    let notes = collect_embeddable_notes(&vault_path, &graph).await;
+    if !embeddings.try_begin_reindex(notes.len()) {
+        tracing::info!("Embedding reindex already running, not starting another");
+        return;
+    }
+    match embeddings.reindex(&notes).await {
+        Ok(count) => tracing::info!("Reindexed {} embeddings", count),
+        Err(e) => tracing::warn!("Failed to reindex embeddings: {}", e),
+    }
+}
+
+/// Execute the RebuildIndex tool.
+///
+/// Starts a rebuild in a background task and returns right away. Calling it
+/// again while a rebuild is running reports progress instead of starting
+/// another; once `completed_rebuilds` goes up, the new index is live.
+pub async fn execute(
+    vault_path: &Path,
+    graph: &Arc<RwLock<GraphIndex>>,
+    embeddings: &Arc<EmbeddingManager>,
+    tracker: &Arc<RebuildTracker>,
+    reindex_embeddings: bool,
+) -> Result<CallToolResult, ErrorData> {
+    let status = if tracker.try_begin() {
+        tokio::spawn(rebuild_in_background(
+            vault_path.to_path_buf(),
+            graph.clone(),
+            embeddings.clone(),
+            tracker.clone(),
+            reindex_embeddings,
+        ));
+        "started"
+    } else {
+        "in_progress"
+    };
+
+    let completed_rebuilds = tracker.completed.load(Ordering::SeqCst);
+    let response = RebuildIndexResponse {
+        status,
+        reindex_embeddings: status == "started" && reindex_embeddings,
+        completed_rebuilds,
+        last_rebuild_notes: (completed_rebuilds > 0)
+            .then(|| tracker.last_notes.load(Ordering::SeqCst)),
+    };
+    let json = serde_json::to_string(&response).map_err(|e| {
+        ErrorData::internal_error(format!("Failed to serialize response: {}", e), None)
+    })?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn parse_response(result: &CallToolResult) -> serde_json::Value {
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+        serde_json::from_str(text).unwrap()
+    }
+
+    /// A vault where A links to B, with its index.
+    async fn indexed_vault() -> (TempDir, Arc<RwLock<GraphIndex>>) {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("A.md"), "See [[B]]").unwrap();
+        std::fs::write(temp_dir.path().join("B.md"), "Target").unwrap();
+
+        let mut graph = GraphIndex::new();
+        graph.initialize(temp_dir.path()).await.unwrap();
+        (temp_dir, Arc::new(RwLock::new(graph)))
+    }
+
+    /// Make the index disagree with the files, as missed watcher events would.
+    async fn corrupt(graph: &RwLock<GraphIndex>) {
+        let mut graph = graph.write().await;
+        graph.update_note(
+            "A",
+            PathBuf::from("A.md"),
+            HashSet::from(["Ghost".to_string()]),
+        );
+        graph.remove_note("B", Path::new("B.md"));
+        graph.update_note("Stale", PathBuf::from("Stale.md"), HashSet::new());
+    }
+
+    fn assert_matches_vault(graph: &GraphIndex) {
+        assert_eq!(
+            graph.get_forward_links("A.md"),
+            Some(&HashSet::from(["B".to_string()]))
+        );
+        assert!(graph.get_backlinks("B").unwrap().contains("A.md"));
+        assert!(
+            graph
+                .get_backlinks("Ghost")
+                .is_none_or(|paths| paths.is_empty())
+        );
+        assert!(graph.get_path("B").is_some());
+        assert!(graph.get_path("Stale").is_none());
+        assert_eq!(graph.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_restores_corrupted_index() {
+        let (temp_dir, graph) = indexed_vault().await;
+        corrupt(&graph).await;
+
+        let notes = rebuild(temp_dir.path(), &graph).await.unwrap();
+        assert_eq!(notes, 2);
+        assert_matches_vault(&*graph.read().await);
+    }
+
+    #[tokio::test]
+    async fn test_swap_keeps_watcher_updates_made_during_scan() {
+        let (temp_dir, graph) = indexed_vault().await;
+        graph.write().await.track_changes();
+        let mut rebuilt = graph.read().await.empty_like();
+        rebuilt.initialize_full(temp_dir.path()).await.unwrap();
+
+        // The watcher applies edits to the live index while the scan runs
+        std::fs::write(temp_dir.path().join("A.md"), "See [[C]]").unwrap();
+        std::fs::write(temp_dir.path().join("C.md"), "New").unwrap();
+        std::fs::remove_file(temp_dir.path().join("B.md")).unwrap();
+        {
+            let mut graph = graph.write().await;
+            graph.update_note("A", PathBuf::from("A.md"), HashSet::from(["C".to_string()]));
+            graph.update_note("C", PathBuf::from("C.md"), HashSet::new());
+            graph.remove_note("B", Path::new("B.md"));
+        }
+
+        assert_eq!(swap_in(temp_dir.path(), &graph, rebuilt).await, 2);
+        let graph = graph.read().await;
+        assert_eq!(
+            graph.get_forward_links("A.md"),
+            Some(&HashSet::from(["C".to_string()]))
+        );
+        assert!(graph.get_path("C").is_some());
+        assert!(graph.get_path("B").is_none());
+    }
+
+    #[test]
+    fn test_guard_releases_slot_on_panic() {
+        let tracker = Arc::new(RebuildTracker::default());
+        assert!(tracker.try_begin());
+
+        let guard_tracker = tracker.clone();
+        let result = std::panic::catch_unwind(move || {
+            let _guard = RebuildGuard {
+                tracker: guard_tracker,
+                notes: None,
+            };
+            panic!("rebuild failed");
+        });
+
+        assert!(result.is_err());
+        assert!(tracker.try_begin());
+        assert_eq!(tracker.completed.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rebuilds_in_background() {
+        let (temp_dir, graph) = indexed_vault().await;
+        let embeddings = Arc::new(EmbeddingManager::new(temp_dir.path()));
+        let tracker = Arc::new(RebuildTracker::default());
+        corrupt(&graph).await;
+
+        let result = execute(temp_dir.path(), &graph, &embeddings, &tracker, false)
+            .await
+            .unwrap();
+        let response = parse_response(&result);
+        assert_eq!(response["status"], "started");
+        assert_eq!(response["completed_rebuilds"], 0);
+
+        for _ in 0..100 {
+            if tracker.completed.load(Ordering::SeqCst) > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_matches_vault(&*graph.read().await);
+
+        let result = execute(temp_dir.path(), &graph, &embeddings, &tracker, false)
+            .await
+            .unwrap();
+        let response = parse_response(&result);
+        assert!(response["completed_rebuilds"].as_u64().unwrap() >= 1);
+        assert_eq!(response["last_rebuild_notes"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_reports_in_progress() {
+        let (temp_dir, graph) = indexed_vault().await;
+        let embeddings = Arc::new(EmbeddingManager::new(temp_dir.path()));
+        let tracker = Arc::new(RebuildTracker::default());
+        assert!(tracker.try_begin());
+
+        let result = execute(temp_dir.path(), &graph, &embeddings, &tracker, true)
+            .await
+            .unwrap();
+        let response = parse_response(&result);
+        assert_eq!(response["status"], "in_progress");
+        assert_eq!(response["reindex_embeddings"], false);
+        assert!(response.get("last_rebuild_notes").is_none());
+    }
+}
@@ -4,27 +4,253 @@
 //! journal, project notes) and returns detailed instructions for consolidating content
 //! into permanent storage. It doesn't perform the consolidation itself - it provides
 //! a comprehensive prompt that guides the agent through the process.
+//!
+//! Alongside the prompt, it lists specific consolidation candidates as structured
+//! content: log days and labile notes that have gone stale (by date and mtime)
+//! or grown past the note size cap, each with a suggested action.
 
+use chrono::{DateTime, Local};
+use obsidian_fs::PrivateRoots;
 use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+use std::path::Path;
+use std::time::SystemTime;
+use tokio::fs;
+
+use super::compact_log::{parse_iso_week_date, split_days};
+use crate::graph::GraphIndex;
+use crate::projects::ProjectDiscoveryConfig;
+
+/// Default age in days after which labile content is a consolidation candidate.
+pub const DEFAULT_STALE_AFTER_DAYS: u32 = 14;
+
+/// Size past which a labile note should be fragmented (~2.5k tokens).
+const FRAGMENT_CHARS: usize = 10_000;
+
+/// What to do with a consolidation candidate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestedAction {
+    /// Move old log days out of Log.md (CompactLog)
+    Archive,
+    /// Move finished sections to permanent notes
+    Migrate,
+    /// Rewrite concisely, keeping key decisions
+    Compact,
+    /// Split into smaller linked notes
+    Fragment,
+}
+
+impl SuggestedAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            SuggestedAction::Archive => "archive",
+            SuggestedAction::Migrate => "migrate",
+            SuggestedAction::Compact => "compact",
+            SuggestedAction::Fragment => "fragment",
+        }
+    }
+}
+
+/// A note or log section that's due for consolidation.
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    /// Memory URI of the note
+    pub uri: String,
+    /// Log day (ISO week date) when the candidate is a section of Log.md
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Days since the log day, or since the note was last modified
+    pub age_days: i64,
+    pub action: SuggestedAction,
+    pub reason: String,
+}
+
+/// Execute the Reflect tool - returns consolidation instructions and candidates.
+pub async fn execute(
+    vault_path: &Path,
+    graph: &GraphIndex,
+    discovery_config: &ProjectDiscoveryConfig,
+    include_private: bool,
+    stale_after_days: Option<u32>,
+) -> Result<CallToolResult, ErrorData> {
+    let candidates = find_candidates(
+        vault_path,
+        graph,
+        discovery_config,
+        include_private,
+        stale_after_days.unwrap_or(DEFAULT_STALE_AFTER_DAYS),
+        Local::now(),
+    )
+    .await;
+
+    let mut prompt = build_reflect_prompt(include_private, graph.private_roots());
+    prompt.push_str(&format_candidates(&candidates));
+
+    Ok(CallToolResult {
+        content: vec![Content::text(prompt)],
+        is_error: None,
+        meta: None,
+        structured_content: Some(serde_json::json!({ "candidates": candidates })),
+    })
+}
+
+/// Find log days and labile notes due for consolidation as of `now`.
+///
+/// Log days are stale by their date and notes by their mtime. Labile notes are
+/// Working Memory (plus the private one with `include_private`) and project notes.
+async fn find_candidates(
+    vault_path: &Path,
+    graph: &GraphIndex,
+    discovery_config: &ProjectDiscoveryConfig,
+    include_private: bool,
+    stale_after_days: u32,
+    now: DateTime<Local>,
+) -> Vec<Candidate> {
+    let stale_after_days = i64::from(stale_after_days);
+    let mut candidates = Vec::new();
+
+    if let Ok(log) = fs::read_to_string(vault_path.join("Log.md")).await {
+        let (_, days) = split_days(&log);
+        for day in days {
+            let Some(iso_week_date) = day.iso_week_date else {
+                continue;
+            };
+            let Some(date) = parse_iso_week_date(iso_week_date) else {
+                continue;
+            };
+            let age_days = (now.date_naive() - date).num_days();
+            if age_days > stale_after_days {
+                candidates.push(Candidate {
+                    uri: "memory:Log".to_string(),
+                    section: Some(iso_week_date.to_string()),
+                    age_days,
+                    action: SuggestedAction::Archive,
+                    reason: "Log day is past the stale threshold; summarize it into the weekly journal, then archive it with CompactLog".to_string(),
+                });
+            }
+        }
+    }
+
+    let mut labile = vec![("Working Memory.md".to_string(), SuggestedAction::Migrate)];
+    if include_private {
+        for root in graph.private_roots().roots() {
+            labile.push((
+                format!("{}/Working Memory.md", root),
+                SuggestedAction::Migrate,
+            ));
+        }
+    }
+    let mut projects: Vec<String> = graph
+        .all_paths()
+        .filter(|path| include_private || !graph.is_private(path))
+        .filter(|path| discovery_config.is_project(path, graph.get_frontmatter(path)))
+        .cloned()
+        .collect();
+    projects.sort();
+    labile.extend(
+        projects
+            .into_iter()
+            .map(|path| (path, SuggestedAction::Compact)),
+    );
+
+    for (path, stale_action) in labile {
+        let full_path = vault_path.join(&path);
+        let (Ok(metadata), Ok(content)) = (
+            fs::metadata(&full_path).await,
+            fs::read_to_string(&full_path).await,
+        ) else {
+            continue;
+        };
+        let uri = format!("memory:{}", path.trim_end_matches(".md"));
+        let age_days = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| SystemTime::from(now).duration_since(modified).ok())
+            .map(|age| (age.as_secs() / 86_400) as i64)
+            .unwrap_or(0);
+
+        if age_days > stale_after_days {
+            let reason = match stale_action {
+                SuggestedAction::Migrate => {
+                    "Not modified since the stale threshold; migrate finished sections to permanent notes"
+                }
+                _ => {
+                    "Project note not modified since the stale threshold; compact shipped work into brief summaries"
+                }
+            };
+            candidates.push(Candidate {
+                uri: uri.clone(),
+                section: None,
+                age_days,
+                action: stale_action,
+                reason: reason.to_string(),
+            });
+        }
+        if content.len() > FRAGMENT_CHARS {
+            candidates.push(Candidate {
+                uri,
+                section: None,
+                age_days,
+                action: SuggestedAction::Fragment,
+                reason: format!(
+                    "{} characters, past the ~{} character note cap; split into focused notes",
+                    content.len(),
+                    FRAGMENT_CHARS
+                ),
+            });
+        }
+    }
+
+    candidates
+}
 
-/// Execute the Reflect tool - returns consolidation instructions.
-pub fn execute(include_private: bool) -> Result<CallToolResult, ErrorData> {
-    let prompt = build_reflect_prompt(include_private);
-    Ok(CallToolResult::success(vec![Content::text(prompt)]))
+/// Render candidates as a prompt section, or nothing if there are none.
+fn format_candidates(candidates: &[Candidate]) -> String {
+    if candidates.is_empty() {
+        return String::new();
+    }
+    let mut section = String::from("\n## Consolidation Candidates\n\n");
+    for candidate in candidates {
+        let target = match &candidate.section {
+            Some(day) => format!("{}#{}", candidate.uri, day),
+            None => candidate.uri.clone(),
+        };
+        section.push_str(&format!(
+            "- {} – {} ({} days old): {}\n",
+            target,
+            candidate.action.as_str(),
+            candidate.age_days,
+            candidate.reason
+        ));
+    }
+    section
 }
 
 /// Build the comprehensive consolidation prompt.
-fn build_reflect_prompt(include_private: bool) -> String {
+fn build_reflect_prompt(include_private: bool, private_roots: &PrivateRoots) -> String {
     let private_section = if include_private {
-        r#"
+        let in_roots = |file: &str| {
+            private_roots
+                .roots()
+                .iter()
+                .map(|root| format!("`{}/{}`", root, file))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!(
+            r#"
 ## Private Memory
 
-You have access to private memory for this session. Include `private/Working Memory.md` 
+You have access to private memory for this session. Include {} 
 in your review alongside the regular Working Memory. Private content should consolidate 
-to `private/*.md` knowledge notes, not public ones.
-"#
+to {} knowledge notes, not public ones.
+"#,
+            in_roots("Working Memory.md"),
+            in_roots("*.md")
+        )
     } else {
-        ""
+        String::new()
     };
 
     format!(
@@ -104,6 +330,7 @@ When consolidating logs to weekly journal, create episodic narratives:
 4. **Apply** - After user approval:
    - Use Write tool for note updates
    - Use WriteLogs tool for Log.md changes
+   - Use CompactLog tool to archive old Log.md days once they're summarized
    - Verify changes with Read tool
 
 5. **Report** - Summarize what was consolidated:
@@ -111,6 +338,12 @@ When consolidating logs to weekly journal, create episodic narratives:
    - Notes updated
    - Information preserved vs. removed
 
+## Candidates
+
+Any log days or notes already due for consolidation are listed below and in
+the structured `candidates` list, each with a suggested action: archive,
+migrate, compact, or fragment.
+
 ## Token Targets
 
 - Auto-loaded files (Remember): <10k tokens combined
@@ -125,19 +358,43 @@ Begin by reading the active context files, then propose your consolidation plan.
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Datelike;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    const DAY: Duration = Duration::from_secs(86_400);
+
+    async fn reflect(include_private: bool) -> CallToolResult {
+        let temp = TempDir::new().unwrap();
+        execute(
+            temp.path(),
+            &GraphIndex::new(),
+            &ProjectDiscoveryConfig::default(),
+            include_private,
+            None,
+        )
+        .await
+        .unwrap()
+    }
 
-    #[test]
-    fn test_execute_returns_success() {
-        let result = execute(false);
-        assert!(result.is_ok());
+    fn set_age(path: &Path, age: Duration) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - age)
+            .unwrap();
+    }
 
-        let call_result = result.unwrap();
+    #[tokio::test]
+    async fn test_execute_returns_success() {
+        let call_result = reflect(false).await;
         assert!(!call_result.is_error.unwrap_or(false));
     }
 
-    #[test]
-    fn test_prompt_contains_key_sections() {
-        let result = execute(false).unwrap();
+    #[tokio::test]
+    async fn test_prompt_contains_key_sections() {
+        let result = reflect(false).await;
         let content = result.content[0]
             .raw
             .as_text()
@@ -150,9 +407,9 @@ mod tests {
         assert!(content.text.contains("Token Targets"));
     }
 
-    #[test]
-    fn test_private_flag_includes_private_section() {
-        let result = execute(true).unwrap();
+    #[tokio::test]
+    async fn test_private_flag_includes_private_section() {
+        let result = reflect(true).await;
         let content = result.content[0]
             .raw
             .as_text()
@@ -162,9 +419,9 @@ mod tests {
         assert!(content.text.contains("private/Working Memory.md"));
     }
 
-    #[test]
-    fn test_no_private_flag_excludes_private_section() {
-        let result = execute(false).unwrap();
+    #[tokio::test]
+    async fn test_no_private_flag_excludes_private_section() {
+        let result = reflect(false).await;
         let content = result.content[0]
             .raw
             .as_text()
@@ -172,4 +429,95 @@ mod tests {
 
         assert!(!content.text.contains("Private Memory"));
     }
+
+    #[tokio::test]
+    async fn test_candidates_only_list_stale_notes() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        fs::create_dir_all(vault.join("projects")).await.unwrap();
+        fs::create_dir_all(vault.join("knowledge")).await.unwrap();
+        fs::write(vault.join("Working Memory.md"), "# Working Memory\n")
+            .await
+            .unwrap();
+        fs::write(vault.join("projects/Fresh.md"), "# Fresh")
+            .await
+            .unwrap();
+        fs::write(vault.join("projects/Stale.md"), "# Stale")
+            .await
+            .unwrap();
+        fs::write(vault.join("knowledge/Old Fact.md"), "# Old Fact")
+            .await
+            .unwrap();
+        set_age(&vault.join("projects/Stale.md"), 30 * DAY);
+        // Permanent notes aren't labile, so age alone doesn't make them candidates
+        set_age(&vault.join("knowledge/Old Fact.md"), 90 * DAY);
+
+        let mut graph = GraphIndex::new();
+        graph.initialize(vault).await.unwrap();
+        let result = execute(
+            vault,
+            &graph,
+            &ProjectDiscoveryConfig::default(),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let structured = result.structured_content.unwrap();
+        let candidates = structured["candidates"].as_array().unwrap();
+        assert_eq!(candidates.len(), 1, "{:?}", candidates);
+        assert_eq!(candidates[0]["uri"], "memory:projects/Stale");
+        assert_eq!(candidates[0]["action"], "compact");
+        assert_eq!(candidates[0]["age_days"], 30);
+
+        let text = &result.content[0].raw.as_text().unwrap().text;
+        assert!(text.contains("- memory:projects/Stale – compact (30 days old)"));
+    }
+
+    #[tokio::test]
+    async fn test_candidates_include_old_log_days_and_large_notes() {
+        let temp = TempDir::new().unwrap();
+        let vault = temp.path();
+        let today = Local::now().date_naive();
+        let old_day = today - chrono::Duration::days(20);
+        let header = |date: chrono::NaiveDate| {
+            let week = date.iso_week();
+            format!(
+                "## {}-W{:02}-{} (Day)",
+                week.year(),
+                week.week(),
+                date.weekday().number_from_monday()
+            )
+        };
+        let log = format!(
+            "{}\n\n- 9:00 AM – Old\n\n{}\n\n- 9:00 AM – Today\n",
+            header(old_day),
+            header(today)
+        );
+        fs::write(vault.join("Log.md"), log).await.unwrap();
+        fs::write(
+            vault.join("Working Memory.md"),
+            "x".repeat(FRAGMENT_CHARS + 1),
+        )
+        .await
+        .unwrap();
+
+        let candidates = find_candidates(
+            vault,
+            &GraphIndex::new(),
+            &ProjectDiscoveryConfig::default(),
+            false,
+            DEFAULT_STALE_AFTER_DAYS,
+            Local::now(),
+        )
+        .await;
+
+        assert_eq!(candidates.len(), 2, "{:?}", candidates);
+        assert_eq!(candidates[0].uri, "memory:Log");
+        assert_eq!(candidates[0].age_days, 20);
+        assert_eq!(candidates[0].action, SuggestedAction::Archive);
+        assert_eq!(candidates[1].uri, "memory:Working Memory");
+        assert_eq!(candidates[1].action, SuggestedAction::Fragment);
+    }
 }
@@ -0,0 +1,203 @@
+//! ReindexEmbeddings tool - rebuild all note embeddings in the background.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::embeddings::{CacheStats, EmbeddingManager};
+use crate::graph::GraphIndex;
+
+/// Response from ReindexEmbeddings tool.
+#[derive(Debug, Serialize)]
+pub struct ReindexResponse {
+    /// "started", "in_progress", or "completed" (nothing to embed)
+    pub status: &'static str,
+    /// Number of notes queued for embedding by this call
+    pub notes: usize,
+    /// Cache state at the time of the call
+    pub cache: CacheStats,
+}
+
+/// Collect every note that should have an embedding.
+///
+/// Paths are gathered under the read lock, which is dropped before any file I/O.
+pub async fn collect_embeddable_notes(
+    vault_path: &Path,
+    graph: &RwLock<GraphIndex>,
+) -> Vec<(String, String)> {
+    let paths: Vec<String> = {
+        let graph = graph.read().await;
+        graph
+            .all_paths()
+            .filter(|path| !graph.is_excluded_from_search(path))
+            .cloned()
+            .collect()
+    };
+
+    let mut notes = Vec::with_capacity(paths.len());
+    for path in paths {
+        let full_path = vault_path.join(&path);
+        if let Ok(content) = tokio::fs::read_to_string(&full_path).await {
+            notes.push((path, content));
+        }
+    }
+
+    notes
+}
+
+/// Execute the ReindexEmbeddings tool.
+///
+/// Recomputes embeddings for all notes in a background task, replacing the
+/// embedding cache once they're done. Calling it again while a reindex is
+/// running reports the current progress instead of starting another one.
+pub async fn execute(
+    vault_path: &Path,
+    graph: &Arc<RwLock<GraphIndex>>,
+    embeddings: &Arc<EmbeddingManager>,
+) -> Result<CallToolResult, ErrorData> {
+    let notes = collect_embeddable_notes(vault_path, graph).await;
+    let total = notes.len();
+
+    let status = if !embeddings.try_begin_reindex(total) {
+        "in_progress"
+    } else if notes.is_empty() {
+        // Nothing to encode, so there's no need to load the model
+        embeddings.reindex(&notes).await.map_err(|e| {
+            ErrorData::internal_error(format!("Failed to reindex embeddings: {}", e), None)
+        })?;
+        "completed"
+    } else {
+        let embeddings = embeddings.clone();
+        tokio::spawn(async move {
+            tracing::info!("Reindexing embeddings for {} notes...", total);
+            match embeddings.reindex(&notes).await {
+                Ok(count) => tracing::info!("Reindexed {} embeddings", count),
+                Err(e) => tracing::warn!("Failed to reindex embeddings: {}", e),
+            }
+        });
+        "started"
+    };
+
+    let response = ReindexResponse {
+        status,
+        notes: if status == "in_progress" { 0 } else { total },
+        cache: embeddings.cache_stats().await,
+    };
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn parse_response(result: &CallToolResult) -> serde_json::Value {
+        let text = &result.content[0]
+            .raw
+            .as_text()
+            .expect("Expected text")
+            .text;
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_collect_embeddable_notes_skips_excluded() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault = temp_dir.path();
+        std::fs::write(vault.join("Visible.md"), "Content").unwrap();
+        std::fs::write(vault.join("Hidden.md"), "---\nnoindex: true\n---\nContent").unwrap();
+
+        let mut graph = GraphIndex::new();
+        graph.initialize(vault).await.unwrap();
+        let graph = RwLock::new(graph);
+
+        let notes = collect_embeddable_notes(vault, &graph).await;
+        assert_eq!(notes, vec![("Visible.md".to_string(), "Content".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_empty_vault_completes() {
+        let temp_dir = TempDir::new().unwrap();
+        let graph = Arc::new(RwLock::new(GraphIndex::new()));
+        let embeddings = Arc::new(EmbeddingManager::new(temp_dir.path()));
+
+        let result = execute(temp_dir.path(), &graph, &embeddings).await.unwrap();
+        let response = parse_response(&result);
+
+        assert_eq!(response["status"], "completed");
+        assert_eq!(response["cache"]["entries"], 0);
+        assert_eq!(response["cache"]["reindexing"], false);
+
+        // Idempotent: a second call behaves the same
+        let result = execute(temp_dir.path(), &graph, &embeddings).await.unwrap();
+        assert_eq!(parse_response(&result)["status"], "completed");
+    }
+
+    #[tokio::test]
+    async fn test_reindex_reports_in_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let graph = Arc::new(RwLock::new(GraphIndex::new()));
+        let embeddings = Arc::new(EmbeddingManager::new(temp_dir.path()));
+        assert!(embeddings.try_begin_reindex(7));
+
+        let result = execute(temp_dir.path(), &graph, &embeddings).await.unwrap();
+        let response = parse_response(&result);
+
+        assert_eq!(response["status"], "in_progress");
+        assert_eq!(response["cache"]["reindexing"], true);
+        assert_eq!(response["cache"]["reindex_total"], 7);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_recomputes_notes_and_swaps_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let embeddings = Arc::new(EmbeddingManager::new(temp_dir.path()));
+        let notes: Vec<(String, String)> = (0..30)
+            .map(|i| (format!("{}.md", i), format!("note {}", i)))
+            .collect();
+        let fake = |texts: Vec<String>| Ok(texts.iter().map(|_| vec![1.0]).collect());
+
+        assert!(embeddings.try_begin_reindex(notes.len()));
+        assert_eq!(embeddings.reindex_with(&notes, fake).await.unwrap(), 30);
+
+        // Every note is fresh in the cache, but a reindex encodes them all again
+        let kept = &notes[..27];
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let manager = embeddings.clone();
+        let encode = move |texts: Vec<String>| {
+            let stats = tokio::runtime::Handle::current().block_on(manager.cache_stats());
+            let embeddings = texts.iter().map(|_| vec![2.0]).collect();
+            recorded.lock().unwrap().push((texts, stats));
+            Ok(embeddings)
+        };
+
+        assert!(embeddings.try_begin_reindex(kept.len()));
+        assert_eq!(embeddings.reindex_with(kept, encode).await.unwrap(), 27);
+
+        let calls = std::mem::take(&mut *calls.lock().unwrap());
+        let encoded: Vec<&String> = calls.iter().flat_map(|(texts, _)| texts).collect();
+        let contents: Vec<&String> = kept.iter().map(|(_, content)| content).collect();
+        assert_eq!(encoded, contents);
+        // The old cache stays in place while the new entries are built
+        let progress: Vec<CacheStats> = calls.iter().map(|(_, stats)| *stats).collect();
+        assert_eq!(
+            progress,
+            vec![
+                CacheStats { entries: 30, reindexing: true, reindex_total: 27, reindexed: 0 },
+                CacheStats { entries: 30, reindexing: true, reindex_total: 27, reindexed: 25 },
+            ]
+        );
+
+        let stats = embeddings.cache_stats().await;
+        assert_eq!(
+            stats,
+            CacheStats { entries: 27, reindexing: false, reindex_total: 27, reindexed: 0 }
+        );
+    }
+}
@@ -9,16 +9,78 @@ use std::path::Path;
 
 use rmcp::model::{CallToolResult, Content, ErrorData, ResourceContents};
 
+use super::compact_log::split_days;
+use super::pagination::{next_cursor, parse_cursor};
 use crate::graph::GraphIndex;
-use crate::projects::{discover_projects, generate_discovery_status_message, DiscoveryResult};
+use crate::projects::{
+    discover_projects, generate_discovery_status_message, DiscoveryResult, ProjectDiscoveryConfig,
+};
 use crate::tools::get_weekly_note_info;
 
+/// Characters of note content per page (roughly 25k tokens)
+const PAGE_CHARS: usize = 100_000;
+
+/// End of the page of files starting at `start`, given each file's length.
+///
+/// Files are added until the next one would exceed `budget`, but every page
+/// holds at least one file so oversized notes still come through.
+fn page_end(sizes: &[usize], start: usize, budget: usize) -> usize {
+    let mut end = start.min(sizes.len());
+    let mut used = 0;
+    while end < sizes.len() && (end == start || used + sizes[end] <= budget) {
+        used += sizes[end];
+        end += 1;
+    }
+    end
+}
+
+/// Keep the most recent days of `log` that fit in `max_chars`.
+///
+/// Days are `## ` sections, oldest first as the Log tool writes them. Text
+/// before the first day is always kept, and so is the latest day even if it
+/// alone is over `max_chars`. Returns the trimmed log and how many days were
+/// dropped.
+fn trim_log(log: &str, max_chars: usize) -> (String, usize) {
+    let (preamble, days) = split_days(log);
+    if days.is_empty() {
+        return (log.to_string(), 0);
+    }
+
+    // Walk back from the latest day while the days still fit
+    let mut kept_days = 0;
+    let mut size = preamble.len();
+    for day in days.iter().rev() {
+        size += day.text.len();
+        if kept_days > 0 && size > max_chars {
+            break;
+        }
+        kept_days += 1;
+    }
+
+    let mut trimmed = preamble.to_string();
+    for day in &days[days.len() - kept_days..] {
+        trimmed.push_str(day.text);
+    }
+    (trimmed, days.len() - kept_days)
+}
+
 /// Execute the Remember tool
+///
+/// With a `budget`, older days of Log.md are dropped until all context files
+/// fit in that many characters, and a note says how much was trimmed.
+/// Context files are returned in pages of about [`PAGE_CHARS`]; when they
+/// don't all fit, the result includes a `next_cursor` to pass back as
+/// `cursor`. The project discovery status is only on the first page.
 pub async fn execute(
     vault_path: &Path,
     graph_index: &GraphIndex,
     cwd: &Path,
+    discovery_config: &ProjectDiscoveryConfig,
+    budget: Option<usize>,
+    cursor: Option<&str>,
 ) -> Result<CallToolResult, ErrorData> {
+    let start = parse_cursor(cursor)?;
+
     // Define paths to all context files
     let log_path = vault_path.join("Log.md");
     let working_memory_path = vault_path.join("Working Memory.md");
@@ -27,76 +89,84 @@ pub async fn execute(
     let (weekly_note_uri, weekly_note_path) = get_weekly_note_path(vault_path);
 
     // Discover projects for current working directory
-    let discovery_result = discover_projects(cwd, graph_index, vault_path);
+    let discovery_result = discover_projects(cwd, graph_index, vault_path, discovery_config);
 
-    // Read all context files
+    // Read all context files, as (uri, content), in the order they're returned
+    let mut files: Vec<(String, String)> = Vec::new();
     let log_content = tokio::fs::read_to_string(&log_path).await.ok();
-    let working_memory_content = tokio::fs::read_to_string(&working_memory_path).await.ok();
-    let weekly_note_content = tokio::fs::read_to_string(&weekly_note_path).await.ok();
+
+    if let Ok(content) = tokio::fs::read_to_string(&working_memory_path).await {
+        files.push((format!("file://{}", working_memory_path.display()), content));
+    }
+
+    if let Ok(content) = tokio::fs::read_to_string(&weekly_note_path).await {
+        files.push((weekly_note_uri, content));
+    }
 
     // Read strict match project notes
-    let mut project_contents = Vec::new();
     for m in &discovery_result.strict_matches {
         if let Ok(content) = tokio::fs::read_to_string(&m.metadata.file_path).await {
-            project_contents.push((
-                m.metadata.file_path.clone(),
-                m.metadata.name.clone(),
-                content,
-            ));
+            files.push((format!("file://{}", m.metadata.file_path.display()), content));
         }
     }
 
-    // Build content blocks array - one resource per file
-    let mut content_blocks: Vec<Content> = Vec::new();
-
-    if let Some(content) = log_content {
-        content_blocks.push(Content::resource(ResourceContents::TextResourceContents {
-            uri: format!("file://{}", log_path.display()),
-            mime_type: Some("text/markdown".into()),
-            text: content,
-            meta: None,
-        }));
+    // Trim the log to whatever budget the other files leave
+    let mut trimmed_days = 0;
+    if let Some(mut log) = log_content {
+        if let Some(budget) = budget {
+            let other_chars: usize = files.iter().map(|(_, content)| content.len()).sum();
+            (log, trimmed_days) = trim_log(&log, budget.saturating_sub(other_chars));
+        }
+        files.insert(0, (format!("file://{}", log_path.display()), log));
     }
 
-    if let Some(content) = working_memory_content {
-        content_blocks.push(Content::resource(ResourceContents::TextResourceContents {
-            uri: format!("file://{}", working_memory_path.display()),
-            mime_type: Some("text/markdown".into()),
-            text: content,
-            meta: None,
-        }));
-    }
+    let sizes: Vec<usize> = files.iter().map(|(_, content)| content.len()).collect();
+    let end = page_end(&sizes, start, PAGE_CHARS);
+    let next_cursor = next_cursor(end, files.len());
 
-    if let Some(content) = weekly_note_content {
-        content_blocks.push(Content::resource(ResourceContents::TextResourceContents {
-            uri: weekly_note_uri,
-            mime_type: Some("text/markdown".into()),
-            text: content,
-            meta: None,
-        }));
+    // Build content blocks array - one resource per file
+    let mut content_blocks: Vec<Content> = files
+        .into_iter()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .map(|(uri, text)| {
+            Content::resource(ResourceContents::TextResourceContents {
+                uri,
+                mime_type: Some("text/markdown".into()),
+                text,
+                meta: None,
+            })
+        })
+        .collect();
+
+    if start == 0 {
+        // Generate project discovery status message
+        let project_status = generate_discovery_status_message(&discovery_result, cwd);
+
+        // Add project status as text content
+        content_blocks.push(Content::text(project_status));
+
+        if trimmed_days > 0 {
+            content_blocks.push(Content::text(format!(
+                "Log.md was trimmed to fit the session context budget: {} older day(s) omitted. \
+                 Use ReadNote on Log to see the full log.",
+                trimmed_days
+            )));
+        }
     }
 
-    // Add strictly matched project notes
-    for (file_path, _name, content) in project_contents {
-        content_blocks.push(Content::resource(ResourceContents::TextResourceContents {
-            uri: format!("file://{}", file_path.display()),
-            mime_type: Some("text/markdown".into()),
-            text: content,
-            meta: None,
-        }));
+    if let Some(cursor) = &next_cursor {
+        content_blocks.push(Content::text(format!(
+            "More context files remain. Call Remember with cursor \"{}\" to load them.",
+            cursor
+        )));
     }
 
-    // Generate project discovery status message
-    let project_status = generate_discovery_status_message(&discovery_result, cwd);
-
-    // Add project status as text content
-    content_blocks.push(Content::text(project_status));
-
     Ok(CallToolResult {
         content: content_blocks,
         is_error: None,
         meta: None,
-        structured_content: Some(build_structured_content(&discovery_result)),
+        structured_content: Some(build_structured_content(&discovery_result, next_cursor)),
     })
 }
 
@@ -112,12 +182,19 @@ fn get_weekly_note_path(vault_path: &Path) -> (String, std::path::PathBuf) {
 }
 
 /// Build structured content for the response
-fn build_structured_content(discovery_result: &DiscoveryResult) -> serde_json::Value {
-    serde_json::json!({
+fn build_structured_content(
+    discovery_result: &DiscoveryResult,
+    next_cursor: Option<String>,
+) -> serde_json::Value {
+    let mut structured = serde_json::json!({
         "projectsFound": discovery_result.strict_matches.len(),
         "projectDisconnects": discovery_result.loose_matches.len(),
         "projectSuggestions": discovery_result.suggestions.len(),
-    })
+    });
+    if let Some(cursor) = next_cursor {
+        structured["next_cursor"] = serde_json::Value::String(cursor);
+    }
+    structured
 }
 
 #[cfg(test)]
@@ -178,9 +255,16 @@ mod tests {
         let vault_path = temp_dir.path();
 
         // Use a non-matching CWD so we don't trigger project discovery
-        let result = execute(vault_path, &graph, Path::new("/tmp"))
-            .await
-            .unwrap();
+        let result = execute(
+            vault_path,
+            &graph,
+            Path::new("/tmp"),
+            &ProjectDiscoveryConfig::default(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
 
         // Should have at least Log, Working Memory, Weekly Note, and status message
         assert!(result.content.len() >= 3);
@@ -219,13 +303,183 @@ mod tests {
             .output()
             .ok();
 
-        let result = execute(vault_path, &graph, &test_cwd).await.unwrap();
+        let result = execute(
+            vault_path,
+            &graph,
+            &test_cwd,
+            &ProjectDiscoveryConfig::default(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
 
         // Check structured content shows project found
         let structured = result.structured_content.unwrap();
         assert_eq!(structured["projectsFound"], 1);
     }
 
+    #[test]
+    fn test_page_end_fills_budget() {
+        let sizes = [40, 30, 50, 200, 10];
+
+        assert_eq!(page_end(&sizes, 0, 100), 2);
+        assert_eq!(page_end(&sizes, 2, 100), 3);
+        // An oversized file still gets a page to itself
+        assert_eq!(page_end(&sizes, 3, 100), 4);
+        assert_eq!(page_end(&sizes, 4, 100), 5);
+        assert_eq!(page_end(&sizes, 9, 100), 5);
+    }
+
+    #[tokio::test]
+    async fn test_remember_pages_return_each_file_once() {
+        let (temp_dir, graph) = create_test_vault();
+        let vault_path = temp_dir.path();
+        // Working Memory alone fills a page
+        std::fs::write(
+            vault_path.join("Working Memory.md"),
+            "x".repeat(PAGE_CHARS),
+        )
+        .unwrap();
+
+        let first = execute(
+            vault_path,
+            &graph,
+            Path::new("/tmp"),
+            &ProjectDiscoveryConfig::default(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let mut cursor = first.structured_content.as_ref().unwrap()["next_cursor"]
+            .as_str()
+            .map(str::to_string);
+        let mut uris: Vec<String> = Vec::new();
+        let mut collect = |result: &CallToolResult| {
+            for block in &result.content {
+                if let Some(resource) = block.raw.as_resource()
+                    && let ResourceContents::TextResourceContents { uri, .. } = &resource.resource
+                {
+                    uris.push(uri.clone());
+                }
+            }
+        };
+        collect(&first);
+
+        let mut pages = 1;
+        while let Some(next) = cursor {
+            let page = execute(
+                vault_path,
+                &graph,
+                Path::new("/tmp"),
+                &ProjectDiscoveryConfig::default(),
+                None,
+                Some(&next),
+            )
+            .await
+            .unwrap();
+            collect(&page);
+            cursor = page.structured_content.as_ref().unwrap()["next_cursor"]
+                .as_str()
+                .map(str::to_string);
+            pages += 1;
+        }
+
+        assert_eq!(pages, 3);
+        let mut unique = uris.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), uris.len(), "duplicate files: {:?}", uris);
+        assert_eq!(uris.len(), 3, "{:?}", uris);
+        assert!(uris[0].ends_with("Log.md"));
+        assert!(uris[1].ends_with("Working Memory.md"));
+    }
+
+    #[test]
+    fn test_trim_log_keeps_latest_days() {
+        let log = "# Log\n\n## 2025-W01-1 (Mon)\n\n- 9:00 AM – Old\n\n## 2025-W01-2 (Tue)\n\n- 9:00 AM – Newer\n\n## 2025-W01-3 (Wed)\n\n- 9:00 AM – Latest\n";
+
+        assert_eq!(trim_log(log, 10_000), (log.to_string(), 0));
+
+        let (trimmed, dropped) = trim_log(log, 100);
+        assert_eq!(dropped, 1);
+        assert_eq!(
+            trimmed,
+            "# Log\n\n## 2025-W01-2 (Tue)\n\n- 9:00 AM – Newer\n\n## 2025-W01-3 (Wed)\n\n- 9:00 AM – Latest\n"
+        );
+
+        // The latest day is kept even when it doesn't fit
+        let (trimmed, dropped) = trim_log(log, 0);
+        assert_eq!(dropped, 2);
+        assert_eq!(trimmed, "# Log\n\n## 2025-W01-3 (Wed)\n\n- 9:00 AM – Latest\n");
+    }
+
+    #[tokio::test]
+    async fn test_remember_budget_trims_older_log_days() {
+        let (temp_dir, graph) = create_test_vault();
+        let vault_path = temp_dir.path();
+        let days: Vec<String> = (1..=7)
+            .map(|day| format!("## 2025-W01-{} (Day)\n\n- 9:00 AM – Entry for day {}\n", day, day))
+            .collect();
+        std::fs::write(vault_path.join("Log.md"), days.join("\n")).unwrap();
+
+        // Room for the other files plus the two latest days of log
+        let other_chars = "### Active\n\nSome notes here\n".len() + "# Week Notes\n\nThis week's journal\n".len();
+        let budget = other_chars + days[5].len() + days[6].len() + 2;
+        let result = execute(
+            vault_path,
+            &graph,
+            Path::new("/tmp"),
+            &ProjectDiscoveryConfig::default(),
+            Some(budget),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let log = result
+            .content
+            .iter()
+            .filter_map(|c| c.raw.as_resource())
+            .find_map(|r| match &r.resource {
+                ResourceContents::TextResourceContents { uri, text, .. } if uri.ends_with("Log.md") => {
+                    Some(text.clone())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert!(log.contains("Entry for day 7"), "{}", log);
+        assert!(log.contains("Entry for day 6"), "{}", log);
+        assert!(!log.contains("Entry for day 5"), "{}", log);
+        assert!(!log.contains("Entry for day 1"), "{}", log);
+
+        let note = result
+            .content
+            .iter()
+            .filter_map(|c| c.raw.as_text())
+            .find(|t| t.text.contains("trimmed"))
+            .expect("trim note");
+        assert!(note.text.contains("5 older day(s) omitted"), "{}", note.text);
+
+        // Without a budget the whole log is returned
+        let result = execute(
+            vault_path,
+            &graph,
+            Path::new("/tmp"),
+            &ProjectDiscoveryConfig::default(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(result
+            .content
+            .iter()
+            .filter_map(|c| c.raw.as_text())
+            .all(|t| !t.text.contains("trimmed")));
+    }
+
     #[tokio::test]
     async fn test_remember_handles_missing_files() {
         let temp_dir = TempDir::new().unwrap();
@@ -233,9 +487,16 @@ mod tests {
         let graph = GraphIndex::new();
 
         // Empty vault - no files exist
-        let result = execute(vault_path, &graph, Path::new("/tmp"))
-            .await
-            .unwrap();
+        let result = execute(
+            vault_path,
+            &graph,
+            Path::new("/tmp"),
+            &ProjectDiscoveryConfig::default(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
 
         // Should still succeed with just the status message
         assert!(!result.content.is_empty());
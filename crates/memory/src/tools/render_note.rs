@@ -0,0 +1,447 @@
+//! RenderNote tool - preview a note as HTML, roughly as Obsidian displays it.
+//!
+//! Builds on ReadNote's HTML format: wiki-links become anchors to the notes
+//! they resolve to, `![[Note]]` and `![[Note#Heading]]` embeds are inlined,
+//! and frontmatter is shown as a table above the body.
+//!
+//! Embeds are expanded up to a configured depth (one level by default). Embeds
+//! past that depth, and embeds of a note already being expanded, render as
+//! links, so embed cycles can't recurse.
+
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+
+use obsidian_fs::{
+    ensure_markdown_extension, extract_headings, heading_anchor, parse_frontmatter,
+    split_frontmatter, Frontmatter,
+};
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag};
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use wiki_links::parse_wiki_links;
+
+use super::common::resolve_note_uri;
+use super::read_note::{escape_attribute, render_html_with_embeds, resolve_link_path};
+use crate::graph::GraphIndex;
+use crate::storage::{Storage, StorageError};
+
+/// Response from RenderNote tool.
+#[derive(Serialize)]
+pub struct RenderNoteResponse {
+    /// The memory URI of the note
+    pub uri: String,
+    /// The rendered note: frontmatter table followed by the body
+    pub html: String,
+    /// Memory URIs of the notes inlined into the body, in link order
+    pub embedded: Vec<String>,
+}
+
+/// Targets of the `![[...]]` embeds in `body`, skipping those inside code.
+fn embed_targets(body: &str) -> BTreeSet<String> {
+    Parser::new_ext(body, Options::all())
+        .filter_map(|event| match event {
+            Event::Start(Tag::Image { link_type: LinkType::WikiLink { .. }, dest_url, .. }) => {
+                Some(dest_url.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The section under the last heading of `path`, including the heading line itself.
+///
+/// Each heading in `path` is looked for within the section of the one before,
+/// so `["Usage", "Notes"]` finds the Notes under Usage. A section runs until
+/// the next heading of the same or a higher level. Headings match by their
+/// link anchor, so `Setup: Linux` matches `Setup Linux`.
+fn heading_section(body: &str, path: &[&str]) -> Option<String> {
+    let headings = extract_headings(body);
+    // Headings to search, then the matched heading and the end of its section
+    let mut within = 0..headings.len();
+    let mut found = None;
+    for heading in path {
+        let wanted = heading_anchor(heading);
+        let index = within
+            .clone()
+            .find(|&i| heading_anchor(&headings[i].text) == wanted)?;
+        let end = (index + 1..headings.len())
+            .find(|&i| headings[i].level <= headings[index].level)
+            .unwrap_or(headings.len());
+        within = index + 1..end;
+        found = Some((index, end));
+    }
+    let (index, end) = found?;
+
+    let lines: Vec<&str> = body.split('\n').collect();
+    let end_line = headings.get(end).map_or(lines.len(), |h| h.line);
+    Some(lines[headings[index].line..end_line].join("\n"))
+}
+
+/// Display text for a frontmatter value: lists are comma-separated.
+fn frontmatter_value(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(text) => text.clone(),
+        JsonValue::Array(items) => items.iter().map(frontmatter_value).collect::<Vec<_>>().join(", "),
+        JsonValue::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Render frontmatter as a two-column table of keys and values, sorted by key.
+fn render_frontmatter(frontmatter: &Frontmatter) -> String {
+    let mut keys: Vec<&String> = frontmatter.keys().collect();
+    keys.sort();
+
+    let mut output = String::from("<table class=\"frontmatter\">\n<tbody>\n");
+    for key in keys {
+        output.push_str(&format!(
+            "<tr><th>{}</th><td>{}</td></tr>\n",
+            escape_attribute(key),
+            escape_attribute(&frontmatter_value(&frontmatter[key]))
+        ));
+    }
+    output.push_str("</tbody>\n</table>\n");
+    output
+}
+
+/// Rendered embeds keyed by embed target, and the URIs of the notes inlined.
+type RenderedEmbeds = (HashMap<String, String>, Vec<String>);
+
+/// Render each embed in `body` that can be inlined, keyed by embed target.
+///
+/// Embeds inside embedded notes are expanded until `depth` levels are used.
+/// Returns the rendered embeds and the URIs of the notes they came from,
+/// nested ones included. Embeds of a note in `ancestors` (the chain of notes
+/// being expanded), block references, missing notes, and missing headings are
+/// left out, so they render as links.
+fn render_embeds<'a, S: Storage>(
+    storage: &'a S,
+    graph: &'a GraphIndex,
+    body: &'a str,
+    ancestors: &'a [String],
+    depth: usize,
+) -> Pin<Box<dyn Future<Output = Result<RenderedEmbeds, ErrorData>> + Send + 'a>> {
+    Box::pin(async move {
+        let mut embeds = HashMap::new();
+        let mut embedded = Vec::new();
+        if depth == 0 {
+            return Ok((embeds, embedded));
+        }
+
+        for target in embed_targets(body) {
+            let Some(link) = parse_wiki_links(&format!("[[{}]]", target)).into_iter().next() else {
+                continue;
+            };
+            if link.block_id.is_some() {
+                continue;
+            }
+            let Some(path) = resolve_link_path(graph, &link) else {
+                continue;
+            };
+            if ancestors.contains(&path) {
+                continue;
+            }
+
+            let uri = path.strip_suffix(".md").unwrap_or(&path).to_string();
+            let content = match storage.read(&uri).await {
+                Ok((content, _metadata)) => content,
+                Err(StorageError::NotFound { .. }) => continue,
+                Err(e) => {
+                    return Err(ErrorData::internal_error(
+                        format!("Failed to read embedded note {}: {}", uri, e),
+                        None,
+                    ));
+                }
+            };
+
+            let (_, embedded_body) = split_frontmatter(&content);
+            let section = if link.header.is_some() {
+                match heading_section(embedded_body, &link.header_path()) {
+                    Some(section) => section,
+                    None => continue,
+                }
+            } else {
+                embedded_body.to_string()
+            };
+
+            let chain = [ancestors, std::slice::from_ref(&path)].concat();
+            let (nested, nested_uris) =
+                render_embeds(storage, graph, &section, &chain, depth - 1).await?;
+
+            let html = format!(
+                "<div class=\"embed\" data-source=\"memory:{}\">\n{}</div>\n",
+                escape_attribute(&uri),
+                render_html_with_embeds(&section, graph, &nested)
+            );
+            embeds.insert(target, html);
+            for uri in std::iter::once(uri).chain(nested_uris) {
+                if !embedded.contains(&uri) {
+                    embedded.push(uri);
+                }
+            }
+        }
+
+        Ok((embeds, embedded))
+    })
+}
+
+/// Execute the RenderNote tool.
+///
+/// Returns the note rendered to HTML with links resolved, embeds inlined up
+/// to `embed_depth` levels, and frontmatter as a table.
+pub async fn execute<S: Storage>(
+    storage: &S,
+    graph: &GraphIndex,
+    note: &str,
+    embed_depth: usize,
+) -> Result<CallToolResult, ErrorData> {
+    let (uri, exists) = resolve_note_uri(storage, graph, note).await.map_err(|e| {
+        ErrorData::internal_error(format!("Failed to resolve note: {}", e), None)
+    })?;
+
+    if !exists {
+        return Err(ErrorData::invalid_params(
+            format!("Note not found: {}", note),
+            None,
+        ));
+    }
+
+    let (content, _metadata) = storage.read(&uri).await.map_err(|e| match e {
+        StorageError::NotFound { .. } => {
+            ErrorData::internal_error("Note was deleted during read", None)
+        }
+        _ => ErrorData::internal_error(format!("Failed to read note: {}", e), None),
+    })?;
+
+    let parsed = parse_frontmatter(&content);
+    let own_path = ensure_markdown_extension(&uri);
+    let (embeds, embedded) =
+        render_embeds(storage, graph, parsed.content, &[own_path], embed_depth).await?;
+
+    let mut html = match &parsed.frontmatter {
+        Some(frontmatter) if !frontmatter.is_empty() => render_frontmatter(frontmatter),
+        _ => String::new(),
+    };
+    html.push_str(&render_html_with_embeds(parsed.content, graph, &embeds));
+
+    let response = RenderNoteResponse { uri, html, embedded };
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use serde::Deserialize;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    #[derive(Deserialize)]
+    struct TestResponse {
+        html: String,
+        embedded: Vec<String>,
+    }
+
+    /// Write notes at `files` (path without `.md`, content) and index them.
+    async fn create_test_env(files: &[(&str, &str)]) -> (TempDir, FileStorage, GraphIndex) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
+        let mut graph = GraphIndex::new();
+        for (uri, content) in files {
+            let path = temp_dir.path().join(format!("{}.md", uri));
+            fs::create_dir_all(path.parent().unwrap()).await.unwrap();
+            fs::write(&path, content).await.unwrap();
+            let name = uri.rsplit('/').next().unwrap();
+            graph.update_note(name, PathBuf::from(format!("{}.md", uri)), HashSet::new());
+        }
+        (temp_dir, storage, graph)
+    }
+
+    async fn render(storage: &FileStorage, graph: &GraphIndex, note: &str) -> TestResponse {
+        render_with_depth(storage, graph, note, crate::config::DEFAULT_EMBED_DEPTH).await
+    }
+
+    async fn render_with_depth(
+        storage: &FileStorage,
+        graph: &GraphIndex,
+        note: &str,
+        depth: usize,
+    ) -> TestResponse {
+        let result = execute(storage, graph, note, depth).await.expect("should succeed");
+        let text = result.content[0]
+            .raw
+            .as_text()
+            .expect("Expected text")
+            .text
+            .clone();
+        serde_json::from_str(&text).expect("Expected valid JSON")
+    }
+
+    #[tokio::test]
+    async fn test_render_inlines_embed_and_resolves_links() {
+        let (_temp_dir, storage, graph) = create_test_env(&[
+            (
+                "Main",
+                "---\ntags: [rust, wasm]\nstatus: draft\n---\n# Main\n\n![[Embedded]]\n\nSee [[Linked]].",
+            ),
+            ("knowledge/Embedded", "---\ntitle: hidden\n---\nEmbedded **body** with [[Linked]]."),
+            ("knowledge/Linked", "Linked note"),
+        ])
+        .await;
+
+        let response = render(&storage, &graph, "Main").await;
+        let html = &response.html;
+
+        assert!(
+            html.starts_with(
+                "<table class=\"frontmatter\">\n<tbody>\n\
+                 <tr><th>status</th><td>draft</td></tr>\n\
+                 <tr><th>tags</th><td>rust, wasm</td></tr>\n"
+            ),
+            "{}",
+            html
+        );
+        assert!(
+            html.contains(
+                "<div class=\"embed\" data-source=\"memory:knowledge/Embedded\">\n\
+                 <p>Embedded <strong>body</strong> with \
+                 <a class=\"wiki-link\" href=\"memory:knowledge/Linked\">Linked</a>.</p>\n</div>"
+            ),
+            "{}",
+            html
+        );
+        assert!(
+            html.contains(r#"See <a class="wiki-link" href="memory:knowledge/Linked">Linked</a>."#),
+            "{}",
+            html
+        );
+        // The embed replaces its paragraph rather than nesting inside it
+        assert!(html.contains("<h1>Main</h1>\n<div class=\"embed\""), "{}", html);
+        // The embedded note's frontmatter isn't rendered
+        assert!(!html.contains("hidden"), "{}", html);
+        assert_eq!(response.embedded, vec!["knowledge/Embedded"]);
+    }
+
+    #[tokio::test]
+    async fn test_render_embeds_heading_section() {
+        let (_temp_dir, storage, graph) = create_test_env(&[
+            ("Main", "![[Guide#Setup: Linux]]"),
+            ("Guide", "# Guide\n\nIntro\n\n## Setup Linux\n\nInstall it.\n\n### Notes\n\nDetail\n\n## Usage\n\nRun it."),
+        ])
+        .await;
+
+        let html = render(&storage, &graph, "Main").await.html;
+
+        assert!(html.contains("<h2>Setup Linux</h2>\n<p>Install it.</p>"), "{}", html);
+        assert!(html.contains("<h3>Notes</h3>"), "{}", html);
+        assert!(!html.contains("Intro"), "{}", html);
+        assert!(!html.contains("Usage"), "{}", html);
+    }
+
+    #[tokio::test]
+    async fn test_render_embeds_heading_chain_section() {
+        let (_temp_dir, storage, graph) = create_test_env(&[
+            ("Main", "![[Guide#Usage#Notes]]"),
+            ("Guide", "# Guide\n\n## Setup\n\n### Notes\n\nSetup detail\n\n## Usage\n\n### Notes\n\nUsage detail\n\n## Other\n\nMore"),
+        ])
+        .await;
+
+        let html = render(&storage, &graph, "Main").await.html;
+
+        assert!(html.contains("<h3>Notes</h3>\n<p>Usage detail</p>"), "{}", html);
+        assert!(!html.contains("Setup detail"), "{}", html);
+        assert!(!html.contains("More"), "{}", html);
+    }
+
+    #[tokio::test]
+    async fn test_render_does_not_follow_embed_cycles() {
+        let (_temp_dir, storage, graph) = create_test_env(&[
+            ("A", "A body\n\n![[B]]\n\n![[A]]"),
+            ("B", "B body\n\n![[A]]"),
+        ])
+        .await;
+
+        let response = render(&storage, &graph, "A").await;
+        let html = &response.html;
+
+        // B is inlined once; its embed of A and A's embed of itself are links
+        assert_eq!(html.matches("A body").count(), 1, "{}", html);
+        assert_eq!(html.matches("B body").count(), 1, "{}", html);
+        assert_eq!(
+            html.matches(r#"<a class="wiki-link" href="memory:A">A</a>"#).count(),
+            2,
+            "{}",
+            html
+        );
+        assert_eq!(response.embedded, vec!["B"]);
+    }
+
+    #[tokio::test]
+    async fn test_render_embed_depth_expands_nested_embeds() {
+        let (_temp_dir, storage, graph) = create_test_env(&[
+            ("A", "A body\n\n![[B]]"),
+            ("B", "B body\n\n![[C]]"),
+            ("C", "C body\n\n![[D]]"),
+            ("D", "D body"),
+        ])
+        .await;
+
+        let shallow = render(&storage, &graph, "A").await;
+        assert!(shallow.html.contains("B body"), "{}", shallow.html);
+        assert!(!shallow.html.contains("C body"), "{}", shallow.html);
+        assert!(shallow.html.contains(r#"href="memory:C""#), "{}", shallow.html);
+        assert_eq!(shallow.embedded, vec!["B"]);
+
+        let deeper = render_with_depth(&storage, &graph, "A", 2).await;
+        assert!(deeper.html.contains("C body"), "{}", deeper.html);
+        assert!(!deeper.html.contains("D body"), "{}", deeper.html);
+        assert!(
+            deeper.html.contains(
+                "<div class=\"embed\" data-source=\"memory:B\">\n<p>B body</p>\n\
+                 <div class=\"embed\" data-source=\"memory:C\">"
+            ),
+            "{}",
+            deeper.html
+        );
+        assert_eq!(deeper.embedded, vec!["B", "C"]);
+    }
+
+    #[tokio::test]
+    async fn test_render_deep_embed_cycle_stops() {
+        let (_temp_dir, storage, graph) = create_test_env(&[
+            ("A", "A body\n\n![[B]]"),
+            ("B", "B body\n\n![[C]]"),
+            ("C", "C body\n\n![[A]]\n\n![[B]]"),
+        ])
+        .await;
+
+        // A generous depth still inlines each note in the chain only once
+        let response = render_with_depth(&storage, &graph, "A", 50).await;
+        let html = &response.html;
+        assert_eq!(html.matches("A body").count(), 1, "{}", html);
+        assert_eq!(html.matches("B body").count(), 1, "{}", html);
+        assert_eq!(html.matches("C body").count(), 1, "{}", html);
+        assert_eq!(response.embedded, vec!["B", "C"]);
+    }
+
+    #[tokio::test]
+    async fn test_render_leaves_missing_embed_dangling() {
+        let (_temp_dir, storage, graph) = create_test_env(&[("Main", "![[Missing]]")]).await;
+
+        let html = render(&storage, &graph, "Main").await.html;
+
+        assert!(
+            html.contains(r#"<span class="wiki-link dangling" data-target="Missing">Missing</span>"#),
+            "{}",
+            html
+        );
+        assert!(!html.contains("frontmatter"), "{}", html);
+    }
+}
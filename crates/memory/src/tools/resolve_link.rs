@@ -0,0 +1,139 @@
+//! ResolveLink tool - find which note a wiki-link points to.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde::Serialize;
+
+use super::common::{resolve_link_target, LinkResolution};
+use crate::graph::GraphIndex;
+use crate::storage::Storage;
+
+/// Response from ResolveLink tool.
+#[derive(Serialize)]
+pub struct ResolveLinkResponse {
+    /// "resolved", "ambiguous", or "not_found"
+    pub status: &'static str,
+    /// The memory URI of the resolved note
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    /// The file path relative to vault
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Memory URIs of every matching note, when ambiguous
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<String>,
+}
+
+/// Execute the ResolveLink tool.
+///
+/// Ambiguous links list every candidate instead of picking one, so the agent
+/// can ask or use a folder-qualified link.
+pub async fn execute<S: Storage>(
+    storage: &S,
+    graph: &GraphIndex,
+    link: &str,
+) -> Result<CallToolResult, ErrorData> {
+    let resolution = resolve_link_target(storage, graph, link).await.map_err(|e| {
+        ErrorData::internal_error(format!("Failed to resolve link: {}", e), None)
+    })?;
+
+    let response = match resolution {
+        LinkResolution::Unique(path) => ResolveLinkResponse {
+            status: "resolved",
+            uri: Some(format!("memory:{}", path)),
+            path: Some(format!("{}.md", path)),
+            candidates: Vec::new(),
+        },
+        LinkResolution::Ambiguous(paths) => ResolveLinkResponse {
+            status: "ambiguous",
+            uri: None,
+            path: None,
+            candidates: paths.iter().map(|p| format!("memory:{}", p)).collect(),
+        },
+        LinkResolution::NotFound => ResolveLinkResponse {
+            status: "not_found",
+            uri: None,
+            path: None,
+            candidates: Vec::new(),
+        },
+    };
+
+    let json = serde_json::to_string(&response)
+        .map_err(|e| ErrorData::internal_error(format!("Failed to serialize response: {}", e), None))?;
+
+    Ok(CallToolResult::success(vec![Content::text(json)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    async fn create_test_env() -> (TempDir, FileStorage, GraphIndex) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf());
+        let graph = GraphIndex::new();
+        (temp_dir, storage, graph)
+    }
+
+    async fn add_note(temp_dir: &TempDir, graph: &mut GraphIndex, path: &str) {
+        let full_path = temp_dir.path().join(path);
+        fs::create_dir_all(full_path.parent().unwrap()).await.unwrap();
+        fs::write(&full_path, "content").await.unwrap();
+        let name = path.rsplit('/').next().unwrap().strip_suffix(".md").unwrap();
+        graph.update_note(name, PathBuf::from(path), HashSet::new());
+    }
+
+    async fn resolve(storage: &FileStorage, graph: &GraphIndex, link: &str) -> serde_json::Value {
+        let result = execute(storage, graph, link).await.expect("should succeed");
+        let text = &result.content[0].raw.as_text().expect("Expected text").text;
+        serde_json::from_str(text).expect("Expected valid JSON")
+    }
+
+    #[tokio::test]
+    async fn test_resolves_unique_link() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        add_note(&temp_dir, &mut graph, "knowledge/Rust.md").await;
+
+        let json = resolve(&storage, &graph, "[[Rust#Ownership|the book]]").await;
+        assert_eq!(json["status"], "resolved");
+        assert_eq!(json["uri"], "memory:knowledge/Rust");
+        assert_eq!(json["path"], "knowledge/Rust.md");
+        assert!(json.get("candidates").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lists_candidates_when_ambiguous() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        add_note(&temp_dir, &mut graph, "projects/Notes.md").await;
+        add_note(&temp_dir, &mut graph, "knowledge/Notes.md").await;
+
+        let json = resolve(&storage, &graph, "[[Notes]]").await;
+        assert_eq!(json["status"], "ambiguous");
+        assert_eq!(
+            json["candidates"],
+            serde_json::json!(["memory:knowledge/Notes", "memory:projects/Notes"])
+        );
+
+        // A folder-qualified link picks one
+        let json = resolve(&storage, &graph, "[[projects/Notes]]").await;
+        assert_eq!(json["status"], "resolved");
+        assert_eq!(json["uri"], "memory:projects/Notes");
+    }
+
+    #[tokio::test]
+    async fn test_reports_not_found() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+        add_note(&temp_dir, &mut graph, "knowledge/Rust.md").await;
+
+        let json = resolve(&storage, &graph, "[[Missing]]").await;
+        assert_eq!(json["status"], "not_found");
+        assert!(json.get("uri").is_none());
+
+        let json = resolve(&storage, &graph, "memory:projects/Rust").await;
+        assert_eq!(json["status"], "not_found");
+    }
+}
@@ -4,11 +4,13 @@ use anyhow::Result;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use rmcp::model::{CallToolResult, Content, ErrorData};
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::Arc;
 use tokio::fs;
 
 use crate::embeddings::EmbeddingManager;
-use crate::graph::GraphIndex;
+use crate::graph::{GraphIndex, SeedScores};
 use semantic_embeddings::{Embedding, EMBEDDING_DIM};
 
 /// Regex for extracting [[wiki-links]] from query text
@@ -25,6 +27,10 @@ static WHITESPACE_RE: Lazy<Regex> = Lazy::new(|| {
 const TOP_K: usize = 10;
 const MIN_SIMILARITY: f32 = 0.3;
 
+/// Proximity of a note that shares an inline tag with a seed but isn't
+/// linked close to it. Tags are a weaker signal than links.
+const TAG_PROXIMITY: f64 = 0.1;
+
 /// Search result with scores
 #[derive(Debug)]
 struct SearchResult {
@@ -35,6 +41,14 @@ struct SearchResult {
     final_score: f32,
 }
 
+/// Graph proximity data for a single seed note, computed once per query.
+struct SeedProximity {
+    /// Cached Personalized PageRank scores within the seed's neighborhood
+    scores: Arc<SeedScores>,
+    /// Names of notes sharing an inline tag with the seed
+    tagged: HashSet<String>,
+}
+
 /// Execute the Search tool.
 pub async fn execute(
     vault_path: &Path,
@@ -78,6 +92,9 @@ pub async fn execute(
         .await
         .map_err(|e| ErrorData::internal_error(format!("Failed to compute embeddings: {}", e), None))?;
 
+    // Graph data for each seed is the same for every candidate note
+    let seeds = build_seed_proximity(graph, &note_refs);
+
     // Compute semantic similarity scores
     let mut results: Vec<SearchResult> = Vec::new();
     for (path, embedding) in &note_embeddings {
@@ -95,14 +112,13 @@ pub async fn execute(
             .to_string();
 
         // Compute graph proximity boost if we have note references
-        let graph_score = if !note_refs.is_empty() {
-            compute_graph_proximity(graph, &note_refs, &note_name)
+        let graph_score = if !seeds.is_empty() {
+            compute_graph_proximity(&seeds, &note_name)
         } else {
             0.0
         };
 
-        // Apply multiplicative boost, capped at 100%
-        let final_score = (semantic_score * (1.0 + graph_score)).min(1.0);
+        let final_score = boosted_score(semantic_score, graph_score);
 
         results.push(SearchResult {
             note_name,
@@ -113,11 +129,7 @@ pub async fn execute(
         });
     }
 
-    // Sort by final score descending
-    results.sort_by(|a, b| b.final_score.partial_cmp(&a.final_score).unwrap());
-
-    // Trim to top K
-    results.truncate(TOP_K);
+    rank_results(&mut results);
 
     tracing::info!(
         results = results.len(),
@@ -131,6 +143,25 @@ pub async fn execute(
     Ok(CallToolResult::success(vec![Content::text(output)]))
 }
 
+/// Apply a multiplicative graph boost to a semantic score, capped at 100%.
+fn boosted_score(semantic_score: f32, graph_score: f32) -> f32 {
+    (semantic_score * (1.0 + graph_score)).min(1.0)
+}
+
+/// Sort results by final score (descending) and keep the top K.
+///
+/// Equal scores are ordered by path, so the same vault and query always give
+/// the same ranking.
+fn rank_results(results: &mut Vec<SearchResult>) {
+    results.sort_by(|a, b| {
+        b.final_score
+            .partial_cmp(&a.final_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    results.truncate(TOP_K);
+}
+
 /// Parse wiki-links from query string.
 fn parse_query(query: &str) -> (Vec<String>, String) {
     let mut note_refs = Vec::new();
@@ -200,7 +231,7 @@ async fn build_query_embedding(
     let mut count = 0;
 
     for text in texts {
-        let embedding = embeddings.get_embedding("__query__", &text).await?;
+        let embedding = embeddings.get_query_embedding(&text).await?;
         for (i, val) in embedding.iter().enumerate() {
             combined[i] += val;
         }
@@ -216,8 +247,10 @@ async fn build_query_embedding(
     Ok(combined)
 }
 
-/// Get all markdown notes in the vault.
-async fn get_all_notes(
+/// Get all markdown notes in the vault that are eligible for search.
+///
+/// Notes that opt out via frontmatter are skipped, so they are never embedded.
+pub(crate) async fn get_all_notes(
     vault_path: &Path,
     graph: &GraphIndex,
     include_private: bool,
@@ -226,7 +259,11 @@ async fn get_all_notes(
 
     for rel_path in graph.all_paths() {
         // Skip private notes unless requested
-        if !include_private && rel_path.starts_with("private/") {
+        if !include_private && graph.is_private(rel_path) {
+            continue;
+        }
+
+        if graph.is_excluded_from_search(rel_path) {
             continue;
         }
 
@@ -239,28 +276,58 @@ async fn get_all_notes(
     notes
 }
 
+/// Collect proximity data for each seed note referenced in the query.
+fn build_seed_proximity(graph: &GraphIndex, seeds: &[String]) -> Vec<SeedProximity> {
+    seeds
+        .iter()
+        .map(|seed| {
+            let tagged = graph
+                .get_paths_for_name(seed)
+                .into_iter()
+                .flatten()
+                .flat_map(|path| graph.tag_neighbors(path))
+                .filter_map(|path| {
+                    Path::new(&path).file_stem().and_then(|s| s.to_str()).map(String::from)
+                })
+                .collect();
+
+            SeedProximity {
+                scores: graph.cached_seed_scores(seed),
+                tagged,
+            }
+        })
+        .collect()
+}
+
 /// Compute graph proximity score using Personalized PageRank.
 ///
 /// For single seed: returns PageRank score from that seed.
-/// For multiple seeds: computes PageRank from each seed and multiplies scores
-/// (intersection - note must be close to ALL seeds).
-fn compute_graph_proximity(graph: &GraphIndex, seeds: &[String], target: &str) -> f32 {
-    use crate::graph::pagerank::personalized_pagerank;
-    
+/// For multiple seeds: multiplies the per-seed scores (intersection - note
+/// must be close to ALL seeds).
+///
+/// Notes more than `NEIGHBORHOOD_RADIUS` hops from a seed score zero for that
+/// seed, even though PageRank reaches them. The cutoff keeps the boost for
+/// notes closely linked to the seed, and keeps the cached seed scores bounded
+/// by the neighborhood size. Notes sharing an inline tag with a seed score at
+/// least `TAG_PROXIMITY` for it, wherever they are in the link graph.
+fn compute_graph_proximity(seeds: &[SeedProximity], target: &str) -> f32 {
     if seeds.is_empty() {
         return 0.0;
     }
 
     let mut combined_score = 1.0;
-    
+
     for seed in seeds {
-        let scores = personalized_pagerank(graph, seed);
-        let score = scores.get(target).copied().unwrap_or(0.0) as f32;
-        
+        let linked = seed.scores.get(target).copied();
+        let tagged = seed.tagged.contains(target).then_some(TAG_PROXIMITY);
+        let Some(score) = linked.into_iter().chain(tagged).reduce(f64::max) else {
+            return 0.0;
+        };
+
         // Multiply scores for intersection (must be close to ALL seeds)
-        combined_score *= score;
+        combined_score *= score as f32;
     }
-    
+
     // Cap at 1.0 (100% boost)
     combined_score.min(1.0)
 }
@@ -383,4 +450,200 @@ mod tests {
         assert!(refs.is_empty());
         assert_eq!(remaining, "just plain text");
     }
+
+    #[tokio::test]
+    async fn test_get_all_notes_skips_excluded_notes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vault = temp_dir.path();
+        std::fs::write(vault.join("Visible.md"), "A searchable note").unwrap();
+        std::fs::write(vault.join("Hidden.md"), "---\nsearch: false\n---\nSecret").unwrap();
+        std::fs::write(vault.join("Unindexed.md"), "---\nnoindex: true\n---\nDraft").unwrap();
+
+        let mut graph = GraphIndex::new();
+        graph.initialize(vault).await.unwrap();
+
+        let notes = get_all_notes(vault, &graph, false).await;
+        let paths: Vec<&str> = notes.iter().map(|(path, _)| path.as_str()).collect();
+
+        // Only these notes are handed to the embedding manager
+        assert_eq!(paths, vec!["Visible.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_notes_skips_ignored_folders() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vault = temp_dir.path();
+        std::fs::create_dir_all(vault.join("templates")).unwrap();
+        std::fs::write(vault.join("Note.md"), "A real note").unwrap();
+        std::fs::write(vault.join("templates/Daily.md"), "A template").unwrap();
+
+        let mut graph = GraphIndex::new();
+        graph.set_ignore(crate::graph::GraphIgnore::new(["templates/"]));
+        graph.initialize(vault).await.unwrap();
+
+        let notes = get_all_notes(vault, &graph, true).await;
+        let paths: Vec<&str> = notes.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths, vec!["Note.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_notes_gates_custom_private_roots() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vault = temp_dir.path();
+        std::fs::create_dir_all(vault.join("personal")).unwrap();
+        std::fs::create_dir_all(vault.join("private")).unwrap();
+        std::fs::write(vault.join("Public.md"), "A public note").unwrap();
+        std::fs::write(vault.join("personal/Diary.md"), "A personal note").unwrap();
+        std::fs::write(vault.join("private/Plain.md"), "Not private here").unwrap();
+
+        let mut graph = GraphIndex::new();
+        graph.set_private_roots(obsidian_fs::PrivateRoots::new(["personal"]));
+        graph.initialize(vault).await.unwrap();
+
+        let paths = |notes: Vec<(String, String)>| {
+            let mut paths: Vec<String> = notes.into_iter().map(|(path, _)| path).collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(
+            paths(get_all_notes(vault, &graph, false).await),
+            vec!["Public.md", "private/Plain.md"]
+        );
+        assert_eq!(
+            paths(get_all_notes(vault, &graph, true).await),
+            vec!["Public.md", "personal/Diary.md", "private/Plain.md"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_all_notes_gates_private_visibility() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let vault = temp_dir.path();
+        std::fs::create_dir_all(vault.join("knowledge")).unwrap();
+        std::fs::write(vault.join("knowledge/Open.md"), "An open note").unwrap();
+        std::fs::write(
+            vault.join("knowledge/Salary.md"),
+            "---\nvisibility: private\n---\nA private note outside private/",
+        )
+        .unwrap();
+        std::fs::write(
+            vault.join("knowledge/Shared.md"),
+            "---\nvisibility: public\n---\nExplicitly public",
+        )
+        .unwrap();
+
+        let mut graph = GraphIndex::new();
+        graph.initialize(vault).await.unwrap();
+
+        let paths = |notes: Vec<(String, String)>| {
+            let mut paths: Vec<String> = notes.into_iter().map(|(path, _)| path).collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(
+            paths(get_all_notes(vault, &graph, false).await),
+            vec!["knowledge/Open.md", "knowledge/Shared.md"]
+        );
+        assert_eq!(
+            paths(get_all_notes(vault, &graph, true).await),
+            vec!["knowledge/Open.md", "knowledge/Salary.md", "knowledge/Shared.md"]
+        );
+    }
+
+    fn result(path: &str, final_score: f32) -> SearchResult {
+        SearchResult {
+            note_name: Path::new(path).file_stem().unwrap().to_string_lossy().to_string(),
+            path: path.to_string(),
+            semantic_score: final_score,
+            graph_score: 0.0,
+            final_score,
+        }
+    }
+
+    #[test]
+    fn test_graph_proximity_cuts_off_outside_neighborhood() {
+        use std::path::PathBuf;
+
+        // A - B - C - D, so D is three hops from A
+        let mut graph = GraphIndex::new();
+        for (name, link) in [("A", Some("B")), ("B", Some("C")), ("C", Some("D")), ("D", None)] {
+            let links = link.map(String::from).into_iter().collect();
+            graph.update_note(name, PathBuf::from(format!("{}.md", name)), links);
+        }
+        let pagerank = crate::graph::pagerank::personalized_pagerank(&graph, "A");
+        assert!(pagerank["D"] > 0.0);
+
+        let seeds = build_seed_proximity(&graph, &["A".to_string()]);
+        assert!(compute_graph_proximity(&seeds, "C") > 0.0);
+        assert_eq!(compute_graph_proximity(&seeds, "D"), 0.0);
+
+        // With several seeds, a note must be within reach of all of them
+        let seeds = build_seed_proximity(&graph, &["A".to_string(), "D".to_string()]);
+        assert!(compute_graph_proximity(&seeds, "B") > 0.0);
+        assert_eq!(compute_graph_proximity(&seeds, "A"), 0.0);
+    }
+
+    #[test]
+    fn test_shared_tags_boost_ranking() {
+        use std::path::PathBuf;
+
+        // Nothing links Topic to Tagged or Plain, but Topic and Tagged share a tag
+        let mut graph = GraphIndex::new();
+        for name in ["Topic", "Tagged", "Plain"] {
+            graph.update_note(name, PathBuf::from(format!("{}.md", name)), Default::default());
+        }
+        graph.set_tags("Topic.md", HashSet::from(["project/active".to_string()]));
+        graph.set_tags("Tagged.md", HashSet::from(["Project/Active".to_string()]));
+
+        let seeds = build_seed_proximity(&graph, &["Topic".to_string()]);
+        assert_eq!(compute_graph_proximity(&seeds, "Tagged"), TAG_PROXIMITY as f32);
+        assert_eq!(compute_graph_proximity(&seeds, "Plain"), 0.0);
+
+        let mut results: Vec<SearchResult> = ["Plain.md", "Tagged.md"]
+            .into_iter()
+            .map(|path| {
+                let mut result = result(path, 0.5);
+                result.graph_score = compute_graph_proximity(&seeds, &result.note_name);
+                result.final_score = boosted_score(result.semantic_score, result.graph_score);
+                result
+            })
+            .collect();
+        rank_results(&mut results);
+
+        let paths: Vec<&str> = results.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["Tagged.md", "Plain.md"]);
+    }
+
+    #[test]
+    fn test_linked_score_outweighs_tag_boost() {
+        use std::path::PathBuf;
+
+        let mut graph = GraphIndex::new();
+        graph.update_note("Topic", PathBuf::from("Topic.md"), HashSet::from(["Linked".to_string()]));
+        graph.update_note("Linked", PathBuf::from("Linked.md"), Default::default());
+        graph.set_tags("Topic.md", HashSet::from(["rust".to_string()]));
+        graph.set_tags("Linked.md", HashSet::from(["rust".to_string()]));
+
+        let seeds = build_seed_proximity(&graph, &["Topic".to_string()]);
+        assert!(compute_graph_proximity(&seeds, "Linked") > TAG_PROXIMITY as f32);
+    }
+
+    #[test]
+    fn test_rank_results_breaks_ties_by_path() {
+        let mut results = vec![
+            result("knowledge/Zebra.md", 0.8),
+            result("knowledge/Apple.md", 0.8),
+            result("Top.md", 0.9),
+        ];
+        rank_results(&mut results);
+
+        let paths: Vec<String> = results.iter().map(|r| r.path.clone()).collect();
+        assert_eq!(paths, vec!["Top.md", "knowledge/Apple.md", "knowledge/Zebra.md"]);
+
+        // Input order doesn't matter
+        results.reverse();
+        rank_results(&mut results);
+        let again: Vec<String> = results.iter().map(|r| r.path.clone()).collect();
+        assert_eq!(again, paths);
+    }
 }
@@ -0,0 +1,220 @@
+//! SimilarToNote tool - find notes semantically similar to an existing note.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+use std::path::Path;
+
+use super::common::resolve_note_uri;
+use super::search::get_all_notes;
+use crate::embeddings::EmbeddingManager;
+use crate::graph::GraphIndex;
+use crate::storage::Storage;
+
+/// Hardcoded similarity parameters (mirrors the Search tool)
+const TOP_K: usize = 10;
+const MIN_SIMILARITY: f32 = 0.3;
+
+/// A note ranked by similarity to the target note
+#[derive(Debug, PartialEq)]
+struct SimilarNote {
+    note_name: String,
+    path: String,
+    score: f32,
+}
+
+/// Execute the SimilarToNote tool.
+///
+/// Uses the target note's cached embedding as the query, so no extra encoding
+/// is needed once embeddings have been preloaded.
+pub async fn execute<S: Storage>(
+    storage: &S,
+    vault_path: &Path,
+    graph: &GraphIndex,
+    embeddings: &EmbeddingManager,
+    note: &str,
+    include_private: bool,
+) -> Result<CallToolResult, ErrorData> {
+    let (uri, exists) = resolve_note_uri(storage, graph, note).await.map_err(|e| {
+        ErrorData::internal_error(format!("Failed to resolve note: {}", e), None)
+    })?;
+
+    if !exists {
+        return Err(ErrorData::invalid_params(
+            format!("Note not found: {}", note),
+            None,
+        ));
+    }
+
+    let target_path = format!("{}.md", uri);
+    let mut notes = get_all_notes(vault_path, graph, include_private).await;
+
+    // The target may itself be private or excluded from search; it still
+    // needs an embedding to act as the query.
+    if !notes.iter().any(|(path, _)| *path == target_path) {
+        let (content, _metadata) = storage.read(&uri).await.map_err(|e| {
+            ErrorData::internal_error(format!("Failed to read note: {}", e), None)
+        })?;
+        notes.push((target_path.clone(), content));
+    }
+
+    let note_embeddings = embeddings
+        .get_embeddings_batch(&notes)
+        .await
+        .map_err(|e| ErrorData::internal_error(format!("Failed to compute embeddings: {}", e), None))?;
+
+    let target_embedding = note_embeddings
+        .iter()
+        .find(|(path, _)| *path == target_path)
+        .map(|(_, embedding)| embedding.clone())
+        .ok_or_else(|| ErrorData::internal_error("Missing embedding for target note", None))?;
+
+    // Everything else came from get_all_notes, so it is already eligible for search
+    let results = rank_similar(&target_path, &target_embedding, &note_embeddings);
+
+    tracing::info!(
+        note = %target_path,
+        results = results.len(),
+        "Similar notes search complete"
+    );
+
+    Ok(CallToolResult::success(vec![Content::text(format_results(
+        &target_path,
+        &results,
+    ))]))
+}
+
+/// Rank candidate notes by cosine similarity to the target embedding.
+///
+/// The target itself is never included. Results are sorted by descending
+/// score (ties by path), filtered by `MIN_SIMILARITY`, and capped at `TOP_K`.
+fn rank_similar(
+    target_path: &str,
+    target_embedding: &[f32],
+    candidates: &[(String, Vec<f32>)],
+) -> Vec<SimilarNote> {
+    let mut results: Vec<SimilarNote> = candidates
+        .iter()
+        .filter(|(path, _)| path != target_path)
+        .filter_map(|(path, embedding)| {
+            let score = EmbeddingManager::cosine_similarity(target_embedding, embedding).ok()?;
+            if score < MIN_SIMILARITY {
+                return None;
+            }
+
+            let note_name = Path::new(path)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_string();
+
+            Some(SimilarNote {
+                note_name,
+                path: path.clone(),
+                score,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    results.truncate(TOP_K);
+    results
+}
+
+/// Format similar notes for output.
+fn format_results(target_path: &str, results: &[SimilarNote]) -> String {
+    let target_name = Path::new(target_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(target_path);
+
+    let mut output = format!("# Notes Similar to [[{}]]\n\n", target_name);
+
+    if results.is_empty() {
+        output.push_str("No similar notes found.\n");
+        return output;
+    }
+
+    output.push_str(&format!("Found {} similar notes:\n\n", results.len()));
+
+    for (i, result) in results.iter().enumerate() {
+        let percent = (result.score * 100.0) as i32;
+        output.push_str(&format!(
+            "{}. **[[{}]]** ({}% similar)\n",
+            i + 1,
+            result.note_name,
+            percent
+        ));
+        output.push_str(&format!("   - Path: `{}`\n", result.path));
+        output.push('\n');
+    }
+
+    output.push_str("*Use ReadNote to view note contents*\n");
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corpus() -> Vec<(String, Vec<f32>)> {
+        vec![
+            ("knowledge/Rust.md".to_string(), vec![1.0, 0.1, 0.0]),
+            ("knowledge/Cargo.md".to_string(), vec![0.9, 0.2, 0.0]),
+            ("knowledge/Borrowing.md".to_string(), vec![0.8, 0.0, 0.3]),
+            ("knowledge/Gardening.md".to_string(), vec![0.0, 0.0, 1.0]),
+        ]
+    }
+
+    #[test]
+    fn test_rank_similar_excludes_target() {
+        let corpus = corpus();
+        let results = rank_similar("knowledge/Rust.md", &corpus[0].1, &corpus);
+
+        assert!(results.iter().all(|r| r.path != "knowledge/Rust.md"));
+    }
+
+    #[test]
+    fn test_rank_similar_orders_expected_neighbors() {
+        let corpus = corpus();
+        let results = rank_similar("knowledge/Rust.md", &corpus[0].1, &corpus);
+
+        let names: Vec<&str> = results.iter().map(|r| r.note_name.as_str()).collect();
+        // Gardening is orthogonal and falls below the similarity threshold
+        assert_eq!(names, vec!["Cargo", "Borrowing"]);
+        assert!(results[0].score > results[1].score);
+    }
+
+    #[test]
+    fn test_rank_similar_caps_results() {
+        let candidates: Vec<(String, Vec<f32>)> = (0..TOP_K + 5)
+            .map(|i| (format!("Note {}.md", i), vec![1.0, i as f32 * 0.01]))
+            .collect();
+
+        let results = rank_similar("Target.md", &[1.0, 0.0], &candidates);
+        assert_eq!(results.len(), TOP_K);
+    }
+
+    #[test]
+    fn test_rank_similar_breaks_ties_by_path() {
+        let candidates = vec![
+            ("b/Twin.md".to_string(), vec![1.0, 1.0]),
+            ("a/Twin.md".to_string(), vec![1.0, 1.0]),
+        ];
+
+        let results = rank_similar("Target.md", &[1.0, 0.0], &candidates);
+        let paths: Vec<&str> = results.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["a/Twin.md", "b/Twin.md"]);
+    }
+
+    #[test]
+    fn test_format_results_empty() {
+        let output = format_results("knowledge/Rust.md", &[]);
+        assert!(output.contains("# Notes Similar to [[Rust]]"));
+        assert!(output.contains("No similar notes found."));
+    }
+}
@@ -0,0 +1,110 @@
+//! UndoLastChange tool - revert the session's most recent note change.
+
+use rmcp::model::{CallToolResult, Content, ErrorData};
+
+use crate::storage::{JournaledStorage, Storage, StorageError};
+
+/// Execute the UndoLastChange tool.
+///
+/// Reverts the last WriteNote, EditNote, DeleteNote, MoveNote (or other
+/// mutating tool call) made in this session. Refuses if an affected note has
+/// been changed since, so edits made elsewhere are never overwritten.
+pub async fn execute<S: Storage>(storage: &JournaledStorage<S>) -> Result<CallToolResult, ErrorData> {
+    let undone = storage.undo_last().await.map_err(|e| match e {
+        StorageError::HashMismatch { uri, .. } => ErrorData::invalid_params(
+            format!(
+                "Cannot undo: {} has been modified since the last change. \
+                 Use ReadNote to review it and edit it directly instead.",
+                uri
+            ),
+            None,
+        ),
+        StorageError::AlreadyExists { uri } => ErrorData::invalid_params(
+            format!("Cannot undo: a note now exists at {}", uri),
+            None,
+        ),
+        StorageError::NotFound { uri } => ErrorData::invalid_params(
+            format!("Cannot undo: {} no longer exists", uri),
+            None,
+        ),
+        _ => ErrorData::internal_error(format!("Failed to undo change: {}", e), None),
+    })?;
+
+    let Some(undone) = undone else {
+        return Ok(CallToolResult::success(vec![Content::text(
+            "Nothing to undo in this session.",
+        )]));
+    };
+
+    let mut text = format!("Undid {}. Restored:\n", undone.label);
+    for uri in &undone.restored {
+        text.push_str(&format!("- memory:{}\n", uri));
+    }
+
+    Ok(CallToolResult::success(vec![Content::text(text)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FileStorage;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    fn create_test_storage() -> (TempDir, JournaledStorage<FileStorage>) {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = JournaledStorage::new(Arc::new(FileStorage::new(temp_dir.path().to_path_buf())));
+        (temp_dir, storage)
+    }
+
+    fn text(result: &CallToolResult) -> &str {
+        &result.content[0].raw.as_text().expect("Expected text").text
+    }
+
+    #[tokio::test]
+    async fn test_undo_delete_note_restores_file() {
+        let (temp_dir, storage) = create_test_storage();
+        fs::write(temp_dir.path().join("test.md"), "Content").await.unwrap();
+
+        storage.begin_operation("delete_note");
+        crate::tools::delete_note::execute(
+            temp_dir.path(),
+            &storage,
+            &crate::graph::GraphIndex::new(),
+            "test",
+            false,
+        )
+        .await
+        .unwrap();
+
+        storage.begin_operation("undo_last_change");
+        let result = execute(&storage).await.unwrap();
+        assert!(text(&result).contains("Undid delete_note"));
+        assert!(text(&result).contains("memory:test"));
+
+        let content = fs::read_to_string(temp_dir.path().join("test.md")).await.unwrap();
+        assert_eq!(content, "Content");
+    }
+
+    #[tokio::test]
+    async fn test_nothing_to_undo() {
+        let (_temp_dir, storage) = create_test_storage();
+
+        storage.begin_operation("undo_last_change");
+        let result = execute(&storage).await.unwrap();
+        assert_eq!(text(&result), "Nothing to undo in this session.");
+    }
+
+    #[tokio::test]
+    async fn test_undo_refuses_after_external_edit() {
+        let (temp_dir, storage) = create_test_storage();
+
+        storage.begin_operation("write_note");
+        storage.write("test", "Written", None).await.unwrap();
+        fs::write(temp_dir.path().join("test.md"), "Edited in Obsidian").await.unwrap();
+
+        let err = execute(&storage).await.unwrap_err();
+        assert!(err.message.contains("has been modified"));
+    }
+}
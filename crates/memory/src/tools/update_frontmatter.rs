@@ -5,6 +5,7 @@ use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 
 use super::common::resolve_note_uri;
+use crate::frontmatter_schema::FrontmatterSchema;
 use crate::graph::GraphIndex;
 use crate::storage::{ContentHash, Storage, StorageError};
 
@@ -20,10 +21,12 @@ pub struct UpdateFrontmatterResponse {
 /// Update frontmatter in a note file.
 ///
 /// Reads the existing note, merges the frontmatter updates, and writes back.
-/// Requires content_hash from a previous ReadNote call.
+/// Requires content_hash from a previous ReadNote call. The merged frontmatter
+/// must satisfy the note folder's schema, if it has one.
 pub async fn execute<S: Storage>(
     storage: &S,
     graph: &GraphIndex,
+    schema: &FrontmatterSchema,
     note: &str,
     updates: HashMap<String, JsonValue>,
     content_hash: &str,
@@ -73,6 +76,10 @@ pub async fn execute<S: Storage>(
         merged.insert(key, value);
     }
 
+    schema
+        .validate(&uri, Some(&merged))
+        .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
+
     // Rebuild file content with updated frontmatter
     let new_content = build_note_with_frontmatter(&merged, content)
         .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
@@ -159,7 +166,7 @@ mod tests {
         updates.insert("type".to_string(), JsonValue::String("updated".to_string()));
         updates.insert("new_field".to_string(), JsonValue::Bool(true));
 
-        let result = execute(&storage, &graph, "test", updates, content_hash.as_str())
+        let result = execute(&storage, &graph, &FrontmatterSchema::default(), "test", updates, content_hash.as_str())
             .await
             .expect("should succeed");
 
@@ -186,7 +193,7 @@ mod tests {
         let mut updates = HashMap::new();
         updates.insert("type".to_string(), JsonValue::String("updated".to_string()));
 
-        let result = execute(&storage, &graph, "test", updates, "wrong_hash").await;
+        let result = execute(&storage, &graph, &FrontmatterSchema::default(), "test", updates, "wrong_hash").await;
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.message.contains("Note modified since last read"));
@@ -205,7 +212,7 @@ mod tests {
         let mut updates = HashMap::new();
         updates.insert("type".to_string(), JsonValue::String("new".to_string()));
 
-        execute(&storage, &graph, "test", updates, content_hash.as_str())
+        execute(&storage, &graph, &FrontmatterSchema::default(), "test", updates, content_hash.as_str())
             .await
             .expect("should succeed");
 
@@ -228,7 +235,7 @@ mod tests {
         let mut updates = HashMap::new();
         updates.insert("status".to_string(), JsonValue::String("active".to_string()));
 
-        let result = execute(&storage, &graph, "projects/MyProject", updates, content_hash.as_str())
+        let result = execute(&storage, &graph, &FrontmatterSchema::default(), "projects/MyProject", updates, content_hash.as_str())
             .await
             .expect("should succeed");
 
@@ -240,6 +247,45 @@ mod tests {
         assert!(updated.contains("type: project"));
     }
 
+    #[tokio::test]
+    async fn test_update_violating_schema_rejected() {
+        let (temp_dir, storage, mut graph) = create_test_env().await;
+
+        let initial_content = "---\nstatus: active\n---\n\nProject content";
+        create_test_note(temp_dir.path(), "projects/App.md", initial_content).await;
+        graph.update_note("App", PathBuf::from("projects/App.md"), HashSet::new());
+        let content_hash = ContentHash::from_content(initial_content);
+
+        let schema = FrontmatterSchema::from_json(
+            r#"{ "projects": { "status": { "type": "string", "required": true } } }"#,
+        )
+        .unwrap();
+
+        let mut updates = HashMap::new();
+        updates.insert("status".to_string(), JsonValue::Null);
+        let err = execute(&storage, &graph, &schema, "projects/App", updates, content_hash.as_str())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("missing required fields: status"), "{}", err.message);
+
+        let mut updates = HashMap::new();
+        updates.insert("status".to_string(), JsonValue::Bool(true));
+        let err = execute(&storage, &graph, &schema, "projects/App", updates, content_hash.as_str())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("status (expected string)"), "{}", err.message);
+
+        // Note is untouched
+        let content = fs::read_to_string(temp_dir.path().join("projects/App.md")).await.unwrap();
+        assert_eq!(content, initial_content);
+
+        let mut updates = HashMap::new();
+        updates.insert("status".to_string(), JsonValue::String("done".to_string()));
+        execute(&storage, &graph, &schema, "projects/App", updates, content_hash.as_str())
+            .await
+            .expect("should succeed");
+    }
+
     #[tokio::test]
     async fn test_nonexistent_file_returns_error() {
         let (_temp_dir, storage, graph) = create_test_env().await;
@@ -247,7 +293,7 @@ mod tests {
         let mut updates = HashMap::new();
         updates.insert("type".to_string(), JsonValue::String("test".to_string()));
 
-        let result = execute(&storage, &graph, "nonexistent", updates, "some_hash").await;
+        let result = execute(&storage, &graph, &FrontmatterSchema::default(), "nonexistent", updates, "some_hash").await;
         assert!(result.is_err());
     }
 }
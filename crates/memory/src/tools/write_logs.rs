@@ -4,19 +4,19 @@
 
 use rmcp::model::{CallToolResult, Content, ErrorData};
 use std::collections::HashMap;
-use std::path::Path;
-use tokio::fs;
 
+use super::compact_log::parse_iso_week_date;
 use super::log::get_day_abbreviation_from_iso;
+use crate::storage::{Storage, StorageError};
 
 /// Replace an entire day's log entries with new entries.
-pub async fn execute(
-    vault_path: &Path,
+pub async fn execute<S: Storage>(
+    storage: &S,
     iso_week_date: &str,
     entries: HashMap<String, String>,
 ) -> Result<CallToolResult, ErrorData> {
     // Validate ISO week date format
-    if !is_valid_iso_week_date(iso_week_date) {
+    if parse_iso_week_date(iso_week_date).is_none() {
         return Err(ErrorData::invalid_params(
             format!(
                 "Invalid ISO week date format: '{}'. Expected format: YYYY-Www-D (e.g., '2025-W50-1')",
@@ -44,14 +44,13 @@ pub async fn execute(
         ));
     }
 
-    let log_path = vault_path.join("Log.md");
     let day_abbrev = get_day_abbreviation_from_iso(iso_week_date);
     let day_header = format!("## {} ({})", iso_week_date, day_abbrev);
 
     // Read existing content
-    let log_content = match fs::read_to_string(&log_path).await {
-        Ok(content) => content,
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+    let (log_content, hash) = match storage.read("Log").await {
+        Ok((content, metadata)) => (content, Some(metadata.hash)),
+        Err(StorageError::NotFound { .. }) => (String::new(), None),
         Err(e) => {
             return Err(ErrorData::internal_error(
                 format!("Failed to read Log.md: {}", e),
@@ -84,7 +83,7 @@ pub async fn execute(
             cleanup_blank_lines(&mut lines);
 
             let content = lines.join("\n");
-            if let Err(e) = fs::write(&log_path, content).await {
+            if let Err(e) = storage.write("Log", &content, hash.as_deref()).await {
                 return Err(ErrorData::internal_error(
                     format!("Failed to write Log.md: {}", e),
                     None,
@@ -145,7 +144,7 @@ pub async fn execute(
     }
 
     let content = lines.join("\n");
-    if let Err(e) = fs::write(&log_path, content).await {
+    if let Err(e) = storage.write("Log", &content, hash.as_deref()).await {
         return Err(ErrorData::internal_error(
             format!("Failed to write Log.md: {}", e),
             None,
@@ -159,37 +158,6 @@ pub async fn execute(
     ))]))
 }
 
-/// Validate ISO week date format: YYYY-Www-D
-fn is_valid_iso_week_date(s: &str) -> bool {
-    // Check format: YYYY-Www-D
-    let parts: Vec<&str> = s.split('-').collect();
-    if parts.len() != 3 {
-        return false;
-    }
-
-    // Year: 4 digits
-    if parts[0].len() != 4 || parts[0].parse::<u32>().is_err() {
-        return false;
-    }
-
-    // Week: Www where w is 01-53
-    if !parts[1].starts_with('W') || parts[1].len() != 3 {
-        return false;
-    }
-    match parts[1][1..].parse::<u32>() {
-        Ok(w) if (1..=53).contains(&w) => {}
-        _ => return false,
-    };
-
-    // Day: 1-7
-    match parts[2].parse::<u32>() {
-        Ok(d) if (1..=7).contains(&d) => {}
-        _ => return false,
-    };
-
-    true
-}
-
 /// Parse 12-hour time format (e.g., "9:30 AM") to (hour24, minute)
 fn parse_time_12h(s: &str) -> Option<(u32, u32)> {
     let parts: Vec<&str> = s.split_whitespace().collect();
@@ -243,19 +211,21 @@ fn cleanup_blank_lines(lines: &mut Vec<String>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::storage::FileStorage;
     use tempfile::TempDir;
+    use tokio::fs;
 
     #[test]
-    fn test_is_valid_iso_week_date() {
-        assert!(is_valid_iso_week_date("2025-W50-1"));
-        assert!(is_valid_iso_week_date("2025-W01-7"));
-        assert!(is_valid_iso_week_date("2026-W52-3"));
-
-        assert!(!is_valid_iso_week_date("2025-50-1")); // Missing W
-        assert!(!is_valid_iso_week_date("2025-W54-1")); // Invalid week
-        assert!(!is_valid_iso_week_date("2025-W50-8")); // Invalid day
-        assert!(!is_valid_iso_week_date("2025-W50-0")); // Invalid day
-        assert!(!is_valid_iso_week_date("invalid"));
+    fn test_iso_week_date_validation() {
+        assert!(parse_iso_week_date("2025-W50-1").is_some());
+        assert!(parse_iso_week_date("2025-W01-7").is_some());
+        assert!(parse_iso_week_date("2026-W52-3").is_some());
+
+        assert!(parse_iso_week_date("2025-50-1").is_none()); // Missing W
+        assert!(parse_iso_week_date("2025-W54-1").is_none()); // Invalid week
+        assert!(parse_iso_week_date("2025-W50-8").is_none()); // Invalid day
+        assert!(parse_iso_week_date("2025-W50-0").is_none()); // Invalid day
+        assert!(parse_iso_week_date("invalid").is_none());
     }
 
     #[test]
@@ -276,6 +246,7 @@ mod tests {
     async fn test_write_logs_creates_new_section() {
         let temp_dir = TempDir::new().unwrap();
         let vault_path = temp_dir.path();
+        let storage = FileStorage::new(vault_path.to_path_buf());
         let log_path = vault_path.join("Log.md");
 
         // Create empty log file
@@ -285,7 +256,7 @@ mod tests {
         entries.insert("9:00 AM".to_string(), "Started work".to_string());
         entries.insert("2:30 PM".to_string(), "Finished task".to_string());
 
-        let result = execute(vault_path, "2025-W50-1", entries).await;
+        let result = execute(&storage, "2025-W50-1", entries).await;
         assert!(result.is_ok());
 
         let content = fs::read_to_string(&log_path).await.unwrap();
@@ -298,6 +269,7 @@ mod tests {
     async fn test_write_logs_replaces_existing_section() {
         let temp_dir = TempDir::new().unwrap();
         let vault_path = temp_dir.path();
+        let storage = FileStorage::new(vault_path.to_path_buf());
         let log_path = vault_path.join("Log.md");
 
         // Create log with existing content
@@ -307,7 +279,7 @@ mod tests {
         let mut entries = HashMap::new();
         entries.insert("10:00 AM".to_string(), "New entry".to_string());
 
-        let result = execute(vault_path, "2025-W50-1", entries).await;
+        let result = execute(&storage, "2025-W50-1", entries).await;
         assert!(result.is_ok());
 
         let content = fs::read_to_string(&log_path).await.unwrap();
@@ -319,6 +291,7 @@ mod tests {
     async fn test_write_logs_deletes_section_when_empty() {
         let temp_dir = TempDir::new().unwrap();
         let vault_path = temp_dir.path();
+        let storage = FileStorage::new(vault_path.to_path_buf());
         let log_path = vault_path.join("Log.md");
 
         // Create log with content
@@ -327,7 +300,7 @@ mod tests {
 
         let entries = HashMap::new(); // Empty = delete
 
-        let result = execute(vault_path, "2025-W50-1", entries).await;
+        let result = execute(&storage, "2025-W50-1", entries).await;
         assert!(result.is_ok());
 
         let content = fs::read_to_string(&log_path).await.unwrap();
@@ -338,9 +311,10 @@ mod tests {
     async fn test_write_logs_invalid_iso_date() {
         let temp_dir = TempDir::new().unwrap();
         let vault_path = temp_dir.path();
+        let storage = FileStorage::new(vault_path.to_path_buf());
 
         let entries = HashMap::new();
-        let result = execute(vault_path, "invalid", entries).await;
+        let result = execute(&storage, "invalid", entries).await;
 
         assert!(result.is_err());
     }
@@ -349,11 +323,12 @@ mod tests {
     async fn test_write_logs_invalid_time_format() {
         let temp_dir = TempDir::new().unwrap();
         let vault_path = temp_dir.path();
+        let storage = FileStorage::new(vault_path.to_path_buf());
 
         let mut entries = HashMap::new();
         entries.insert("invalid".to_string(), "Message".to_string());
 
-        let result = execute(vault_path, "2025-W50-1", entries).await;
+        let result = execute(&storage, "2025-W50-1", entries).await;
         assert!(result.is_err());
     }
 
@@ -361,6 +336,7 @@ mod tests {
     async fn test_write_logs_sorts_chronologically() {
         let temp_dir = TempDir::new().unwrap();
         let vault_path = temp_dir.path();
+        let storage = FileStorage::new(vault_path.to_path_buf());
         let log_path = vault_path.join("Log.md");
 
         fs::write(&log_path, "").await.unwrap();
@@ -370,7 +346,7 @@ mod tests {
         entries.insert("9:00 AM".to_string(), "First".to_string());
         entries.insert("12:00 PM".to_string(), "Second".to_string());
 
-        execute(vault_path, "2025-W50-1", entries).await.unwrap();
+        execute(&storage, "2025-W50-1", entries).await.unwrap();
 
         let content = fs::read_to_string(&log_path).await.unwrap();
         let first_pos = content.find("First").unwrap();
@@ -1,11 +1,12 @@
 //! WriteNote tool - write note content with optimistic locking via content_hash.
 
-use obsidian_fs::ensure_markdown_extension;
+use obsidian_fs::{ensure_markdown_extension, parse_frontmatter};
 use rmcp::model::{CallToolResult, Content, ErrorData};
 use serde::Serialize;
 use std::path::Path;
 
-use super::common::resolve_note_uri;
+use super::common::{read_only_error, resolve_note_uri};
+use crate::frontmatter_schema::FrontmatterSchema;
 use crate::graph::GraphIndex;
 use crate::storage::{ContentHash, Storage, StorageError};
 
@@ -26,10 +27,12 @@ pub struct WriteNoteResponse {
 ///
 /// Creates new notes or overwrites existing ones.
 /// For existing notes, content_hash parameter should be provided (will be required in future).
+/// Content whose frontmatter violates the note folder's schema is rejected.
 pub async fn execute<S: Storage>(
     vault_path: &Path,
     storage: &S,
     graph: &GraphIndex,
+    schema: &FrontmatterSchema,
     note: &str,
     content: &str,
     content_hash: Option<&str>,
@@ -76,6 +79,12 @@ pub async fn execute<S: Storage>(
         ));
     }
 
+    // Enforce the folder's frontmatter schema, if it has one
+    let parsed = parse_frontmatter(content);
+    schema
+        .validate(&uri, parsed.frontmatter.as_ref())
+        .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
+
     // Attempt to write (pass hash for optimistic locking on existing files)
     storage.write(&uri, content, content_hash).await.map_err(|e| match e {
         StorageError::ParentNotFound { uri, parent } => ErrorData::invalid_params(
@@ -95,6 +104,7 @@ pub async fn execute<S: Storage>(
             ),
             None,
         ),
+        StorageError::ReadOnly { uri, folder } => read_only_error(&uri, &folder),
         _ => ErrorData::internal_error(format!("Failed to write note: {}", e), None),
     })?;
 
@@ -159,6 +169,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "test",
             "Hello, world!",
             None, // No hash for new file
@@ -194,6 +205,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "test",
             "New content",
             None,
@@ -225,6 +237,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "test",
             "Version 2",
             Some(correct_hash.as_str()),
@@ -257,6 +270,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "test",
             "New content",
             Some("wrong_hash"),
@@ -277,6 +291,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "test",
             "Version 1",
             None,
@@ -291,6 +306,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "test",
             "Version 2",
             Some(&response1.content_hash),
@@ -308,6 +324,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "test",
             "Version 3",
             Some(&response2.content_hash),
@@ -338,6 +355,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "knowledge/test",
             "Content",
             None,
@@ -361,6 +379,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "missing/parent/test",
             "Content",
             None,
@@ -381,6 +400,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "new_note",
             "Content",
             Some("some_hash"),
@@ -392,6 +412,75 @@ mod tests {
         assert!(err.message.contains("Note does not exist"));
     }
 
+    fn projects_schema() -> FrontmatterSchema {
+        FrontmatterSchema::from_json(
+            r#"{ "projects": {
+                "status": { "type": "string", "required": true },
+                "created": { "type": "date", "required": true }
+            } }"#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_write_missing_required_field_rejected() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+        fs::create_dir(temp_dir.path().join("projects")).await.unwrap();
+
+        let result = execute(
+            temp_dir.path(),
+            &storage,
+            &graph,
+            &projects_schema(),
+            "projects/App",
+            "---\nstatus: active\n---\n\nContent",
+            None,
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(err.message.contains("missing required fields: created"), "{}", err.message);
+        assert!(!err.message.contains("status"), "{}", err.message);
+        assert!(!temp_dir.path().join("projects/App.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_write_valid_frontmatter_accepted() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+        fs::create_dir(temp_dir.path().join("projects")).await.unwrap();
+
+        execute(
+            temp_dir.path(),
+            &storage,
+            &graph,
+            &projects_schema(),
+            "projects/App",
+            "---\nstatus: active\ncreated: 2025-01-31\n---\n\nContent",
+            None,
+        )
+        .await
+        .expect("should succeed");
+
+        assert!(temp_dir.path().join("projects/App.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_write_outside_schema_folder_not_validated() {
+        let (temp_dir, storage, graph) = create_test_env().await;
+
+        execute(
+            temp_dir.path(),
+            &storage,
+            &graph,
+            &projects_schema(),
+            "scratch",
+            "No frontmatter at all",
+            None,
+        )
+        .await
+        .expect("should succeed");
+    }
+
     // Integration tests - test the actual ReadNote→WriteNote flow
 
     #[tokio::test]
@@ -417,6 +506,7 @@ mod tests {
             &storage,
             &graph,
             "My Note",
+            super::super::read_note::NoteFormat::Markdown,
         )
         .await
         .expect("ReadNote should succeed");
@@ -433,6 +523,7 @@ mod tests {
             temp_dir.path(),
             &storage,
             &graph,
+            &FrontmatterSchema::default(),
             "My Note",
             "Version 2",
             Some(content_hash),
@@ -449,4 +540,41 @@ mod tests {
             .unwrap();
         assert_eq!(content, "Version 2");
     }
+
+    #[tokio::test]
+    async fn test_write_to_protected_folder_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("published")).await.unwrap();
+        let storage = FileStorage::new(temp_dir.path().to_path_buf())
+            .protect_folders(vec!["published".to_string()]);
+        let graph = GraphIndex::new();
+
+        let err = execute(
+            temp_dir.path(),
+            &storage,
+            &graph,
+            &FrontmatterSchema::default(),
+            "published/Post",
+            "Draft",
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.message.contains("'published/' folder is read-only"));
+        assert!(!temp_dir.path().join("published/Post.md").exists());
+
+        // Outside the protected folder, writes go through
+        execute(
+            temp_dir.path(),
+            &storage,
+            &graph,
+            &FrontmatterSchema::default(),
+            "Draft",
+            "Draft",
+            None,
+        )
+        .await
+        .expect("should succeed");
+        assert!(temp_dir.path().join("Draft.md").exists());
+    }
 }
@@ -1,7 +1,8 @@
 //! File watcher for keeping the graph index and embeddings up to date.
 //!
 //! Watches the vault directory for changes to markdown files and updates
-//! the graph index and invalidates stale embeddings.
+//! the graph index and embeddings. Changed notes that were already embedded
+//! are re-embedded right away, re-encoding only the sections that changed.
 //! Uses debouncing to batch rapid changes.
 //!
 //! Note: The read whitelist is NOT invalidated by the file watcher. Instead,
@@ -18,7 +19,6 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio::sync::{mpsc, RwLock};
-use wiki_links::extract_linked_notes;
 
 use crate::embeddings::EmbeddingManager;
 use crate::graph::GraphIndex;
@@ -131,12 +131,19 @@ async fn process_events(
 
                         // Now process the file
                         tracing::trace!("Processing {} - mtime changed", path.display());
-                        if let Err(e) = update_file(&vault_path, path, &graph).await {
-                            tracing::warn!("Failed to update index for {}: {}", path.display(), e);
+                        match update_file(&vault_path, path, &graph).await {
+                            Ok(content) => {
+                                refresh_embedding(&embeddings, &relative_path_str, &content).await
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Failed to update index for {}: {}",
+                                    path.display(),
+                                    e
+                                );
+                                embeddings.invalidate(&relative_path_str).await;
+                            }
                         }
-                        // Invalidate stale embedding so it gets recomputed on next search
-                        embeddings.invalidate(&relative_path_str).await;
-                        tracing::debug!("Invalidated embedding for: {}", relative_path_str);
                     } else {
                         // File was deleted - clean up cache entry
                         mtime_cache.remove(path);
@@ -162,12 +169,25 @@ async fn process_events(
     }
 }
 
-/// Update the graph index for a single file.
+/// Re-embed the changed sections of a note, or drop its embedding if that fails
+/// so it gets recomputed on the next search.
+async fn refresh_embedding(embeddings: &EmbeddingManager, relative_path: &str, content: &str) {
+    match embeddings.refresh(relative_path, content).await {
+        Ok(0) => {}
+        Ok(count) => tracing::debug!("Re-embedded {} sections of {}", count, relative_path),
+        Err(e) => {
+            tracing::warn!("Failed to re-embed {}: {}", relative_path, e);
+            embeddings.invalidate(relative_path).await;
+        }
+    }
+}
+
+/// Update the graph index for a single file, returning its content.
 async fn update_file(
     vault_path: &Path,
     file_path: &Path,
     graph: &Arc<RwLock<GraphIndex>>,
-) -> Result<(), std::io::Error> {
+) -> Result<String, std::io::Error> {
     let content = tokio::fs::read_to_string(file_path).await?;
 
     // Get note name (filename without .md extension)
@@ -183,17 +203,21 @@ async fn update_file(
         .unwrap_or(file_path)
         .to_path_buf();
 
-    // Extract linked notes
-    let linked_notes = extract_linked_notes(&content);
-    let links: HashSet<String> = linked_notes.into_iter().collect();
+    // Extract frontmatter, linked notes, and tags
+    let note = obsidian_fs::parse_note(&content);
+    let links: HashSet<String> = note.linked_notes.into_iter().collect();
+    let tags = note.tags.into_iter().collect();
+    let frontmatter = note.frontmatter;
 
     // Update the graph
     let mut graph = graph.write().await;
+    graph.set_frontmatter(&relative_path.to_string_lossy(), frontmatter);
+    graph.set_tags(&relative_path.to_string_lossy(), tags);
     graph.update_note(&note_name, relative_path.clone(), links);
 
     tracing::debug!("Updated index for: {}", relative_path.display());
 
-    Ok(())
+    Ok(content)
 }
 
 /// Remove a file from the graph index.
@@ -239,7 +263,7 @@ mod tests {
     async fn test_update_file_indexes_links() {
         let temp_dir = TempDir::new().unwrap();
         let file_path = temp_dir.path().join("test.md");
-        fs::write(&file_path, "Links to [[Note A]] and [[Note B]]").unwrap();
+        fs::write(&file_path, "Links to [[Note A]] and [[Note B]] #topic/rust").unwrap();
 
         let graph = Arc::new(RwLock::new(GraphIndex::new()));
 
@@ -252,6 +276,23 @@ mod tests {
         let links = graph.get_forward_links("test.md").unwrap();
         assert!(links.contains("Note A"));
         assert!(links.contains("Note B"));
+        assert!(graph.get_tags("test.md").unwrap().contains("topic/rust"));
+    }
+
+    #[tokio::test]
+    async fn test_update_file_caches_frontmatter() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.md");
+        fs::write(&file_path, "---\nsearch: false\n---\nHidden").unwrap();
+
+        let graph = Arc::new(RwLock::new(GraphIndex::new()));
+        update_file(temp_dir.path(), &file_path, &graph).await.unwrap();
+        assert!(graph.read().await.is_excluded_from_search("test.md"));
+
+        // Dropping the flag makes the note searchable again
+        fs::write(&file_path, "Visible").unwrap();
+        update_file(temp_dir.path(), &file_path, &graph).await.unwrap();
+        assert!(!graph.read().await.is_excluded_from_search("test.md"));
     }
 
     #[tokio::test]
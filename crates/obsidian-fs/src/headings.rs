@@ -0,0 +1,169 @@
+//! Markdown heading extraction.
+//!
+//! Recognizes ATX headings (`# Title` through `###### Title`), skipping YAML
+//! frontmatter and fenced code blocks so `# comments` inside them aren't
+//! mistaken for headings.
+
+/// A heading found in a note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// Heading level, 1-6 (number of `#`)
+    pub level: usize,
+    /// Heading text without the `#` markers or closing sequence
+    pub text: String,
+    /// Zero-based line index of the heading within the note
+    pub line: usize,
+}
+
+/// Parse a single line as an ATX heading, returning its level and text.
+pub fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    // Up to three spaces of indentation are allowed
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let line = &line[indent..];
+
+    let level = line.len() - line.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+
+    // Strip an optional closing sequence of #s ("## Title ##")
+    let text = rest.trim();
+    let text = match text.trim_end_matches('#') {
+        stripped if stripped.is_empty() || stripped.ends_with([' ', '\t']) => stripped.trim_end(),
+        _ => text,
+    };
+    Some((level, text))
+}
+
+/// Extract all headings from a note, in document order.
+pub fn extract_headings(content: &str) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut lines = content.split('\n').enumerate().peekable();
+
+    // Skip frontmatter
+    if lines.peek().is_some_and(|(_, line)| line.trim_end() == "---") {
+        lines.next();
+        for (_, line) in lines.by_ref() {
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+
+    let mut fence: Option<&str> = None;
+    for (index, line) in lines {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") {
+            fence = Some("```");
+            continue;
+        }
+        if trimmed.starts_with("~~~") {
+            fence = Some("~~~");
+            continue;
+        }
+
+        if let Some((level, text)) = parse_heading(line.trim_end_matches('\r')) {
+            headings.push(Heading {
+                level,
+                text: text.to_string(),
+                line: index,
+            });
+        }
+    }
+
+    headings
+}
+
+/// Anchor Obsidian uses to link to a heading, e.g. `Setup: Linux` → `Setup Linux`.
+///
+/// Characters Obsidian doesn't allow in heading links (`#`, `^`, `|`, `[`, `]`,
+/// `:`, `\`) become spaces, and runs of whitespace collapse to one space.
+/// Use [`heading_link_target`] for the URL-safe form used in markdown links.
+pub fn heading_anchor(text: &str) -> String {
+    let cleaned: String = text
+        .chars()
+        .map(|c| match c {
+            '#' | '^' | '|' | '[' | ']' | ':' | '\\' => ' ',
+            c => c,
+        })
+        .collect();
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Link target for a heading in a markdown link: `[Setup](#Setup%20Linux)`.
+pub fn heading_link_target(text: &str) -> String {
+    let mut target = String::from("#");
+    for c in heading_anchor(text).chars() {
+        match c {
+            ' ' => target.push_str("%20"),
+            '(' => target.push_str("%28"),
+            ')' => target.push_str("%29"),
+            '<' => target.push_str("%3C"),
+            '>' => target.push_str("%3E"),
+            c => target.push(c),
+        }
+    }
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_heading_levels() {
+        assert_eq!(parse_heading("# Title"), Some((1, "Title")));
+        assert_eq!(parse_heading("### Tasks  "), Some((3, "Tasks")));
+        assert_eq!(parse_heading("## Closed ##"), Some((2, "Closed")));
+        assert_eq!(parse_heading("## C#"), Some((2, "C#")));
+        assert_eq!(parse_heading("#"), Some((1, "")));
+    }
+
+    #[test]
+    fn parse_heading_rejects_non_headings() {
+        assert_eq!(parse_heading("#tag"), None);
+        assert_eq!(parse_heading("####### Seven"), None);
+        assert_eq!(parse_heading("    # Indented code"), None);
+        assert_eq!(parse_heading("Text # not a heading"), None);
+    }
+
+    #[test]
+    fn heading_anchor_matches_obsidian() {
+        assert_eq!(heading_anchor("Getting Started"), "Getting Started");
+        assert_eq!(heading_anchor("Setup: Linux"), "Setup Linux");
+        assert_eq!(heading_anchor("C# [draft] | v2"), "C draft v2");
+        assert_eq!(heading_anchor("Café (notes)"), "Café (notes)");
+    }
+
+    #[test]
+    fn heading_link_target_encodes_spaces_and_parens() {
+        assert_eq!(heading_link_target("Getting Started"), "#Getting%20Started");
+        assert_eq!(heading_link_target("Notes (old)"), "#Notes%20%28old%29");
+    }
+
+    #[test]
+    fn extract_skips_frontmatter_and_code_blocks() {
+        let content = "---\n# yaml comment\ntype: note\n---\n# Title\n\n```sh\n# shell comment\n```\n## Tasks\n- one";
+        let headings = extract_headings(content);
+        assert_eq!(
+            headings,
+            vec![
+                Heading { level: 1, text: "Title".into(), line: 4 },
+                Heading { level: 2, text: "Tasks".into(), line: 9 },
+            ]
+        );
+    }
+}
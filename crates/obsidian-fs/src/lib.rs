@@ -1,16 +1,23 @@
 //! Path resolution and frontmatter parsing utilities for Obsidian notes
 //!
 //! Handles note path resolution, normalization, search path generation,
-//! and YAML frontmatter parsing. These are pure functions with no I/O -
+//! YAML frontmatter parsing, heading and tag extraction. These are pure functions with no I/O -
 //! actual filesystem operations stay in the TypeScript layer (or future
 //! Rust MCP server).
 
 mod frontmatter;
+mod headings;
+mod note;
+mod tags;
 
 pub use frontmatter::{
     build_note_with_frontmatter, parse_frontmatter, serialize_frontmatter, split_frontmatter,
     Frontmatter, FrontmatterError, ParsedNote,
 };
+pub use headings::{extract_headings, heading_anchor, heading_link_target, parse_heading, Heading};
+pub use note::{parse_note, IndexedNote};
+pub use tags::extract_inline_tags;
+pub use wiki_links::WikiLink;
 
 use serde::{Deserialize, Serialize};
 
@@ -22,11 +29,60 @@ pub const COMMON_SEARCH_PATHS: &[&str] = &[
     "projects",
 ];
 
+/// Folder that holds private notes unless configured otherwise
+pub const DEFAULT_PRIVATE_ROOT: &str = "private";
+
+/// Folders whose notes are private: skipped by default and only read or
+/// searched with explicit consent.
+///
+/// Roots are vault-relative folders such as `private` or `journal/private`;
+/// a note is private when it's anywhere under one of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrivateRoots(Vec<String>);
+
+impl PrivateRoots {
+    /// Private roots from folder names, ignoring surrounding slashes and empty names.
+    pub fn new<I, S>(roots: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self(
+            roots
+                .into_iter()
+                .map(|root| root.as_ref().trim().trim_matches('/').to_string())
+                .filter(|root| !root.is_empty())
+                .collect(),
+        )
+    }
+
+    /// The configured root folders, without trailing slashes.
+    pub fn roots(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Whether `path` (relative to the vault) is under a private root.
+    pub fn contains(&self, path: &str) -> bool {
+        self.0.iter().any(|root| {
+            path.strip_prefix(root.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+impl Default for PrivateRoots {
+    fn default() -> Self {
+        Self::new([DEFAULT_PRIVATE_ROOT])
+    }
+}
+
 /// Options for resolving note paths
 #[derive(Debug, Clone, Default)]
 pub struct ResolutionOptions {
-    /// Whether to include private folder in search
+    /// Whether to include private folders in search
     pub include_private: bool,
+    /// Folders treated as private
+    pub private_roots: PrivateRoots,
 }
 
 /// Priority categories for path resolution
@@ -40,8 +96,11 @@ enum PathPriority {
     Private = 5,
 }
 
-fn get_priority(path: &str) -> PathPriority {
-    if !path.contains('/') {
+fn get_priority(path: &str, private_roots: &PrivateRoots) -> PathPriority {
+    // Checked first so private roots nested in other folders still sort last
+    if private_roots.contains(path) {
+        PathPriority::Private
+    } else if !path.contains('/') {
         PathPriority::Root
     } else if path.starts_with("knowledge/") {
         PathPriority::Knowledge
@@ -49,8 +108,6 @@ fn get_priority(path: &str) -> PathPriority {
         PathPriority::Journal
     } else if path.starts_with("projects/") {
         PathPriority::Projects
-    } else if path.starts_with("private/") {
-        PathPriority::Private
     } else {
         PathPriority::Other
     }
@@ -58,7 +115,7 @@ fn get_priority(path: &str) -> PathPriority {
 
 /// Resolve a note path from available options using priority order.
 ///
-/// Priority: root → knowledge/ → journal/ → projects/ → others → private/
+/// Priority: root → knowledge/ → journal/ → projects/ → others → private roots
 ///
 /// Returns the best matching path, or None if no paths provided.
 pub fn resolve_note_path(available_paths: &[&str], options: &ResolutionOptions) -> Option<String> {
@@ -73,7 +130,7 @@ pub fn resolve_note_path(available_paths: &[&str], options: &ResolutionOptions)
     let paths: Vec<&str> = if !options.include_private {
         let non_private: Vec<&str> = available_paths
             .iter()
-            .filter(|p| !p.starts_with("private/"))
+            .filter(|p| !options.private_roots.contains(p))
             .copied()
             .collect();
         if non_private.is_empty() {
@@ -87,14 +144,15 @@ pub fn resolve_note_path(available_paths: &[&str], options: &ResolutionOptions)
 
     // Sort by priority and return the first
     let mut sorted = paths;
-    sorted.sort_by_key(|p| get_priority(p));
+    sorted.sort_by_key(|p| get_priority(p, &options.private_roots));
     sorted.first().map(|s| s.to_string())
 }
 
 /// Generate search paths for a note name.
 ///
-/// Returns an array of paths to try (without .md extension).
-pub fn generate_search_paths(note_name: &str, include_private: bool) -> Vec<String> {
+/// Returns an array of paths to try (without .md extension). Paths in
+/// private roots are only included with `options.include_private`.
+pub fn generate_search_paths(note_name: &str, options: &ResolutionOptions) -> Vec<String> {
     let mut paths = Vec::new();
 
     for folder in COMMON_SEARCH_PATHS {
@@ -105,8 +163,10 @@ pub fn generate_search_paths(note_name: &str, include_private: bool) -> Vec<Stri
         }
     }
 
-    if include_private {
-        paths.push(format!("private/{}", note_name));
+    if options.include_private {
+        for root in options.private_roots.roots() {
+            paths.push(format!("{}/{}", root, note_name));
+        }
     }
 
     paths
@@ -130,20 +190,61 @@ pub struct NoteRef {
 /// - Wiki link: "[[knowledge/Note]]"
 /// - With .md: "knowledge/Note.md"
 pub fn normalize_note_reference(note_ref: &str) -> NoteRef {
+    let diagnostics = diagnose_note_reference(note_ref);
+    NoteRef {
+        path: diagnostics.path,
+        name: diagnostics.name,
+    }
+}
+
+/// A normalized note reference plus what normalization stripped from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRefDiagnostics {
+    /// The path without extension: "knowledge/Note"
+    pub path: String,
+    /// Just the note name: "Note"
+    pub name: String,
+    /// The reference started with the `memory:` URI scheme
+    pub had_memory_prefix: bool,
+    /// The reference was wrapped in `[[wiki link]]` brackets
+    pub had_wiki_brackets: bool,
+    /// The reference ended with `.md`
+    pub had_md_extension: bool,
+    /// Why the normalized path fails `validate_relative_path`, if it does
+    pub path_error: Option<String>,
+}
+
+impl NoteRefDiagnostics {
+    /// Whether the normalized path passes `validate_relative_path`
+    pub fn is_valid(&self) -> bool {
+        self.path_error.is_none()
+    }
+}
+
+/// Normalize a note reference like `normalize_note_reference`, reporting which
+/// decorations were stripped and whether the resulting path is valid
+pub fn diagnose_note_reference(note_ref: &str) -> NoteRefDiagnostics {
     let mut normalized = note_ref.trim();
 
     // Strip [[wiki link]] brackets if present
-    if normalized.starts_with("[[") && normalized.ends_with("]]") {
+    let had_wiki_brackets = normalized.starts_with("[[") && normalized.ends_with("]]");
+    if had_wiki_brackets {
         normalized = &normalized[2..normalized.len() - 2];
     }
 
     // Strip memory: URI scheme
-    if let Some(stripped) = normalized.strip_prefix("memory:") {
-        normalized = stripped;
-    }
+    let had_memory_prefix = match normalized.strip_prefix("memory:") {
+        Some(stripped) => {
+            normalized = stripped;
+            true
+        }
+        None => false,
+    };
 
     // Strip .md extension if present
-    let path = if normalized.ends_with(".md") {
+    let had_md_extension = normalized.ends_with(".md");
+    let path = if had_md_extension {
         &normalized[..normalized.len() - 3]
     } else {
         normalized
@@ -152,9 +253,13 @@ pub fn normalize_note_reference(note_ref: &str) -> NoteRef {
     // Extract just the note name (last path component)
     let name = path.rsplit('/').next().unwrap_or(path).to_string();
 
-    NoteRef {
+    NoteRefDiagnostics {
         path: path.to_string(),
         name,
+        had_memory_prefix,
+        had_wiki_brackets,
+        had_md_extension,
+        path_error: validate_relative_path(path).err().map(|e| e.to_string()),
     }
 }
 
@@ -256,6 +361,7 @@ mod tests {
             &paths,
             &ResolutionOptions {
                 include_private: true,
+                ..Default::default()
             },
         );
         assert_eq!(result, Some("private/Note".to_string()));
@@ -268,6 +374,7 @@ mod tests {
             &paths,
             &ResolutionOptions {
                 include_private: false,
+                ..Default::default()
             },
         );
         assert_eq!(result, Some("Note".to_string()));
@@ -280,6 +387,7 @@ mod tests {
             &paths,
             &ResolutionOptions {
                 include_private: false,
+                ..Default::default()
             },
         );
         assert_eq!(result, Some("private/Note".to_string()));
@@ -288,7 +396,7 @@ mod tests {
     // generateSearchPaths tests
     #[test]
     fn generate_common_search_paths() {
-        let paths = generate_search_paths("Test", false);
+        let paths = generate_search_paths("Test", &ResolutionOptions::default());
         assert_eq!(
             paths,
             vec!["Test", "knowledge/Test", "journal/Test", "projects/Test"]
@@ -297,16 +405,71 @@ mod tests {
 
     #[test]
     fn generate_includes_private_when_requested() {
-        let paths = generate_search_paths("Test", true);
+        let paths = generate_search_paths(
+            "Test",
+            &ResolutionOptions {
+                include_private: true,
+                ..Default::default()
+            },
+        );
         assert!(paths.contains(&"private/Test".to_string()));
     }
 
     #[test]
     fn generate_excludes_private_by_default() {
-        let paths = generate_search_paths("Test", false);
+        let paths = generate_search_paths("Test", &ResolutionOptions::default());
+        assert!(!paths.contains(&"private/Test".to_string()));
+    }
+
+    #[test]
+    fn resolve_deprioritizes_custom_private_roots() {
+        let options = ResolutionOptions {
+            include_private: false,
+            private_roots: PrivateRoots::new(["personal", "journal/private/"]),
+        };
+        let paths = vec!["journal/private/Note", "personal/Note", "private/Note"];
+        assert_eq!(
+            resolve_note_path(&paths, &options),
+            Some("private/Note".to_string())
+        );
+
+        let options = ResolutionOptions {
+            include_private: true,
+            ..options
+        };
+        let paths = vec!["journal/private/Note", "other/Note"];
+        assert_eq!(
+            resolve_note_path(&paths, &options),
+            Some("other/Note".to_string())
+        );
+    }
+
+    #[test]
+    fn generate_includes_every_private_root_with_consent() {
+        let mut options = ResolutionOptions {
+            include_private: false,
+            private_roots: PrivateRoots::new(["personal", "journal/private"]),
+        };
+        let paths = generate_search_paths("Test", &options);
+        assert!(!paths.iter().any(|p| p.starts_with("personal/")));
+
+        options.include_private = true;
+        let paths = generate_search_paths("Test", &options);
+        assert!(paths.contains(&"personal/Test".to_string()));
+        assert!(paths.contains(&"journal/private/Test".to_string()));
         assert!(!paths.contains(&"private/Test".to_string()));
     }
 
+    #[test]
+    fn private_roots_match_whole_folders() {
+        let roots = PrivateRoots::default();
+        assert!(roots.contains("private/Note"));
+        assert!(roots.contains("private/work/Note"));
+        assert!(!roots.contains("private-ish/Note"));
+        assert!(!roots.contains("private"));
+        assert!(!roots.contains("knowledge/private/Note"));
+    }
+
     // normalizeNoteReference tests
     #[test]
     fn normalize_strips_memory_prefix() {
@@ -341,6 +504,57 @@ mod tests {
         assert_eq!(result.path, "knowledge/Note");
     }
 
+    // diagnoseNoteReference tests
+    #[test]
+    fn diagnose_reports_every_combination_of_decorations() {
+        for brackets in [false, true] {
+            for memory in [false, true] {
+                for md in [false, true] {
+                    let mut note_ref = "knowledge/Note".to_string();
+                    if md {
+                        note_ref.push_str(".md");
+                    }
+                    if memory {
+                        note_ref.insert_str(0, "memory:");
+                    }
+                    if brackets {
+                        note_ref = format!("[[{}]]", note_ref);
+                    }
+
+                    let result = diagnose_note_reference(&note_ref);
+                    assert_eq!(result.path, "knowledge/Note", "{}", note_ref);
+                    assert_eq!(result.name, "Note", "{}", note_ref);
+                    assert_eq!(result.had_wiki_brackets, brackets, "{}", note_ref);
+                    assert_eq!(result.had_memory_prefix, memory, "{}", note_ref);
+                    assert_eq!(result.had_md_extension, md, "{}", note_ref);
+                    assert!(result.is_valid(), "{}", note_ref);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn diagnose_reports_invalid_paths() {
+        let result = diagnose_note_reference("[[memory:../secret.md]]");
+        assert_eq!(result.path, "../secret");
+        assert!(result.had_wiki_brackets && result.had_memory_prefix && result.had_md_extension);
+        assert!(!result.is_valid());
+        assert_eq!(
+            result.path_error.as_deref(),
+            Some("Path contains directory traversal")
+        );
+    }
+
+    #[test]
+    fn diagnose_matches_normalize() {
+        for note_ref in ["Note", " [[Note]] ", "memory:a/b/Note.md", "Note.md.md"] {
+            let diagnostics = diagnose_note_reference(note_ref);
+            let normalized = normalize_note_reference(note_ref);
+            assert_eq!(diagnostics.path, normalized.path);
+            assert_eq!(diagnostics.name, normalized.name);
+        }
+    }
+
     #[test]
     fn normalize_returns_note_as_is_if_already_normalized() {
         let result = normalize_note_reference("knowledge/Note");
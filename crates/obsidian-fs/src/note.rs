@@ -0,0 +1,86 @@
+//! Single-pass note parsing for indexing.
+//!
+//! Building the note graph needs a note's frontmatter, linked notes, and tags.
+//! `parse_note` splits off the frontmatter once and finds the links and tags
+//! with one `wiki_links::scan_note` pass, instead of each caller running the
+//! link, Markdown link, and tag parsers over the raw file in turn.
+
+use crate::frontmatter::{Frontmatter, parse_frontmatter};
+use crate::tags::unique_tag_names;
+use wiki_links::{WikiLink, scan_note};
+
+/// Everything the indexer needs from a note.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedNote<'a> {
+    /// The frontmatter key-value pairs, if present
+    pub frontmatter: Option<Frontmatter>,
+    /// The note content after the frontmatter (borrows from the input)
+    pub body: &'a str,
+    /// Wiki links outside code, frontmatter included, in document order
+    pub links: Vec<WikiLink>,
+    /// Unique names of the notes linked by wiki links or internal Markdown
+    /// links, as `wiki_links::extract_all_linked_notes` returns them
+    pub linked_notes: Vec<String>,
+    /// Unique inline tags without `#`, in document order
+    pub tags: Vec<String>,
+}
+
+/// Parse a note's frontmatter, body, links, and inline tags.
+///
+/// Gives the same results as `parse_frontmatter`, `extract_all_linked_notes`,
+/// and `extract_inline_tags` on the raw note, scanning its text once.
+pub fn parse_note(raw: &str) -> IndexedNote<'_> {
+    let parsed = parse_frontmatter(raw);
+    let scan = scan_note(raw);
+    IndexedNote {
+        frontmatter: parsed.frontmatter,
+        body: parsed.content,
+        linked_notes: scan.linked_notes(),
+        links: scan.wiki_links,
+        tags: unique_tag_names(scan.tags),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::extract_inline_tags;
+    use wiki_links::{ParseOptions, extract_all_linked_notes, parse_wiki_links_with_options};
+
+    #[test]
+    fn parse_note_matches_individual_parsers() {
+        let notes = [
+            "---\ntitle: Rust\nrelated: \"[[Cargo]]\"\n---\n# Rust\n\nSee [[WASM]] and [[tools/Cargo|cargo]] #lang/rust",
+            "No frontmatter, links to ![[diagram.png]], [guide](docs/Guide.md) and [[Note#Header]] #draft",
+            "---\ntitle: Empty\n---\n",
+            "```md\n[[Not a link]] #nope\n```\n#real [[Real]] `#code`",
+            "",
+        ];
+        for raw in notes {
+            let note = parse_note(raw);
+            let parsed = parse_frontmatter(raw);
+            let options = ParseOptions { skip_code: true };
+            assert_eq!(note.frontmatter, parsed.frontmatter);
+            assert_eq!(note.body, parsed.content);
+            assert_eq!(note.links, parse_wiki_links_with_options(raw, options));
+            assert_eq!(note.linked_notes, extract_all_linked_notes(raw));
+            assert_eq!(note.tags, extract_inline_tags(raw));
+        }
+    }
+
+    #[test]
+    fn parse_note_extracts_everything() {
+        let note = parse_note(
+            "---\ntype: project\ncolor: #fff\n---\nBuilt on [[Loro]] #crdt and [spec](Spec.md) #crdt",
+        );
+        assert_eq!(
+            note.frontmatter.unwrap().get("type"),
+            Some(&serde_json::json!("project"))
+        );
+        assert_eq!(note.body, "Built on [[Loro]] #crdt and [spec](Spec.md) #crdt");
+        assert_eq!(note.links.len(), 1);
+        assert_eq!(note.links[0].name, "Loro");
+        assert_eq!(note.linked_notes, vec!["Loro", "Spec"]);
+        assert_eq!(note.tags, vec!["crdt"]);
+    }
+}
@@ -0,0 +1,49 @@
+//! Inline tag extraction.
+//!
+//! Tags are parsed by `wiki_links::parse_tags`, which skips YAML frontmatter,
+//! fenced code blocks, and inline code spans; this module only collects their
+//! names.
+
+use wiki_links::{Tag, parse_tags};
+
+/// Extract the unique inline tags in a note, without `#`, in document order.
+pub fn extract_inline_tags(content: &str) -> Vec<String> {
+    unique_tag_names(parse_tags(content))
+}
+
+/// Names of `tags` without repeats, in order.
+pub(crate) fn unique_tag_names(tags: Vec<Tag>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for tag in tags {
+        if !names.contains(&tag.name) {
+            names.push(tag.name);
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_simple_and_nested_tags() {
+        let content = "#project work on #rust/wasm and #project again\n- [ ] #todo, later";
+        assert_eq!(
+            extract_inline_tags(content),
+            vec!["project", "rust/wasm", "todo"]
+        );
+    }
+
+    #[test]
+    fn extract_rejects_non_tags() {
+        let content = "# Heading\nissue #123, C# and a#b, url.com/#anchor, ## Sub";
+        assert!(extract_inline_tags(content).is_empty());
+    }
+
+    #[test]
+    fn extract_skips_frontmatter_and_code() {
+        let content = "---\ncolor: #ff0000\n---\n#real\n```sh\n# comment #shell\n```\nrun `git #nope` then #done";
+        assert_eq!(extract_inline_tags(content), vec!["real", "done"]);
+    }
+}
@@ -24,30 +24,102 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Result<f32> {
     Ok(dot_product / (magnitude_a * magnitude_b))
 }
 
+/// How to compare two embedding vectors.
+///
+/// Cosine ignores magnitude and suits normalized sentence embeddings, which is
+/// why it is the default. Dot product rewards magnitude as well as direction.
+/// Euclidean is a distance, so lower values mean more similar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SimilarityMetric {
+    #[default]
+    Cosine,
+    Dot,
+    Euclidean,
+}
+
+impl SimilarityMetric {
+    /// Compare two vectors: a similarity for `Cosine`/`Dot`, a distance for `Euclidean`.
+    pub fn compute(self, a: &[f32], b: &[f32]) -> Result<f32> {
+        match self {
+            SimilarityMetric::Cosine => cosine_similarity(a, b),
+            SimilarityMetric::Dot => dot_product(a, b),
+            SimilarityMetric::Euclidean => euclidean_distance(a, b),
+        }
+    }
+
+    /// Whether larger values from `compute` mean more similar.
+    pub fn higher_is_closer(self) -> bool {
+        !matches!(self, SimilarityMetric::Euclidean)
+    }
+}
+
+/// Compute the dot product of two embedding vectors
+pub fn dot_product(a: &[f32], b: &[f32]) -> Result<f32> {
+    if a.len() != b.len() {
+        anyhow::bail!("Vector dimensions must match: {} vs {}", a.len(), b.len());
+    }
+
+    Ok(a.iter().zip(b.iter()).map(|(x, y)| x * y).sum())
+}
+
+/// Compute the Euclidean distance between two embedding vectors
+pub fn euclidean_distance(a: &[f32], b: &[f32]) -> Result<f32> {
+    if a.len() != b.len() {
+        anyhow::bail!("Vector dimensions must match: {} vs {}", a.len(), b.len());
+    }
+
+    Ok(a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt())
+}
+
 /// Find indices of top K most similar embeddings to query
 ///
-/// Returns indices sorted by similarity (descending)
+/// Returns indices sorted by cosine similarity (descending). Equal similarities
+/// are ordered by index (ascending), so results are deterministic.
 pub fn find_most_similar(query: &[f32], candidates: &[Vec<f32>], top_k: usize) -> Result<Vec<u32>> {
+    find_most_similar_with(query, candidates, top_k, SimilarityMetric::Cosine)
+}
+
+/// Find indices of top K most similar embeddings to query under `metric`
+///
+/// Returns indices sorted from most to least similar, which for Euclidean
+/// distance means ascending distance. Ties are ordered by index (ascending).
+pub fn find_most_similar_with(
+    query: &[f32],
+    candidates: &[Vec<f32>],
+    top_k: usize,
+    metric: SimilarityMetric,
+) -> Result<Vec<u32>> {
     if candidates.is_empty() {
         return Ok(vec![]);
     }
 
-    // Compute similarities for all candidates
-    let mut similarities: Vec<(usize, f32)> = candidates
+    // Score every candidate; unscorable ones rank last
+    let worst = if metric.higher_is_closer() {
+        f32::NEG_INFINITY
+    } else {
+        f32::INFINITY
+    };
+    let mut scores: Vec<(usize, f32)> = candidates
         .iter()
         .enumerate()
-        .map(|(idx, candidate)| {
-            let sim = cosine_similarity(query, candidate).unwrap_or(0.0);
-            (idx, sim)
-        })
+        .map(|(idx, candidate)| (idx, metric.compute(query, candidate).unwrap_or(worst)))
         .collect();
 
-    // Sort by similarity descending
-    similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    // Most similar first, ties by index
+    scores.sort_by(|a, b| {
+        let by_score = if metric.higher_is_closer() {
+            b.1.partial_cmp(&a.1)
+        } else {
+            a.1.partial_cmp(&b.1)
+        };
+        by_score
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.0.cmp(&b.0))
+    });
 
     // Take top K
-    let k = top_k.min(similarities.len());
-    Ok(similarities[..k]
+    let k = top_k.min(scores.len());
+    Ok(scores[..k]
         .iter()
         .map(|(idx, _)| *idx as u32)
         .collect())
@@ -107,6 +179,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_most_similar_ties_ordered_by_index() {
+        let query = vec![1.0, 0.0];
+        let candidates = vec![
+            vec![0.0, 1.0], // idx 0: similarity 0.0
+            vec![1.0, 1.0], // idx 1: tie
+            vec![1.0, 1.0], // idx 2: tie
+            vec![1.0, 1.0], // idx 3: tie
+        ];
+
+        let result = find_most_similar(&query, &candidates, 4).unwrap();
+        assert_eq!(result, vec![1, 2, 3, 0]);
+    }
+
+    /// Query [1, 0] against candidates that each metric ranks differently:
+    /// idx 0 points the same way but is long, idx 1 is nearby but off-axis,
+    /// idx 2 is unrelated.
+    fn metric_candidates() -> Vec<Vec<f32>> {
+        vec![vec![5.0, 0.0], vec![0.9, 0.4], vec![-1.0, 1.0]]
+    }
+
+    #[test]
+    fn test_find_most_similar_cosine_ranks_by_direction() {
+        let result =
+            find_most_similar_with(&[1.0, 0.0], &metric_candidates(), 3, SimilarityMetric::Cosine)
+                .unwrap();
+        assert_eq!(result, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_find_most_similar_dot_rewards_magnitude() {
+        let candidates = vec![vec![0.9, 0.4], vec![5.0, 5.0], vec![-1.0, 1.0]];
+        let result =
+            find_most_similar_with(&[1.0, 0.0], &candidates, 3, SimilarityMetric::Dot).unwrap();
+        // Cosine would prefer idx 0; the dot product favors the longer idx 1
+        assert_eq!(result, vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn test_find_most_similar_euclidean_ranks_nearest_first() {
+        let result =
+            find_most_similar_with(&[1.0, 0.0], &metric_candidates(), 3, SimilarityMetric::Euclidean)
+                .unwrap();
+        // idx 1 is closest in space even though idx 0 points the same way
+        assert_eq!(result, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_default_metric_is_cosine() {
+        assert_eq!(SimilarityMetric::default(), SimilarityMetric::Cosine);
+        assert_eq!(
+            find_most_similar(&[1.0, 0.0], &metric_candidates(), 3).unwrap(),
+            find_most_similar_with(&[1.0, 0.0], &metric_candidates(), 3, SimilarityMetric::Cosine)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_euclidean_distance() {
+        assert_eq!(euclidean_distance(&[0.0, 0.0], &[3.0, 4.0]).unwrap(), 5.0);
+        assert!(euclidean_distance(&[1.0], &[1.0, 2.0]).is_err());
+    }
+
     #[test]
     fn test_find_most_similar_empty() {
         let query = vec![1.0, 0.0];
@@ -7,7 +7,10 @@ mod model;
 mod embedded;
 
 // Re-export for external use
-pub use embedding::{cosine_similarity, find_most_similar};
+pub use embedding::{
+    cosine_similarity, dot_product, euclidean_distance, find_most_similar, find_most_similar_with,
+    SimilarityMetric,
+};
 pub use model::ModelManager;
 
 /// Embedding dimension for all-MiniLM-L6-v2 model.
@@ -124,6 +127,11 @@ impl SemanticEmbeddings {
         self.model.encode_single(text)
     }
 
+    /// Count the tokens `text` encodes to, including the [CLS] and [SEP] markers.
+    pub fn count_tokens(&self, text: &str) -> anyhow::Result<usize> {
+        self.model.count_tokens(text)
+    }
+
     /// Encode multiple texts in batch (more efficient than multiple encode() calls).
     ///
     /// Returns Vec of embedding vectors.
@@ -148,6 +156,18 @@ impl SemanticEmbeddings {
     ) -> anyhow::Result<Vec<u32>> {
         find_most_similar(query, candidates, top_k)
     }
+
+    /// Find indices of top K most similar embeddings to query under `metric`.
+    ///
+    /// For `SimilarityMetric::Euclidean`, most similar means smallest distance.
+    pub fn find_most_similar_with(
+        query: &[f32],
+        candidates: &[Vec<f32>],
+        top_k: usize,
+        metric: SimilarityMetric,
+    ) -> anyhow::Result<Vec<u32>> {
+        find_most_similar_with(query, candidates, top_k, metric)
+    }
 }
 
 impl Default for SemanticEmbeddings {
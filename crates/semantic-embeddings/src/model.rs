@@ -84,6 +84,21 @@ impl ModelManager {
         Ok(())
     }
 
+    /// Count the tokens the model sees for `text`, including special tokens
+    pub fn count_tokens(&self, text: &str) -> Result<usize> {
+        self.ensure_loaded()?;
+
+        let state_guard = self.state.lock().unwrap();
+        let state = state_guard.as_ref().unwrap();
+
+        let encoding = state
+            .tokenizer
+            .encode(text, true)
+            .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+
+        Ok(encoding.len())
+    }
+
     /// Encode a single text into embedding vector
     pub fn encode_single(&self, text: &str) -> Result<Vec<f32>> {
         self.ensure_loaded()?;
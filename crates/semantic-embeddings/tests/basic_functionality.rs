@@ -72,3 +72,23 @@ fn test_batch_encoding() {
         );
     }
 }
+
+#[test]
+fn test_identical_text_encodes_identically() {
+    let emb1 = TEST_MODEL.encode_single("The weather is lovely").expect("Failed to encode");
+    let emb2 = TEST_MODEL.encode_single("The weather is lovely").expect("Failed to encode");
+
+    assert_eq!(emb1.len(), 384);
+    assert_eq!(emb1, emb2, "Encoding should be deterministic");
+}
+
+#[test]
+fn test_count_tokens_includes_special_tokens() {
+    // [CLS] cat [SEP]
+    assert_eq!(TEST_MODEL.count_tokens("cat").expect("Failed to tokenize"), 3);
+
+    let longer = TEST_MODEL
+        .count_tokens("The weather is lovely")
+        .expect("Failed to tokenize");
+    assert!(longer > 3);
+}
@@ -0,0 +1,165 @@
+//! Change queries for external integrations.
+//!
+//! Tools that mirror a vault elsewhere (static-site generators, backups) poll
+//! `changed_since` with the token from their previous call to learn which notes
+//! to re-export. Loro version vectors are per document, so the token records
+//! every note's version rather than a single vault-wide vector.
+
+use crate::fs::FileSystem;
+use crate::vault::{Result, Vault, VaultError};
+use loro::{LoroDoc, VersionVector};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Notes that changed since a previous `changed_since` call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangesSince {
+    /// Notes that are new or whose document advanced past the token, sorted
+    pub changed: Vec<String>,
+    /// Notes in the token that are no longer in the vault, sorted
+    pub removed: Vec<String>,
+    /// Opaque token to pass to the next `changed_since` call
+    pub token: Vec<u8>,
+}
+
+impl<F: FileSystem> Vault<F> {
+    /// List notes whose documents changed since `token`.
+    ///
+    /// Pass an empty token on the first call to get every note. A note counts as
+    /// changed when its version vector isn't included in the version recorded
+    /// in the token, so both local edits and changes received from peers show up.
+    pub async fn changed_since(&self, token: &[u8]) -> Result<ChangesSince> {
+        let mut previous: BTreeMap<String, Vec<u8>> = if token.is_empty() {
+            BTreeMap::new()
+        } else {
+            bincode::deserialize(token)
+                .map_err(|e| VaultError::Other(format!("Invalid change token: {}", e)))?
+        };
+
+        let mut changed = Vec::new();
+        let mut versions = BTreeMap::new();
+
+        for path in self.registered_files() {
+            let Some(version) = self.current_version(&path).await? else {
+                continue;
+            };
+
+            let advanced = match previous.remove(&path) {
+                Some(seen) => !Self::version_includes(&seen, &version),
+                None => true,
+            };
+            if advanced {
+                changed.push(path.clone());
+            }
+            versions.insert(path, version);
+        }
+
+        let token = bincode::serialize(&versions)
+            .map_err(|e| VaultError::Other(format!("Failed to encode change token: {}", e)))?;
+
+        Ok(ChangesSince {
+            changed,
+            removed: previous.into_keys().collect(),
+            token,
+        })
+    }
+
+    /// Encoded version of a document, without loading it if it isn't cached.
+    pub(crate) async fn current_version(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        if let Some(doc) = self.documents().get(path) {
+            return Ok(Some(doc.version().encode()));
+        }
+
+        let sync_path = self.document_sync_path(path);
+        if !self.fs.exists(&sync_path).await? {
+            return Ok(None);
+        }
+
+        // Snapshots carry their full version vector in the blob header
+        let bytes = self.fs.read(&sync_path).await?;
+        let meta = LoroDoc::decode_import_blob_meta(&bytes, false)
+            .map_err(|e| VaultError::Other(format!("Failed to decode blob meta: {}", e)))?;
+        let version: VersionVector = meta.partial_end_vv;
+        Ok(Some(version.encode()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::InMemoryFs;
+    use crate::PeerId;
+    use std::sync::Arc;
+
+    async fn vault_with_notes(paths: &[&str]) -> (Arc<InMemoryFs>, Vault<Arc<InMemoryFs>>) {
+        let fs = Arc::new(InMemoryFs::new());
+        for path in paths {
+            fs.write(path, format!("# {}", path).as_bytes()).await.unwrap();
+        }
+        let vault = Vault::init(Arc::clone(&fs), PeerId::from(1u64)).await.unwrap();
+        (fs, vault)
+    }
+
+    async fn edit(fs: &InMemoryFs, vault: &Vault<Arc<InMemoryFs>>, path: &str, content: &str) {
+        fs.write(path, content.as_bytes()).await.unwrap();
+        vault.on_file_changed(path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn empty_token_lists_every_note() {
+        let (_fs, vault) = vault_with_notes(&["a.md", "b.md", "c.md"]).await;
+
+        let changes = vault.changed_since(&[]).await.unwrap();
+        assert_eq!(changes.changed, vec!["a.md", "b.md", "c.md"]);
+        assert!(changes.removed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn returns_exactly_the_edited_notes() {
+        let (fs, vault) = vault_with_notes(&["a.md", "b.md", "c.md"]).await;
+        let token = vault.changed_since(&[]).await.unwrap().token;
+
+        edit(&fs, &vault, "a.md", "# a\n\nedited").await;
+        edit(&fs, &vault, "c.md", "# c\n\nedited").await;
+
+        let changes = vault.changed_since(&token).await.unwrap();
+        assert_eq!(changes.changed, vec!["a.md", "c.md"]);
+
+        // Nothing changed since the new token
+        let again = vault.changed_since(&changes.token).await.unwrap();
+        assert!(again.changed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reports_new_and_removed_notes() {
+        let (fs, vault) = vault_with_notes(&["a.md", "b.md"]).await;
+        let token = vault.changed_since(&[]).await.unwrap().token;
+
+        edit(&fs, &vault, "new.md", "# New").await;
+        fs.delete("b.md").await.unwrap();
+        vault.delete_file("b.md").await.unwrap();
+
+        let changes = vault.changed_since(&token).await.unwrap();
+        assert_eq!(changes.changed, vec!["new.md"]);
+        assert_eq!(changes.removed, vec!["b.md"]);
+    }
+
+    #[tokio::test]
+    async fn reads_versions_of_unloaded_documents() {
+        let (fs, vault) = vault_with_notes(&["a.md"]).await;
+        let token = vault.changed_since(&[]).await.unwrap().token;
+        drop(vault);
+
+        // A fresh load has no documents cached; versions come from blob headers
+        let vault = Vault::load(Arc::clone(&fs), PeerId::from(1u64)).await.unwrap();
+        assert!(vault.changed_since(&token).await.unwrap().changed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_token() {
+        let (_fs, vault) = vault_with_notes(&["a.md"]).await;
+        let err = vault.changed_since(b"not a token").await.unwrap_err();
+        assert!(err.to_string().contains("Invalid change token"), "{}", err);
+    }
+}
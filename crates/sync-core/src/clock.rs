@@ -0,0 +1,96 @@
+//! Time sources for sync logic.
+//!
+//! Sync flag expiry and peer timestamps read time through a [`Clock`] so tests
+//! can swap in a [`TestClock`] and advance it by hand instead of sleeping.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A source of monotonic and wall-clock time.
+pub trait Clock: Send + Sync {
+    /// Monotonic time, for measuring how long ago something happened.
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time in milliseconds since the Unix epoch, for timestamps.
+    fn now_ms(&self) -> f64;
+}
+
+/// The real clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn now_ms(&self) -> f64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0)
+    }
+}
+
+/// A clock that only moves when advanced, for deterministic tests.
+///
+/// Wall-clock time starts at 0 ms; monotonic time starts at the moment the
+/// clock was created.
+#[derive(Debug)]
+pub struct TestClock {
+    start: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl TestClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+
+    /// Time advanced since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn now_ms(&self) -> f64 {
+        self.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_only_moves_when_advanced() {
+        let clock = TestClock::new();
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now_ms(), 0.0);
+
+        clock.advance(Duration::from_millis(1500));
+        assert_eq!(clock.now() - start, Duration::from_millis(1500));
+        assert_eq!(clock.now_ms(), 1500.0);
+    }
+}
@@ -0,0 +1,207 @@
+//! Persistent log of sync conflicts.
+//!
+//! Whenever an incoming update is merged with concurrent local edits, or two
+//! independently created documents are reconciled, a `ConflictRecord` is
+//! appended to `.sync/conflicts.log` (one JSON object per line) so users can
+//! review what was merged after the fact.
+
+use crate::fs::FileSystem;
+use crate::peer_id::PeerId;
+use crate::vault::{Result, Vault, VaultError};
+use serde::{Deserialize, Serialize};
+
+/// How a conflict was resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictResolution {
+    /// Concurrent edits on a shared history, merged by the CRDT
+    Merged,
+    /// Divergent histories, remote content replaced local content
+    RemoteWins,
+    /// Divergent histories, local content was newer and kept
+    LocalKept,
+}
+
+/// What happens when a peer deletes a note that has edits it hadn't seen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeleteEditPolicy {
+    /// The deletion is applied and the unseen edits are discarded
+    DeleteWins,
+    /// The note is kept and re-registered, so the deletion is undone everywhere
+    #[default]
+    EditWins,
+}
+
+/// A single entry in the conflict log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictRecord {
+    /// Path of the conflicting note
+    pub path: String,
+    /// Peers whose concurrent edits were involved (hex IDs, sorted)
+    pub peers: Vec<String>,
+    /// When the conflict was resolved, in milliseconds since Unix epoch
+    pub timestamp: f64,
+    /// How the conflict was resolved
+    pub resolution: ConflictResolution,
+    /// Lines present after resolution that weren't there before
+    pub lines_added: usize,
+    /// Lines present before resolution that are gone afterwards
+    pub lines_removed: usize,
+}
+
+impl ConflictRecord {
+    /// Build a record from the note's markdown before and after resolution.
+    pub fn new(
+        path: &str,
+        peers: Vec<String>,
+        timestamp: f64,
+        resolution: ConflictResolution,
+        before: &str,
+        after: &str,
+    ) -> Self {
+        let (lines_added, lines_removed) = line_changes(before, after);
+        Self {
+            path: path.to_string(),
+            peers,
+            timestamp,
+            resolution,
+            lines_added,
+            lines_removed,
+        }
+    }
+}
+
+/// Count added and removed lines between two texts, ignoring order.
+fn line_changes(before: &str, after: &str) -> (usize, usize) {
+    let mut remaining: Vec<&str> = before.lines().collect();
+    let mut added = 0;
+    for line in after.lines() {
+        match remaining.iter().position(|l| *l == line) {
+            Some(i) => {
+                remaining.swap_remove(i);
+            }
+            None => added += 1,
+        }
+    }
+    (added, remaining.len())
+}
+
+/// Sorted, de-duplicated hex IDs of the peers at the tips of a document's history.
+pub(crate) fn frontier_peers(frontiers: &loro::Frontiers) -> Vec<String> {
+    let mut peers: Vec<String> = frontiers
+        .iter()
+        .map(|id| PeerId::from(id.peer).to_string())
+        .collect();
+    peers.sort();
+    peers.dedup();
+    peers
+}
+
+/// Conflict log, one JSON `ConflictRecord` per line
+pub const CONFLICT_LOG: &str = ".sync/conflicts.log";
+
+impl<F: FileSystem> Vault<F> {
+    /// All recorded conflicts, oldest first.
+    ///
+    /// Lines that fail to parse (e.g. a truncated final write) are skipped.
+    pub async fn conflict_history(&self) -> Result<Vec<ConflictRecord>> {
+        if !self.fs.exists(CONFLICT_LOG).await? {
+            return Ok(Vec::new());
+        }
+        let bytes = self.fs.read(CONFLICT_LOG).await?;
+        let records = String::from_utf8_lossy(&bytes)
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        Ok(records)
+    }
+
+    /// Recorded conflicts for the note at `path`, oldest first.
+    pub async fn conflict_history_for(&self, path: &str) -> Result<Vec<ConflictRecord>> {
+        let mut records = self.conflict_history().await?;
+        records.retain(|record| record.path == path);
+        Ok(records)
+    }
+
+    /// Append a record to the conflict log.
+    pub(crate) async fn record_conflict(&self, record: &ConflictRecord) -> Result<()> {
+        let mut line = serde_json::to_string(record)
+            .map_err(|e| VaultError::Other(format!("Failed to encode conflict: {}", e)))?;
+        line.push('\n');
+        self.fs.append(CONFLICT_LOG, line.as_bytes()).await?;
+
+        tracing::info!(
+            "Recorded {:?} conflict for {} ({} peers)",
+            record.resolution,
+            record.path,
+            record.peers.len()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::InMemoryFs;
+
+    fn record(path: &str, timestamp: f64) -> ConflictRecord {
+        ConflictRecord::new(
+            path,
+            vec!["0000000000003039".into()],
+            timestamp,
+            ConflictResolution::Merged,
+            "a",
+            "a\nb",
+        )
+    }
+
+    #[test]
+    fn test_line_changes() {
+        assert_eq!(line_changes("a\nb\nc", "a\nb\nc"), (0, 0));
+        assert_eq!(line_changes("a\nb", "a\nb\nc"), (1, 0));
+        assert_eq!(line_changes("a\nb\nc", "a\nc"), (0, 1));
+        assert_eq!(line_changes("a\nb", "a\nx"), (1, 1));
+        // Duplicate lines are counted individually
+        assert_eq!(line_changes("a\na", "a"), (0, 1));
+    }
+
+    #[test]
+    fn test_record_serializes_camel_case() {
+        let record = ConflictRecord::new(
+            "note.md",
+            vec!["0000000000003039".into()],
+            1.0,
+            ConflictResolution::RemoteWins,
+            "old",
+            "new",
+        );
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["resolution"], "remoteWins");
+        assert_eq!(json["linesAdded"], 1);
+        assert_eq!(json["linesRemoved"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_conflict_history_for_filters_by_path() {
+        let vault = Vault::init(InMemoryFs::new(), PeerId::from(12345u64)).await.unwrap();
+        vault.record_conflict(&record("a.md", 1.0)).await.unwrap();
+        vault.record_conflict(&record("b.md", 2.0)).await.unwrap();
+        vault.record_conflict(&record("a.md", 3.0)).await.unwrap();
+
+        let history = vault.conflict_history_for("a.md").await.unwrap();
+        assert_eq!(history, vec![record("a.md", 1.0), record("a.md", 3.0)]);
+        assert_eq!(vault.conflict_history().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_conflict_history_for_note_without_conflicts() {
+        let vault = Vault::init(InMemoryFs::new(), PeerId::from(12345u64)).await.unwrap();
+        assert!(vault.conflict_history_for("a.md").await.unwrap().is_empty());
+
+        vault.record_conflict(&record("b.md", 1.0)).await.unwrap();
+        assert!(vault.conflict_history_for("a.md").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+//! Divergence diagnostics for support.
+//!
+//! When two peers disagree about a vault's content, one peer exports its state
+//! with `export_state` (the registry version, every file node's path, and every
+//! document's version vector; no note content) and the other compares it
+//! against its own with `diff_against`. The report says which documents differ
+//! and whether each side has edits the other hasn't seen.
+
+use crate::fs::FileSystem;
+use crate::vault::{Result, Vault, VaultError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How a local version relates to the remote one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum VersionRelation {
+    /// Both sides have the same operations
+    Equal,
+    /// Local has operations the remote hasn't seen, and nothing is missing
+    LocalAhead,
+    /// Remote has operations local hasn't seen, and nothing is missing
+    RemoteAhead,
+    /// Each side has operations the other hasn't seen
+    Concurrent,
+}
+
+/// A document whose versions differ between the two vaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDivergence {
+    pub path: String,
+    pub relation: VersionRelation,
+}
+
+/// Differences between this vault and an exported peer state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DivergenceReport {
+    /// How the registry (file tree) versions relate
+    pub registry: VersionRelation,
+    /// Documents present on both sides with different versions, sorted by path
+    pub documents: Vec<DocumentDivergence>,
+    /// Files registered only locally, sorted
+    pub only_local: Vec<String>,
+    /// Files registered only in the export, sorted
+    pub only_remote: Vec<String>,
+    /// File nodes at different paths on each side, as `(local, remote)` pairs
+    pub moved: Vec<(String, String)>,
+}
+
+impl DivergenceReport {
+    /// Returns true if the vaults agree.
+    pub fn is_empty(&self) -> bool {
+        self.registry == VersionRelation::Equal
+            && self.documents.is_empty()
+            && self.only_local.is_empty()
+            && self.only_remote.is_empty()
+            && self.moved.is_empty()
+    }
+}
+
+/// Version summary of a vault, as produced by `export_state`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VaultState {
+    /// Encoded registry version vector
+    registry: Vec<u8>,
+    /// File node ID (`peer`, `counter`) to path
+    files: BTreeMap<(u64, i32), String>,
+    /// Path to encoded document version vector
+    documents: BTreeMap<String, Vec<u8>>,
+}
+
+impl<F: FileSystem> Vault<F> {
+    /// Export the versions `diff_against` compares, for sharing with support.
+    ///
+    /// Contains paths and version vectors only, no note content.
+    pub async fn export_state(&self) -> Result<Vec<u8>> {
+        let state = self.state().await?;
+        bincode::serialize(&state)
+            .map_err(|e| VaultError::Other(format!("Failed to encode vault state: {}", e)))
+    }
+
+    /// Compare this vault with another vault's `export_state`.
+    pub async fn diff_against(&self, other_export: &[u8]) -> Result<DivergenceReport> {
+        let remote: VaultState = bincode::deserialize(other_export)
+            .map_err(|e| VaultError::Other(format!("Invalid vault export: {}", e)))?;
+        let local = self.state().await?;
+
+        let mut documents = Vec::new();
+        for (path, local_version) in &local.documents {
+            let Some(remote_version) = remote.documents.get(path) else {
+                continue;
+            };
+            let relation = Self::version_relation(local_version, remote_version);
+            if relation != VersionRelation::Equal {
+                documents.push(DocumentDivergence {
+                    path: path.clone(),
+                    relation,
+                });
+            }
+        }
+
+        let mut only_local = Vec::new();
+        let mut moved = Vec::new();
+        for (node, local_path) in &local.files {
+            match remote.files.get(node) {
+                None => only_local.push(local_path.clone()),
+                Some(remote_path) if remote_path != local_path => {
+                    moved.push((local_path.clone(), remote_path.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        let mut only_remote: Vec<String> = remote
+            .files
+            .iter()
+            .filter(|(node, _)| !local.files.contains_key(node))
+            .map(|(_, path)| path.clone())
+            .collect();
+
+        only_local.sort();
+        only_remote.sort();
+        moved.sort();
+
+        Ok(DivergenceReport {
+            registry: Self::version_relation(&local.registry, &remote.registry),
+            documents,
+            only_local,
+            only_remote,
+            moved,
+        })
+    }
+
+    /// How encoded version vector `local` relates to `remote`.
+    fn version_relation(local: &[u8], remote: &[u8]) -> VersionRelation {
+        match (
+            Self::version_includes(local, remote),
+            Self::version_includes(remote, local),
+        ) {
+            (true, true) => VersionRelation::Equal,
+            (true, false) => VersionRelation::LocalAhead,
+            (false, true) => VersionRelation::RemoteAhead,
+            (false, false) => VersionRelation::Concurrent,
+        }
+    }
+
+    async fn state(&self) -> Result<VaultState> {
+        let registry = self.registry().oplog_vv().encode();
+        let files = self
+            .path_to_node()
+            .iter()
+            .map(|(path, node)| ((node.peer, node.counter), path.clone()))
+            .collect();
+
+        let mut documents = BTreeMap::new();
+        for path in self.registered_files() {
+            if let Some(version) = self.current_version(&path).await? {
+                documents.insert(path, version);
+            }
+        }
+
+        Ok(VaultState {
+            registry,
+            files,
+            documents,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::InMemoryFs;
+    use crate::PeerId;
+    use std::sync::Arc;
+
+    type TestVault = Vault<Arc<InMemoryFs>>;
+
+    async fn edit(fs: &InMemoryFs, vault: &TestVault, path: &str, content: &str) {
+        fs.write(path, content.as_bytes()).await.unwrap();
+        vault.on_file_changed(path).await.unwrap();
+    }
+
+    /// Two vaults that have fully synced `paths`.
+    async fn synced_vaults(
+        paths: &[&str],
+    ) -> (Arc<InMemoryFs>, TestVault, Arc<InMemoryFs>, TestVault) {
+        let fs1 = Arc::new(InMemoryFs::new());
+        for path in paths {
+            fs1.write(path, format!("# {}", path).as_bytes())
+                .await
+                .unwrap();
+        }
+        let vault1 = Vault::init(Arc::clone(&fs1), PeerId::from(1u64))
+            .await
+            .unwrap();
+        let fs2 = Arc::new(InMemoryFs::new());
+        let vault2 = Vault::init(Arc::clone(&fs2), PeerId::from(2u64))
+            .await
+            .unwrap();
+        vault2.merge_with(&vault1).await.unwrap();
+        (fs1, vault1, fs2, vault2)
+    }
+
+    #[tokio::test]
+    async fn test_synced_vaults_do_not_diverge() {
+        let (_fs1, vault1, _fs2, vault2) = synced_vaults(&["a.md", "b.md"]).await;
+
+        let report = vault1
+            .diff_against(&vault2.export_state().await.unwrap())
+            .await
+            .unwrap();
+        assert!(report.is_empty(), "{:?}", report);
+    }
+
+    #[tokio::test]
+    async fn test_classifies_one_sided_and_concurrent_edits() {
+        let (fs1, vault1, fs2, vault2) = synced_vaults(&["a.md", "b.md", "c.md"]).await;
+
+        edit(&fs1, &vault1, "a.md", "# a.md\n\nlocal edit").await;
+        edit(&fs2, &vault2, "b.md", "# b.md\n\nremote edit").await;
+        edit(&fs1, &vault1, "c.md", "# c.md\n\nlocal edit").await;
+        edit(&fs2, &vault2, "c.md", "# c.md\n\nremote edit").await;
+        edit(&fs2, &vault2, "new.md", "# New").await;
+
+        let report = vault1
+            .diff_against(&vault2.export_state().await.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            report.documents,
+            vec![
+                DocumentDivergence {
+                    path: "a.md".into(),
+                    relation: VersionRelation::LocalAhead,
+                },
+                DocumentDivergence {
+                    path: "b.md".into(),
+                    relation: VersionRelation::RemoteAhead,
+                },
+                DocumentDivergence {
+                    path: "c.md".into(),
+                    relation: VersionRelation::Concurrent,
+                },
+            ]
+        );
+        assert_eq!(report.only_remote, vec!["new.md"]);
+        assert!(report.only_local.is_empty());
+        assert_eq!(report.registry, VersionRelation::RemoteAhead);
+    }
+
+    #[tokio::test]
+    async fn test_reports_moved_files() {
+        let (_fs1, vault1, _fs2, vault2) = synced_vaults(&["a.md"]).await;
+
+        vault2.rename_file("a.md", "archive/a.md").await.unwrap();
+
+        let report = vault1
+            .diff_against(&vault2.export_state().await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            report.moved,
+            vec![("a.md".to_string(), "archive/a.md".to_string())]
+        );
+        assert_eq!(report.registry, VersionRelation::RemoteAhead);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_export() {
+        let (_fs1, vault1, _fs2, _vault2) = synced_vaults(&[]).await;
+        assert!(vault1.diff_against(b"garbage").await.is_err());
+    }
+}
@@ -10,10 +10,13 @@
 
 use crate::markdown;
 use crate::PeerId;
-use loro::{ExportMode, Frontiers, LoroDoc, LoroMap, LoroText, UpdateOptions, VersionVector};
+use loro::{ExportMode, Frontiers, ID, LoroDoc, LoroMap, LoroText, UpdateOptions, VersionVector};
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use thiserror::Error;
 use tracing::{debug, error};
 use uuid::Uuid;
@@ -25,15 +28,59 @@ pub enum DocumentError {
 
     #[error("Serialization error: {0}")]
     Serialization(String),
+
+    #[error("Invalid version: {0}")]
+    InvalidVersion(String),
 }
 
 pub type Result<T> = std::result::Result<T, DocumentError>;
 
+/// One change in a document's history.
+///
+/// Each commit is recorded as its own change, so an entry is one checkpoint
+/// of the note: a file save, an import of local edits, or a remote edit.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct HistoryEntry {
+    /// Version token for `markdown_at_version`, `<peer>:<counter>`
+    pub version: String,
+    /// Peer that made the change
+    pub peer: PeerId,
+    /// Lamport timestamp, for ordering changes across peers
+    pub lamport: u32,
+    /// Unix timestamp in seconds, if the change recorded one
+    pub timestamp: Option<i64>,
+}
+
+/// Parse a `<peer>:<counter>` version token into the ID of its last op.
+fn parse_version(version: &str) -> Result<ID> {
+    let invalid = || DocumentError::InvalidVersion(version.to_string());
+    let (peer, counter) = version.split_once(':').ok_or_else(invalid)?;
+    let peer: PeerId = peer.parse().map_err(|_| invalid())?;
+    let counter = counter.parse().map_err(|_| invalid())?;
+    Ok(ID::new(peer.as_u64(), counter))
+}
+
+/// A Loro document that attributes changes to `peer_id`.
+///
+/// Timestamps are recorded and commits are never merged into earlier changes,
+/// so every commit shows up in `NoteDocument::history`.
+fn new_loro_doc(peer_id: PeerId) -> LoroDoc {
+    let doc = LoroDoc::new();
+    doc.set_peer_id(peer_id.as_u64()).ok();
+    doc.set_record_timestamp(true);
+    doc.set_change_merge_interval(-1);
+    doc
+}
+
 /// A single note (markdown file) as a Loro document
+///
+/// Clones share the underlying Loro document and its dirty flag.
 #[derive(Clone)]
 pub struct NoteDocument {
     doc: LoroDoc,
     path: String,
+    /// Changed since the `.loro` file was last written
+    dirty: Arc<AtomicBool>,
 }
 
 impl NoteDocument {
@@ -44,8 +91,7 @@ impl NoteDocument {
     /// Use `from_markdown()` to create a new document with original content and doc_id.
     /// The peer_id must be set before any operations to ensure consistent version vectors.
     pub fn new(path: &str, peer_id: PeerId) -> Self {
-        let doc = LoroDoc::new();
-        doc.set_peer_id(peer_id.as_u64()).ok();
+        let doc = new_loro_doc(peer_id);
 
         // Set path metadata only - doc_id comes from imported content or from_markdown()
         let meta = doc.get_map("_meta");
@@ -55,13 +101,18 @@ impl NoteDocument {
         Self {
             doc,
             path: path.to_string(),
+            dirty: Arc::new(AtomicBool::new(true)),
         }
     }
 
     /// Create a NoteDocument by importing from existing Loro bytes.
     ///
-    /// The peer_id is set before import so any new operations (like path metadata updates)
-    /// are attributed to this peer. Imported operations preserve their original peer IDs.
+    /// The peer_id is set before import so any later operations are attributed to
+    /// this peer. Imported operations preserve their original peer IDs.
+    ///
+    /// Nothing is written on load: `bytes` may be missing operations this peer
+    /// made in its own copy, and a new operation here would reuse their IDs.
+    /// Use `update_path()` to record a move.
     pub fn from_bytes(path: &str, bytes: &[u8], peer_id: PeerId) -> Result<Self> {
         debug!(
             path = %path,
@@ -69,8 +120,7 @@ impl NoteDocument {
             "loro_from_bytes: starting import"
         );
 
-        let doc = LoroDoc::new();
-        doc.set_peer_id(peer_id.as_u64()).ok();
+        let doc = new_loro_doc(peer_id);
         doc.import(bytes).map_err(|e| {
             error!(
                 path = %path,
@@ -88,15 +138,10 @@ impl NoteDocument {
             "loro_from_bytes: import complete"
         );
 
-        // Update path metadata (this is intentional - records the current path)
-        let meta = doc.get_map("_meta");
-        meta.insert("path", path)
-            .map_err(|e| DocumentError::Loro(e.to_string()))?;
-        doc.commit();
-
         Ok(Self {
             doc,
             path: path.to_string(),
+            dirty: Arc::new(AtomicBool::new(false)),
         })
     }
 
@@ -145,6 +190,7 @@ impl NoteDocument {
             .map_err(|e| DocumentError::Loro(e.to_string()))?;
         self.path = new_path.to_string();
         self.doc.commit();
+        self.mark_dirty();
         Ok(())
     }
 
@@ -172,8 +218,7 @@ impl NoteDocument {
     /// Generates a unique `doc_id` to track document lineage across syncs.
     /// The peer_id must be set before any operations to ensure consistent version vectors.
     pub fn from_markdown(path: &str, content: &str, peer_id: PeerId) -> Result<Self> {
-        let doc = LoroDoc::new();
-        doc.set_peer_id(peer_id.as_u64()).ok();
+        let doc = new_loro_doc(peer_id);
         let parsed = markdown::parse(content);
 
         // Set internal metadata with unique doc_id
@@ -205,6 +250,7 @@ impl NoteDocument {
         Ok(Self {
             doc,
             path: path.to_string(),
+            dirty: Arc::new(AtomicBool::new(true)),
         })
     }
 
@@ -242,6 +288,11 @@ impl NoteDocument {
         self.doc.state_vv()
     }
 
+    /// Make later edits under another peer ID.
+    pub fn set_peer_id(&self, peer_id: PeerId) {
+        self.doc.set_peer_id(peer_id.as_u64()).ok();
+    }
+
     /// Get current frontiers (tips of the DAG)
     pub fn frontiers(&self) -> Frontiers {
         self.doc.state_frontiers()
@@ -292,6 +343,10 @@ impl NoteDocument {
             "loro_import: complete"
         );
 
+        if vv_after != vv_before {
+            self.mark_dirty();
+        }
+
         // Update local path cache from imported metadata if present
         if let Some(stored) = self.stored_path() {
             self.path = stored;
@@ -315,6 +370,21 @@ impl NoteDocument {
         self.doc.commit();
     }
 
+    /// Whether the document changed since it was last written to disk.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Relaxed)
+    }
+
+    /// Flag the document as needing to be written.
+    pub fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Record that the document's current state has been written.
+    pub fn mark_clean(&self) {
+        self.dirty.store(false, Ordering::Relaxed);
+    }
+
     // ========== Debug API Methods ==========
 
     /// Get the number of changes in the document's oplog.
@@ -327,6 +397,54 @@ impl NoteDocument {
         self.doc.len_ops()
     }
 
+    /// Every change in the oplog, oldest first.
+    ///
+    /// Changes are ordered by Lamport timestamp, with ties (concurrent edits
+    /// from different peers) broken by peer ID.
+    pub fn history(&self) -> Vec<HistoryEntry> {
+        let heads = self.doc.oplog_frontiers().to_vec();
+        let mut changes = Vec::new();
+        self.doc
+            .travel_change_ancestors(&heads, &mut |change| {
+                changes.push(change);
+                ControlFlow::Continue(())
+            })
+            .ok();
+
+        let mut entries: Vec<HistoryEntry> = changes
+            .into_iter()
+            .map(|change| {
+                let peer = PeerId::from(change.id.peer);
+                let last_counter = change.id.counter + change.len as i32 - 1;
+                HistoryEntry {
+                    version: format!("{}:{}", peer, last_counter),
+                    peer,
+                    lamport: change.lamport,
+                    timestamp: (change.timestamp != 0).then_some(change.timestamp),
+                }
+            })
+            .collect();
+        entries.sort_by_key(|entry| (entry.lamport, entry.peer.as_u64()));
+        entries
+    }
+
+    /// The note's markdown as of a `version` token from `history`.
+    ///
+    /// Reads from a fork, so this document stays at its current version.
+    pub fn markdown_at_version(&self, version: &str) -> Result<String> {
+        let frontiers = Frontiers::from(parse_version(version)?);
+        let fork = self.doc.fork();
+        fork.checkout(&frontiers)
+            .map_err(|_| DocumentError::InvalidVersion(version.to_string()))?;
+
+        let snapshot = Self {
+            doc: fork,
+            path: self.path.clone(),
+            dirty: Arc::new(AtomicBool::new(false)),
+        };
+        Ok(snapshot.to_markdown())
+    }
+
     /// Update the body text by computing and applying a line-based diff.
     ///
     /// Uses Loro's built-in `update_by_line()` which computes line-based diffs
@@ -366,6 +484,7 @@ impl NoteDocument {
             "update_body: complete"
         );
 
+        self.mark_dirty();
         Ok(true) // Changes applied (commit happens in caller)
     }
 
@@ -416,6 +535,9 @@ impl NoteDocument {
             }
         }
 
+        if changed {
+            self.mark_dirty();
+        }
         Ok(changed) // Commit happens in caller
     }
 }
@@ -510,6 +632,33 @@ World"#;
         assert_eq!(doc2.body().to_string(), "Hello");
     }
 
+    #[test]
+    fn test_history_lists_each_edit() {
+        let doc = NoteDocument::from_markdown("test.md", "First", test_peer_id()).unwrap();
+        doc.update_body("Second").unwrap();
+        doc.commit();
+        doc.update_body("Third").unwrap();
+        doc.commit();
+
+        let history = doc.history();
+        assert_eq!(history.len(), 3);
+        assert!(history.iter().all(|entry| entry.peer == test_peer_id()));
+        assert!(history.iter().all(|entry| entry.timestamp.is_some()));
+        assert!(history.windows(2).all(|w| w[0].lamport < w[1].lamport));
+
+        assert_eq!(doc.markdown_at_version(&history[0].version).unwrap(), "First");
+        assert_eq!(doc.markdown_at_version(&history[1].version).unwrap(), "Second");
+        // Reading an old version leaves the document at the latest one
+        assert_eq!(doc.body().to_string(), "Third");
+    }
+
+    #[test]
+    fn test_markdown_at_unknown_version_fails() {
+        let doc = NoteDocument::from_markdown("test.md", "First", test_peer_id()).unwrap();
+        assert!(doc.markdown_at_version("not-a-version").is_err());
+        assert!(doc.markdown_at_version("00000000000003e7:0").is_err());
+    }
+
     #[test]
     fn test_update_body_with_update_by_line() {
         // Test that update_body (using update_by_line) works correctly
@@ -5,6 +5,7 @@
 //! - Native: `Arc<EventBus>` with `RwLock` for multi-threaded Tokio runtime
 //! - WASM: `Rc<EventBus>` with `RefCell` for single-threaded browser environment
 
+use crate::conflicts::DeleteEditPolicy;
 use serde::Serialize;
 
 /// Sync events emitted during sync operations for real-time monitoring.
@@ -50,6 +51,42 @@ pub enum SyncEvent {
         /// When the operation occurred, in milliseconds since Unix epoch.
         timestamp: f64,
     },
+    /// A note was moved to different paths on two peers at once.
+    ///
+    /// The registry tree keeps one of the moves on every peer; this peer's move
+    /// lost and its file was moved to the resolved path.
+    MoveConflict {
+        /// Path both peers moved the note from.
+        path: String,
+        /// Where this peer had moved the note.
+        #[serde(rename = "localPath")]
+        local_path: String,
+        /// Where the note ended up on every peer.
+        #[serde(rename = "resolvedPath")]
+        resolved_path: String,
+        /// When the conflict was resolved, in milliseconds since Unix epoch.
+        timestamp: f64,
+    },
+    /// A peer deleted a note that has edits it hadn't seen.
+    DeleteEditConflict {
+        /// Path of the deleted note.
+        path: String,
+        /// How the conflict was resolved.
+        policy: DeleteEditPolicy,
+        /// When the conflict was resolved, in milliseconds since Unix epoch.
+        timestamp: f64,
+    },
+    /// A note's body is over the size limit, so its changes aren't synced.
+    NoteTooLarge {
+        /// Path of the note.
+        path: String,
+        /// Body size in bytes.
+        size: usize,
+        /// Configured limit in bytes.
+        limit: usize,
+        /// When the note was rejected, in milliseconds since Unix epoch.
+        timestamp: f64,
+    },
     /// A peer connected (handshake complete).
     PeerConnected {
         /// Peer's unique identifier (from handshake).
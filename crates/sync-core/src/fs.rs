@@ -42,6 +42,22 @@ pub struct FileStat {
     pub size: u64,
     /// Whether this is a directory
     pub is_dir: bool,
+    /// Hash of the file contents (`content_hash`), if the implementation
+    /// knows it without re-reading the file
+    pub content_hash: Option<u64>,
+}
+
+/// FNV-1a hash of file contents, as reported in `FileStat::content_hash`.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
 }
 
 /// Directory entry
@@ -81,6 +97,24 @@ pub trait FileSystem: Send + Sync {
 
     /// Create directory (and parents if needed)
     async fn mkdir(&self, path: &str) -> Result<()>;
+
+    /// List every file under `prefix`, recursively.
+    ///
+    /// `prefix` is a directory path ("" for the whole vault); entry names are
+    /// full paths from the vault root and directories are not included.
+    /// A missing prefix yields an empty list. The default walks `list`;
+    /// implementations can override it with something cheaper.
+    async fn list_recursive(&self, prefix: &str) -> Result<Vec<FileEntry>> {
+        walk_files(self, prefix).await
+    }
+
+    /// Append to a file, creating it (and parent directories) if needed.
+    ///
+    /// The default reads the whole file and writes it back; implementations
+    /// that can append in place should override it.
+    async fn append(&self, path: &str, content: &[u8]) -> Result<()> {
+        append_by_rewrite(self, path, content).await
+    }
 }
 
 /// Platform-independent filesystem abstraction (WASM version without Send + Sync).
@@ -108,6 +142,71 @@ pub trait FileSystem {
 
     /// Create directory (and parents if needed)
     async fn mkdir(&self, path: &str) -> Result<()>;
+
+    /// List every file under `prefix`, recursively.
+    ///
+    /// `prefix` is a directory path ("" for the whole vault); entry names are
+    /// full paths from the vault root and directories are not included.
+    /// A missing prefix yields an empty list. The default walks `list`;
+    /// implementations can override it with something cheaper.
+    async fn list_recursive(&self, prefix: &str) -> Result<Vec<FileEntry>> {
+        walk_files(self, prefix).await
+    }
+
+    /// Append to a file, creating it (and parent directories) if needed.
+    ///
+    /// The default reads the whole file and writes it back; implementations
+    /// that can append in place should override it.
+    async fn append(&self, path: &str, content: &[u8]) -> Result<()> {
+        append_by_rewrite(self, path, content).await
+    }
+}
+
+/// Append by reading the file (if any) and writing it back with `content` added.
+async fn append_by_rewrite<F: FileSystem + ?Sized>(
+    fs: &F,
+    path: &str,
+    content: &[u8],
+) -> Result<()> {
+    let mut data = if fs.exists(path).await? {
+        fs.read(path).await?
+    } else {
+        Vec::new()
+    };
+    data.extend_from_slice(content);
+    fs.write(path, &data).await
+}
+
+/// Recursively collect files under `prefix` using `FileSystem::list`.
+async fn walk_files<F: FileSystem + ?Sized>(fs: &F, prefix: &str) -> Result<Vec<FileEntry>> {
+    let root = prefix.trim_matches('/').to_string();
+    if !root.is_empty() && !fs.exists(&root).await? {
+        return Ok(Vec::new());
+    }
+
+    let mut files = Vec::new();
+    let mut dirs_to_visit = vec![root];
+
+    while let Some(dir) = dirs_to_visit.pop() {
+        for entry in fs.list(&dir).await? {
+            let path = if dir.is_empty() {
+                entry.name
+            } else {
+                format!("{}/{}", dir, entry.name)
+            };
+
+            if entry.is_dir {
+                dirs_to_visit.push(path);
+            } else {
+                files.push(FileEntry {
+                    name: path,
+                    is_dir: false,
+                });
+            }
+        }
+    }
+
+    Ok(files)
 }
 
 /// In-memory filesystem for testing
@@ -198,6 +297,27 @@ impl FileSystem for InMemoryFs {
         Ok(())
     }
 
+    async fn append(&self, path: &str, content: &[u8]) -> Result<()> {
+        let path = Self::normalize_path(path);
+
+        // Create parent directories
+        if let Some(parent) = Self::parent_path(&path) {
+            self.mkdir(&parent).await?;
+        }
+
+        let mut files = self.files.write().unwrap();
+        files
+            .entry(path.clone())
+            .or_default()
+            .extend_from_slice(content);
+        drop(files);
+
+        // Update mtime
+        let mut mtimes = self.mtimes.write().unwrap();
+        mtimes.insert(path, Self::current_time_ms());
+        Ok(())
+    }
+
     async fn list(&self, path: &str) -> Result<Vec<FileEntry>> {
         let path = Self::normalize_path(path);
         let prefix = if path.is_empty() {
@@ -298,6 +418,7 @@ impl FileSystem for InMemoryFs {
                 mtime_millis: mtime,
                 size: content.len() as u64,
                 is_dir: false,
+                content_hash: Some(content_hash(content)),
             });
         }
 
@@ -307,6 +428,7 @@ impl FileSystem for InMemoryFs {
                 mtime_millis: 0,
                 size: 0,
                 is_dir: true,
+                content_hash: None,
             });
         }
 
@@ -328,6 +450,21 @@ impl FileSystem for InMemoryFs {
         dirs.insert(path, ());
         Ok(())
     }
+
+    async fn list_recursive(&self, prefix: &str) -> Result<Vec<FileEntry>> {
+        let prefix = Self::normalize_path(prefix);
+        let dir_prefix = format!("{}/", prefix);
+
+        let files = self.files.read().unwrap();
+        Ok(files
+            .keys()
+            .filter(|path| prefix.is_empty() || path.starts_with(&dir_prefix))
+            .map(|path| FileEntry {
+                name: path.clone(),
+                is_dir: false,
+            })
+            .collect())
+    }
 }
 
 // Implement FileSystem for Arc<T> where T: FileSystem
@@ -363,6 +500,14 @@ impl<T: FileSystem + Send + Sync> FileSystem for std::sync::Arc<T> {
     async fn mkdir(&self, path: &str) -> Result<()> {
         (**self).mkdir(path).await
     }
+
+    async fn list_recursive(&self, prefix: &str) -> Result<Vec<FileEntry>> {
+        (**self).list_recursive(prefix).await
+    }
+
+    async fn append(&self, path: &str, content: &[u8]) -> Result<()> {
+        (**self).append(path, content).await
+    }
 }
 
 #[cfg(test)]
@@ -411,4 +556,93 @@ mod tests {
         assert_eq!(entries[0].name, "c.txt");
         assert!(!entries[0].is_dir);
     }
+
+    fn sorted_names(entries: Vec<FileEntry>) -> Vec<String> {
+        assert!(entries.iter().all(|e| !e.is_dir));
+        let mut names: Vec<String> = entries.into_iter().map(|e| e.name).collect();
+        names.sort();
+        names
+    }
+
+    async fn nested_fs() -> InMemoryFs {
+        let fs = InMemoryFs::new();
+        fs.write("root.md", b"").await.unwrap();
+        fs.write("notes/a.md", b"").await.unwrap();
+        fs.write("notes/deep/b.md", b"").await.unwrap();
+        fs.write("notes-archive/c.md", b"").await.unwrap();
+        fs.mkdir("empty").await.unwrap();
+        fs
+    }
+
+    /// Exposes only the required methods, so `list_recursive` and `append` use
+    /// the defaults.
+    struct ListOnly(InMemoryFs);
+
+    #[async_trait]
+    impl FileSystem for ListOnly {
+        async fn read(&self, path: &str) -> Result<Vec<u8>> {
+            self.0.read(path).await
+        }
+        async fn write(&self, path: &str, content: &[u8]) -> Result<()> {
+            self.0.write(path, content).await
+        }
+        async fn list(&self, path: &str) -> Result<Vec<FileEntry>> {
+            self.0.list(path).await
+        }
+        async fn delete(&self, path: &str) -> Result<()> {
+            self.0.delete(path).await
+        }
+        async fn exists(&self, path: &str) -> Result<bool> {
+            self.0.exists(path).await
+        }
+        async fn stat(&self, path: &str) -> Result<FileStat> {
+            self.0.stat(path).await
+        }
+        async fn mkdir(&self, path: &str) -> Result<()> {
+            self.0.mkdir(path).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_recursive_returns_nested_files() {
+        let fs = nested_fs().await;
+        let all = vec!["notes-archive/c.md", "notes/a.md", "notes/deep/b.md", "root.md"];
+
+        assert_eq!(sorted_names(fs.list_recursive("").await.unwrap()), all);
+
+        let walked = ListOnly(nested_fs().await);
+        assert_eq!(sorted_names(walked.list_recursive("").await.unwrap()), all);
+    }
+
+    async fn check_append(fs: &impl FileSystem) {
+        fs.append("logs/Log.md", b"first\n").await.unwrap();
+        assert!(fs.exists("logs").await.unwrap());
+        assert_eq!(fs.read("logs/Log.md").await.unwrap(), b"first\n");
+
+        fs.append("logs/Log.md", b"second\n").await.unwrap();
+        assert_eq!(fs.read("logs/Log.md").await.unwrap(), b"first\nsecond\n");
+    }
+
+    #[tokio::test]
+    async fn test_append_creates_and_extends_files() {
+        check_append(&InMemoryFs::new()).await;
+        check_append(&ListOnly(InMemoryFs::new())).await;
+    }
+
+    #[tokio::test]
+    async fn test_list_recursive_respects_prefix() {
+        let fs = nested_fs().await;
+        let walked = ListOnly(nested_fs().await);
+
+        for prefix in ["notes", "notes/", "/notes"] {
+            let expected = vec!["notes/a.md", "notes/deep/b.md"];
+            assert_eq!(sorted_names(fs.list_recursive(prefix).await.unwrap()), expected);
+            assert_eq!(sorted_names(walked.list_recursive(prefix).await.unwrap()), expected);
+        }
+
+        assert_eq!(sorted_names(fs.list_recursive("notes/deep").await.unwrap()), vec!["notes/deep/b.md"]);
+        assert!(fs.list_recursive("empty").await.unwrap().is_empty());
+        assert!(fs.list_recursive("missing").await.unwrap().is_empty());
+        assert!(walked.list_recursive("missing").await.unwrap().is_empty());
+    }
 }
@@ -9,28 +9,46 @@
 //! - Sync protocol between peers
 //! - FileSystem and SyncTransport trait abstractions
 
+pub mod changes;
+pub mod clock;
+pub mod conflicts;
+pub mod divergence;
 pub mod document;
 pub mod events;
 pub mod fs;
 pub mod markdown;
+pub mod merge;
 pub mod peer_id;
+pub mod peer_versions;
 pub mod peers;
 pub mod protocol;
+pub mod registry_log;
+pub mod scope;
 pub mod swim;
 pub mod sync;
 pub mod sync_engine;
 pub mod transport;
 pub mod vault;
 
-pub use document::NoteDocument;
+pub use changes::ChangesSince;
+pub use clock::{Clock, SystemClock, TestClock};
+pub use conflicts::{ConflictRecord, ConflictResolution, DeleteEditPolicy};
+pub use divergence::{DivergenceReport, DocumentDivergence, VersionRelation};
+pub use document::{HistoryEntry, NoteDocument};
 pub use events::{EventBus, Subscription, SyncEvent};
 pub use fs::{FileEntry, FileStat, FileSystem, InMemoryFs};
+pub use merge::MergeReport;
 pub use peer_id::{PeerId, PeerIdError};
 pub use protocol::{
     GossipMessage, Handshake, HandshakeRole, PeerMessage, SyncEnvelope, MAX_MESSAGE_SIZE,
     PROTOCOL_VERSION,
 };
 pub use peers::{ConnectedPeer, ConnectionDirection, PeerError, PeerRegistry};
+pub use registry_log::RegistryCompaction;
+pub use scope::SyncScope;
 pub use sync::SyncMessage;
-pub use transport::{PeerConnection, PeerInfo, SyncTransport};
-pub use vault::Vault;
+pub use transport::{ChannelTransport, PeerConnection, PeerInfo, SyncTransport};
+pub use vault::{
+    document_sync_path, PathValidationError, SyncFlagStats, Vault, VaultError, DEFAULT_FLAG_TTL,
+    DEFAULT_MAX_NOTE_SIZE,
+};
@@ -0,0 +1,166 @@
+//! Offline vault-to-vault merge.
+//!
+//! Merges two copies of a vault that were edited independently (e.g. a laptop
+//! and a phone that never synced) without a network connection. Both vaults run
+//! the regular SyncRequest → SyncExchange → SyncResponse round-trip in-process,
+//! so registry and document CRDTs are merged by the same import logic used for
+//! live sync, and both vaults end up with the same state on disk.
+
+use crate::conflicts::ConflictRecord;
+use crate::fs::FileSystem;
+use crate::sync_engine::Result;
+use crate::vault::Vault;
+use loro::TreeID;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// What changed in the local vault as a result of a merge.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeReport {
+    /// Files that only existed in the other vault
+    pub added: Vec<String>,
+    /// Files deleted in the other vault
+    pub removed: Vec<String>,
+    /// Files moved or renamed in the other vault, as `(old, new)` pairs
+    pub renamed: Vec<(String, String)>,
+    /// Files whose content changed
+    pub updated: Vec<String>,
+    /// Conflicts recorded in the local vault while merging
+    pub conflicts: Vec<ConflictRecord>,
+}
+
+impl MergeReport {
+    /// Returns true if the merge changed anything locally.
+    pub fn has_changes(&self) -> bool {
+        !self.added.is_empty()
+            || !self.removed.is_empty()
+            || !self.renamed.is_empty()
+            || !self.updated.is_empty()
+    }
+}
+
+impl<F: FileSystem> Vault<F> {
+    /// Merge another vault into this one, and this one into the other.
+    ///
+    /// The report describes changes from this vault's point of view. Conflicts
+    /// are also appended to each vault's conflict log as usual.
+    pub async fn merge_with<G: FileSystem>(&self, other: &Vault<G>) -> Result<MergeReport> {
+        let before = self.node_paths();
+        let conflicts_before = self.conflict_history().await?.len();
+
+        let mut updated = BTreeSet::new();
+        let (ours, _) = sync_round(self, other).await?;
+        updated.extend(ours);
+        // A response leaves out notes its sender just received, so our side of
+        // a note both vaults changed only reaches the other in a second round
+        let (_, ours) = sync_round(other, self).await?;
+        updated.extend(ours);
+
+        let after = self.node_paths();
+        let mut report = MergeReport::default();
+        for (node, new_path) in &after {
+            match before.get(node) {
+                None => report.added.push(new_path.clone()),
+                Some(old_path) if old_path != new_path => {
+                    report.renamed.push((old_path.clone(), new_path.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for (node, old_path) in &before {
+            if !after.contains_key(node) {
+                report.removed.push(old_path.clone());
+            }
+        }
+
+        // Added and renamed files are already reported; keep `updated` to content edits
+        for path in report.added.iter().chain(report.renamed.iter().map(|(_, new)| new)) {
+            updated.remove(path);
+        }
+        let live: BTreeSet<&String> = after.values().collect();
+        report.updated = updated.into_iter().filter(|p| live.contains(p)).collect();
+
+        report.added.sort();
+        report.removed.sort();
+        report.renamed.sort();
+        report.conflicts = self
+            .conflict_history()
+            .await?
+            .into_iter()
+            .skip(conflicts_before)
+            .collect();
+
+        tracing::info!(
+            "Merged vaults: {} added, {} removed, {} renamed, {} updated, {} conflicts",
+            report.added.len(),
+            report.removed.len(),
+            report.renamed.len(),
+            report.updated.len(),
+            report.conflicts.len()
+        );
+        Ok(report)
+    }
+
+    /// Current path of every registered file node.
+    pub(crate) fn node_paths(&self) -> HashMap<TreeID, String> {
+        self.path_to_node()
+            .iter()
+            .map(|(path, node)| (*node, path.clone()))
+            .collect()
+    }
+}
+
+/// One SyncRequest → SyncExchange → SyncResponse round-trip started by `from`.
+///
+/// Returns the paths modified in `from` and in `to`.
+async fn sync_round<A: FileSystem, B: FileSystem>(
+    from: &Vault<A>,
+    to: &Vault<B>,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let request = from.prepare_sync_request().await?;
+    let (exchange, _) = to.process_sync_message(&request).await?;
+    let Some(exchange) = exchange else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+    let (response, from_modified) = from.process_sync_message(&exchange).await?;
+    let to_modified = match response {
+        Some(response) => to.process_sync_message(&response).await?.1,
+        None => Vec::new(),
+    };
+    Ok((from_modified, to_modified))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::InMemoryFs;
+    use crate::peer_id::PeerId;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_empty_report_has_no_changes() {
+        assert!(!MergeReport::default().has_changes());
+
+        let report = MergeReport {
+            renamed: vec![("a.md".into(), "b.md".into())],
+            ..Default::default()
+        };
+        assert!(report.has_changes());
+    }
+
+    #[tokio::test]
+    async fn test_merge_identical_vaults_reports_nothing() {
+        let fs1 = Arc::new(InMemoryFs::new());
+        fs1.write("note.md", b"# Note").await.unwrap();
+        let vault1 = Vault::init(Arc::clone(&fs1), PeerId::from(1)).await.unwrap();
+        let vault2 = Vault::init(Arc::new(InMemoryFs::new()), PeerId::from(2))
+            .await
+            .unwrap();
+        vault2.merge_with(&vault1).await.unwrap();
+
+        let report = vault1.merge_with(&vault2).await.unwrap();
+        assert!(!report.has_changes());
+        assert!(report.conflicts.is_empty());
+    }
+}
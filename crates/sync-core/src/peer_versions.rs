@@ -0,0 +1,239 @@
+//! Per-peer versions, for "who is up to date on this note?"
+//!
+//! Peers send the version vector of the registry and every document in their
+//! sync requests.
+//! When a message is processed with `process_sync_message_from`, those
+//! versions are remembered as the peer's acknowledged state, and
+//! `document_sync_peers` compares them with our current document versions.
+//! Versions are only as fresh as the peer's last sync request; updates pushed
+//! since then aren't counted until the peer syncs again.
+//!
+//! Registry versions are also saved to `.sync/registry-acks.json`, so after a
+//! restart `compact_registry` still knows about peers that are offline and
+//! behind, including ones that never wrote to the registry.
+
+use crate::fs::FileSystem;
+use crate::vault::{Result, Vault, VaultError};
+use crate::PeerId;
+use std::collections::{BTreeMap, HashMap};
+
+/// Registry version each peer last reported, as JSON keyed by peer ID
+pub const REGISTRY_ACKS_FILE: &str = ".sync/registry-acks.json";
+
+/// Encoded versions a peer last reported.
+#[derive(Debug, Default)]
+pub(crate) struct PeerVersions {
+    registry: Option<Vec<u8>>,
+    documents: HashMap<String, Vec<u8>>,
+}
+
+impl<F: FileSystem> Vault<F> {
+    /// Remember the registry and document versions `peer` reported in a sync request.
+    ///
+    /// Replaces the versions previously recorded for the registry and those paths.
+    /// Returns whether the peer's registry version changed, in which case it
+    /// should be persisted with `save_registry_acks`.
+    pub fn record_peer_versions(
+        &self,
+        peer: PeerId,
+        registry_version: &[u8],
+        document_versions: &HashMap<String, Vec<u8>>,
+    ) -> bool {
+        let mut peer_versions = self.peer_versions.lock().unwrap();
+        let known = peer_versions.entry(peer).or_default();
+        let changed = known.registry.as_deref() != Some(registry_version);
+        known.registry = Some(registry_version.to_vec());
+        for (path, version) in document_versions {
+            known.documents.insert(path.clone(), version.clone());
+        }
+        changed
+    }
+
+    /// Persist the registry version every peer last reported.
+    pub(crate) async fn save_registry_acks(&self) -> Result<()> {
+        let acks: BTreeMap<String, Vec<u8>> = self
+            .peer_versions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(peer, known)| Some((peer.to_string(), known.registry.clone()?)))
+            .collect();
+        let json = serde_json::to_vec(&acks)
+            .map_err(|e| VaultError::Other(format!("Failed to encode registry acks: {}", e)))?;
+        self.fs.write(REGISTRY_ACKS_FILE, &json).await?;
+        Ok(())
+    }
+
+    /// Restore the registry versions saved by `save_registry_acks`, if any.
+    ///
+    /// Fails on an unreadable file rather than forgetting peers, since that
+    /// would let `compact_registry` drop history they still need.
+    pub(crate) async fn load_registry_acks(&self) -> Result<()> {
+        if !self.fs.exists(REGISTRY_ACKS_FILE).await? {
+            return Ok(());
+        }
+        let bytes = self.fs.read(REGISTRY_ACKS_FILE).await?;
+        let acks: BTreeMap<String, Vec<u8>> = serde_json::from_slice(&bytes).map_err(|e| {
+            VaultError::Other(format!("Invalid registry acks in {}: {}", REGISTRY_ACKS_FILE, e))
+        })?;
+
+        let mut peer_versions = self.peer_versions.lock().unwrap();
+        for (peer, version) in acks {
+            let peer: PeerId = peer.parse().map_err(|e| {
+                VaultError::Other(format!("Invalid peer in {}: {}", REGISTRY_ACKS_FILE, e))
+            })?;
+            peer_versions.entry(peer).or_default().registry = Some(version);
+        }
+        Ok(())
+    }
+
+    /// The encoded version of `path` that `peer` last reported, if any.
+    pub fn peer_document_version(&self, peer: PeerId, path: &str) -> Option<Vec<u8>> {
+        self.peer_versions
+            .lock()
+            .unwrap()
+            .get(&peer)
+            .and_then(|known| known.documents.get(path))
+            .cloned()
+    }
+
+    /// The encoded registry version `peer` last reported, if any.
+    pub fn peer_registry_version(&self, peer: PeerId) -> Option<Vec<u8>> {
+        self.peer_versions
+            .lock()
+            .unwrap()
+            .get(&peer)
+            .and_then(|known| known.registry.clone())
+    }
+
+    /// Peers with recorded versions, whether or not they're still connected.
+    pub(crate) fn peers_with_versions(&self) -> Vec<PeerId> {
+        self.peer_versions.lock().unwrap().keys().copied().collect()
+    }
+
+    /// Whether each connected peer has our current version of `path`.
+    ///
+    /// Returns `(peer, up_to_date)` for every connected peer, sorted by peer ID.
+    /// A peer is up to date when its last reported version includes ours;
+    /// peers that never reported a version for `path` aren't. Returns an empty
+    /// list if `path` has no document.
+    pub async fn document_sync_peers(&self, path: &str) -> Result<Vec<(PeerId, bool)>> {
+        let Some(current) = self.get_document_version(path).await? else {
+            return Ok(Vec::new());
+        };
+
+        let mut peers: Vec<(PeerId, bool)> = self
+            .get_connected_peers()
+            .iter()
+            .filter_map(|peer| peer.id.parse().ok())
+            .map(|peer| {
+                let up_to_date = self
+                    .peer_document_version(peer, path)
+                    .is_some_and(|known| Self::version_includes(&known, &current));
+                (peer, up_to_date)
+            })
+            .collect();
+        peers.sort_by_key(|(peer, _)| peer.as_u64());
+        Ok(peers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::InMemoryFs;
+    use crate::peers::ConnectionDirection;
+    use crate::{FileSystem, PeerId, Vault};
+    use std::sync::Arc;
+
+    type TestVault = Vault<Arc<InMemoryFs>>;
+
+    async fn vault_with(peer: u64, files: &[(&str, &str)]) -> (Arc<InMemoryFs>, TestVault) {
+        let fs = Arc::new(InMemoryFs::new());
+        for (path, content) in files {
+            fs.write(path, content.as_bytes()).await.unwrap();
+        }
+        let vault = Vault::init(Arc::clone(&fs), PeerId::from(peer))
+            .await
+            .unwrap();
+        (fs, vault)
+    }
+
+    /// Have `local` process a sync request from `remote`, as if sent over the wire.
+    async fn receive_sync_request(local: &TestVault, remote: &TestVault) {
+        let request = remote.prepare_sync_request().await.unwrap();
+        local
+            .process_sync_message_from(remote.peer_id(), &request)
+            .await
+            .unwrap();
+    }
+
+    fn connect(vault: &TestVault, peer: &TestVault) {
+        vault
+            .peer_connected(
+                peer.peer_id().to_string(),
+                "ws://peer".into(),
+                ConnectionDirection::Incoming,
+            )
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_document_sync_peers_flags_stale_peers() {
+        let (fs1, vault1) = vault_with(1, &[("a.md", "# A")]).await;
+        let (_fs2, vault2) = vault_with(2, &[]).await;
+        let (_fs3, vault3) = vault_with(3, &[]).await;
+        let (_fs4, vault4) = vault_with(4, &[]).await;
+        vault2.merge_with(&vault1).await.unwrap();
+        vault3.merge_with(&vault1).await.unwrap();
+        for peer in [&vault2, &vault3, &vault4] {
+            connect(&vault1, peer);
+        }
+
+        // Peer 3 reports the original version, then we edit the note
+        receive_sync_request(&vault1, &vault3).await;
+        fs1.write("a.md", b"# A\n\nedited").await.unwrap();
+        vault1.on_file_changed("a.md").await.unwrap();
+
+        // Peer 2 catches up and reports the edited version; peer 4 never reports
+        vault2.merge_with(&vault1).await.unwrap();
+        receive_sync_request(&vault1, &vault2).await;
+
+        assert_eq!(
+            vault1.document_sync_peers("a.md").await.unwrap(),
+            vec![
+                (PeerId::from(2u64), true),
+                (PeerId::from(3u64), false),
+                (PeerId::from(4u64), false),
+            ]
+        );
+
+        // Peer 3 catches up on its next sync request
+        vault3.merge_with(&vault1).await.unwrap();
+        receive_sync_request(&vault1, &vault3).await;
+        assert_eq!(
+            vault1.document_sync_peers("a.md").await.unwrap()[1],
+            (PeerId::from(3u64), true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_document_sync_peers_only_lists_connected_peers() {
+        let (_fs1, vault1) = vault_with(1, &[("a.md", "# A")]).await;
+        let (_fs2, vault2) = vault_with(2, &[]).await;
+        vault2.merge_with(&vault1).await.unwrap();
+        receive_sync_request(&vault1, &vault2).await;
+
+        // Versions are recorded, but peer 2 isn't connected
+        let recorded = vault1.peer_document_version(vault2.peer_id(), "a.md");
+        assert!(recorded.is_some());
+        assert!(vault1.document_sync_peers("a.md").await.unwrap().is_empty());
+
+        connect(&vault1, &vault2);
+        assert_eq!(
+            vault1.document_sync_peers("a.md").await.unwrap(),
+            vec![(PeerId::from(2u64), true)]
+        );
+        let missing = vault1.document_sync_peers("missing.md").await.unwrap();
+        assert!(missing.is_empty());
+    }
+}
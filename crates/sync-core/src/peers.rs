@@ -46,6 +46,9 @@ pub enum DisconnectReason {
     DuplicateConnection,
 }
 
+/// Default time a connection may stay in `Connecting` before it is expired (ms).
+pub const DEFAULT_HANDSHAKE_TIMEOUT_MS: f64 = 10_000.0;
+
 /// Result of checking for duplicate connections.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DuplicateCheckResult {
@@ -133,6 +136,27 @@ pub struct ConnectedPeer {
     pub last_seen: f64,
     /// Times this peer has connected this session
     pub connection_count: u32,
+    /// Times this peer has reconnected after a disconnect this session
+    pub reconnect_count: u32,
+}
+
+impl ConnectedPeer {
+    /// Merge a new connection from the same peer into this record.
+    ///
+    /// A peer that is already connected (duplicate connect) only has its
+    /// activity refreshed. Otherwise this is a reconnection: history such as
+    /// `first_seen` is preserved and the counters are incremented.
+    fn merge_connection(&mut self, address: String, direction: ConnectionDirection, timestamp: f64) {
+        if self.state != ConnectionState::Connected {
+            self.state = ConnectionState::Connected;
+            self.disconnect_reason = None;
+            self.connection_count += 1;
+            self.reconnect_count += 1;
+        }
+        self.last_seen = timestamp;
+        self.address = address;
+        self.direction = direction;
+    }
 }
 
 // ============================================================================
@@ -152,6 +176,8 @@ mod platform {
         peers: RwLock<HashMap<String, ConnectedPeer>>,
         /// Maps connection IDs to peer IDs (for pre-handshake → post-handshake resolution)
         connections: RwLock<HashMap<String, String>>,
+        /// Maps origin peer IDs to the neighbor their messages last arrived through
+        routes: RwLock<HashMap<String, String>>,
     }
 
     impl Default for PeerRegistry {
@@ -159,6 +185,7 @@ mod platform {
             Self {
                 peers: RwLock::new(HashMap::new()),
                 connections: RwLock::new(HashMap::new()),
+                routes: RwLock::new(HashMap::new()),
             }
         }
     }
@@ -185,21 +212,8 @@ mod platform {
             let mut peers = self.peers.write().unwrap_or_else(|e| e.into_inner());
 
             let peer = if let Some(peer) = peers.get_mut(&id) {
-                // Existing peer
-                if peer.state == ConnectionState::Connected {
-                    // Duplicate connect - idempotent, just update activity
-                    peer.last_seen = timestamp;
-                    peer.address = address;
-                    peer.direction = direction;
-                } else {
-                    // Reconnection
-                    peer.state = ConnectionState::Connected;
-                    peer.disconnect_reason = None;
-                    peer.connection_count += 1;
-                    peer.last_seen = timestamp;
-                    peer.address = address;
-                    peer.direction = direction;
-                }
+                // Existing peer: duplicate connect or reconnection
+                peer.merge_connection(address, direction, timestamp);
                 peer.clone()
             } else {
                 // New peer
@@ -212,6 +226,7 @@ mod platform {
                     first_seen: timestamp,
                     last_seen: timestamp,
                     connection_count: 1,
+                    reconnect_count: 0,
                 };
                 peers.insert(id, peer.clone());
                 peer
@@ -239,6 +254,12 @@ mod platform {
                 peer.state = ConnectionState::Disconnected;
                 peer.disconnect_reason = Some(reason);
                 peer.last_seen = timestamp;
+
+                // Origins reached through this neighbor are no longer reachable that way
+                self.routes
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .retain(|_, via| via.as_str() != peer_id);
                 true
             } else {
                 false
@@ -317,6 +338,7 @@ mod platform {
                 first_seen: timestamp,
                 last_seen: timestamp,
                 connection_count: 1,
+                reconnect_count: 0,
             };
             peers.insert(connection_id, peer.clone());
             peer
@@ -334,24 +356,53 @@ mod platform {
             let mut connections = self.connections.write().unwrap_or_else(|e| e.into_inner());
 
             // Get the existing connecting peer
-            let mut peer = peers
+            let mut pending = peers
                 .remove(connection_id)
                 .ok_or_else(|| super::PeerError::UnknownConnection(connection_id.to_string()))?;
 
-            // Update to connected state with real peer ID
-            peer.id = peer_id.clone();
-            peer.state = ConnectionState::Connected;
-            peer.last_seen = timestamp;
-
             // Update connection mapping
             connections.insert(connection_id.to_string(), peer_id.clone());
 
-            // Re-insert under real peer ID
-            peers.insert(peer_id, peer.clone());
+            let peer = if let Some(peer) = peers.get_mut(&peer_id) {
+                // Known peer: keep its history
+                peer.merge_connection(pending.address, pending.direction, timestamp);
+                peer.clone()
+            } else {
+                // First time we see this peer: re-insert under real peer ID
+                pending.id = peer_id.clone();
+                pending.state = ConnectionState::Connected;
+                pending.last_seen = timestamp;
+                peers.insert(peer_id, pending.clone());
+                pending
+            };
 
             Ok(peer)
         }
 
+        /// Remove connections that have been `Connecting` for at least `timeout_ms`.
+        ///
+        /// Returns the expired connection IDs (sorted) so the caller can close
+        /// the underlying sockets with `DisconnectReason::ProtocolError`.
+        pub fn expire_connecting(&self, timeout_ms: f64, timestamp: f64) -> Vec<String> {
+            let mut peers = self.peers.write().unwrap_or_else(|e| e.into_inner());
+            let mut connections = self.connections.write().unwrap_or_else(|e| e.into_inner());
+
+            let mut expired: Vec<String> = peers
+                .values()
+                .filter(|p| p.state == ConnectionState::Connecting)
+                .filter(|p| timestamp - p.first_seen >= timeout_ms)
+                .map(|p| p.id.clone())
+                .collect();
+            expired.sort();
+
+            for connection_id in &expired {
+                peers.remove(connection_id);
+                connections.remove(connection_id);
+            }
+
+            expired
+        }
+
         /// Get peer by connection ID (for pre-handshake lookups).
         pub fn get_peer_by_connection_id(&self, connection_id: &str) -> Option<ConnectedPeer> {
             let connections = self.connections.read().unwrap_or_else(|e| e.into_inner());
@@ -372,6 +423,28 @@ mod platform {
                 .cloned()
                 .unwrap_or_else(|| connection_id.to_string())
         }
+
+        /// Record that a message authored by `origin` arrived from neighbor `via`.
+        ///
+        /// `via == origin` means the origin is a direct neighbor.
+        pub fn record_route(&self, origin: &str, via: &str) {
+            if origin.is_empty() || via.is_empty() {
+                return;
+            }
+            self.routes
+                .write()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(origin.to_string(), via.to_string());
+        }
+
+        /// Neighbor that messages from `origin` last arrived through.
+        pub fn route_to(&self, origin: &str) -> Option<String> {
+            self.routes
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(origin)
+                .cloned()
+        }
     }
 }
 
@@ -392,6 +465,8 @@ mod platform {
         peers: RefCell<HashMap<String, ConnectedPeer>>,
         /// Maps connection IDs to peer IDs (for pre-handshake → post-handshake resolution)
         connections: RefCell<HashMap<String, String>>,
+        /// Maps origin peer IDs to the neighbor their messages last arrived through
+        routes: RefCell<HashMap<String, String>>,
     }
 
     impl Default for PeerRegistry {
@@ -399,6 +474,7 @@ mod platform {
             Self {
                 peers: RefCell::new(HashMap::new()),
                 connections: RefCell::new(HashMap::new()),
+                routes: RefCell::new(HashMap::new()),
             }
         }
     }
@@ -425,21 +501,8 @@ mod platform {
             let mut peers = self.peers.borrow_mut();
 
             let peer = if let Some(peer) = peers.get_mut(&id) {
-                // Existing peer
-                if peer.state == ConnectionState::Connected {
-                    // Duplicate connect - idempotent, just update activity
-                    peer.last_seen = timestamp;
-                    peer.address = address;
-                    peer.direction = direction;
-                } else {
-                    // Reconnection
-                    peer.state = ConnectionState::Connected;
-                    peer.disconnect_reason = None;
-                    peer.connection_count += 1;
-                    peer.last_seen = timestamp;
-                    peer.address = address;
-                    peer.direction = direction;
-                }
+                // Existing peer: duplicate connect or reconnection
+                peer.merge_connection(address, direction, timestamp);
                 peer.clone()
             } else {
                 // New peer
@@ -452,6 +515,7 @@ mod platform {
                     first_seen: timestamp,
                     last_seen: timestamp,
                     connection_count: 1,
+                    reconnect_count: 0,
                 };
                 peers.insert(id, peer.clone());
                 peer
@@ -479,6 +543,9 @@ mod platform {
                 peer.state = ConnectionState::Disconnected;
                 peer.disconnect_reason = Some(reason);
                 peer.last_seen = timestamp;
+
+                // Origins reached through this neighbor are no longer reachable that way
+                self.routes.borrow_mut().retain(|_, via| via.as_str() != peer_id);
                 true
             } else {
                 false
@@ -546,6 +613,7 @@ mod platform {
                 first_seen: timestamp,
                 last_seen: timestamp,
                 connection_count: 1,
+                reconnect_count: 0,
             };
             peers.insert(connection_id, peer.clone());
             peer
@@ -563,24 +631,53 @@ mod platform {
             let mut connections = self.connections.borrow_mut();
 
             // Get the existing connecting peer
-            let mut peer = peers
+            let mut pending = peers
                 .remove(connection_id)
                 .ok_or_else(|| super::PeerError::UnknownConnection(connection_id.to_string()))?;
 
-            // Update to connected state with real peer ID
-            peer.id = peer_id.clone();
-            peer.state = ConnectionState::Connected;
-            peer.last_seen = timestamp;
-
             // Update connection mapping
             connections.insert(connection_id.to_string(), peer_id.clone());
 
-            // Re-insert under real peer ID
-            peers.insert(peer_id, peer.clone());
+            let peer = if let Some(peer) = peers.get_mut(&peer_id) {
+                // Known peer: keep its history
+                peer.merge_connection(pending.address, pending.direction, timestamp);
+                peer.clone()
+            } else {
+                // First time we see this peer: re-insert under real peer ID
+                pending.id = peer_id.clone();
+                pending.state = ConnectionState::Connected;
+                pending.last_seen = timestamp;
+                peers.insert(peer_id, pending.clone());
+                pending
+            };
 
             Ok(peer)
         }
 
+        /// Remove connections that have been `Connecting` for at least `timeout_ms`.
+        ///
+        /// Returns the expired connection IDs (sorted) so the caller can close
+        /// the underlying sockets with `DisconnectReason::ProtocolError`.
+        pub fn expire_connecting(&self, timeout_ms: f64, timestamp: f64) -> Vec<String> {
+            let mut peers = self.peers.borrow_mut();
+            let mut connections = self.connections.borrow_mut();
+
+            let mut expired: Vec<String> = peers
+                .values()
+                .filter(|p| p.state == ConnectionState::Connecting)
+                .filter(|p| timestamp - p.first_seen >= timeout_ms)
+                .map(|p| p.id.clone())
+                .collect();
+            expired.sort();
+
+            for connection_id in &expired {
+                peers.remove(connection_id);
+                connections.remove(connection_id);
+            }
+
+            expired
+        }
+
         /// Get peer by connection ID (for pre-handshake lookups).
         pub fn get_peer_by_connection_id(&self, connection_id: &str) -> Option<ConnectedPeer> {
             let connections = self.connections.borrow();
@@ -596,6 +693,23 @@ mod platform {
                 .cloned()
                 .unwrap_or_else(|| connection_id.to_string())
         }
+
+        /// Record that a message authored by `origin` arrived from neighbor `via`.
+        ///
+        /// `via == origin` means the origin is a direct neighbor.
+        pub fn record_route(&self, origin: &str, via: &str) {
+            if origin.is_empty() || via.is_empty() {
+                return;
+            }
+            self.routes
+                .borrow_mut()
+                .insert(origin.to_string(), via.to_string());
+        }
+
+        /// Neighbor that messages from `origin` last arrived through.
+        pub fn route_to(&self, origin: &str) -> Option<String> {
+            self.routes.borrow().get(origin).cloned()
+        }
     }
 }
 
@@ -625,6 +739,7 @@ mod tests {
         assert_eq!(peer.first_seen, 1000.0);
         assert_eq!(peer.last_seen, 1000.0);
         assert_eq!(peer.connection_count, 1);
+        assert_eq!(peer.reconnect_count, 0);
     }
 
     #[test]
@@ -950,6 +1065,53 @@ mod tests {
         assert_eq!(peer.direction, ConnectionDirection::Outgoing);
     }
 
+    #[test]
+    fn test_reconnect_reuses_record() {
+        let registry = PeerRegistry::new();
+
+        registry
+            .peer_connected(
+                "peer1".into(),
+                "addr".into(),
+                ConnectionDirection::Incoming,
+                1000.0,
+            )
+            .unwrap();
+        registry.peer_disconnected("peer1", DisconnectReason::NetworkError, 2000.0);
+        let peer = registry
+            .peer_connected(
+                "peer1".into(),
+                "addr".into(),
+                ConnectionDirection::Incoming,
+                3000.0,
+            )
+            .unwrap();
+
+        assert_eq!(registry.get_known_peers().len(), 1);
+        assert_eq!(peer.reconnect_count, 1);
+        assert_eq!(peer.connection_count, 2);
+        assert_eq!(peer.first_seen, 1000.0);
+    }
+
+    #[test]
+    fn test_duplicate_connect_is_not_a_reconnect() {
+        let registry = PeerRegistry::new();
+
+        for timestamp in [1000.0, 2000.0] {
+            registry
+                .peer_connected(
+                    "peer1".into(),
+                    "addr".into(),
+                    ConnectionDirection::Incoming,
+                    timestamp,
+                )
+                .unwrap();
+        }
+
+        let peer = registry.get_peer("peer1").unwrap();
+        assert_eq!(peer.reconnect_count, 0);
+    }
+
     // ========== Connection ID mapping tests ==========
 
     #[test]
@@ -1014,6 +1176,49 @@ mod tests {
         assert_eq!(registry.resolve_peer_id("conn-1"), "peer-abc");
     }
 
+    #[test]
+    fn test_handshake_reconnect_merges_into_existing_record() {
+        let registry = PeerRegistry::new();
+
+        // First connection
+        registry.peer_connecting(
+            "conn-1".into(),
+            "addr1".into(),
+            ConnectionDirection::Incoming,
+            1000.0,
+        );
+        registry
+            .peer_handshake_complete("conn-1", "peer-1".into(), 1500.0)
+            .unwrap();
+        registry.peer_disconnected("peer-1", DisconnectReason::RemoteClosed, 2000.0);
+
+        // Same peer reconnects on a new connection
+        registry.peer_connecting(
+            "conn-2".into(),
+            "addr2".into(),
+            ConnectionDirection::Outgoing,
+            3000.0,
+        );
+        let peer = registry
+            .peer_handshake_complete("conn-2", "peer-1".into(), 3500.0)
+            .unwrap();
+
+        assert_eq!(peer.id, "peer-1");
+        assert_eq!(peer.state, ConnectionState::Connected);
+        assert_eq!(peer.disconnect_reason, None);
+        assert_eq!(peer.reconnect_count, 1);
+        assert_eq!(peer.connection_count, 2);
+        assert_eq!(peer.first_seen, 1000.0); // Preserved
+        assert_eq!(peer.last_seen, 3500.0);
+        assert_eq!(peer.address, "addr2");
+        assert_eq!(peer.direction, ConnectionDirection::Outgoing);
+
+        // One record, reachable from both connection IDs
+        assert_eq!(registry.get_known_peers().len(), 1);
+        assert_eq!(registry.resolve_peer_id("conn-1"), "peer-1");
+        assert_eq!(registry.resolve_peer_id("conn-2"), "peer-1");
+    }
+
     #[test]
     fn test_handshake_unknown_connection_fails() {
         let registry = PeerRegistry::new();
@@ -1098,6 +1303,97 @@ mod tests {
         assert_eq!(peer.disconnect_reason, Some(DisconnectReason::UserRequested));
     }
 
+    #[test]
+    fn test_expire_connecting_removes_stale_handshakes() {
+        let registry = PeerRegistry::new();
+        registry.peer_connecting(
+            "conn-1".into(),
+            "addr1".into(),
+            ConnectionDirection::Incoming,
+            1000.0,
+        );
+        registry.peer_connecting(
+            "conn-2".into(),
+            "addr2".into(),
+            ConnectionDirection::Incoming,
+            8000.0,
+        );
+
+        // conn-1 has waited 10s, conn-2 only 3s
+        let expired = registry.expire_connecting(10_000.0, 11_000.0);
+        assert_eq!(expired, vec!["conn-1".to_string()]);
+
+        assert!(registry.get_peer("conn-1").is_none());
+        assert!(registry.get_peer_by_connection_id("conn-1").is_none());
+        assert_eq!(registry.resolve_peer_id("conn-1"), "conn-1");
+        assert!(registry.get_peer("conn-2").is_some());
+    }
+
+    #[test]
+    fn test_expire_connecting_ignores_completed_handshakes() {
+        let registry = PeerRegistry::new();
+        registry.peer_connecting(
+            "conn-1".into(),
+            "addr".into(),
+            ConnectionDirection::Outgoing,
+            1000.0,
+        );
+        registry
+            .peer_handshake_complete("conn-1", "peer-1".into(), 2000.0)
+            .unwrap();
+
+        let expired = registry.expire_connecting(10_000.0, 60_000.0);
+        assert!(expired.is_empty());
+        assert!(registry.is_connected("peer-1"));
+        assert_eq!(registry.resolve_peer_id("conn-1"), "peer-1");
+    }
+
+    // ========== Routing tests ==========
+
+    #[test]
+    fn test_record_route_relayed_origin() {
+        let registry = PeerRegistry::new();
+        registry.record_route("author", "relay");
+        registry.record_route("relay", "relay");
+
+        assert_eq!(registry.route_to("author").as_deref(), Some("relay"));
+        assert_eq!(registry.route_to("relay").as_deref(), Some("relay"));
+        assert!(registry.route_to("unknown").is_none());
+
+        // Author later connects directly
+        registry.record_route("author", "author");
+        assert_eq!(registry.route_to("author").as_deref(), Some("author"));
+    }
+
+    #[test]
+    fn test_disconnect_drops_routes_via_neighbor() {
+        let registry = PeerRegistry::new();
+        registry
+            .peer_connected(
+                "relay".into(),
+                "addr".into(),
+                ConnectionDirection::Outgoing,
+                1000.0,
+            )
+            .unwrap();
+        registry.record_route("author", "relay");
+        registry.record_route("other", "elsewhere");
+
+        registry.peer_disconnected("relay", DisconnectReason::RemoteClosed, 2000.0);
+
+        assert!(registry.route_to("author").is_none());
+        assert_eq!(registry.route_to("other").as_deref(), Some("elsewhere"));
+    }
+
+    #[test]
+    fn test_record_route_ignores_empty_ids() {
+        let registry = PeerRegistry::new();
+        registry.record_route("", "relay");
+        registry.record_route("author", "");
+        assert!(registry.route_to("").is_none());
+        assert!(registry.route_to("author").is_none());
+    }
+
     // ========== Connection deduplication tests ==========
 
     #[test]
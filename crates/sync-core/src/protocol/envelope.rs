@@ -51,10 +51,13 @@ impl GossipMessage {
 
 /// A sync data envelope with optional piggybacked gossip updates.
 ///
-/// Wire format: `{"type":"sync","data":[1,2,3,...],"gossip":[...]}`
+/// Wire format: `{"type":"sync","data":[1,2,3,...],"gossip":[...],"origin":"..."}`
 ///
 /// The `data` field is a `Vec<u8>` that serializes as a JSON number array,
 /// matching the TypeScript plugin's `Array.from(syncData)` convention.
+///
+/// `origin` is set when a peer relays data it did not author, so the
+/// receiver can tell the original author apart from the immediate sender.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SyncEnvelope {
@@ -63,6 +66,18 @@ pub struct SyncEnvelope {
     pub data: Vec<u8>,
     #[serde(default)]
     pub gossip: Vec<GossipUpdate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+/// Just the routing fields of a `SyncEnvelope`, so peeking at the origin
+/// doesn't allocate the data payload.
+#[derive(Deserialize)]
+struct EnvelopeHeader {
+    #[serde(rename = "type")]
+    msg_type: String,
+    #[serde(default)]
+    origin: Option<String>,
 }
 
 impl SyncEnvelope {
@@ -72,6 +87,26 @@ impl SyncEnvelope {
             msg_type: "sync".to_string(),
             data,
             gossip,
+            origin: None,
+        }
+    }
+
+    /// Mark this envelope as relayed on behalf of `origin`.
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    /// Read the `origin` of a sync envelope without parsing its payload.
+    ///
+    /// Returns `None` for non-envelope input or envelopes sent directly by
+    /// their author.
+    pub fn peek_origin(data: &[u8]) -> Option<String> {
+        let header: EnvelopeHeader = serde_json::from_slice(data).ok()?;
+        if header.msg_type == "sync" {
+            header.origin
+        } else {
+            None
         }
     }
 
@@ -230,6 +265,36 @@ mod tests {
         assert!(SyncEnvelope::from_json(json).is_none());
     }
 
+    #[test]
+    fn test_sync_envelope_origin_roundtrip() {
+        let msg = SyncEnvelope::new(vec![1, 2], vec![]).with_origin("a1b2c3d4e5f67890");
+        let json = msg.to_json();
+        let parsed = SyncEnvelope::from_json(&json).unwrap();
+        assert_eq!(parsed.origin.as_deref(), Some("a1b2c3d4e5f67890"));
+        assert_eq!(msg, parsed);
+    }
+
+    #[test]
+    fn test_sync_envelope_direct_omits_origin() {
+        let msg = SyncEnvelope::new(vec![1], vec![]);
+        let json = String::from_utf8(msg.to_json()).unwrap();
+        assert!(!json.contains("origin"));
+        assert!(SyncEnvelope::from_json(json.as_bytes()).unwrap().origin.is_none());
+    }
+
+    #[test]
+    fn test_sync_envelope_peek_origin() {
+        let relayed = SyncEnvelope::new(vec![1, 2, 3], sample_updates()).with_origin("author");
+        assert_eq!(SyncEnvelope::peek_origin(&relayed.to_json()).as_deref(), Some("author"));
+
+        let direct = SyncEnvelope::new(vec![1, 2, 3], vec![]);
+        assert!(SyncEnvelope::peek_origin(&direct.to_json()).is_none());
+
+        let gossip = br#"{"type":"gossip","updates":[],"origin":"author"}"#;
+        assert!(SyncEnvelope::peek_origin(gossip).is_none());
+        assert!(SyncEnvelope::peek_origin(&[0x00, 0x01]).is_none());
+    }
+
     // ==================== PeerMessage ====================
 
     #[test]
@@ -0,0 +1,480 @@
+//! Incremental persistence for the file registry.
+//!
+//! Instead of re-exporting a full registry snapshot after every change, the
+//! vault appends the updates made since the last save to `.sync/registry.log`
+//! and only folds them into `registry.loro` once the log passes the
+//! [`RegistryCompaction`] threshold. Loading imports the snapshot, then replays
+//! the log on top of it.
+//!
+//! Snapshots still carry the registry's full history, including every create,
+//! move and delete. Once every known peer has synced the latest registry,
+//! `compact_registry` replaces it with a shallow snapshot that drops that
+//! history. Loro keeps deleted tree nodes in the state, so tombstones shrink
+//! to their current state rather than disappearing entirely.
+
+use crate::fs::FileSystem;
+use crate::vault::{Result, Vault, VaultError, REGISTRY_FILE};
+use crate::PeerId;
+use loro::{ExportMode, LoroDoc, VersionVector};
+use std::collections::BTreeSet;
+
+/// Registry updates since the last snapshot, as length-prefixed entries
+pub const REGISTRY_LOG_FILE: &str = ".sync/registry.log";
+
+/// When to compact the registry log into a fresh snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistryCompaction {
+    /// Log entries allowed before compacting
+    pub max_updates: usize,
+    /// Log size in bytes allowed before compacting
+    pub max_log_bytes: usize,
+}
+
+impl Default for RegistryCompaction {
+    fn default() -> Self {
+        Self {
+            max_updates: 100,
+            max_log_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// What the registry log on disk holds.
+#[derive(Debug)]
+pub(crate) struct RegistryLog {
+    /// Registry version already persisted (snapshot plus log)
+    saved: VersionVector,
+    /// Entries in the log
+    updates: usize,
+    /// Size of the log in bytes
+    bytes: usize,
+    compaction: RegistryCompaction,
+}
+
+impl RegistryLog {
+    /// State for a registry persisted up to `saved` with an empty log.
+    pub(crate) fn new(saved: VersionVector) -> Self {
+        Self {
+            saved,
+            updates: 0,
+            bytes: 0,
+            compaction: RegistryCompaction::default(),
+        }
+    }
+
+    /// Whether appending `len` more bytes should trigger a compaction.
+    fn should_compact(&self, len: usize) -> bool {
+        self.updates + 1 > self.compaction.max_updates
+            || self.bytes + len > self.compaction.max_log_bytes
+    }
+}
+
+/// Frame an update as a little-endian `u32` length followed by its bytes.
+fn encode_entry(update: &[u8]) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(4 + update.len());
+    entry.extend_from_slice(&(update.len() as u32).to_le_bytes());
+    entry.extend_from_slice(update);
+    entry
+}
+
+/// Split a log into its entries, dropping a truncated final entry.
+pub(crate) fn decode_entries(mut log: &[u8]) -> Vec<&[u8]> {
+    let mut entries = Vec::new();
+    while log.len() >= 4 {
+        let len = u32::from_le_bytes([log[0], log[1], log[2], log[3]]) as usize;
+        let Some(entry) = log.get(4..4 + len) else {
+            tracing::warn!("Ignoring truncated registry log entry");
+            break;
+        };
+        entries.push(entry);
+        log = &log[4 + len..];
+    }
+    entries
+}
+
+/// Read the registry log, or nothing if there isn't one.
+pub(crate) async fn read_registry_log<F: FileSystem>(fs: &F) -> Result<Vec<u8>> {
+    if fs.exists(REGISTRY_LOG_FILE).await? {
+        Ok(fs.read(REGISTRY_LOG_FILE).await?)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// Replay log entries onto `registry`, returning how many were applied.
+pub(crate) fn replay_registry_log(registry: &LoroDoc, log: &[u8]) -> Result<usize> {
+    let entries = decode_entries(log);
+    for entry in &entries {
+        registry
+            .import(entry)
+            .map_err(|e| VaultError::RegistryCorrupted(format!("registry log: {}", e)))?;
+    }
+    Ok(entries.len())
+}
+
+impl<F: FileSystem> Vault<F> {
+    /// Set when the registry log is compacted into a snapshot
+    /// (default [`RegistryCompaction::default`]).
+    pub fn with_registry_compaction(self, compaction: RegistryCompaction) -> Self {
+        self.registry_log.lock().unwrap().compaction = compaction;
+        self
+    }
+
+    /// Record that the log already holds `updates` entries totalling `bytes`.
+    pub(crate) fn set_registry_log_size(&self, updates: usize, bytes: usize) {
+        let mut log = self.registry_log.lock().unwrap();
+        log.updates = updates;
+        log.bytes = bytes;
+    }
+
+    /// Persist registry changes made since the last save.
+    ///
+    /// Appends them to the log, or writes a full snapshot when the log has
+    /// grown past the compaction threshold. Does nothing if nothing changed.
+    pub(crate) async fn save_registry(&self) -> Result<()> {
+        let (update, version) = {
+            let registry = self.registry();
+            registry.commit();
+            let version = registry.oplog_vv();
+            let log = self.registry_log.lock().unwrap();
+            if version == log.saved {
+                return Ok(());
+            }
+            let update = registry
+                .export(ExportMode::updates(&log.saved))
+                .map_err(|e| VaultError::Other(format!("Registry export failed: {}", e)))?;
+            (update, version)
+        };
+
+        if self.registry_log.lock().unwrap().should_compact(update.len()) {
+            return self.write_registry_snapshot().await;
+        }
+
+        let entry = encode_entry(&update);
+        self.fs.append(REGISTRY_LOG_FILE, &entry).await?;
+
+        let mut log = self.registry_log.lock().unwrap();
+        log.saved = version;
+        log.updates += 1;
+        log.bytes += entry.len();
+        Ok(())
+    }
+
+    /// Write a full registry snapshot and clear the log.
+    pub(crate) async fn write_registry_snapshot(&self) -> Result<()> {
+        let (snapshot, version) = {
+            let registry = self.registry();
+            let snapshot = registry
+                .export(ExportMode::Snapshot)
+                .map_err(|e| VaultError::Other(format!("Registry export failed: {}", e)))?;
+            (snapshot, registry.oplog_vv())
+        };
+
+        self.fs.write(REGISTRY_FILE, &snapshot).await?;
+        if self.fs.exists(REGISTRY_LOG_FILE).await? {
+            self.fs.delete(REGISTRY_LOG_FILE).await?;
+        }
+
+        let mut log = self.registry_log.lock().unwrap();
+        log.saved = version;
+        log.updates = 0;
+        log.bytes = 0;
+        tracing::debug!("Compacted registry into a {} byte snapshot", snapshot.len());
+        Ok(())
+    }
+
+    /// Known peers that haven't reported our latest registry version.
+    ///
+    /// Covers every peer that has ever written to the registry (taken from its
+    /// version vector), every peer that has ever sent a sync request (from the
+    /// persisted registry acks), and peers seen this session, so offline peers
+    /// are still counted after a restart. Sorted by peer ID.
+    pub fn unacked_registry_peers(&self) -> Vec<String> {
+        let (current, writers) = {
+            let registry = self.registry();
+            registry.commit();
+            let version = registry.oplog_vv();
+            let writers: Vec<PeerId> = version
+                .keys()
+                .map(|peer| PeerId::from(*peer))
+                .filter(|peer| *peer != self.peer_id())
+                .collect();
+            (version.encode(), writers)
+        };
+
+        let mut peers: BTreeSet<String> = self
+            .get_known_peers()
+            .into_iter()
+            .map(|peer| peer.id)
+            .collect();
+        peers.extend(writers.iter().map(PeerId::to_string));
+        peers.extend(self.peers_with_versions().iter().map(PeerId::to_string));
+        peers
+            .into_iter()
+            .filter(|id| {
+                let acked = id
+                    .parse()
+                    .ok()
+                    .and_then(|peer| self.peer_registry_version(peer))
+                    .is_some_and(|version| Self::version_includes(&version, &current));
+                !acked
+            })
+            .collect()
+    }
+
+    /// Drop the registry's history, keeping only its current state.
+    ///
+    /// Replaces the registry with a shallow snapshot at its current version and
+    /// persists it, returning the new snapshot size in bytes. Peers that haven't
+    /// seen the latest registry couldn't merge with the trimmed history, so this
+    /// fails while `unacked_registry_peers` lists any. That includes every peer
+    /// that has changed or synced the registry, whether or not it has connected
+    /// since the vault was loaded.
+    pub async fn compact_registry(&self) -> Result<usize> {
+        let unacked = self.unacked_registry_peers();
+        if !unacked.is_empty() {
+            return Err(VaultError::Other(format!(
+                "Can't compact registry, not yet synced by: {}",
+                unacked.join(", ")
+            )));
+        }
+
+        let shallow = {
+            let registry = self.registry();
+            registry
+                .export(ExportMode::shallow_snapshot(&registry.oplog_frontiers()))
+                .map_err(|e| VaultError::Other(format!("Registry export failed: {}", e)))?
+        };
+        let registry = Self::import_registry(&shallow, self.peer_id())?;
+        *self.registry_mut() = registry;
+        self.rebuild_path_cache();
+
+        self.write_registry_snapshot().await?;
+        Ok(shallow.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::InMemoryFs;
+    use crate::PeerId;
+    use std::sync::Arc;
+
+    type TestVault = Vault<Arc<InMemoryFs>>;
+
+    fn test_peer_id() -> PeerId {
+        PeerId::from(12345u64)
+    }
+
+    #[test]
+    fn test_decode_entries_drops_truncated_tail() {
+        let mut log = encode_entry(b"first");
+        log.extend(encode_entry(b"second"));
+        let full = log.len();
+        log.extend(encode_entry(b"third"));
+        log.truncate(full + 6);
+
+        assert_eq!(decode_entries(&log), vec![&b"first"[..], &b"second"[..]]);
+    }
+
+    #[tokio::test]
+    async fn test_small_updates_append_to_log() {
+        let fs = Arc::new(InMemoryFs::new());
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        vault.write_registry_snapshot().await.unwrap();
+        let snapshot = fs.read(REGISTRY_FILE).await.unwrap();
+
+        for i in 0..10 {
+            vault.register_file(&format!("note-{}.md", i)).unwrap();
+            vault.save_registry().await.unwrap();
+        }
+
+        // The snapshot is untouched; each save added one log entry
+        assert_eq!(fs.read(REGISTRY_FILE).await.unwrap(), snapshot);
+        let log = fs.read(REGISTRY_LOG_FILE).await.unwrap();
+        assert_eq!(decode_entries(&log).len(), 10);
+
+        // Saving without changes writes nothing
+        vault.save_registry().await.unwrap();
+        assert_eq!(fs.read(REGISTRY_LOG_FILE).await.unwrap(), log);
+    }
+
+    #[tokio::test]
+    async fn test_log_compacts_past_threshold() {
+        let fs = Arc::new(InMemoryFs::new());
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id())
+            .await
+            .unwrap()
+            .with_registry_compaction(RegistryCompaction {
+                max_updates: 3,
+                max_log_bytes: usize::MAX,
+            });
+        vault.write_registry_snapshot().await.unwrap();
+        let snapshot = fs.read(REGISTRY_FILE).await.unwrap();
+
+        for i in 0..3 {
+            vault.register_file(&format!("note-{}.md", i)).unwrap();
+            vault.save_registry().await.unwrap();
+        }
+        assert_eq!(fs.read(REGISTRY_FILE).await.unwrap(), snapshot);
+
+        vault.register_file("note-3.md").unwrap();
+        vault.save_registry().await.unwrap();
+        assert_ne!(fs.read(REGISTRY_FILE).await.unwrap(), snapshot);
+        assert!(!fs.exists(REGISTRY_LOG_FILE).await.unwrap());
+    }
+
+    /// A vault with `count` notes, deleted again, fully synced with peer 2.
+    async fn vault_with_deleted_notes(count: usize) -> (Arc<InMemoryFs>, TestVault, TestVault) {
+        let fs = Arc::new(InMemoryFs::new());
+        fs.write("keep.md", b"# Keep").await.unwrap();
+        for i in 0..count {
+            let path = format!("notes/{}.md", i);
+            fs.write(&path, b"# Note").await.unwrap();
+        }
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        for i in 0..count {
+            let path = format!("notes/{}.md", i);
+            fs.delete(&path).await.unwrap();
+            vault.delete_file(&path).await.unwrap();
+        }
+        let peer = Vault::init(Arc::new(InMemoryFs::new()), PeerId::from(2u64))
+            .await
+            .unwrap();
+        peer.merge_with(&vault).await.unwrap();
+        (fs, vault, peer)
+    }
+
+    /// Have `vault` receive `peer`'s sync request, as if `peer` were connected.
+    async fn receive_sync_request(vault: &TestVault, peer: &TestVault) {
+        let request = peer.prepare_sync_request().await.unwrap();
+        vault
+            .process_sync_message_from(peer.peer_id(), &request)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compact_registry_drops_history() {
+        let (fs, vault, peer) = vault_with_deleted_notes(50).await;
+        receive_sync_request(&vault, &peer).await;
+        vault.write_registry_snapshot().await.unwrap();
+        let before = fs.read(REGISTRY_FILE).await.unwrap().len();
+
+        let after = vault.compact_registry().await.unwrap();
+
+        assert!(after < before, "{} >= {}", after, before);
+        assert_eq!(fs.read(REGISTRY_FILE).await.unwrap().len(), after);
+        assert_eq!(vault.registered_files(), vec!["keep.md"]);
+
+        // The compacted registry loads, and keeps accepting changes
+        drop(vault);
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        assert_eq!(vault.registered_files(), vec!["keep.md"]);
+        vault.rename_file("keep.md", "kept.md").await.unwrap();
+        assert_eq!(vault.registered_files(), vec!["kept.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_compact_registry_waits_for_peers() {
+        let (fs, vault, peer) = vault_with_deleted_notes(5).await;
+        vault
+            .peer_connected(
+                peer.peer_id().to_string(),
+                "ws://peer".into(),
+                crate::ConnectionDirection::Incoming,
+            )
+            .unwrap();
+        let snapshot = fs.read(REGISTRY_FILE).await.unwrap();
+
+        // Connected, but hasn't reported its registry version
+        let peer_id = peer.peer_id().to_string();
+        assert_eq!(vault.unacked_registry_peers(), vec![peer_id]);
+        assert!(vault.compact_registry().await.is_err());
+
+        // Reported, but we deleted another note since
+        receive_sync_request(&vault, &peer).await;
+        vault.delete_file("keep.md").await.unwrap();
+        assert!(vault.compact_registry().await.is_err());
+        assert_eq!(fs.read(REGISTRY_FILE).await.unwrap(), snapshot);
+
+        peer.merge_with(&vault).await.unwrap();
+        receive_sync_request(&vault, &peer).await;
+        assert!(vault.unacked_registry_peers().is_empty());
+        vault.compact_registry().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compact_registry_waits_for_offline_writers() {
+        let (fs, vault, peer) = vault_with_deleted_notes(5).await;
+        peer.fs.write("theirs.md", b"# Theirs").await.unwrap();
+        peer.on_file_changed("theirs.md").await.unwrap();
+        vault.merge_with(&peer).await.unwrap();
+        vault.save_registry().await.unwrap();
+
+        // After a restart the peer isn't connected, but it wrote to the registry
+        drop(vault);
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        let peer_id = peer.peer_id().to_string();
+        assert_eq!(vault.unacked_registry_peers(), vec![peer_id]);
+        assert!(vault.compact_registry().await.is_err());
+
+        peer.merge_with(&vault).await.unwrap();
+        receive_sync_request(&vault, &peer).await;
+        assert!(vault.unacked_registry_peers().is_empty());
+        vault.compact_registry().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compact_registry_waits_for_offline_readers_after_restart() {
+        let (fs, vault, peer) = vault_with_deleted_notes(5).await;
+        receive_sync_request(&vault, &peer).await;
+        let peer_id = peer.peer_id().to_string();
+        assert!(!vault.get_registry_version().contains_key(&peer_id));
+
+        // Changed after the peer's last sync, then restarted with the peer offline
+        fs.delete("keep.md").await.unwrap();
+        vault.delete_file("keep.md").await.unwrap();
+        vault.save_registry().await.unwrap();
+        drop(vault);
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        assert_eq!(vault.unacked_registry_peers(), vec![peer_id]);
+        assert!(vault.compact_registry().await.is_err());
+
+        peer.merge_with(&vault).await.unwrap();
+        receive_sync_request(&vault, &peer).await;
+        assert!(vault.unacked_registry_peers().is_empty());
+        vault.compact_registry().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_load_replays_log_onto_snapshot() {
+        let fs = Arc::new(InMemoryFs::new());
+        fs.write("a.md", b"# Note").await.unwrap();
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        vault.write_registry_snapshot().await.unwrap();
+        let snapshot = fs.read(REGISTRY_FILE).await.unwrap();
+
+        // Files created after init, so registering them changes the registry
+        for path in ["notes/b.md", "notes/c.md"] {
+            fs.write(path, b"# Note").await.unwrap();
+            vault.register_file(path).unwrap();
+            vault.save_registry().await.unwrap();
+        }
+        let expected = vault.registered_files();
+        assert_eq!(expected, vec!["a.md", "notes/b.md", "notes/c.md"]);
+        drop(vault);
+
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        assert_eq!(vault.registered_files(), expected);
+
+        // Further saves keep appending to the existing log
+        fs.write("d.md", b"# Note").await.unwrap();
+        vault.register_file("d.md").unwrap();
+        vault.save_registry().await.unwrap();
+        assert_eq!(fs.read(REGISTRY_FILE).await.unwrap(), snapshot);
+        let log = fs.read(REGISTRY_LOG_FILE).await.unwrap();
+        assert!(decode_entries(&log).len() >= 3);
+    }
+}
@@ -0,0 +1,120 @@
+//! Selective sync: which vault paths participate in sync.
+//!
+//! A `SyncScope` is a list of include and exclude globs matched against
+//! vault-relative paths. A path is in scope when it matches an include glob
+//! (or there are none) and no exclude glob. Globs support `*` (any characters
+//! within one path segment), `**` (any number of segments) and `?` (one
+//! character); a glob ending in `/` matches everything under that folder.
+
+use serde::{Deserialize, Serialize};
+
+/// Include and exclude globs deciding which paths sync.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SyncScope {
+    /// Paths to sync; empty means the whole vault
+    pub include: Vec<String>,
+    /// Paths never synced, even if included
+    pub exclude: Vec<String>,
+}
+
+impl SyncScope {
+    /// Scope covering the whole vault.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Whether `path` is inside this scope.
+    pub fn contains(&self, path: &str) -> bool {
+        let included =
+            self.include.is_empty() || self.include.iter().any(|glob| glob_matches(glob, path));
+        included && !self.exclude.iter().any(|glob| glob_matches(glob, path))
+    }
+}
+
+/// Match a vault-relative `path` against `glob`.
+fn glob_matches(glob: &str, path: &str) -> bool {
+    let glob = glob.trim_start_matches("./");
+    let glob = match glob.strip_suffix('/') {
+        Some(folder) => format!("{}/**", folder),
+        None => glob.to_string(),
+    };
+    let glob: Vec<&str> = glob.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&glob, &path)
+}
+
+fn match_segments(glob: &[&str], path: &[&str]) -> bool {
+    match glob.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                match_segment(segment.as_bytes(), name.as_bytes()) && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match one path segment against a glob segment (`*` and `?` wildcards).
+fn match_segment(glob: &[u8], name: &[u8]) -> bool {
+    match glob.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_segment(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(include: &[&str], exclude: &[&str]) -> SyncScope {
+        SyncScope {
+            include: include.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_empty_scope_includes_everything() {
+        let scope = SyncScope::all();
+        assert!(scope.contains("note.md"));
+        assert!(scope.contains("deep/nested/note.md"));
+    }
+
+    #[test]
+    fn test_include_globs() {
+        let scope = scope(&["notes/**", "*.md"], &[]);
+        assert!(scope.contains("notes/a.md"));
+        assert!(scope.contains("notes/deep/b.md"));
+        assert!(scope.contains("root.md"));
+        assert!(!scope.contains("journal/2024-01-01.md"));
+    }
+
+    #[test]
+    fn test_exclude_wins_over_include() {
+        let scope = scope(&["notes/"], &["notes/private/", "**/draft-*.md"]);
+        assert!(scope.contains("notes/a.md"));
+        assert!(!scope.contains("notes/private/secret.md"));
+        assert!(!scope.contains("notes/deep/draft-1.md"));
+        assert!(!scope.contains("other/a.md"));
+    }
+
+    #[test]
+    fn test_star_stays_within_segment() {
+        let scope = scope(&["journal/*.md"], &[]);
+        assert!(scope.contains("journal/2024-w01.md"));
+        assert!(!scope.contains("journal/archive/2023-w01.md"));
+    }
+
+    #[test]
+    fn test_question_mark_matches_one_character() {
+        let scope = scope(&[], &["tmp?.md"]);
+        assert!(!scope.contains("tmp1.md"));
+        assert!(scope.contains("tmp.md"));
+        assert!(scope.contains("tmp12.md"));
+    }
+}
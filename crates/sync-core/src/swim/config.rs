@@ -0,0 +1,70 @@
+//! SWIM protocol timing.
+//!
+//! The defaults suit a LAN. On slower or lossy links (WAN, mobile) raise the
+//! probe timeout and suspicion multiplier so slow peers aren't declared dead;
+//! on a fast LAN lower them to detect failures sooner.
+
+use super::FailureDetectorConfig;
+use std::time::Duration;
+
+/// Tunable SWIM parameters shared by the membership list and failure detector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwimConfig {
+    /// How often to probe a random peer (default: 1s)
+    pub probe_interval: Duration,
+    /// How long to wait for a direct ack before probing indirectly (default: 500ms)
+    pub probe_timeout: Duration,
+    /// Number of peers asked to probe indirectly, K (default: 3)
+    pub indirect_probes: usize,
+    /// Probe intervals a suspected peer has to refute before it's dead (default: 5)
+    pub suspicion_multiplier: u32,
+}
+
+impl SwimConfig {
+    /// Time before a suspected peer is declared dead.
+    pub fn suspicion_timeout(&self) -> Duration {
+        self.probe_interval
+            .saturating_mul(self.suspicion_multiplier)
+    }
+}
+
+impl Default for SwimConfig {
+    fn default() -> Self {
+        Self {
+            probe_interval: Duration::from_secs(1),
+            probe_timeout: Duration::from_millis(500),
+            indirect_probes: 3,
+            suspicion_multiplier: 5,
+        }
+    }
+}
+
+impl From<SwimConfig> for FailureDetectorConfig {
+    fn from(config: SwimConfig) -> Self {
+        Self {
+            ping_interval: config.probe_interval,
+            ping_timeout: config.probe_timeout,
+            indirect_peers: config.indirect_probes,
+            suspicion_timeout: config.suspicion_timeout(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suspicion_timeout_scales_with_probe_interval() {
+        let config = SwimConfig {
+            probe_interval: Duration::from_millis(250),
+            suspicion_multiplier: 8,
+            ..SwimConfig::default()
+        };
+        assert_eq!(config.suspicion_timeout(), Duration::from_secs(2));
+        assert_eq!(
+            SwimConfig::default().suspicion_timeout(),
+            Duration::from_secs(5)
+        );
+    }
+}
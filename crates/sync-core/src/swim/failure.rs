@@ -6,6 +6,7 @@
 //! 3. If indirect ping also fails, mark target as "suspected"
 //! 4. If suspicion timeout expires, mark as "dead"
 
+use super::SwimConfig;
 use crate::PeerId;
 use std::collections::HashMap;
 use std::time::Duration;
@@ -25,12 +26,7 @@ pub struct FailureDetectorConfig {
 
 impl Default for FailureDetectorConfig {
     fn default() -> Self {
-        Self {
-            ping_interval: Duration::from_secs(1),
-            ping_timeout: Duration::from_millis(500),
-            indirect_peers: 3,
-            suspicion_timeout: Duration::from_secs(5),
-        }
+        SwimConfig::default().into()
     }
 }
 
@@ -472,6 +468,46 @@ mod tests {
         assert_eq!(detector.suspicion_count(), 0);
     }
 
+    /// Run a probe loop against a peer that never responds, returning when it's declared dead.
+    fn time_until_dead(config: FailureDetectorConfig) -> u64 {
+        let mut detector = FailureDetector::new(config);
+        detector.start_ping(peer_a(), 0);
+
+        for now in (0..60_000).step_by(100) {
+            for event in detector.check_timeouts(now) {
+                match event {
+                    FailureEvent::PeerSuspected { peer_id } => detector.suspect(peer_id, 1, now),
+                    FailureEvent::PeerDead { .. } => return now,
+                    _ => {}
+                }
+            }
+            for (seq, _) in detector.pending_indirect_probes() {
+                detector.set_indirect_peers(seq, vec![peer_b()]);
+            }
+        }
+        panic!("peer was never declared dead");
+    }
+
+    #[test]
+    fn test_shorter_suspicion_timeout_detects_dead_sooner() {
+        let default = SwimConfig::default();
+        let short = SwimConfig {
+            suspicion_multiplier: 2,
+            ..default
+        };
+
+        // Ping times out at 500ms, indirect probes at 1500ms, then suspicion runs
+        assert_eq!(time_until_dead(default.into()), 6500);
+        assert_eq!(time_until_dead(short.into()), 3500);
+
+        let fast_lan = SwimConfig {
+            probe_interval: Duration::from_millis(200),
+            probe_timeout: Duration::from_millis(100),
+            ..default
+        };
+        assert_eq!(time_until_dead(fast_lan.into()), 1300);
+    }
+
     #[test]
     fn test_clear_suspicion() {
         let mut detector = FailureDetector::new(test_config());
@@ -5,7 +5,7 @@
 //! - Their current state (Alive, Suspected, Dead)
 //! - Incarnation numbers for conflict resolution
 
-use super::{GossipUpdate, PeerInfo};
+use super::{FailureDetector, GossipUpdate, PeerInfo, SwimConfig};
 use crate::protocol::GossipMessage;
 use crate::PeerId;
 use serde::Serialize;
@@ -15,7 +15,8 @@ use std::collections::HashMap;
 const MAX_GOSSIP_QUEUE_SIZE: usize = 100;
 
 /// State of a member in the membership list.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub enum MemberState {
     /// Peer is believed to be alive
     Alive,
@@ -72,6 +73,28 @@ impl Member {
     }
 }
 
+/// Serializable view of one member, for debug UIs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberSnapshot {
+    /// Peer ID as a hex string
+    pub peer_id: String,
+    pub address: Option<String>,
+    pub state: MemberState,
+    pub incarnation: u64,
+}
+
+impl From<&Member> for MemberSnapshot {
+    fn from(member: &Member) -> Self {
+        Self {
+            peer_id: member.info.peer_id.to_string(),
+            address: member.info.address.clone(),
+            state: member.state,
+            incarnation: member.incarnation,
+        }
+    }
+}
+
 /// Result of processing incoming gossip updates.
 ///
 /// Named fields prevent callers from accidentally relaying raw input
@@ -84,6 +107,36 @@ pub struct ProcessedGossip {
     pub relay: Vec<GossipUpdate>,
 }
 
+/// Why `validate_gossip` rejected a gossip update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "camelCase")]
+pub enum GossipRejection {
+    /// Not a valid update: wrong shape, unknown type, or malformed peer ID
+    Malformed { error: String },
+    /// Incarnation older than the one already known for the peer
+    StaleIncarnation { incarnation: u64, known: u64 },
+}
+
+/// A gossip update rejected by `validate_gossip`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RejectedGossip {
+    /// Position of the update in the received array
+    pub index: usize,
+    /// The update as received
+    pub update: serde_json::Value,
+    #[serde(flatten)]
+    pub reason: GossipRejection,
+}
+
+/// Received gossip split into updates to process and updates rejected.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GossipValidation {
+    /// Valid updates, in received order — pass these to `process_gossip`
+    pub accepted: Vec<GossipUpdate>,
+    /// Rejected updates with the reason for each
+    pub rejected: Vec<RejectedGossip>,
+}
+
 /// Messages to send after a peer completes its handshake.
 ///
 /// Named fields prevent callers from mixing up which message goes where.
@@ -115,6 +168,8 @@ pub struct MembershipList {
     pending_gossip: Vec<GossipUpdate>,
     /// Maximum gossip updates to piggyback per message
     gossip_fanout: usize,
+    /// Probe timing and indirect-probe fanout
+    config: SwimConfig,
 }
 
 impl MembershipList {
@@ -139,9 +194,31 @@ impl MembershipList {
             members: HashMap::new(),
             pending_gossip: Vec::new(),
             gossip_fanout: 3,
+            config: SwimConfig::default(),
         }
     }
 
+    /// Use `config` for probe timing instead of the defaults.
+    pub fn with_config(mut self, config: SwimConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Change the probe timing after construction.
+    pub fn set_config(&mut self, config: SwimConfig) {
+        self.config = config;
+    }
+
+    /// Get the SWIM configuration.
+    pub fn config(&self) -> &SwimConfig {
+        &self.config
+    }
+
+    /// Create a failure detector for the probe loop using this list's config.
+    pub fn failure_detector(&self) -> FailureDetector {
+        FailureDetector::new(self.config.into())
+    }
+
     /// Get our local peer ID.
     pub fn local_peer_id(&self) -> PeerId {
         self.local_peer_id
@@ -274,6 +351,14 @@ impl MembershipList {
         self.members.values()
     }
 
+    /// Snapshot of every member in any state, sorted by peer ID.
+    pub fn snapshot(&self) -> Vec<MemberSnapshot> {
+        let mut members: Vec<MemberSnapshot> =
+            self.members.values().map(MemberSnapshot::from).collect();
+        members.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+        members
+    }
+
     /// Get all alive members.
     pub fn alive_members(&self) -> impl Iterator<Item = &Member> {
         self.members
@@ -475,6 +560,65 @@ impl MembershipList {
         ProcessedGossip { new_peers, relay }
     }
 
+    /// Validate a JSON array of received gossip before processing it.
+    ///
+    /// Unlike deserializing the whole array, a bad entry doesn't discard the
+    /// rest: each update is parsed on its own (which checks the peer ID format)
+    /// and rejected if it's malformed or carries an incarnation older than the
+    /// one known for its peer. Fails only if `gossip_json` isn't a JSON array.
+    pub fn validate_gossip(&self, gossip_json: &str) -> serde_json::Result<GossipValidation> {
+        let entries: Vec<serde_json::Value> = serde_json::from_str(gossip_json)?;
+        let mut validation = GossipValidation::default();
+
+        for (index, entry) in entries.into_iter().enumerate() {
+            let reason = match serde_json::from_value::<GossipUpdate>(entry.clone()) {
+                Err(e) => GossipRejection::Malformed {
+                    error: e.to_string(),
+                },
+                Ok(update) => match self.stale_incarnation(&update) {
+                    Some(reason) => reason,
+                    None => {
+                        validation.accepted.push(update);
+                        continue;
+                    }
+                },
+            };
+            validation.rejected.push(RejectedGossip {
+                index,
+                update: entry,
+                reason,
+            });
+        }
+
+        Ok(validation)
+    }
+
+    /// Rejection for an update older than what we know about its peer.
+    ///
+    /// Incarnations only grow, so an update below the known incarnation
+    /// (our own, for updates about us) describes a state already superseded.
+    fn stale_incarnation(&self, update: &GossipUpdate) -> Option<GossipRejection> {
+        let (peer_id, incarnation) = match update {
+            GossipUpdate::Alive { peer, incarnation } => (peer.peer_id, *incarnation),
+            GossipUpdate::Suspect {
+                peer_id,
+                incarnation,
+            }
+            | GossipUpdate::Dead {
+                peer_id,
+                incarnation,
+            } => (*peer_id, *incarnation),
+            GossipUpdate::Removed { .. } => return None,
+        };
+
+        let known = if peer_id == self.local_peer_id {
+            self.local_incarnation
+        } else {
+            self.members.get(&peer_id)?.incarnation
+        };
+        (incarnation < known).then_some(GossipRejection::StaleIncarnation { incarnation, known })
+    }
+
     /// Generate Alive gossip updates for all known members.
     ///
     /// Used when responding to a new peer to share full membership.
@@ -540,6 +684,11 @@ impl MembershipList {
         candidates.truncate(k);
         candidates
     }
+
+    /// Pick the configured number of random members to probe `target` indirectly.
+    pub fn pick_indirect_probers(&self, target: PeerId) -> Vec<&Member> {
+        self.pick_k_random_members(self.config.indirect_probes, target)
+    }
 }
 
 #[cfg(test)]
@@ -800,6 +949,30 @@ mod tests {
         assert!(!changed);
     }
 
+    #[test]
+    fn test_snapshot_serializes_every_state() {
+        let peer_d: PeerId = "0fedcba987654321".parse().unwrap();
+        let mut list = MembershipList::new(local_id(), None);
+        list.add(PeerInfo::new(peer_a(), Some("ws://a:8080".into())), 1);
+        list.add(PeerInfo::client_only(peer_b()), 2);
+        list.add(PeerInfo::client_only(peer_c()), 1);
+        list.add(PeerInfo::client_only(peer_d), 4);
+        list.suspect(peer_b(), 3);
+        list.mark_dead(peer_c());
+        list.mark_removed(peer_d);
+
+        let json = serde_json::to_value(list.snapshot()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "peerId": peer_d.to_string(), "address": null, "state": "removed", "incarnation": 4 },
+                { "peerId": peer_b().to_string(), "address": null, "state": "suspected", "incarnation": 3 },
+                { "peerId": peer_a().to_string(), "address": "ws://a:8080", "state": "alive", "incarnation": 1 },
+                { "peerId": peer_c().to_string(), "address": null, "state": "dead", "incarnation": 1 },
+            ])
+        );
+    }
+
     #[test]
     fn test_mark_removed() {
         let mut list = MembershipList::new(local_id(), None);
@@ -870,6 +1043,65 @@ mod tests {
         assert_eq!(member.state, MemberState::Suspected);
     }
 
+    #[test]
+    fn test_validate_gossip_reports_rejections() {
+        let mut list = MembershipList::with_incarnation(local_id(), None, 5);
+        list.add(PeerInfo::new(peer_a(), None), 3);
+
+        let gossip = serde_json::json!([
+            // Valid: newer incarnation for a known peer, and an unknown peer
+            {"type": "alive", "peer": {"peerId": peer_a().to_string(), "address": null}, "incarnation": 4},
+            {"type": "suspect", "peer_id": peer_b().to_string(), "incarnation": 1},
+            // Malformed: bad peer ID, unknown type, missing incarnation
+            {"type": "dead", "peer_id": "not-a-peer-id", "incarnation": 1},
+            {"type": "vanished", "peer_id": peer_c().to_string()},
+            {"type": "dead", "peer_id": peer_c().to_string()},
+            // Stale: older than known for peer A, and than our own incarnation
+            {"type": "dead", "peer_id": peer_a().to_string(), "incarnation": 2},
+            {"type": "suspect", "peer_id": local_id().to_string(), "incarnation": 4},
+            // Removed carries no incarnation
+            {"type": "removed", "peer_id": peer_c().to_string()},
+        ]);
+
+        let validation = list.validate_gossip(&gossip.to_string()).unwrap();
+
+        assert_eq!(
+            validation.accepted,
+            vec![
+                GossipUpdate::alive(PeerInfo::new(peer_a(), None), 4),
+                GossipUpdate::suspect(peer_b(), 1),
+                GossipUpdate::removed(peer_c()),
+            ]
+        );
+        let rejected: Vec<_> = validation
+            .rejected
+            .iter()
+            .map(|r| (r.index, r.reason.clone()))
+            .collect();
+        assert!(matches!(rejected[0], (2, GossipRejection::Malformed { .. })));
+        assert!(matches!(rejected[1], (3, GossipRejection::Malformed { .. })));
+        assert!(matches!(rejected[2], (4, GossipRejection::Malformed { .. })));
+        assert_eq!(
+            rejected[3..],
+            [
+                (5, GossipRejection::StaleIncarnation { incarnation: 2, known: 3 }),
+                (6, GossipRejection::StaleIncarnation { incarnation: 4, known: 5 }),
+            ]
+        );
+        assert_eq!(validation.rejected[0].update, gossip[2]);
+
+        // Validation doesn't change the membership list
+        assert_eq!(list.get(&peer_a()).unwrap().incarnation, 3);
+        assert!(list.get(&peer_b()).is_none());
+    }
+
+    #[test]
+    fn test_validate_gossip_requires_an_array() {
+        let list = MembershipList::new(local_id(), None);
+        assert!(list.validate_gossip("{\"type\": \"alive\"}").is_err());
+        assert_eq!(list.validate_gossip("[]").unwrap(), GossipValidation::default());
+    }
+
     #[test]
     fn test_process_gossip_dead() {
         let mut list = MembershipList::new(local_id(), None);
@@ -1045,6 +1277,27 @@ mod tests {
         assert_eq!(members.len(), 1);
     }
 
+    #[test]
+    fn test_pick_indirect_probers_uses_configured_k() {
+        let config = SwimConfig {
+            indirect_probes: 1,
+            ..SwimConfig::default()
+        };
+        let mut list = MembershipList::new(local_id(), None).with_config(config);
+
+        list.add(PeerInfo::new(peer_a(), None), 1);
+        list.add(PeerInfo::new(peer_b(), None), 1);
+        list.add(PeerInfo::new(peer_c(), None), 1);
+
+        let probers = list.pick_indirect_probers(peer_a());
+        assert_eq!(probers.len(), 1);
+        assert_ne!(probers[0].info.peer_id, peer_a());
+        assert_eq!(
+            list.failure_detector().config().suspicion_timeout,
+            config.suspicion_timeout()
+        );
+    }
+
     // ==================== Iterators ====================
 
     #[test]
@@ -17,11 +17,16 @@
 //! - Rapid convergence through infection-style spread
 
 pub mod buddy;
+pub mod config;
 pub mod failure;
 pub mod membership;
 pub mod messages;
 
 pub use buddy::{select_buddy, BuddyAssignment, BuddyTracker, BuddyVerification};
+pub use config::SwimConfig;
 pub use failure::{FailureDetector, FailureDetectorConfig, FailureEvent};
-pub use membership::{Member, MemberState, MembershipList, PeerConnectedMessages, ProcessedGossip};
+pub use membership::{
+    GossipRejection, GossipValidation, Member, MemberSnapshot, MemberState, MembershipList,
+    PeerConnectedMessages, ProcessedGossip, RejectedGossip,
+};
 pub use messages::{GossipUpdate, PeerInfo, SwimMessage};
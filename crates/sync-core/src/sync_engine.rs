@@ -13,15 +13,18 @@
 //!
 //! This symmetric protocol enables full bidirectional sync in a single round-trip.
 
+use crate::conflicts::{frontier_peers, ConflictRecord, ConflictResolution, DeleteEditPolicy};
 use crate::document::NoteDocument;
 use crate::events::SyncEvent;
 use crate::fs::FileSystem;
 use crate::sync::{SyncMessage, SyncRequestData, SyncResponseData};
 use crate::vault::Vault;
+use crate::PeerId;
 
+use loro::{TreeID, TreeParentId};
 use std::collections::HashMap;
 use thiserror::Error;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
 #[derive(Debug, Error)]
 pub enum SyncEngineError {
@@ -89,6 +92,26 @@ impl<F: FileSystem> Vault<F> {
     pub async fn process_sync_message(
         &self,
         data: &[u8],
+    ) -> Result<(Option<Vec<u8>>, Vec<String>)> {
+        self.process_sync_message_inner(None, data).await
+    }
+
+    /// Process an incoming sync message sent by `from`.
+    ///
+    /// Same as `process_sync_message`, but also records the document versions
+    /// the peer reports in sync requests (see `document_sync_peers`).
+    pub async fn process_sync_message_from(
+        &self,
+        from: PeerId,
+        data: &[u8],
+    ) -> Result<(Option<Vec<u8>>, Vec<String>)> {
+        self.process_sync_message_inner(Some(from), data).await
+    }
+
+    async fn process_sync_message_inner(
+        &self,
+        from: Option<PeerId>,
+        data: &[u8],
     ) -> Result<(Option<Vec<u8>>, Vec<String>)> {
         // Ensure consistency before processing any sync message
         self.ensure_consistency().await?;
@@ -107,6 +130,11 @@ impl<F: FileSystem> Vault<F> {
                 registry_version,
                 document_versions,
             } => {
+                if let Some(peer) = from
+                    && self.record_peer_versions(peer, &registry_version, &document_versions)
+                {
+                    self.save_registry_acks().await?;
+                }
                 // Peer is requesting sync - respond with SyncExchange (symmetric protocol)
                 let exchange = self.prepare_sync_exchange(&registry_version, document_versions).await?;
                 let exchange_bytes = bincode::serialize(&exchange)
@@ -143,6 +171,16 @@ impl<F: FileSystem> Vault<F> {
 
                 debug!("SyncExchange: received {} document updates, {} version vectors",
                     response.document_updates.len(), request.document_versions.len());
+                if let Some(peer) = from {
+                    let changed = self.record_peer_versions(
+                        peer,
+                        &request.registry_version,
+                        &request.document_versions,
+                    );
+                    if changed {
+                        self.save_registry_acks().await?;
+                    }
+                }
 
                 // Track which files we're receiving so we don't echo them back
                 let received_files: std::collections::HashSet<String> =
@@ -208,7 +246,15 @@ impl<F: FileSystem> Vault<F> {
 
                 // Mark as synced BEFORE deleting (for echo detection)
                 self.mark_synced(&path);
+                let tracked = self.is_path_synced(&path) && self.path_to_node().contains_key(&path);
                 self.delete_file(&path).await?;
+                // Remove the markdown too, as for deletions that arrive in a full
+                // sync. Left behind, it would be picked up again as a new note
+                // whose history restarts under our peer ID, reusing op IDs peers
+                // already have from the deleted one.
+                if tracked && self.fs.exists(&path).await.unwrap_or(false) {
+                    self.fs.delete(&path).await.map_err(crate::vault::VaultError::from)?;
+                }
                 Ok((None, vec![path]))
             }
 
@@ -228,7 +274,22 @@ impl<F: FileSystem> Vault<F> {
                 self.mark_synced(&old_path);
                 self.mark_synced(&new_path);
                 self.rename_file(&old_path, &new_path).await?;
-                Ok((None, vec![new_path]))
+                // Move the markdown too, as for moves that arrive in a full sync,
+                // so the old path isn't picked up again as a new note. If sync
+                // already wrote the new path, the note was merged into it.
+                if self.is_path_synced(&old_path) {
+                    if !self.fs.exists(&new_path).await.map_err(crate::vault::VaultError::from)? {
+                        self.move_note_files(&old_path, &new_path).await?;
+                    } else if self.fs.exists(&old_path).await.map_err(crate::vault::VaultError::from)? {
+                        self.fs.delete(&old_path).await.map_err(crate::vault::VaultError::from)?;
+                    }
+                }
+
+                let mut modified = vec![new_path.clone()];
+                if self.rewrites_links_on_rename() {
+                    modified.extend(self.rewrite_links_after_rename(&old_path, &new_path).await?);
+                }
+                Ok((None, modified))
             }
         }
     }
@@ -428,6 +489,8 @@ impl<F: FileSystem> Vault<F> {
     async fn apply_registry_updates(&self, data: &[u8]) -> Result<()> {
         debug!("apply_registry_updates: data_len={}", data.len());
 
+        let before = self.node_paths();
+
         // Import registry updates
         self.registry_mut()
             .import(data)
@@ -437,14 +500,11 @@ impl<F: FileSystem> Vault<F> {
         self.rebuild_path_cache();
 
         // Sync filesystem with tree state - delete files that are deleted in tree
-        self.apply_registry_changes().await?;
+        self.apply_registry_changes(&before).await?;
+        self.apply_registry_moves(&before).await?;
 
-        // Save updated registry to disk
-        let registry_bytes = self.registry().export(loro::ExportMode::snapshot()).unwrap();
-        self.fs
-            .write(&format!("{}/registry.loro", crate::vault::SYNC_DIR), &registry_bytes)
-            .await
-            .map_err(crate::vault::VaultError::from)?;
+        // Save updated registry to disk (appended to the log, compacted periodically)
+        self.save_registry().await?;
 
         // Mark registry as synced so it will be reconciled before next sync import
         self.mark_registry_synced();
@@ -455,15 +515,64 @@ impl<F: FileSystem> Vault<F> {
 
     /// Apply registry changes to filesystem.
     ///
-    /// Deletes files on disk that are marked as deleted in the tree.
-    async fn apply_registry_changes(&self) -> Result<()> {
+    /// Deletes files on disk whose nodes were live before the import and are
+    /// now deleted in the tree. A note with edits the deleting peer hadn't seen
+    /// is a delete/edit conflict, resolved by the vault's [`DeleteEditPolicy`].
+    /// `before` holds each live node's path before the import, since a node
+    /// under a deleted folder can no longer be traced back to the root.
+    async fn apply_registry_changes(&self, before: &HashMap<TreeID, String>) -> Result<()> {
         let tree = self.file_tree();
+        let policy = self.delete_edit_policy();
 
-        // Find deleted files and clean them up
+        // Find deleted files and clean them up (emptied folders are pruned
+        // from the tree, but their directories are left alone)
         for node_id in tree.nodes() {
-            if tree.is_node_deleted(&node_id).unwrap_or(false) {
-                // Get the path before it was deleted (if we can reconstruct it)
-                if let Some(path) = self.get_node_path(&node_id) {
+            if tree.is_node_deleted(&node_id).unwrap_or(false)
+                && !Self::is_folder_node(&tree, node_id)
+            {
+                // Only nodes this import deleted; ones deleted earlier were
+                // already handled
+                if let Some(path) = before.get(&node_id).cloned() {
+                    // A live node has the path again (e.g. a note kept after a conflict),
+                    // or we don't sync the path and leave our copy alone
+                    if self.path_to_node().contains_key(&path) || !self.is_path_synced(&path) {
+                        continue;
+                    }
+
+                    // Deleted along with a folder that was pruned while a peer
+                    // added this file to it, not deleted itself: keep it if we
+                    // have it, so it's registered again
+                    if matches!(tree.parent(node_id), Some(TreeParentId::Node(_))) {
+                        if self.fs.exists(&path).await.unwrap_or(false) {
+                            info!("Keeping {}: its folder was pruned concurrently", path);
+                            self.register_file(&path)?;
+                        }
+                        continue;
+                    }
+
+                    if self.has_unseen_edits(&node_id, &path).await? {
+                        self.emit(SyncEvent::DeleteEditConflict {
+                            path: path.clone(),
+                            policy,
+                            timestamp: self.now_ms(),
+                        });
+                        if policy == DeleteEditPolicy::EditWins {
+                            info!("Keeping {}: deleted by a peer that hadn't seen its latest edits", path);
+                            self.register_file(&path)?;
+                            // Bring the markdown back if it's already gone from disk
+                            if !self.fs.exists(&path).await.unwrap_or(false) {
+                                let doc = self.get_document(&path).await?;
+                                self.mark_synced(&path);
+                                self.fs
+                                    .write(&path, doc.to_markdown().as_bytes())
+                                    .await
+                                    .map_err(crate::vault::VaultError::from)?;
+                            }
+                            continue;
+                        }
+                        info!("Deleting {} despite edits the deleting peer hadn't seen", path);
+                    }
+
                     // Remove from filesystem
                     if self.fs.exists(&path).await.unwrap_or(false) {
                         debug!("apply_registry_changes: deleting {}", path);
@@ -476,10 +585,10 @@ impl<F: FileSystem> Vault<F> {
 
                     // Remove .loro document
                     let sync_path = self.document_sync_path(&path);
-                    if self.fs.exists(&sync_path).await.unwrap_or(false) {
-                        if let Err(e) = self.fs.delete(&sync_path).await {
-                            warn!("Failed to delete .loro file {}: {}", sync_path, e);
-                        }
+                    if self.fs.exists(&sync_path).await.unwrap_or(false)
+                        && let Err(e) = self.fs.delete(&sync_path).await
+                    {
+                        warn!("Failed to delete .loro file {}: {}", sync_path, e);
                     }
 
                     // Remove from documents cache
@@ -491,6 +600,79 @@ impl<F: FileSystem> Vault<F> {
         Ok(())
     }
 
+    /// Whether the note at `path` has edits the peer that deleted `node_id` hadn't seen.
+    ///
+    /// False for deletions made before the deleted version was recorded.
+    async fn has_unseen_edits(&self, node_id: &TreeID, path: &str) -> Result<bool> {
+        let Some(deleted) = self.node_deleted_version(node_id) else {
+            return Ok(false);
+        };
+        let Some(local) = self.current_version(path).await? else {
+            return Ok(false);
+        };
+        Ok(!Self::version_includes(&deleted, &local))
+    }
+
+    /// Move files on disk to match nodes moved by a registry update.
+    ///
+    /// Concurrent moves of the same note are resolved by the registry tree, so
+    /// every peer converges on one path. A move is concurrent with ours when the
+    /// peer moved the note from a path we no longer had it at; we then follow
+    /// the tree, moving the note off our path, and emit `MoveConflict`.
+    async fn apply_registry_moves(&self, before: &HashMap<TreeID, String>) -> Result<()> {
+        let after = self.node_paths();
+
+        for (node_id, old_path) in before {
+            let Some(new_path) = after.get(node_id) else {
+                continue;
+            };
+            // A file outside the sync scope isn't the note that moved
+            if new_path == old_path || !self.is_path_synced(old_path) {
+                continue;
+            }
+
+            // Don't overwrite an unrelated file that already has the new path
+            let old_exists = self.fs.exists(old_path).await.unwrap_or(false);
+            if old_exists && self.fs.exists(new_path).await.unwrap_or(false) {
+                warn!(
+                    "apply_registry_moves: {} already exists, leaving {} in place",
+                    new_path, old_path
+                );
+                continue;
+            }
+
+            debug!("apply_registry_moves: moving {} -> {}", old_path, new_path);
+            // Mark both paths as synced BEFORE moving (for echo detection)
+            self.mark_synced(old_path);
+            self.mark_synced(new_path);
+            self.move_note_files(old_path, new_path).await?;
+
+            self.emit(SyncEvent::FileOp {
+                operation: "rename".into(),
+                path: old_path.clone(),
+                new_path: Some(new_path.clone()),
+                timestamp: self.now_ms(),
+            });
+
+            if let Some(moved_from) = self.node_moved_from(node_id)
+                && &moved_from != old_path
+            {
+                info!(
+                    "Move conflict: {} moved to {} here and {} by a peer, keeping {}",
+                    moved_from, old_path, new_path, new_path
+                );
+                self.emit(SyncEvent::MoveConflict {
+                    path: moved_from,
+                    local_path: old_path.clone(),
+                    resolved_path: new_path.clone(),
+                    timestamp: self.now_ms(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     /// Apply document updates from a sync response.
     ///
     /// Note: SyncResponse doesn't include mtime, so "latest wins" falls back to "remote wins"
@@ -527,6 +709,10 @@ impl<F: FileSystem> Vault<F> {
         remote_mtime: Option<u64>,
     ) -> Result<bool> {
         debug!("apply_single_update: {} - data_len={}", path, data.len());
+        if !self.is_path_synced(path) {
+            debug!("apply_single_update: {} - outside the sync scope, skipping", path);
+            return Ok(false);
+        }
 
         // Check if document exists (in cache or on disk)
         let sync_path = self.document_sync_path(path);
@@ -537,6 +723,17 @@ impl<F: FileSystem> Vault<F> {
             .await
             .map_err(crate::vault::VaultError::from)?;
 
+        // An edit made before the peer saw our rename belongs to the note at its
+        // new path, rather than to a new note at the old one
+        if !exists_in_cache
+            && !exists_on_disk
+            && !self.path_to_node().contains_key(path)
+            && let Some(new_path) = self.moved_to(path)
+        {
+            debug!("apply_single_update: {} was renamed to {}, applying there", path, new_path);
+            return Box::pin(self.apply_single_update(&new_path, data, remote_mtime)).await;
+        }
+
         if exists_in_cache || exists_on_disk {
             // Get local mtime and peer_id before borrowing doc (needed for "latest wins" comparison)
             let local_mtime = self.fs.stat(path).await.ok().map(|s| s.mtime_millis);
@@ -549,6 +746,7 @@ impl<F: FileSystem> Vault<F> {
             // Document exists - check for divergent histories before merging
             let mut doc = self.get_document_mut(path).await?;
             let local_vv = doc.version();
+            let local_markdown = doc.to_markdown();
 
             // Create temp doc FROM LOCAL STATE, then import remote to get merged version
             // This correctly handles incremental updates (not just full snapshots)
@@ -564,6 +762,13 @@ impl<F: FileSystem> Vault<F> {
             // Documents created independently have different doc_ids.
             let remote_only_doc = NoteDocument::from_bytes(path, data, peer_id)?;
 
+            // Leave the note as it is if either outcome would be over the size limit
+            if self.check_note_size(path, &temp_doc.body().to_string()).is_err()
+                || self.check_note_size(path, &remote_only_doc.body().to_string()).is_err()
+            {
+                return Ok(false);
+            }
+
             let local_doc_id = doc.doc_id();
             let remote_doc_id = remote_only_doc.doc_id();
 
@@ -578,6 +783,7 @@ impl<F: FileSystem> Vault<F> {
                 path, local_doc_id, remote_doc_id, is_divergent
             );
 
+            let mut conflict = None;
             let modified = if is_divergent {
                 // Divergent histories - use content reconciliation to avoid interleaving
                 debug!(
@@ -604,6 +810,7 @@ impl<F: FileSystem> Vault<F> {
 
                     if body_changed || fm_changed {
                         doc.commit();
+                        conflict = Some(ConflictResolution::RemoteWins);
                         true
                     } else {
                         false
@@ -613,12 +820,23 @@ impl<F: FileSystem> Vault<F> {
                         "apply_single_update: {} - local is newer (local={:?}, remote={:?}), keeping local",
                         path, local_mtime, remote_mtime
                     );
+                    if remote_only_doc.to_markdown() != local_markdown {
+                        conflict = Some(ConflictResolution::LocalKept);
+                    }
                     false
                 }
             } else if !local_includes_merged {
                 // Remote has changes we don't have, but histories are compatible - safe to import
                 let version_before = doc.version();
+                let frontiers_before = doc.frontiers();
                 doc.import(data)?;
+
+                // More than one tip after import means the update was concurrent
+                // with local edits rather than a continuation of them
+                let frontiers_after = doc.frontiers();
+                if frontiers_after.len() > 1 && frontiers_after != frontiers_before {
+                    conflict = Some(ConflictResolution::Merged);
+                }
                 version_before != doc.version()
             } else {
                 // We already have everything remote has
@@ -627,26 +845,74 @@ impl<F: FileSystem> Vault<F> {
 
             debug!("apply_single_update: {} - modified={}", path, modified);
 
+            let conflict = conflict.map(|resolution| {
+                let peers = match resolution {
+                    ConflictResolution::Merged => frontier_peers(&doc.frontiers()),
+                    _ => {
+                        let mut peers = frontier_peers(&doc.frontiers());
+                        peers.extend(frontier_peers(&remote_only_doc.frontiers()));
+                        peers.sort();
+                        peers.dedup();
+                        peers
+                    }
+                };
+                ConflictRecord::new(
+                    path,
+                    peers,
+                    self.now_ms(),
+                    resolution,
+                    &local_markdown,
+                    &doc.to_markdown(),
+                )
+            });
+
             if modified {
-                // Update the document in cache before saving
-                self.update_document(path, doc);
                 // Mark as synced BEFORE writing to disk (for echo detection)
                 self.mark_synced(path);
+                self.record_synced_version(path, doc.version());
+                // Update the document in cache before saving
+                self.update_document(path, doc);
                 self.save_document(path).await?;
                 debug!("apply_single_update: saved {} to disk", path);
             }
 
+            if let Some(record) = conflict {
+                self.record_conflict(&record).await?;
+            }
+
             Ok(modified)
         } else {
+            // An incremental update needs history we don't have (e.g. an edit to a
+            // note we deleted). Left pending, our next edit would restart the note
+            // under our peer ID, reusing op IDs peers already have; a full sync
+            // sends the whole note if it's still live.
+            let meta = loro::LoroDoc::decode_import_blob_meta(data, false)
+                .map_err(|e| crate::vault::VaultError::Other(format!("Failed to decode blob meta: {}", e)))?;
+            if !meta.partial_start_vv.is_empty() {
+                debug!("apply_single_update: {} - update without its base history, skipping", path);
+                return Ok(false);
+            }
+
             // Document is new - create directly from sync data (preserves peer ID)
             let doc = NoteDocument::from_bytes(path, data, self.peer_id())?;
+            if self.check_note_size(path, &doc.body().to_string()).is_err() {
+                return Ok(false);
+            }
+            // A note deleted here, from a peer that hasn't seen the deletion yet.
+            // The peer settles a delete/edit conflict when it does, and registers
+            // the note again if it's kept.
+            if !self.path_to_node().contains_key(path) && self.is_deleted_note(&doc) {
+                debug!("apply_single_update: {} - deleted here, skipping", path);
+                return Ok(false);
+            }
+            self.protect_deleted_edits(&doc);
 
             // Mark as synced BEFORE writing to disk (for echo detection)
             self.mark_synced(path);
+            self.record_synced_version(path, doc.version());
 
             // Save to disk
-            let snapshot = doc.export_snapshot();
-            self.fs.write(&sync_path, &snapshot).await.map_err(crate::vault::VaultError::from)?;
+            self.write_document_state(path, &doc).await?;
             self.fs.write(path, doc.to_markdown().as_bytes()).await.map_err(crate::vault::VaultError::from)?;
 
             // Note: Don't register in tree here - tree sync handles that via registry.
@@ -788,6 +1054,31 @@ mod tests {
         assert!(doc.to_markdown().contains("Updated content"));
     }
 
+    #[tokio::test]
+    async fn test_is_modification_local_distinguishes_sync_from_edit() {
+        let vault1 = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(InMemoryFs::new(), test_peer_id_2()).await.unwrap();
+
+        vault1.fs.write("note.md", b"From peer").await.unwrap();
+        vault1.on_file_changed("note.md").await.unwrap();
+        let update = vault1.prepare_document_update("note.md").await.unwrap().unwrap();
+        vault2.process_sync_message(&update).await.unwrap();
+
+        // The watcher fires for the write sync just made: nothing new locally
+        vault2.on_file_changed("note.md").await.unwrap();
+        let version = vault2.get_document_version("note.md").await.unwrap().unwrap();
+        assert!(!vault2.is_modification_local("note.md", &version));
+
+        // A local edit on top of the synced content adds operations
+        vault2.fs.write("note.md", b"From peer, edited here").await.unwrap();
+        vault2.on_file_changed("note.md").await.unwrap();
+        let version = vault2.get_document_version("note.md").await.unwrap().unwrap();
+        assert!(vault2.is_modification_local("note.md", &version));
+
+        // Files sync never wrote are always local
+        assert!(vault1.is_modification_local("note.md", &version));
+    }
+
     #[tokio::test]
     async fn test_version_includes_basic() {
         // Test the version_includes helper function with direct Loro operations
@@ -1414,4 +1705,398 @@ mod tests {
         // This is tested implicitly by the fallback in apply_single_update:
         // match (&local_doc_id, &remote_doc_id) { ... _ => false }
     }
+
+    /// Sync vault2 from vault1 so both share note.md's history.
+    async fn shared_note_vaults(
+        fs1: &std::sync::Arc<InMemoryFs>,
+        fs2: &std::sync::Arc<InMemoryFs>,
+    ) -> (Vault<std::sync::Arc<InMemoryFs>>, Vault<std::sync::Arc<InMemoryFs>>) {
+        use std::sync::Arc;
+
+        fs1.write("note.md", b"Line 1").await.unwrap();
+        let vault1 = Vault::init(Arc::clone(fs1), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(Arc::clone(fs2), test_peer_id_2()).await.unwrap();
+
+        let request = vault2.prepare_sync_request().await.unwrap();
+        let (exchange, _) = vault1.process_sync_message(&request).await.unwrap();
+        let (final_resp, _) = vault2.process_sync_message(&exchange.unwrap()).await.unwrap();
+        if let Some(resp) = final_resp {
+            vault1.process_sync_message(&resp).await.unwrap();
+        }
+        (vault1, vault2)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_merge_records_conflict() {
+        use crate::conflicts::ConflictResolution;
+        use std::sync::Arc;
+
+        let fs1 = Arc::new(InMemoryFs::new());
+        let fs2 = Arc::new(InMemoryFs::new());
+        let (vault1, vault2) = shared_note_vaults(&fs1, &fs2).await;
+        assert!(vault1.conflict_history().await.unwrap().is_empty());
+
+        // Concurrent edits on both sides
+        fs2.write("note.md", b"Line 1\nLine 2 from vault2").await.unwrap();
+        vault2.on_file_changed("note.md").await.unwrap();
+        fs1.write("note.md", b"Line 1\nLine 2 from vault1").await.unwrap();
+        vault1.on_file_changed("note.md").await.unwrap();
+
+        let update = vault2.prepare_document_update("note.md").await.unwrap().unwrap();
+        vault1.process_sync_message(&update).await.unwrap();
+
+        let history = vault1.conflict_history().await.unwrap();
+        assert_eq!(history.len(), 1);
+        let record = &history[0];
+        assert_eq!(record.path, "note.md");
+        assert_eq!(record.resolution, ConflictResolution::Merged);
+        assert_eq!(
+            record.peers,
+            vec![test_peer_id().to_string(), test_peer_id_2().to_string()]
+        );
+        assert!(record.lines_added >= 1, "remote line should be added: {:?}", record);
+        assert!(record.timestamp > 0.0);
+
+        // The log is on disk, so it survives a reload
+        drop(vault1);
+        let reloaded = Vault::load(Arc::clone(&fs1), test_peer_id()).await.unwrap();
+        assert_eq!(reloaded.conflict_history().await.unwrap(), history);
+    }
+
+    #[tokio::test]
+    async fn test_sequential_update_records_no_conflict() {
+        use std::sync::Arc;
+
+        let fs1 = Arc::new(InMemoryFs::new());
+        let fs2 = Arc::new(InMemoryFs::new());
+        let (vault1, vault2) = shared_note_vaults(&fs1, &fs2).await;
+
+        // Only vault2 edits - vault1 just catches up
+        fs2.write("note.md", b"Line 1\nLine 2").await.unwrap();
+        vault2.on_file_changed("note.md").await.unwrap();
+
+        let update = vault2.prepare_document_update("note.md").await.unwrap().unwrap();
+        let (_, modified) = vault1.process_sync_message(&update).await.unwrap();
+
+        assert_eq!(modified, vec!["note.md".to_string()]);
+        assert!(vault1.conflict_history().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_divergent_resolution_records_conflict() {
+        use crate::conflicts::ConflictResolution;
+        use std::sync::Arc;
+
+        let fs1 = Arc::new(InMemoryFs::new());
+        let fs2 = Arc::new(InMemoryFs::new());
+        fs1.write("note.md", b"Older content").await.unwrap();
+        fs1.set_mtime("note.md", 1000);
+        fs2.write("note.md", b"Newer content").await.unwrap();
+        fs2.set_mtime("note.md", 2000);
+
+        let vault1 = Vault::init(Arc::clone(&fs1), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(Arc::clone(&fs2), test_peer_id_2()).await.unwrap();
+
+        let older = vault1.prepare_document_update("note.md").await.unwrap().unwrap();
+        let newer = vault2.prepare_document_update("note.md").await.unwrap().unwrap();
+
+        // The older side takes the newer content
+        vault1.process_sync_message(&newer).await.unwrap();
+        let history = vault1.conflict_history().await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].resolution, ConflictResolution::RemoteWins);
+        assert_eq!((history[0].lines_added, history[0].lines_removed), (1, 1));
+        assert!(history[0].peers.contains(&test_peer_id_2().to_string()));
+
+        // The newer side keeps its content but still logs the conflict
+        let (_, modified) = vault2.process_sync_message(&older).await.unwrap();
+        assert!(modified.is_empty());
+        let history = vault2.conflict_history().await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].resolution, ConflictResolution::LocalKept);
+    }
+
+    /// Sync two vaults that both have `Old.md` and a note linking to it, then
+    /// deliver a rename of `Old.md` from vault1 to vault2.
+    async fn apply_synced_rename(rewrite_links: bool) -> (Vault<InMemoryFs>, Vec<String>) {
+        let vault1 = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(InMemoryFs::new(), test_peer_id_2()).await.unwrap();
+        vault2.set_rewrite_links_on_rename(rewrite_links);
+
+        vault1.fs.write("Old.md", b"# Old").await.unwrap();
+        vault1.on_file_changed("Old.md").await.unwrap();
+        vault1.fs.write("Linking.md", b"See [[Old]] and [[Old#Intro|intro]]").await.unwrap();
+        vault1.on_file_changed("Linking.md").await.unwrap();
+
+        let request = vault2.prepare_sync_request().await.unwrap();
+        let (exchange, _) = vault1.process_sync_message(&request).await.unwrap();
+        vault2.process_sync_message(&exchange.unwrap()).await.unwrap();
+
+        let rename = vault1.prepare_file_renamed("Old.md", "archive/New.md").unwrap();
+        let (_, modified) = vault2.process_sync_message(&rename).await.unwrap();
+        (vault2, modified)
+    }
+
+    #[tokio::test]
+    async fn test_file_renamed_rewrites_links_when_enabled() {
+        let (vault2, modified) = apply_synced_rename(true).await;
+
+        assert!(modified.contains(&"archive/New.md".to_string()));
+        assert!(modified.contains(&"Linking.md".to_string()));
+
+        let content = String::from_utf8(vault2.fs.read("Linking.md").await.unwrap()).unwrap();
+        assert_eq!(content, "See [[New]] and [[New#Intro|intro]]");
+
+        // The rewrite is folded into the CRDT so it syncs like a local edit
+        let doc = vault2.get_document("Linking.md").await.unwrap();
+        assert!(doc.to_markdown().contains("[[New#Intro|intro]]"));
+        assert!(vault2.consume_sync_flag("Linking.md"));
+    }
+
+    #[tokio::test]
+    async fn test_file_renamed_leaves_links_when_disabled() {
+        let (vault2, modified) = apply_synced_rename(false).await;
+
+        assert_eq!(modified, vec!["archive/New.md".to_string()]);
+        let content = String::from_utf8(vault2.fs.read("Linking.md").await.unwrap()).unwrap();
+        assert_eq!(content, "See [[Old]] and [[Old#Intro|intro]]");
+    }
+
+    #[tokio::test]
+    async fn test_file_renamed_moves_markdown() {
+        let (vault2, _) = apply_synced_rename(false).await;
+
+        assert!(!vault2.fs.exists("Old.md").await.unwrap());
+        assert_eq!(vault2.fs.read("archive/New.md").await.unwrap(), b"# Old");
+        assert!(!vault2.list_files().await.unwrap().contains(&"Old.md".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_update_for_note_renamed_twice_applies_at_new_path() {
+        let vault1 = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(InMemoryFs::new(), test_peer_id_2()).await.unwrap();
+        vault1.fs.write("note.md", b"# Note").await.unwrap();
+        vault1.on_file_changed("note.md").await.unwrap();
+        let request = vault2.prepare_sync_request().await.unwrap();
+        let (exchange, _) = vault1.process_sync_message(&request).await.unwrap();
+        vault2.process_sync_message(&exchange.unwrap()).await.unwrap();
+
+        for (from, to) in [("note.md", "a.md"), ("a.md", "b.md")] {
+            vault1.fs.write(to, b"# Note").await.unwrap();
+            vault1.fs.delete(from).await.unwrap();
+            vault1.rename_file(from, to).await.unwrap();
+        }
+        vault2.fs.write("note.md", b"# Note\n\nEdited").await.unwrap();
+        vault2.on_file_changed("note.md").await.unwrap();
+        let update = vault2.prepare_document_update("note.md").await.unwrap().unwrap();
+        vault1.process_sync_message(&update).await.unwrap();
+
+        // Not a second copy of the note at its first path
+        assert!(!vault1.fs.exists("note.md").await.unwrap());
+        assert_eq!(vault1.fs.read("b.md").await.unwrap(), b"# Note\n\nEdited");
+    }
+
+    #[tokio::test]
+    async fn test_file_deleted_removes_markdown() {
+        let vault1 = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(InMemoryFs::new(), test_peer_id_2()).await.unwrap();
+        vault1.fs.write("note.md", b"# Note").await.unwrap();
+        vault1.on_file_changed("note.md").await.unwrap();
+        let request = vault2.prepare_sync_request().await.unwrap();
+        let (exchange, _) = vault1.process_sync_message(&request).await.unwrap();
+        vault2.process_sync_message(&exchange.unwrap()).await.unwrap();
+        // A file only vault2 has isn't touched by vault1's deletion of that path
+        vault2.fs.write("untracked.md", b"# Mine").await.unwrap();
+
+        for path in ["note.md", "untracked.md"] {
+            let delete = vault1.prepare_file_deleted(path).unwrap();
+            vault2.process_sync_message(&delete).await.unwrap();
+        }
+
+        // Left behind, the markdown would come back as a new note
+        assert!(!vault2.fs.exists("note.md").await.unwrap());
+        assert!(!vault2.list_files().await.unwrap().contains(&"note.md".to_string()));
+        assert!(vault2.fs.exists("untracked.md").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_of_note_deleted_here_is_skipped() {
+        let vault1 = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(InMemoryFs::new(), test_peer_id_2()).await.unwrap();
+        vault1.fs.write("note.md", b"# Note").await.unwrap();
+        vault1.on_file_changed("note.md").await.unwrap();
+        let request = vault2.prepare_sync_request().await.unwrap();
+        let (exchange, _) = vault1.process_sync_message(&request).await.unwrap();
+        vault2.process_sync_message(&exchange.unwrap()).await.unwrap();
+
+        vault2.fs.delete("note.md").await.unwrap();
+        vault2.delete_file("note.md").await.unwrap();
+
+        // vault1 hasn't seen the deletion, and sends the whole note since
+        // vault2 no longer has it
+        let request = vault2.prepare_sync_request().await.unwrap();
+        let (exchange, _) = vault1.process_sync_message(&request).await.unwrap();
+        let (_, modified) = vault2.process_sync_message(&exchange.unwrap()).await.unwrap();
+
+        assert!(modified.is_empty());
+        assert!(!vault2.fs.exists("note.md").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_moves_converge() {
+        use std::sync::{Arc, Mutex};
+
+        let fs1 = Arc::new(InMemoryFs::new());
+        let fs2 = Arc::new(InMemoryFs::new());
+        fs1.write("X.md", b"# X").await.unwrap();
+        let vault1 = Vault::init(Arc::clone(&fs1), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(Arc::clone(&fs2), test_peer_id_2()).await.unwrap();
+        vault1.merge_with(&vault2).await.unwrap();
+        assert!(fs2.exists("X.md").await.unwrap());
+
+        // Each peer moves the note before hearing about the other's move
+        for (fs, vault, new_path) in [(&fs1, &vault1, "Y.md"), (&fs2, &vault2, "Z.md")] {
+            let content = fs.read("X.md").await.unwrap();
+            fs.write(new_path, &content).await.unwrap();
+            fs.delete("X.md").await.unwrap();
+            vault.rename_file("X.md", new_path).await.unwrap();
+        }
+
+        let conflicts = Arc::new(Mutex::new(Vec::new()));
+        let _subscriptions: Vec<_> = [&vault1, &vault2]
+            .into_iter()
+            .map(|vault| {
+                let conflicts = Arc::clone(&conflicts);
+                vault.subscribe(move |event| {
+                    if let SyncEvent::MoveConflict {
+                        local_path,
+                        resolved_path,
+                        ..
+                    } = event
+                    {
+                        conflicts.lock().unwrap().push((local_path, resolved_path));
+                    }
+                })
+            })
+            .collect();
+
+        vault1.merge_with(&vault2).await.unwrap();
+
+        // Both registries agree on one of the two moves...
+        let files = vault1.registered_files();
+        assert_eq!(files, vault2.registered_files());
+        assert_eq!(files.len(), 1);
+        let resolved = files[0].as_str();
+        assert!(resolved == "Y.md" || resolved == "Z.md", "{}", resolved);
+
+        // ...and both filesystems follow it
+        for fs in [&fs1, &fs2] {
+            assert_eq!(fs.read(resolved).await.unwrap(), b"# X");
+            for path in ["X.md", "Y.md", "Z.md"] {
+                if path != resolved {
+                    assert!(!fs.exists(path).await.unwrap(), "{} left behind", path);
+                }
+            }
+        }
+
+        // The peer whose move lost reports the conflict
+        let conflicts = conflicts.lock().unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_ne!(conflicts[0].0, resolved);
+        assert_eq!(conflicts[0].1, resolved);
+    }
+
+    /// Sync `note.md` between two vaults, then delete it in vault1 while vault2
+    /// (using `policy`) edits it, and deliver the deletion to vault2.
+    ///
+    /// Returns the filesystems, vaults and the conflict policies vault2 reported.
+    async fn concurrent_delete_and_edit(
+        policy: DeleteEditPolicy,
+    ) -> (
+        std::sync::Arc<InMemoryFs>,
+        std::sync::Arc<InMemoryFs>,
+        Vault<std::sync::Arc<InMemoryFs>>,
+        Vec<DeleteEditPolicy>,
+    ) {
+        use std::sync::{Arc, Mutex};
+
+        let fs1 = Arc::new(InMemoryFs::new());
+        let fs2 = Arc::new(InMemoryFs::new());
+        fs1.write("note.md", b"Original").await.unwrap();
+        let vault1 = Vault::init(Arc::clone(&fs1), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(Arc::clone(&fs2), test_peer_id_2()).await.unwrap();
+        vault2.set_delete_edit_policy(policy);
+        vault1.merge_with(&vault2).await.unwrap();
+
+        fs1.delete("note.md").await.unwrap();
+        vault1.delete_file("note.md").await.unwrap();
+        fs2.write("note.md", b"Original\nEdited").await.unwrap();
+        vault2.on_file_changed("note.md").await.unwrap();
+
+        let conflicts = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&conflicts);
+        let _subscription = vault2.subscribe(move |event| {
+            if let SyncEvent::DeleteEditConflict { policy, .. } = event {
+                seen.lock().unwrap().push(policy);
+            }
+        });
+        vault2.merge_with(&vault1).await.unwrap();
+
+        let conflicts = conflicts.lock().unwrap().clone();
+        (fs1, fs2, vault2, conflicts)
+    }
+
+    #[tokio::test]
+    async fn test_delete_edit_conflict_edit_wins() {
+        let (fs1, fs2, vault2, conflicts) =
+            concurrent_delete_and_edit(DeleteEditPolicy::EditWins).await;
+
+        assert_eq!(conflicts, vec![DeleteEditPolicy::EditWins]);
+        assert_eq!(vault2.registered_files(), vec!["note.md".to_string()]);
+        assert_eq!(fs2.read("note.md").await.unwrap(), b"Original\nEdited");
+        // The kept note is re-registered, so the deleting peer gets it back
+        assert_eq!(fs1.read("note.md").await.unwrap(), b"Original\nEdited");
+    }
+
+    #[tokio::test]
+    async fn test_delete_edit_conflict_delete_wins() {
+        let (fs1, fs2, vault2, conflicts) =
+            concurrent_delete_and_edit(DeleteEditPolicy::DeleteWins).await;
+
+        assert_eq!(conflicts, vec![DeleteEditPolicy::DeleteWins]);
+        assert!(vault2.registered_files().is_empty());
+        assert!(!fs2.exists("note.md").await.unwrap());
+        assert!(!fs1.exists("note.md").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_delete_after_seeing_edit_is_not_a_conflict() {
+        use std::sync::{Arc, Mutex};
+
+        let fs1 = Arc::new(InMemoryFs::new());
+        let fs2 = Arc::new(InMemoryFs::new());
+        fs1.write("note.md", b"Original").await.unwrap();
+        let vault1 = Vault::init(Arc::clone(&fs1), test_peer_id()).await.unwrap();
+        let vault2 = Vault::init(Arc::clone(&fs2), test_peer_id_2()).await.unwrap();
+        vault1.merge_with(&vault2).await.unwrap();
+
+        // vault1 sees vault2's edit before deleting the note
+        fs2.write("note.md", b"Original\nEdited").await.unwrap();
+        vault2.on_file_changed("note.md").await.unwrap();
+        vault1.merge_with(&vault2).await.unwrap();
+        fs1.delete("note.md").await.unwrap();
+        vault1.delete_file("note.md").await.unwrap();
+
+        let conflicts = Arc::new(Mutex::new(0));
+        let seen = Arc::clone(&conflicts);
+        let _subscription = vault2.subscribe(move |event| {
+            if matches!(event, SyncEvent::DeleteEditConflict { .. }) {
+                *seen.lock().unwrap() += 1;
+            }
+        });
+        vault2.merge_with(&vault1).await.unwrap();
+
+        assert_eq!(*conflicts.lock().unwrap(), 0);
+        assert!(!fs2.exists("note.md").await.unwrap());
+    }
 }
@@ -0,0 +1,306 @@
+//! In-process transport over channels, for deterministic protocol tests.
+//!
+//! A `ChannelNetwork` is a shared registry of transports. Each transport
+//! registers a listener under its peer ID; `connect` creates a pair of
+//! connected `ChannelConnection`s and hands the far end to the listener,
+//! where it is returned by `accept`.
+//!
+//! Messages are delivered in order with no delays or drops, so two `Vault`s
+//! can exchange real `SyncMessage`s without a WebSocket server.
+
+use super::{PeerConnection, PeerInfo, Result, SyncTransport, TransportError};
+use async_trait::async_trait;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::lock::Mutex;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Address prefix used in `PeerInfo::address` for channel peers.
+const ADDRESS_SCHEME: &str = "channel://";
+
+/// One end of an in-memory, bidirectional peer connection.
+pub struct ChannelConnection {
+    /// ID of the peer on the other end
+    peer_id: String,
+    tx: UnboundedSender<Vec<u8>>,
+    rx: Mutex<UnboundedReceiver<Vec<u8>>>,
+}
+
+impl ChannelConnection {
+    /// Create a connected pair: the first end talks to `b`, the second to `a`.
+    pub fn pair(a: &str, b: &str) -> (Self, Self) {
+        let (a_tx, b_rx) = unbounded();
+        let (b_tx, a_rx) = unbounded();
+
+        let a_end = Self {
+            peer_id: b.to_string(),
+            tx: a_tx,
+            rx: Mutex::new(a_rx),
+        };
+        let b_end = Self {
+            peer_id: a.to_string(),
+            tx: b_tx,
+            rx: Mutex::new(b_rx),
+        };
+
+        (a_end, b_end)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl PeerConnection for ChannelConnection {
+    fn peer_id(&self) -> &str {
+        &self.peer_id
+    }
+
+    async fn send(&self, data: &[u8]) -> Result<()> {
+        self.tx
+            .unbounded_send(data.to_vec())
+            .map_err(|_| TransportError::SendFailed(format!("connection to {} closed", self.peer_id)))
+    }
+
+    async fn recv(&self) -> Result<Vec<u8>> {
+        self.rx
+            .lock()
+            .await
+            .next()
+            .await
+            .ok_or_else(|| TransportError::ReceiveFailed(format!("connection to {} closed", self.peer_id)))
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.tx.close_channel();
+        self.rx.lock().await.close();
+        Ok(())
+    }
+}
+
+/// Shared registry connecting `ChannelTransport`s by peer ID.
+#[derive(Clone, Default)]
+pub struct ChannelNetwork {
+    listeners: Arc<RwLock<HashMap<String, UnboundedSender<ChannelConnection>>>>,
+}
+
+impl ChannelNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a transport for `peer_id` and register it on this network.
+    ///
+    /// Registering the same ID again replaces the previous transport's listener.
+    pub fn transport(&self, peer_id: &str) -> ChannelTransport {
+        let (tx, rx) = unbounded();
+        self.listeners
+            .write()
+            .unwrap()
+            .insert(peer_id.to_string(), tx);
+
+        ChannelTransport {
+            peer_id: peer_id.to_string(),
+            network: self.clone(),
+            incoming: Mutex::new(rx),
+        }
+    }
+
+    /// IDs of all registered transports.
+    fn peer_ids(&self) -> Vec<String> {
+        self.listeners.read().unwrap().keys().cloned().collect()
+    }
+
+    fn listener(&self, peer_id: &str) -> Option<UnboundedSender<ChannelConnection>> {
+        self.listeners.read().unwrap().get(peer_id).cloned()
+    }
+}
+
+/// `SyncTransport` backed by a `ChannelNetwork`.
+pub struct ChannelTransport {
+    peer_id: String,
+    network: ChannelNetwork,
+    incoming: Mutex<UnboundedReceiver<ChannelConnection>>,
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl SyncTransport for ChannelTransport {
+    fn peer_id(&self) -> &str {
+        &self.peer_id
+    }
+
+    async fn discover_peers(&self) -> Result<Vec<PeerInfo>> {
+        let mut peers: Vec<PeerInfo> = self
+            .network
+            .peer_ids()
+            .into_iter()
+            .filter(|id| *id != self.peer_id)
+            .map(|id| PeerInfo {
+                address: format!("{}{}", ADDRESS_SCHEME, id),
+                id,
+                name: None,
+            })
+            .collect();
+        peers.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(peers)
+    }
+
+    async fn connect(&self, peer: &PeerInfo) -> Result<Box<dyn PeerConnection>> {
+        let listener = self
+            .network
+            .listener(&peer.id)
+            .ok_or_else(|| TransportError::PeerNotFound(peer.id.clone()))?;
+
+        let (local, remote) = ChannelConnection::pair(&self.peer_id, &peer.id);
+        listener
+            .unbounded_send(remote)
+            .map_err(|_| TransportError::ConnectionFailed(format!("{} is not accepting", peer.id)))?;
+
+        Ok(Box::new(local))
+    }
+
+    async fn accept(&self) -> Result<Box<dyn PeerConnection>> {
+        let connection = self
+            .incoming
+            .lock()
+            .await
+            .next()
+            .await
+            .ok_or_else(|| TransportError::ConnectionFailed("transport closed".into()))?;
+
+        Ok(Box::new(connection))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::{FileSystem, InMemoryFs};
+    use crate::sync::SyncMessage;
+    use crate::{PeerId, Vault};
+
+    fn decode(bytes: &[u8]) -> SyncMessage {
+        bincode::deserialize(bytes).unwrap()
+    }
+
+    async fn connect_pair(
+        a: &ChannelTransport,
+        b: &ChannelTransport,
+    ) -> (Box<dyn PeerConnection>, Box<dyn PeerConnection>) {
+        let peer = a
+            .discover_peers()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|p| p.id == b.peer_id())
+            .unwrap();
+
+        let outgoing = a.connect(&peer).await.unwrap();
+        let incoming = b.accept().await.unwrap();
+        (outgoing, incoming)
+    }
+
+    #[tokio::test]
+    async fn test_discover_excludes_self() {
+        let network = ChannelNetwork::new();
+        let alice = network.transport("alice");
+        let _bob = network.transport("bob");
+        let _carol = network.transport("carol");
+
+        let peers = alice.discover_peers().await.unwrap();
+        let ids: Vec<&str> = peers.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["bob", "carol"]);
+        assert_eq!(peers[0].address, "channel://bob");
+    }
+
+    #[tokio::test]
+    async fn test_messages_delivered_in_order() {
+        let network = ChannelNetwork::new();
+        let alice = network.transport("alice");
+        let bob = network.transport("bob");
+
+        let (a, b) = connect_pair(&alice, &bob).await;
+        assert_eq!(a.peer_id(), "bob");
+        assert_eq!(b.peer_id(), "alice");
+
+        a.send(b"one").await.unwrap();
+        a.send(b"two").await.unwrap();
+        b.send(b"reply").await.unwrap();
+
+        assert_eq!(b.recv().await.unwrap(), b"one");
+        assert_eq!(b.recv().await.unwrap(), b"two");
+        assert_eq!(a.recv().await.unwrap(), b"reply");
+    }
+
+    #[tokio::test]
+    async fn test_connect_unknown_peer_fails() {
+        let network = ChannelNetwork::new();
+        let alice = network.transport("alice");
+        let ghost = PeerInfo {
+            id: "ghost".into(),
+            name: None,
+            address: "channel://ghost".into(),
+        };
+
+        let result = alice.connect(&ghost).await;
+        assert!(matches!(result, Err(TransportError::PeerNotFound(id)) if id == "ghost"));
+    }
+
+    #[tokio::test]
+    async fn test_close_ends_both_directions() {
+        let network = ChannelNetwork::new();
+        let alice = network.transport("alice");
+        let bob = network.transport("bob");
+
+        let (a, b) = connect_pair(&alice, &bob).await;
+        a.close().await.unwrap();
+
+        assert!(matches!(b.recv().await, Err(TransportError::ReceiveFailed(_))));
+        assert!(matches!(b.send(b"late").await, Err(TransportError::SendFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sync_round_trip_over_channel() {
+        let fs1 = InMemoryFs::new();
+        let fs2 = InMemoryFs::new();
+        fs1.write("file1.md", b"# From Vault 1").await.unwrap();
+        fs2.write("file2.md", b"# From Vault 2").await.unwrap();
+
+        let vault1 = Vault::init(fs1, PeerId::from(12345u64)).await.unwrap();
+        let vault2 = Vault::init(fs2, PeerId::from(67890u64)).await.unwrap();
+
+        let network = ChannelNetwork::new();
+        let t1 = network.transport("vault1");
+        let t2 = network.transport("vault2");
+        let (to_vault2, to_vault1) = connect_pair(&t1, &t2).await;
+
+        // 1. Vault 1 -> SyncRequest
+        let request = vault1.prepare_sync_request().await.unwrap();
+        to_vault2.send(&request).await.unwrap();
+
+        // 2. Vault 2 -> SyncExchange
+        let received = to_vault1.recv().await.unwrap();
+        assert!(matches!(decode(&received), SyncMessage::SyncRequest { .. }));
+        let (exchange, _) = vault2.process_sync_message(&received).await.unwrap();
+        to_vault1.send(&exchange.unwrap()).await.unwrap();
+
+        // 3. Vault 1 applies the exchange -> SyncResponse
+        let received = to_vault2.recv().await.unwrap();
+        assert!(matches!(decode(&received), SyncMessage::SyncExchange { .. }));
+        let (response, modified1) = vault1.process_sync_message(&received).await.unwrap();
+        assert!(modified1.contains(&"file2.md".to_string()));
+        to_vault2.send(&response.unwrap()).await.unwrap();
+
+        // 4. Vault 2 applies the response, nothing further to send
+        let received = to_vault1.recv().await.unwrap();
+        assert!(matches!(decode(&received), SyncMessage::SyncResponse { .. }));
+        let (none, modified2) = vault2.process_sync_message(&received).await.unwrap();
+        assert!(none.is_none());
+        assert!(modified2.contains(&"file1.md".to_string()));
+
+        let doc1 = vault2.get_document("file1.md").await.unwrap();
+        assert!(doc1.to_markdown().contains("From Vault 1"));
+        let doc2 = vault1.get_document("file2.md").await.unwrap();
+        assert!(doc2.to_markdown().contains("From Vault 2"));
+    }
+}
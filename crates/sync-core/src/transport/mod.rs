@@ -3,10 +3,15 @@
 //! Implementations:
 //! - LAN: mDNS discovery + WebSocket (desktop only)
 //! - WebRTC: Signaling server + WebRTC DataChannel (all platforms)
+//! - Channel: in-process channels for deterministic protocol tests
+
+pub mod channel;
 
 use async_trait::async_trait;
 use thiserror::Error;
 
+pub use channel::{ChannelConnection, ChannelNetwork, ChannelTransport};
+
 #[derive(Debug, Error)]
 pub enum TransportError {
     #[error("Connection failed: {0}")]
@@ -1,13 +1,21 @@
 //! Vault: Manages a collection of NoteDocuments and syncs with peers.
 
+use crate::clock::{Clock, SystemClock};
+use crate::conflicts::DeleteEditPolicy;
 use crate::document::NoteDocument;
 use crate::events::{EventBus, SyncEvent, Subscription};
-use crate::fs::{FileSystem, FsError};
+use crate::fs::{FileStat, FileSystem, FsError};
+use crate::peer_versions::PeerVersions;
 use crate::peers::{ConnectedPeer, ConnectionDirection, DisconnectReason, PeerError, PeerRegistry};
+use crate::registry_log::{
+    decode_entries, read_registry_log, replay_registry_log, RegistryLog, REGISTRY_LOG_FILE,
+};
+use crate::scope::SyncScope;
 use crate::PeerId;
 
 use loro::{LoroDoc, LoroTree, TreeID, TreeParentId, VersionVector};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use thiserror::Error;
@@ -68,8 +76,28 @@ pub struct DocumentInfo {
 
 /// Directory for sync state
 pub(crate) const SYNC_DIR: &str = ".sync";
-/// File registry document
-const REGISTRY_FILE: &str = ".sync/registry.loro";
+/// File registry snapshot (later updates are in the registry log)
+pub(crate) const REGISTRY_FILE: &str = ".sync/registry.loro";
+
+/// Copy of an unreadable registry, kept by `rebuild_registry`
+const REGISTRY_BACKUP_FILE: &str = ".sync/registry.loro.corrupt";
+/// Copy of the registry log at the time of a rebuild, kept by `rebuild_registry`
+const REGISTRY_LOG_BACKUP_FILE: &str = ".sync/registry.log.corrupt";
+
+/// Sync scope set with `set_sync_scope`, as JSON
+const SCOPE_FILE: &str = ".sync/scope.json";
+
+/// Tree metadata key marking a file as pinned
+const PINNED_KEY: &str = "pinned";
+
+/// Stat of each markdown file as of the last reconcile
+const STAT_CACHE_FILE: &str = ".sync/stat-cache.json";
+
+/// Earlier paths of a file recorded in its tree metadata, oldest first
+const MOVED_FROM_PATHS_KEY: &str = "moved_from_paths";
+
+/// Earlier paths kept per file, so a long chain of renames can't grow the registry unbounded
+const MAX_MOVED_FROM_PATHS: usize = 8;
 
 #[derive(Debug, Error)]
 pub enum VaultError {
@@ -82,10 +110,66 @@ pub enum VaultError {
     #[error("Vault not initialized")]
     NotInitialized,
 
+    #[error("Invalid path: {0}")]
+    PathValidation(#[from] PathValidationError),
+
+    #[error("Tree operation failed: {0}")]
+    TreeOperation(String),
+
+    #[error("Target already exists: {0}")]
+    TargetExists(String),
+
+    #[error("Source file not found: {0}")]
+    SourceNotFound(String),
+
+    #[error("Registry corrupted: {0}")]
+    RegistryCorrupted(String),
+
+    #[error("Note too large: {path} body is {size} bytes (limit {limit})")]
+    NoteTooLarge {
+        path: String,
+        size: usize,
+        limit: usize,
+    },
+
     #[error("Vault error: {0}")]
     Other(String),
 }
 
+/// Reason a path was rejected by `validate_sync_path`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PathValidationError {
+    #[error("Empty path not allowed")]
+    Empty,
+
+    #[error("Path traversal not allowed")]
+    Traversal,
+
+    #[error("Empty path segment not allowed")]
+    EmptySegment,
+
+    #[error("Absolute path not allowed")]
+    Absolute,
+
+    #[error("Windows absolute path not allowed")]
+    WindowsAbsolute,
+
+    #[error("Backslash in path not allowed")]
+    Backslash,
+
+    #[error("Null byte in path not allowed")]
+    NullByte,
+
+    #[error("Only markdown files allowed")]
+    NotMarkdown,
+
+    #[error("Control character in path not allowed")]
+    ControlCharacter,
+
+    #[error("Path too long")]
+    TooLong,
+}
+
 pub type Result<T> = std::result::Result<T, VaultError>;
 
 /// A detected file move
@@ -108,20 +192,71 @@ pub struct ReconcileReport {
     pub moved: Vec<FileMove>,
     /// Orphaned .loro hashes (file was deleted, not moved)
     pub orphaned: Vec<String>,
+    /// Pinned files whose markdown was rewritten from the Loro doc
+    pub restored: Vec<String>,
+    /// Files not compared because their stat matched the last reconcile
+    pub skipped: Vec<String>,
+    /// Files left unsynced because their body is over the size limit
+    pub too_large: Vec<String>,
 }
 
 impl ReconcileReport {
     /// Check if any changes were made
     pub fn has_changes(&self) -> bool {
-        !self.indexed.is_empty() || !self.reindexed.is_empty() || !self.moved.is_empty()
+        !self.indexed.is_empty()
+            || !self.reindexed.is_empty()
+            || !self.moved.is_empty()
+            || !self.restored.is_empty()
     }
 
     /// Total number of files processed
     pub fn total_processed(&self) -> usize {
-        self.indexed.len() + self.reindexed.len() + self.moved.len()
+        self.indexed.len() + self.reindexed.len() + self.moved.len() + self.restored.len()
+    }
+}
+
+/// File stat recorded by `reconcile`, used to skip unchanged files next time.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+struct CachedStat {
+    mtime_millis: u64,
+    size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_hash: Option<u64>,
+}
+
+impl CachedStat {
+    fn from_stat(stat: &FileStat) -> Self {
+        Self {
+            mtime_millis: stat.mtime_millis,
+            size: stat.size,
+            content_hash: stat.content_hash,
+        }
+    }
+
+    /// Whether `stat` describes the same content. Hashes are only compared
+    /// when both sides have one; otherwise mtime and size decide.
+    fn matches(&self, stat: &FileStat) -> bool {
+        if self.mtime_millis != stat.mtime_millis || self.size != stat.size {
+            return false;
+        }
+        match (self.content_hash, stat.content_hash) {
+            (Some(cached), Some(current)) => cached == current,
+            _ => true,
+        }
     }
 }
 
+/// Report from importing a directory of markdown files
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Files with no existing document (new CRDT created)
+    pub created: Vec<String>,
+    /// Files whose content differed and were diff-merged into the existing document
+    pub merged: Vec<String>,
+    /// Files whose content already matched the existing document
+    pub unchanged: Vec<String>,
+}
+
 /// Tracks sync state for echo detection and consistency reconciliation.
 ///
 /// When a file is received from sync, we mark it here BEFORE writing to disk.
@@ -137,11 +272,43 @@ pub struct SyncState {
     pending_reconcile: Arc<Mutex<HashSet<String>>>,
     /// Registry may need reconciliation before next sync import
     registry_pending: Arc<Mutex<bool>>,
+    /// Map of path -> document version last written by sync (for exact echo detection)
+    synced_versions: Arc<Mutex<HashMap<String, VersionVector>>>,
+    /// Flag lifecycle counters for tuning the TTL
+    stats: Arc<Mutex<SyncFlagStats>>,
+    /// How long a sync flag stays valid
+    ttl: Duration,
+    /// Time source for flag timestamps
+    clock: Arc<dyn Clock>,
+}
+
+/// Counters for sync flag lifecycles.
+///
+/// A high `consumed_expired` means file watchers fire after the flag TTL and echoes
+/// get re-broadcast; a high `expired_unconsumed` means flags are set for writes
+/// the watcher never reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncFlagStats {
+    /// Flags set by `mark_synced`
+    pub flags_set: u64,
+    /// Flags consumed within the TTL (echo suppressed)
+    pub consumed_valid: u64,
+    /// Flags consumed after the TTL (echo not suppressed)
+    pub consumed_expired: u64,
+    /// Flags that expired without being consumed (dropped by cleanup or overwritten)
+    pub expired_unconsumed: u64,
 }
 
-/// Time-to-live for sync flags. Flags older than this are considered stale.
+/// Default time-to-live for sync flags. Flags older than this are considered stale.
 /// Set to 30s to provide safety margin for echo detection even with delayed file watchers.
-const FLAG_TTL: Duration = Duration::from_secs(30);
+pub const DEFAULT_FLAG_TTL: Duration = Duration::from_secs(30);
+
+/// Default limit on a note's body size, in bytes.
+///
+/// Large enough for any hand-written note; bigger files are usually pasted
+/// logs or generated data that would bloat every sync message.
+pub const DEFAULT_MAX_NOTE_SIZE: usize = 10 * 1024 * 1024;
 
 impl Default for SyncState {
     fn default() -> Self {
@@ -149,6 +316,10 @@ impl Default for SyncState {
             synced_paths: Arc::new(Mutex::new(HashMap::new())),
             pending_reconcile: Arc::new(Mutex::new(HashSet::new())),
             registry_pending: Arc::new(Mutex::new(false)),
+            synced_versions: Arc::new(Mutex::new(HashMap::new())),
+            stats: Arc::new(Mutex::new(SyncFlagStats::default())),
+            ttl: DEFAULT_FLAG_TTL,
+            clock: Arc::new(SystemClock),
         }
     }
 }
@@ -158,14 +329,48 @@ impl SyncState {
         Self::default()
     }
 
+    /// Create a tracker whose flags stay valid for `ttl` instead of [`DEFAULT_FLAG_TTL`].
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            ..Self::default()
+        }
+    }
+
+    /// Create a tracker that reads time from `clock` (e.g. a [`TestClock`](crate::clock::TestClock)).
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            ..Self::default()
+        }
+    }
+
+    /// How long a sync flag stays valid.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// How long ago `timestamp` was, by this tracker's clock.
+    fn age(&self, timestamp: Instant) -> Duration {
+        self.clock.now().saturating_duration_since(timestamp)
+    }
+
     /// Mark a path as having been synced (call before writing to disk).
     /// Adds to both synced_paths (for echo detection) and pending_reconcile
     /// (to ensure consistency before next sync import).
     pub fn mark_synced(&self, path: &str) {
-        self.synced_paths
+        let previous = self
+            .synced_paths
             .lock()
             .unwrap()
-            .insert(path.to_string(), Instant::now());
+            .insert(path.to_string(), self.clock.now());
+        {
+            let mut stats = self.stats.lock().unwrap();
+            stats.flags_set += 1;
+            if previous.is_some_and(|timestamp| self.age(timestamp) >= self.ttl) {
+                stats.expired_unconsumed += 1;
+            }
+        }
         self.pending_reconcile
             .lock()
             .unwrap()
@@ -174,20 +379,22 @@ impl SyncState {
 
     /// Check if path was synced and consume the flag (returns true once).
     /// Also removes from pending_reconcile set.
-    /// Returns false if the flag has expired (older than FLAG_TTL).
+    /// Returns false if the flag has expired (older than the TTL).
     pub fn consume_synced(&self, path: &str) -> bool {
         self.pending_reconcile.lock().unwrap().remove(path);
         let mut paths = self.synced_paths.lock().unwrap();
         if let Some(timestamp) = paths.remove(path) {
             // Check if flag is still valid (not expired)
-            if timestamp.elapsed() < FLAG_TTL {
+            if self.age(timestamp) < self.ttl {
+                self.stats.lock().unwrap().consumed_valid += 1;
                 return true;
             }
+            self.stats.lock().unwrap().consumed_expired += 1;
             // Flag expired - log for diagnostics
             tracing::debug!(
                 "Sync flag expired for {} (age={}ms)",
                 path,
-                timestamp.elapsed().as_millis()
+                self.age(timestamp).as_millis()
             );
         }
         false
@@ -199,7 +406,7 @@ impl SyncState {
     pub fn is_synced(&self, path: &str) -> bool {
         let paths = self.synced_paths.lock().unwrap();
         if let Some(timestamp) = paths.get(path) {
-            return timestamp.elapsed() < FLAG_TTL;
+            return self.age(*timestamp) < self.ttl;
         }
         false
     }
@@ -209,7 +416,27 @@ impl SyncState {
     #[allow(dead_code)]
     pub fn cleanup_expired(&self) {
         let mut paths = self.synced_paths.lock().unwrap();
-        paths.retain(|_, timestamp| timestamp.elapsed() < FLAG_TTL);
+        let before = paths.len();
+        paths.retain(|_, timestamp| self.age(*timestamp) < self.ttl);
+        self.stats.lock().unwrap().expired_unconsumed += (before - paths.len()) as u64;
+    }
+
+    /// Remember the document version sync wrote for a path.
+    pub fn record_synced_version(&self, path: &str, version: VersionVector) {
+        self.synced_versions
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), version);
+    }
+
+    /// The document version sync last wrote for a path, if any.
+    pub fn synced_version(&self, path: &str) -> Option<VersionVector> {
+        self.synced_versions.lock().unwrap().get(path).cloned()
+    }
+
+    /// Snapshot of the flag lifecycle counters.
+    pub fn stats(&self) -> SyncFlagStats {
+        *self.stats.lock().unwrap()
     }
 
     /// Take all paths pending reconciliation (called before sync import).
@@ -266,6 +493,24 @@ pub struct Vault<F: FileSystem> {
     /// Tracks sync state for echo detection and consistency reconciliation
     sync_state: SyncState,
 
+    /// Rewrite wiki-links in other notes when a peer renames a note (opt-in)
+    rewrite_links_on_rename: AtomicBool,
+
+    /// Which paths participate in sync (the whole vault by default)
+    scope: Mutex<SyncScope>,
+
+    /// How deletions of notes with unseen edits are applied
+    delete_edit_policy: Mutex<DeleteEditPolicy>,
+
+    /// Largest note body tracked, in bytes
+    max_note_size: AtomicUsize,
+
+    /// Registry state persisted so far, for incremental saves
+    pub(crate) registry_log: Mutex<RegistryLog>,
+
+    /// Versions each peer last reported in a sync request
+    pub(crate) peer_versions: Mutex<HashMap<PeerId, PeerVersions>>,
+
     /// Event bus for sync events (native: Arc for multi-threaded Tokio)
     #[cfg(not(target_arch = "wasm32"))]
     events: Arc<EventBus>,
@@ -368,50 +613,16 @@ impl<F: FileSystem> Vault<F> {
         fs.mkdir(SYNC_DIR).await?;
         fs.mkdir(&format!("{}/documents", SYNC_DIR)).await?;
 
-        let registry = LoroDoc::new();
-        // Set peer ID before any operations for consistent version vectors
-        registry.set_peer_id(peer_id.as_u64()).ok();
-        // Initialize the file tree (LoroTree inside registry)
-        // The tree is created on first access via get_tree()
-        let _file_tree = registry.get_tree("files");
+        let registry = Self::new_registry(peer_id);
 
         // Save initial registry
         let registry_bytes = registry.export(loro::ExportMode::Snapshot).unwrap();
         fs.write(REGISTRY_FILE, &registry_bytes).await?;
+        if fs.exists(REGISTRY_LOG_FILE).await? {
+            fs.delete(REGISTRY_LOG_FILE).await?;
+        }
 
-        #[cfg(not(target_arch = "wasm32"))]
-        let events = Arc::new(EventBus::new());
-        #[cfg(target_arch = "wasm32")]
-        let events = Rc::new(EventBus::new());
-
-        #[cfg(not(target_arch = "wasm32"))]
-        let peers = Arc::new(PeerRegistry::new());
-        #[cfg(target_arch = "wasm32")]
-        let peers = Rc::new(PeerRegistry::new());
-
-        // Wrap fields in interior mutability containers
-        #[cfg(target_arch = "wasm32")]
-        let vault = Self {
-            registry: RefCell::new(registry),
-            path_to_node: RefCell::new(HashMap::new()),
-            documents: RefCell::new(HashMap::new()),
-            fs,
-            peer_id,
-            sync_state: SyncState::new(),
-            events,
-            peers,
-        };
-        #[cfg(not(target_arch = "wasm32"))]
-        let vault = Self {
-            registry: Mutex::new(registry),
-            path_to_node: Mutex::new(HashMap::new()),
-            documents: Mutex::new(HashMap::new()),
-            fs,
-            peer_id,
-            sync_state: SyncState::new(),
-            events,
-            peers,
-        };
+        let vault = Self::with_registry(fs, peer_id, registry);
 
         // Scan and index all existing markdown files
         vault.index_existing_files().await?;
@@ -431,64 +642,157 @@ impl<F: FileSystem> Vault<F> {
             return Err(VaultError::NotInitialized);
         }
 
-        // Load registry
+        // Load registry snapshot, then replay updates logged since
         let registry = if fs.exists(REGISTRY_FILE).await? {
             let bytes = fs.read(REGISTRY_FILE).await?;
-            let doc = LoroDoc::new();
-            // Set peer ID before import so any new operations use our ID
-            doc.set_peer_id(peer_id.as_u64()).ok();
-            doc.import(&bytes).ok();
-            doc
+            Self::import_registry(&bytes, peer_id)?
         } else {
-            let doc = LoroDoc::new();
-            // Set peer ID before any operations for consistent version vectors
-            doc.set_peer_id(peer_id.as_u64()).ok();
-            // Initialize file tree for new registries
-            let _file_tree = doc.get_tree("files");
-            doc
+            Self::new_registry(peer_id)
         };
+        let log = read_registry_log(&fs).await?;
+        let log_updates = replay_registry_log(&registry, &log)?;
 
-        #[cfg(not(target_arch = "wasm32"))]
-        let events = Arc::new(EventBus::new());
-        #[cfg(target_arch = "wasm32")]
-        let events = Rc::new(EventBus::new());
+        let vault = Self::with_registry(fs, peer_id, registry);
+        vault.set_registry_log_size(log_updates, log.len());
+        vault.load_sync_scope().await?;
+        vault.load_registry_acks().await?;
 
-        #[cfg(not(target_arch = "wasm32"))]
-        let peers = Arc::new(PeerRegistry::new());
-        #[cfg(target_arch = "wasm32")]
-        let peers = Rc::new(PeerRegistry::new());
+        // Build path cache from loaded tree
+        vault.rebuild_path_cache();
+
+        // Reconcile filesystem with Loro state
+        vault.reconcile().await?;
+
+        Ok(vault)
+    }
+
+    /// Rebuild the registry of a vault whose `registry.loro` can't be loaded.
+    ///
+    /// The unreadable registry and its log are kept as `registry.loro.corrupt`
+    /// and `registry.log.corrupt`. Whatever still imports from them is kept, so
+    /// the file nodes it holds keep their IDs and peers don't see them as new;
+    /// only markdown files on disk that it lacks get fresh nodes. The result is
+    /// reconciled against the existing `.loro` documents (moves are detected,
+    /// orphans reported). Note content history is untouched.
+    pub async fn rebuild_registry(fs: F, peer_id: PeerId) -> Result<Self> {
+        if !fs.exists(SYNC_DIR).await? {
+            return Err(VaultError::NotInitialized);
+        }
+
+        if fs.exists(REGISTRY_FILE).await? {
+            let bytes = fs.read(REGISTRY_FILE).await?;
+            fs.write(REGISTRY_BACKUP_FILE, &bytes).await?;
+        }
+        let log = read_registry_log(&fs).await?;
+        if !log.is_empty() {
+            fs.write(REGISTRY_LOG_BACKUP_FILE, &log).await?;
+        }
+
+        let registry = Self::salvage_registry(&fs, peer_id, &log).await?;
+        let vault = Self::with_registry(fs, peer_id, registry);
+        vault.load_sync_scope().await?;
+        vault.load_registry_acks().await?;
+        vault.rebuild_path_cache();
+
+        for path in vault.list_files().await? {
+            if let Err(e) = vault.register_file(&path) {
+                tracing::warn!("Skipping {} during registry rebuild: {}", path, e);
+            }
+        }
+        vault.reconcile().await?;
+        vault.write_registry_snapshot().await?;
+
+        tracing::info!(
+            "Rebuilt registry with {} files",
+            vault.path_to_node().len()
+        );
+        Ok(vault)
+    }
+
+    /// Import as much of a corrupted registry as possible: the snapshot, if it
+    /// still reads, then each log entry that applies on top of it.
+    async fn salvage_registry(fs: &F, peer_id: PeerId, log: &[u8]) -> Result<LoroDoc> {
+        let registry = Self::new_registry(peer_id);
+        if fs.exists(REGISTRY_FILE).await? {
+            let bytes = fs.read(REGISTRY_FILE).await?;
+            if let Err(e) = registry.import(&bytes) {
+                tracing::warn!("Registry snapshot is unreadable, rebuilding without it: {}", e);
+            }
+        }
+        for entry in decode_entries(log) {
+            if let Err(e) = registry.import(entry) {
+                tracing::warn!("Skipping unreadable registry log entry: {}", e);
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Create an empty registry doc owned by `peer_id`.
+    fn new_registry(peer_id: PeerId) -> LoroDoc {
+        let registry = LoroDoc::new();
+        // Set peer ID before any operations for consistent version vectors
+        registry.set_peer_id(peer_id.as_u64()).ok();
+        // Initialize the file tree (LoroTree inside registry)
+        // The tree is created on first access via get_tree()
+        let _file_tree = registry.get_tree("files");
+        registry
+    }
+
+    /// Import a saved registry, failing if the bytes are unreadable or incomplete.
+    pub(crate) fn import_registry(bytes: &[u8], peer_id: PeerId) -> Result<LoroDoc> {
+        let doc = LoroDoc::new();
+        // Set peer ID before import so any new operations use our ID
+        doc.set_peer_id(peer_id.as_u64()).ok();
+        let status = doc
+            .import(bytes)
+            .map_err(|e| VaultError::RegistryCorrupted(e.to_string()))?;
+        if let Some(pending) = status.pending {
+            return Err(VaultError::RegistryCorrupted(format!(
+                "missing dependencies for {:?}",
+                pending
+            )));
+        }
+        Ok(doc)
+    }
 
+    /// Wrap a registry and filesystem into a vault with empty caches.
+    fn with_registry(fs: F, peer_id: PeerId, registry: LoroDoc) -> Self {
         // Wrap fields in interior mutability containers
+        let registry_log = Mutex::new(RegistryLog::new(registry.oplog_vv()));
+
         #[cfg(target_arch = "wasm32")]
-        let vault = Self {
-            registry: RefCell::new(registry),
-            path_to_node: RefCell::new(HashMap::new()),
-            documents: RefCell::new(HashMap::new()),
-            fs,
-            peer_id,
-            sync_state: SyncState::new(),
-            events,
-            peers,
-        };
+        let (registry, path_to_node, documents, events, peers) = (
+            RefCell::new(registry),
+            RefCell::new(HashMap::new()),
+            RefCell::new(HashMap::new()),
+            Rc::new(EventBus::new()),
+            Rc::new(PeerRegistry::new()),
+        );
         #[cfg(not(target_arch = "wasm32"))]
-        let vault = Self {
-            registry: Mutex::new(registry),
-            path_to_node: Mutex::new(HashMap::new()),
-            documents: Mutex::new(HashMap::new()),
+        let (registry, path_to_node, documents, events, peers) = (
+            Mutex::new(registry),
+            Mutex::new(HashMap::new()),
+            Mutex::new(HashMap::new()),
+            Arc::new(EventBus::new()),
+            Arc::new(PeerRegistry::new()),
+        );
+
+        Self {
+            registry,
+            path_to_node,
+            documents,
             fs,
             peer_id,
             sync_state: SyncState::new(),
+            rewrite_links_on_rename: AtomicBool::new(false),
+            scope: Mutex::new(SyncScope::all()),
+            delete_edit_policy: Mutex::new(DeleteEditPolicy::default()),
+            max_note_size: AtomicUsize::new(DEFAULT_MAX_NOTE_SIZE),
+            registry_log,
+            peer_versions: Mutex::new(HashMap::new()),
             events,
             peers,
-        };
-
-        // Build path cache from loaded tree
-        vault.rebuild_path_cache();
-
-        // Reconcile filesystem with Loro state
-        vault.reconcile().await?;
-
-        Ok(vault)
+        }
     }
 
     /// Reconcile filesystem state with Loro documents.
@@ -499,9 +803,13 @@ impl<F: FileSystem> Vault<F> {
     /// - External file moves → migrate Loro doc to new path hash
     /// - External file deletions → orphaned .loro files (logged, not deleted)
     /// 
-    /// The filesystem (markdown) is always the source of truth.
+    /// The filesystem (markdown) is the source of truth, except for pinned
+    /// notes: their external modifications are reverted by re-writing the
+    /// markdown from the Loro doc (see `set_pinned`).
     pub async fn reconcile(&self) -> Result<ReconcileReport> {
         let mut report = ReconcileReport::default();
+        let stat_cache = self.load_stat_cache().await;
+        let mut fresh_stats: HashMap<String, CachedStat> = HashMap::new();
         
         // Get all markdown files in the vault
         let md_files: std::collections::HashSet<String> = 
@@ -590,19 +898,44 @@ impl<F: FileSystem> Vault<F> {
             let sync_path = format!("{}/documents/{}.loro", SYNC_DIR, hash);
             
             if loro_hashes.contains(&hash) {
+                // Same mtime and size as last time - skip the full comparison
+                if let (Some(cached), Ok(stat)) = (stat_cache.get(path), self.fs.stat(path).await)
+                    && cached.matches(&stat)
+                {
+                    fresh_stats.insert(path.clone(), *cached);
+                    report.skipped.push(path.clone());
+                    continue;
+                }
+
                 // Both exist - check if markdown was modified externally
                 if self.needs_reindex(path, &sync_path).await? {
+                    if self.is_pinned(path) {
+                        tracing::info!("Pinned file modified externally, restoring: {}", path);
+                        self.restore_markdown(path).await?;
+                        report.restored.push(path.clone());
+                        continue;
+                    }
                     tracing::info!("File modified externally, re-indexing: {}", path);
-                    self.reindex_file(path).await?;
-                    report.reindexed.push(path.clone());
+                    match self.reindex_file(path).await {
+                        Err(VaultError::NoteTooLarge { .. }) => report.too_large.push(path.clone()),
+                        result => {
+                            result?;
+                            report.reindexed.push(path.clone());
+                        }
+                    }
                 }
             } else {
                 // Truly new file (not a move target)
                 tracing::info!("New file detected, indexing: {}", path);
-                self.on_file_changed(path).await?;
-                // Register in tree for delete/rename tracking
-                self.register_file(path)?;
-                report.indexed.push(path.clone());
+                match self.on_file_changed(path).await {
+                    Err(VaultError::NoteTooLarge { .. }) => report.too_large.push(path.clone()),
+                    result => {
+                        result?;
+                        // Register in tree for delete/rename tracking
+                        self.register_file(path)?;
+                        report.indexed.push(path.clone());
+                    }
+                }
             }
         }
         
@@ -615,9 +948,38 @@ impl<F: FileSystem> Vault<F> {
                 report.orphaned.push(old_path);
             }
         }
+
+        // Record stats of everything now known to match its Loro doc. Oversized
+        // files don't match, so they're checked again next time.
+        for path in &md_files {
+            if fresh_stats.contains_key(path) || report.too_large.contains(path) {
+                continue;
+            }
+            if let Ok(stat) = self.fs.stat(path).await {
+                fresh_stats.insert(path.clone(), CachedStat::from_stat(&stat));
+            }
+        }
+        if fresh_stats != stat_cache {
+            self.save_stat_cache(&fresh_stats).await?;
+        }
         
         Ok(report)
     }
+
+    /// Load the stat cache written by the last reconcile (empty if missing or unreadable).
+    async fn load_stat_cache(&self) -> HashMap<String, CachedStat> {
+        match self.fs.read(STAT_CACHE_FILE).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    async fn save_stat_cache(&self, stats: &HashMap<String, CachedStat>) -> Result<()> {
+        let bytes = serde_json::to_vec(stats)
+            .map_err(|e| VaultError::Other(format!("Failed to encode stat cache: {}", e)))?;
+        self.fs.write(STAT_CACHE_FILE, &bytes).await?;
+        Ok(())
+    }
     
     /// Migrate a Loro document from old path hash to new path.
     ///
@@ -625,16 +987,14 @@ impl<F: FileSystem> Vault<F> {
     /// Uses `from_bytes` to import before setting metadata, preserving the original peer ID.
     async fn migrate_document(&self, old_hash: &str, new_path: &str) -> Result<()> {
         let old_sync_path = format!("{}/documents/{}.loro", SYNC_DIR, old_hash);
-        let new_hash = simple_hash(new_path);
-        let new_sync_path = format!("{}/documents/{}.loro", SYNC_DIR, new_hash);
 
         // Load the old document (import first, then update path - preserves peer ID)
         let bytes = self.fs.read(&old_sync_path).await?;
-        let doc = NoteDocument::from_bytes(new_path, &bytes, self.peer_id)?;
+        let mut doc = NoteDocument::from_bytes(new_path, &bytes, self.peer_id)?;
+        doc.update_path(new_path)?;
 
         // Save to new location
-        let snapshot = doc.export_snapshot();
-        self.fs.write(&new_sync_path, &snapshot).await?;
+        self.write_document_state(new_path, &doc).await?;
 
         // Delete old file
         self.fs.delete(&old_sync_path).await?;
@@ -694,24 +1054,26 @@ impl<F: FileSystem> Vault<F> {
     ///
     /// This is used when external modifications are detected during reconciliation.
     /// Preserves the peer ID by updating the existing document rather than replacing it.
+    /// Fails with `NoteTooLarge`, leaving the doc as it was, if the new body is
+    /// over the size limit.
     async fn reindex_file(&self, path: &str) -> Result<()> {
         let bytes = self.fs.read(path).await?;
         let content = String::from_utf8_lossy(&bytes);
         let parsed = crate::markdown::parse(&content);
+        self.check_note_size(path, &parsed.body)?;
 
         // Load existing .loro document
         let sync_path = self.document_sync_path(path);
         let loro_bytes = self.fs.read(&sync_path).await?;
         let doc = NoteDocument::from_bytes(path, &loro_bytes, self.peer_id)?;
+        self.protect_deleted_edits(&doc);
 
         // Diff-merge the changes (preserves peer ID)
         let body_changed = doc.update_body(&parsed.body)?;
         let fm_changed = doc.update_frontmatter(parsed.frontmatter.as_ref())?;
 
         if body_changed || fm_changed {
-            doc.commit();
-            let snapshot = doc.export_snapshot();
-            self.fs.write(&sync_path, &snapshot).await?;
+            self.write_document_state(path, &doc).await?;
             tracing::debug!("Re-indexed document via diff: {}", path);
         }
 
@@ -721,6 +1083,25 @@ impl<F: FileSystem> Vault<F> {
         Ok(())
     }
 
+    /// Re-write a file's markdown from its existing Loro doc.
+    ///
+    /// Used for pinned files, where the Loro doc wins over external edits.
+    /// Marks the path as synced first so the write isn't treated as a local change.
+    async fn restore_markdown(&self, path: &str) -> Result<()> {
+        let sync_path = self.document_sync_path(path);
+        let loro_bytes = self.fs.read(&sync_path).await?;
+        let doc = NoteDocument::from_bytes(path, &loro_bytes, self.peer_id)?;
+
+        self.mark_synced(path);
+        self.fs.write(path, doc.to_markdown().as_bytes()).await?;
+        tracing::debug!("Restored markdown from Loro doc: {}", path);
+
+        // Update cache
+        self.documents_mut().insert(path.to_string(), doc);
+
+        Ok(())
+    }
+
     // ========== Sync Consistency Methods ==========
 
     /// Ensure consistency of all pending paths before processing sync messages.
@@ -740,6 +1121,8 @@ impl<F: FileSystem> Vault<F> {
             match self.reconcile_single(&path).await {
                 Ok(true) => tracing::debug!("Reconciled stale doc before sync: {}", path),
                 Ok(false) => {} // Already consistent
+                // Oversized notes stay unsynced (already reported) rather than failing the sync
+                Err(VaultError::NoteTooLarge { .. }) => {}
                 Err(e) => {
                     // Check if file was deleted - skip gracefully
                     if matches!(e, VaultError::Fs(ref fs_err) if matches!(fs_err, crate::fs::FsError::NotFound(_))) {
@@ -771,6 +1154,7 @@ impl<F: FileSystem> Vault<F> {
             Ok(d) => d,
             Err(_) => return Ok(false), // Corrupted loro doc, let sync recreate it
         };
+        self.protect_deleted_edits(&doc);
 
         let loro_content = doc.to_markdown();
 
@@ -782,15 +1166,20 @@ impl<F: FileSystem> Vault<F> {
             return Ok(false); // Already consistent
         }
 
+        // Pinned files keep the Loro state; overwrite the external edit instead
+        if self.is_pinned(path) {
+            self.restore_markdown(path).await?;
+            return Ok(true);
+        }
+
         // Update loro to match filesystem
         let parsed = crate::markdown::parse(&md_content);
+        self.check_note_size(path, &parsed.body)?;
         let body_changed = doc.update_body(&parsed.body)?;
         let fm_changed = doc.update_frontmatter(parsed.frontmatter.as_ref())?;
 
         if body_changed || fm_changed {
-            doc.commit();
-            let snapshot = doc.export_snapshot();
-            self.fs.write(&sync_path, &snapshot).await?;
+            self.write_document_state(path, &doc).await?;
         }
 
         self.documents_mut().insert(path.to_string(), doc);
@@ -801,11 +1190,15 @@ impl<F: FileSystem> Vault<F> {
     ///
     /// Ensures the in-memory registry matches the persisted state before sync import.
     async fn reconcile_registry(&self) -> Result<()> {
-        let registry_path = format!("{}/registry.loro", SYNC_DIR);
-        if let Ok(data) = self.fs.read(&registry_path).await {
-            self.registry_mut()
-                .import(&data)
-                .map_err(|e| VaultError::Other(format!("Registry reconcile failed: {}", e)))?;
+        if let Ok(data) = self.fs.read(REGISTRY_FILE).await {
+            let log = read_registry_log(&self.fs).await?;
+            {
+                let registry = self.registry_mut();
+                registry
+                    .import(&data)
+                    .map_err(|e| VaultError::Other(format!("Registry reconcile failed: {}", e)))?;
+                replay_registry_log(&registry, &log)?;
+            }
             self.rebuild_path_cache();
             tracing::debug!("Reconciled registry before sync");
         }
@@ -817,6 +1210,11 @@ impl<F: FileSystem> Vault<F> {
         self.peer_id
     }
 
+    /// The filesystem the vault reads and writes notes through.
+    pub fn fs(&self) -> &F {
+        &self.fs
+    }
+
     /// Subscribe to sync events. Returns `Subscription` that unsubscribes on drop.
     ///
     /// The callback receives `SyncEvent` objects for real-time monitoring.
@@ -840,10 +1238,7 @@ impl<F: FileSystem> Vault<F> {
 
     /// Get current timestamp in milliseconds.
     pub(crate) fn now_ms(&self) -> f64 {
-        web_time::SystemTime::now()
-            .duration_since(web_time::SystemTime::UNIX_EPOCH)
-            .map(|d| d.as_secs_f64() * 1000.0)
-            .unwrap_or(0.0)
+        self.sync_state.clock.now_ms()
     }
 
     /// Mark a path as synced (call before writing to disk).
@@ -864,6 +1259,57 @@ impl<F: FileSystem> Vault<F> {
         self.sync_state.consume_synced(path)
     }
 
+    /// Set how long sync flags stay valid (default [`DEFAULT_FLAG_TTL`]).
+    ///
+    /// Raise it when file watcher events arrive late (slow or mobile filesystems)
+    /// and synced writes get re-broadcast; `consumed_expired` in
+    /// [`Vault::sync_flag_stats`] shows when that happens.
+    pub fn with_flag_ttl(mut self, ttl: Duration) -> Self {
+        self.sync_state.ttl = ttl;
+        self
+    }
+
+    /// Read time from `clock` instead of the system clock.
+    ///
+    /// Covers sync flag expiry and the timestamps on peers and events, so tests
+    /// can advance a [`TestClock`](crate::clock::TestClock) instead of sleeping.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.sync_state.clock = clock;
+        self
+    }
+
+    /// Get sync flag lifecycle counters (for diagnosing echo loops).
+    pub fn sync_flag_stats(&self) -> SyncFlagStats {
+        self.sync_state.stats()
+    }
+
+    /// Write every dirty loaded document and the registry in one pass.
+    ///
+    /// Documents with uncommitted changes count as dirty. This is the
+    /// durability backstop for in-memory edits: call it before shutting down
+    /// (e.g. when the plugin unloads). Returns the number of documents written.
+    pub async fn flush(&self) -> Result<usize> {
+        // Clone out under the borrow, write after it is released
+        let dirty: Vec<(String, NoteDocument)> = self
+            .documents()
+            .iter()
+            .filter(|(_, doc)| {
+                let before = doc.version();
+                doc.commit();
+                doc.is_dirty() || doc.version() != before
+            })
+            .map(|(path, doc)| (path.clone(), doc.clone()))
+            .collect();
+
+        for (path, doc) in &dirty {
+            self.write_document_state(path, doc).await?;
+        }
+        self.save_registry().await?;
+
+        tracing::debug!("Flushed {} documents", dirty.len());
+        Ok(dirty.len())
+    }
+
     /// Get the version vector for a document as encoded bytes.
     ///
     /// Returns None if the document hasn't been loaded.
@@ -901,6 +1347,25 @@ impl<F: FileSystem> Vault<F> {
         current.includes_vv(&synced)
     }
 
+    /// Check whether a file modification includes local edits.
+    ///
+    /// `new_version` is the document's version after the modification was applied
+    /// (`get_document_version` after `on_file_changed`). Returns false when it adds
+    /// nothing beyond the version sync last wrote for `path`, i.e. the change is a
+    /// sync echo. Unlike `consume_sync_flag` this is exact and doesn't depend on
+    /// the flag TTL. Paths sync never wrote count as local.
+    pub fn is_modification_local(&self, path: &str, new_version: &[u8]) -> bool {
+        match self.sync_state.synced_version(path) {
+            Some(synced) => !Self::version_includes(&synced.encode(), new_version),
+            None => true,
+        }
+    }
+
+    /// Record the version of a document sync just wrote (see `is_modification_local`).
+    pub(crate) fn record_synced_version(&self, path: &str, version: VersionVector) {
+        self.sync_state.record_synced_version(path, version);
+    }
+
     /// Check if vault is initialized
     pub async fn is_initialized(&self) -> Result<bool> {
         Ok(self.fs.exists(SYNC_DIR).await?)
@@ -951,7 +1416,9 @@ impl<F: FileSystem> Vault<F> {
         if self.fs.exists(&sync_path).await? {
             let bytes = self.fs.read(&sync_path).await?;
             // Use from_bytes to preserve peer ID (imports before setting metadata)
-            return Ok(NoteDocument::from_bytes(path, &bytes, self.peer_id)?);
+            let doc = NoteDocument::from_bytes(path, &bytes, self.peer_id)?;
+            self.protect_deleted_edits(&doc);
+            return Ok(doc);
         }
 
         // Otherwise load from markdown file
@@ -967,9 +1434,7 @@ impl<F: FileSystem> Vault<F> {
 
     /// Get the sync storage path for a document
     pub(crate) fn document_sync_path(&self, path: &str) -> String {
-        // Simple hash-based naming
-        let hash = simple_hash(path);
-        format!("{}/documents/{}.loro", SYNC_DIR, hash)
+        document_sync_path(path)
     }
 
     /// Handle a file change (from file watcher or Obsidian event).
@@ -977,8 +1442,8 @@ impl<F: FileSystem> Vault<F> {
     /// Uses diff-and-merge to update existing documents, preserving peer ID.
     /// Only creates a new document if no .loro file exists on disk.
     pub async fn on_file_changed(&self, path: &str) -> Result<()> {
-        // Skip non-markdown files and .sync directory
-        if !path.ends_with(".md") || path.starts_with(SYNC_DIR) {
+        // Skip non-markdown files, the .sync directory, and excluded paths
+        if !self.is_path_synced(path) {
             return Ok(());
         }
 
@@ -986,6 +1451,7 @@ impl<F: FileSystem> Vault<F> {
         let bytes = self.fs.read(path).await?;
         let content = String::from_utf8_lossy(&bytes);
         let parsed = crate::markdown::parse(&content);
+        self.check_note_size(path, &parsed.body)?;
         let sync_path = self.document_sync_path(path);
 
         // If document is in cache, diff-and-merge
@@ -995,10 +1461,8 @@ impl<F: FileSystem> Vault<F> {
             let fm_changed = existing_doc.update_frontmatter(parsed.frontmatter.as_ref())?;
 
             if body_changed || fm_changed {
-                existing_doc.commit();
-                let snapshot = existing_doc.export_snapshot();
-                self.documents_mut().insert(path.to_string(), existing_doc);
-                self.fs.write(&sync_path, &snapshot).await?;
+                self.documents_mut().insert(path.to_string(), existing_doc.clone());
+                self.write_document_state(path, &existing_doc).await?;
                 tracing::debug!("Updated document via diff: {}", path);
             } else {
                 tracing::debug!("No changes detected (sync echo): {}", path);
@@ -1011,14 +1475,13 @@ impl<F: FileSystem> Vault<F> {
             // Load from disk and diff-merge (preserves peer ID)
             let loro_bytes = self.fs.read(&sync_path).await?;
             let doc = NoteDocument::from_bytes(path, &loro_bytes, self.peer_id)?;
+            self.protect_deleted_edits(&doc);
 
             let body_changed = doc.update_body(&parsed.body)?;
             let fm_changed = doc.update_frontmatter(parsed.frontmatter.as_ref())?;
 
             if body_changed || fm_changed {
-                doc.commit();
-                let snapshot = doc.export_snapshot();
-                self.fs.write(&sync_path, &snapshot).await?;
+                self.write_document_state(path, &doc).await?;
                 tracing::debug!("Updated cold-cache document via diff: {}", path);
             } else {
                 tracing::debug!("No changes detected (cold cache sync echo): {}", path);
@@ -1030,8 +1493,7 @@ impl<F: FileSystem> Vault<F> {
 
         // Document doesn't exist anywhere - create new (this is the only time we need new peer ID)
         let new_doc = NoteDocument::from_markdown(path, &content, self.peer_id)?;
-        let snapshot = new_doc.export_snapshot();
-        self.fs.write(&sync_path, &snapshot).await?;
+        self.write_document_state(path, &new_doc).await?;
         self.documents_mut().insert(path.to_string(), new_doc);
 
         // Register in tree for delete/rename tracking
@@ -1052,43 +1514,91 @@ impl<F: FileSystem> Vault<F> {
             self.fs.write(path, markdown.as_bytes()).await?;
 
             // Save sync state
-            let sync_path = self.document_sync_path(path);
-            let snapshot = doc.export_snapshot();
-            self.fs.write(&sync_path, &snapshot).await?;
+            self.write_document_state(path, &doc).await?;
         }
         Ok(())
     }
 
-    /// List all markdown files in the vault
-    pub async fn list_files(&self) -> Result<Vec<String>> {
-        let mut files = Vec::new();
-        let mut dirs_to_visit = vec![String::new()]; // Start with root
+    /// Reject a note whose body is over the size limit, emitting `NoteTooLarge`.
+    pub(crate) fn check_note_size(&self, path: &str, body: &str) -> Result<()> {
+        let limit = self.max_note_size();
+        if body.len() <= limit {
+            return Ok(());
+        }
 
-        while let Some(dir) = dirs_to_visit.pop() {
-            let entries = self.fs.list(&dir).await?;
+        tracing::warn!("Not syncing {}: body is {} bytes (limit {})", path, body.len(), limit);
+        self.emit(SyncEvent::NoteTooLarge {
+            path: path.to_string(),
+            size: body.len(),
+            limit,
+            timestamp: self.now_ms(),
+        });
+        Err(VaultError::NoteTooLarge {
+            path: path.to_string(),
+            size: body.len(),
+            limit,
+        })
+    }
 
-            for entry in entries {
-                let path = if dir.is_empty() {
-                    entry.name.clone()
-                } else {
-                    format!("{}/{}", dir, entry.name)
-                };
+    /// Commit `doc` and write its `.loro` file, clearing its dirty flag.
+    pub(crate) async fn write_document_state(&self, path: &str, doc: &NoteDocument) -> Result<()> {
+        doc.commit();
+        let snapshot = doc.export_snapshot();
+        self.fs.write(&self.document_sync_path(path), &snapshot).await?;
+        doc.mark_clean();
+        Ok(())
+    }
 
-                // Skip .sync directory and hidden files
-                if path.starts_with(SYNC_DIR) || path.starts_with('.') {
-                    continue;
-                }
+    /// List the vault's synced markdown files (see `is_path_synced`).
+    pub async fn list_files(&self) -> Result<Vec<String>> {
+        let mut files = list_markdown_files(&self.fs).await?;
+        files.retain(|path| self.is_path_synced(path));
+        Ok(files)
+    }
 
-                if entry.is_dir {
-                    dirs_to_visit.push(path);
-                } else if path.ends_with(".md") {
-                    files.push(path);
+    /// Import a directory of markdown files (e.g. a backup from `export_markdown_tree`).
+    ///
+    /// Each file is written into the vault and diff-merged into its existing
+    /// `NoteDocument` via `on_file_changed`, so CRDT history is preserved and
+    /// concurrent peer edits merge cleanly. Files without a document get a new one.
+    pub async fn import_markdown_tree<S: FileSystem>(&self, source: &S) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut paths = list_markdown_files(source).await?;
+        paths.retain(|path| self.is_path_synced(path));
+        paths.sort();
+
+        for path in paths {
+            Self::validate_sync_path(&path)?;
+            let content = source.read(&path).await?;
+            let before = self.get_document_version(&path).await?;
+
+            self.fs.write(&path, &content).await?;
+            self.on_file_changed(&path).await?;
+            // Re-register notes that were deleted from the tree (no-op otherwise)
+            self.register_file(&path)?;
+
+            match before {
+                None => report.created.push(path),
+                Some(before) => {
+                    // No new operations since `before` means nothing was merged
+                    let after = self.get_document_version(&path).await?.unwrap_or_default();
+                    if Self::version_includes(&before, &after) {
+                        report.unchanged.push(path);
+                    } else {
+                        report.merged.push(path);
+                    }
                 }
             }
         }
 
-        Ok(files)
-    }
+        tracing::info!(
+            "Imported markdown tree: {} created, {} merged, {} unchanged",
+            report.created.len(),
+            report.merged.len(),
+            report.unchanged.len()
+        );
+        Ok(report)
+    }
 
     /// Index all existing markdown files in the vault.
     ///
@@ -1182,6 +1692,122 @@ impl<F: FileSystem> Vault<F> {
         Some(parts.join("/"))
     }
 
+    /// Whether `node_id` is a folder node.
+    pub(crate) fn is_folder_node(tree: &LoroTree, node_id: TreeID) -> bool {
+        let Ok(meta) = tree.get_meta(node_id) else {
+            return false;
+        };
+        match meta.get("type") {
+            Some(loro::ValueOrContainer::Value(val)) => {
+                val.as_string().is_some_and(|s| s.as_ref() == "folder")
+            }
+            _ => false,
+        }
+    }
+
+    /// Path a file node was last moved from, if it was ever moved.
+    pub(crate) fn node_moved_from(&self, node_id: &TreeID) -> Option<String> {
+        let meta = self.file_tree().get_meta(*node_id).ok()?;
+        match meta.get("moved_from")? {
+            loro::ValueOrContainer::Value(val) => val.as_string().map(|s| s.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Paths a file node was moved from, oldest first (up to `MAX_MOVED_FROM_PATHS`).
+    fn node_moved_from_paths(&self, node_id: &TreeID) -> Vec<String> {
+        let Ok(meta) = self.file_tree().get_meta(*node_id) else {
+            return Vec::new();
+        };
+        match meta.get(MOVED_FROM_PATHS_KEY) {
+            Some(loro::ValueOrContainer::Value(loro::LoroValue::List(paths))) => paths
+                .iter()
+                .filter_map(|p| p.as_string().map(|s| s.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Current path of the live file last moved away from `old_path`, if any.
+    ///
+    /// Follows renames since, so a file renamed twice is still found from its
+    /// first path.
+    pub(crate) fn moved_to(&self, old_path: &str) -> Option<String> {
+        let nodes: Vec<(String, TreeID)> = self
+            .path_to_node()
+            .iter()
+            .map(|(path, node)| (path.clone(), *node))
+            .collect();
+        let last_moved_from = nodes
+            .iter()
+            .find(|(_, node)| self.node_moved_from(node).as_deref() == Some(old_path));
+        let moved_from = last_moved_from.or_else(|| {
+            nodes
+                .iter()
+                .find(|(_, node)| self.node_moved_from_paths(node).iter().any(|p| p == old_path))
+        });
+        moved_from.map(|(path, _)| path.clone())
+    }
+
+    /// Encoded document version a file node was deleted at, if recorded.
+    pub(crate) fn node_deleted_version(&self, node_id: &TreeID) -> Option<Vec<u8>> {
+        let meta = self.file_tree().get_meta(*node_id).ok()?;
+        match meta.get("deleted_version")? {
+            loro::ValueOrContainer::Value(val) => val.as_binary().map(|b| b.to_vec()),
+            _ => None,
+        }
+    }
+
+    /// Versions at which the note with `doc_id` was deleted from the tree.
+    fn deleted_versions(&self, doc_id: &str) -> Vec<VersionVector> {
+        let tree = self.file_tree();
+        tree.nodes()
+            .into_iter()
+            .filter(|node_id| tree.is_node_deleted(node_id).unwrap_or(false))
+            .filter(|node_id| {
+                tree.get_meta(*node_id).is_ok_and(|meta| {
+                    matches!(
+                        meta.get("deleted_doc_id"),
+                        Some(loro::ValueOrContainer::Value(val)) if val.as_string().is_some_and(|id| **id == doc_id)
+                    )
+                })
+            })
+            .filter_map(|node_id| self.node_deleted_version(&node_id))
+            .filter_map(|bytes| VersionVector::decode(&bytes).ok())
+            .collect()
+    }
+
+    /// Whether `doc` is a note that was deleted from the tree.
+    pub(crate) fn is_deleted_note(&self, doc: &NoteDocument) -> bool {
+        doc.doc_id().is_some_and(|doc_id| !self.deleted_versions(&doc_id).is_empty())
+    }
+
+    /// Whether a deleted note with `doc`'s history had edits of ours that `doc` lacks.
+    ///
+    /// We dropped those edits with the note's .loro, but peers may still have
+    /// them (e.g. a broadcast not yet processed).
+    fn lacks_deleted_edits(&self, doc: &NoteDocument) -> bool {
+        let Some(doc_id) = doc.doc_id() else {
+            return false;
+        };
+        let ours = self.peer_id.as_u64();
+        let have = doc.version().get(&ours).copied().unwrap_or(0);
+        self.deleted_versions(&doc_id)
+            .iter()
+            .any(|deleted| deleted.get(&ours).copied().unwrap_or(0) > have)
+    }
+
+    /// Edit `doc` under a fresh peer ID if it's a note we deleted, come back
+    /// without our last edits to it.
+    ///
+    /// Continuing under our own peer ID would reuse the op IDs of those edits.
+    pub(crate) fn protect_deleted_edits(&self, doc: &NoteDocument) {
+        if self.lacks_deleted_edits(doc) {
+            tracing::debug!("{} is missing edits we deleted, editing under a new peer ID", doc.path());
+            doc.set_peer_id(PeerId::generate());
+        }
+    }
+
     /// Find a node by path using the cache
     fn find_node_by_path(&self, path: &str) -> Option<TreeID> {
         self.path_to_node().get(path).copied()
@@ -1191,43 +1817,43 @@ impl<F: FileSystem> Vault<F> {
     fn validate_sync_path(path: &str) -> Result<()> {
         // Empty path
         if path.is_empty() {
-            return Err(VaultError::Other("Empty path not allowed".into()));
+            return Err(PathValidationError::Empty.into());
         }
         // Path traversal
         if path.contains("..") {
-            return Err(VaultError::Other("Path traversal not allowed".into()));
+            return Err(PathValidationError::Traversal.into());
         }
         // Empty segments (a//b.md)
         if path.contains("//") {
-            return Err(VaultError::Other("Empty path segment not allowed".into()));
+            return Err(PathValidationError::EmptySegment.into());
         }
         // Absolute paths (Unix)
         if path.starts_with('/') {
-            return Err(VaultError::Other("Absolute path not allowed".into()));
+            return Err(PathValidationError::Absolute.into());
         }
         // Absolute paths (Windows - drive letter)
         if path.len() >= 2 && path.chars().nth(1) == Some(':') {
-            return Err(VaultError::Other("Windows absolute path not allowed".into()));
+            return Err(PathValidationError::WindowsAbsolute.into());
         }
         // Backslash
         if path.contains('\\') {
-            return Err(VaultError::Other("Backslash in path not allowed".into()));
+            return Err(PathValidationError::Backslash.into());
         }
         // Null bytes
         if path.contains('\0') {
-            return Err(VaultError::Other("Null byte in path not allowed".into()));
+            return Err(PathValidationError::NullByte.into());
         }
         // Must be .md
         if !path.ends_with(".md") {
-            return Err(VaultError::Other("Only markdown files allowed".into()));
+            return Err(PathValidationError::NotMarkdown.into());
         }
         // Control characters
         if path.chars().any(|c| c.is_control()) {
-            return Err(VaultError::Other("Control character in path not allowed".into()));
+            return Err(PathValidationError::ControlCharacter.into());
         }
         // Path length limit (filesystem safety)
         if path.len() > 1024 {
-            return Err(VaultError::Other("Path too long".into()));
+            return Err(PathValidationError::TooLong.into());
         }
         Ok(())
     }
@@ -1255,17 +1881,17 @@ impl<F: FileSystem> Vault<F> {
         let tree = self.file_tree();
         let node_id = tree
             .create(parent_id)
-            .map_err(|e| VaultError::Other(format!("Failed to create file node: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to create file node: {}", e)))?;
 
         let meta = tree
             .get_meta(node_id)
-            .map_err(|e| VaultError::Other(format!("Failed to get file meta: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to get file meta: {}", e)))?;
         meta.insert("type", "file")
-            .map_err(|e| VaultError::Other(format!("Failed to set file type: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to set file type: {}", e)))?;
         meta.insert("name", file_name[0])
-            .map_err(|e| VaultError::Other(format!("Failed to set file name: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to set file name: {}", e)))?;
         meta.insert("doc_id", simple_hash(path))
-            .map_err(|e| VaultError::Other(format!("Failed to set doc_id: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to set doc_id: {}", e)))?;
 
         // Update cache
         self.path_to_node_mut().insert(path.to_string(), node_id);
@@ -1274,15 +1900,87 @@ impl<F: FileSystem> Vault<F> {
         Ok(node_id)
     }
 
+    /// Pin or unpin a registered file.
+    ///
+    /// The flag is stored in the file's tree metadata, so it syncs with the
+    /// registry. While pinned, reconciliation treats the Loro doc as the source
+    /// of truth and re-writes the markdown instead of re-indexing it.
+    /// The registry is saved so the flag survives a reload.
+    pub async fn set_pinned(&self, path: &str, pinned: bool) -> Result<()> {
+        let node_id = self
+            .find_node_by_path(path)
+            .ok_or_else(|| VaultError::SourceNotFound(path.to_string()))?;
+
+        let tree = self.file_tree();
+        let meta = tree
+            .get_meta(node_id)
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to get file meta: {}", e)))?;
+        if pinned {
+            meta.insert(PINNED_KEY, true)
+                .map_err(|e| VaultError::TreeOperation(format!("Failed to set pinned flag: {}", e)))?;
+        } else {
+            meta.delete(PINNED_KEY)
+                .map_err(|e| VaultError::TreeOperation(format!("Failed to clear pinned flag: {}", e)))?;
+        }
+
+        self.save_registry().await?;
+
+        tracing::debug!("Set pinned={} for {}", pinned, path);
+        Ok(())
+    }
+
+    /// Check whether a registered file is pinned.
+    pub fn is_pinned(&self, path: &str) -> bool {
+        let Some(node_id) = self.find_node_by_path(path) else {
+            return false;
+        };
+        let Ok(meta) = self.file_tree().get_meta(node_id) else {
+            return false;
+        };
+        matches!(
+            meta.get(PINNED_KEY),
+            Some(loro::ValueOrContainer::Value(loro::LoroValue::Bool(true)))
+        )
+    }
+
     /// Delete a file from the tree (CRDT operation - tracked, reversible).
     /// Also cleans up the .loro document file.
+    ///
+    /// The deleted version and document ID are recorded on the node, so a peer
+    /// can tell edits it hasn't seen, and we can tell when the note comes back
+    /// without edits of ours (see `protect_deleted_edits`).
+    ///
+    /// Does nothing for paths outside the sync scope.
     pub async fn delete_file(&self, path: &str) -> Result<()> {
         Self::validate_sync_path(path)?;
+        if !self.is_path_synced(path) {
+            return Ok(());
+        }
 
         if let Some(node_id) = self.find_node_by_path(path) {
+            // Record the version being deleted so peers can tell whether they
+            // have edits this deletion didn't account for
+            let version = self.current_version(path).await?;
+            let doc_id = match version {
+                Some(_) => self.get_document(path).await?.doc_id(),
+                None => None,
+            };
             let tree = self.file_tree();
+            if let Some(version) = version {
+                let meta = tree
+                    .get_meta(node_id)
+                    .map_err(|e| VaultError::TreeOperation(format!("Failed to get file meta: {}", e)))?;
+                meta.insert("deleted_version", version)
+                    .map_err(|e| VaultError::TreeOperation(format!("Failed to record deleted version: {}", e)))?;
+                if let Some(doc_id) = doc_id {
+                    meta.insert("deleted_doc_id", doc_id)
+                        .map_err(|e| VaultError::TreeOperation(format!("Failed to record deleted document: {}", e)))?;
+                }
+            }
+            let parent = tree.parent(node_id);
             tree.delete(node_id)
-                .map_err(|e| VaultError::Other(format!("Failed to delete file node: {}", e)))?;
+                .map_err(|e| VaultError::TreeOperation(format!("Failed to delete file node: {}", e)))?;
+            self.prune_empty_folders(parent)?;
 
             // Remove from cache
             self.path_to_node_mut().remove(path);
@@ -1303,6 +2001,11 @@ impl<F: FileSystem> Vault<F> {
     }
 
     /// Rename/move a file in the tree (CRDT operation via tree move).
+    ///
+    /// A move out of the sync scope is still recorded, since the note now lives
+    /// at `new_path` for peers that sync it. A move into the scope starts
+    /// tracking the note as if it were new, and a move that stays outside the
+    /// scope is ignored.
     pub async fn rename_file(&self, old_path: &str, new_path: &str) -> Result<()> {
         Self::validate_sync_path(old_path)?;
         Self::validate_sync_path(new_path)?;
@@ -1312,63 +2015,79 @@ impl<F: FileSystem> Vault<F> {
             return Ok(());
         }
 
+        if !self.is_path_synced(old_path) {
+            if !self.is_path_synced(new_path) {
+                return Ok(());
+            }
+            if let Some(node_id) = self.find_node_by_path(old_path) {
+                if self.find_node_by_path(new_path).is_some() {
+                    return Err(VaultError::TargetExists(new_path.to_string()));
+                }
+                self.move_file_node(node_id, old_path, new_path)?;
+            }
+            if self.fs.exists(new_path).await? {
+                self.on_file_changed(new_path).await?;
+            }
+            return Ok(());
+        }
+
         let Some(node_id) = self.find_node_by_path(old_path) else {
             // Source not in tree - this can happen when receiving FileRenamed before
             // the registry has synced. Handle the rename at filesystem level if possible.
-            if self.fs.exists(old_path).await.unwrap_or(false) {
-                // Source exists on disk but not in tree - rename on disk and register target
-                tracing::debug!(
-                    "rename_file: source {} not in tree but exists on disk - renaming and registering",
-                    old_path
-                );
-
-                // Rename the actual file
-                let content = self.fs.read(old_path).await?;
-                self.fs.write(new_path, &content).await?;
-                self.fs.delete(old_path).await?;
-
-                // Move .loro file if it exists
-                let old_sync = self.document_sync_path(old_path);
-                let new_sync = self.document_sync_path(new_path);
-                if self.fs.exists(&old_sync).await.unwrap_or(false) {
-                    let sync_content = self.fs.read(&old_sync).await?;
-                    self.fs.write(&new_sync, &sync_content).await?;
-                    self.fs.delete(&old_sync).await?;
-                }
-
-                // Update documents cache - extract first to release mutex before re-acquiring
-                let doc = self.documents_mut().remove(old_path);
-                if let Some(doc) = doc {
-                    self.documents_mut().insert(new_path.to_string(), doc);
-                }
+            return self.rename_unregistered_file(old_path, new_path).await;
+        };
 
-                // Register in tree
-                self.register_file(new_path)?;
-                return Ok(());
-            } else if self.fs.exists(new_path).await.unwrap_or(false) {
-                // Target already exists (rename already happened) - just register it
-                tracing::debug!(
-                    "rename_file: source {} not in tree, but {} exists - registering target",
-                    old_path, new_path
-                );
-                self.register_file(new_path)?;
+        // Check target doesn't exist
+        if self.find_node_by_path(new_path).is_some() {
+            return Err(VaultError::TargetExists(new_path.to_string()));
+        }
 
-                // Clean up orphaned .loro at old path if it exists
-                let old_sync = self.document_sync_path(old_path);
-                if self.fs.exists(&old_sync).await.unwrap_or(false) {
-                    let _ = self.fs.delete(&old_sync).await;
-                }
+        self.move_file_node(node_id, old_path, new_path)?;
 
-                return Ok(());
+        // Sync may already have created the note at the new path, from an edit
+        // a peer made after the rename that arrived before it: merge ours in
+        let old_sync_path = self.document_sync_path(old_path);
+        let new_sync_path = self.document_sync_path(new_path);
+        let has_new_doc =
+            self.documents().contains_key(new_path) || self.fs.exists(&new_sync_path).await?;
+        let has_old_doc =
+            self.documents().contains_key(old_path) || self.fs.exists(&old_sync_path).await?;
+        if has_new_doc && has_old_doc {
+            let ours = self.get_document(old_path).await?;
+            let mut doc = self.get_document_mut(new_path).await?;
+            // Unrelated notes aren't merged; the one at the new path is kept
+            if ours.doc_id() == doc.doc_id() {
+                doc.import(&ours.export_snapshot())?;
+                self.update_document(new_path, doc);
+                self.save_document(new_path).await?;
             }
-            return Err(VaultError::Other(format!("Source file not found: {}", old_path)));
-        };
+            self.documents_mut().remove(old_path);
+            if self.fs.exists(&old_sync_path).await? {
+                self.fs.delete(&old_sync_path).await?;
+            }
+            tracing::info!("Renamed file in tree: {} -> {} (merged)", old_path, new_path);
+            return Ok(());
+        }
 
-        // Check target doesn't exist
-        if self.find_node_by_path(new_path).is_some() {
-            return Err(VaultError::Other(format!("Target already exists: {}", new_path)));
+        // Move .loro document file
+        if self.fs.exists(&old_sync_path).await? {
+            let bytes = self.fs.read(&old_sync_path).await?;
+            self.fs.write(&new_sync_path, &bytes).await?;
+            self.fs.delete(&old_sync_path).await?;
+        }
+
+        // Update documents cache - extract first to release mutex before re-acquiring
+        let doc = self.documents_mut().remove(old_path);
+        if let Some(doc) = doc {
+            self.documents_mut().insert(new_path.to_string(), doc);
         }
 
+        tracing::info!("Renamed file in tree: {} -> {}", old_path, new_path);
+        Ok(())
+    }
+
+    /// Move a file node to `new_path` in the tree, updating its metadata and the path cache.
+    fn move_file_node(&self, node_id: TreeID, old_path: &str, new_path: &str) -> Result<()> {
         let new_parts: Vec<&str> = new_path.split('/').collect();
         let (new_folders, new_name) = new_parts.split_at(new_parts.len() - 1);
 
@@ -1379,31 +2098,125 @@ impl<F: FileSystem> Vault<F> {
         }
 
         let tree = self.file_tree();
+        let old_parent = tree.parent(node_id);
 
         // Move node to new parent (Loro API is `mov`)
         tree.mov(node_id, new_parent)
-            .map_err(|e| VaultError::Other(format!("Failed to move file node: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to move file node: {}", e)))?;
+        self.prune_empty_folders(old_parent)?;
 
         // Update name in metadata
         let meta = tree
             .get_meta(node_id)
-            .map_err(|e| VaultError::Other(format!("Failed to get file meta: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to get file meta: {}", e)))?;
         meta.insert("name", new_name[0])
-            .map_err(|e| VaultError::Other(format!("Failed to update file name: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to update file name: {}", e)))?;
         meta.insert("doc_id", simple_hash(new_path))
-            .map_err(|e| VaultError::Other(format!("Failed to update doc_id: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to update doc_id: {}", e)))?;
+        // Lets peers tell a concurrent move (from a path they no longer have)
+        // apart from one made on top of theirs
+        meta.insert("moved_from", old_path)
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to update moved_from: {}", e)))?;
+        let mut moved_from_paths = self.node_moved_from_paths(&node_id);
+        moved_from_paths.push(old_path.to_string());
+        let excess = moved_from_paths.len().saturating_sub(MAX_MOVED_FROM_PATHS);
+        moved_from_paths.drain(..excess);
+        meta.insert(MOVED_FROM_PATHS_KEY, moved_from_paths)
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to update moved_from_paths: {}", e)))?;
 
         // Update caches
         self.path_to_node_mut().remove(old_path);
         self.path_to_node_mut().insert(new_path.to_string(), node_id);
+        Ok(())
+    }
 
-        // Move .loro document file
-        let old_sync_path = self.document_sync_path(old_path);
-        let new_sync_path = self.document_sync_path(new_path);
-        if self.fs.exists(&old_sync_path).await? {
-            let bytes = self.fs.read(&old_sync_path).await?;
-            self.fs.write(&new_sync_path, &bytes).await?;
-            self.fs.delete(&old_sync_path).await?;
+    /// Rename a file whose source isn't in the tree.
+    ///
+    /// Moves the files on disk, then settles the tree with
+    /// `settle_renamed_node`, since sync may register either path while the
+    /// files are being moved.
+    async fn rename_unregistered_file(&self, old_path: &str, new_path: &str) -> Result<()> {
+        if self.fs.exists(old_path).await.unwrap_or(false) {
+            // Source exists on disk but not in tree - rename on disk and register target
+            tracing::debug!(
+                "rename_file: source {} not in tree but exists on disk - renaming and registering",
+                old_path
+            );
+            self.move_note_files(old_path, new_path).await?;
+        } else if self.fs.exists(new_path).await.unwrap_or(false) {
+            // Target already exists (rename already happened) - just register it
+            tracing::debug!(
+                "rename_file: source {} not in tree, but {} exists - registering target",
+                old_path, new_path
+            );
+
+            // Clean up orphaned .loro at old path if it exists
+            let old_sync = self.document_sync_path(old_path);
+            if self.fs.exists(&old_sync).await.unwrap_or(false) {
+                let _ = self.fs.delete(&old_sync).await;
+            }
+        } else {
+            return Err(VaultError::SourceNotFound(old_path.to_string()));
+        }
+
+        self.settle_renamed_node(old_path, new_path)?;
+        Ok(())
+    }
+
+    /// Leave a single file node at `new_path` after a rename done on disk.
+    ///
+    /// Checks the tree again now that the disk is settled: a source node that
+    /// appeared meanwhile is moved to `new_path`, or dropped if `new_path` got
+    /// its own node too. Nothing here awaits, so no sync can land between the
+    /// checks and the fix.
+    fn settle_renamed_node(&self, old_path: &str, new_path: &str) -> Result<TreeID> {
+        match (
+            self.find_node_by_path(old_path),
+            self.find_node_by_path(new_path),
+        ) {
+            (Some(old_node), None) => {
+                tracing::debug!(
+                    "rename_file: {} was registered during the rename - moving its node",
+                    old_path
+                );
+                self.move_file_node(old_node, old_path, new_path)?;
+                Ok(old_node)
+            }
+            (Some(old_node), Some(new_node)) => {
+                tracing::debug!(
+                    "rename_file: {} and {} were both registered during the rename - keeping {}",
+                    old_path,
+                    new_path,
+                    new_path
+                );
+                let tree = self.file_tree();
+                let parent = tree.parent(old_node);
+                tree.delete(old_node).map_err(|e| {
+                    VaultError::TreeOperation(format!("Failed to delete file node: {}", e))
+                })?;
+                self.prune_empty_folders(parent)?;
+                self.path_to_node_mut().remove(old_path);
+                Ok(new_node)
+            }
+            (None, Some(new_node)) => Ok(new_node),
+            (None, None) => self.register_file(new_path),
+        }
+    }
+
+    /// Move a note's markdown file, `.loro` document and cache entry to a new path.
+    pub(crate) async fn move_note_files(&self, old_path: &str, new_path: &str) -> Result<()> {
+        if self.fs.exists(old_path).await? {
+            let content = self.fs.read(old_path).await?;
+            self.fs.write(new_path, &content).await?;
+            self.fs.delete(old_path).await?;
+        }
+
+        let old_sync = self.document_sync_path(old_path);
+        let new_sync = self.document_sync_path(new_path);
+        if self.fs.exists(&old_sync).await.unwrap_or(false) {
+            let sync_content = self.fs.read(&old_sync).await?;
+            self.fs.write(&new_sync, &sync_content).await?;
+            self.fs.delete(&old_sync).await?;
         }
 
         // Update documents cache - extract first to release mutex before re-acquiring
@@ -1411,11 +2224,122 @@ impl<F: FileSystem> Vault<F> {
         if let Some(doc) = doc {
             self.documents_mut().insert(new_path.to_string(), doc);
         }
+        Ok(())
+    }
 
-        tracing::info!("Renamed file in tree: {} -> {}", old_path, new_path);
+    /// Enable or disable rewriting wiki-links when a peer renames a note.
+    ///
+    /// When enabled, applying a `FileRenamed` message updates links to the old
+    /// name in every other note. The edits sync like any local change, so enable
+    /// this on one peer only (e.g. the daemon), or peers will rewrite the same
+    /// links concurrently. Disabled by default.
+    pub fn set_rewrite_links_on_rename(&self, enabled: bool) {
+        self.rewrite_links_on_rename.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether wiki-links are rewritten when a peer renames a note.
+    pub fn rewrites_links_on_rename(&self) -> bool {
+        self.rewrite_links_on_rename.load(Ordering::Relaxed)
+    }
+
+    /// Choose what happens when a peer deletes a note with edits it hadn't seen.
+    ///
+    /// Defaults to [`DeleteEditPolicy::EditWins`], which keeps the note rather
+    /// than silently losing the edits.
+    pub fn set_delete_edit_policy(&self, policy: DeleteEditPolicy) {
+        *self.delete_edit_policy.lock().unwrap() = policy;
+    }
+
+    /// How deletions of notes with unseen edits are applied.
+    pub fn delete_edit_policy(&self) -> DeleteEditPolicy {
+        *self.delete_edit_policy.lock().unwrap()
+    }
+
+    /// Set the largest note body, in bytes, that is tracked and synced
+    /// (default [`DEFAULT_MAX_NOTE_SIZE`]).
+    ///
+    /// Changes to bigger notes are rejected with [`VaultError::NoteTooLarge`].
+    pub fn set_max_note_size(&self, bytes: usize) {
+        self.max_note_size.store(bytes, Ordering::Relaxed);
+    }
+
+    /// The largest note body, in bytes, that is tracked and synced.
+    pub fn max_note_size(&self) -> usize {
+        self.max_note_size.load(Ordering::Relaxed)
+    }
+
+    /// Limit sync to paths inside `scope`, saving it for later loads.
+    ///
+    /// Paths outside the scope are left alone: local changes to them aren't
+    /// tracked, and peers' changes to them aren't applied here.
+    pub async fn set_sync_scope(&self, scope: SyncScope) -> Result<()> {
+        let json = serde_json::to_vec_pretty(&scope)
+            .map_err(|e| VaultError::Other(format!("Failed to save sync scope: {}", e)))?;
+        self.fs.write(SCOPE_FILE, &json).await?;
+        *self.scope.lock().unwrap() = scope;
+        Ok(())
+    }
+
+    /// Restore the scope saved by `set_sync_scope`, if there is one.
+    async fn load_sync_scope(&self) -> Result<()> {
+        if !self.fs.exists(SCOPE_FILE).await? {
+            return Ok(());
+        }
+        let bytes = self.fs.read(SCOPE_FILE).await?;
+        let scope = serde_json::from_slice(&bytes)
+            .map_err(|e| VaultError::Other(format!("Invalid sync scope in {}: {}", SCOPE_FILE, e)))?;
+        *self.scope.lock().unwrap() = scope;
         Ok(())
     }
 
+    /// The current sync scope.
+    pub fn sync_scope(&self) -> SyncScope {
+        self.scope.lock().unwrap().clone()
+    }
+
+    /// Whether changes to `path` are synced.
+    ///
+    /// False for non-markdown files, sync state under `.sync/`, and paths
+    /// outside the [`SyncScope`].
+    pub fn is_path_synced(&self, path: &str) -> bool {
+        path.ends_with(".md")
+            && !path.starts_with(SYNC_DIR)
+            && self.scope.lock().unwrap().contains(path)
+    }
+
+    /// Point wiki-links in other notes at `new_path` after a rename from `old_path`.
+    ///
+    /// Returns the paths of the notes that were rewritten.
+    pub(crate) async fn rewrite_links_after_rename(
+        &self,
+        old_path: &str,
+        new_path: &str,
+    ) -> Result<Vec<String>> {
+        let mut rewritten = Vec::new();
+
+        for path in self.registered_files() {
+            if path == new_path || !path.ends_with(".md") || !self.fs.exists(&path).await? {
+                continue;
+            }
+
+            let bytes = self.fs.read(&path).await?;
+            let content = String::from_utf8_lossy(&bytes);
+            let Some(updated) = wiki_links::rename_links(&content, old_path, new_path) else {
+                continue;
+            };
+
+            // Mark as synced BEFORE writing (for echo detection), then fold into the CRDT
+            self.mark_synced(&path);
+            self.fs.write(&path, updated.as_bytes()).await?;
+            self.on_file_changed(&path).await?;
+
+            tracing::debug!("Rewrote links to {} in {}", new_path, path);
+            rewritten.push(path);
+        }
+
+        Ok(rewritten)
+    }
+
     /// Check if a file is deleted in the tree
     pub fn is_file_deleted(&self, path: &str) -> bool {
         match self.find_node_by_path(path) {
@@ -1427,6 +2351,28 @@ impl<F: FileSystem> Vault<F> {
         }
     }
 
+    /// List the paths of all live (non-deleted) files in the tree, sorted.
+    ///
+    /// Unlike `list_files`, this reflects registry state rather than what is on disk.
+    pub fn registered_files(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.path_to_node().keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    /// Render every live note to markdown (frontmatter + body) from CRDT state.
+    ///
+    /// Keys are vault-relative paths. The result is a snapshot of the vault
+    /// independent of the sync format, suitable for plain-markdown backups.
+    pub async fn export_markdown_tree(&self) -> Result<HashMap<String, String>> {
+        let mut tree = HashMap::new();
+        for path in self.registered_files() {
+            let doc = self.get_document(&path).await?;
+            tree.insert(path, doc.to_markdown());
+        }
+        Ok(tree)
+    }
+
     /// Get or create a folder node
     fn get_or_create_folder(&self, parent: TreeParentId, name: &str) -> Result<TreeParentId> {
         let tree = self.file_tree();
@@ -1468,19 +2414,47 @@ impl<F: FileSystem> Vault<F> {
         // Create new folder node
         let node_id = tree
             .create(parent)
-            .map_err(|e| VaultError::Other(format!("Failed to create folder node: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to create folder node: {}", e)))?;
 
         let meta = tree
             .get_meta(node_id)
-            .map_err(|e| VaultError::Other(format!("Failed to get folder meta: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to get folder meta: {}", e)))?;
         meta.insert("type", "folder")
-            .map_err(|e| VaultError::Other(format!("Failed to set folder type: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to set folder type: {}", e)))?;
         meta.insert("name", name)
-            .map_err(|e| VaultError::Other(format!("Failed to set folder name: {}", e)))?;
+            .map_err(|e| VaultError::TreeOperation(format!("Failed to set folder name: {}", e)))?;
 
         Ok(TreeParentId::Node(node_id))
     }
 
+    /// Delete `folder` if it no longer has children, then its parent, and so
+    /// on up to the root.
+    ///
+    /// Call after a file node leaves `folder` (its parent before the delete or
+    /// move), so emptied folders don't linger in the tree. A file a peer adds
+    /// to the folder concurrently is deleted with it by the tree CRDT; that
+    /// peer registers it again when applying the prune.
+    fn prune_empty_folders(&self, folder: Option<TreeParentId>) -> Result<()> {
+        let tree = self.file_tree();
+        let mut current = folder;
+
+        while let Some(TreeParentId::Node(folder_id)) = current {
+            let is_empty = tree
+                .children(folder_id)
+                .is_none_or(|children| children.is_empty());
+            if !Self::is_folder_node(&tree, folder_id) || !is_empty {
+                break;
+            }
+
+            current = tree.parent(folder_id);
+            tree.delete(folder_id).map_err(|e| {
+                VaultError::TreeOperation(format!("Failed to delete folder node: {}", e))
+            })?;
+        }
+
+        Ok(())
+    }
+
     // ========== Debug API Methods ==========
     //
     // These methods expose internal CRDT state for debugging and dashboard UIs.
@@ -1639,6 +2613,15 @@ impl<F: FileSystem> Vault<F> {
         Ok(peer)
     }
 
+    /// Drop connections still awaiting a handshake after `timeout_ms`.
+    ///
+    /// Returns the expired connection IDs; the caller should close those
+    /// sockets with `DisconnectReason::ProtocolError`. No event is emitted,
+    /// since the peer never became visible as connected.
+    pub fn expire_pending_handshakes(&self, timeout_ms: f64) -> Vec<String> {
+        self.peers.expire_connecting(timeout_ms, self.now_ms())
+    }
+
     /// Get peer by connection ID (for pre-handshake lookups).
     pub fn get_peer_by_connection_id(&self, connection_id: &str) -> Option<ConnectedPeer> {
         self.peers.get_peer_by_connection_id(connection_id)
@@ -1649,6 +2632,16 @@ impl<F: FileSystem> Vault<F> {
         self.peers.resolve_peer_id(connection_id)
     }
 
+    /// Record that a message authored by `origin` arrived from neighbor `via`.
+    pub fn record_route(&self, origin: &str, via: &str) {
+        self.peers.record_route(origin, via)
+    }
+
+    /// Neighbor that messages from `origin` last arrived through.
+    pub fn route_to(&self, origin: &str) -> Option<String> {
+        self.peers.route_to(origin)
+    }
+
     /// Get all peers seen this session (connected and disconnected).
     pub fn get_known_peers(&self) -> Vec<ConnectedPeer> {
         self.peers.get_known_peers()
@@ -1665,6 +2658,40 @@ impl<F: FileSystem> Vault<F> {
     }
 }
 
+/// List all markdown files under the root of `fs`, skipping hidden files and
+/// directories (`.sync`, `.git`, `.obsidian`, ...).
+///
+/// Hidden top-level directories are never walked; each visible one is listed
+/// with `list_recursive`, dropping anything under a nested hidden directory.
+async fn list_markdown_files<T: FileSystem>(fs: &T) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    for entry in fs.list("").await? {
+        if entry.name.starts_with('.') {
+            continue;
+        }
+        if entry.is_dir {
+            let nested = fs.list_recursive(&entry.name).await?;
+            files.extend(nested.into_iter().map(|entry| entry.name));
+        } else {
+            files.push(entry.name);
+        }
+    }
+
+    files.retain(|path| {
+        path.ends_with(".md") && !path.split('/').any(|segment| segment.starts_with('.'))
+    });
+    Ok(files)
+}
+
+/// Vault-relative path of the `.loro` file storing the note at `path`.
+///
+/// For tools that read a vault's sync state without opening a `Vault`.
+pub fn document_sync_path(path: &str) -> String {
+    // Simple hash-based naming
+    let hash = simple_hash(path);
+    format!("{}/documents/{}.loro", SYNC_DIR, hash)
+}
+
 /// FNV-1a hash for deterministic file naming.
 /// Uses FNV-1a instead of DefaultHasher because DefaultHasher is not stable across Rust versions.
 fn simple_hash(s: &str) -> String {
@@ -1682,6 +2709,7 @@ fn simple_hash(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::TestClock;
     use crate::fs::InMemoryFs;
 
     fn test_peer_id() -> PeerId {
@@ -1761,36 +2789,270 @@ mod tests {
     }
     
     #[tokio::test]
-    async fn test_reconcile_detects_deleted_files() {
+    async fn test_load_detects_corrupted_registry() {
         use std::sync::Arc;
-        
+
         let fs = Arc::new(InMemoryFs::new());
-        
-        // Initialize vault with two files
-        fs.write("keep.md", b"# Keep this").await.unwrap();
-        fs.write("delete.md", b"# Delete this").await.unwrap();
-        let _vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
-        
-        // Simulate deleting a file while plugin was off
-        fs.delete("delete.md").await.unwrap();
-        
-        // Load vault - should detect orphaned .loro file
+        fs.write("note.md", b"# Note").await.unwrap();
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        vault.save_registry().await.unwrap();
+        drop(vault);
+
+        // Garbage bytes
+        fs.write(REGISTRY_FILE, b"not a loro document").await.unwrap();
+        let result = Vault::load(Arc::clone(&fs), test_peer_id()).await;
+        assert!(matches!(result, Err(VaultError::RegistryCorrupted(_))));
+
+        // Truncated snapshot
+        let registry = Vault::<InMemoryFs>::new_registry(test_peer_id());
+        let tree = registry.get_tree("files");
+        tree.create(TreeParentId::Root).unwrap();
+        let bytes = registry.export(loro::ExportMode::Snapshot).unwrap();
+        fs.write(REGISTRY_FILE, &bytes[..bytes.len() / 2]).await.unwrap();
+        let result = Vault::load(Arc::clone(&fs), test_peer_id()).await;
+        assert!(matches!(result, Err(VaultError::RegistryCorrupted(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_registry_reconstructs_tree() {
+        use std::sync::Arc;
+
+        let fs = Arc::new(InMemoryFs::new());
+        fs.write("a.md", b"# A").await.unwrap();
+        fs.write("notes/b.md", b"# B").await.unwrap();
+        fs.write("notes/deep/c.md", b"# C").await.unwrap();
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        vault.save_registry().await.unwrap();
+        let expected = vault.registered_files();
+        let doc_id = vault.get_document("notes/b.md").await.unwrap().doc_id();
+        drop(vault);
+
+        fs.write(REGISTRY_FILE, b"corrupted").await.unwrap();
+        assert!(Vault::load(Arc::clone(&fs), test_peer_id()).await.is_err());
+
+        let vault = Vault::rebuild_registry(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        assert_eq!(vault.registered_files(), expected);
+        // Existing documents are reused, not re-created
+        let rebuilt = vault.get_document("notes/b.md").await.unwrap();
+        assert_eq!(rebuilt.doc_id(), doc_id);
+        // The unreadable registry is kept for inspection
+        assert_eq!(fs.read(REGISTRY_BACKUP_FILE).await.unwrap(), b"corrupted");
+        drop(vault);
+
+        // The rebuilt registry was saved and loads normally
         let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
-        
-        // list_files should not include the deleted file
-        let files = vault.list_files().await.unwrap();
-        assert!(!files.contains(&"delete.md".to_string()));
-        assert!(files.contains(&"keep.md".to_string()));
+        assert_eq!(vault.registered_files(), expected);
     }
-    
+
     #[tokio::test]
-    async fn test_reconcile_detects_file_move() {
+    async fn test_rebuild_registry_keeps_readable_node_ids() {
         use std::sync::Arc;
-        
+
         let fs = Arc::new(InMemoryFs::new());
-        
-        // Initialize vault with a file
-        fs.write("old_name.md", b"# Unique Content ABC123").await.unwrap();
+        fs.write("a.md", b"# A").await.unwrap();
+        fs.write("notes/b.md", b"# B").await.unwrap();
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        vault.write_registry_snapshot().await.unwrap();
+        let a = vault.find_node_by_path("a.md").unwrap();
+        let b = vault.find_node_by_path("notes/b.md").unwrap();
+        drop(vault);
+
+        // A torn append leaves an unreadable log entry
+        let mut log = 7u32.to_le_bytes().to_vec();
+        log.extend_from_slice(b"garbage");
+        fs.write(REGISTRY_LOG_FILE, &log).await.unwrap();
+        fs.write("c.md", b"# C").await.unwrap();
+        assert!(matches!(
+            Vault::load(Arc::clone(&fs), test_peer_id()).await,
+            Err(VaultError::RegistryCorrupted(_))
+        ));
+
+        let vault = Vault::rebuild_registry(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        // Nodes from the readable snapshot keep their IDs; only c.md is new
+        assert_eq!(vault.find_node_by_path("a.md"), Some(a));
+        assert_eq!(vault.find_node_by_path("notes/b.md"), Some(b));
+        assert!(vault.find_node_by_path("c.md").is_some());
+        assert_eq!(fs.read(REGISTRY_LOG_BACKUP_FILE).await.unwrap(), log);
+        drop(vault);
+
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        assert_eq!(vault.find_node_by_path("a.md"), Some(a));
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_registry_requires_initialized_vault() {
+        let result = Vault::rebuild_registry(InMemoryFs::new(), test_peer_id()).await;
+        assert!(matches!(result, Err(VaultError::NotInitialized)));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_skips_unchanged_files() {
+        use std::sync::Arc;
+
+        let fs = Arc::new(InMemoryFs::new());
+        fs.write("same.md", b"# Same").await.unwrap();
+        fs.write("edited.md", b"# Before").await.unwrap();
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+
+        // First reconcile has no recorded stats, so everything is compared
+        let report = vault.reconcile().await.unwrap();
+        assert!(report.skipped.is_empty());
+
+        fs.write("edited.md", b"# After edit").await.unwrap();
+        let report = vault.reconcile().await.unwrap();
+        assert_eq!(report.skipped, vec!["same.md".to_string()]);
+        assert_eq!(report.reindexed, vec!["edited.md".to_string()]);
+
+        // Stats persist across reloads
+        drop(vault);
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        let mut report = vault.reconcile().await.unwrap();
+        report.skipped.sort();
+        assert_eq!(report.skipped, vec!["edited.md".to_string(), "same.md".to_string()]);
+        assert!(!report.has_changes());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_content_hash_catches_same_stat_edit() {
+        let fs = InMemoryFs::new();
+        fs.write("note.md", b"# Original").await.unwrap();
+        fs.set_mtime("note.md", 1000);
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+        vault.reconcile().await.unwrap();
+
+        // Same size and forged mtime, different content
+        vault.fs.write("note.md", b"# Modified").await.unwrap();
+        vault.fs.set_mtime("note.md", 1000);
+
+        let report = vault.reconcile().await.unwrap();
+        assert!(report.skipped.is_empty());
+        assert_eq!(report.reindexed, vec!["note.md".to_string()]);
+        let doc = vault.get_document("note.md").await.unwrap();
+        assert_eq!(doc.to_markdown(), "# Modified");
+    }
+
+    #[test]
+    fn test_cached_stat_matches() {
+        let stat = |mtime_millis, size, content_hash| FileStat {
+            mtime_millis,
+            size,
+            is_dir: false,
+            content_hash,
+        };
+        let cached = CachedStat::from_stat(&stat(10, 5, Some(1)));
+
+        assert!(cached.matches(&stat(10, 5, Some(1))));
+        assert!(cached.matches(&stat(10, 5, None)), "no hash falls back to mtime+size");
+        assert!(!cached.matches(&stat(10, 5, Some(2))));
+        assert!(!cached.matches(&stat(11, 5, Some(1))));
+        assert!(!cached.matches(&stat(10, 6, None)));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_restores_pinned_file() {
+        use std::sync::Arc;
+
+        let fs = Arc::new(InMemoryFs::new());
+        fs.write("pinned.md", b"# Pinned Content").await.unwrap();
+        fs.write("other.md", b"# Other Content").await.unwrap();
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        vault.set_pinned("pinned.md", true).await.unwrap();
+        drop(vault);
+
+        // Simulate external edits while the plugin was off
+        fs.write("pinned.md", b"# Edited Externally").await.unwrap();
+        fs.write("other.md", b"# Other Edited").await.unwrap();
+
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        assert!(vault.is_pinned("pinned.md"));
+        assert!(!vault.is_pinned("other.md"));
+
+        // Pinned file is reverted to the CRDT content, on disk and in the doc
+        let on_disk = String::from_utf8(fs.read("pinned.md").await.unwrap()).unwrap();
+        assert_eq!(on_disk, "# Pinned Content");
+        let doc = vault.get_document("pinned.md").await.unwrap();
+        assert_eq!(doc.to_markdown(), "# Pinned Content");
+        // The restore write must not be re-broadcast as a local change
+        assert!(vault.consume_sync_flag("pinned.md"));
+
+        // Unpinned file is still re-indexed from markdown
+        let doc = vault.get_document("other.md").await.unwrap();
+        assert_eq!(doc.to_markdown(), "# Other Edited");
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_report_lists_restored_files() {
+        let fs = InMemoryFs::new();
+        fs.write("note.md", b"# Original").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+        vault.set_pinned("note.md", true).await.unwrap();
+
+        vault.fs.write("note.md", b"# Changed").await.unwrap();
+        let report = vault.reconcile().await.unwrap();
+
+        assert_eq!(report.restored, vec!["note.md".to_string()]);
+        assert!(report.reindexed.is_empty());
+        assert!(report.has_changes());
+        let content = vault.fs.read("note.md").await.unwrap();
+        assert_eq!(content, b"# Original");
+    }
+
+    #[tokio::test]
+    async fn test_unpin_resumes_reindexing() {
+        let fs = InMemoryFs::new();
+        fs.write("note.md", b"# Original").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+
+        vault.set_pinned("note.md", true).await.unwrap();
+        vault.set_pinned("note.md", false).await.unwrap();
+        assert!(!vault.is_pinned("note.md"));
+
+        vault.fs.write("note.md", b"# Changed").await.unwrap();
+        let report = vault.reconcile().await.unwrap();
+
+        assert_eq!(report.reindexed, vec!["note.md".to_string()]);
+        assert!(report.restored.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_pinned_unregistered_file_fails() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        let result = vault.set_pinned("missing.md", true).await;
+        assert!(matches!(result, Err(VaultError::SourceNotFound(_))));
+        assert!(!vault.is_pinned("missing.md"));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_detects_deleted_files() {
+        use std::sync::Arc;
+        
+        let fs = Arc::new(InMemoryFs::new());
+        
+        // Initialize vault with two files
+        fs.write("keep.md", b"# Keep this").await.unwrap();
+        fs.write("delete.md", b"# Delete this").await.unwrap();
+        let _vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        
+        // Simulate deleting a file while plugin was off
+        fs.delete("delete.md").await.unwrap();
+        
+        // Load vault - should detect orphaned .loro file
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        
+        // list_files should not include the deleted file
+        let files = vault.list_files().await.unwrap();
+        assert!(!files.contains(&"delete.md".to_string()));
+        assert!(files.contains(&"keep.md".to_string()));
+    }
+    
+    #[tokio::test]
+    async fn test_reconcile_detects_file_move() {
+        use std::sync::Arc;
+        
+        let fs = Arc::new(InMemoryFs::new());
+        
+        // Initialize vault with a file
+        fs.write("old_name.md", b"# Unique Content ABC123").await.unwrap();
         let _vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
         
         // Simulate renaming the file while plugin was off
@@ -1867,6 +3129,51 @@ mod tests {
         assert!(vault.is_file_deleted("note.md"));
     }
 
+    #[tokio::test]
+    async fn test_deleted_note_back_without_our_edits_edits_under_new_peer() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        vault.fs.write("note.md", b"# Hello").await.unwrap();
+        vault.on_file_changed("note.md").await.unwrap();
+        let stale = vault.get_document("note.md").await.unwrap().export_snapshot();
+        vault.fs.write("note.md", b"# Hello there").await.unwrap();
+        vault.on_file_changed("note.md").await.unwrap();
+        vault.fs.delete("note.md").await.unwrap();
+        vault.delete_file("note.md").await.unwrap();
+
+        // A peer brings the note back as it was before our last edit
+        let doc = NoteDocument::from_bytes("note.md", &stale, test_peer_id()).unwrap();
+        let ours = test_peer_id().as_u64();
+        let before = doc.version().get(&ours).copied();
+        vault.protect_deleted_edits(&doc);
+        doc.update_body("Edited").unwrap();
+
+        // Our peer ID would reuse the op IDs of the deleted edit
+        assert_eq!(doc.version().get(&ours).copied(), before);
+        assert_eq!(doc.body().to_string(), "Edited");
+    }
+
+    #[tokio::test]
+    async fn test_recreated_identical_file_is_a_new_note() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        vault.fs.write("note.md", b"").await.unwrap();
+        vault.on_file_changed("note.md").await.unwrap();
+        let deleted_id = vault.get_document("note.md").await.unwrap().doc_id();
+
+        vault.fs.delete("note.md").await.unwrap();
+        vault.delete_file("note.md").await.unwrap();
+        assert!(!vault.fs.exists(&vault.document_sync_path("note.md")).await.unwrap());
+
+        // Same (empty) content at another path: not the deleted note moving
+        vault.fs.write("other.md", b"").await.unwrap();
+        let report = vault.reconcile().await.unwrap();
+        assert!(report.moved.is_empty());
+        assert!(report.orphaned.is_empty());
+        assert_eq!(report.indexed, vec!["other.md".to_string()]);
+
+        let doc = vault.get_document("other.md").await.unwrap();
+        assert_ne!(doc.doc_id(), deleted_id);
+    }
+
     #[tokio::test]
     async fn test_rename_file_updates_tree() {
         let fs = InMemoryFs::new();
@@ -1889,6 +3196,349 @@ mod tests {
         // The important thing is new.md works
     }
 
+    /// Filesystem that pauses the first write to `pause_on` until resumed, so a
+    /// test can change the tree while an operation is mid-flight.
+    struct PausingFs {
+        inner: InMemoryFs,
+        pause_on: &'static str,
+        armed: AtomicBool,
+        paused: tokio::sync::Notify,
+        resume: tokio::sync::Notify,
+    }
+
+    impl PausingFs {
+        fn new(inner: InMemoryFs, pause_on: &'static str) -> Self {
+            Self {
+                inner,
+                pause_on,
+                armed: AtomicBool::new(true),
+                paused: tokio::sync::Notify::new(),
+                resume: tokio::sync::Notify::new(),
+            }
+        }
+
+        /// Run `change` while the paused write waits.
+        async fn while_paused<T>(&self, change: impl FnOnce() -> T) -> T {
+            self.paused.notified().await;
+            let result = change();
+            self.resume.notify_one();
+            result
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl FileSystem for PausingFs {
+        async fn read(&self, path: &str) -> crate::fs::Result<Vec<u8>> {
+            self.inner.read(path).await
+        }
+        async fn write(&self, path: &str, content: &[u8]) -> crate::fs::Result<()> {
+            if path == self.pause_on && self.armed.swap(false, Ordering::SeqCst) {
+                self.paused.notify_one();
+                self.resume.notified().await;
+            }
+            self.inner.write(path, content).await
+        }
+        async fn list(&self, path: &str) -> crate::fs::Result<Vec<crate::fs::FileEntry>> {
+            self.inner.list(path).await
+        }
+        async fn delete(&self, path: &str) -> crate::fs::Result<()> {
+            self.inner.delete(path).await
+        }
+        async fn exists(&self, path: &str) -> crate::fs::Result<bool> {
+            self.inner.exists(path).await
+        }
+        async fn stat(&self, path: &str) -> crate::fs::Result<crate::fs::FileStat> {
+            self.inner.stat(path).await
+        }
+        async fn mkdir(&self, path: &str) -> crate::fs::Result<()> {
+            self.inner.mkdir(path).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_fallback_moves_source_registered_mid_rename() {
+        let vault = Vault::init(PausingFs::new(InMemoryFs::new(), "new.md"), test_peer_id())
+            .await
+            .unwrap();
+        // On disk but not yet in the tree
+        vault.fs.write("old.md", b"# Note").await.unwrap();
+
+        // Sync registers the source while the fallback is moving the file
+        let sync = vault
+            .fs
+            .while_paused(|| vault.register_file("old.md").unwrap());
+        let (renamed, appeared) = tokio::join!(vault.rename_file("old.md", "new.md"), sync);
+        renamed.unwrap();
+
+        assert_eq!(vault.registered_files(), vec!["new.md"]);
+        assert_eq!(vault.find_node_by_path("new.md"), Some(appeared));
+        assert!(!vault.fs.exists("old.md").await.unwrap());
+        assert_eq!(vault.fs.read("new.md").await.unwrap(), b"# Note");
+    }
+
+    #[tokio::test]
+    async fn test_rename_fallback_consolidates_both_paths_registered_mid_rename() {
+        let vault = Vault::init(PausingFs::new(InMemoryFs::new(), "new.md"), test_peer_id())
+            .await
+            .unwrap();
+        vault.fs.write("old.md", b"# Note").await.unwrap();
+
+        // Sync registers both paths while the fallback is moving the file
+        let sync = vault.fs.while_paused(|| {
+            let old_node = vault.register_file("old.md").unwrap();
+            (old_node, vault.register_file("new.md").unwrap())
+        });
+        let (renamed, (old_node, new_node)) =
+            tokio::join!(vault.rename_file("old.md", "new.md"), sync);
+        renamed.unwrap();
+
+        assert_eq!(vault.registered_files(), vec!["new.md"]);
+        assert_eq!(vault.find_node_by_path("new.md"), Some(new_node));
+        assert!(vault.file_tree().is_node_deleted(&old_node).unwrap());
+
+        // The tree and the path cache agree
+        vault.rebuild_path_cache();
+        assert_eq!(vault.registered_files(), vec!["new.md"]);
+    }
+
+    /// Names of the live folder nodes in the tree, sorted.
+    fn folder_names<F: FileSystem>(vault: &Vault<F>) -> Vec<String> {
+        let tree = vault.file_tree();
+        let mut names: Vec<String> = tree
+            .nodes()
+            .into_iter()
+            .filter(|node| !tree.is_node_deleted(node).unwrap_or(true))
+            .filter_map(|node| {
+                let meta = tree.get_meta(node).ok()?;
+                let value = |key: &str| match meta.get(key)? {
+                    loro::ValueOrContainer::Value(val) => val.as_string().map(|s| s.to_string()),
+                    _ => None,
+                };
+                if value("type")? != "folder" {
+                    return None;
+                }
+                value("name")
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_deleting_last_file_prunes_empty_folders() {
+        let fs = InMemoryFs::new();
+        fs.write("a/b/note.md", b"# Note").await.unwrap();
+        fs.write("keep/other.md", b"# Other").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+        assert_eq!(folder_names(&vault), vec!["a", "b", "keep"]);
+
+        vault.delete_file("a/b/note.md").await.unwrap();
+
+        assert_eq!(folder_names(&vault), vec!["keep"]);
+        assert_eq!(vault.registered_files(), vec!["keep/other.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_pruning_keeps_folders_with_other_children() {
+        let fs = InMemoryFs::new();
+        fs.write("a/b/one.md", b"# One").await.unwrap();
+        fs.write("a/two.md", b"# Two").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+
+        vault.delete_file("a/b/one.md").await.unwrap();
+        assert_eq!(folder_names(&vault), vec!["a"]);
+
+        // Moving the last file out prunes the folder too
+        vault.rename_file("a/two.md", "c/two.md").await.unwrap();
+        assert_eq!(folder_names(&vault), vec!["c"]);
+        assert_eq!(vault.registered_files(), vec!["c/two.md"]);
+
+        // A file can be created in a pruned folder again
+        vault.register_file("a/b/three.md").unwrap();
+        assert_eq!(folder_names(&vault), vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_pruned_folders_sync_to_peers() {
+        use std::sync::Arc;
+
+        let fs1 = Arc::new(InMemoryFs::new());
+        fs1.write("a/b/note.md", b"# Note").await.unwrap();
+        fs1.write("keep.md", b"# Keep").await.unwrap();
+        let vault1 = Vault::init(Arc::clone(&fs1), test_peer_id()).await.unwrap();
+        let fs2 = Arc::new(InMemoryFs::new());
+        let vault2 = Vault::init(Arc::clone(&fs2), test_peer_id_2()).await.unwrap();
+        vault2.merge_with(&vault1).await.unwrap();
+        assert_eq!(folder_names(&vault2), vec!["a", "b"]);
+
+        vault1.delete_file("a/b/note.md").await.unwrap();
+        vault2.merge_with(&vault1).await.unwrap();
+
+        assert!(folder_names(&vault2).is_empty());
+        assert_eq!(vault2.registered_files(), vec!["keep.md"]);
+        assert!(fs2.exists("keep.md").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_file_added_to_concurrently_pruned_folder_is_kept() {
+        use std::sync::Arc;
+
+        let fs1 = Arc::new(InMemoryFs::new());
+        fs1.write("a/b/note.md", b"# Note").await.unwrap();
+        let vault1 = Vault::init(Arc::clone(&fs1), test_peer_id()).await.unwrap();
+        let fs2 = Arc::new(InMemoryFs::new());
+        let vault2 = Vault::init(Arc::clone(&fs2), test_peer_id_2()).await.unwrap();
+        vault2.merge_with(&vault1).await.unwrap();
+
+        // Peer 1 empties the folder while peer 2 adds a note to it
+        vault1.delete_file("a/b/note.md").await.unwrap();
+        fs2.write("a/b/new.md", b"# New").await.unwrap();
+        vault2.on_file_changed("a/b/new.md").await.unwrap();
+
+        vault2.merge_with(&vault1).await.unwrap();
+        vault2.merge_with(&vault1).await.unwrap();
+
+        assert_eq!(vault2.registered_files(), vec!["a/b/new.md"]);
+        assert_eq!(vault1.registered_files(), vec!["a/b/new.md"]);
+        assert_eq!(fs2.read("a/b/new.md").await.unwrap(), b"# New");
+    }
+
+    #[tokio::test]
+    async fn test_registered_files_tracks_tree() {
+        let fs = InMemoryFs::new();
+        fs.write("b.md", b"# B").await.unwrap();
+        fs.write("a.md", b"# A").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+        assert_eq!(vault.registered_files(), vec!["a.md", "b.md"]);
+
+        vault.fs.write("c.md", b"# B").await.unwrap();
+        vault.rename_file("b.md", "c.md").await.unwrap();
+        vault.delete_file("a.md").await.unwrap();
+        assert_eq!(vault.registered_files(), vec!["c.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_expire_pending_handshakes() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        vault.peer_connecting("conn-1".into(), "addr".into(), ConnectionDirection::Incoming);
+        vault.peer_connecting("conn-2".into(), "addr".into(), ConnectionDirection::Incoming);
+        vault.peer_handshake_complete("conn-2", "peer-2".into()).unwrap();
+
+        // Generous timeout: nothing has waited that long yet
+        assert!(vault.expire_pending_handshakes(60_000.0).is_empty());
+
+        // Zero timeout: any connection still awaiting a handshake is dropped
+        assert_eq!(vault.expire_pending_handshakes(0.0), vec!["conn-1".to_string()]);
+        assert!(vault.get_peer_by_connection_id("conn-1").is_none());
+        let known: Vec<String> = vault.get_known_peers().into_iter().map(|p| p.id).collect();
+        assert_eq!(known, vec!["peer-2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_relayed_routes_follow_neighbor() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        vault
+            .peer_connected("relay".into(), "addr".into(), ConnectionDirection::Outgoing)
+            .unwrap();
+
+        // A message authored by "author" arrives through "relay"
+        vault.record_route("author", "relay");
+        assert_eq!(vault.route_to("author").as_deref(), Some("relay"));
+
+        // Losing the relay loses the route
+        vault.peer_disconnected("relay", DisconnectReason::RemoteClosed);
+        assert!(vault.route_to("author").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_markdown_tree() {
+        let fs = InMemoryFs::new();
+        fs.write("plain.md", b"# Plain\n\nJust a body.").await.unwrap();
+        fs.write("notes/tagged.md", b"---\ntags:\n- a\n- b\n---\n# Heading\n\nBody text.")
+            .await
+            .unwrap();
+        fs.write("notes/doomed.md", b"# Doomed").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+        vault.delete_file("notes/doomed.md").await.unwrap();
+
+        let exported = vault.export_markdown_tree().await.unwrap();
+
+        let expected: HashMap<String, String> = [
+            ("plain.md", "# Plain\n\nJust a body."),
+            ("notes/tagged.md", "---\ntags:\n- a\n- b\n---\n\n# Heading\n\nBody text."),
+        ]
+        .into_iter()
+        .map(|(path, markdown)| (path.to_string(), markdown.to_string()))
+        .collect();
+        assert_eq!(exported, expected);
+    }
+
+    #[tokio::test]
+    async fn test_export_reflects_crdt_not_disk() {
+        let fs = InMemoryFs::new();
+        fs.write("note.md", b"# Original").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+
+        // An external edit that hasn't been picked up yet isn't exported
+        vault.fs.write("note.md", b"# Unsynced edit").await.unwrap();
+        let exported = vault.export_markdown_tree().await.unwrap();
+        assert_eq!(exported["note.md"], "# Original");
+        assert!(exported.keys().all(|path| !path.starts_with(SYNC_DIR)));
+    }
+
+    #[tokio::test]
+    async fn test_import_markdown_tree_diff_merges() {
+        let fs = InMemoryFs::new();
+        fs.write("same.md", b"# Same").await.unwrap();
+        fs.write("edited.md", b"# Edited\n\nFirst line.").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+        let before = vault.get_document_info("edited.md").await.unwrap().unwrap();
+
+        let backup = InMemoryFs::new();
+        backup.write("same.md", b"# Same").await.unwrap();
+        backup
+            .write("edited.md", b"# Edited\n\nFirst line.\nSecond line.")
+            .await
+            .unwrap();
+        backup.write("new/fresh.md", b"# Fresh").await.unwrap();
+
+        let report = vault.import_markdown_tree(&backup).await.unwrap();
+        assert_eq!(report.created, vec!["new/fresh.md"]);
+        assert_eq!(report.merged, vec!["edited.md"]);
+        assert_eq!(report.unchanged, vec!["same.md"]);
+
+        // Merged into the same document, on top of its existing history
+        let after = vault.get_document_info("edited.md").await.unwrap().unwrap();
+        assert_eq!(after.doc_id, before.doc_id);
+        assert!(after.op_count > before.op_count);
+        let doc = vault.get_document("edited.md").await.unwrap();
+        assert_eq!(doc.to_markdown(), "# Edited\n\nFirst line.\nSecond line.");
+
+        // Written to disk and registered in the tree
+        assert_eq!(vault.fs.read("new/fresh.md").await.unwrap(), b"# Fresh");
+        assert_eq!(vault.registered_files(), vec!["edited.md", "new/fresh.md", "same.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_export_round_trip() {
+        let fs = InMemoryFs::new();
+        fs.write("a.md", b"---\ntitle: A\n---\n\nBody").await.unwrap();
+        let source = Vault::init(fs, test_peer_id()).await.unwrap();
+
+        let backup = InMemoryFs::new();
+        for (path, markdown) in source.export_markdown_tree().await.unwrap() {
+            backup.write(&path, markdown.as_bytes()).await.unwrap();
+        }
+
+        let target = Vault::init(InMemoryFs::new(), test_peer_id_2()).await.unwrap();
+        let report = target.import_markdown_tree(&backup).await.unwrap();
+        assert_eq!(report.created, vec!["a.md"]);
+        assert_eq!(
+            target.export_markdown_tree().await.unwrap(),
+            source.export_markdown_tree().await.unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn test_path_traversal_rejected() {
         let fs = InMemoryFs::new();
@@ -1896,13 +3546,13 @@ mod tests {
 
         // Path traversal should be rejected
         let result = vault.delete_file("../secret.md").await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::Traversal))));
 
         let result = vault.rename_file("note.md", "../secret.md").await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::Traversal))));
 
         let result = vault.register_file("../evil.md");
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::Traversal))));
     }
 
     #[tokio::test]
@@ -1912,10 +3562,10 @@ mod tests {
 
         // Null bytes should be rejected
         let result = vault.delete_file("foo\0.md").await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::NullByte))));
 
         let result = vault.register_file("bar\0.md");
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::NullByte))));
     }
 
     #[tokio::test]
@@ -1925,10 +3575,10 @@ mod tests {
 
         // Non-markdown files should be rejected
         let result = vault.register_file("script.js");
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::NotMarkdown))));
 
         let result = vault.delete_file("image.png").await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::NotMarkdown))));
     }
 
     #[tokio::test]
@@ -1938,10 +3588,10 @@ mod tests {
 
         // Empty path should be rejected
         let result = vault.register_file("");
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::Empty))));
 
         let result = vault.delete_file("").await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::Empty))));
     }
 
     #[tokio::test]
@@ -1951,10 +3601,10 @@ mod tests {
 
         // Empty path segments (a//b.md) should be rejected
         let result = vault.register_file("a//b.md");
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::EmptySegment))));
 
         let result = vault.delete_file("foo//bar.md").await;
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::EmptySegment))));
     }
 
     #[tokio::test]
@@ -1965,7 +3615,45 @@ mod tests {
         // Path over 1024 chars should be rejected
         let long_path = format!("{}.md", "a".repeat(1025));
         let result = vault.register_file(&long_path);
-        assert!(result.is_err());
+        assert!(matches!(result, Err(VaultError::PathValidation(PathValidationError::TooLong))));
+    }
+
+    #[test]
+    fn test_validate_sync_path_variants() {
+        use PathValidationError::*;
+
+        let cases = [
+            ("/etc/notes.md", Absolute),
+            ("C:notes.md", WindowsAbsolute),
+            ("notes\\a.md", Backslash),
+            ("notes\ta.md", ControlCharacter),
+        ];
+        for (path, expected) in cases {
+            match Vault::<InMemoryFs>::validate_sync_path(path) {
+                Err(VaultError::PathValidation(actual)) => assert_eq!(actual, expected, "{:?}", path),
+                other => panic!("expected {:?} for {:?}, got {:?}", expected, path, other),
+            }
+        }
+        assert!(Vault::<InMemoryFs>::validate_sync_path("notes/a.md").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rename_missing_source_returns_source_not_found() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+
+        let result = vault.rename_file("missing.md", "new.md").await;
+        assert!(matches!(result, Err(VaultError::SourceNotFound(ref p)) if p == "missing.md"));
+    }
+
+    #[tokio::test]
+    async fn test_rename_onto_existing_returns_target_exists() {
+        let fs = InMemoryFs::new();
+        fs.write("a.md", b"# A").await.unwrap();
+        fs.write("b.md", b"# B").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+
+        let result = vault.rename_file("a.md", "b.md").await;
+        assert!(matches!(result, Err(VaultError::TargetExists(ref p)) if p == "b.md"));
     }
 
     #[tokio::test]
@@ -2200,6 +3888,365 @@ mod tests {
         assert!(tracker.is_synced("c.md"));
     }
 
+    /// Age a flag by `age` without waiting.
+    fn backdate_flag(tracker: &SyncState, path: &str, age: Duration) {
+        let marked_at = Instant::now()
+            .checked_sub(age)
+            .expect("monotonic clock should be past the flag age");
+        tracker.synced_paths.lock().unwrap().insert(path.to_string(), marked_at);
+    }
+
+    /// Age a flag past DEFAULT_FLAG_TTL without waiting.
+    fn expire_flag(tracker: &SyncState, path: &str) {
+        backdate_flag(tracker, path, DEFAULT_FLAG_TTL + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_sync_state_longer_ttl_outlives_default() {
+        let tracker = SyncState::with_ttl(DEFAULT_FLAG_TTL * 2);
+        tracker.mark_synced("slow.md");
+        backdate_flag(&tracker, "slow.md", DEFAULT_FLAG_TTL + Duration::from_secs(5));
+
+        // Past the default window, but still valid under the longer TTL
+        assert!(tracker.is_synced("slow.md"));
+        tracker.cleanup_expired();
+        assert!(tracker.consume_synced("slow.md"));
+        assert_eq!(tracker.stats().consumed_valid, 1);
+
+        // The same age expires under the default TTL
+        let default = SyncState::new();
+        default.mark_synced("slow.md");
+        backdate_flag(&default, "slow.md", DEFAULT_FLAG_TTL + Duration::from_secs(5));
+        assert!(!default.consume_synced("slow.md"));
+    }
+
+    #[test]
+    fn test_sync_state_flag_expires_exactly_at_ttl() {
+        let clock = Arc::new(TestClock::new());
+        let tracker = SyncState::with_clock(clock.clone());
+        tracker.mark_synced("a.md");
+        tracker.mark_synced("b.md");
+
+        clock.advance(DEFAULT_FLAG_TTL - Duration::from_millis(1));
+        assert!(tracker.is_synced("a.md"));
+        assert!(tracker.consume_synced("a.md"));
+
+        clock.advance(Duration::from_millis(1));
+        assert!(!tracker.is_synced("b.md"));
+        assert!(!tracker.consume_synced("b.md"));
+        assert_eq!(tracker.stats().consumed_valid, 1);
+        assert_eq!(tracker.stats().consumed_expired, 1);
+    }
+
+    #[tokio::test]
+    async fn test_vault_with_clock() {
+        let clock = Arc::new(TestClock::new());
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id())
+            .await
+            .unwrap()
+            .with_clock(clock.clone());
+
+        vault.mark_synced("note.md");
+        let direction = ConnectionDirection::Incoming;
+        vault.peer_connecting("conn-1".into(), "ws://a".into(), direction);
+        clock.advance(Duration::from_secs(10));
+
+        // Handshake timeouts and peer timestamps follow the clock
+        assert!(vault.expire_pending_handshakes(10_001.0).is_empty());
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(vault.expire_pending_handshakes(10_001.0), vec!["conn-1"]);
+
+        clock.advance(DEFAULT_FLAG_TTL);
+        assert!(!vault.consume_sync_flag("note.md"));
+    }
+
+    #[tokio::test]
+    async fn test_vault_with_flag_ttl() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id())
+            .await
+            .unwrap()
+            .with_flag_ttl(Duration::from_secs(90));
+        assert_eq!(vault.sync_state.ttl(), Duration::from_secs(90));
+
+        vault.mark_synced("note.md");
+        backdate_flag(&vault.sync_state, "note.md", Duration::from_secs(60));
+        assert!(vault.consume_sync_flag("note.md"));
+    }
+
+    #[tokio::test]
+    async fn test_flush_persists_uncommitted_document() {
+        let fs = InMemoryFs::new();
+        fs.write("note.md", b"# Note\n\nBefore").await.unwrap();
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+        vault.on_file_changed("note.md").await.unwrap();
+
+        // Edit in memory without committing or writing
+        vault.documents()["note.md"].update_body("After").unwrap();
+        assert_eq!(vault.flush().await.unwrap(), 1);
+
+        let sync_path = vault.document_sync_path("note.md");
+        let bytes = vault.fs.read(&sync_path).await.unwrap();
+        let doc = NoteDocument::from_bytes("note.md", &bytes, test_peer_id()).unwrap();
+        assert_eq!(doc.body().to_string(), "After");
+    }
+
+    #[tokio::test]
+    async fn test_flush_persists_batched_edits() {
+        let fs = InMemoryFs::new();
+        for name in ["a.md", "b.md", "c.md"] {
+            fs.write(name, b"original").await.unwrap();
+        }
+        let vault = Vault::init(fs, test_peer_id()).await.unwrap();
+        for name in ["a.md", "b.md", "c.md"] {
+            vault.on_file_changed(name).await.unwrap();
+        }
+        assert!(vault.documents().values().all(|doc| !doc.is_dirty()));
+
+        // A batch of in-memory edits to two documents, none written yet
+        {
+            let documents = vault.documents();
+            documents["a.md"].update_body("edited a").unwrap();
+            documents["a.md"].commit();
+            documents["b.md"].update_body("edited b").unwrap();
+            assert!(documents["a.md"].is_dirty());
+            assert!(!documents["c.md"].is_dirty());
+        }
+
+        assert_eq!(vault.flush().await.unwrap(), 2);
+        assert!(vault.documents().values().all(|doc| !doc.is_dirty()));
+        for (name, body) in [("a.md", "edited a"), ("b.md", "edited b"), ("c.md", "original")] {
+            let bytes = vault.fs.read(&vault.document_sync_path(name)).await.unwrap();
+            let doc = NoteDocument::from_bytes(name, &bytes, test_peer_id()).unwrap();
+            assert_eq!(doc.body().to_string(), body);
+        }
+
+        // Nothing left to write
+        assert_eq!(vault.flush().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_excluded_paths_are_not_tracked() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        vault
+            .set_sync_scope(SyncScope {
+                include: vec!["notes/".to_string()],
+                exclude: Vec::new(),
+            })
+            .await
+            .unwrap();
+
+        assert!(vault.is_path_synced("notes/a.md"));
+        assert!(!vault.is_path_synced("private/b.md"));
+        assert!(!vault.is_path_synced("notes/image.png"));
+        assert!(!vault.is_path_synced(".sync/registry.loro"));
+
+        vault.fs.write("notes/a.md", b"# A").await.unwrap();
+        vault.fs.write("private/b.md", b"# B").await.unwrap();
+        vault.on_file_changed("notes/a.md").await.unwrap();
+        vault.on_file_changed("private/b.md").await.unwrap();
+        assert!(vault.documents().contains_key("notes/a.md"));
+        assert!(!vault.documents().contains_key("private/b.md"));
+        assert_eq!(vault.list_files().await.unwrap(), vec!["notes/a.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_files_skips_hidden_directories() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        for path in [
+            "root.md",
+            "notes/a.md",
+            "notes/.trash/old.md",
+            ".git/notes.md",
+            ".obsidian/plugins/readme.md",
+            ".hidden.md",
+        ] {
+            vault.fs.write(path, b"# Note").await.unwrap();
+        }
+
+        let mut files = vault.list_files().await.unwrap();
+        files.sort();
+        assert_eq!(files, vec!["notes/a.md", "root.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_sync_scope_persists_across_loads() {
+        let fs = Arc::new(InMemoryFs::new());
+        let vault = Vault::init(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        let scope = SyncScope {
+            include: Vec::new(),
+            exclude: vec!["private/".to_string()],
+        };
+        vault.set_sync_scope(scope.clone()).await.unwrap();
+        drop(vault);
+
+        fs.write("private/b.md", b"# B").await.unwrap();
+        let vault = Vault::load(Arc::clone(&fs), test_peer_id()).await.unwrap();
+        assert_eq!(vault.sync_scope(), scope);
+        assert!(!vault.registered_files().contains(&"private/b.md".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_out_of_scope_changes_are_not_applied() {
+        let sender = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        for path in ["notes/a.md", "private/b.md", "private/c.md"] {
+            sender.fs.write(path, b"# Shared").await.unwrap();
+            sender.on_file_changed(path).await.unwrap();
+        }
+
+        let receiver = Vault::init(InMemoryFs::new(), test_peer_id_2()).await.unwrap();
+        let scope = SyncScope {
+            include: vec!["notes/".to_string()],
+            exclude: Vec::new(),
+        };
+        receiver.set_sync_scope(scope).await.unwrap();
+        receiver.fs.write("private/b.md", b"# Mine").await.unwrap();
+
+        // Remote updates outside the scope don't land here
+        for path in ["notes/a.md", "private/c.md"] {
+            let update = sender.prepare_document_update(path).await.unwrap().unwrap();
+            receiver.process_sync_message(&update).await.unwrap();
+        }
+        assert!(receiver.fs.exists("notes/a.md").await.unwrap());
+        assert!(!receiver.fs.exists("private/c.md").await.unwrap());
+
+        // Nor do deletions or moves of our own unsynced copy
+        let delete = sender.prepare_file_deleted("private/b.md").unwrap();
+        receiver.process_sync_message(&delete).await.unwrap();
+        let rename = sender.prepare_file_renamed("private/b.md", "private/d.md").unwrap();
+        receiver.process_sync_message(&rename).await.unwrap();
+        assert_eq!(receiver.fs.read("private/b.md").await.unwrap(), b"# Mine");
+        assert!(receiver.registered_files().iter().all(|path| !path.starts_with("private/")));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_note_is_rejected() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        vault.set_max_note_size(16);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        let _subscription = vault.subscribe(move |event| {
+            if let SyncEvent::NoteTooLarge { path, size, .. } = event {
+                seen.lock().unwrap().push((path, size));
+            }
+        });
+
+        vault.fs.write("small.md", b"under the limit").await.unwrap();
+        vault.on_file_changed("small.md").await.unwrap();
+        assert!(vault.documents().contains_key("small.md"));
+
+        vault.fs.write("big.md", b"well over the sixteen byte limit").await.unwrap();
+        let result = vault.on_file_changed("big.md").await;
+        assert!(matches!(
+            result,
+            Err(VaultError::NoteTooLarge { ref path, size: 32, limit: 16 }) if path == "big.md"
+        ));
+        assert!(!vault.documents().contains_key("big.md"));
+        assert!(!vault.registered_files().contains(&"big.md".to_string()));
+
+        // Growing a tracked note past the limit leaves its document untouched
+        vault.fs.write("small.md", b"grown past the sixteen byte limit").await.unwrap();
+        assert!(vault.on_file_changed("small.md").await.is_err());
+        let body = vault.documents().get("small.md").unwrap().body().to_string();
+        assert_eq!(body, "under the limit");
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![("big.md".to_string(), 32), ("small.md".to_string(), 33)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_skips_oversized_notes() {
+        let vault = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        vault.fs.write("small.md", b"under the limit").await.unwrap();
+        vault.on_file_changed("small.md").await.unwrap();
+        vault.set_max_note_size(16);
+
+        // A new oversized note and an external edit growing a tracked one
+        vault.fs.write("big.md", b"well over the sixteen byte limit").await.unwrap();
+        vault.fs.write("small.md", b"grown past the sixteen byte limit").await.unwrap();
+        vault.fs.write("other.md", b"fits").await.unwrap();
+
+        let report = vault.reconcile().await.unwrap();
+        let mut too_large = report.too_large.clone();
+        too_large.sort();
+        assert_eq!(too_large, vec!["big.md", "small.md"]);
+        assert_eq!(report.indexed, vec!["other.md"]);
+        assert!(report.reindexed.is_empty());
+        assert!(!vault.documents().contains_key("big.md"));
+        let body = vault.documents().get("small.md").unwrap().body().to_string();
+        assert_eq!(body, "under the limit");
+
+        // Still oversized next time rather than skipped as unchanged
+        let report = vault.reconcile().await.unwrap();
+        assert_eq!(report.too_large.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_remote_update_is_not_applied() {
+        let sender = Vault::init(InMemoryFs::new(), test_peer_id()).await.unwrap();
+        let receiver = Vault::init(InMemoryFs::new(), test_peer_id_2()).await.unwrap();
+        receiver.set_max_note_size(16);
+
+        sender.fs.write("big.md", b"well over the sixteen byte limit").await.unwrap();
+        sender.on_file_changed("big.md").await.unwrap();
+        let update = sender.prepare_document_update("big.md").await.unwrap().unwrap();
+
+        let (_, modified) = receiver.process_sync_message(&update).await.unwrap();
+        assert!(modified.is_empty());
+        assert!(!receiver.fs.exists("big.md").await.unwrap());
+    }
+
+    #[test]
+    fn test_sync_state_stats_count_valid_consumes() {
+        let tracker = SyncState::new();
+        tracker.mark_synced("a.md");
+        tracker.mark_synced("b.md");
+        assert!(tracker.consume_synced("a.md"));
+        assert!(!tracker.consume_synced("never-marked.md"));
+
+        assert_eq!(
+            tracker.stats(),
+            SyncFlagStats {
+                flags_set: 2,
+                consumed_valid: 1,
+                consumed_expired: 0,
+                expired_unconsumed: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_sync_state_stats_count_expired_consume() {
+        let tracker = SyncState::new();
+        tracker.mark_synced("a.md");
+        expire_flag(&tracker, "a.md");
+
+        assert!(!tracker.consume_synced("a.md"));
+        let stats = tracker.stats();
+        assert_eq!(stats.consumed_expired, 1);
+        assert_eq!(stats.consumed_valid, 0);
+        assert_eq!(stats.expired_unconsumed, 0);
+    }
+
+    #[test]
+    fn test_sync_state_stats_count_expired_unconsumed() {
+        let tracker = SyncState::new();
+        tracker.mark_synced("a.md");
+        tracker.mark_synced("b.md");
+        expire_flag(&tracker, "a.md");
+        expire_flag(&tracker, "b.md");
+
+        // Cleanup drops one, re-marking replaces the other
+        tracker.mark_synced("b.md");
+        tracker.cleanup_expired();
+
+        let stats = tracker.stats();
+        assert_eq!(stats.flags_set, 3);
+        assert_eq!(stats.expired_unconsumed, 2);
+        assert!(tracker.is_synced("b.md"));
+    }
+
     #[test]
     fn test_sync_state_rename_marks_both_paths() {
         // This tests the behavior expected when a rename sync is processed
@@ -0,0 +1,319 @@
+//! Property test: random edits, deletes, and renames across peers converge.
+//!
+//! Each case seeds a shared set of notes, then applies a random sequence of
+//! local operations on random vaults. Every local operation is broadcast as a
+//! real-time `SyncMessage` over `ChannelTransport` links, and `Deliver` steps
+//! pick which link's next message is processed, so delivery is interleaved
+//! arbitrarily. Once the sequence ends, all queues are drained and every pair
+//! runs a full SyncRequest → SyncExchange → SyncResponse round-trip. All
+//! vaults must then agree on the live file set, registry version, and the
+//! `to_markdown` output of every note.
+
+use proptest::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use sync_core::transport::ChannelNetwork;
+use sync_core::{FileSystem, InMemoryFs, PeerConnection, PeerId, SyncTransport, Vault};
+
+type TestVault = Vault<Arc<InMemoryFs>>;
+
+const SEED_FILES: [&str; 3] = ["alpha.md", "notes/beta.md", "notes/gamma.md"];
+
+/// A single step in a generated scenario.
+///
+/// Vault, file, and link choices are plain indices taken modulo the number
+/// available, so proptest can shrink them towards zero independently.
+#[derive(Debug, Clone)]
+enum Op {
+    /// Overwrite a live note on `vault` with `text` and broadcast the update
+    Edit { vault: usize, file: usize, text: String },
+    /// Delete a live note on `vault` and broadcast the deletion
+    Delete { vault: usize, file: usize },
+    /// Rename a live note on `vault` to a fresh path and broadcast the rename
+    Rename { vault: usize, file: usize },
+    /// Deliver the next queued message on `link`, if any
+    Deliver { link: usize },
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (0..4usize, 0..4usize, "[a-z ]{0,12}(\n[a-z ]{0,12}){0,2}")
+            .prop_map(|(vault, file, text)| Op::Edit { vault, file, text }),
+        1 => (0..4usize, 0..4usize).prop_map(|(vault, file)| Op::Delete { vault, file }),
+        1 => (0..4usize, 0..4usize).prop_map(|(vault, file)| Op::Rename { vault, file }),
+        4 => (0..16usize).prop_map(|link| Op::Deliver { link }),
+    ]
+}
+
+/// One direction of a connection between two vaults.
+struct Link {
+    from: usize,
+    to: usize,
+    tx: Box<dyn PeerConnection>,
+    rx: Box<dyn PeerConnection>,
+    /// Messages sent on `tx` but not yet processed by `to`
+    pending: usize,
+}
+
+struct Cluster {
+    fs: Vec<Arc<InMemoryFs>>,
+    vaults: Vec<TestVault>,
+    links: Vec<Link>,
+    renames: usize,
+}
+
+impl Cluster {
+    async fn new(size: usize) -> Self {
+        let mut fs = Vec::new();
+        let mut vaults = Vec::new();
+        for i in 0..size {
+            let vault_fs = Arc::new(InMemoryFs::new());
+            if i == 0 {
+                for path in SEED_FILES {
+                    vault_fs.write(path, format!("# {}", path).as_bytes()).await.unwrap();
+                }
+            }
+            let vault = Vault::init(Arc::clone(&vault_fs), PeerId::from(1000 + i as u64))
+                .await
+                .unwrap();
+            fs.push(vault_fs);
+            vaults.push(vault);
+        }
+
+        let network = ChannelNetwork::new();
+        let transports: Vec<_> = (0..size)
+            .map(|i| network.transport(&format!("vault-{}", i)))
+            .collect();
+
+        let mut links = Vec::new();
+        for from in 0..size {
+            for to in 0..size {
+                if from == to {
+                    continue;
+                }
+                let peer = transports[from]
+                    .discover_peers()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .find(|p| p.id == transports[to].peer_id())
+                    .unwrap();
+                let tx = transports[from].connect(&peer).await.unwrap();
+                let rx = transports[to].accept().await.unwrap();
+                links.push(Link { from, to, tx, rx, pending: 0 });
+            }
+        }
+
+        let mut cluster = Self { fs, vaults, links, renames: 0 };
+        cluster.settle().await;
+        cluster
+    }
+
+    fn link_index(&self, from: usize, to: usize) -> usize {
+        self.links
+            .iter()
+            .position(|l| l.from == from && l.to == to)
+            .unwrap()
+    }
+
+    /// Live notes on a vault according to its file tree, sorted for stable indexing.
+    fn live_files(&self, vault: usize) -> Vec<String> {
+        self.vaults[vault].registered_files()
+    }
+
+    async fn broadcast(&mut self, from: usize, data: &[u8]) {
+        for link in self.links.iter_mut().filter(|l| l.from == from) {
+            link.tx.send(data).await.unwrap();
+            link.pending += 1;
+        }
+    }
+
+    async fn apply(&mut self, op: &Op) {
+        let size = self.vaults.len();
+        match op {
+            Op::Edit { vault, file, text } => {
+                let vault = vault % size;
+                let files = self.live_files(vault);
+                let Some(path) = files.get(file % files.len().max(1)) else {
+                    return;
+                };
+                self.fs[vault].write(path, text.as_bytes()).await.unwrap();
+                let v = &self.vaults[vault];
+                v.on_file_changed(path).await.unwrap();
+                if let Some(data) = v.prepare_document_update(path).await.unwrap() {
+                    self.broadcast(vault, &data).await;
+                }
+            }
+            Op::Delete { vault, file } => {
+                let vault = vault % size;
+                let files = self.live_files(vault);
+                let Some(path) = files.get(file % files.len().max(1)) else {
+                    return;
+                };
+                // A renamed note may only exist in the tree on peers that received the rename
+                let _ = self.fs[vault].delete(path).await;
+                let v = &self.vaults[vault];
+                v.delete_file(path).await.unwrap();
+                let data = v.prepare_file_deleted(path).unwrap();
+                self.broadcast(vault, &data).await;
+            }
+            Op::Rename { vault, file } => {
+                let vault = vault % size;
+                let files = self.live_files(vault);
+                let Some(old_path) = files.get(file % files.len().max(1)).cloned() else {
+                    return;
+                };
+                self.renames += 1;
+                let new_path = format!(
+                    "{}-{}.md",
+                    old_path.trim_end_matches(".md"),
+                    self.renames
+                );
+
+                let v = &self.vaults[vault];
+                let content = v.get_document(&old_path).await.unwrap().to_markdown();
+                let fs = &self.fs[vault];
+                fs.write(&new_path, content.as_bytes()).await.unwrap();
+                let _ = fs.delete(&old_path).await;
+
+                v.rename_file(&old_path, &new_path).await.unwrap();
+                let data = v.prepare_file_renamed(&old_path, &new_path).unwrap();
+                self.broadcast(vault, &data).await;
+            }
+            Op::Deliver { link } => {
+                let link = link % self.links.len();
+                self.deliver(link).await;
+            }
+        }
+    }
+
+    /// Process the next queued message on a link. Returns false if it was empty.
+    async fn deliver(&mut self, link: usize) -> bool {
+        if self.links[link].pending == 0 {
+            return false;
+        }
+        self.links[link].pending -= 1;
+
+        let to = self.links[link].to;
+        let data = self.links[link].rx.recv().await.unwrap();
+        // Like the daemon, tolerate real-time messages that no longer apply
+        // (e.g. a rename of a note this peer already deleted); the full sync
+        // in `settle` is what must reconcile them.
+        if let Ok((reply, _)) = self.vaults[to].process_sync_message(&data).await {
+            assert!(reply.is_none(), "real-time messages never produce a reply");
+        }
+        true
+    }
+
+    /// Full symmetric sync from `a` to `b` over the channel links.
+    async fn full_sync(&mut self, a: usize, b: usize) {
+        let ab = self.link_index(a, b);
+        let ba = self.link_index(b, a);
+
+        let request = self.vaults[a].prepare_sync_request().await.unwrap();
+        self.links[ab].tx.send(&request).await.unwrap();
+        let received = self.links[ab].rx.recv().await.unwrap();
+        let (exchange, _) = self.vaults[b].process_sync_message(&received).await.unwrap();
+
+        self.links[ba].tx.send(&exchange.unwrap()).await.unwrap();
+        let received = self.links[ba].rx.recv().await.unwrap();
+        let (response, _) = self.vaults[a].process_sync_message(&received).await.unwrap();
+
+        if let Some(response) = response {
+            self.links[ab].tx.send(&response).await.unwrap();
+            let received = self.links[ab].rx.recv().await.unwrap();
+            self.vaults[b].process_sync_message(&received).await.unwrap();
+        }
+    }
+
+    /// Drain every queue, then run two rounds of full syncs between every pair.
+    async fn settle(&mut self) {
+        for link in 0..self.links.len() {
+            while self.deliver(link).await {}
+        }
+
+        let size = self.vaults.len();
+        for _ in 0..2 {
+            for a in 0..size {
+                for b in 0..size {
+                    if a != b {
+                        self.full_sync(a, b).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Registry version, live files, and rendered markdown for one vault.
+    async fn snapshot(&self, vault: usize) -> (BTreeMap<String, i32>, BTreeMap<String, String>) {
+        let v = &self.vaults[vault];
+        let registry = v.get_registry_version().into_iter().collect();
+
+        let mut notes = BTreeMap::new();
+        for path in self.live_files(vault) {
+            let doc = v.get_document(&path).await.unwrap();
+            notes.insert(path, doc.to_markdown());
+        }
+
+        (registry, notes)
+    }
+}
+
+fn run_scenario(size: usize, ops: &[Op]) -> Result<(), TestCaseError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut cluster = Cluster::new(size).await;
+        let seeded: BTreeSet<String> = cluster.live_files(0).into_iter().collect();
+        prop_assert_eq!(seeded.len(), SEED_FILES.len());
+
+        for op in ops {
+            cluster.apply(op).await;
+        }
+        cluster.settle().await;
+
+        let expected = cluster.snapshot(0).await;
+        for vault in 1..size {
+            let actual = cluster.snapshot(vault).await;
+            prop_assert_eq!(&actual.0, &expected.0, "registry diverged on vault {}", vault);
+            prop_assert_eq!(&actual.1, &expected.1, "notes diverged on vault {}", vault);
+        }
+        Ok(())
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn vaults_converge(
+        size in 2..=3usize,
+        ops in proptest::collection::vec(op_strategy(), 0..24),
+    ) {
+        run_scenario(size, &ops)?;
+    }
+}
+
+#[test]
+fn concurrent_edits_to_same_note_converge() {
+    let ops = vec![
+        Op::Edit { vault: 0, file: 0, text: "from zero".into() },
+        Op::Edit { vault: 1, file: 0, text: "from one".into() },
+        Op::Deliver { link: 0 },
+        Op::Deliver { link: 1 },
+    ];
+    run_scenario(2, &ops).unwrap();
+}
+
+#[test]
+fn rename_racing_edit_converges() {
+    let ops = vec![
+        Op::Rename { vault: 0, file: 1 },
+        Op::Edit { vault: 1, file: 1, text: "edited".into() },
+        Op::Deliver { link: 1 },
+        Op::Deliver { link: 0 },
+    ];
+    run_scenario(2, &ops).unwrap();
+}
@@ -0,0 +1,133 @@
+//! Offline merge of two vault copies that diverged without syncing.
+//!
+//! Two vaults start from the same synced state, then each applies its own
+//! edits, deletes, renames, and new notes. After `merge_with`, both must agree
+//! on the live file set and the markdown of every note, both in CRDT state and
+//! on disk.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use sync_core::{ConflictResolution, FileSystem, InMemoryFs, PeerId, Vault};
+
+type TestVault = Vault<Arc<InMemoryFs>>;
+
+const SHARED: &str = "# Shared\n\nfirst line\nsecond line\n";
+
+async fn edit(fs: &InMemoryFs, vault: &TestVault, path: &str, content: &str) {
+    fs.write(path, content.as_bytes()).await.unwrap();
+    vault.on_file_changed(path).await.unwrap();
+}
+
+async fn rename(fs: &InMemoryFs, vault: &TestVault, old_path: &str, new_path: &str) {
+    let content = fs.read(old_path).await.unwrap();
+    fs.write(new_path, &content).await.unwrap();
+    fs.delete(old_path).await.unwrap();
+    vault.rename_file(old_path, new_path).await.unwrap();
+}
+
+async fn delete(fs: &InMemoryFs, vault: &TestVault, path: &str) {
+    fs.delete(path).await.unwrap();
+    vault.delete_file(path).await.unwrap();
+}
+
+/// Rendered markdown of every live note, from CRDT state and from disk.
+async fn notes(
+    fs: &InMemoryFs,
+    vault: &TestVault,
+) -> (BTreeMap<String, String>, BTreeMap<String, String>) {
+    let mut crdt = BTreeMap::new();
+    let mut disk = BTreeMap::new();
+    for path in vault.registered_files() {
+        let doc = vault.get_document(&path).await.unwrap();
+        crdt.insert(path.clone(), doc.to_markdown());
+        let bytes = fs.read(&path).await.unwrap();
+        disk.insert(path, String::from_utf8(bytes).unwrap());
+    }
+    (crdt, disk)
+}
+
+#[tokio::test]
+async fn divergent_vaults_converge_after_merge() {
+    let laptop_fs = Arc::new(InMemoryFs::new());
+    laptop_fs.write("shared.md", SHARED.as_bytes()).await.unwrap();
+    laptop_fs.write("notes/two.md", b"# Two").await.unwrap();
+    laptop_fs.write("notes/three.md", b"# Three").await.unwrap();
+    let laptop = Vault::init(Arc::clone(&laptop_fs), PeerId::from(1)).await.unwrap();
+
+    let phone_fs = Arc::new(InMemoryFs::new());
+    let phone = Vault::init(Arc::clone(&phone_fs), PeerId::from(2)).await.unwrap();
+    let initial = phone.merge_with(&laptop).await.unwrap();
+    assert_eq!(initial.added, ["notes/three.md", "notes/two.md", "shared.md"]);
+
+    // Offline on the laptop: edit the first line, move a note, add a note
+    edit(
+        &laptop_fs,
+        &laptop,
+        "shared.md",
+        "# Shared\n\nfirst line (laptop)\nsecond line\n",
+    )
+    .await;
+    rename(&laptop_fs, &laptop, "notes/two.md", "archive/two.md").await;
+    edit(&laptop_fs, &laptop, "laptop.md", "# From laptop").await;
+
+    // Offline on the phone: edit the second line, delete a note, add a note
+    edit(
+        &phone_fs,
+        &phone,
+        "shared.md",
+        "# Shared\n\nfirst line\nsecond line (phone)\n",
+    )
+    .await;
+    delete(&phone_fs, &phone, "notes/three.md").await;
+    edit(&phone_fs, &phone, "phone.md", "# From phone").await;
+
+    let report = phone.merge_with(&laptop).await.unwrap();
+
+    assert_eq!(report.added, ["laptop.md"]);
+    assert_eq!(
+        report.renamed,
+        [("notes/two.md".to_string(), "archive/two.md".to_string())]
+    );
+    assert!(report.removed.is_empty());
+    assert_eq!(report.updated, ["shared.md"]);
+    assert!(report
+        .conflicts
+        .iter()
+        .any(|c| c.path == "shared.md" && c.resolution == ConflictResolution::Merged));
+
+    let expected_files = ["archive/two.md", "laptop.md", "phone.md", "shared.md"];
+    assert_eq!(phone.registered_files(), expected_files);
+    assert_eq!(laptop.registered_files(), expected_files);
+
+    let (phone_crdt, phone_disk) = notes(&phone_fs, &phone).await;
+    let (laptop_crdt, laptop_disk) = notes(&laptop_fs, &laptop).await;
+    assert_eq!(phone_crdt, laptop_crdt);
+    assert_eq!(phone_disk, laptop_disk);
+    assert_eq!(phone_crdt, phone_disk);
+
+    // Both concurrent line edits survive the merge
+    let shared = &phone_crdt["shared.md"];
+    assert!(shared.contains("first line (laptop)"), "{}", shared);
+    assert!(shared.contains("second line (phone)"), "{}", shared);
+
+    assert!(!laptop_fs.exists("notes/three.md").await.unwrap());
+    assert!(!phone_fs.exists("notes/two.md").await.unwrap());
+}
+
+#[tokio::test]
+async fn merge_is_idempotent() {
+    let laptop_fs = Arc::new(InMemoryFs::new());
+    laptop_fs.write("note.md", b"# Note").await.unwrap();
+    let laptop = Vault::init(Arc::clone(&laptop_fs), PeerId::from(1)).await.unwrap();
+    let phone = Vault::init(Arc::new(InMemoryFs::new()), PeerId::from(2)).await.unwrap();
+
+    phone.merge_with(&laptop).await.unwrap();
+    edit(&laptop_fs, &laptop, "note.md", "# Note\n\nmore").await;
+
+    let first = phone.merge_with(&laptop).await.unwrap();
+    assert_eq!(first.updated, ["note.md"]);
+
+    let second = phone.merge_with(&laptop).await.unwrap();
+    assert!(!second.has_changes());
+    assert!(second.conflicts.is_empty());
+}
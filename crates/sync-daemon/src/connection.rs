@@ -4,10 +4,12 @@
 //! between read and write halves for async operation.
 
 use crate::message::{Handshake, MAX_MESSAGE_SIZE};
+use sync_core::protocol::SyncEnvelope;
 use sync_core::PeerId;
 use anyhow::{anyhow, Result};
 use futures::{SinkExt, StreamExt};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, Mutex};
 use tokio::task::JoinHandle;
@@ -20,12 +22,35 @@ use tracing::{debug, error, warn};
 /// Message received from a peer connection.
 #[derive(Debug)]
 pub struct IncomingMessage {
-    /// Peer ID (resolved after handshake by poll_event/poll_events)
+    /// Peer ID of the immediate sender (resolved after handshake by poll_event/poll_events)
     pub peer_id: String,
+    /// Peer ID of the original author, if the sender relayed this message
+    pub origin: Option<String>,
     /// Raw message data
     pub data: Vec<u8>,
 }
 
+impl IncomingMessage {
+    /// Create a message from raw data, reading the relay origin if present.
+    pub fn new(peer_id: String, data: Vec<u8>) -> Self {
+        Self {
+            origin: SyncEnvelope::peek_origin(&data),
+            peer_id,
+            data,
+        }
+    }
+
+    /// Peer ID of the original author (the sender itself unless relayed).
+    pub fn origin(&self) -> &str {
+        self.origin.as_deref().unwrap_or(&self.peer_id)
+    }
+
+    /// Whether this message was relayed on behalf of another peer.
+    pub fn is_relayed(&self) -> bool {
+        self.origin() != self.peer_id
+    }
+}
+
 /// Internal event emitted by a connection's read loop.
 ///
 /// These use `conn_id` (the internal connection identifier) and are
@@ -51,6 +76,8 @@ pub struct PeerConnection {
     pub conn_id: String,
     /// Real peer ID (known after handshake)
     pub real_peer_id: Option<String>,
+    /// When the WebSocket was accepted (for handshake timeouts)
+    pub opened_at: Instant,
     /// Write half of the WebSocket (wrapped for sharing across tasks)
     write: Arc<Mutex<futures::stream::SplitSink<WebSocketStream<TcpStream>, Message>>>,
     /// Handle to the read task
@@ -77,6 +104,7 @@ impl PeerConnection {
         Self {
             conn_id,
             real_peer_id: None,
+            opened_at: Instant::now(),
             write,
             read_task: Some(read_task),
         }
@@ -133,10 +161,10 @@ impl PeerConnection {
                     } else {
                         // Regular sync message — peer_id starts as conn_id,
                         // gets resolved by poll_event/poll_events before reaching callers
-                        let _ = event_tx.send(ConnectionEvent::Message(IncomingMessage {
-                            peer_id: conn_id.clone(),
+                        let _ = event_tx.send(ConnectionEvent::Message(IncomingMessage::new(
+                            conn_id.clone(),
                             data,
-                        }));
+                        )));
                     }
                 }
                 Some(Err(e)) => {
@@ -11,6 +11,7 @@ pub mod outgoing;
 pub mod persistence;
 pub mod server;
 pub mod watcher;
+pub mod webhook;
 
 // Re-export key types for convenience
 pub use connection::{ConnectionEvent, IncomingMessage, PeerConnection};
@@ -21,3 +22,4 @@ pub use outgoing::{OutgoingConnection, OutgoingState, ReconnectConfig, Reconnect
 pub use persistence::{PeerStorage, PersistedPeer, PersistedPeers};
 pub use server::{ServerEvent, WebSocketServer};
 pub use watcher::{FileEvent, FileEventKind, FileWatcher};
+pub use webhook::{ChangeKind, WebhookNotifier, WebhookPayload};
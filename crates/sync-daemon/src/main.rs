@@ -7,21 +7,26 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, warn};
 use tracing_subscriber::EnvFilter;
 
 // Use library exports
 use sync_daemon::manager::{ConnectionManager, ManagerEvent};
-use sync_daemon::native_fs::NativeFs;
+use sync_daemon::native_fs::{NativeFs, DEFAULT_MAX_CONCURRENT_OPS};
 use sync_daemon::server::{ServerEvent, WebSocketServer};
 use sync_daemon::watcher::{FileEvent, FileEventKind, FileWatcher};
+use sync_daemon::webhook::WebhookNotifier;
 use sync_daemon::IncomingMessage;
 
 use sync_core::fs::FileSystem;
-use sync_core::protocol::{GossipMessage, PeerMessage};
-use sync_core::swim::{GossipUpdate, MembershipList, PeerInfo};
-use sync_core::{PeerId, Vault};
+use sync_core::protocol::{GossipMessage, PeerMessage, SyncEnvelope};
+use sync_core::swim::{GossipUpdate, MembershipList, PeerInfo, SwimConfig};
+use sync_core::{DeleteEditPolicy, PeerId, Vault};
+
+/// How often to look for connections that have exceeded the handshake timeout.
+const HANDSHAKE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Parser, Debug)]
 #[command(name = "sync-daemon")]
@@ -56,11 +61,66 @@ struct Args {
     #[arg(long)]
     peer_id: Option<String>,
 
+    /// Seconds a new connection may take to send its handshake before it is dropped
+    #[arg(long, default_value_t = 10)]
+    handshake_timeout: u64,
+
+    /// Rewrite wiki-links in other notes when a peer renames a note
+    #[arg(long)]
+    rewrite_links_on_rename: bool,
+
+    /// Apply a peer's deletion even if the note has edits that peer hadn't seen
+    /// (by default the note is kept)
+    #[arg(long)]
+    delete_wins: bool,
+
+    /// Maximum number of filesystem operations to run at once
+    #[arg(long, default_value_t = DEFAULT_MAX_CONCURRENT_OPS)]
+    max_fs_ops: usize,
+
+    /// URL to POST a JSON notification to for each change applied from a peer
+    #[arg(long)]
+    webhook_url: Option<String>,
+
+    /// Milliseconds between SWIM probes of a random peer (default: 1000)
+    #[arg(long)]
+    swim_probe_interval: Option<u64>,
+
+    /// Milliseconds to wait for a direct probe ack before probing indirectly (default: 500).
+    /// Raise on slow or lossy links so slow peers aren't suspected
+    #[arg(long)]
+    swim_probe_timeout: Option<u64>,
+
+    /// Number of peers asked to probe a silent peer indirectly (default: 3)
+    #[arg(long)]
+    swim_indirect_probes: Option<usize>,
+
+    /// Probe intervals a suspected peer has to refute before it's declared dead (default: 5)
+    #[arg(long)]
+    swim_suspicion_multiplier: Option<u32>,
+
     /// Enable verbose logging
     #[arg(long)]
     verbose: bool,
 }
 
+/// SWIM timing from the command line, with defaults for unset flags.
+fn swim_config(args: &Args) -> SwimConfig {
+    let default = SwimConfig::default();
+    SwimConfig {
+        probe_interval: args
+            .swim_probe_interval
+            .map_or(default.probe_interval, Duration::from_millis),
+        probe_timeout: args
+            .swim_probe_timeout
+            .map_or(default.probe_timeout, Duration::from_millis),
+        indirect_probes: args.swim_indirect_probes.unwrap_or(default.indirect_probes),
+        suspicion_multiplier: args
+            .swim_suspicion_multiplier
+            .unwrap_or(default.suspicion_multiplier),
+    }
+}
+
 #[derive(clap::Subcommand, Debug)]
 enum Command {
     /// Connect to a peer and add them to the mesh
@@ -68,6 +128,17 @@ enum Command {
         /// WebSocket address of the peer (e.g., ws://peer.example.com:8080)
         address: String,
     },
+    /// Write every note to a directory as plain markdown (no sync state)
+    Export {
+        /// Directory to write the markdown files into
+        target: PathBuf,
+    },
+    /// Merge another copy of this vault that was edited offline (both are updated)
+    Merge {
+        /// Path to the other vault directory
+        #[arg(long)]
+        other: PathBuf,
+    },
 }
 
 /// Daemon state holding all components.
@@ -82,6 +153,8 @@ struct Daemon {
     watcher: FileWatcher,
     /// SWIM membership list for gossip-based peer discovery
     membership: MembershipList,
+    /// Posts applied changes to the configured webhook URL, if any
+    webhook: Option<WebhookNotifier>,
 }
 
 impl Daemon {
@@ -175,8 +248,18 @@ impl Daemon {
     /// `poll_event()` for incoming, or `poll_events()` for outgoing).
     async fn on_sync_message(&mut self, msg: IncomingMessage) {
         let peer_id = &msg.peer_id;
+        let origin = msg.origin();
 
-        debug!("Processing message from {} ({} bytes)", peer_id, msg.data.len());
+        if msg.is_relayed() {
+            debug!(
+                "Processing message from {} relayed by {} ({} bytes)",
+                origin,
+                peer_id,
+                msg.data.len()
+            );
+        } else {
+            debug!("Processing message from {} ({} bytes)", peer_id, msg.data.len());
+        }
 
         // Try to parse as a typed JSON message (gossip or sync envelope)
         let sync_data = match PeerMessage::from_json(&msg.data) {
@@ -200,8 +283,21 @@ impl Daemon {
         let should_relay_raw = self.is_file_lifecycle_message(&sync_data);
 
         let vault = self.vault.lock().await;
+        vault.record_route(origin, peer_id);
+
+        if let Some(webhook) = &self.webhook {
+            webhook.set_origin(Some(origin));
+        }
+        // Record the versions the sender reports so `document_sync_peers` can use them
+        let result = match origin.parse::<PeerId>() {
+            Ok(from) => vault.process_sync_message_from(from, &sync_data).await,
+            Err(_) => vault.process_sync_message(&sync_data).await,
+        };
+        if let Some(webhook) = &self.webhook {
+            webhook.set_origin(None);
+        }
 
-        match vault.process_sync_message(&sync_data).await {
+        match result {
             Ok((response, modified_paths)) => {
                 // Send response if any
                 if let Some(response_data) = response {
@@ -214,7 +310,8 @@ impl Daemon {
                 if !modified_paths.is_empty() && self.server.peer_count() > 1 {
                     if should_relay_raw {
                         // FileDeleted/FileRenamed: relay the original message directly
-                        self.server.broadcast_except(&sync_data, peer_id).await;
+                        let relayed = SyncEnvelope::new(sync_data.clone(), vec![]).with_origin(origin);
+                        self.server.broadcast_except(&relayed.to_json(), peer_id).await;
                         info!(
                             "Relayed file lifecycle event for {} to {} other peer(s)",
                             modified_paths.join(", "),
@@ -225,7 +322,8 @@ impl Daemon {
                         for path in &modified_paths {
                             match vault.prepare_document_update(path).await {
                                 Ok(Some(update)) => {
-                                    self.server.broadcast_except(&update, peer_id).await;
+                                    let relayed = SyncEnvelope::new(update, vec![]).with_origin(origin);
+                                    self.server.broadcast_except(&relayed.to_json(), peer_id).await;
                                 }
                                 Ok(None) => {
                                     debug!("No update to relay for {}", path);
@@ -370,6 +468,71 @@ impl Daemon {
     }
 }
 
+/// Export the vault's CRDT state as plain markdown into `target`.
+async fn export_vault(vault_path: PathBuf, target: PathBuf) -> Result<()> {
+    let fs = NativeFs::new(vault_path);
+    let vault = Vault::load(fs, PeerId::generate())
+        .await
+        .context("Failed to load vault for export")?;
+
+    let notes = vault.export_markdown_tree().await?;
+    let out = NativeFs::new(target.clone());
+    for (path, markdown) in &notes {
+        out.write(path, markdown.as_bytes())
+            .await
+            .with_context(|| format!("Failed to write {}", path))?;
+    }
+
+    info!("Exported {} note(s) to {:?}", notes.len(), target);
+    Ok(())
+}
+
+/// Merge the vault at `other_path` with the one at `vault_path`, offline.
+async fn merge_vaults(vault_path: PathBuf, other_path: PathBuf, peer_id: PeerId) -> Result<()> {
+    let vault = Vault::load(NativeFs::new(vault_path), peer_id)
+        .await
+        .context("Failed to load vault")?;
+    let other = Vault::load(NativeFs::new(other_path.clone()), PeerId::generate())
+        .await
+        .with_context(|| format!("Failed to load other vault {:?}", other_path))?;
+
+    let report = vault.merge_with(&other).await?;
+
+    for path in &report.added {
+        info!("Added {}", path);
+    }
+    for path in &report.removed {
+        info!("Removed {}", path);
+    }
+    for (old_path, new_path) in &report.renamed {
+        info!("Renamed {} -> {}", old_path, new_path);
+    }
+    for path in &report.updated {
+        info!("Updated {}", path);
+    }
+    for conflict in &report.conflicts {
+        warn!(
+            "Conflict in {} ({:?}, +{} -{} lines)",
+            conflict.path, conflict.resolution, conflict.lines_added, conflict.lines_removed
+        );
+    }
+
+    if report.has_changes() {
+        info!(
+            "Merged {:?}: {} added, {} removed, {} renamed, {} updated, {} conflict(s)",
+            other_path,
+            report.added.len(),
+            report.removed.len(),
+            report.renamed.len(),
+            report.updated.len(),
+            report.conflicts.len()
+        );
+    } else {
+        info!("Vaults already in sync, nothing to merge");
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
@@ -384,12 +547,25 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt().with_env_filter(filter).init();
 
     // Handle subcommands
-    if let Some(Command::AddPeer { address }) = args.command {
-        info!("add-peer command: {}", address);
-        // TODO: Connect to running daemon via IPC and add peer
-        eprintln!("add-peer subcommand not yet implemented");
-        eprintln!("For now, use --bootstrap {} on daemon startup", address);
-        return Ok(());
+    match args.command {
+        Some(Command::AddPeer { address }) => {
+            info!("add-peer command: {}", address);
+            // TODO: Connect to running daemon via IPC and add peer
+            eprintln!("add-peer subcommand not yet implemented");
+            eprintln!("For now, use --bootstrap {} on daemon startup", address);
+            return Ok(());
+        }
+        Some(Command::Export { target }) => {
+            return export_vault(args.vault, target).await;
+        }
+        Some(Command::Merge { other }) => {
+            let peer_id = match args.peer_id {
+                Some(id_str) => id_str.parse().context("Invalid peer ID")?,
+                None => PeerId::generate(),
+            };
+            return merge_vaults(args.vault, other, peer_id).await;
+        }
+        None => {}
     }
 
     info!("Starting sync-daemon");
@@ -405,7 +581,7 @@ async fn main() -> Result<()> {
     }
 
     // Generate or parse peer ID
-    let peer_id: sync_core::PeerId = match args.peer_id {
+    let peer_id: sync_core::PeerId = match &args.peer_id {
         Some(id_str) => id_str.parse().context("Invalid peer ID")?,
         None => {
             let id = sync_core::PeerId::generate();
@@ -415,7 +591,7 @@ async fn main() -> Result<()> {
     };
 
     // Create filesystem
-    let fs = NativeFs::new(args.vault.clone());
+    let fs = NativeFs::new(args.vault.clone()).with_max_concurrent_ops(args.max_fs_ops);
 
     // Initialize or load vault
     let vault = if fs.exists(".sync").await? {
@@ -428,14 +604,33 @@ async fn main() -> Result<()> {
 
     info!("Vault loaded, peer ID: {}", vault.peer_id());
 
+    if args.rewrite_links_on_rename {
+        vault.set_rewrite_links_on_rename(true);
+        info!("Rewriting wiki-links on synced renames");
+    }
+
+    if args.delete_wins {
+        vault.set_delete_edit_policy(DeleteEditPolicy::DeleteWins);
+        info!("Peer deletions win over unseen local edits");
+    }
+
+    let webhook = args.webhook_url.clone().map(|url| {
+        info!("Posting applied changes to webhook {}", url);
+        WebhookNotifier::attach(&vault, url)
+    });
+
+    let handshake_timeout = Duration::from_secs(args.handshake_timeout);
+
     // Create WebSocket server (takes string peer_id for protocol messages)
-    let server = WebSocketServer::new(peer_id.to_string(), args.advertise.clone());
+    let server = WebSocketServer::new(peer_id.to_string(), args.advertise.clone())
+        .with_handshake_timeout(handshake_timeout);
 
     // Create connection manager for outgoing connections
     let (outgoing, mut outgoing_rx) = ConnectionManager::new(
         peer_id.to_string(),
         args.advertise.clone(),
     );
+    let outgoing = outgoing.with_handshake_timeout(handshake_timeout);
 
     // Only listen for incoming connections if not in client-only mode
     let listener = if !args.client_only {
@@ -449,7 +644,8 @@ async fn main() -> Result<()> {
     info!("File watcher started");
 
     // Create SWIM membership list for gossip-based peer discovery
-    let membership = MembershipList::new(peer_id, args.advertise.clone());
+    let membership =
+        MembershipList::new(peer_id, args.advertise.clone()).with_config(swim_config(&args));
 
     // Create daemon state
     let mut daemon = Daemon {
@@ -458,6 +654,7 @@ async fn main() -> Result<()> {
         outgoing,
         watcher,
         membership,
+        webhook,
     };
 
     // Connect to bootstrap peers
@@ -468,6 +665,9 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Periodically drop connections that never complete their handshake
+    let mut handshake_check = tokio::time::interval(HANDSHAKE_CHECK_INTERVAL);
+
     info!("Daemon running. Press Ctrl+C to stop.");
 
     // Main event loop
@@ -538,6 +738,12 @@ async fn main() -> Result<()> {
                 }
             }
 
+            // Expire half-open connections
+            _ = handshake_check.tick() => {
+                daemon.server.expire_pending_handshakes().await;
+                daemon.outgoing.expire_pending_handshakes().await;
+            }
+
             // Handle graceful shutdown
             _ = tokio::signal::ctrl_c() => {
                 info!("Shutdown signal received");
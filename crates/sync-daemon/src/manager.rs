@@ -9,10 +9,11 @@
 
 use crate::connection::{ConnectionEvent, IncomingMessage, PeerConnection};
 use crate::outgoing::{OutgoingConnection, OutgoingState, ReconnectConfig};
+use crate::server::DEFAULT_HANDSHAKE_TIMEOUT;
 use anyhow::Result;
 use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 use tokio_tungstenite::accept_async;
@@ -64,6 +65,15 @@ impl Connection {
         }
     }
 
+    /// When this connection started waiting for the remote handshake, if it still is.
+    fn awaiting_handshake_since(&self) -> Option<Instant> {
+        match self {
+            Connection::Incoming(c) if c.real_peer_id.is_none() => Some(c.opened_at),
+            Connection::Incoming(_) => None,
+            Connection::Outgoing(c) => c.handshake_started,
+        }
+    }
+
     async fn send(&self, data: &[u8]) -> Result<()> {
         match self {
             Connection::Incoming(c) => c.send(data).await,
@@ -92,6 +102,8 @@ pub struct ConnectionManager {
     manager_tx: mpsc::UnboundedSender<ManagerEvent>,
     /// Reconnection configuration
     reconnect_config: ReconnectConfig,
+    /// How long a connection may wait for the remote handshake
+    handshake_timeout: Duration,
 }
 
 impl ConnectionManager {
@@ -114,11 +126,18 @@ impl ConnectionManager {
                 event_rx,
                 manager_tx,
                 reconnect_config: ReconnectConfig::default(),
+                handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
             },
             manager_rx,
         )
     }
 
+    /// Set how long a connection may wait for the remote handshake.
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
     /// Get our peer ID.
     pub fn peer_id(&self) -> &str {
         &self.our_peer_id
@@ -358,6 +377,34 @@ impl ConnectionManager {
         }
     }
 
+    /// Close connections that haven't completed a handshake in time.
+    ///
+    /// Call periodically from the event loop. Each expired connection is closed
+    /// with `DisconnectReason::ProtocolError`. Returns their connection IDs.
+    pub async fn expire_pending_handshakes(&mut self) -> Vec<String> {
+        let mut expired: Vec<String> = self
+            .connections
+            .iter()
+            .filter(|(_, conn)| {
+                conn.awaiting_handshake_since()
+                    .is_some_and(|since| since.elapsed() >= self.handshake_timeout)
+            })
+            .map(|(conn_id, _)| conn_id.clone())
+            .collect();
+        expired.sort();
+
+        for conn_id in &expired {
+            warn!(
+                "No handshake from {} within {:?}, closing",
+                conn_id, self.handshake_timeout
+            );
+            self.close_connection(conn_id, DisconnectReason::ProtocolError)
+                .await;
+        }
+
+        expired
+    }
+
     /// Send data to a specific peer.
     pub async fn send(&self, peer_id: &str, data: &[u8]) -> Result<()> {
         let conn_id = self
@@ -474,6 +521,13 @@ mod tests {
         assert!(!manager.is_connected("other-peer"));
     }
 
+    #[tokio::test]
+    async fn test_expire_pending_handshakes_empty() {
+        let (manager, _rx) = ConnectionManager::new("our-peer".into(), None);
+        let mut manager = manager.with_handshake_timeout(Duration::ZERO);
+        assert!(manager.expire_pending_handshakes().await.is_empty());
+    }
+
     // Note: Full integration tests require actual WebSocket connections,
     // which are better suited for e2e tests in tests/e2e.rs
 }
@@ -1,18 +1,86 @@
 //! Native filesystem implementation using tokio::fs.
+//!
+//! Every operation holds a permit from a semaphore while it runs, so a burst of
+//! file events can't queue unbounded I/O on tokio's blocking pool.
 
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::path::PathBuf;
-use sync_core::fs::{FileEntry, FileStat, FileSystem, FsError, Result};
+use std::sync::Mutex;
+use sync_core::fs::{content_hash, FileEntry, FileStat, FileSystem, FsError, Result};
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Default cap on concurrent filesystem operations
+pub const DEFAULT_MAX_CONCURRENT_OPS: usize = 64;
+
+/// Content hash of a file as of a given mtime and size
+#[derive(Clone, Copy)]
+struct KnownHash {
+    mtime_millis: u64,
+    size: u64,
+    hash: u64,
+}
 
 /// Native filesystem implementation for the daemon
 pub struct NativeFs {
     base_path: PathBuf,
+    /// Hashes of contents this instance has read or written, so `stat` can
+    /// report them without re-reading the file
+    hashes: Mutex<HashMap<String, KnownHash>>,
+    /// Permits for in-flight operations
+    ops: Semaphore,
 }
 
 impl NativeFs {
     pub fn new(base_path: PathBuf) -> Self {
-        Self { base_path }
+        Self {
+            base_path,
+            hashes: Mutex::new(HashMap::new()),
+            ops: Semaphore::new(DEFAULT_MAX_CONCURRENT_OPS),
+        }
+    }
+
+    /// Set how many filesystem operations may run at once
+    /// (default [`DEFAULT_MAX_CONCURRENT_OPS`]). Clamped to at least 1.
+    pub fn with_max_concurrent_ops(mut self, max: usize) -> Self {
+        self.ops = Semaphore::new(max.max(1));
+        self
+    }
+
+    /// Wait for a free operation slot.
+    async fn permit(&self) -> SemaphorePermit<'_> {
+        self.ops
+            .acquire()
+            .await
+            .expect("NativeFs semaphore is never closed")
+    }
+
+    /// Remember the hash of `content` against the file's current mtime and size.
+    async fn remember_hash(&self, path: &str, content: &[u8]) {
+        let Ok(metadata) = fs::metadata(self.full_path(path)).await else {
+            return;
+        };
+        let known = KnownHash {
+            mtime_millis: mtime_millis(&metadata),
+            size: metadata.len(),
+            hash: content_hash(content),
+        };
+        self.hashes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(path.to_string(), known);
+    }
+
+    /// Hash remembered for `path`, if the file hasn't changed since.
+    fn known_hash(&self, path: &str, mtime_millis: u64, size: u64) -> Option<u64> {
+        self.hashes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(path)
+            .filter(|k| k.mtime_millis == mtime_millis && k.size == size)
+            .map(|k| k.hash)
     }
 
     fn full_path(&self, path: &str) -> PathBuf {
@@ -27,13 +95,15 @@ impl NativeFs {
 #[async_trait]
 impl FileSystem for NativeFs {
     async fn read(&self, path: &str) -> Result<Vec<u8>> {
+        let _permit = self.permit().await;
         let full_path = self.full_path(path);
-        fs::read(&full_path)
-            .await
-            .map_err(|e| FsError::Io(e.to_string()))
+        let content = fs::read(&full_path).await.map_err(|e| read_error(path, e))?;
+        self.remember_hash(path, &content).await;
+        Ok(content)
     }
 
     async fn write(&self, path: &str, content: &[u8]) -> Result<()> {
+        let _permit = self.permit().await;
         let full_path = self.full_path(path);
 
         // Create parent directories if needed
@@ -45,10 +115,13 @@ impl FileSystem for NativeFs {
 
         fs::write(&full_path, content)
             .await
-            .map_err(|e| FsError::Io(e.to_string()))
+            .map_err(|e| FsError::Io(e.to_string()))?;
+        self.remember_hash(path, content).await;
+        Ok(())
     }
 
     async fn list(&self, path: &str) -> Result<Vec<FileEntry>> {
+        let _permit = self.permit().await;
         let full_path = self.full_path(path);
         let mut entries = Vec::new();
 
@@ -77,7 +150,12 @@ impl FileSystem for NativeFs {
     }
 
     async fn delete(&self, path: &str) -> Result<()> {
+        let _permit = self.permit().await;
         let full_path = self.full_path(path);
+        self.hashes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(path);
         let metadata = fs::metadata(&full_path)
             .await
             .map_err(|e| FsError::Io(e.to_string()))?;
@@ -94,36 +172,221 @@ impl FileSystem for NativeFs {
     }
 
     async fn exists(&self, path: &str) -> Result<bool> {
+        let _permit = self.permit().await;
         let full_path = self.full_path(path);
-        Ok(full_path.exists())
+        Ok(fs::try_exists(&full_path).await.unwrap_or(false))
     }
 
     async fn stat(&self, path: &str) -> Result<FileStat> {
+        let _permit = self.permit().await;
         let full_path = self.full_path(path);
         let metadata = fs::metadata(&full_path)
             .await
-            .map_err(|e| FsError::Io(e.to_string()))?;
+            .map_err(|e| read_error(path, e))?;
 
-        let mtime_millis = metadata
-            .modified()
-            .map(|t| {
-                t.duration_since(std::time::UNIX_EPOCH)
-                    .map(|d| d.as_millis() as u64)
-                    .unwrap_or(0)
-            })
-            .unwrap_or(0);
+        let mtime_millis = mtime_millis(&metadata);
+        let content_hash = if metadata.is_dir() {
+            None
+        } else {
+            self.known_hash(path, mtime_millis, metadata.len())
+        };
 
         Ok(FileStat {
             mtime_millis,
             size: metadata.len(),
             is_dir: metadata.is_dir(),
+            content_hash,
         })
     }
 
     async fn mkdir(&self, path: &str) -> Result<()> {
+        let _permit = self.permit().await;
         let full_path = self.full_path(path);
         fs::create_dir_all(&full_path)
             .await
             .map_err(|e| FsError::Io(e.to_string()))
     }
+
+    /// Appends in place, so the file is never rewritten.
+    async fn append(&self, path: &str, content: &[u8]) -> Result<()> {
+        let _permit = self.permit().await;
+        let full_path = self.full_path(path);
+
+        // Create parent directories if needed
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| FsError::Io(e.to_string()))?;
+        }
+
+        // The remembered hash no longer matches the file
+        self.hashes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(path);
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&full_path)
+            .await
+            .map_err(|e| FsError::Io(e.to_string()))?;
+        file.write_all(content)
+            .await
+            .map_err(|e| FsError::Io(e.to_string()))?;
+        file.flush().await.map_err(|e| FsError::Io(e.to_string()))
+    }
+
+    /// Walks with `DirEntry::file_type`, which avoids a metadata call per entry.
+    async fn list_recursive(&self, prefix: &str) -> Result<Vec<FileEntry>> {
+        let _permit = self.permit().await;
+        let root = prefix.trim_matches('/').to_string();
+        let is_dir = fs::metadata(self.full_path(&root))
+            .await
+            .is_ok_and(|metadata| metadata.is_dir());
+        if !is_dir {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        let mut dirs_to_visit = vec![root];
+
+        while let Some(dir) = dirs_to_visit.pop() {
+            let mut entries = fs::read_dir(self.full_path(&dir))
+                .await
+                .map_err(|e| FsError::Io(e.to_string()))?;
+
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| FsError::Io(e.to_string()))?
+            {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let path = if dir.is_empty() {
+                    name
+                } else {
+                    format!("{}/{}", dir, name)
+                };
+                let file_type = entry
+                    .file_type()
+                    .await
+                    .map_err(|e| FsError::Io(e.to_string()))?;
+
+                if file_type.is_dir() {
+                    dirs_to_visit.push(path);
+                } else {
+                    files.push(FileEntry {
+                        name: path,
+                        is_dir: false,
+                    });
+                }
+            }
+        }
+
+        Ok(files)
+    }
+}
+
+/// Map an error reading `path`, reporting a missing file as `FsError::NotFound`.
+fn read_error(path: &str, e: std::io::Error) -> FsError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        FsError::NotFound(path.to_string())
+    } else {
+        FsError::Io(e.to_string())
+    }
+}
+
+/// Modification time in milliseconds since epoch (0 if unavailable).
+fn mtime_millis(metadata: &std::fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .map(|t| {
+            t.duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0)
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_many_concurrent_writes_complete() {
+        let dir = TempDir::new().unwrap();
+        let fs = Arc::new(NativeFs::new(dir.path().to_path_buf()).with_max_concurrent_ops(4));
+
+        let writes = (0..200).map(|i| {
+            let fs = Arc::clone(&fs);
+            tokio::spawn(async move {
+                let path = format!("notes/{}/note-{}.md", i % 10, i);
+                fs.write(&path, format!("# Note {}", i).as_bytes()).await
+            })
+        });
+        let all = futures::future::join_all(writes);
+        let results = tokio::time::timeout(Duration::from_secs(30), all)
+            .await
+            .expect("writes deadlocked");
+        for result in results {
+            result.unwrap().unwrap();
+        }
+
+        assert_eq!(fs.list_recursive("").await.unwrap().len(), 200);
+        assert_eq!(fs.read("notes/3/note-13.md").await.unwrap(), b"# Note 13");
+        // Every permit was returned
+        assert_eq!(fs.ops.available_permits(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_append_creates_and_extends_files() {
+        let dir = TempDir::new().unwrap();
+        let fs = NativeFs::new(dir.path().to_path_buf());
+
+        fs.append("logs/Log.md", b"first\n").await.unwrap();
+        assert_eq!(fs.read("logs/Log.md").await.unwrap(), b"first\n");
+
+        fs.append("logs/Log.md", b"second\n").await.unwrap();
+        assert_eq!(fs.read("logs/Log.md").await.unwrap(), b"first\nsecond\n");
+        let stat = fs.stat("logs/Log.md").await.unwrap();
+        assert_eq!(stat.content_hash, Some(content_hash(b"first\nsecond\n")));
+    }
+
+    #[tokio::test]
+    async fn test_missing_file_is_not_found() {
+        let dir = TempDir::new().unwrap();
+        let fs = NativeFs::new(dir.path().to_path_buf());
+
+        assert!(matches!(fs.read("missing.md").await, Err(FsError::NotFound(_))));
+        assert!(matches!(fs.stat("missing.md").await, Err(FsError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_operations_wait_for_a_free_permit() {
+        let dir = TempDir::new().unwrap();
+        let fs = Arc::new(NativeFs::new(dir.path().to_path_buf()).with_max_concurrent_ops(2));
+
+        // Two operations in flight use up the cap
+        let first = fs.permit().await;
+        let _second = fs.permit().await;
+
+        let write = tokio::spawn({
+            let fs = Arc::clone(&fs);
+            async move { fs.write("a.md", b"# A").await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!write.is_finished());
+        assert!(!dir.path().join("a.md").exists());
+
+        drop(first);
+        tokio::time::timeout(Duration::from_secs(5), write)
+            .await
+            .expect("write never got a permit")
+            .unwrap()
+            .unwrap();
+        assert_eq!(fs.read("a.md").await.unwrap(), b"# A");
+    }
 }
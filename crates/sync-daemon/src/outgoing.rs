@@ -10,7 +10,7 @@ use crate::message::{Handshake, MAX_MESSAGE_SIZE};
 use anyhow::Result;
 use futures::{SinkExt, StreamExt};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, Mutex};
 use tokio::task::JoinHandle;
@@ -135,6 +135,8 @@ pub struct OutgoingConnection {
     pub remote_peer_id: Option<String>,
     /// Connection state
     pub state: OutgoingState,
+    /// When the WebSocket opened, while still awaiting the remote handshake
+    pub handshake_started: Option<Instant>,
     /// Write half of the WebSocket
     write: Option<
         Arc<Mutex<futures::stream::SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>>>,
@@ -154,6 +156,7 @@ impl OutgoingConnection {
             our_address,
             remote_peer_id: None,
             state: OutgoingState::Connecting,
+            handshake_started: None,
             write: None,
             read_task: None,
             reconnect: ReconnectState::new(),
@@ -171,6 +174,7 @@ impl OutgoingConnection {
         let (ws_stream, _) = connect_async(&self.address).await?;
 
         self.state = OutgoingState::Handshaking;
+        self.handshake_started = Some(Instant::now());
 
         let (write, read) = ws_stream.split();
         let write = Arc::new(Mutex::new(write));
@@ -250,10 +254,7 @@ impl OutgoingConnection {
                         // Regular sync message — peer_id starts as address,
                         // gets resolved by poll_events before reaching callers
                         let _ = event_tx.send(ConnectionEvent::Message(
-                            crate::connection::IncomingMessage {
-                                peer_id: address.clone(),
-                                data,
-                            },
+                            crate::connection::IncomingMessage::new(address.clone(), data),
                         ));
                     }
                 }
@@ -285,6 +286,7 @@ impl OutgoingConnection {
     pub fn on_handshake_complete(&mut self, peer_id: String) {
         self.remote_peer_id = Some(peer_id);
         self.state = OutgoingState::Connected;
+        self.handshake_started = None;
     }
 
     /// Send data to the remote peer.
@@ -321,6 +323,7 @@ impl OutgoingConnection {
     pub fn prepare_reconnect(&mut self, now_ms: u64, config: &ReconnectConfig) {
         self.state = OutgoingState::Reconnecting;
         self.remote_peer_id = None;
+        self.handshake_started = None;
         self.write = None;
         if let Some(task) = self.read_task.take() {
             task.abort();
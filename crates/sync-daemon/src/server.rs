@@ -8,11 +8,18 @@ use crate::connection::{ConnectionEvent, IncomingMessage, PeerConnection};
 use anyhow::Result;
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::Duration;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
 use tokio_tungstenite::accept_async;
 use tracing::{debug, error, info, warn};
 
+use sync_core::peers::{DisconnectReason, DEFAULT_HANDSHAKE_TIMEOUT_MS};
+
+/// Default time an accepted connection may wait for the peer's handshake.
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration =
+    Duration::from_millis(DEFAULT_HANDSHAKE_TIMEOUT_MS as u64);
+
 /// Event emitted by the server after the handshake lifecycle is resolved.
 /// Callers only see peer IDs — connection IDs are an internal detail.
 #[derive(Debug)]
@@ -42,6 +49,8 @@ pub struct WebSocketServer {
     conn_id_to_peer: HashMap<String, String>,
     /// Counter for generating connection IDs
     next_conn_id: u64,
+    /// How long a pending connection may wait for the peer's handshake
+    handshake_timeout: Duration,
     /// Channel sender for connection events (messages, handshakes, closes)
     event_tx: mpsc::UnboundedSender<ConnectionEvent>,
     /// Channel receiver for connection events
@@ -60,11 +69,18 @@ impl WebSocketServer {
             peers: HashMap::new(),
             conn_id_to_peer: HashMap::new(),
             next_conn_id: 1,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT,
             event_tx,
             event_rx,
         }
     }
 
+    /// Set how long a connection may wait for the peer's handshake.
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
     /// Bind to an address and return the TCP listener.
     pub async fn bind(listen_addr: &str) -> Result<TcpListener> {
         let listener = TcpListener::bind(listen_addr).await?;
@@ -178,6 +194,35 @@ impl WebSocketServer {
         }
     }
 
+    /// Close pending connections that haven't completed a handshake in time.
+    ///
+    /// Call periodically from the event loop. Expired connections are closed
+    /// with `DisconnectReason::ProtocolError` and dropped without emitting an
+    /// event, like any other pre-handshake close. Returns their connection IDs.
+    pub async fn expire_pending_handshakes(&mut self) -> Vec<String> {
+        let mut expired: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, conn)| conn.opened_at.elapsed() >= self.handshake_timeout)
+            .map(|(conn_id, _)| conn_id.clone())
+            .collect();
+        expired.sort();
+
+        for conn_id in &expired {
+            if let Some(mut conn) = self.pending.remove(conn_id) {
+                warn!(
+                    "No handshake from {} within {:?}, closing ({:?})",
+                    conn_id,
+                    self.handshake_timeout,
+                    DisconnectReason::ProtocolError
+                );
+                conn.close().await;
+            }
+        }
+
+        expired
+    }
+
     /// Send data to a specific peer by their real peer ID.
     pub async fn send(&self, peer_id: &str, data: &[u8]) -> Result<()> {
         let conn = self
@@ -214,6 +259,11 @@ impl WebSocketServer {
         self.peers.len()
     }
 
+    /// Get the number of connections still awaiting a handshake.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
     /// Get list of connected peer IDs.
     pub fn connected_peers(&self) -> Vec<String> {
         self.peers.keys().cloned().collect()
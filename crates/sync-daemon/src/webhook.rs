@@ -0,0 +1,323 @@
+//! Webhook notifications for changes applied from peers.
+//!
+//! Subscribes to the vault's `SyncEvent` stream and POSTs a small JSON payload
+//! for each document update, delete, or rename received from a peer. Delivery
+//! runs on its own task behind a bounded queue, so a slow or unreachable
+//! endpoint never blocks sync: when the queue is full, new notifications are
+//! dropped with a warning.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sync_core::{FileSystem, Subscription, SyncEvent, Vault};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// Notifications waiting for delivery before new ones are dropped
+pub const WEBHOOK_QUEUE_CAPACITY: usize = 256;
+
+/// Delivery attempts per notification (first try plus retries)
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 4;
+
+/// Delay before the first retry; doubles on each further retry
+const WEBHOOK_RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Timeout for a single POST
+const WEBHOOK_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Kind of change a webhook reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Updated,
+    Deleted,
+    Renamed,
+}
+
+/// JSON body POSTed for each applied change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    /// Vault-relative path of the changed note (the old path for renames)
+    pub path: String,
+    pub change_kind: ChangeKind,
+    /// Peer the change came from
+    pub peer: String,
+    /// New path, for renames only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_path: Option<String>,
+}
+
+impl WebhookPayload {
+    /// Build the payload for a sync event, if it reports a change.
+    fn from_event(event: &SyncEvent, peer: &str) -> Option<Self> {
+        let (path, change_kind, new_path) = match event {
+            SyncEvent::DocumentUpdated { path, .. } => (path, ChangeKind::Updated, None),
+            SyncEvent::FileOp {
+                operation,
+                path,
+                new_path,
+                ..
+            } => match operation.as_str() {
+                "delete" => (path, ChangeKind::Deleted, None),
+                "rename" => (path, ChangeKind::Renamed, new_path.clone()),
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        Some(Self {
+            path: path.clone(),
+            change_kind,
+            peer: peer.to_string(),
+            new_path,
+        })
+    }
+}
+
+/// Posts a webhook for each change applied from a peer.
+///
+/// Only events emitted while an origin peer is set (see [`set_origin`]) are
+/// reported, which excludes the `FileOp` events the vault emits for local
+/// deletes and renames.
+///
+/// [`set_origin`]: WebhookNotifier::set_origin
+pub struct WebhookNotifier {
+    origin: Arc<Mutex<Option<String>>>,
+    _subscription: Subscription,
+}
+
+impl WebhookNotifier {
+    /// Subscribe to `vault` and start delivering to `url` on a background task.
+    pub fn attach<F: FileSystem>(vault: &Vault<F>, url: String) -> Self {
+        let (tx, rx) = mpsc::channel(WEBHOOK_QUEUE_CAPACITY);
+        tokio::spawn(deliver(url, rx));
+
+        let origin: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
+        let current = Arc::clone(&origin);
+        let subscription = vault.subscribe(move |event| {
+            let Some(peer) = current.lock().unwrap().clone() else {
+                return;
+            };
+            let Some(payload) = WebhookPayload::from_event(&event, &peer) else {
+                return;
+            };
+            if let Err(e) = tx.try_send(payload) {
+                warn!("Webhook queue full, dropping notification: {}", e);
+            }
+        });
+
+        Self {
+            origin,
+            _subscription: subscription,
+        }
+    }
+
+    /// Set the peer whose message is being applied, or `None` when done.
+    pub fn set_origin(&self, peer: Option<&str>) {
+        *self.origin.lock().unwrap() = peer.map(str::to_string);
+    }
+}
+
+/// Deliver queued payloads in order, retrying failures with backoff.
+async fn deliver(url: String, mut rx: mpsc::Receiver<WebhookPayload>) {
+    let client = match reqwest::Client::builder()
+        .timeout(WEBHOOK_REQUEST_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Failed to create webhook client, webhooks disabled: {}", e);
+            return;
+        }
+    };
+
+    while let Some(payload) = rx.recv().await {
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize webhook payload: {}", e);
+                continue;
+            }
+        };
+
+        let mut delay = WEBHOOK_RETRY_BASE_DELAY;
+        for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+            let result = client
+                .post(&url)
+                .header("content-type", "application/json")
+                .body(body.clone())
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+
+            match result {
+                Ok(_) => {
+                    debug!("Webhook delivered for {}", payload.path);
+                    break;
+                }
+                Err(e) if attempt < WEBHOOK_MAX_ATTEMPTS => {
+                    debug!("Webhook attempt {} for {} failed: {}", attempt, payload.path, e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                Err(e) => {
+                    warn!(
+                        "Giving up on webhook for {} after {} attempts: {}",
+                        payload.path, WEBHOOK_MAX_ATTEMPTS, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sync_core::{InMemoryFs, PeerId};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Accept `statuses.len()` requests, answering each with the next status,
+    /// and forward every request body.
+    async fn mock_receiver(statuses: Vec<u16>) -> (String, mpsc::UnboundedReceiver<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+
+                // Read headers, then Content-Length bytes of body
+                let body_start = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                };
+                let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+                let length: usize = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map(|value| value.trim().parse().unwrap())
+                    .unwrap_or(0);
+                while request.len() < body_start + length {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+
+                let response = format!(
+                    "HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+                tx.send(request[body_start..].to_vec()).unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    async fn next_payload(rx: &mut mpsc::UnboundedReceiver<Vec<u8>>) -> WebhookPayload {
+        let body = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("webhook should fire")
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// A DocumentUpdate message from another vault for `note.md`.
+    async fn remote_update() -> Vec<u8> {
+        let fs = InMemoryFs::new();
+        fs.write("note.md", b"# From peer").await.unwrap();
+        let remote = Vault::init(fs, PeerId::from(2u64)).await.unwrap();
+        remote.prepare_document_update("note.md").await.unwrap().unwrap()
+    }
+
+    #[test]
+    fn payload_serializes_change_kind() {
+        let payload = WebhookPayload {
+            path: "a.md".into(),
+            change_kind: ChangeKind::Updated,
+            peer: "peer-a".into(),
+            new_path: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&payload).unwrap(),
+            r#"{"path":"a.md","change_kind":"updated","peer":"peer-a"}"#
+        );
+    }
+
+    #[test]
+    fn payload_ignores_non_change_events() {
+        let event = SyncEvent::PeerDisconnected {
+            peer_id: "peer-a".into(),
+            timestamp: 0.0,
+        };
+        assert_eq!(WebhookPayload::from_event(&event, "peer-a"), None);
+
+        let event = SyncEvent::FileOp {
+            operation: "rename".into(),
+            path: "a.md".into(),
+            new_path: Some("b.md".into()),
+            timestamp: 0.0,
+        };
+        let payload = WebhookPayload::from_event(&event, "peer-a").unwrap();
+        assert_eq!(payload.change_kind, ChangeKind::Renamed);
+        assert_eq!(payload.new_path.as_deref(), Some("b.md"));
+    }
+
+    #[tokio::test]
+    async fn webhook_fires_on_document_update() {
+        let (url, mut bodies) = mock_receiver(vec![200]).await;
+        let vault = Vault::init(InMemoryFs::new(), PeerId::from(1u64)).await.unwrap();
+        let webhook = WebhookNotifier::attach(&vault, url);
+
+        webhook.set_origin(Some("peer-b"));
+        vault.process_sync_message(&remote_update().await).await.unwrap();
+        webhook.set_origin(None);
+
+        let payload = next_payload(&mut bodies).await;
+        assert_eq!(
+            payload,
+            WebhookPayload {
+                path: "note.md".into(),
+                change_kind: ChangeKind::Updated,
+                peer: "peer-b".into(),
+                new_path: None,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn webhook_retries_failed_delivery() {
+        let (url, mut bodies) = mock_receiver(vec![500, 200]).await;
+        let vault = Vault::init(InMemoryFs::new(), PeerId::from(1u64)).await.unwrap();
+        let webhook = WebhookNotifier::attach(&vault, url);
+
+        webhook.set_origin(Some("peer-b"));
+        vault.process_sync_message(&remote_update().await).await.unwrap();
+        webhook.set_origin(None);
+
+        let first = next_payload(&mut bodies).await;
+        let retry = next_payload(&mut bodies).await;
+        assert_eq!(first, retry);
+    }
+
+    #[tokio::test]
+    async fn local_changes_are_not_reported() {
+        let (url, mut bodies) = mock_receiver(vec![200]).await;
+        let vault = Vault::init(InMemoryFs::new(), PeerId::from(1u64)).await.unwrap();
+        let _webhook = WebhookNotifier::attach(&vault, url);
+
+        // No origin set: a local delete broadcast isn't an applied change
+        vault.prepare_file_deleted("note.md").unwrap();
+
+        let result = tokio::time::timeout(Duration::from_millis(300), bodies.recv()).await;
+        assert!(result.is_err(), "no webhook expected for local changes");
+    }
+}
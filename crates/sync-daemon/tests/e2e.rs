@@ -8,7 +8,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use futures::{SinkExt, StreamExt};
-use sync_core::protocol::{Handshake, HandshakeRole};
+use sync_core::protocol::{Handshake, HandshakeRole, SyncEnvelope};
 use sync_core::PeerId;
 use sync_daemon::{
     native_fs::NativeFs, server::ServerEvent, server::WebSocketServer, watcher::FileWatcher,
@@ -238,6 +238,61 @@ async fn test_poll_event_message() {
     client.close().await;
 }
 
+#[tokio::test]
+async fn test_poll_event_relayed_message_has_origin() {
+    let (server, listener, addr) = create_server("aa00bb11cc22dd33").await;
+
+    let listener = Arc::new(listener);
+    let listener_clone = Arc::clone(&listener);
+    let server = Arc::new(Mutex::new(server));
+    let server_clone = Arc::clone(&server);
+
+    let accept_handle = tokio::spawn(async move {
+        let (stream, peer_addr) = listener_clone.accept().await.expect("Failed to accept");
+        server_clone
+            .lock()
+            .await
+            .accept_connection(stream, peer_addr)
+            .await;
+    });
+
+    let mut client = TestClient::connect_and_handshake(addr).await;
+    accept_handle.await.expect("Accept task failed");
+
+    let mut guard = server.lock().await;
+    let event = poll_event_timeout(&mut guard, Duration::from_secs(2))
+        .await
+        .expect("Should receive PeerConnected");
+    assert!(matches!(event, ServerEvent::PeerConnected { .. }));
+    drop(guard);
+
+    // The client relays data authored by another peer, then sends its own
+    let author = PeerId::generate().to_string();
+    let relayed = SyncEnvelope::new(vec![1, 2, 3], vec![]).with_origin(author.clone());
+    client.send_binary(&relayed.to_json()).await;
+    client.send_binary(b"direct").await;
+
+    let mut guard = server.lock().await;
+    match poll_event_timeout(&mut guard, Duration::from_secs(2)).await {
+        Some(ServerEvent::Message(msg)) => {
+            assert_eq!(msg.peer_id, client.peer_id);
+            assert_eq!(msg.origin(), author);
+            assert!(msg.is_relayed());
+        }
+        other => panic!("Expected Message, got {:?}", other),
+    }
+    match poll_event_timeout(&mut guard, Duration::from_secs(2)).await {
+        Some(ServerEvent::Message(msg)) => {
+            assert_eq!(msg.origin(), client.peer_id);
+            assert!(!msg.is_relayed());
+        }
+        other => panic!("Expected Message, got {:?}", other),
+    }
+
+    drop(guard);
+    client.close().await;
+}
+
 #[tokio::test]
 async fn test_poll_event_peer_disconnected() {
     let (server, listener, addr) = create_server("aa00bb11cc22dd33").await;
@@ -326,6 +381,94 @@ async fn test_pre_handshake_close_not_emitted() {
     assert!(result.is_err(), "Should not emit event for pre-handshake close");
 }
 
+#[tokio::test]
+async fn test_handshake_timeout_drops_silent_connection() {
+    let (server, listener, addr) = create_server("aa00bb11cc22dd33").await;
+    let server = server.with_handshake_timeout(Duration::from_millis(200));
+
+    let listener = Arc::new(listener);
+    let listener_clone = Arc::clone(&listener);
+    let server = Arc::new(Mutex::new(server));
+    let server_clone = Arc::clone(&server);
+
+    let accept_handle = tokio::spawn(async move {
+        let (stream, peer_addr) = listener_clone.accept().await.expect("Failed to accept");
+        server_clone
+            .lock()
+            .await
+            .accept_connection(stream, peer_addr)
+            .await;
+    });
+
+    // Open a WebSocket but never send our handshake
+    let url = format!("ws://{}", addr);
+    let (mut ws, _) = connect_async(&url).await.expect("Failed to connect");
+    let _ = ws.next().await; // server handshake
+    accept_handle.await.expect("Accept task failed");
+
+    let mut guard = server.lock().await;
+    assert_eq!(guard.pending_count(), 1);
+
+    // Still inside the window: nothing expires
+    assert!(guard.expire_pending_handshakes().await.is_empty());
+    assert_eq!(guard.pending_count(), 1);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let expired = guard.expire_pending_handshakes().await;
+    assert_eq!(expired.len(), 1);
+    assert_eq!(guard.pending_count(), 0);
+    assert_eq!(guard.peer_count(), 0);
+    drop(guard);
+
+    // The client sees the connection closed
+    let closed = timeout(Duration::from_secs(2), async {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            }
+        }
+    })
+    .await;
+    assert!(closed.is_ok(), "Client should observe the close");
+}
+
+#[tokio::test]
+async fn test_handshake_timeout_keeps_completed_peers() {
+    let (server, listener, addr) = create_server("aa00bb11cc22dd33").await;
+    let server = server.with_handshake_timeout(Duration::from_millis(100));
+
+    let listener = Arc::new(listener);
+    let listener_clone = Arc::clone(&listener);
+    let server = Arc::new(Mutex::new(server));
+    let server_clone = Arc::clone(&server);
+
+    let accept_handle = tokio::spawn(async move {
+        let (stream, peer_addr) = listener_clone.accept().await.expect("Failed to accept");
+        server_clone
+            .lock()
+            .await
+            .accept_connection(stream, peer_addr)
+            .await;
+    });
+
+    let client = TestClient::connect_and_handshake(addr).await;
+    accept_handle.await.expect("Accept task failed");
+
+    let mut guard = server.lock().await;
+    let event = poll_event_timeout(&mut guard, Duration::from_secs(2))
+        .await
+        .expect("Should receive PeerConnected");
+    assert!(matches!(event, ServerEvent::PeerConnected { .. }));
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(guard.expire_pending_handshakes().await.is_empty());
+    assert_eq!(guard.peer_count(), 1);
+    drop(guard);
+
+    client.close().await;
+}
+
 #[tokio::test]
 async fn test_broadcast_except_by_peer_id() {
     let (server, listener, addr) = create_server("aa00bb11cc22dd33").await;
@@ -744,6 +887,57 @@ async fn test_native_fs_nested_directories() {
     assert_eq!(content, b"# Topic");
 }
 
+#[tokio::test]
+async fn test_native_fs_stat_reports_known_content_hash() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let fs = NativeFs::new(temp_dir.path().to_path_buf());
+
+    use sync_core::fs::{content_hash, FileSystem};
+
+    // Written through this instance - hash known without re-reading
+    fs.write("note.md", b"# Note").await.unwrap();
+    let stat = fs.stat("note.md").await.unwrap();
+    assert_eq!(stat.content_hash, Some(content_hash(b"# Note")));
+
+    // Changed behind its back - hash unknown until read again
+    std::fs::write(temp_dir.path().join("note.md"), b"# Changed externally").unwrap();
+    assert_eq!(fs.stat("note.md").await.unwrap().content_hash, None);
+
+    fs.read("note.md").await.unwrap();
+    let stat = fs.stat("note.md").await.unwrap();
+    assert_eq!(stat.content_hash, Some(content_hash(b"# Changed externally")));
+}
+
+#[tokio::test]
+async fn test_native_fs_list_recursive() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let fs = NativeFs::new(temp_dir.path().to_path_buf());
+
+    use sync_core::fs::FileSystem;
+
+    fs.write("root.md", b"").await.unwrap();
+    fs.write("knowledge/topic.md", b"").await.unwrap();
+    fs.write("knowledge/deep/nested.md", b"").await.unwrap();
+    fs.write("knowledge-old/stale.md", b"").await.unwrap();
+
+    let names = |entries: Vec<sync_core::fs::FileEntry>| {
+        let mut names: Vec<String> = entries.into_iter().map(|e| e.name).collect();
+        names.sort();
+        names
+    };
+
+    let all = fs.list_recursive("").await.expect("List failed");
+    assert_eq!(
+        names(all),
+        vec!["knowledge-old/stale.md", "knowledge/deep/nested.md", "knowledge/topic.md", "root.md"]
+    );
+
+    let scoped = fs.list_recursive("knowledge").await.expect("List failed");
+    assert_eq!(names(scoped), vec!["knowledge/deep/nested.md", "knowledge/topic.md"]);
+
+    assert!(fs.list_recursive("missing").await.unwrap().is_empty());
+}
+
 // ============================================================================
 // Message Size Limit Tests
 // ============================================================================
@@ -0,0 +1,267 @@
+//! Typed errors passed across the WASM boundary.
+//!
+//! Vault failures reach JavaScript as `Error` objects carrying a `kind`
+//! discriminant (and a `path` when one is known), so the plugin can branch on
+//! the failure instead of parsing messages:
+//!
+//! ```text
+//! { kind: "targetExists", message: "Target already exists: b.md", path: "b.md" }
+//! ```
+
+use serde::Serialize;
+use sync_core::document::DocumentError;
+use sync_core::fs::FsError;
+use sync_core::sync_engine::SyncEngineError;
+use sync_core::{PeerError, PeerIdError, VaultError};
+
+/// Category of a failure, serialized as the `kind` field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorKind {
+    /// Vault has no `.sync` directory
+    NotInitialized,
+    /// Path rejected by sync path validation (traversal, not markdown, ...)
+    PathValidation,
+    /// File tree (registry) operation failed
+    TreeOperation,
+    /// Saved registry couldn't be loaded; see `rebuildRegistry`
+    RegistryCorrupted,
+    /// Rename target is already registered
+    TargetExists,
+    /// File to operate on is not registered
+    SourceNotFound,
+    /// Note body is over the vault's size limit; see `setMaxNoteSize`
+    NoteTooLarge,
+    /// File or directory missing on disk
+    NotFound,
+    /// File or directory already present on disk
+    AlreadyExists,
+    /// Any other filesystem failure
+    Filesystem,
+    /// Loro document failure
+    Document,
+    /// Malformed sync message
+    Protocol,
+    /// Caller passed an invalid argument
+    InvalidArgument,
+    /// Connection ID not known to the peer registry
+    UnknownConnection,
+    /// Anything without a more specific kind
+    Other,
+}
+
+/// Error object handed to JavaScript.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WasmError {
+    pub kind: ErrorKind,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl WasmError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    /// Attach the path the failing call operated on, unless the error already names one.
+    pub fn with_path(mut self, path: &str) -> Self {
+        if self.path.is_none() {
+            self.path = Some(path.to_string());
+        }
+        self
+    }
+
+    /// Build a JS `Error` with `kind` (and `path`) set as properties.
+    #[cfg(target_arch = "wasm32")]
+    pub fn into_js(self) -> wasm_bindgen::JsValue {
+        use wasm_bindgen::JsValue;
+
+        let error = js_sys::Error::new(&self.message);
+        let kind = serde_wasm_bindgen::to_value(&self.kind).unwrap_or(JsValue::NULL);
+        js_sys::Reflect::set(&error, &JsValue::from_str("kind"), &kind).ok();
+        if let Some(path) = &self.path {
+            js_sys::Reflect::set(&error, &JsValue::from_str("path"), &JsValue::from_str(path)).ok();
+        }
+        error.into()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<WasmError> for wasm_bindgen::JsValue {
+    fn from(error: WasmError) -> Self {
+        error.into_js()
+    }
+}
+
+impl From<VaultError> for WasmError {
+    fn from(error: VaultError) -> Self {
+        let message = error.to_string();
+        match error {
+            VaultError::Fs(e) => WasmError {
+                message,
+                ..WasmError::from(e)
+            },
+            VaultError::Document(_) => Self::new(ErrorKind::Document, message),
+            VaultError::NotInitialized => Self::new(ErrorKind::NotInitialized, message),
+            VaultError::PathValidation(_) => Self::new(ErrorKind::PathValidation, message),
+            VaultError::TreeOperation(_) => Self::new(ErrorKind::TreeOperation, message),
+            VaultError::RegistryCorrupted(_) => Self::new(ErrorKind::RegistryCorrupted, message),
+            VaultError::TargetExists(path) => {
+                Self::new(ErrorKind::TargetExists, message).with_path(&path)
+            }
+            VaultError::SourceNotFound(path) => {
+                Self::new(ErrorKind::SourceNotFound, message).with_path(&path)
+            }
+            VaultError::NoteTooLarge { path, .. } => {
+                Self::new(ErrorKind::NoteTooLarge, message).with_path(&path)
+            }
+            VaultError::Other(_) => Self::new(ErrorKind::Other, message),
+        }
+    }
+}
+
+impl From<SyncEngineError> for WasmError {
+    fn from(error: SyncEngineError) -> Self {
+        let message = error.to_string();
+        match error {
+            SyncEngineError::Vault(e) => WasmError::from(e),
+            SyncEngineError::Document(_) => Self::new(ErrorKind::Document, message),
+            SyncEngineError::Serialization(_) | SyncEngineError::Deserialization(_) => {
+                Self::new(ErrorKind::Protocol, message)
+            }
+        }
+    }
+}
+
+impl From<FsError> for WasmError {
+    fn from(error: FsError) -> Self {
+        let kind = match error {
+            FsError::NotFound(_) => ErrorKind::NotFound,
+            FsError::AlreadyExists(_) => ErrorKind::AlreadyExists,
+            FsError::IsDirectory(_) | FsError::NotDirectory(_) | FsError::Io(_) => {
+                ErrorKind::Filesystem
+            }
+        };
+        Self::new(kind, error.to_string())
+    }
+}
+
+impl From<DocumentError> for WasmError {
+    fn from(error: DocumentError) -> Self {
+        Self::new(ErrorKind::Document, error.to_string())
+    }
+}
+
+impl From<PeerError> for WasmError {
+    fn from(error: PeerError) -> Self {
+        let kind = match error {
+            PeerError::EmptyId => ErrorKind::InvalidArgument,
+            PeerError::UnknownConnection(_) => ErrorKind::UnknownConnection,
+        };
+        Self::new(kind, error.to_string())
+    }
+}
+
+impl From<PeerIdError> for WasmError {
+    fn from(error: PeerIdError) -> Self {
+        Self::new(ErrorKind::InvalidArgument, format!("Invalid peer ID: {}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sync_core::PathValidationError;
+
+    fn to_json(error: impl Into<WasmError>) -> serde_json::Value {
+        serde_json::to_value(error.into()).unwrap()
+    }
+
+    #[test]
+    fn test_vault_error_kinds() {
+        let cases = [
+            (VaultError::NotInitialized, "notInitialized"),
+            (
+                VaultError::PathValidation(PathValidationError::Traversal),
+                "pathValidation",
+            ),
+            (VaultError::TreeOperation("boom".into()), "treeOperation"),
+            (
+                VaultError::RegistryCorrupted("bad snapshot".into()),
+                "registryCorrupted",
+            ),
+            (VaultError::TargetExists("b.md".into()), "targetExists"),
+            (VaultError::SourceNotFound("a.md".into()), "sourceNotFound"),
+            (
+                VaultError::NoteTooLarge {
+                    path: "big.md".into(),
+                    size: 20,
+                    limit: 10,
+                },
+                "noteTooLarge",
+            ),
+            (VaultError::Fs(FsError::NotFound("a.md".into())), "notFound"),
+            (VaultError::Fs(FsError::AlreadyExists("a.md".into())), "alreadyExists"),
+            (VaultError::Fs(FsError::Io("disk".into())), "filesystem"),
+            (
+                VaultError::Document(DocumentError::Loro("bad".into())),
+                "document",
+            ),
+            (VaultError::Other("misc".into()), "other"),
+        ];
+
+        for (error, kind) in cases {
+            let message = error.to_string();
+            let json = to_json(error);
+            assert_eq!(json["kind"], kind);
+            assert_eq!(json["message"], message);
+        }
+    }
+
+    #[test]
+    fn test_path_included_when_known() {
+        let json = to_json(VaultError::TargetExists("notes/b.md".into()));
+        assert_eq!(json["path"], "notes/b.md");
+
+        let json = to_json(VaultError::SourceNotFound("a.md".into()));
+        assert_eq!(json["path"], "a.md");
+
+        // No path field at all when the error doesn't name one
+        let json = to_json(VaultError::NotInitialized);
+        assert!(json.get("path").is_none());
+    }
+
+    #[test]
+    fn test_with_path_keeps_existing_path() {
+        let error = WasmError::from(VaultError::PathValidation(PathValidationError::NotMarkdown))
+            .with_path("image.png");
+        assert_eq!(error.path.as_deref(), Some("image.png"));
+
+        let error = WasmError::from(VaultError::TargetExists("b.md".into())).with_path("a.md");
+        assert_eq!(error.path.as_deref(), Some("b.md"));
+    }
+
+    #[test]
+    fn test_sync_engine_error_kinds() {
+        let json = to_json(SyncEngineError::Deserialization("truncated".into()));
+        assert_eq!(json["kind"], "protocol");
+
+        let json = to_json(SyncEngineError::Vault(VaultError::SourceNotFound("a.md".into())));
+        assert_eq!(json["kind"], "sourceNotFound");
+        assert_eq!(json["path"], "a.md");
+    }
+
+    #[test]
+    fn test_peer_error_kinds() {
+        assert_eq!(to_json(PeerError::EmptyId)["kind"], "invalidArgument");
+        assert_eq!(
+            to_json(PeerError::UnknownConnection("conn-1".into()))["kind"],
+            "unknownConnection"
+        );
+    }
+}
@@ -187,6 +187,7 @@ impl FileSystem for JsFileSystemBridge {
             mtime_millis: js_stat.mtime as u64,
             size: js_stat.size as u64,
             is_dir: js_stat.is_dir,
+            content_hash: None,
         })
     }
 
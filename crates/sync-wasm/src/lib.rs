@@ -23,8 +23,20 @@
 //!                              WasmVault (exposed to JS)
 //! ```
 //!
+//! `WasmVault` methods reject with a JS `Error` carrying a `kind` discriminant
+//! (see [`ErrorKind`]) and, when known, the `path` involved.
+//!
 //! **Note**: This crate only compiles for `wasm32` targets. When building for native
-//! targets (e.g., during `cargo check --workspace`), this crate provides no exports.
+//! targets (e.g., during `cargo check --workspace`), this crate exports only the error types.
+
+mod error;
+pub use error::{ErrorKind, WasmError};
+
+mod log_filter;
+pub use log_filter::TargetFilter;
+
+mod log_visitor;
+pub use log_visitor::{FieldValue, MessageVisitor};
 
 #[cfg(target_arch = "wasm32")]
 mod fs_bridge;
@@ -52,8 +64,11 @@ mod wasm_impl {
         static LOGGER_CALLBACK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
     }
 
-    /// A tracing layer that invokes a JavaScript callback for each log event.
-    struct JsCallbackLayer;
+    /// A tracing layer that invokes a JavaScript callback for each log event
+    /// whose target passes `filter`.
+    struct JsCallbackLayer {
+        filter: TargetFilter,
+    }
 
     impl<S> tracing_subscriber::Layer<S> for JsCallbackLayer
     where
@@ -64,6 +79,10 @@ mod wasm_impl {
             event: &tracing::Event<'_>,
             _ctx: tracing_subscriber::layer::Context<'_, S>,
         ) {
+            if !self.filter.allows(event.metadata().target()) {
+                return;
+            }
+
             LOGGER_CALLBACK.with(|cb| {
                 if let Some(callback) = cb.borrow().as_ref() {
                     // Extract event data
@@ -71,10 +90,14 @@ mod wasm_impl {
                     let level = metadata.level().as_str();
                     let target = metadata.target();
 
-                    // Build message from event fields
+                    // Build message and structured fields from event fields
                     let mut visitor = MessageVisitor::default();
                     event.record(&mut visitor);
-                    let message = visitor.message;
+                    let fields = js_sys::Object::new();
+                    for (name, value) in visitor.fields() {
+                        let _ = js_sys::Reflect::set(&fields, &(*name).into(), &field_to_js(value));
+                    }
+                    let message = visitor.finish();
 
                     // Get timestamp in milliseconds
                     let timestamp = web_time::SystemTime::now()
@@ -87,6 +110,7 @@ mod wasm_impl {
                     let _ = js_sys::Reflect::set(&js_event, &"level".into(), &level.into());
                     let _ = js_sys::Reflect::set(&js_event, &"target".into(), &target.into());
                     let _ = js_sys::Reflect::set(&js_event, &"message".into(), &message.into());
+                    let _ = js_sys::Reflect::set(&js_event, &"fields".into(), &fields);
                     let _ = js_sys::Reflect::set(&js_event, &"timestamp".into(), &timestamp.into());
 
                     // Call the JavaScript callback
@@ -96,36 +120,14 @@ mod wasm_impl {
         }
     }
 
-    /// Visitor to extract message from tracing event fields
-    #[derive(Default)]
-    struct MessageVisitor {
-        message: String,
-    }
-
-    impl tracing::field::Visit for MessageVisitor {
-        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
-            if field.name() == "message" {
-                self.message = format!("{:?}", value);
-            } else if self.message.is_empty() {
-                // Build message from all fields if no explicit message
-                if !self.message.is_empty() {
-                    self.message.push_str(", ");
-                }
-                self.message.push_str(&format!("{}={:?}", field.name(), value));
-            } else {
-                // Append additional fields
-                self.message.push_str(&format!(" {}={:?}", field.name(), value));
-            }
-        }
-
-        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
-            if field.name() == "message" {
-                self.message = value.to_string();
-            } else if self.message.is_empty() {
-                self.message = format!("{}={}", field.name(), value);
-            } else {
-                self.message.push_str(&format!(" {}={}", field.name(), value));
-            }
+    /// Convert a field value to the matching JS type (64-bit integers become numbers).
+    fn field_to_js(value: &FieldValue) -> JsValue {
+        match value {
+            FieldValue::Bool(value) => JsValue::from_bool(*value),
+            FieldValue::I64(value) => JsValue::from_f64(*value as f64),
+            FieldValue::U64(value) => JsValue::from_f64(*value as f64),
+            FieldValue::F64(value) => JsValue::from_f64(*value),
+            FieldValue::Str(value) => JsValue::from_str(value),
         }
     }
 
@@ -138,14 +140,32 @@ mod wasm_impl {
         has_logger: bool,
     }
 
+    /// Read the logger target filter (`allowedTargets`/`blockedTargets`) from the init config.
+    fn read_log_filter(config: &js_sys::Object) -> TargetFilter {
+        let prefixes = |key: &str| -> Vec<String> {
+            js_sys::Reflect::get(config, &key.into())
+                .ok()
+                .filter(|v| !v.is_undefined())
+                .and_then(|v| serde_wasm_bindgen::from_value(v).ok())
+                .unwrap_or_default()
+        };
+        TargetFilter {
+            allowed: prefixes("allowedTargets"),
+            blocked: prefixes("blockedTargets"),
+        }
+    }
+
     /// Initialize the WASM module (sets up panic hook and tracing for better debugging).
     ///
     /// Accepts an optional configuration object:
     /// - `init()` - console-only logging (default)
     /// - `init({})` - console-only logging
     /// - `init({ logger: (event) => {...} })` - callback + console logging
+    /// - `init({ logger, allowedTargets: ["sync_core"], blockedTargets: ["sync_core::swim"] })` -
+    ///   only forward events whose target is under an allowed prefix and no blocked one
     ///
-    /// The logger callback receives events with: `{ level, target, message, timestamp }`
+    /// The logger callback receives events with: `{ level, target, message, fields, timestamp }`,
+    /// where `fields` holds the event's structured fields other than the message.
     #[wasm_bindgen]
     pub fn init(config: Option<js_sys::Object>) {
         console_error_panic_hook::set_once();
@@ -177,8 +197,10 @@ mod wasm_impl {
                     .build(),
             );
 
+            let filter = config.as_ref().map(read_log_filter).unwrap_or_default();
+
             let subscriber = tracing_subscriber::registry()
-                .with(JsCallbackLayer)
+                .with(JsCallbackLayer { filter })
                 .with(console_layer);
 
             tracing::subscriber::set_global_default(subscriber).ok();
@@ -215,6 +237,41 @@ mod wasm_impl {
         sync_core::PeerId::generate().to_string()
     }
 
+    /// Explain how a note reference normalizes.
+    ///
+    /// Returns the normalized `path` and `name`, which decorations were stripped
+    /// (`hadWikiBrackets`, `hadMemoryPrefix`, `hadMdExtension`), and `pathError`
+    /// if the resulting path is unsafe.
+    #[wasm_bindgen(js_name = validateNoteReference)]
+    pub fn validate_note_reference(note_ref: &str) -> Result<JsValue, JsValue> {
+        let diagnostics = obsidian_fs::diagnose_note_reference(note_ref);
+        serde_wasm_bindgen::to_value(&diagnostics).map_err(JsValue::from)
+    }
+
+    /// A note passed to `parseWikiLinksBatch`.
+    #[derive(Deserialize)]
+    struct NoteContent {
+        path: String,
+        content: String,
+    }
+
+    /// Parse wiki links in many notes with a single call.
+    ///
+    /// Takes `{ path, content }[]` and returns an object mapping each path to
+    /// its links, the same links `wiki_links::parse_wiki_links` finds per note.
+    #[wasm_bindgen(js_name = parseWikiLinksBatch)]
+    pub fn parse_wiki_links_batch(entries: JsValue) -> Result<JsValue, JsValue> {
+        let entries: Vec<NoteContent> = serde_wasm_bindgen::from_value(entries)?;
+        let links = wiki_links::parse_wiki_links_batch(
+            entries
+                .iter()
+                .map(|entry| (entry.path.as_str(), entry.content.as_str())),
+        );
+        // Use serialize_maps_as_objects to return a plain JS object instead of Map
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        links.serialize(&serializer).map_err(JsValue::from)
+    }
+
     #[wasm_bindgen]
     extern "C" {
         #[wasm_bindgen(js_namespace = console)]
@@ -246,6 +303,17 @@ mod wasm_impl {
         }
     }
 
+    /// Apply an optional echo-suppression flag TTL (milliseconds) from JS.
+    fn with_flag_ttl(
+        vault: sync_core::Vault<fs_bridge::JsFileSystemBridge>,
+        flag_ttl_ms: Option<f64>,
+    ) -> sync_core::Vault<fs_bridge::JsFileSystemBridge> {
+        match flag_ttl_ms {
+            Some(ms) if ms > 0.0 => vault.with_flag_ttl(std::time::Duration::from_millis(ms as u64)),
+            _ => vault,
+        }
+    }
+
     /// Vault manager exposed to TypeScript.
     ///
     /// Wraps the core `Vault` and provides async methods that work with JS Promises.
@@ -260,17 +328,22 @@ mod wasm_impl {
         ///
         /// Call this when the user clicks "Initialize Sync" for the first time.
         /// The `peer_id` should be a hex string from `generatePeerId()` or a legacy UUID.
+        /// `flag_ttl_ms` overrides how long echo-suppression flags stay valid.
         #[wasm_bindgen]
-        pub async fn init(fs: fs_bridge::JsFileSystemBridge, peer_id: String) -> Result<WasmVault, JsError> {
+        pub async fn init(
+            fs: fs_bridge::JsFileSystemBridge,
+            peer_id: String,
+            flag_ttl_ms: Option<f64>,
+        ) -> Result<WasmVault, JsValue> {
             let peer_id: sync_core::PeerId = peer_id
                 .parse()
-                .map_err(|e| JsError::new(&format!("Invalid peer ID: {}", e)))?;
+                .map_err(WasmError::from)?;
 
             let inner = sync_core::Vault::init(fs, peer_id)
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(WasmError::from)?;
 
-            Ok(WasmVault { inner })
+            Ok(WasmVault { inner: with_flag_ttl(inner, flag_ttl_ms) })
         }
 
         /// Load an existing vault and reconcile with filesystem.
@@ -278,17 +351,40 @@ mod wasm_impl {
         /// Call this on plugin startup if vault is already initialized.
         /// Reconciliation detects files added/modified/deleted while plugin was off.
         /// The `peer_id` should be a hex string from `generatePeerId()` or a legacy UUID.
+        /// `flag_ttl_ms` overrides how long echo-suppression flags stay valid.
         ///
         /// Returns a report of what was reconciled.
         #[wasm_bindgen]
-        pub async fn load(fs: fs_bridge::JsFileSystemBridge, peer_id: String) -> Result<WasmVault, JsError> {
+        pub async fn load(
+            fs: fs_bridge::JsFileSystemBridge,
+            peer_id: String,
+            flag_ttl_ms: Option<f64>,
+        ) -> Result<WasmVault, JsValue> {
             let peer_id: sync_core::PeerId = peer_id
                 .parse()
-                .map_err(|e| JsError::new(&format!("Invalid peer ID: {}", e)))?;
+                .map_err(WasmError::from)?;
 
             let inner = sync_core::Vault::load(fs, peer_id)
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(WasmError::from)?;
+
+            Ok(WasmVault { inner: with_flag_ttl(inner, flag_ttl_ms) })
+        }
+
+        /// Rebuild the file registry from the `.loro` documents and markdown on disk.
+        ///
+        /// Use when `load()` fails with a `registryCorrupted` error. The unreadable
+        /// registry is kept at `.sync/registry.loro.corrupt`. File nodes that can
+        /// still be read keep their IDs, so peers don't see them as duplicates.
+        #[wasm_bindgen(js_name = rebuildRegistry)]
+        pub async fn rebuild_registry(fs: fs_bridge::JsFileSystemBridge, peer_id: String) -> Result<WasmVault, JsValue> {
+            let peer_id: sync_core::PeerId = peer_id
+                .parse()
+                .map_err(WasmError::from)?;
+
+            let inner = sync_core::Vault::rebuild_registry(fs, peer_id)
+                .await
+                .map_err(WasmError::from)?;
 
             Ok(WasmVault { inner })
         }
@@ -298,19 +394,20 @@ mod wasm_impl {
         /// This is automatically called during `load()`, but can be called again
         /// if needed (e.g., after detecting external filesystem changes).
         #[wasm_bindgen]
-        pub async fn reconcile(&self) -> Result<JsValue, JsError> {
+        pub async fn reconcile(&self) -> Result<JsValue, JsValue> {
             let report = self.inner
                 .reconcile()
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(WasmError::from)?;
             
             let js_report = ReconcileReportJs {
                 indexed: report.indexed,
                 reindexed: report.reindexed,
                 orphaned: report.orphaned,
+                restored: report.restored,
             };
             
-            serde_wasm_bindgen::to_value(&js_report).map_err(|e| JsError::new(&e.to_string()))
+            serde_wasm_bindgen::to_value(&js_report).map_err(JsValue::from)
         }
 
         /// Get our peer ID.
@@ -321,11 +418,23 @@ mod wasm_impl {
 
         /// Check if vault is initialized (has .sync directory).
         #[wasm_bindgen(js_name = isInitialized)]
-        pub async fn is_initialized(&self) -> Result<bool, JsError> {
+        pub async fn is_initialized(&self) -> Result<bool, JsValue> {
             self.inner
                 .is_initialized()
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(|e| WasmError::from(e).into_js())
+        }
+
+        /// Write any in-memory document changes and the registry to disk.
+        ///
+        /// The plugin should call this on unload, before `free()`, so no edits
+        /// are lost. Returns the number of documents written.
+        #[wasm_bindgen]
+        pub async fn flush(&self) -> Result<usize, JsValue> {
+            self.inner
+                .flush()
+                .await
+                .map_err(|e| WasmError::from(e).into_js())
         }
 
         /// Handle a file change event from Obsidian.
@@ -333,11 +442,11 @@ mod wasm_impl {
         /// Call this when Obsidian fires modify/create events for markdown files.
         /// Updates the Loro document to match the file content.
         #[wasm_bindgen(js_name = onFileChanged)]
-        pub async fn on_file_changed(&self, path: &str) -> Result<(), JsError> {
+        pub async fn on_file_changed(&self, path: &str) -> Result<(), JsValue> {
             self.inner
                 .on_file_changed(path)
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(|e| WasmError::from(e).with_path(path).into_js())
         }
 
         /// Get the version vector for a document as encoded bytes.
@@ -346,12 +455,12 @@ mod wasm_impl {
         /// Use this to track the synced version and detect if subsequent
         /// modifications are purely from sync or include local edits.
         #[wasm_bindgen(js_name = getDocumentVersion)]
-        pub async fn get_document_version(&self, path: &str) -> Result<JsValue, JsError> {
+        pub async fn get_document_version(&self, path: &str) -> Result<JsValue, JsValue> {
             let version = self
                 .inner
                 .get_document_version(path)
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(|e| WasmError::from(e).with_path(path))?;
 
             match version {
                 Some(bytes) => {
@@ -372,16 +481,26 @@ mod wasm_impl {
             sync_core::Vault::<sync_core::fs::InMemoryFs>::version_includes(current_version, synced_version)
         }
 
+        /// Check whether a file modification includes local edits.
+        ///
+        /// Pass the document version after `onFileChanged`. Returns false if the
+        /// change only contains what sync last wrote for this path - an exact
+        /// alternative to `consumeSyncFlag` that doesn't depend on the flag TTL.
+        #[wasm_bindgen(js_name = isModificationLocal)]
+        pub fn is_modification_local(&self, path: &str, new_version: &[u8]) -> bool {
+            self.inner.is_modification_local(path, new_version)
+        }
+
         /// List all markdown files in the vault.
         #[wasm_bindgen(js_name = listFiles)]
-        pub async fn list_files(&self) -> Result<JsValue, JsError> {
+        pub async fn list_files(&self) -> Result<JsValue, JsValue> {
             let files = self
                 .inner
                 .list_files()
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(WasmError::from)?;
 
-            serde_wasm_bindgen::to_value(&files).map_err(|e| JsError::new(&e.to_string()))
+            serde_wasm_bindgen::to_value(&files).map_err(JsValue::from)
         }
 
         // ========== Sync Protocol Methods ==========
@@ -391,11 +510,11 @@ mod wasm_impl {
         /// Returns serialized bytes containing our version vectors for all documents.
         /// Send this to the peer immediately after connection.
         #[wasm_bindgen(js_name = prepareSyncRequest)]
-        pub async fn prepare_sync_request(&self) -> Result<Vec<u8>, JsError> {
+        pub async fn prepare_sync_request(&self) -> Result<Vec<u8>, JsValue> {
             self.inner
                 .prepare_sync_request()
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(|e| WasmError::from(e).into_js())
         }
 
         /// Process an incoming sync message from a peer.
@@ -404,18 +523,25 @@ mod wasm_impl {
         /// - Optional response bytes to send back to the peer
         /// - Array of file paths that were modified (need to be saved/reloaded)
         ///
-        /// Call this when you receive a message from a peer.
+        /// Call this when you receive a message from a peer. Pass the sender's
+        /// peer ID so the versions it reports are tracked for `documentSyncPeers`.
         #[wasm_bindgen(js_name = processSyncMessage)]
-        pub async fn process_sync_message(&self, data: &[u8]) -> Result<JsValue, JsError> {
+        pub async fn process_sync_message(
+            &self,
+            data: &[u8],
+            from_peer_id: Option<String>,
+        ) -> Result<JsValue, JsValue> {
             log(&format!("processSyncMessage: received {} bytes", data.len()));
 
-            let (response, modified_paths) = self
-                .inner
-                .process_sync_message(data)
-                .await
+            let from = from_peer_id.and_then(|id| id.parse::<sync_core::PeerId>().ok());
+            let result = match from {
+                Some(from) => self.inner.process_sync_message_from(from, data).await,
+                None => self.inner.process_sync_message(data).await,
+            };
+            let (response, modified_paths) = result
                 .map_err(|e| {
                     error(&format!("processSyncMessage error: {}", e));
-                    JsError::new(&e.to_string())
+                    WasmError::from(e)
                 })?;
 
             log(&format!("processSyncMessage: response={}, modified={:?}",
@@ -429,7 +555,7 @@ mod wasm_impl {
             };
 
             serde_wasm_bindgen::to_value(&result)
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(JsValue::from)
         }
 
         /// Prepare a document update to broadcast after a local file change.
@@ -439,12 +565,12 @@ mod wasm_impl {
         ///
         /// Call this after `onFileChanged` to get the update to broadcast.
         #[wasm_bindgen(js_name = prepareDocumentUpdate)]
-        pub async fn prepare_document_update(&self, path: &str) -> Result<JsValue, JsError> {
+        pub async fn prepare_document_update(&self, path: &str) -> Result<JsValue, JsValue> {
             let update = self
                 .inner
                 .prepare_document_update(path)
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(|e| WasmError::from(e).with_path(path))?;
 
             match update {
                 Some(bytes) => {
@@ -462,11 +588,11 @@ mod wasm_impl {
         /// Call this when Obsidian fires a delete event for a markdown file.
         /// The deletion is tracked in the registry LoroTree and syncs to peers.
         #[wasm_bindgen(js_name = deleteFile)]
-        pub async fn delete_file(&self, path: &str) -> Result<(), JsError> {
+        pub async fn delete_file(&self, path: &str) -> Result<(), JsValue> {
             self.inner
                 .delete_file(path)
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(|e| WasmError::from(e).with_path(path).into_js())
         }
 
         /// Rename/move a file in the tree (CRDT operation).
@@ -474,11 +600,84 @@ mod wasm_impl {
         /// Call this when Obsidian fires a rename event for a markdown file.
         /// The rename is tracked in the registry LoroTree and syncs to peers.
         #[wasm_bindgen(js_name = renameFile)]
-        pub async fn rename_file(&self, old_path: &str, new_path: &str) -> Result<(), JsError> {
+        pub async fn rename_file(&self, old_path: &str, new_path: &str) -> Result<(), JsValue> {
             self.inner
                 .rename_file(old_path, new_path)
                 .await
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(|e| WasmError::from(e).into_js())
+        }
+
+        /// Pin or unpin a file.
+        ///
+        /// Pinned files keep their CRDT content during reconciliation: external
+        /// markdown edits are reverted instead of re-indexed.
+        #[wasm_bindgen(js_name = setPinned)]
+        pub async fn set_pinned(&self, path: &str, pinned: bool) -> Result<(), JsValue> {
+            self.inner
+                .set_pinned(path, pinned)
+                .await
+                .map_err(|e| WasmError::from(e).with_path(path).into_js())
+        }
+
+        /// Check if a file is pinned.
+        #[wasm_bindgen(js_name = isPinned)]
+        pub fn is_pinned(&self, path: &str) -> bool {
+            self.inner.is_pinned(path)
+        }
+
+        /// Enable or disable rewriting wiki-links when a peer renames a note.
+        ///
+        /// Off by default. Obsidian already updates links for renames made in
+        /// the app, so only enable this on one peer.
+        #[wasm_bindgen(js_name = setRewriteLinksOnRename)]
+        pub fn set_rewrite_links_on_rename(&self, enabled: bool) {
+            self.inner.set_rewrite_links_on_rename(enabled);
+        }
+
+        /// Choose what happens when a peer deletes a note with edits it hadn't seen.
+        ///
+        /// Off by default: the note is kept and re-registered. When enabled the
+        /// deletion wins and the unseen edits are discarded.
+        #[wasm_bindgen(js_name = setDeleteWins)]
+        pub fn set_delete_wins(&self, enabled: bool) {
+            self.inner.set_delete_edit_policy(if enabled {
+                sync_core::DeleteEditPolicy::DeleteWins
+            } else {
+                sync_core::DeleteEditPolicy::EditWins
+            });
+        }
+
+        /// Set the largest note body, in bytes, that is tracked and synced.
+        ///
+        /// Changes to bigger notes throw an error of kind `noteTooLarge`.
+        #[wasm_bindgen(js_name = setMaxNoteSize)]
+        pub fn set_max_note_size(&self, bytes: usize) {
+            self.inner.set_max_note_size(bytes);
+        }
+
+        /// Limit sync to paths matching `include` globs and no `exclude` glob.
+        ///
+        /// An empty `include` list means the whole vault. The scope is saved
+        /// to `.sync/scope.json` and restored when the vault loads.
+        #[wasm_bindgen(js_name = setSyncScope)]
+        pub async fn set_sync_scope(
+            &self,
+            include: Vec<String>,
+            exclude: Vec<String>,
+        ) -> Result<(), JsValue> {
+            self.inner
+                .set_sync_scope(sync_core::SyncScope { include, exclude })
+                .await
+                .map_err(|e| WasmError::from(e).into_js())
+        }
+
+        /// Check if changes to a file are synced.
+        ///
+        /// Returns false for non-markdown files and paths outside the sync scope,
+        /// so file watcher handlers can skip calling into WASM for them.
+        #[wasm_bindgen(js_name = isPathSynced)]
+        pub fn is_path_synced(&self, path: &str) -> bool {
+            self.inner.is_path_synced(path)
         }
 
         /// Check if a file is deleted in the tree.
@@ -502,11 +701,11 @@ mod wasm_impl {
         ///
         /// Call this after `deleteFile` to get the message to broadcast.
         #[wasm_bindgen(js_name = prepareFileDeleted)]
-        pub fn prepare_file_deleted(&self, path: &str) -> Result<JsValue, JsError> {
+        pub fn prepare_file_deleted(&self, path: &str) -> Result<JsValue, JsValue> {
             let bytes = self
                 .inner
                 .prepare_file_deleted(path)
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(|e| WasmError::from(e).with_path(path))?;
 
             let array = js_sys::Uint8Array::from(bytes.as_slice());
             Ok(array.into())
@@ -516,11 +715,11 @@ mod wasm_impl {
         ///
         /// Call this after `renameFile` to get the message to broadcast.
         #[wasm_bindgen(js_name = prepareFileRenamed)]
-        pub fn prepare_file_renamed(&self, old_path: &str, new_path: &str) -> Result<JsValue, JsError> {
+        pub fn prepare_file_renamed(&self, old_path: &str, new_path: &str) -> Result<JsValue, JsValue> {
             let bytes = self
                 .inner
                 .prepare_file_renamed(old_path, new_path)
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(WasmError::from)?;
 
             let array = js_sys::Uint8Array::from(bytes.as_slice());
             Ok(array.into())
@@ -532,22 +731,32 @@ mod wasm_impl {
         ///
         /// Returns an object mapping peer ID hex strings to counter values.
         #[wasm_bindgen(js_name = getRegistryVersion)]
-        pub fn get_registry_version(&self) -> Result<JsValue, JsError> {
+        pub fn get_registry_version(&self) -> Result<JsValue, JsValue> {
             let version = self.inner.get_registry_version();
             // Use serialize_maps_as_objects to return a plain JS object instead of Map
             let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
             version.serialize(&serializer)
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(JsValue::from)
         }
 
         /// Get registry oplog statistics.
         ///
         /// Returns `{ changeCount, opCount }`.
         #[wasm_bindgen(js_name = getRegistryStats)]
-        pub fn get_registry_stats(&self) -> Result<JsValue, JsError> {
+        pub fn get_registry_stats(&self) -> Result<JsValue, JsValue> {
             let stats = self.inner.get_registry_stats();
             serde_wasm_bindgen::to_value(&stats)
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(JsValue::from)
+        }
+
+        /// Get sync flag lifecycle counters for diagnosing echo loops.
+        ///
+        /// Returns `{ flagsSet, consumedValid, consumedExpired, expiredUnconsumed }`.
+        #[wasm_bindgen(js_name = getSyncFlagStats)]
+        pub fn get_sync_flag_stats(&self) -> Result<JsValue, JsValue> {
+            let stats = self.inner.sync_flag_stats();
+            serde_wasm_bindgen::to_value(&stats)
+                .map_err(JsValue::from)
         }
 
         /// Get cheap metadata from the .loro blob header.
@@ -555,12 +764,12 @@ mod wasm_impl {
         /// Returns blob metadata (version vectors, timestamps, change count) without
         /// loading the full document. Returns `null` if the document doesn't exist.
         #[wasm_bindgen(js_name = getDocumentBlobMeta)]
-        pub async fn get_document_blob_meta(&self, path: &str) -> Result<JsValue, JsError> {
+        pub async fn get_document_blob_meta(&self, path: &str) -> Result<JsValue, JsValue> {
             let meta = self.inner.get_document_blob_meta(path).await
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(|e| WasmError::from(e).with_path(path))?;
             match meta {
                 Some(m) => serde_wasm_bindgen::to_value(&m)
-                    .map_err(|e| JsError::new(&e.to_string())),
+                    .map_err(JsValue::from),
                 None => Ok(JsValue::NULL),
             }
         }
@@ -570,16 +779,46 @@ mod wasm_impl {
         /// Returns content metadata including body length, frontmatter status, and doc_id.
         /// Returns `null` if the document doesn't exist.
         #[wasm_bindgen(js_name = getDocumentInfo)]
-        pub async fn get_document_info(&self, path: &str) -> Result<JsValue, JsError> {
+        pub async fn get_document_info(&self, path: &str) -> Result<JsValue, JsValue> {
             let info = self.inner.get_document_info(path).await
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(|e| WasmError::from(e).with_path(path))?;
             match info {
                 Some(i) => serde_wasm_bindgen::to_value(&i)
-                    .map_err(|e| JsError::new(&e.to_string())),
+                    .map_err(JsValue::from),
                 None => Ok(JsValue::NULL),
             }
         }
 
+        /// Get the recorded sync conflicts for a note, oldest first.
+        ///
+        /// Returns an array of `{ path, peers, timestamp, resolution, linesAdded, linesRemoved }`
+        /// (empty if the note never had a conflict).
+        #[wasm_bindgen(js_name = getConflicts)]
+        pub async fn get_conflicts(&self, path: &str) -> Result<JsValue, JsValue> {
+            let records = self.inner.conflict_history_for(path).await
+                .map_err(|e| WasmError::from(e).with_path(path))?;
+            serde_wasm_bindgen::to_value(&records)
+                .map_err(JsValue::from)
+        }
+
+        /// Whether each connected peer has our current version of a note.
+        ///
+        /// Returns an array of `{ peerId, upToDate }` sorted by peer ID, based on the
+        /// versions peers last reported when syncing (empty if the note doesn't exist).
+        #[wasm_bindgen(js_name = documentSyncPeers)]
+        pub async fn document_sync_peers(&self, path: &str) -> Result<JsValue, JsValue> {
+            let peers: Vec<DocumentSyncPeer> = self.inner.document_sync_peers(path).await
+                .map_err(|e| WasmError::from(e).with_path(path))?
+                .into_iter()
+                .map(|(peer_id, up_to_date)| DocumentSyncPeer {
+                    peer_id: peer_id.to_string(),
+                    up_to_date,
+                })
+                .collect();
+            serde_wasm_bindgen::to_value(&peers)
+                .map_err(JsValue::from)
+        }
+
         // ========== Peer Management Methods ==========
 
         /// Notify that a peer has connected (call after handshake completes).
@@ -592,30 +831,30 @@ mod wasm_impl {
             peer_id: String,
             address: String,
             direction: String,
-        ) -> Result<JsValue, JsError> {
+        ) -> Result<JsValue, JsValue> {
             let dir = match direction.as_str() {
                 "incoming" => sync_core::peers::ConnectionDirection::Incoming,
                 "outgoing" => sync_core::peers::ConnectionDirection::Outgoing,
-                _ => return Err(JsError::new("direction must be 'incoming' or 'outgoing'")),
+                _ => return Err(WasmError::new(ErrorKind::InvalidArgument, "direction must be 'incoming' or 'outgoing'").into()),
             };
 
             let peer = self.inner.peer_connected(peer_id, address, dir)
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(WasmError::from)?;
             serde_wasm_bindgen::to_value(&peer)
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(JsValue::from)
         }
 
         /// Notify that a peer has disconnected.
         ///
         /// Updates the registry and emits a `PeerDisconnected` event if known.
         #[wasm_bindgen(js_name = peerDisconnected)]
-        pub fn peer_disconnected(&self, peer_id: String, reason: String) -> Result<(), JsError> {
+        pub fn peer_disconnected(&self, peer_id: String, reason: String) -> Result<(), JsValue> {
             let reason = match reason.as_str() {
                 "userRequested" => sync_core::peers::DisconnectReason::UserRequested,
                 "networkError" => sync_core::peers::DisconnectReason::NetworkError,
                 "remoteClosed" => sync_core::peers::DisconnectReason::RemoteClosed,
                 "protocolError" => sync_core::peers::DisconnectReason::ProtocolError,
-                _ => return Err(JsError::new("reason must be 'userRequested', 'networkError', 'remoteClosed', or 'protocolError'")),
+                _ => return Err(WasmError::new(ErrorKind::InvalidArgument, "reason must be 'userRequested', 'networkError', 'remoteClosed', or 'protocolError'").into()),
             };
             self.inner.peer_disconnected(&peer_id, reason);
             Ok(())
@@ -629,16 +868,16 @@ mod wasm_impl {
             connection_id: String,
             address: String,
             direction: String,
-        ) -> Result<JsValue, JsError> {
+        ) -> Result<JsValue, JsValue> {
             let dir = match direction.as_str() {
                 "incoming" => sync_core::peers::ConnectionDirection::Incoming,
                 "outgoing" => sync_core::peers::ConnectionDirection::Outgoing,
-                _ => return Err(JsError::new("direction must be 'incoming' or 'outgoing'")),
+                _ => return Err(WasmError::new(ErrorKind::InvalidArgument, "direction must be 'incoming' or 'outgoing'").into()),
             };
 
             let peer = self.inner.peer_connecting(connection_id, address, dir);
             serde_wasm_bindgen::to_value(&peer)
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(JsValue::from)
         }
 
         /// Called when handshake completes. Maps connection_id to real peer_id.
@@ -648,20 +887,29 @@ mod wasm_impl {
             &self,
             connection_id: String,
             peer_id: String,
-        ) -> Result<JsValue, JsError> {
+        ) -> Result<JsValue, JsValue> {
             let peer = self.inner.peer_handshake_complete(&connection_id, peer_id)
-                .map_err(|e| JsError::new(&e.to_string()))?;
+                .map_err(WasmError::from)?;
             serde_wasm_bindgen::to_value(&peer)
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(JsValue::from)
+        }
+
+        /// Drop connections still awaiting a handshake after `timeoutMs`.
+        ///
+        /// Returns the expired connection IDs. The caller should close those
+        /// sockets; they are already removed from the peer registry.
+        #[wasm_bindgen(js_name = expirePendingHandshakes)]
+        pub fn expire_pending_handshakes(&self, timeout_ms: f64) -> Vec<String> {
+            self.inner.expire_pending_handshakes(timeout_ms)
         }
 
         /// Get peer by connection ID (for pre-handshake lookups).
         /// Returns null if not found.
         #[wasm_bindgen(js_name = getPeerByConnectionId)]
-        pub fn get_peer_by_connection_id(&self, connection_id: String) -> Result<JsValue, JsError> {
+        pub fn get_peer_by_connection_id(&self, connection_id: String) -> Result<JsValue, JsValue> {
             match self.inner.get_peer_by_connection_id(&connection_id) {
                 Some(peer) => serde_wasm_bindgen::to_value(&peer)
-                    .map_err(|e| JsError::new(&e.to_string())),
+                    .map_err(JsValue::from),
                 None => Ok(JsValue::NULL),
             }
         }
@@ -674,30 +922,30 @@ mod wasm_impl {
 
         /// Get all peers seen this session (connected and disconnected).
         #[wasm_bindgen(js_name = getKnownPeers)]
-        pub fn get_known_peers(&self) -> Result<JsValue, JsError> {
+        pub fn get_known_peers(&self) -> Result<JsValue, JsValue> {
             let peers = self.inner.get_known_peers();
             serde_wasm_bindgen::to_value(&peers)
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(JsValue::from)
         }
 
         /// Get info for a specific peer.
         ///
         /// Returns `null` if the peer is not known.
         #[wasm_bindgen(js_name = getPeerInfo)]
-        pub fn get_peer_info(&self, peer_id: String) -> Result<JsValue, JsError> {
+        pub fn get_peer_info(&self, peer_id: String) -> Result<JsValue, JsValue> {
             match self.inner.get_peer_info(&peer_id) {
                 Some(peer) => serde_wasm_bindgen::to_value(&peer)
-                    .map_err(|e| JsError::new(&e.to_string())),
+                    .map_err(JsValue::from),
                 None => Ok(JsValue::NULL),
             }
         }
 
         /// Get currently connected peers only.
         #[wasm_bindgen(js_name = getConnectedPeers)]
-        pub fn get_connected_peers(&self) -> Result<JsValue, JsError> {
+        pub fn get_connected_peers(&self) -> Result<JsValue, JsValue> {
             let peers = self.inner.get_connected_peers();
             serde_wasm_bindgen::to_value(&peers)
-                .map_err(|e| JsError::new(&e.to_string()))
+                .map_err(JsValue::from)
         }
 
         // ========== Sync Event Subscriptions ==========
@@ -731,6 +979,14 @@ mod wasm_impl {
         modified_paths: Vec<String>,
     }
 
+    /// A connected peer and whether it has our version of a note
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct DocumentSyncPeer {
+        peer_id: String,
+        up_to_date: bool,
+    }
+
     /// Report from reconciliation for JS
     #[derive(serde::Serialize)]
     #[serde(rename_all = "camelCase")]
@@ -741,6 +997,8 @@ mod wasm_impl {
         reindexed: Vec<String>,
         /// Orphaned .loro file hashes
         orphaned: Vec<String>,
+        /// Pinned files whose markdown was rewritten from the CRDT
+        restored: Vec<String>,
     }
 
     /// Serialize Option<Vec<u8>> as null or Uint8Array-compatible array
@@ -827,6 +1085,21 @@ mod wasm_impl {
                 .map_err(|e| JsError::new(&e.to_string()))
         }
 
+        /// Validate received gossip before processing it.
+        ///
+        /// Malformed entries and stale incarnations are rejected individually
+        /// instead of failing the whole batch.
+        ///
+        /// @param gossipJson - JSON array of received gossip updates
+        /// @returns JSON string: `{ accepted: GossipUpdate[], rejected: RejectedGossip[] }`
+        #[wasm_bindgen(js_name = validateGossip)]
+        pub fn validate_gossip(&self, gossip_json: String) -> Result<String, JsError> {
+            let validation = self.inner.borrow().validate_gossip(&gossip_json)
+                .map_err(|e| JsError::new(&format!("Invalid gossip JSON: {}", e)))?;
+            serde_json::to_string(&validation)
+                .map_err(|e| JsError::new(&e.to_string()))
+        }
+
         /// Generate full gossip for initial sync with a new peer.
         ///
         /// @returns JSON array of GossipUpdate objects (Alive for all known members)
@@ -847,6 +1120,56 @@ mod wasm_impl {
                 .map_err(|e: serde_json::Error| JsError::new(&e.to_string()))
         }
 
+        /// Tune SWIM probe timing; the defaults suit a LAN.
+        ///
+        /// @param probeIntervalMs - Time between probes of a random peer (default: 1000)
+        /// @param probeTimeoutMs - Wait for a direct ack before probing indirectly (default: 500)
+        /// @param indirectProbes - Peers asked to probe a silent peer indirectly (default: 3)
+        /// @param suspicionMultiplier - Probe intervals a suspected peer has to refute
+        /// before it's declared dead (default: 5)
+        #[wasm_bindgen(js_name = setSwimConfig)]
+        pub fn set_swim_config(
+            &self,
+            probe_interval_ms: u32,
+            probe_timeout_ms: u32,
+            indirect_probes: usize,
+            suspicion_multiplier: u32,
+        ) {
+            self.inner.borrow_mut().set_config(sync_core::swim::SwimConfig {
+                probe_interval: std::time::Duration::from_millis(probe_interval_ms.into()),
+                probe_timeout: std::time::Duration::from_millis(probe_timeout_ms.into()),
+                indirect_probes,
+                suspicion_multiplier,
+            });
+        }
+
+        /// Milliseconds before a suspected peer is declared dead, from the SWIM config.
+        #[wasm_bindgen(js_name = suspicionTimeoutMs)]
+        pub fn suspicion_timeout_ms(&self) -> f64 {
+            self.inner.borrow().config().suspicion_timeout().as_millis() as f64
+        }
+
+        /// Pick the configured number of alive members to probe `targetPeerId` indirectly.
+        ///
+        /// @returns array of member objects, excluding the target
+        #[wasm_bindgen(js_name = pickIndirectProbers)]
+        pub fn pick_indirect_probers(&self, target_peer_id: String) -> Result<JsValue, JsError> {
+            let target = target_peer_id.parse()
+                .map_err(|e: sync_core::peer_id::PeerIdError| JsError::new(&e.to_string()))?;
+            let members: Vec<_> = self.inner.borrow()
+                .pick_indirect_probers(target)
+                .into_iter()
+                .map(|m| MemberInfo {
+                    peer_id: m.info.peer_id.to_string(),
+                    address: m.info.address.clone(),
+                    incarnation: m.incarnation,
+                })
+                .collect();
+
+            serde_wasm_bindgen::to_value(&members)
+                .map_err(|e| JsError::new(&e.to_string()))
+        }
+
         /// Get count of known members (excluding ourselves).
         #[wasm_bindgen(js_name = memberCount)]
         pub fn member_count(&self) -> usize {
@@ -889,6 +1212,17 @@ mod wasm_impl {
                 .map_err(|e| JsError::new(&e.to_string()))
         }
 
+        /// Get every known member, whatever its state.
+        ///
+        /// @returns array of `{ peerId, address, state, incarnation }` sorted by peer ID,
+        /// where `state` is `"alive"`, `"suspected"`, `"dead"`, or `"removed"`
+        #[wasm_bindgen(js_name = getAllMembers)]
+        pub fn get_all_members(&self) -> Result<JsValue, JsError> {
+            let members = self.inner.borrow().snapshot();
+            serde_wasm_bindgen::to_value(&members)
+                .map_err(|e| JsError::new(&e.to_string()))
+        }
+
         /// Get a peer's current incarnation number (for reconnection incarnation bumps).
         #[wasm_bindgen(js_name = getMemberIncarnation)]
         pub fn get_member_incarnation(&self, peer_id: String) -> Option<u64> {
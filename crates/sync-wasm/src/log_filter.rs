@@ -0,0 +1,73 @@
+//! Target filtering for the JS logger callback.
+//!
+//! Targets are matched by module prefix: `sync_core` matches `sync_core` and
+//! `sync_core::vault`, but not `sync_core_extra`. A trailing `::*` is accepted
+//! and means the same thing (`sync_core::*`).
+
+/// Which log targets are forwarded to the logger callback.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetFilter {
+    /// Target prefixes to forward; empty forwards every target
+    pub allowed: Vec<String>,
+    /// Target prefixes never forwarded, even if allowed
+    pub blocked: Vec<String>,
+}
+
+impl TargetFilter {
+    /// Whether events from `target` should be forwarded.
+    pub fn allows(&self, target: &str) -> bool {
+        let allowed = self.allowed.is_empty()
+            || self.allowed.iter().any(|prefix| target_matches(prefix, target));
+        allowed && !self.blocked.iter().any(|prefix| target_matches(prefix, target))
+    }
+}
+
+/// Whether `target` is the module `prefix` or one of its submodules.
+fn target_matches(prefix: &str, target: &str) -> bool {
+    let prefix = prefix.strip_suffix("::*").unwrap_or(prefix);
+    match target.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with("::"),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(allowed: &[&str], blocked: &[&str]) -> TargetFilter {
+        TargetFilter {
+            allowed: allowed.iter().map(|s| s.to_string()).collect(),
+            blocked: blocked.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_empty_filter_allows_everything() {
+        let filter = TargetFilter::default();
+        assert!(filter.allows("sync_core::vault"));
+        assert!(filter.allows("loro_internal::oplog"));
+    }
+
+    #[test]
+    fn test_allowed_prefixes_match_whole_modules() {
+        let filter = filter(&["sync_core::*", "sync_wasm"], &[]);
+        assert!(filter.allows("sync_core"));
+        assert!(filter.allows("sync_core::sync_engine"));
+        assert!(filter.allows("sync_wasm::fs_bridge"));
+        assert!(!filter.allows("sync_core_extra"));
+        assert!(!filter.allows("loro_internal::oplog"));
+    }
+
+    #[test]
+    fn test_blocked_prefixes_win() {
+        let swim = filter(&["sync_core"], &["sync_core::swim"]);
+        assert!(swim.allows("sync_core::vault"));
+        assert!(!swim.allows("sync_core::swim"));
+        assert!(!swim.allows("sync_core::swim::membership"));
+
+        let loro = filter(&[], &["loro"]);
+        assert!(!loro.allows("loro::tree"));
+        assert!(loro.allows("loro_internal::oplog"));
+    }
+}
@@ -0,0 +1,171 @@
+//! Turning tracing event fields into the logger callback's `message` and `fields`.
+
+use std::fmt;
+
+use tracing::field::{Field, Visit};
+
+/// A structured field value, keeping its type for the JS `fields` object.
+///
+/// Values recorded with `Debug` (`?value`) arrive as their formatted string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::I64(value) => write!(f, "{}", value),
+            Self::U64(value) => write!(f, "{}", value),
+            Self::F64(value) => write!(f, "{:?}", value),
+            Self::Str(value) => f.write_str(value),
+        }
+    }
+}
+
+/// Visitor that collects an event's message and structured fields.
+///
+/// `finish` builds the log line: the `message` field first, followed by every
+/// other field as space-separated `key=value` pairs. Events without a message
+/// produce just the pairs.
+#[derive(Debug, Default)]
+pub struct MessageVisitor {
+    message: Option<String>,
+    fields: Vec<(&'static str, FieldValue)>,
+}
+
+impl MessageVisitor {
+    /// Fields other than `message`, in the order they were recorded.
+    pub fn fields(&self) -> &[(&'static str, FieldValue)] {
+        &self.fields
+    }
+
+    /// The log line for the fields recorded so far.
+    pub fn finish(self) -> String {
+        let mut parts = Vec::with_capacity(self.fields.len() + 1);
+        parts.extend(self.message);
+        parts.extend(
+            self.fields
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value)),
+        );
+        parts.join(" ")
+    }
+
+    fn record(&mut self, field: &Field, value: FieldValue) {
+        match value {
+            FieldValue::Str(message) if field.name() == "message" => self.message = Some(message),
+            value => self.fields.push((field.name(), value)),
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, FieldValue::Str(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, FieldValue::Str(value.to_string()));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, FieldValue::Bool(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, FieldValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record(field, FieldValue::U64(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, FieldValue::F64(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    /// Layer that keeps the visitor for the last event.
+    struct Capture(Arc<Mutex<Option<MessageVisitor>>>);
+
+    impl<S: tracing::Subscriber> Layer<S> for Capture {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            let mut visitor = MessageVisitor::default();
+            event.record(&mut visitor);
+            *self.0.lock().unwrap() = Some(visitor);
+        }
+    }
+
+    /// Visitor that recorded the event logged by `log`.
+    fn visit(log: impl FnOnce()) -> MessageVisitor {
+        let captured = Arc::new(Mutex::new(None));
+        let subscriber = tracing_subscriber::registry().with(Capture(Arc::clone(&captured)));
+        tracing::subscriber::with_default(subscriber, log);
+        captured.lock().unwrap().take().expect("no event recorded")
+    }
+
+    /// Message built for the event logged by `log`.
+    fn message_for(log: impl FnOnce()) -> String {
+        visit(log).finish()
+    }
+
+    #[test]
+    fn test_fields_without_message() {
+        let message = message_for(|| tracing::info!(peer = 7, path = "notes/a.md", synced = true));
+        assert_eq!(message, "peer=7 path=notes/a.md synced=true");
+    }
+
+    #[test]
+    fn test_fields_follow_message() {
+        let message = message_for(|| tracing::info!(path = "a.md", "Synced {} updates", 3));
+        assert_eq!(message, "Synced 3 updates path=a.md");
+    }
+
+    #[test]
+    fn test_message_only() {
+        assert_eq!(message_for(|| tracing::warn!("Connection lost")), "Connection lost");
+    }
+
+    #[test]
+    fn test_collects_typed_fields() {
+        let visitor = visit(|| {
+            tracing::info!(
+                peer_id = 7u64,
+                offset = -2i64,
+                path = "a.md",
+                synced = true,
+                ratio = 0.5,
+                changed = ?vec!["a.md"],
+                "Applied update"
+            )
+        });
+
+        assert_eq!(
+            visitor.fields(),
+            &[
+                ("peer_id", FieldValue::U64(7)),
+                ("offset", FieldValue::I64(-2)),
+                ("path", FieldValue::Str("a.md".to_string())),
+                ("synced", FieldValue::Bool(true)),
+                ("ratio", FieldValue::F64(0.5)),
+                ("changed", FieldValue::Str("[\"a.md\"]".to_string())),
+            ]
+        );
+        assert_eq!(
+            visitor.finish(),
+            "Applied update peer_id=7 offset=-2 path=a.md synced=true ratio=0.5 changed=[\"a.md\"]"
+        );
+    }
+}
@@ -4,11 +4,19 @@
 //! - Basic links: `[[Note]]`
 //! - Aliases: `[[Note|Display Text]]`
 //! - Headers: `[[Note#Header]]`
+//! - Heading chains: `[[Note#Heading#Subheading]]`
 //! - Block references: `[[Note#^block-id]]`
 //! - Embeds: `![[Note]]`
 //! - Paths: `[[folder/Note]]`
+//!
+//! Standard Markdown links (`[label](folder/Note.md)`) are parsed separately by
+//! `parse_markdown_links`, skipping code blocks and inline code. Wiki links in
+//! code are only skipped when asked, with `parse_wiki_links_with_options`.
+//! Inline tags (`#tag`, `#nested/tag`) are parsed by `parse_tags`. `scan_note`
+//! finds wiki links, Markdown links, and tags together in one pass.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// A parsed wiki link from Obsidian markdown content.
 ///
@@ -21,7 +29,8 @@ pub struct WikiLink {
     pub parent: Option<String>,
     /// File extension without the dot: "md" or None (like `Path::extension()`)
     pub extension: Option<String>,
-    /// Header reference if present: "Header Section"
+    /// Header reference if present: "Header Section", or a heading chain
+    /// joined by `#`: "Heading One#Subheading" (see `header_path`)
     pub header: Option<String>,
     /// Block ID if present: "block-123"
     pub block_id: Option<String>,
@@ -29,6 +38,12 @@ pub struct WikiLink {
     pub alias: Option<String>,
     /// Whether this is an embed (`![[...]]`)
     pub is_embed: bool,
+    /// Byte offset where the link starts in the parsed content, at the `!` for embeds
+    #[serde(default)]
+    pub start: usize,
+    /// Byte offset just past the link's closing `]]`
+    #[serde(default)]
+    pub end: usize,
 }
 
 impl WikiLink {
@@ -52,34 +67,87 @@ impl WikiLink {
     pub fn display_name(&self) -> &str {
         self.alias.as_deref().unwrap_or(&self.name)
     }
+
+    /// Returns the headings of the header reference, outermost first:
+    /// `[[Note#Heading One#Subheading]]` gives ["Heading One", "Subheading"]
+    ///
+    /// Empty when the link has no header.
+    pub fn header_path(&self) -> Vec<&str> {
+        self.header
+            .as_deref()
+            .map_or_else(Vec::new, |header| header.split('#').collect())
+    }
+
+    /// Renders the link back to markdown: "![[folder/Note.md#Header|alias]]"
+    ///
+    /// Parsing the result yields a link equal to `self`, apart from `start`
+    /// and `end`, which then span the whole result.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        if self.is_embed {
+            out.push('!');
+        }
+        out.push_str("[[");
+        out.push_str(&self.path());
+
+        if let Some(header) = &self.header {
+            out.push('#');
+            out.push_str(header);
+        }
+        if let Some(block_id) = &self.block_id {
+            out.push_str("#^");
+            out.push_str(block_id);
+        }
+
+        if let Some(alias) = &self.alias {
+            out.push('|');
+            out.push_str(alias);
+        } else if self.block_id.is_none() && self.header.is_none() && out.ends_with(']') {
+            // The parser trims the path, so a space keeps a trailing `]` from closing the link early
+            out.push(' ');
+        }
+
+        out.push_str("]]");
+        out
+    }
+}
+
+/// Options for `parse_wiki_links_with_options`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Skip links that open inside a fenced code block or inline code span.
+    /// A link opening outside code counts even if it runs into a code block.
+    pub skip_code: bool,
 }
 
 /// Parse all wiki links from markdown content
 pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
-    let mut links = Vec::new();
+    parse_wiki_links_with_options(content, ParseOptions::default())
+}
+
+/// Parse wiki links from markdown content, with `options`
+pub fn parse_wiki_links_with_options(content: &str, options: ParseOptions) -> Vec<WikiLink> {
     let chars: Vec<char> = content.chars().collect();
+    let in_code = options.skip_code.then(|| code_mask(&chars));
+    scan_links(&chars, in_code.as_deref())
+}
+
+/// Find all links in `chars`, skipping any that open where `in_code` is set
+fn scan_links(chars: &[char], in_code: Option<&[bool]>) -> Vec<WikiLink> {
+    let mut links = Vec::new();
     let len = chars.len();
     let mut i = 0;
+    let byte_offsets = byte_offsets(chars);
 
     while i < len {
-        // Check for embed: ![[
-        if i + 2 < len && chars[i] == '!' && chars[i + 1] == '[' && chars[i + 2] == '[' {
-            if let Some((link, end)) = parse_link_at(&chars, i + 1, true) {
-                links.push(link);
-                i = end;
-                continue;
-            }
+        if in_code.is_some_and(|mask| mask[i]) {
+            i += 1;
+            continue;
         }
-        // Check for regular link: [[ (but not preceded by !)
-        if i + 1 < len && chars[i] == '[' && chars[i + 1] == '[' {
-            // Make sure it's not part of an embed we already handled
-            if i == 0 || chars[i - 1] != '!' {
-                if let Some((link, end)) = parse_link_at(&chars, i, false) {
-                    links.push(link);
-                    i = end;
-                    continue;
-                }
-            }
+        if let Some((link, end)) = wiki_link_at(chars, &byte_offsets, i) {
+            links.push(link);
+            i = end;
+            continue;
         }
         i += 1;
     }
@@ -87,9 +155,118 @@ pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
     links
 }
 
+/// Parse a wiki link or embed opening at `i`.
+/// Returns the link and the position after the closing `]]`.
+fn wiki_link_at(chars: &[char], byte_offsets: &[usize], i: usize) -> Option<(WikiLink, usize)> {
+    let len = chars.len();
+    // Check for embed: ![[
+    if i + 2 < len && chars[i] == '!' && chars[i + 1] == '[' && chars[i + 2] == '[' {
+        return parse_link_at(chars, byte_offsets, i + 1, true);
+    }
+    // Check for regular link: [[ (but not preceded by !, which is an embed)
+    if i + 1 < len && chars[i] == '[' && chars[i + 1] == '[' && (i == 0 || chars[i - 1] != '!') {
+        return parse_link_at(chars, byte_offsets, i, false);
+    }
+    None
+}
+
+/// Parse wiki links in several documents at once.
+///
+/// Takes `(path, content)` pairs and returns each path's links, in the same
+/// order `parse_wiki_links` would. A path given twice keeps its last content.
+pub fn parse_wiki_links_batch<'a, I>(documents: I) -> HashMap<String, Vec<WikiLink>>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    documents
+        .into_iter()
+        .map(|(path, content)| (path.to_string(), parse_wiki_links(content)))
+        .collect()
+}
+
+/// Rewrite the targets of links picked by `rename`, leaving everything else as is.
+///
+/// `rename` is called for each wiki link in order and returns the link's new
+/// target path (`folder/Note`, `Note.md`), or `None` to leave it alone. The
+/// new target replaces only the path: the alias, header or block reference,
+/// and embed marker are kept. Links left alone, and all text between links,
+/// stay byte-identical.
+pub fn rewrite_links(content: &str, rename: &dyn Fn(&WikiLink) -> Option<String>) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut copied = 0;
+
+    for link in parse_wiki_links(content) {
+        let Some(target) = rename(&link) else {
+            continue;
+        };
+        let (parent, name, extension) = parse_path(&target);
+        let updated = WikiLink {
+            parent,
+            name,
+            extension,
+            ..link.clone()
+        };
+        if updated == link {
+            continue;
+        }
+
+        result.push_str(&content[copied..link.start]);
+        result.push_str(&updated.to_markdown());
+        copied = link.end;
+    }
+
+    result.push_str(&content[copied..]);
+    result
+}
+
+/// Rewrite links to the note at `old_path` so they point at `new_path`.
+///
+/// Paths are vault-relative, with or without the `.md` extension. Links by bare
+/// name (`[[Note]]`) are matched on the file name and stay bare; links with a
+/// folder (`[[folder/Note]]`) must match the full path and get the new folder.
+/// Aliases, fragments, embeds, and an explicit `.md` extension are preserved.
+///
+/// Returns `None` if no link changed.
+pub fn rename_links(content: &str, old_path: &str, new_path: &str) -> Option<String> {
+    let old_path = old_path.strip_suffix(".md").unwrap_or(old_path);
+    let new_path = new_path.strip_suffix(".md").unwrap_or(new_path);
+    let old_name = old_path.rsplit('/').next().unwrap_or(old_path);
+    let new_name = new_path.rsplit('/').next().unwrap_or(new_path);
+
+    let result = rewrite_links(content, &|link| {
+        let has_md_extension = link.extension.as_deref() == Some("md");
+        let path = link.path();
+        let target = if has_md_extension {
+            path.strip_suffix(".md").unwrap_or(&path)
+        } else {
+            &path
+        };
+
+        let replacement = match link.parent {
+            Some(_) if target == old_path => new_path,
+            None if target == old_name => new_name,
+            _ => return None,
+        };
+        if has_md_extension {
+            Some(format!("{}.md", replacement))
+        } else {
+            Some(replacement.to_string())
+        }
+    });
+
+    (result != content).then_some(result)
+}
+
 /// Parse a link starting at position `start` (pointing to first `[`)
 /// Returns the parsed link and the position after the closing `]]`
-fn parse_link_at(chars: &[char], start: usize, is_embed: bool) -> Option<(WikiLink, usize)> {
+///
+/// `byte_offsets[i]` is the byte offset of `chars[i]`, used for the link's span.
+fn parse_link_at(
+    chars: &[char],
+    byte_offsets: &[usize],
+    start: usize,
+    is_embed: bool,
+) -> Option<(WikiLink, usize)> {
     let len = chars.len();
 
     // Verify we have [[
@@ -108,9 +285,17 @@ fn parse_link_at(chars: &[char], start: usize, is_embed: bool) -> Option<(WikiLi
             if depth == 0 {
                 // Found closing ]]
                 let content: String = chars[content_start..i].iter().collect();
-                let link = parse_link_content(&content, is_embed);
+                let link_start = if is_embed { start - 1 } else { start };
+                let link = WikiLink {
+                    start: byte_offsets[link_start],
+                    end: byte_offsets[i + 2],
+                    ..parse_link_content(&content, is_embed)
+                };
                 return Some((link, i + 2));
             }
+            // Skip past this ]] so it can't also close the outer link
+            i += 2;
+            continue;
         }
         if i + 1 < len && chars[i] == '[' && chars[i + 1] == '[' {
             depth += 1;
@@ -148,28 +333,30 @@ fn parse_link_content(content: &str, is_embed: bool) -> WikiLink {
         block_id,
         alias,
         is_embed,
+        start: 0,
+        end: 0,
     }
 }
 
 /// Parse a target string to extract the path and any fragment (header or block reference)
 /// Returns (path_part, header, block_id)
+///
+/// Headers before a block reference are kept: `Note#A#B#^id` has header "A#B".
 fn parse_fragment(target: &str) -> (&str, Option<String>, Option<String>) {
-    // Check for block reference: Note#^block-id
-    if let Some(block_pos) = target.find("#^") {
-        let path_part = &target[..block_pos];
-        let block_id = &target[block_pos + 2..];
-        return (path_part, None, Some(block_id.to_string()));
-    }
+    // Check for block reference: Note#^block-id, everything after is the ID
+    let (target, block_id) = match target.find("#^") {
+        Some(block_pos) => (&target[..block_pos], Some(target[block_pos + 2..].to_string())),
+        None => (target, None),
+    };
 
-    // Check for header reference: Note#Header
+    // Check for header reference: Note#Header or a chain Note#Header#Subheader
     if let Some(header_pos) = target.find('#') {
         let path_part = &target[..header_pos];
         let header = &target[header_pos + 1..];
-        return (path_part, Some(header.to_string()), None);
+        return (path_part, Some(header.to_string()), block_id);
     }
 
-    // No fragment
-    (target, None, None)
+    (target, None, block_id)
 }
 
 /// Parse a path string into parent, name, and extension
@@ -200,7 +387,11 @@ fn parse_path(path: &str) -> (Option<String>, String, Option<String>) {
 
 /// Extract all unique note names from wiki links in content
 pub fn extract_linked_notes(content: &str) -> Vec<String> {
-    let links = parse_wiki_links(content);
+    unique_names(parse_wiki_links(content))
+}
+
+/// Note names of `links`, in order, without repeats
+fn unique_names(links: Vec<WikiLink>) -> Vec<String> {
     let mut seen = std::collections::HashSet::new();
     let mut result = Vec::new();
 
@@ -213,6 +404,491 @@ pub fn extract_linked_notes(content: &str) -> Vec<String> {
     result
 }
 
+/// Extract all unique note names from wiki links and internal Markdown links
+///
+/// Like `extract_linked_notes`, but `[label](folder/Note.md)` links to notes in
+/// the vault count too, and links inside code are skipped, as examples rather
+/// than real links. Wiki-link names come first, in order.
+pub fn extract_all_linked_notes(content: &str) -> Vec<String> {
+    let options = ParseOptions { skip_code: true };
+    linked_note_names(
+        &parse_wiki_links_with_options(content, options),
+        &parse_markdown_links(content),
+    )
+}
+
+/// Unique note names of wiki links, then internal Markdown links, in order
+fn linked_note_names(wiki_links: &[WikiLink], markdown_links: &[MarkdownLink]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    wiki_links
+        .iter()
+        .map(|link| link.name.clone())
+        .chain(markdown_links.iter().filter_map(MarkdownLink::note_name))
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// A standard Markdown link: `[label](url)` or `![alt](url)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkdownLink {
+    /// The link text between the brackets: "label"
+    pub label: String,
+    /// The destination, without angle brackets or title: "knowledge/Note.md"
+    pub url: String,
+    /// Whether the destination is a relative path in the vault rather than a
+    /// URL with a scheme (`https:`, `mailto:`, ...)
+    pub is_internal: bool,
+    /// Whether this is an image embed (`![...](...)`)
+    pub is_embed: bool,
+}
+
+impl MarkdownLink {
+    /// The vault path the link points at, percent-decoded and without any
+    /// `#fragment`: "knowledge/My Note.md"
+    ///
+    /// Returns `None` for external links and links to a heading in the same note.
+    pub fn path(&self) -> Option<String> {
+        if !self.is_internal {
+            return None;
+        }
+        let path = self.url.split('#').next().unwrap_or_default();
+        let path = path.strip_prefix("./").unwrap_or(path);
+        if path.is_empty() {
+            return None;
+        }
+        Some(percent_decode(path))
+    }
+
+    /// The linked note's name, for notes with no extension or `.md`: "My Note"
+    ///
+    /// Returns `None` for external links and links to other files like images.
+    pub fn note_name(&self) -> Option<String> {
+        let path = self.path()?;
+        let (_, name, extension) = parse_path(&path);
+        match extension.as_deref() {
+            None | Some("md") if !name.is_empty() => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Parse all standard Markdown links from content.
+///
+/// Links inside fenced code blocks and inline code spans are skipped, as are
+/// wiki links and reference-style links (`[label][ref]`).
+pub fn parse_markdown_links(content: &str) -> Vec<MarkdownLink> {
+    let chars: Vec<char> = content.chars().collect();
+    let in_code = code_mask(&chars);
+    let len = chars.len();
+    let mut links = Vec::new();
+    let mut i = 0;
+
+    while i < len {
+        if in_code[i] {
+            i += 1;
+            continue;
+        }
+        let (link, next) = markdown_link_step(&chars, &in_code, i);
+        links.extend(link);
+        i = next;
+    }
+
+    links
+}
+
+/// Parse the Markdown link starting at `i`, if there is one.
+/// Returns it and the position to continue scanning from.
+fn markdown_link_step(
+    chars: &[char],
+    in_code: &[bool],
+    i: usize,
+) -> (Option<MarkdownLink>, usize) {
+    match chars[i] {
+        '\\' => (None, i + 2),
+        // Wiki links are parsed by `parse_wiki_links`
+        '[' if chars.get(i + 1) == Some(&'[') => (None, i + 2),
+        '[' => match parse_markdown_link_at(chars, in_code, i) {
+            Some((link, end)) => {
+                let is_embed = i > 0 && chars[i - 1] == '!';
+                (Some(MarkdownLink { is_embed, ..link }), end)
+            }
+            None => (None, i + 1),
+        },
+        _ => (None, i + 1),
+    }
+}
+
+/// Parse a `[label](url)` link whose `[` is at `start`.
+/// Returns the link and the position after the closing `)`.
+fn parse_markdown_link_at(
+    chars: &[char],
+    in_code: &[bool],
+    start: usize,
+) -> Option<(MarkdownLink, usize)> {
+    let len = chars.len();
+
+    // Find the `]` closing the label, allowing balanced brackets inside
+    let mut i = start + 1;
+    let mut depth = 1;
+    while i < len {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if i >= len || chars.get(i + 1) != Some(&'(') {
+        return None;
+    }
+    let label: String = chars[start + 1..i].iter().collect();
+
+    // Find the `)` closing the destination, allowing balanced parentheses inside
+    let dest_start = i + 2;
+    let mut j = dest_start;
+    let mut depth = 1;
+    let mut in_angle = false;
+    while j < len {
+        match chars[j] {
+            '\n' => return None,
+            '\\' => j += 1,
+            '<' if j == dest_start => in_angle = true,
+            '>' if in_angle => in_angle = false,
+            '(' if !in_angle => depth += 1,
+            ')' if !in_angle => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+        j += 1;
+    }
+    if j >= len || in_code[dest_start..j].iter().any(|&code| code) {
+        return None;
+    }
+
+    let destination: String = chars[dest_start..j].iter().collect();
+    let url = parse_destination(&destination)?;
+    let is_internal = !has_scheme(&url) && !url.starts_with("//");
+    let link = MarkdownLink {
+        label,
+        url,
+        is_internal,
+        is_embed: false,
+    };
+    Some((link, j + 1))
+}
+
+/// The URL from a link destination, dropping angle brackets and any title:
+/// `<My Note.md> "Title"` -> `My Note.md`
+fn parse_destination(destination: &str) -> Option<String> {
+    let destination = destination.trim();
+    let url = if let Some(rest) = destination.strip_prefix('<') {
+        &rest[..rest.find('>')?]
+    } else {
+        destination.split_whitespace().next().unwrap_or_default()
+    };
+    if url.is_empty() {
+        return None;
+    }
+    Some(url.to_string())
+}
+
+/// Whether a URL starts with a scheme like `https:` or `mailto:`
+fn has_scheme(url: &str) -> bool {
+    let Some((scheme, _)) = url.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Decode `%XX` escapes, leaving malformed ones as they are
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            if let Some(byte) = hex {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| s.to_string())
+}
+
+/// An inline tag: `#project/active`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    /// The full tag name without the `#`, nested segments included: "project/active"
+    pub name: String,
+    /// Byte offset of the tag's `#` in the parsed content
+    pub start: usize,
+    /// Byte offset just past the tag's last character
+    pub end: usize,
+}
+
+impl Tag {
+    /// Returns the nested segments of the name, outermost first:
+    /// `#a/b/c` gives ["a", "b", "c"]
+    pub fn segments(&self) -> Vec<&str> {
+        self.name.split('/').collect()
+    }
+}
+
+/// Parse all inline tags (`#tag`, `#nested/tag`) from markdown content.
+///
+/// A tag's `#` must start the content or follow whitespace, so URL fragments
+/// (`page#section`), `word#text`, and heading markers (`## Heading`) aren't
+/// tags. Tags inside YAML frontmatter, fenced code blocks, and inline code
+/// spans are skipped, as are all-digit names like `#2024`. Spans are offsets
+/// into the whole content, frontmatter included.
+pub fn parse_tags(content: &str) -> Vec<Tag> {
+    let chars: Vec<char> = content.chars().collect();
+    let byte_offsets = byte_offsets(&chars);
+    let frontmatter_len = frontmatter_len(content);
+    let body_start = byte_offsets.partition_point(|&offset| offset < frontmatter_len);
+    let mut in_code = vec![true; body_start];
+    in_code.extend(code_mask(&chars[body_start..]));
+    let len = chars.len();
+    let mut tags = Vec::new();
+    let mut i = body_start;
+
+    while i < len {
+        if in_code[i] {
+            i += 1;
+            continue;
+        }
+        let (tag, next) = tag_step(&chars, &byte_offsets, i);
+        tags.extend(tag);
+        i = next;
+    }
+
+    tags
+}
+
+/// Parse the tag whose `#` is at `i`, if there is one.
+/// Returns it and the position to continue scanning from.
+fn tag_step(chars: &[char], byte_offsets: &[usize], i: usize) -> (Option<Tag>, usize) {
+    let opens_tag = chars[i] == '#' && (i == 0 || chars[i - 1].is_whitespace());
+    if !opens_tag {
+        return (None, i + 1);
+    }
+
+    let len = chars.len();
+    let mut end = i + 1;
+    while end < len && is_tag_char(chars[end]) {
+        end += 1;
+    }
+    // A trailing slash isn't part of the tag: `#project/ `
+    while end > i + 1 && chars[end - 1] == '/' {
+        end -= 1;
+    }
+
+    let name: String = chars[i + 1..end].iter().collect();
+    let has_text = name.chars().any(|c| c != '/' && !c.is_numeric());
+    let tag = (!name.starts_with('/') && has_text).then(|| Tag {
+        name,
+        start: byte_offsets[i],
+        end: byte_offsets[end],
+    });
+    (tag, end.max(i + 1))
+}
+
+/// Everything `scan_note` finds in a note
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoteScan {
+    /// Wiki links outside code, as `parse_wiki_links_with_options` with
+    /// `skip_code` finds them
+    pub wiki_links: Vec<WikiLink>,
+    /// Markdown links, as `parse_markdown_links` finds them
+    pub markdown_links: Vec<MarkdownLink>,
+    /// Inline tags, as `parse_tags` finds them
+    pub tags: Vec<Tag>,
+}
+
+impl NoteScan {
+    /// Unique names of the linked notes, as `extract_all_linked_notes` returns them
+    pub fn linked_notes(&self) -> Vec<String> {
+        linked_note_names(&self.wiki_links, &self.markdown_links)
+    }
+}
+
+/// Find a note's wiki links, Markdown links, and tags in one pass over its
+/// content.
+///
+/// Each kind keeps its own scan position, so the results are the same as
+/// running the individual parsers, while the content is decoded and its code
+/// blocks are found only once.
+pub fn scan_note(content: &str) -> NoteScan {
+    let chars: Vec<char> = content.chars().collect();
+    let in_code = code_mask(&chars);
+    let byte_offsets = byte_offsets(&chars);
+    let frontmatter_len = frontmatter_len(content);
+    let mut scan = NoteScan::default();
+    let mut next_wiki_link = 0;
+    let mut next_markdown_link = 0;
+    let mut next_tag = byte_offsets.partition_point(|&offset| offset < frontmatter_len);
+
+    for i in 0..chars.len() {
+        if in_code[i] {
+            continue;
+        }
+        if i >= next_wiki_link {
+            match wiki_link_at(&chars, &byte_offsets, i) {
+                Some((link, end)) => {
+                    scan.wiki_links.push(link);
+                    next_wiki_link = end;
+                }
+                None => next_wiki_link = i + 1,
+            }
+        }
+        if i >= next_markdown_link {
+            let (link, next) = markdown_link_step(&chars, &in_code, i);
+            scan.markdown_links.extend(link);
+            next_markdown_link = next;
+        }
+        if i >= next_tag {
+            let (tag, next) = tag_step(&chars, &byte_offsets, i);
+            scan.tags.extend(tag);
+            next_tag = next;
+        }
+    }
+
+    scan
+}
+
+/// Byte length of the YAML frontmatter block at the start of `content`,
+/// delimiters included, or 0 if there is none
+fn frontmatter_len(content: &str) -> usize {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return 0;
+    };
+    let mut offset = content.len() - rest.len();
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return offset;
+        }
+    }
+    0
+}
+
+/// Whether `c` can appear in a tag name after the `#`
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Byte offset of each char, plus the total length, for spans
+fn byte_offsets(chars: &[char]) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(chars.len() + 1);
+    let mut offset = 0;
+    for c in chars {
+        offsets.push(offset);
+        offset += c.len_utf8();
+    }
+    offsets.push(offset);
+    offsets
+}
+
+/// Mark which chars are inside fenced code blocks or inline code spans
+fn code_mask(chars: &[char]) -> Vec<bool> {
+    let len = chars.len();
+    let mut mask = vec![false; len];
+    // The fence char and length of the open code block, if any
+    let mut fence: Option<(char, usize)> = None;
+    let mut line_start = 0;
+
+    while line_start < len {
+        let line_end = chars[line_start..]
+            .iter()
+            .position(|&c| c == '\n')
+            .map_or(len, |pos| line_start + pos + 1);
+        let line = &chars[line_start..line_end];
+        let indent = line.iter().take_while(|&&c| c == ' ').count();
+        let marker = line.get(indent).copied().filter(|&c| c == '`' || c == '~');
+        let run = marker.map_or(0, |m| {
+            line[indent..].iter().take_while(|&&c| c == m).count()
+        });
+
+        match fence {
+            Some((fence_char, fence_len)) => {
+                mask[line_start..line_end].fill(true);
+                let rest = &line[indent + run..];
+                if marker == Some(fence_char)
+                    && run >= fence_len
+                    && rest.iter().all(|c| c.is_whitespace())
+                {
+                    fence = None;
+                }
+            }
+            None if indent <= 3 && run >= 3 => {
+                mask[line_start..line_end].fill(true);
+                fence = marker.map(|m| (m, run));
+            }
+            None => mark_code_spans(line, &mut mask[line_start..line_end]),
+        }
+        line_start = line_end;
+    }
+
+    mask
+}
+
+/// Mark inline code spans in a line: a run of backticks up to the next run of
+/// the same length
+fn mark_code_spans(line: &[char], mask: &mut [bool]) {
+    let len = line.len();
+    let mut i = 0;
+    while i < len {
+        if line[i] != '`' {
+            i += 1;
+            continue;
+        }
+        let run = line[i..].iter().take_while(|&&c| c == '`').count();
+        let mut j = i + run;
+        let mut close = None;
+        while j < len {
+            if line[j] == '`' {
+                let close_run = line[j..].iter().take_while(|&&c| c == '`').count();
+                if close_run == run {
+                    close = Some(j + close_run);
+                    break;
+                }
+                j += close_run;
+            } else {
+                j += 1;
+            }
+        }
+        match close {
+            Some(end) => {
+                mask[i..end].fill(true);
+                i = end;
+            }
+            None => i += run,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,6 +909,31 @@ mod tests {
         assert_eq!(links[1].file_name(), "CLAUDE.local");
     }
 
+    #[test]
+    fn parse_batch_matches_per_document_parsing() {
+        let documents = [
+            ("a.md", "See [[Note]] and [[folder/Other|alias]]"),
+            ("notes/b.md", "![[img.png]] [[Note#Header]] [[Note#^b1]]"),
+            ("empty.md", "No links here"),
+            ("c.md", "[[Unclosed and [[Closed]]"),
+        ];
+
+        let batch = parse_wiki_links_batch(documents);
+
+        assert_eq!(batch.len(), documents.len());
+        for (path, content) in documents {
+            assert_eq!(batch[path], parse_wiki_links(content), "{}", path);
+        }
+        assert!(batch["empty.md"].is_empty());
+    }
+
+    #[test]
+    fn parse_batch_keeps_last_content_for_duplicate_paths() {
+        let batch = parse_wiki_links_batch([("a.md", "[[First]]"), ("a.md", "[[Second]]")]);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch["a.md"][0].name, "Second");
+    }
+
     #[test]
     fn parse_links_with_aliases() {
         let content = "[[Note Name|Display Text]]";
@@ -264,6 +965,26 @@ mod tests {
         assert_eq!(links[0].block_id, Some("block-123".to_string()));
     }
 
+    #[test]
+    fn parse_heading_chains() {
+        let links = parse_wiki_links("[[Note#Heading One#Subheading]] [[Note#A#B#^block]] [[Note#^id#x]]");
+
+        assert_eq!(links[0].name, "Note");
+        assert_eq!(links[0].header, Some("Heading One#Subheading".to_string()));
+        assert_eq!(links[0].header_path(), vec!["Heading One", "Subheading"]);
+        assert_eq!(links[0].block_id, None);
+
+        // Headings before a block reference are kept, in order
+        assert_eq!(links[1].name, "Note");
+        assert_eq!(links[1].header_path(), vec!["A", "B"]);
+        assert_eq!(links[1].block_id, Some("block".to_string()));
+        assert_eq!(links[1].to_markdown(), "[[Note#A#B#^block]]");
+
+        // Everything after `#^` is the block ID
+        assert!(links[2].header_path().is_empty());
+        assert_eq!(links[2].block_id, Some("id#x".to_string()));
+    }
+
     #[test]
     fn parse_embed_links() {
         let content = "![[Image]]";
@@ -382,4 +1103,298 @@ mod tests {
 
         assert!(notes.is_empty());
     }
+
+    #[test]
+    fn to_markdown_round_trips() {
+        let content = "[[Note]] ![[folder/Image.png]] [[Note#Header|alias]] [[a/b/Note.md#^block-1]]";
+        for link in parse_wiki_links(content) {
+            let markdown = link.to_markdown();
+            let expected = WikiLink {
+                start: 0,
+                end: markdown.len(),
+                ..link
+            };
+            assert_eq!(parse_wiki_links(&markdown), vec![expected]);
+        }
+
+        // Nested closing brackets don't overlap
+        let link = &parse_wiki_links("[[a|[[b]]]]")[0];
+        assert_eq!(link.alias.as_deref(), Some("[[b]]"));
+
+        let link = &parse_wiki_links("[[knowledge/Note#Section|Display]]")[0];
+        assert_eq!(link.to_markdown(), "[[knowledge/Note#Section|Display]]");
+    }
+
+    #[test]
+    fn rename_links_renames_bare_and_path_links() {
+        let content = "See [[Old]], [[notes/Old|alias]], and ![[Old#^block]].";
+        let result = rename_links(content, "notes/Old.md", "archive/New.md").unwrap();
+        assert_eq!(result, "See [[New]], [[archive/New|alias]], and ![[New#^block]].");
+    }
+
+    #[test]
+    fn rename_links_preserves_md_extension_and_headers() {
+        let content = "[[notes/Old.md#Intro]] and [[Old.md]]";
+        let result = rename_links(content, "notes/Old", "notes/New").unwrap();
+        assert_eq!(result, "[[notes/New.md#Intro]] and [[New.md]]");
+    }
+
+    #[test]
+    fn rename_links_ignores_other_notes() {
+        // Different folder, different name, and a folder-only move of a bare link
+        let content = "[[other/Old]] [[Older]] [[Old]]";
+        assert_eq!(rename_links(content, "notes/Old.md", "archive/Old.md"), None);
+        assert_eq!(
+            rename_links(content, "notes/Old.md", "notes/New.md").unwrap(),
+            "[[other/Old]] [[Older]] [[New]]"
+        );
+    }
+
+    #[test]
+    fn rename_links_handles_multibyte_text() {
+        let content = "Café → [[Old]] ✓";
+        assert_eq!(rename_links(content, "Old", "Nouveau").unwrap(), "Café → [[Nouveau]] ✓");
+    }
+
+    #[test]
+    fn rewrite_links_keeps_fragments_aliases_and_embeds() {
+        let content = "[[Old#Intro|the old one]], ![[Old#^quote]], ![[Old.md]]";
+        let result = rewrite_links(content, &|link| {
+            let extension = link.extension.as_deref().map_or(String::new(), |ext| format!(".{}", ext));
+            (link.name == "Old").then(|| format!("archive/New{}", extension))
+        });
+        assert_eq!(
+            result,
+            "[[archive/New#Intro|the old one]], ![[archive/New#^quote]], ![[archive/New.md]]"
+        );
+    }
+
+    #[test]
+    fn rewrite_links_leaves_other_text_untouched() {
+        let content = "Café [[ Other |x]] → [[Old]]\n![[ Other ]] `[[Old` ✓";
+        let result = rewrite_links(content, &|link| {
+            (link.name == "Old").then(|| "Nouveau".to_string())
+        });
+        assert_eq!(result, "Café [[ Other |x]] → [[Nouveau]]\n![[ Other ]] `[[Old` ✓");
+
+        // A target equal to the current one is no change
+        assert_eq!(rewrite_links(content, &|link| Some(link.path())), content);
+        assert_eq!(rewrite_links(content, &|_| None), content);
+    }
+
+    #[test]
+    fn parse_markdown_links_internal_and_external() {
+        let content = "See [the note](knowledge/Note.md), [docs](https://example.com/a_(b)) \
+                       and ![diagram](<images/My Diagram.png> \"Title\").";
+        let links = parse_markdown_links(content);
+
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].label, "the note");
+        assert_eq!(links[0].url, "knowledge/Note.md");
+        assert!(links[0].is_internal);
+        assert!(!links[0].is_embed);
+        assert_eq!(links[1].url, "https://example.com/a_(b)");
+        assert!(!links[1].is_internal);
+        assert_eq!(links[2].url, "images/My Diagram.png");
+        assert!(links[2].is_internal);
+        assert!(links[2].is_embed);
+    }
+
+    #[test]
+    fn parse_markdown_links_skips_code_and_wiki_links() {
+        let content = "\
+[real](Real.md) `[inline](Inline.md)` ``[double `tick`](Double.md)``
+```
+[fenced](Fenced.md)
+```
+~~~~md
+[tilde](Tilde.md)
+```
+~~~~
+[[Wiki]] \\[escaped](Escaped.md) [ref][1] [after](After.md)
+";
+        let urls: Vec<String> = parse_markdown_links(content)
+            .into_iter()
+            .map(|link| link.url)
+            .collect();
+        assert_eq!(urls, vec!["Real.md", "After.md"]);
+    }
+
+    #[test]
+    fn markdown_link_note_names() {
+        let link = |url: &str| parse_markdown_links(&format!("[x]({})", url)).remove(0);
+
+        assert_eq!(
+            link("knowledge/My%20Note.md#Intro").note_name().as_deref(),
+            Some("My Note")
+        );
+        assert_eq!(link("./Note").note_name().as_deref(), Some("Note"));
+        assert_eq!(
+            link("knowledge/My%20Note.md").path().as_deref(),
+            Some("knowledge/My Note.md")
+        );
+        assert_eq!(link("image.png").note_name(), None);
+        assert_eq!(link("#Heading").note_name(), None);
+        assert_eq!(link("mailto:me@example.com").note_name(), None);
+        assert_eq!(link("//example.com/Note.md").note_name(), None);
+    }
+
+    #[test]
+    fn extract_all_linked_notes_merges_link_kinds() {
+        let content = "[[Alpha]] [alpha again](Alpha.md) [beta](knowledge/Beta.md) \
+                       [site](https://example.com/Gamma.md) `[code](Delta.md)`";
+        assert_eq!(extract_all_linked_notes(content), vec!["Alpha", "Beta"]);
+        // The wiki-link-only variant is unchanged
+        assert_eq!(extract_linked_notes(content), vec!["Alpha"]);
+    }
+
+    #[test]
+    fn parse_with_skip_code_ignores_links_in_code() {
+        let content = "\
+[[Real]] `[[Inline]]` ``nested `tick` [[Double]]`` [[After `code`]]
+```rust
+let s = \"[[Fenced]]\";
+```
+[[Straddles
+```
+]]
+```
+[[Outside]]
+";
+        let names = |options| -> Vec<String> {
+            parse_wiki_links_with_options(content, options)
+                .into_iter()
+                .map(|link| link.name)
+                .collect()
+        };
+
+        assert_eq!(
+            names(ParseOptions { skip_code: true }),
+            vec!["Real", "After `code`", "Straddles\n```", "Outside"]
+        );
+        // Off by default, so every link is found
+        assert_eq!(
+            names(ParseOptions::default()),
+            vec![
+                "Real",
+                "Inline",
+                "Double",
+                "After `code`",
+                "Fenced",
+                "Straddles\n```",
+                "Outside"
+            ]
+        );
+        assert_eq!(parse_wiki_links(content).len(), 7);
+        assert_eq!(extract_all_linked_notes(content).len(), 4);
+    }
+
+    #[test]
+    fn link_offsets_are_byte_offsets() {
+        let content = "Café ✓ [[Note]] and ![[日本/画像.png]] then [[Note]]";
+        let links = parse_wiki_links(content);
+
+        assert_eq!(links.len(), 3);
+        for link in &links {
+            let span = &content[link.start..link.end];
+            assert_eq!(span, link.to_markdown(), "{:?}", link);
+        }
+        assert_eq!(&content[links[1].start..links[1].end], "![[日本/画像.png]]");
+        // Repeated links to the same note keep their own positions
+        assert_eq!(links[0].start, "Café ✓ ".len());
+        assert_eq!(links[2].end, content.len());
+        assert!(links[2].start > links[1].end);
+    }
+
+    fn tag_names(content: &str) -> Vec<String> {
+        parse_tags(content)
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect()
+    }
+
+    #[test]
+    fn parse_nested_tags() {
+        let content = "Status: #a/b/c and #project/active/.";
+        let tags = parse_tags(content);
+
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].name, "a/b/c");
+        assert_eq!(tags[0].segments(), vec!["a", "b", "c"]);
+        assert_eq!(&content[tags[0].start..tags[0].end], "#a/b/c");
+        // Trailing slashes and punctuation aren't part of the tag
+        assert_eq!(tags[1].name, "project/active");
+        assert_eq!(&content[tags[1].start..tags[1].end], "#project/active");
+    }
+
+    #[test]
+    fn tags_must_follow_whitespace() {
+        assert!(parse_tags("word#notatag").is_empty());
+        assert!(parse_tags("See https://example.com/page#section").is_empty());
+        assert!(parse_tags("[[Note#Header]] and [link](#heading)").is_empty());
+        assert!(parse_tags("Escaped \\#tag").is_empty());
+        assert_eq!(tag_names("#first\tsecond #second"), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn headings_are_not_tags() {
+        let content = "# Heading\n## Sub heading #inline\n###\n#tag at line start";
+        assert_eq!(tag_names(content), vec!["inline", "tag"]);
+    }
+
+    #[test]
+    fn tags_in_code_and_numbers_are_skipped() {
+        let content = "#real `#inline` #2024 #2024-review #日本\n```\n#fenced\n```\n#after";
+        assert_eq!(
+            tag_names(content),
+            vec!["real", "2024-review", "日本", "after"]
+        );
+
+        let tags = parse_tags(content);
+        let last = tags.last().unwrap();
+        assert_eq!(&content[last.start..last.end], "#after");
+    }
+
+    #[test]
+    fn scan_note_matches_individual_parsers() {
+        let notes = [
+            "---\nrelated: \"[[Rust]]\"\ncolor: #ff0000\n---\n# Rust\n\nSee [[WASM]] and [[tools/Cargo|cargo]] #lang/rust",
+            "Links to ![[diagram.png]], [[Note#Header]], [doc](docs/Guide.md) and ![img](a.png) #draft",
+            "[[Note| #alias]] [label #tag](Target.md) \\[not](a link) [[![[Nested]]]]",
+            "```md\n[[Not a link]] [no](No.md) #nope\n```\n#real `[[code]] #code` [[Open",
+            "Café #日本 [[日本/画像.png]] #2024 [x]([[y]]) #a//b/",
+            "",
+        ];
+        for content in notes {
+            let scan = scan_note(content);
+            let options = ParseOptions { skip_code: true };
+            assert_eq!(scan.wiki_links, parse_wiki_links_with_options(content, options));
+            assert_eq!(scan.markdown_links, parse_markdown_links(content));
+            assert_eq!(scan.tags, parse_tags(content));
+            assert_eq!(scan.linked_notes(), extract_all_linked_notes(content));
+        }
+    }
+
+    #[test]
+    fn scan_note_finds_everything() {
+        let content = "---\ntags: [x]\n---\n[[Loro]] #crdt, [spec](knowledge/Spec.md) #crdt\n`#no`";
+        let scan = scan_note(content);
+        assert_eq!(scan.wiki_links.len(), 1);
+        assert_eq!(scan.markdown_links.len(), 1);
+        let tags: Vec<&str> = scan.tags.iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(tags, vec!["crdt", "crdt"]);
+        assert_eq!(scan.linked_notes(), vec!["Loro", "Spec"]);
+    }
+
+    #[test]
+    fn tags_in_frontmatter_are_skipped() {
+        let content = "---\ncolor: #ff0000\ntags: [a]\n---\n#body";
+        let tags = parse_tags(content);
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "body");
+        assert_eq!(&content[tags[0].start..tags[0].end], "#body");
+
+        // Without a closing delimiter there is no frontmatter
+        assert_eq!(tag_names("---\n#open"), vec!["open"]);
+    }
 }
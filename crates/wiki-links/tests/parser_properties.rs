@@ -0,0 +1,90 @@
+//! Property-based tests hardening the wiki-link parser against adversarial input.
+
+use proptest::prelude::*;
+use wiki_links::{WikiLink, extract_linked_notes, parse_wiki_links};
+
+/// Inputs dense in the characters the parser treats specially.
+fn link_heavy() -> impl Strategy<Value = String> {
+    proptest::collection::vec(
+        prop_oneof![
+            Just("[[".to_string()),
+            Just("]]".to_string()),
+            Just("![[".to_string()),
+            Just("#^".to_string()),
+            "[\\[\\]!#|^/. a-zé\n]{1,3}",
+        ],
+        0..24,
+    )
+    .prop_map(|parts| parts.concat())
+}
+
+/// Assert every parsed link survives a `to_markdown` round trip.
+fn assert_round_trips(input: &str) {
+    for link in parse_wiki_links(input) {
+        let markdown = link.to_markdown();
+        let expected = WikiLink {
+            start: 0,
+            end: markdown.len(),
+            ..link
+        };
+        assert_eq!(
+            parse_wiki_links(&markdown),
+            vec![expected],
+            "input {:?} rendered as {:?}",
+            input,
+            markdown
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn never_panics_on_arbitrary_input(input in ".*") {
+        let _ = parse_wiki_links(&input);
+        let _ = extract_linked_notes(&input);
+    }
+
+    #[test]
+    fn never_panics_on_link_heavy_input(input in link_heavy()) {
+        let links = parse_wiki_links(&input);
+        // Every link consumes at least the four bracket chars
+        prop_assert!(links.len() <= input.chars().count() / 4);
+    }
+
+    #[test]
+    fn parsed_links_round_trip(input in link_heavy()) {
+        assert_round_trips(&input);
+    }
+}
+
+/// Tricky inputs kept as fixed regression seeds.
+#[test]
+fn regression_seeds() {
+    let seeds = [
+        "",
+        "[[",
+        "]]",
+        "[[]]",
+        "![[]]",
+        "!![[a]]",
+        "[[[a]]",
+        "[[a]]]",
+        "[[a] ]]",
+        "[[a[[b]]c]]",
+        "[[a|[[b]]]]",
+        "[[a[[b]]",
+        "[[#]]",
+        "[[#^]]",
+        "[[a#b#^c]]",
+        "[[a#^b#c|d|e]]",
+        "[[/]]",
+        "[[.md]]",
+        "[[ é/ñ.md ]]",
+        "[[\n]]",
+        "![[![[a]]]]",
+    ];
+
+    for seed in seeds {
+        assert_round_trips(seed);
+    }
+}
@@ -1,6 +1,7 @@
 import { Notice, Plugin, Platform, FileSystemAdapter, Events, TFile } from "obsidian";
 import { SyncView, VIEW_TYPE_SYNC } from "./views/SyncView";
 import { DebugModal } from "./views/DebugModal";
+import { ConfirmModal } from "./views/ConfirmModal";
 import {
   initWasm,
   isWasmReady,
@@ -11,6 +12,7 @@ import {
   ConnectedPeer,
   DisconnectReason,
   LogEvent,
+  isWasmError,
 } from "./wasm";
 import { createFsBridge } from "./fs/ObsidianFs";
 import { PeerManager, PeerInfo, VaultPeerManager } from "./network";
@@ -58,11 +60,17 @@ interface P2PSyncSettings {
   knownPeers: KnownPeer[];
   /** SWIM incarnation number for gossip protocol ordering */
   swimIncarnation: number;
+  /** Globs of paths to sync; empty means the whole vault */
+  syncInclude: string[];
+  /** Globs of paths never to sync */
+  syncExclude: string[];
 }
 
 const DEFAULT_SETTINGS: P2PSyncSettings = {
   knownPeers: [],
   swimIncarnation: 1,
+  syncInclude: [],
+  syncExclude: [],
 };
 
 /**
@@ -199,7 +207,9 @@ export default class P2PSyncPlugin extends Plugin {
           this.app.vault.adapter.append(debugLogPath, line).catch((err) => {
             console.error("Failed to write debug log:", err);
           });
-        }
+        },
+        // Skip internal CRDT library logs
+        allowedTargets: ["sync_core", "sync_wasm"],
       });
       log.info("WASM initialized");
     } catch (err) {
@@ -300,8 +310,13 @@ export default class P2PSyncPlugin extends Plugin {
       this.peerManager = null;
     }
 
-    // Clean up vault
+    // Flush in-memory changes, then clean up vault
     if (this.vault) {
+      try {
+        await this.vault.flush();
+      } catch (err) {
+        log.error("Failed to flush vault on unload:", err);
+      }
       this.vault.free();
       this.vault = null;
     }
@@ -431,6 +446,9 @@ export default class P2PSyncPlugin extends Plugin {
       peerDisconnected: (id: string, reason: DisconnectReason): void => {
         vault.peerDisconnected(id, reason);
       },
+      expirePendingHandshakes: (timeoutMs: number): string[] => {
+        return vault.expirePendingHandshakes(timeoutMs);
+      },
       resolvePeerId: (connectionId: string): string => {
         return vault.resolvePeerId(connectionId);
       },
@@ -610,6 +628,15 @@ export default class P2PSyncPlugin extends Plugin {
         return true;
       });
     }
+
+    // Validate sync scope globs
+    for (const key of ["syncInclude", "syncExclude"] as const) {
+      const globs = this.settings[key];
+      if (!Array.isArray(globs) || globs.some(g => typeof g !== "string")) {
+        log.warn(`Invalid ${key} in settings, resetting to empty array`);
+        this.settings[key] = [];
+      }
+    }
   }
 
   /**
@@ -694,8 +721,29 @@ export default class P2PSyncPlugin extends Plugin {
       const syncDirExists = await this.app.vault.adapter.exists(".sync");
 
       if (syncDirExists) {
-        this.vault = await WasmVault.load(fsBridge, this.peerId);
-        log.info("Vault loaded");
+        try {
+          this.vault = await WasmVault.load(fsBridge, this.peerId);
+          log.info("Vault loaded");
+        } catch (err) {
+          if (!isWasmError(err, "registryCorrupted")) throw err;
+          log.warn("Registry corrupted:", err);
+          const confirmed = await new ConfirmModal(
+            this.app,
+            "Sync registry is corrupted",
+            "P2P Sync can't read its file registry (.sync/registry.loro). Rebuilding " +
+              "keeps whatever is still readable and re-registers the remaining notes " +
+              "from disk; notes and their history are not changed. Sync stays off " +
+              "until the registry is rebuilt.",
+            "Rebuild registry"
+          ).ask();
+          if (!confirmed) {
+            new Notice("P2P Sync: Registry not rebuilt, sync is disabled");
+            return;
+          }
+          this.vault = await WasmVault.rebuildRegistry(fsBridge, this.peerId);
+          new Notice("Sync registry was corrupted and has been rebuilt");
+        }
+        await this.applySyncScope();
         this.updateStatusBar("loaded");
         this.subscribeToDebugEvents();
         this.events.trigger("state-changed");
@@ -724,12 +772,34 @@ export default class P2PSyncPlugin extends Plugin {
 
     const fsBridge = createFsBridge(this.app.vault);
     this.vault = await WasmVault.init(fsBridge, this.peerId);
+    await this.applySyncScope();
     log.info("Vault initialized");
     this.updateStatusBar("initialized");
     this.subscribeToDebugEvents();
     this.events.trigger("state-changed");
   }
 
+  /**
+   * Limit sync to paths matching `include` globs and no `exclude` glob.
+   *
+   * An empty `include` list means the whole vault.
+   */
+  async setSyncScope(include: string[], exclude: string[]): Promise<void> {
+    this.settings.syncInclude = include;
+    this.settings.syncExclude = exclude;
+    await this.saveSettings();
+    await this.applySyncScope();
+  }
+
+  /**
+   * Pass the sync scope from settings to the vault.
+   */
+  private async applySyncScope(): Promise<void> {
+    if (!this.vault) return;
+    const { syncInclude, syncExclude } = this.settings;
+    await this.vaultQueue.run(() => this.vault!.setSyncScope(syncInclude, syncExclude));
+  }
+
   /**
    * Check if the vault is initialized.
    */
@@ -800,7 +870,7 @@ export default class P2PSyncPlugin extends Plugin {
     try {
       // Queue the WASM call to prevent concurrent &mut self borrows
       const result = await this.vaultQueue.run(() =>
-        this.vault!.processSyncMessage(data)
+        this.vault!.processSyncMessage(data, peerId)
       ) as SyncMessageResult;
       
       log.debug(`Sync result - response=${result.response ? result.response.length + ' bytes' : 'null'}, modifiedPaths=${JSON.stringify(result.modifiedPaths)}`);
@@ -1009,7 +1079,7 @@ export default class P2PSyncPlugin extends Plugin {
       this.app.vault.on("modify", async (file) => {
         if (!this.vault) return;
         if (!(file instanceof TFile)) return;
-        if (!file.path.endsWith(".md")) return;
+        if (!this.vault.isPathSynced(file.path)) return;
         
         // Skip files that are too large to prevent memory issues
         if (file.stat.size > MAX_FILE_SIZE) {
@@ -1043,7 +1113,7 @@ export default class P2PSyncPlugin extends Plugin {
       this.app.vault.on("create", async (file) => {
         if (!this.vault) return;
         if (!(file instanceof TFile)) return;
-        if (!file.path.endsWith(".md")) return;
+        if (!this.vault.isPathSynced(file.path)) return;
         
         // Skip files that are too large
         if (file.stat.size > MAX_FILE_SIZE) {
@@ -1076,7 +1146,7 @@ export default class P2PSyncPlugin extends Plugin {
       this.app.vault.on("delete", async (file) => {
         if (!this.vault) return;
         if (!(file instanceof TFile)) return;
-        if (!file.path.endsWith(".md")) return;
+        if (!this.vault.isPathSynced(file.path)) return;
 
         log.debug("File deleted:", file.path);
         try {
@@ -1107,7 +1177,8 @@ export default class P2PSyncPlugin extends Plugin {
       this.app.vault.on("rename", async (file, oldPath) => {
         if (!this.vault) return;
         if (!(file instanceof TFile)) return;
-        if (!file.path.endsWith(".md")) return;
+        // The vault handles renames into or out of the sync scope
+        if (!this.vault.isPathSynced(oldPath) && !this.vault.isPathSynced(file.path)) return;
 
         log.debug("File renamed:", oldPath, "->", file.path);
         try {
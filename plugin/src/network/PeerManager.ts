@@ -16,7 +16,15 @@ import { EventEmitter } from "events";
 import { Platform } from "obsidian";
 import { SyncWebSocketClient } from "./WebSocketClient";
 import { log } from "../logger";
-import type { ConnectedPeer, DisconnectReason, GossipUpdate, ProcessedGossip, SwimPeerInfo, SwimMember } from "../wasm";
+import type {
+  ConnectedPeer,
+  DisconnectReason,
+  GossipUpdate,
+  GossipValidation,
+  ProcessedGossip,
+  SwimPeerInfo,
+  SwimMember,
+} from "../wasm";
 
 // Type for dynamically loaded WebSocket server
 interface SyncWebSocketServer extends EventEmitter {
@@ -32,6 +40,12 @@ interface SyncWebSocketServer extends EventEmitter {
 /** Default port for the WebSocket server */
 const DEFAULT_PORT = 8765;
 
+/** How long a new connection may take to send its handshake before it is dropped */
+const HANDSHAKE_TIMEOUT_MS = 10_000;
+
+/** How often to look for connections that have exceeded the handshake timeout */
+const HANDSHAKE_CHECK_INTERVAL_MS = 1_000;
+
 /**
  * Interface for vault peer management methods.
  * PeerManager uses this to notify Rust of connection events.
@@ -40,6 +54,7 @@ export interface VaultPeerManager {
   peerConnecting(connectionId: string, address: string, direction: string): ConnectedPeer;
   peerHandshakeComplete(connectionId: string, peerId: string): ConnectedPeer;
   peerDisconnected(id: string, reason: DisconnectReason): void;
+  expirePendingHandshakes(timeoutMs: number): string[];
   resolvePeerId(connectionId: string): string;
   getKnownPeers(): ConnectedPeer[];
   getConnectedPeers(): ConnectedPeer[];
@@ -62,6 +77,7 @@ export interface PeerInfo {
   state: "connecting" | "connected" | "disconnected";
   disconnectReason?: "userRequested" | "networkError" | "remoteClosed" | "protocolError";
   connectionCount: number;
+  reconnectCount: number;
   connectedAt: Date;
   lastActivityAt: Date;
 }
@@ -80,6 +96,7 @@ interface MembershipLike {
   getAliveMembers(): unknown;
   contains(peerId: string): boolean;
   getMemberIncarnation(peerId: string): number | undefined;
+  validateGossip(gossipJson: string): string;
   processGossip(gossipJson: string, fromPeerId: string): string;
   drainGossip(): string;
   generateFullGossip(): string;
@@ -104,6 +121,9 @@ export class PeerManager extends EventEmitter {
   /** Peers currently being connected to (prevents duplicate connection attempts) */
   private connectingPeers: Set<string> = new Set();
 
+  /** Timer that drops connections stuck before their handshake */
+  private handshakeTimer: ReturnType<typeof setInterval> | null = null;
+
   /**
    * @param peerId - Our unique peer identifier
    * @param pluginDir - Absolute path to the plugin directory (for loading ws-server.js on desktop)
@@ -163,6 +183,9 @@ export class PeerManager extends EventEmitter {
   async start(port: number = DEFAULT_PORT): Promise<number> {
     this.serverPort = port;
 
+    // Drop half-open connections, incoming or outgoing
+    this.handshakeTimer = setInterval(() => this.expireHandshakes(), HANDSHAKE_CHECK_INTERVAL_MS);
+
     // Only start server on desktop (mobile can't run a server)
     if (Platform.isDesktop && this.pluginDir) {
       // Load the WebSocket server module using Node's require with absolute path.
@@ -210,6 +233,11 @@ export class PeerManager extends EventEmitter {
    * Stop the peer manager (stops server and closes all connections).
    */
   async stop(): Promise<void> {
+    if (this.handshakeTimer) {
+      clearInterval(this.handshakeTimer);
+      this.handshakeTimer = null;
+    }
+
     // Close all outgoing connections
     for (const [, conn] of this.connections) {
       if (conn.socket) {
@@ -397,6 +425,7 @@ export class PeerManager extends EventEmitter {
       state: peer.state,
       disconnectReason: peer.disconnectReason,
       connectionCount: peer.connectionCount,
+      reconnectCount: peer.reconnectCount,
       connectedAt: new Date(peer.firstSeen),
       lastActivityAt: new Date(peer.lastSeen),
     }));
@@ -521,7 +550,14 @@ export class PeerManager extends EventEmitter {
     const membership = this.getMembership();
     if (!membership || updates.length === 0) return [];
 
-    const gossipJson = JSON.stringify(updates);
+    // Drop malformed or stale updates individually so one bad entry doesn't discard the batch
+    const { accepted, rejected }: GossipValidation = JSON.parse(membership.validateGossip(JSON.stringify(updates)));
+    for (const { index, reason, update } of rejected) {
+      log.warn(`Rejected gossip update ${index} from ${fromPeerId} (${reason}):`, update);
+    }
+    if (accepted.length === 0) return [];
+
+    const gossipJson = JSON.stringify(accepted);
     const { newPeers, relay }: ProcessedGossip = JSON.parse(membership.processGossip(gossipJson, fromPeerId));
 
     // Relay state-changing updates only (API prevents amplification by design)
@@ -647,6 +683,25 @@ export class PeerManager extends EventEmitter {
 
   // ========== Private Helpers ==========
 
+  /**
+   * Close connections that haven't completed their handshake within
+   * HANDSHAKE_TIMEOUT_MS. Rust has already removed them from the peer registry.
+   */
+  private expireHandshakes(): void {
+    if (!this.vault) return;
+
+    for (const connectionId of this.vault.expirePendingHandshakes(HANDSHAKE_TIMEOUT_MS)) {
+      log.warn(`Dropping ${connectionId}: no handshake within ${HANDSHAKE_TIMEOUT_MS / 1000}s`);
+      const conn = this.connections.get(connectionId);
+      if (conn?.socket) {
+        conn.socket.disconnect();
+      } else if (this.server) {
+        this.server.disconnect(connectionId);
+      }
+      this.connections.delete(connectionId);
+    }
+  }
+
   /**
    * Clean up a connection and notify vault/SWIM of the disconnect.
    */
@@ -0,0 +1,54 @@
+import { App, Modal, Setting } from "obsidian";
+
+/**
+ * Modal asking the user to confirm an action.
+ *
+ * `ask()` resolves to true if the user confirms, false if they cancel or
+ * close the modal.
+ */
+export class ConfirmModal extends Modal {
+  private confirmed = false;
+  private resolve: ((confirmed: boolean) => void) | null = null;
+
+  constructor(
+    app: App,
+    private readonly title: string,
+    private readonly message: string,
+    private readonly confirmText: string
+  ) {
+    super(app);
+  }
+
+  /** Open the modal and wait for the user's answer. */
+  ask(): Promise<boolean> {
+    return new Promise((resolve) => {
+      this.resolve = resolve;
+      this.open();
+    });
+  }
+
+  onOpen(): void {
+    this.setTitle(this.title);
+    this.contentEl.createEl("p", { text: this.message });
+
+    new Setting(this.contentEl)
+      .addButton((button) =>
+        button.setButtonText("Cancel").onClick(() => this.close())
+      )
+      .addButton((button) =>
+        button
+          .setButtonText(this.confirmText)
+          .setWarning()
+          .onClick(() => {
+            this.confirmed = true;
+            this.close();
+          })
+      );
+  }
+
+  onClose(): void {
+    this.contentEl.empty();
+    this.resolve?.(this.confirmed);
+    this.resolve = null;
+  }
+}
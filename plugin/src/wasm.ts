@@ -11,6 +11,8 @@ import {
   health_check,
   version,
   generatePeerId as wasmGeneratePeerId,
+  validateNoteReference as wasmValidateNoteReference,
+  parseWikiLinksBatch as wasmParseWikiLinksBatch,
   JsFileSystemBridge,
   WasmVault,
   WasmSubscription as WasmSubscriptionImpl,
@@ -55,6 +57,43 @@ export interface ConnectedPeer {
   lastSeen: number;
   /** Times this peer has connected this session */
   connectionCount: number;
+  /** Times this peer has reconnected after a disconnect this session */
+  reconnectCount: number;
+}
+
+// ========== Error Types ==========
+
+/** Discriminant of errors thrown by `WasmVault` methods */
+export type WasmErrorKind =
+  | "notInitialized"
+  | "pathValidation"
+  | "treeOperation"
+  | "registryCorrupted"
+  | "targetExists"
+  | "sourceNotFound"
+  | "noteTooLarge"
+  | "notFound"
+  | "alreadyExists"
+  | "filesystem"
+  | "document"
+  | "protocol"
+  | "invalidArgument"
+  | "unknownConnection"
+  | "other";
+
+/** `Error` thrown by `WasmVault` methods, tagged with its kind */
+export interface WasmError extends Error {
+  kind: WasmErrorKind;
+  /** Path the failing operation was about, if known */
+  path?: string;
+}
+
+/** Check whether a caught value is a typed WASM error, optionally of a given kind */
+export function isWasmError(err: unknown, kind?: WasmErrorKind): err is WasmError {
+  if (!(err instanceof Error) || typeof (err as Partial<WasmError>).kind !== "string") {
+    return false;
+  }
+  return kind === undefined || (err as WasmError).kind === kind;
 }
 
 // ========== Init Config Types ==========
@@ -65,8 +104,10 @@ export interface LogEvent {
   level: string;
   /** Module/target that emitted the log */
   target: string;
-  /** Log message */
+  /** Log message, followed by the structured fields as `key=value` pairs */
   message: string;
+  /** Structured fields other than the message (e.g. `{ peer_id, path }`) */
+  fields: Record<string, string | number | boolean>;
   /** Timestamp in milliseconds since Unix epoch */
   timestamp: number;
 }
@@ -75,6 +116,10 @@ export interface LogEvent {
 export interface InitConfig {
   /** Optional logger callback for routing logs to file/custom handler */
   logger?: (event: LogEvent) => void;
+  /** Target prefixes forwarded to `logger` (e.g. "sync_core"); all targets if empty */
+  allowedTargets?: string[];
+  /** Target prefixes never forwarded to `logger`, even if allowed */
+  blockedTargets?: string[];
 }
 
 // ========== SWIM Gossip Types ==========
@@ -115,6 +160,24 @@ export interface ProcessedGossip {
   relay: GossipUpdate[];
 }
 
+/** A received gossip update rejected by `validateGossip`. */
+export type RejectedGossip = {
+  /** Position of the update in the received array */
+  index: number;
+  /** The update as received */
+  update: unknown;
+} & (
+  | { reason: "malformed"; error: string }
+  | { reason: "staleIncarnation"; incarnation: number; known: number }
+);
+
+/** Received gossip split into valid updates and rejections. */
+export interface GossipValidation {
+  /** Valid updates — pass these to `processGossip` */
+  accepted: GossipUpdate[];
+  rejected: RejectedGossip[];
+}
+
 // ========== Debug API Types ==========
 
 /** Version vector as a map of peer ID hex strings to counter values */
@@ -148,6 +211,13 @@ export interface DocumentInfo {
   hasFrontmatter: boolean;
 }
 
+/** A connected peer and whether it has our current version of a note */
+export interface DocumentSyncPeer {
+  peerId: string;
+  /** False if the peer's last reported version is behind ours, or it never reported one */
+  upToDate: boolean;
+}
+
 // ========== Sync Event Types ==========
 
 /** Sync events emitted during sync operations for real-time monitoring. */
@@ -188,6 +258,37 @@ export type SyncEvent =
       /** When the operation occurred, in milliseconds since Unix epoch. */
       timestamp: number;
     }
+  | {
+      type: "moveConflict";
+      /** Path both peers moved the note from. */
+      path: string;
+      /** Where this peer had moved the note. */
+      localPath: string;
+      /** Where the note ended up on every peer. */
+      resolvedPath: string;
+      /** When the conflict was resolved, in milliseconds since Unix epoch. */
+      timestamp: number;
+    }
+  | {
+      type: "deleteEditConflict";
+      /** Path of the deleted note. */
+      path: string;
+      /** How the conflict was resolved. */
+      policy: "deleteWins" | "editWins";
+      /** When the conflict was resolved, in milliseconds since Unix epoch. */
+      timestamp: number;
+    }
+  | {
+      type: "noteTooLarge";
+      /** Path of the note. */
+      path: string;
+      /** Body size in bytes. */
+      size: number;
+      /** Configured limit in bytes. */
+      limit: number;
+      /** When the note was rejected, in milliseconds since Unix epoch. */
+      timestamp: number;
+    }
   | {
       type: "peerConnected";
       /** Peer's unique identifier (from handshake). */
@@ -232,6 +333,7 @@ let initialized = false;
  *
  * @param config - Optional configuration:
  *   - `logger`: Callback to receive log events (for file logging)
+ *   - `allowedTargets` / `blockedTargets`: Target prefixes to include in or drop from `logger`
  */
 export async function initWasm(config?: InitConfig): Promise<void> {
   if (initialized) {
@@ -293,3 +395,68 @@ export function generatePeerId(): string {
   }
   return wasmGeneratePeerId();
 }
+
+/** How a note reference normalizes, from `validateNoteReference` */
+export interface NoteReferenceDiagnostics {
+  /** Normalized path without extension: "knowledge/Note" */
+  path: string;
+  /** Just the note name: "Note" */
+  name: string;
+  /** The reference started with `memory:` */
+  hadMemoryPrefix: boolean;
+  /** The reference was wrapped in `[[...]]` */
+  hadWikiBrackets: boolean;
+  /** The reference ended with `.md` */
+  hadMdExtension: boolean;
+  /** Why the normalized path is unsafe, if it is */
+  pathError?: string | null;
+}
+
+/**
+ * Explain how a note reference normalizes.
+ *
+ * Reports what was stripped from `[[memory:knowledge/Note.md]]`-style
+ * references and whether the resulting path is valid.
+ */
+export function validateNoteReference(noteRef: string): NoteReferenceDiagnostics {
+  if (!initialized) {
+    throw new Error("WASM not initialized. Call initWasm() first.");
+  }
+  return wasmValidateNoteReference(noteRef) as NoteReferenceDiagnostics;
+}
+
+/** A wiki link parsed by `parseWikiLinksBatch` */
+export interface WikiLink {
+  /** Note name without folder or extension: "Note" */
+  name: string;
+  /** Parent folder: "private/knowledge", or null for root */
+  parent: string | null;
+  /** Extension without the dot: "md", or null */
+  extension: string | null;
+  /** Header reference: "Header Section" */
+  header: string | null;
+  /** Block ID: "block-123" */
+  block_id: string | null;
+  /** Display alias: "my custom text" */
+  alias: string | null;
+  /** Whether this is an embed (`![[...]]`) */
+  is_embed: boolean;
+  /** UTF-8 byte offset where the link starts, at the `!` for embeds (not a JS string index) */
+  start: number;
+  /** UTF-8 byte offset just past the closing `]]` */
+  end: number;
+}
+
+/**
+ * Parse wiki links in many notes with one WASM call.
+ *
+ * Returns an object mapping each path to the links in its content.
+ */
+export function parseWikiLinksBatch(
+  entries: { path: string; content: string }[]
+): Record<string, WikiLink[]> {
+  if (!initialized) {
+    throw new Error("WASM not initialized. Call initWasm() first.");
+  }
+  return wasmParseWikiLinksBatch(entries) as Record<string, WikiLink[]>;
+}
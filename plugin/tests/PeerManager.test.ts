@@ -19,6 +19,7 @@ function createMockVault(): VaultPeerManager & {
   peerConnectingSpy: ReturnType<typeof vi.fn>;
   peerHandshakeCompleteSpy: ReturnType<typeof vi.fn>;
   peerDisconnectedSpy: ReturnType<typeof vi.fn>;
+  expirePendingHandshakesSpy: ReturnType<typeof vi.fn>;
   resolveIdMap: Map<string, string>;
   connectedPeers: ConnectedPeer[];
 } {
@@ -36,6 +37,7 @@ function createMockVault(): VaultPeerManager & {
         firstSeen: Date.now(),
         lastSeen: Date.now(),
         connectionCount: 1,
+        reconnectCount: 0,
       };
       return peer;
     }
@@ -52,6 +54,7 @@ function createMockVault(): VaultPeerManager & {
         firstSeen: Date.now(),
         lastSeen: Date.now(),
         connectionCount: 1,
+        reconnectCount: 0,
       };
       connectedPeers.push(peer);
       return peer;
@@ -63,16 +66,20 @@ function createMockVault(): VaultPeerManager & {
     if (idx >= 0) connectedPeers.splice(idx, 1);
   });
 
+  const expirePendingHandshakesSpy = vi.fn((_timeoutMs: number): string[] => []);
+
   return {
     peerConnecting: peerConnectingSpy,
     peerHandshakeComplete: peerHandshakeCompleteSpy,
     peerDisconnected: peerDisconnectedSpy,
+    expirePendingHandshakes: expirePendingHandshakesSpy,
     resolvePeerId: (connectionId: string) => resolveIdMap.get(connectionId) ?? connectionId,
     getKnownPeers: () => [],
     getConnectedPeers: () => [...connectedPeers],
     peerConnectingSpy,
     peerHandshakeCompleteSpy,
     peerDisconnectedSpy,
+    expirePendingHandshakesSpy,
     resolveIdMap,
     connectedPeers,
   };
@@ -86,6 +93,9 @@ function createMockMembership() {
     getAliveMembers: vi.fn(() => []),
     contains: vi.fn(() => false),
     getMemberIncarnation: vi.fn(() => undefined as number | undefined),
+    validateGossip: vi.fn((gossipJson: string) =>
+      JSON.stringify({ accepted: JSON.parse(gossipJson), rejected: [] })
+    ),
     processGossip: vi.fn(() => JSON.stringify({ newPeers: [], relay: [] })),
     drainGossip: vi.fn(() => "[]"),
     generateFullGossip: vi.fn(() => "[]"),
@@ -356,6 +366,35 @@ describe("PeerManager", () => {
     });
   });
 
+  describe("handshake timeout", () => {
+    it("should close connections Rust reports as expired", async () => {
+      await manager.start();
+      const connectPromise = manager.connectToUrl("wss://example.com/sync");
+      const socket = socketFactory.getLatest()!;
+      socket.simulateOpen();
+      const connectionId = await connectPromise;
+
+      mockVault.expirePendingHandshakesSpy.mockReturnValueOnce([connectionId]);
+      vi.advanceTimersByTime(1_000);
+
+      expect(mockVault.expirePendingHandshakesSpy).toHaveBeenCalledWith(10_000);
+      expect(socket.readyState).toBe(MockWebSocket.CLOSED);
+
+      // No reconnect after an expired handshake
+      vi.advanceTimersByTime(10_000);
+      expect(socketFactory.getLatest()).toBe(socket);
+      await manager.stop();
+    });
+
+    it("should stop checking once stopped", async () => {
+      await manager.start();
+      await manager.stop();
+
+      vi.advanceTimersByTime(5_000);
+      expect(mockVault.expirePendingHandshakesSpy).not.toHaveBeenCalled();
+    });
+  });
+
   describe("connectToPeer()", () => {
     describe("Given an established connection that disconnects", () => {
       let firstSocket: MockWebSocket;
@@ -865,6 +904,42 @@ describe("PeerManager", () => {
         expect(socketA.sentMessages).toHaveLength(0);
       });
     });
+
+    describe("Given gossip with rejected updates", () => {
+      it("should process only the accepted updates", () => {
+        const mockMembership = createMockMembership();
+        (manager as any)._membership = mockMembership;
+
+        const valid = { type: "alive" as const, peer: { peerId: "peer-b", address: null }, incarnation: 1 };
+        const stale = { type: "dead" as const, peerId: "peer-c", incarnation: 1 };
+        mockMembership.validateGossip.mockReturnValueOnce(
+          JSON.stringify({
+            accepted: [valid],
+            rejected: [{ index: 1, update: stale, reason: "staleIncarnation", incarnation: 1, known: 2 }],
+          })
+        );
+
+        manager.handleGossip([valid, stale], "peer-a");
+
+        expect(mockMembership.processGossip).toHaveBeenCalledWith(JSON.stringify([valid]), "peer-a");
+      });
+
+      it("should skip processing when every update is rejected", () => {
+        const mockMembership = createMockMembership();
+        (manager as any)._membership = mockMembership;
+
+        const malformed = { type: "dead" as const, peerId: "not-a-peer-id", incarnation: 1 };
+        mockMembership.validateGossip.mockReturnValueOnce(
+          JSON.stringify({
+            accepted: [],
+            rejected: [{ index: 0, update: malformed, reason: "malformed", error: "invalid peer ID" }],
+          })
+        );
+
+        expect(manager.handleGossip([malformed], "peer-a")).toEqual([]);
+        expect(mockMembership.processGossip).not.toHaveBeenCalled();
+      });
+    });
   });
 
   describe("onPeerDisconnected()", () => {